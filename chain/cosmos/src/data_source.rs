@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use anyhow::{Context, Error, Result};
 
-use graph::components::subgraph::InstanceDSTemplateInfo;
+use graph::components::subgraph::{HostMetrics, InstanceDSTemplateInfo};
 use graph::{
     blockchain::{self, Block, Blockchain, TriggerWithHandler},
     components::store::StoredDynamicDataSource,
@@ -96,6 +96,7 @@ impl blockchain::DataSource<Chain> for DataSource {
         trigger: &<Chain as Blockchain>::TriggerData,
         block: &Arc<<Chain as Blockchain>::Block>,
         _logger: &Logger,
+        _metrics: &HostMetrics,
     ) -> Result<Option<TriggerWithHandler<Chain>>> {
         if self.source.start_block > block.number() {
             return Ok(None);
@@ -1200,6 +1200,19 @@ pub trait EthereumAdapter: Send + Sync + 'static {
         address: H160,
         block_ptr: BlockPtr,
     ) -> Box<dyn Future<Item = Bytes, Error = EthereumRpcError> + Send>;
+
+    /// Read the raw value of storage slot `slot` of the contract at
+    /// `address`. Values are cached in the call cache just like the results
+    /// of `contract_call`; the returned `CallSource` indicates where the
+    /// value came from for accounting purposes
+    async fn get_storage_at(
+        &self,
+        logger: &Logger,
+        address: H160,
+        slot: H256,
+        block_ptr: BlockPtr,
+        cache: Arc<dyn EthereumCallCache>,
+    ) -> Result<(H256, call::Source), EthereumRpcError>;
 }
 
 #[cfg(test)]
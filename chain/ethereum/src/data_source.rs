@@ -15,7 +15,7 @@ use graph::prelude::ethabi::ethereum_types::H160;
 use graph::prelude::ethabi::{StateMutability, Token};
 use graph::prelude::lazy_static;
 use graph::prelude::regex::Regex;
-use graph::prelude::{Link, SubgraphManifestValidationError};
+use graph::prelude::{BigInt, Link, SubgraphManifestValidationError};
 use graph::slog::{debug, error, o, trace};
 use itertools::Itertools;
 use serde::de;
@@ -43,7 +43,7 @@ use graph::{
 
 use graph::data::subgraph::{
     calls_host_fn, DataSourceContext, Source, MIN_SPEC_VERSION, SPEC_VERSION_0_0_8,
-    SPEC_VERSION_1_2_0,
+    SPEC_VERSION_1_2_0, SPEC_VERSION_1_3_0,
 };
 
 use crate::adapter::EthereumAdapter as _;
@@ -179,9 +179,10 @@ impl blockchain::DataSource<Chain> for DataSource {
         trigger: &<Chain as Blockchain>::TriggerData,
         block: &Arc<<Chain as Blockchain>::Block>,
         logger: &Logger,
+        metrics: &HostMetrics,
     ) -> Result<Option<TriggerWithHandler<Chain>>, Error> {
         let block = block.light_block();
-        self.match_and_decode(trigger, block, logger)
+        self.match_and_decode(trigger, block, logger, metrics)
     }
 
     fn name(&self) -> &str {
@@ -249,6 +250,7 @@ impl blockchain::DataSource<Chain> for DataSource {
             creation_block: self.creation_block,
             done_at: None,
             causality_region: CausalityRegion::ONCHAIN,
+            content_hash: None,
         }
     }
 
@@ -263,6 +265,7 @@ impl blockchain::DataSource<Chain> for DataSource {
             creation_block,
             done_at,
             causality_region,
+            content_hash: _,
         } = stored;
 
         ensure!(
@@ -382,6 +385,34 @@ impl blockchain::DataSource<Chain> for DataSource {
             }
         }
 
+        if spec_version < &SPEC_VERSION_1_3_0 {
+            for handler in &self.mapping.event_handlers {
+                if handler.has_param_filters() {
+                    errors.push(anyhow!(
+                        "handler {}: filtering events with `where` is only supported for specVersion >= 1.3.0",
+                        handler.event
+                    ));
+                    break;
+                }
+            }
+        }
+
+        for handler in self
+            .mapping
+            .event_handlers
+            .iter()
+            .filter(|handler| handler.has_param_filters())
+        {
+            match self.contract_event_with_signature(&handler.event) {
+                Some(event) => errors.extend(handler.filter.validate(event)),
+                None => errors.push(anyhow!(
+                    "handler {}: event not found in contract `{}`",
+                    handler.event,
+                    self.contract_abi.name
+                )),
+            }
+        }
+
         for handler in &self.mapping.event_handlers {
             for call in handler.calls.decls.as_ref() {
                 match self.mapping.find_abi(&call.expr.abi) {
@@ -421,6 +452,9 @@ impl blockchain::DataSource<Chain> for DataSource {
             if handler.has_additional_topics() {
                 min_version = std::cmp::max(min_version, SPEC_VERSION_1_2_0);
             }
+            if handler.has_param_filters() {
+                min_version = std::cmp::max(min_version, SPEC_VERSION_1_3_0);
+            }
         }
 
         min_version
@@ -675,6 +709,7 @@ impl DataSource {
         trigger: &EthereumTrigger,
         block: &Arc<LightEthereumBlock>,
         logger: &Logger,
+        metrics: &HostMetrics,
     ) -> Result<Option<TriggerWithHandler<Chain>>, Error> {
         if !self.matches_trigger_address(trigger) {
             return Ok(None);
@@ -730,7 +765,7 @@ impl DataSource {
                 // but have indexed vs. non-indexed params that are encoded differently).
                 //
                 // Map (handler, event ABI) pairs to (handler, decoded params) pairs.
-                let mut matching_handlers = valid_handlers
+                let matching_handlers = valid_handlers
                     .into_iter()
                     .filter_map(|(event_handler, event_abi)| {
                         event_abi
@@ -756,6 +791,25 @@ impl DataSource {
                     })
                     .collect::<Vec<_>>();
 
+                // Drop handlers whose `where` filter rejects the decoded params. This
+                // happens before the handler runs so filtered events don't cost any gas.
+                let mut matching_handlers = matching_handlers
+                    .into_iter()
+                    .filter_map(|(event_handler, params)| {
+                        match event_handler.filter.matches(&params) {
+                            Ok(true) => Some(Ok((event_handler, params))),
+                            Ok(false) => {
+                                metrics.inc_filtered_trigger_count(&event_handler.handler);
+                                None
+                            }
+                            Err(e) => Some(Err(e.context(format!(
+                                "failed to evaluate `where` filter of handler `{}`",
+                                event_handler.handler
+                            )))),
+                        }
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+
                 if matching_handlers.is_empty() {
                     return Ok(None);
                 }
@@ -1564,6 +1618,8 @@ pub struct MappingEventHandler {
     pub receipt: bool,
     #[serde(default)]
     pub calls: CallDecls,
+    #[serde(default, rename = "where")]
+    pub filter: EventParamFilters,
 }
 
 // Custom deserializer for H256 fields that removes the '0x' prefix before parsing
@@ -1624,6 +1680,330 @@ impl MappingEventHandler {
             || self.topic2.as_ref().map_or(false, |v| !v.is_empty())
             || self.topic3.as_ref().map_or(false, |v| !v.is_empty())
     }
+
+    pub fn has_param_filters(&self) -> bool {
+        !self.filter.filters.is_empty()
+    }
+}
+
+/// Conditions on the decoded parameters of an event that must all hold
+/// for the event handler to be called, declared in the manifest as
+///
+/// ```yaml
+/// where:
+///   value: { gt: 0 }
+///   kind: { in: [1, 2] }
+/// ```
+///
+/// Each entry under `where` names an event parameter and gets turned into
+/// one `EventParamFilter` per comparison.
+#[derive(Clone, CheapClone, Debug, Default, Hash, Eq, PartialEq)]
+pub struct EventParamFilters {
+    pub filters: Arc<Vec<EventParamFilter>>,
+    readonly: (),
+}
+
+impl EventParamFilters {
+    /// Checks whether the decoded `params` pass all filters. An error means
+    /// that the filters do not fit the event, which validation should have
+    /// caught.
+    pub fn matches(&self, params: &[LogParam]) -> Result<bool, Error> {
+        for filter in self.filters.iter() {
+            if !filter.matches(params)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn validate(&self, event: &Event) -> Vec<Error> {
+        self.filters
+            .iter()
+            .filter_map(|filter| filter.validate(event).err())
+            .collect()
+    }
+}
+
+impl<'de> de::Deserialize<'de> for EventParamFilters {
+    fn deserialize<D>(deserializer: D) -> Result<EventParamFilters, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Values {
+            One(FilterValue),
+            Many(Vec<FilterValue>),
+        }
+
+        let params: std::collections::BTreeMap<String, std::collections::BTreeMap<String, Values>> =
+            de::Deserialize::deserialize(deserializer)?;
+
+        let mut filters = vec![];
+        for (param, conditions) in params {
+            if conditions.is_empty() {
+                return Err(de::Error::custom(format!(
+                    "`where` filter for param `{param}` has no conditions"
+                )));
+            }
+            for (op, values) in conditions {
+                let op = op.parse::<FilterOp>().map_err(de::Error::custom)?;
+                let values: Vec<String> = match values {
+                    Values::One(value) => vec![value.0],
+                    Values::Many(values) if op.is_list() => {
+                        values.into_iter().map(|value| value.0).collect()
+                    }
+                    Values::Many(_) => {
+                        return Err(de::Error::custom(format!(
+                            "operator `{op}` on param `{param}` expects a single value"
+                        )))
+                    }
+                };
+                filters.push(EventParamFilter {
+                    param: Word::from(param.as_str()),
+                    op,
+                    values,
+                    readonly: (),
+                });
+            }
+        }
+
+        Ok(EventParamFilters {
+            filters: Arc::new(filters),
+            readonly: (),
+        })
+    }
+}
+
+/// A scalar from the manifest. Numbers and booleans are kept in their
+/// textual form and only interpreted once the type of the parameter they
+/// are compared with is known.
+struct FilterValue(String);
+
+impl<'de> de::Deserialize<'de> for FilterValue {
+    fn deserialize<D>(deserializer: D) -> Result<FilterValue, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct FilterValueVisitor;
+
+        impl<'de> de::Visitor<'de> for FilterValueVisitor {
+            type Value = FilterValue;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a string, number or boolean")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<FilterValue, E> {
+                Ok(FilterValue(v.to_owned()))
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<FilterValue, E> {
+                Ok(FilterValue(v.to_string()))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<FilterValue, E> {
+                Ok(FilterValue(v.to_string()))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<FilterValue, E> {
+                Ok(FilterValue(v.to_string()))
+            }
+        }
+
+        deserializer.deserialize_any(FilterValueVisitor)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum FilterOp {
+    Eq,
+    Not,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    NotIn,
+}
+
+impl FilterOp {
+    fn is_list(&self) -> bool {
+        matches!(self, FilterOp::In | FilterOp::NotIn)
+    }
+
+    fn is_ordering(&self) -> bool {
+        matches!(
+            self,
+            FilterOp::Gt | FilterOp::Gte | FilterOp::Lt | FilterOp::Lte
+        )
+    }
+}
+
+impl FromStr for FilterOp {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "eq" => Ok(FilterOp::Eq),
+            "not" => Ok(FilterOp::Not),
+            "gt" => Ok(FilterOp::Gt),
+            "gte" => Ok(FilterOp::Gte),
+            "lt" => Ok(FilterOp::Lt),
+            "lte" => Ok(FilterOp::Lte),
+            "in" => Ok(FilterOp::In),
+            "not_in" => Ok(FilterOp::NotIn),
+            _ => Err(anyhow!(
+                "invalid `where` operator `{s}`, expected one of \
+                 eq, not, gt, gte, lt, lte, in, not_in"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for FilterOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            FilterOp::Eq => "eq",
+            FilterOp::Not => "not",
+            FilterOp::Gt => "gt",
+            FilterOp::Gte => "gte",
+            FilterOp::Lt => "lt",
+            FilterOp::Lte => "lte",
+            FilterOp::In => "in",
+            FilterOp::NotIn => "not_in",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A single comparison like `value > 0` on a decoded event parameter
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct EventParamFilter {
+    pub param: Word,
+    pub op: FilterOp,
+    pub values: Vec<String>,
+    readonly: (),
+}
+
+impl EventParamFilter {
+    fn matches(&self, params: &[LogParam]) -> Result<bool, Error> {
+        let token = &params
+            .iter()
+            .find(|param| param.name == self.param.as_str())
+            .ok_or_else(|| anyhow!("unknown param {}", self.param))?
+            .value;
+
+        let value = || self.values[0].as_str();
+        let matches = match self.op {
+            FilterOp::Eq => token_eq(token, value())?,
+            FilterOp::Not => !token_eq(token, value())?,
+            FilterOp::Gt => token_cmp(token, value())?.is_gt(),
+            FilterOp::Gte => token_cmp(token, value())?.is_ge(),
+            FilterOp::Lt => token_cmp(token, value())?.is_lt(),
+            FilterOp::Lte => token_cmp(token, value())?.is_le(),
+            FilterOp::In | FilterOp::NotIn => {
+                let mut found = false;
+                for value in &self.values {
+                    if token_eq(token, value)? {
+                        found = true;
+                        break;
+                    }
+                }
+                found == (self.op == FilterOp::In)
+            }
+        };
+        Ok(matches)
+    }
+
+    /// Checks that the parameter exists in `event`, that the operator can
+    /// be used with its type, and that all values can be parsed as that type.
+    fn validate(&self, event: &Event) -> Result<(), Error> {
+        let input = event
+            .inputs
+            .iter()
+            .find(|input| input.name == self.param.as_str())
+            .ok_or_else(|| {
+                anyhow!(
+                    "`where` filter on event `{}` refers to unknown param `{}`",
+                    event.name,
+                    self.param
+                )
+            })?;
+
+        // Indexed params of dynamic types are only available as the hash of
+        // their value, which is decoded as `FixedBytes(32)`
+        let kind = match &input.kind {
+            ParamType::String | ParamType::Bytes if input.indexed => ParamType::FixedBytes(32),
+            kind => kind.clone(),
+        };
+
+        let supported = match kind {
+            ParamType::Int(_) | ParamType::Uint(_) => true,
+            ParamType::Address
+            | ParamType::Bool
+            | ParamType::String
+            | ParamType::Bytes
+            | ParamType::FixedBytes(_) => !self.op.is_ordering(),
+            _ => false,
+        };
+        if !supported {
+            return Err(anyhow!(
+                "`where` operator `{}` can not be used with param `{}` of type `{}`",
+                self.op,
+                self.param,
+                kind
+            ));
+        }
+
+        let token = zero_token(&kind);
+        for value in &self.values {
+            token_eq(&token, value).with_context(|| {
+                format!(
+                    "invalid `where` value `{}` for param `{}` of type `{}`",
+                    value, self.param, kind
+                )
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// A token of type `kind`, used to check that filter values can be
+/// compared with params of that type.
+fn zero_token(kind: &ParamType) -> Token {
+    match kind {
+        ParamType::Int(_) => Token::Int(0.into()),
+        ParamType::Uint(_) => Token::Uint(0.into()),
+        ParamType::Address => Token::Address(Address::zero()),
+        ParamType::Bool => Token::Bool(false),
+        ParamType::String => Token::String(String::new()),
+        ParamType::Bytes => Token::Bytes(vec![]),
+        ParamType::FixedBytes(len) => Token::FixedBytes(vec![0; *len]),
+        _ => Token::Tuple(vec![]),
+    }
+}
+
+fn token_eq(token: &Token, value: &str) -> Result<bool, Error> {
+    match token {
+        Token::Int(_) | Token::Uint(_) => Ok(token_cmp(token, value)?.is_eq()),
+        Token::Address(address) => Ok(*address == Address::from_str(value)?),
+        Token::Bool(b) => Ok(*b == value.parse::<bool>()?),
+        Token::String(s) => Ok(s == value),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => {
+            Ok(*bytes == hex::decode(value.trim_start_matches("0x"))?)
+        }
+        _ => Err(anyhow!("can not compare `{token}` with `{value}`")),
+    }
+}
+
+fn token_cmp(token: &Token, value: &str) -> Result<std::cmp::Ordering, Error> {
+    let token = match token {
+        Token::Int(n) => BigInt::from_signed_u256(n),
+        Token::Uint(n) => BigInt::from_unsigned_u256(n),
+        _ => return Err(anyhow!("can not compare `{token}` with `{value}`")),
+    };
+    Ok(token.cmp(&BigInt::from_str(value)?))
 }
 
 /// Hashes a string to a H256 hash.
@@ -1866,3 +2246,88 @@ fn test_call_expr() {
     assert_eq!(expr.func, "growth");
     assert_eq!(expr.args, vec![call_arg]);
 }
+
+#[test]
+fn test_event_param_filters() {
+    use graph::prelude::ethabi::{EventParam, Uint};
+
+    let handler: MappingEventHandler = serde_json::from_str(
+        r#"{
+            "event": "Transfer(indexed address,indexed address,uint256)",
+            "handler": "handleTransfer",
+            "where": {
+                "from": { "not": "0x0000000000000000000000000000000000000000" },
+                "value": { "gt": 10, "lte": "1000" }
+            }
+        }"#,
+    )
+    .unwrap();
+    assert!(handler.has_param_filters());
+    assert_eq!(handler.filter.filters.len(), 3);
+
+    let params = |from: Address, value: u64| {
+        vec![
+            LogParam {
+                name: "from".to_string(),
+                value: Token::Address(from),
+            },
+            LogParam {
+                name: "value".to_string(),
+                value: Token::Uint(Uint::from(value)),
+            },
+        ]
+    };
+    let from = Address::from_low_u64_be(1);
+
+    assert!(handler.filter.matches(&params(from, 11)).unwrap());
+    assert!(handler.filter.matches(&params(from, 1000)).unwrap());
+    assert!(!handler.filter.matches(&params(from, 10)).unwrap());
+    assert!(!handler.filter.matches(&params(from, 1001)).unwrap());
+    assert!(!handler
+        .filter
+        .matches(&params(Address::zero(), 11))
+        .unwrap());
+
+    let filters: EventParamFilters =
+        serde_json::from_str(r#"{ "kind": { "in": [1, 2] } }"#).unwrap();
+    let kind = |kind: i64| {
+        vec![LogParam {
+            name: "kind".to_string(),
+            value: Token::Int(BigInt::from(kind).to_signed_u256()),
+        }]
+    };
+    assert!(filters.matches(&kind(2)).unwrap());
+    assert!(!filters.matches(&kind(-1)).unwrap());
+
+    // Operators that compare with a single value don't accept a list
+    assert!(serde_json::from_str::<EventParamFilters>(r#"{ "kind": { "eq": [1, 2] } }"#).is_err());
+    assert!(serde_json::from_str::<EventParamFilters>(r#"{ "kind": { "like": 1 } }"#).is_err());
+
+    let event = Event {
+        name: "Transfer".to_string(),
+        inputs: vec![
+            EventParam {
+                name: "from".to_string(),
+                kind: ParamType::Address,
+                indexed: true,
+            },
+            EventParam {
+                name: "value".to_string(),
+                kind: ParamType::Uint(256),
+                indexed: false,
+            },
+        ],
+        anonymous: false,
+    };
+    assert!(handler.filter.validate(&event).is_empty());
+
+    let invalid: EventParamFilters = serde_json::from_str(
+        r#"{
+            "from": { "gt": "0x0000000000000000000000000000000000000000" },
+            "value": { "eq": "lots" },
+            "missing": { "eq": 1 }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(invalid.validate(&event).len(), 3);
+}
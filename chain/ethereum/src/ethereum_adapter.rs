@@ -563,6 +563,46 @@ impl EthereumAdapter {
             .compat()
     }
 
+    async fn storage_at(
+        &self,
+        logger: &Logger,
+        address: Address,
+        slot: H256,
+        block_ptr: BlockPtr,
+    ) -> Result<H256, EthereumRpcError> {
+        let web3 = self.web3.clone();
+        let logger = Logger::new(&logger, o!("provider" => self.provider.clone()));
+
+        let block_id = self.block_ptr_to_id(&block_ptr);
+        let retry_log_message = format!("eth_getStorageAt RPC call for block {}", block_ptr);
+
+        retry(retry_log_message, &logger)
+            .when(|result| result.is_err())
+            .limit(ENV_VARS.request_retries)
+            .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+            .run(move || {
+                let web3 = web3.cheap_clone();
+                async move {
+                    // `web3.eth().storage` only accepts block numbers, but we
+                    // want to be able to address blocks by hash
+                    let params = vec![
+                        web3::helpers::serialize(&address),
+                        web3::helpers::serialize(&U256::from_big_endian(slot.as_bytes())),
+                        web3::helpers::serialize(&block_id),
+                    ];
+                    let value =
+                        web3::Transport::execute(web3.transport(), "eth_getStorageAt", params)
+                            .await
+                            .map_err(EthereumRpcError::Web3Error)?;
+                    json::from_value::<H256>(value).map_err(|e| {
+                        EthereumRpcError::Web3Error(web3::Error::Decoder(e.to_string()))
+                    })
+                }
+            })
+            .await
+            .map_err(|e| e.into_inner().unwrap_or(EthereumRpcError::Timeout))
+    }
+
     async fn call(
         &self,
         logger: Logger,
@@ -570,11 +610,6 @@ impl EthereumAdapter {
         block_ptr: BlockPtr,
         gas: Option<u32>,
     ) -> Result<call::Retval, ContractCallError> {
-        fn reverted(logger: &Logger, reason: &str) -> Result<call::Retval, ContractCallError> {
-            info!(logger, "Contract call reverted"; "reason" => reason);
-            Ok(call::Retval::Null)
-        }
-
         let web3 = self.web3.clone();
         let logger = Logger::new(&logger, o!("provider" => self.provider.clone()));
 
@@ -588,130 +623,61 @@ impl EthereumAdapter {
                 let web3 = web3.cheap_clone();
                 let logger = logger.cheap_clone();
                 async move {
-                    let req = CallRequest {
-                        to: Some(call_data.address),
-                        gas: gas.map(|val| web3::types::U256::from(val)),
-                        data: Some(Bytes::from(call_data.encoded_call.to_vec())),
-                        from: None,
-                        gas_price: None,
-                        value: None,
-                        access_list: None,
-                        max_fee_per_gas: None,
-                        max_priority_fee_per_gas: None,
-                        transaction_type: None,
-                    };
+                    let req = call_request(&call_data, gas);
                     let result = web3.eth().call(req, Some(block_id)).boxed().await;
+                    call_retval(&logger, result)
+                }
+            })
+            .map_err(|e| e.into_inner().unwrap_or(ContractCallError::Timeout))
+            .boxed()
+            .await
+    }
 
-                    // Try to check if the call was reverted. The JSON-RPC response for reverts is
-                    // not standardized, so we have ad-hoc checks for each Ethereum client.
-
-                    // 0xfe is the "designated bad instruction" of the EVM, and Solidity uses it for
-                    // asserts.
-                    const PARITY_BAD_INSTRUCTION_FE: &str = "Bad instruction fe";
-
-                    // 0xfd is REVERT, but on some contracts, and only on older blocks,
-                    // this happens. Makes sense to consider it a revert as well.
-                    const PARITY_BAD_INSTRUCTION_FD: &str = "Bad instruction fd";
-
-                    const PARITY_BAD_JUMP_PREFIX: &str = "Bad jump";
-                    const PARITY_STACK_LIMIT_PREFIX: &str = "Out of stack";
-
-                    // See f0af4ab0-6b7c-4b68-9141-5b79346a5f61.
-                    const PARITY_OUT_OF_GAS: &str = "Out of gas";
-
-                    // Also covers Nethermind reverts
-                    const PARITY_VM_EXECUTION_ERROR: i64 = -32015;
-                    const PARITY_REVERT_PREFIX: &str = "revert";
-
-                    const XDAI_REVERT: &str = "revert";
-
-                    // Deterministic Geth execution errors. We might need to expand this as
-                    // subgraphs come across other errors. See
-                    // https://github.com/ethereum/go-ethereum/blob/cd57d5cd38ef692de8fbedaa56598b4e9fbfbabc/core/vm/errors.go
-                    const GETH_EXECUTION_ERRORS: &[&str] = &[
-                        // The "revert" substring covers a few known error messages, including:
-                        // Hardhat: "error: transaction reverted",
-                        // Ganache and Moonbeam: "vm exception while processing transaction: revert",
-                        // Geth: "execution reverted"
-                        // And others.
-                        "revert",
-                        "invalid jump destination",
-                        "invalid opcode",
-                        // Ethereum says 1024 is the stack sizes limit, so this is deterministic.
-                        "stack limit reached 1024",
-                        // See f0af4ab0-6b7c-4b68-9141-5b79346a5f61 for why the gas limit is considered deterministic.
-                        "out of gas",
-                        "stack underflow",
-                    ];
+    /// Make all `calls` at `block_ptr` with a single JSON-RPC batch request.
+    /// The return values are in the same order as `calls`
+    async fn call_in_batch(
+        &self,
+        logger: Logger,
+        calls: Vec<(call::Request, Option<u32>)>,
+        block_ptr: BlockPtr,
+    ) -> Result<Vec<call::Retval>, ContractCallError> {
+        let web3 = self.web3.clone();
+        let logger = Logger::new(&logger, o!("provider" => self.provider.clone()));
 
-                    let env_geth_call_errors = ENV_VARS.geth_eth_call_errors.iter();
-                    let mut geth_execution_errors = GETH_EXECUTION_ERRORS
+        let block_id = self.block_ptr_to_id(&block_ptr);
+        let retry_log_message = format!(
+            "eth_call RPC batch of {} calls for block {}",
+            calls.len(),
+            block_ptr
+        );
+        retry(retry_log_message, &logger)
+            .limit(ENV_VARS.request_retries)
+            .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+            .run(move || {
+                let calls = calls.clone();
+                let web3 = web3.cheap_clone();
+                let logger = logger.cheap_clone();
+                async move {
+                    let batching_web3 = Web3::new(Batch::new(web3.transport().clone()));
+                    let eth = batching_web3.eth();
+                    let results: Vec<_> = calls
                         .iter()
-                        .copied()
-                        .chain(env_geth_call_errors.map(|s| s.as_str()));
-
-                    let as_solidity_revert_with_reason = |bytes: &[u8]| {
-                        let solidity_revert_function_selector =
-                            &tiny_keccak::keccak256(b"Error(string)")[..4];
-
-                        match bytes.len() >= 4 && &bytes[..4] == solidity_revert_function_selector {
-                            false => None,
-                            true => ethabi::decode(&[ParamType::String], &bytes[4..])
-                                .ok()
-                                .and_then(|tokens| tokens[0].clone().into_string()),
-                        }
-                    };
-
-                    match result {
-                        // A successful response.
-                        Ok(bytes) => Ok(call::Retval::Value(scalar::Bytes::from(bytes))),
-
-                        // Check for Geth revert.
-                        Err(web3::Error::Rpc(rpc_error))
-                            if geth_execution_errors
-                                .any(|e| rpc_error.message.to_lowercase().contains(e)) =>
-                        {
-                            reverted(&logger, &rpc_error.message)
-                        }
-
-                        // Check for Parity revert.
-                        Err(web3::Error::Rpc(ref rpc_error))
-                            if rpc_error.code.code() == PARITY_VM_EXECUTION_ERROR =>
-                        {
-                            match rpc_error.data.as_ref().and_then(|d| d.as_str()) {
-                                Some(data)
-                                    if data.to_lowercase().starts_with(PARITY_REVERT_PREFIX)
-                                        || data.starts_with(PARITY_BAD_JUMP_PREFIX)
-                                        || data.starts_with(PARITY_STACK_LIMIT_PREFIX)
-                                        || data == PARITY_BAD_INSTRUCTION_FE
-                                        || data == PARITY_BAD_INSTRUCTION_FD
-                                        || data == PARITY_OUT_OF_GAS
-                                        || data == XDAI_REVERT =>
-                                {
-                                    let reason = if data == PARITY_BAD_INSTRUCTION_FE {
-                                        PARITY_BAD_INSTRUCTION_FE.to_owned()
-                                    } else {
-                                        let payload = data.trim_start_matches(PARITY_REVERT_PREFIX);
-                                        hex::decode(payload)
-                                            .ok()
-                                            .and_then(|payload| {
-                                                as_solidity_revert_with_reason(&payload)
-                                            })
-                                            .unwrap_or("no reason".to_owned())
-                                    };
-                                    reverted(&logger, &reason)
-                                }
+                        .map(|(call_data, gas)| {
+                            eth.call(call_request(call_data, *gas), Some(block_id))
+                        })
+                        .collect();
 
-                                // The VM execution error was not identified as a revert.
-                                _ => Err(ContractCallError::Web3Error(web3::Error::Rpc(
-                                    rpc_error.clone(),
-                                ))),
-                            }
-                        }
+                    batching_web3
+                        .transport()
+                        .submit_batch()
+                        .await
+                        .map_err(ContractCallError::Web3Error)?;
 
-                        // The error was not identified as a revert.
-                        Err(err) => Err(ContractCallError::Web3Error(err)),
+                    let mut retvals = Vec::with_capacity(results.len());
+                    for result in results {
+                        retvals.push(call_retval(&logger, result.await)?);
                     }
+                    Ok(retvals)
                 }
             })
             .map_err(|e| e.into_inner().unwrap_or(ContractCallError::Timeout))
@@ -749,6 +715,214 @@ impl EthereumAdapter {
 
         Ok(req.response(result, call::Source::Rpc))
     }
+    /// Like `contract_calls`, but the calls that are not in the cache are
+    /// sent to the Ethereum node in a single JSON-RPC batch request instead
+    /// of one request per call
+    pub async fn contract_calls_in_batch(
+        &self,
+        logger: &Logger,
+        calls: &[&ContractCall],
+        cache: Arc<dyn EthereumCallCache>,
+    ) -> Result<Vec<(Option<Vec<Token>>, call::Source)>, ContractCallError> {
+        self.make_contract_calls(logger, calls, cache, true).await
+    }
+
+    async fn make_contract_calls(
+        &self,
+        logger: &Logger,
+        calls: &[&ContractCall],
+        cache: Arc<dyn EthereumCallCache>,
+        in_batch: bool,
+    ) -> Result<Vec<(Option<Vec<Token>>, call::Source)>, ContractCallError> {
+        fn as_req(
+            logger: &Logger,
+            call: &ContractCall,
+            index: u32,
+        ) -> Result<call::Request, ContractCallError> {
+            // Emit custom error for type mismatches.
+            for (token, kind) in call
+                .args
+                .iter()
+                .zip(call.function.inputs.iter().map(|p| &p.kind))
+            {
+                if !token.type_check(kind) {
+                    return Err(ContractCallError::TypeError(token.clone(), kind.clone()));
+                }
+            }
+
+            // Encode the call parameters according to the ABI
+            let req = {
+                let encoded_call = call
+                    .function
+                    .encode_input(&call.args)
+                    .map_err(ContractCallError::EncodingError)?;
+                call::Request::new(call.address, encoded_call, index)
+            };
+
+            trace!(logger, "eth_call";
+                "fn" => &call.function.name,
+                "address" => hex::encode(call.address),
+                "data" => hex::encode(req.encoded_call.as_ref()),
+                "block_hash" => call.block_ptr.hash_hex(),
+                "block_number" => call.block_ptr.block_number()
+            );
+            Ok(req)
+        }
+
+        fn decode(
+            logger: &Logger,
+            resp: call::Response,
+            call: &ContractCall,
+        ) -> (Option<Vec<Token>>, call::Source) {
+            let call::Response {
+                retval,
+                source,
+                req: _,
+            } = resp;
+            use call::Retval::*;
+            match retval {
+                Value(output) => match call.function.decode_output(&output) {
+                    Ok(tokens) => (Some(tokens), source),
+                    Err(e) => {
+                        // Decode failures are reverts. The reasoning is that if Solidity fails to
+                        // decode an argument, that's a revert, so the same goes for the output.
+                        let reason = format!("failed to decode output: {}", e);
+                        info!(logger, "Contract call reverted"; "reason" => reason);
+                        (None, call::Source::Rpc)
+                    }
+                },
+                Null => {
+                    // We got a `0x` response. For old Geth, this can mean a revert. It can also be
+                    // that the contract actually returned an empty response. A view call is meant
+                    // to return something, so we treat empty responses the same as reverts.
+                    info!(logger, "Contract call reverted"; "reason" => "empty response");
+                    (None, call::Source::Rpc)
+                }
+            }
+        }
+
+        fn log_call_error(logger: &Logger, e: &ContractCallError, call: &ContractCall) {
+            match e {
+                ContractCallError::Web3Error(e) => error!(logger,
+                    "Ethereum node returned an error when calling function \"{}\" of contract \"{}\": {}",
+                    call.function.name, call.contract_name, e),
+                ContractCallError::Timeout => error!(logger,
+                    "Ethereum node did not respond when calling function \"{}\" of contract \"{}\"",
+                    call.function.name, call.contract_name),
+                _ => error!(logger,
+                    "Failed to call function \"{}\" of contract \"{}\": {}",
+                    call.function.name, call.contract_name, e),
+            }
+        }
+
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let block_ptr = calls.first().unwrap().block_ptr.clone();
+        if calls.iter().any(|call| call.block_ptr != block_ptr) {
+            return Err(ContractCallError::Internal(
+                "all calls must have the same block pointer".to_string(),
+            ));
+        }
+
+        let reqs: Vec<_> = calls
+            .iter()
+            .enumerate()
+            .map(|(index, call)| as_req(logger, call, index as u32))
+            .collect::<Result<_, _>>()?;
+
+        let (mut resps, missing) = cache
+            .get_calls(&reqs, block_ptr)
+            .map_err(|e| error!(logger, "call cache get error"; "error" => e.to_string()))
+            .unwrap_or_else(|_| (Vec::new(), reqs));
+
+        if in_batch {
+            resps.extend(
+                self.call_and_cache_in_batch(logger, calls, missing, cache)
+                    .await?,
+            );
+        } else {
+            let futs = missing.into_iter().map(|req| {
+                let cache = cache.clone();
+                async move {
+                    let call = calls[req.index as usize];
+                    match self.call_and_cache(logger, call, req, cache.clone()).await {
+                        Ok(resp) => Ok(resp),
+                        Err(e) => {
+                            log_call_error(logger, &e, call);
+                            Err(e)
+                        }
+                    }
+                }
+            });
+            resps.extend(try_join_all(futs).await?);
+        }
+
+        // If we make it here, we have a response for every call.
+        debug_assert_eq!(resps.len(), calls.len());
+
+        // Bring the responses into the same order as the calls
+        resps.sort_by_key(|resp| resp.req.index);
+
+        let decoded: Vec<_> = resps
+            .into_iter()
+            .map(|res| {
+                let call = &calls[res.req.index as usize];
+                decode(logger, res, call)
+            })
+            .collect();
+
+        Ok(decoded)
+    }
+
+    async fn call_and_cache_in_batch(
+        &self,
+        logger: &Logger,
+        calls: &[&ContractCall],
+        reqs: Vec<call::Request>,
+        cache: Arc<dyn EthereumCallCache>,
+    ) -> Result<Vec<call::Response>, ContractCallError> {
+        let Some(first) = reqs.first() else {
+            return Ok(Vec::new());
+        };
+        let block_ptr = calls[first.index as usize].block_ptr.clone();
+
+        let batch = reqs
+            .iter()
+            .map(|req| (req.cheap_clone(), calls[req.index as usize].gas))
+            .collect();
+        let retvals = self
+            .call_in_batch(logger.clone(), batch, block_ptr.cheap_clone())
+            .await
+            .map_err(|e| {
+                error!(logger, "Failed to make a batch of {} contract calls", reqs.len();
+                        "error" => e.to_string());
+                e
+            })?;
+
+        let resps = reqs
+            .into_iter()
+            .zip(retvals)
+            .map(|(req, retval)| {
+                let _ = cache
+                    .set_call(
+                        logger,
+                        req.cheap_clone(),
+                        block_ptr.cheap_clone(),
+                        retval.clone(),
+                    )
+                    .map_err(|e| {
+                        error!(logger, "EthereumAdapter: call cache set error";
+                                "contract_address" => format!("{:?}", req.address),
+                                "error" => e.to_string())
+                    });
+                req.response(retval, call::Source::Rpc)
+            })
+            .collect();
+        Ok(resps)
+    }
+
     /// Request blocks by hash through JSON-RPC.
     fn load_blocks_rpc(
         &self,
@@ -1099,6 +1273,151 @@ fn detect_null_block<T>(res: &Result<T, Error>) -> bool {
     }
 }
 
+fn call_request(call_data: &call::Request, gas: Option<u32>) -> CallRequest {
+    CallRequest {
+        to: Some(call_data.address),
+        gas: gas.map(|val| web3::types::U256::from(val)),
+        data: Some(Bytes::from(call_data.encoded_call.to_vec())),
+        from: None,
+        gas_price: None,
+        value: None,
+        access_list: None,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        transaction_type: None,
+    }
+}
+
+/// Turn the response to an `eth_call` into its return value, treating the
+/// errors that indicate that the call reverted as a `Null` return value
+fn call_retval(
+    logger: &Logger,
+    result: Result<Bytes, web3::Error>,
+) -> Result<call::Retval, ContractCallError> {
+    fn reverted(logger: &Logger, reason: &str) -> Result<call::Retval, ContractCallError> {
+        info!(logger, "Contract call reverted"; "reason" => reason);
+        Ok(call::Retval::Null)
+    }
+
+    // Try to check if the call was reverted. The JSON-RPC response for reverts is
+    // not standardized, so we have ad-hoc checks for each Ethereum client.
+
+    // 0xfe is the "designated bad instruction" of the EVM, and Solidity uses it for
+    // asserts.
+    const PARITY_BAD_INSTRUCTION_FE: &str = "Bad instruction fe";
+
+    // 0xfd is REVERT, but on some contracts, and only on older blocks,
+    // this happens. Makes sense to consider it a revert as well.
+    const PARITY_BAD_INSTRUCTION_FD: &str = "Bad instruction fd";
+
+    const PARITY_BAD_JUMP_PREFIX: &str = "Bad jump";
+    const PARITY_STACK_LIMIT_PREFIX: &str = "Out of stack";
+
+    // See f0af4ab0-6b7c-4b68-9141-5b79346a5f61.
+    const PARITY_OUT_OF_GAS: &str = "Out of gas";
+
+    // Also covers Nethermind reverts
+    const PARITY_VM_EXECUTION_ERROR: i64 = -32015;
+    const PARITY_REVERT_PREFIX: &str = "revert";
+
+    const XDAI_REVERT: &str = "revert";
+
+    // Deterministic Geth execution errors. We might need to expand this as
+    // subgraphs come across other errors. See
+    // https://github.com/ethereum/go-ethereum/blob/cd57d5cd38ef692de8fbedaa56598b4e9fbfbabc/core/vm/errors.go
+    const GETH_EXECUTION_ERRORS: &[&str] = &[
+        // The "revert" substring covers a few known error messages, including:
+        // Hardhat: "error: transaction reverted",
+        // Ganache and Moonbeam: "vm exception while processing transaction: revert",
+        // Geth: "execution reverted"
+        // And others.
+        "revert",
+        "invalid jump destination",
+        "invalid opcode",
+        // Ethereum says 1024 is the stack sizes limit, so this is deterministic.
+        "stack limit reached 1024",
+        // See f0af4ab0-6b7c-4b68-9141-5b79346a5f61 for why the gas limit is considered deterministic.
+        "out of gas",
+        "stack underflow",
+    ];
+
+    let env_geth_call_errors = ENV_VARS.geth_eth_call_errors.iter();
+    let mut geth_execution_errors = GETH_EXECUTION_ERRORS
+        .iter()
+        .copied()
+        .chain(env_geth_call_errors.map(|s| s.as_str()));
+
+    let as_solidity_revert_with_reason = |bytes: &[u8]| {
+        let solidity_revert_function_selector = &tiny_keccak::keccak256(b"Error(string)")[..4];
+
+        match bytes.len() >= 4 && &bytes[..4] == solidity_revert_function_selector {
+            false => None,
+            true => ethabi::decode(&[ParamType::String], &bytes[4..])
+                .ok()
+                .and_then(|tokens| tokens[0].clone().into_string()),
+        }
+    };
+
+    match result {
+        // A successful response.
+        Ok(bytes) => Ok(call::Retval::Value(scalar::Bytes::from(bytes))),
+
+        // Check for Geth revert.
+        Err(web3::Error::Rpc(rpc_error))
+            if geth_execution_errors.any(|e| rpc_error.message.to_lowercase().contains(e)) =>
+        {
+            reverted(&logger, &rpc_error.message)
+        }
+
+        // Check for Parity revert.
+        Err(web3::Error::Rpc(ref rpc_error))
+            if rpc_error.code.code() == PARITY_VM_EXECUTION_ERROR =>
+        {
+            match rpc_error.data.as_ref().and_then(|d| d.as_str()) {
+                Some(data)
+                    if data.to_lowercase().starts_with(PARITY_REVERT_PREFIX)
+                        || data.starts_with(PARITY_BAD_JUMP_PREFIX)
+                        || data.starts_with(PARITY_STACK_LIMIT_PREFIX)
+                        || data == PARITY_BAD_INSTRUCTION_FE
+                        || data == PARITY_BAD_INSTRUCTION_FD
+                        || data == PARITY_OUT_OF_GAS
+                        || data == XDAI_REVERT =>
+                {
+                    let reason = if data == PARITY_BAD_INSTRUCTION_FE {
+                        PARITY_BAD_INSTRUCTION_FE.to_owned()
+                    } else {
+                        let payload = data.trim_start_matches(PARITY_REVERT_PREFIX);
+                        hex::decode(payload)
+                            .ok()
+                            .and_then(|payload| as_solidity_revert_with_reason(&payload))
+                            .unwrap_or("no reason".to_owned())
+                    };
+                    reverted(&logger, &reason)
+                }
+
+                // The VM execution error was not identified as a revert.
+                _ => Err(ContractCallError::Web3Error(web3::Error::Rpc(
+                    rpc_error.clone(),
+                ))),
+            }
+        }
+
+        // The error was not identified as a revert.
+        Err(err) => Err(ContractCallError::Web3Error(err)),
+    }
+}
+
+// Storage reads are kept in the call cache next to `eth_call` results, keyed by the prefixed slot
+// instead of call data. ABI-encoded call data is a 4 byte selector followed by 32 byte words,
+// so a 48 byte key can never be confused with a well-formed contract call.
+const STORAGE_AT_CACHE_KEY_PREFIX: &[u8] = b"eth_getStorageAt";
+
+fn storage_at_cache_key(slot: H256) -> Vec<u8> {
+    let mut key = STORAGE_AT_CACHE_KEY_PREFIX.to_vec();
+    key.extend_from_slice(slot.as_bytes());
+    key
+}
+
 #[async_trait]
 impl EthereumAdapterTrait for EthereumAdapter {
     fn provider(&self) -> &str {
@@ -1448,6 +1767,52 @@ impl EthereumAdapterTrait for EthereumAdapter {
         Box::new(self.code(logger, address, block_ptr))
     }
 
+    async fn get_storage_at(
+        &self,
+        logger: &Logger,
+        address: H160,
+        slot: H256,
+        block_ptr: BlockPtr,
+        cache: Arc<dyn EthereumCallCache>,
+    ) -> Result<(H256, call::Source), EthereumRpcError> {
+        let req = call::Request::new(address, storage_at_cache_key(slot), 0);
+
+        match cache.get_call(&req, block_ptr.cheap_clone()) {
+            Ok(Some(call::Response {
+                retval: call::Retval::Value(value),
+                source,
+                ..
+            })) if value.len() == 32 => return Ok((H256::from_slice(value.as_slice()), source)),
+            Ok(_) => {}
+            Err(e) => error!(logger, "call cache get error"; "error" => e.to_string()),
+        }
+
+        debug!(
+            logger, "eth_getStorageAt";
+            "address" => format!("{}", address),
+            "slot" => format!("{:x}", slot),
+            "block" => format!("{}", block_ptr)
+        );
+        let value = self
+            .storage_at(logger, address, slot, block_ptr.cheap_clone())
+            .await?;
+
+        let _ = cache
+            .set_call(
+                logger,
+                req,
+                block_ptr,
+                call::Retval::Value(scalar::Bytes::from(value.as_bytes())),
+            )
+            .map_err(|e| {
+                error!(logger, "EthereumAdapter: call cache set error";
+                        "contract_address" => format!("{:?}", address),
+                        "error" => e.to_string())
+            });
+
+        Ok((value, call::Source::Rpc))
+    }
+
     async fn next_existing_ptr_to_number(
         &self,
         logger: &Logger,
@@ -1513,139 +1878,7 @@ impl EthereumAdapterTrait for EthereumAdapter {
         calls: &[&ContractCall],
         cache: Arc<dyn EthereumCallCache>,
     ) -> Result<Vec<(Option<Vec<Token>>, call::Source)>, ContractCallError> {
-        fn as_req(
-            logger: &Logger,
-            call: &ContractCall,
-            index: u32,
-        ) -> Result<call::Request, ContractCallError> {
-            // Emit custom error for type mismatches.
-            for (token, kind) in call
-                .args
-                .iter()
-                .zip(call.function.inputs.iter().map(|p| &p.kind))
-            {
-                if !token.type_check(kind) {
-                    return Err(ContractCallError::TypeError(token.clone(), kind.clone()));
-                }
-            }
-
-            // Encode the call parameters according to the ABI
-            let req = {
-                let encoded_call = call
-                    .function
-                    .encode_input(&call.args)
-                    .map_err(ContractCallError::EncodingError)?;
-                call::Request::new(call.address, encoded_call, index)
-            };
-
-            trace!(logger, "eth_call";
-                "fn" => &call.function.name,
-                "address" => hex::encode(call.address),
-                "data" => hex::encode(req.encoded_call.as_ref()),
-                "block_hash" => call.block_ptr.hash_hex(),
-                "block_number" => call.block_ptr.block_number()
-            );
-            Ok(req)
-        }
-
-        fn decode(
-            logger: &Logger,
-            resp: call::Response,
-            call: &ContractCall,
-        ) -> (Option<Vec<Token>>, call::Source) {
-            let call::Response {
-                retval,
-                source,
-                req: _,
-            } = resp;
-            use call::Retval::*;
-            match retval {
-                Value(output) => match call.function.decode_output(&output) {
-                    Ok(tokens) => (Some(tokens), source),
-                    Err(e) => {
-                        // Decode failures are reverts. The reasoning is that if Solidity fails to
-                        // decode an argument, that's a revert, so the same goes for the output.
-                        let reason = format!("failed to decode output: {}", e);
-                        info!(logger, "Contract call reverted"; "reason" => reason);
-                        (None, call::Source::Rpc)
-                    }
-                },
-                Null => {
-                    // We got a `0x` response. For old Geth, this can mean a revert. It can also be
-                    // that the contract actually returned an empty response. A view call is meant
-                    // to return something, so we treat empty responses the same as reverts.
-                    info!(logger, "Contract call reverted"; "reason" => "empty response");
-                    (None, call::Source::Rpc)
-                }
-            }
-        }
-
-        fn log_call_error(logger: &Logger, e: &ContractCallError, call: &ContractCall) {
-            match e {
-                ContractCallError::Web3Error(e) => error!(logger,
-                    "Ethereum node returned an error when calling function \"{}\" of contract \"{}\": {}",
-                    call.function.name, call.contract_name, e),
-                ContractCallError::Timeout => error!(logger,
-                    "Ethereum node did not respond when calling function \"{}\" of contract \"{}\"",
-                    call.function.name, call.contract_name),
-                _ => error!(logger,
-                    "Failed to call function \"{}\" of contract \"{}\": {}",
-                    call.function.name, call.contract_name, e),
-            }
-        }
-
-        if calls.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        let block_ptr = calls.first().unwrap().block_ptr.clone();
-        if calls.iter().any(|call| call.block_ptr != block_ptr) {
-            return Err(ContractCallError::Internal(
-                "all calls must have the same block pointer".to_string(),
-            ));
-        }
-
-        let reqs: Vec<_> = calls
-            .iter()
-            .enumerate()
-            .map(|(index, call)| as_req(logger, call, index as u32))
-            .collect::<Result<_, _>>()?;
-
-        let (mut resps, missing) = cache
-            .get_calls(&reqs, block_ptr)
-            .map_err(|e| error!(logger, "call cache get error"; "error" => e.to_string()))
-            .unwrap_or_else(|_| (Vec::new(), reqs));
-
-        let futs = missing.into_iter().map(|req| {
-            let cache = cache.clone();
-            async move {
-                let call = calls[req.index as usize];
-                match self.call_and_cache(logger, call, req, cache.clone()).await {
-                    Ok(resp) => Ok(resp),
-                    Err(e) => {
-                        log_call_error(logger, &e, call);
-                        Err(e)
-                    }
-                }
-            }
-        });
-        resps.extend(try_join_all(futs).await?);
-
-        // If we make it here, we have a response for every call.
-        debug_assert_eq!(resps.len(), calls.len());
-
-        // Bring the responses into the same order as the calls
-        resps.sort_by_key(|resp| resp.req.index);
-
-        let decoded: Vec<_> = resps
-            .into_iter()
-            .map(|res| {
-                let call = &calls[res.req.index as usize];
-                decode(logger, res, call)
-            })
-            .collect();
-
-        Ok(decoded)
+        self.make_contract_calls(logger, calls, cache, false).await
     }
 
     /// Load Ethereum blocks in bulk, returning results as they come back as a Stream.
@@ -2603,20 +2836,31 @@ mod tests {
     use crate::trigger::{EthereumBlockTriggerType, EthereumTrigger};
 
     use super::{
-        check_block_receipt_support, parse_block_triggers, EthereumBlock, EthereumBlockFilter,
-        EthereumBlockWithCalls,
+        check_block_receipt_support, parse_block_triggers, storage_at_cache_key, EthereumAdapter,
+        EthereumAdapterTrait, EthereumBlock, EthereumBlockFilter, EthereumBlockWithCalls,
+        STORAGE_AT_CACHE_KEY_PREFIX,
     };
+    use crate::adapter::{ContractCall, ProviderEthRpcMetrics};
+    use crate::transport::Transport;
     use graph::blockchain::BlockPtr;
+    use graph::components::store::EthereumCallCache;
+    use graph::data::store::ethereum::call;
+    use graph::data::store::scalar;
+    use graph::endpoint::EndpointMetrics;
+    use graph::http::HeaderMap;
     use graph::prelude::ethabi::ethereum_types::U64;
+    use graph::prelude::ethabi::{self, Token};
     use graph::prelude::tokio::{self};
     use graph::prelude::web3::transports::test::TestTransport;
     use graph::prelude::web3::types::{Address, Block, Bytes, H256};
     use graph::prelude::web3::Web3;
-    use graph::prelude::EthereumCall;
+    use graph::prelude::{CachedEthereumCall, Error, EthereumCall, MetricsRegistry};
+    use graph::slog::Logger;
+    use graph::url::Url;
     use jsonrpc_core::serde_json::{self, Value};
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
     use std::iter::FromIterator;
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn parse_block_triggers_every_block() {
@@ -2851,6 +3095,198 @@ mod tests {
         );
     }
 
+    /// A call cache that only keeps values in memory and remembers which
+    /// requests it was asked about
+    #[derive(Default)]
+    struct TestCallCache {
+        values: Mutex<HashMap<call::Request, call::Retval>>,
+        requests: Mutex<Vec<call::Request>>,
+    }
+
+    impl TestCallCache {
+        fn with(values: Vec<(call::Request, call::Retval)>) -> Arc<Self> {
+            Arc::new(TestCallCache {
+                values: Mutex::new(values.into_iter().collect()),
+                requests: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn lookup(&self, req: &call::Request) -> Option<call::Response> {
+            self.requests.lock().unwrap().push(req.clone());
+            self.values
+                .lock()
+                .unwrap()
+                .get(req)
+                .map(|retval| req.clone().response(retval.clone(), call::Source::Store))
+        }
+    }
+
+    impl EthereumCallCache for TestCallCache {
+        fn get_call(
+            &self,
+            req: &call::Request,
+            _block: BlockPtr,
+        ) -> Result<Option<call::Response>, Error> {
+            Ok(self.lookup(req))
+        }
+
+        fn get_calls(
+            &self,
+            reqs: &[call::Request],
+            _block: BlockPtr,
+        ) -> Result<(Vec<call::Response>, Vec<call::Request>), Error> {
+            let mut resps = Vec::new();
+            let mut missing = Vec::new();
+            for req in reqs {
+                match self.lookup(req) {
+                    Some(resp) => resps.push(resp),
+                    None => missing.push(req.clone()),
+                }
+            }
+            Ok((resps, missing))
+        }
+
+        fn get_calls_in_block(&self, _block: BlockPtr) -> Result<Vec<CachedEthereumCall>, Error> {
+            Ok(Vec::new())
+        }
+
+        fn set_call(
+            &self,
+            _logger: &Logger,
+            req: call::Request,
+            _block: BlockPtr,
+            retval: call::Retval,
+        ) -> Result<(), Error> {
+            self.values.lock().unwrap().insert(req, retval);
+            Ok(())
+        }
+    }
+
+    /// An adapter for a node that is not running; tests that use it must
+    /// get all their values from the call cache
+    async fn offline_adapter() -> EthereumAdapter {
+        let transport = Transport::new_rpc(
+            Url::parse("http://127.0.0.1").unwrap(),
+            HeaderMap::new(),
+            Arc::new(EndpointMetrics::mock()),
+            "",
+        );
+        let provider_metrics = Arc::new(ProviderEthRpcMetrics::new(Arc::new(
+            MetricsRegistry::mock(),
+        )));
+        EthereumAdapter::new(
+            graph::log::logger(true),
+            String::new(),
+            transport,
+            provider_metrics,
+            true,
+            false,
+        )
+        .await
+    }
+
+    #[test]
+    fn storage_at_cache_key_is_not_call_data() {
+        let key = storage_at_cache_key(hash(7));
+
+        assert!(key.starts_with(STORAGE_AT_CACHE_KEY_PREFIX));
+        assert!(key.ends_with(hash(7).as_bytes()));
+        // Encoded calls are a 4 byte selector followed by 32 byte words
+        assert_ne!(0, (key.len() - 4) % 32);
+        assert_ne!(storage_at_cache_key(hash(7)), storage_at_cache_key(hash(8)));
+    }
+
+    #[tokio::test]
+    async fn get_storage_at_reads_from_call_cache() {
+        let adapter = offline_adapter().await;
+        let logger = graph::log::logger(true);
+        let block_ptr = BlockPtr::from((hash(2), 2));
+        let value = hash(42);
+
+        let req = call::Request::new(address(1), storage_at_cache_key(hash(7)), 0);
+        let cache = TestCallCache::with(vec![(
+            req.clone(),
+            call::Retval::Value(scalar::Bytes::from(value.as_bytes())),
+        )]);
+
+        let (stored, source) = adapter
+            .get_storage_at(&logger, address(1), hash(7), block_ptr, cache.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(value, stored);
+        assert_eq!(call::Source::Store, source);
+        assert_eq!(vec![req], *cache.requests.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn contract_calls_decode_cached_results() {
+        const ABI: &str = r#"[{
+            "type": "function",
+            "name": "balanceOf",
+            "inputs": [{ "name": "owner", "type": "address" }],
+            "outputs": [{ "name": "", "type": "uint256" }],
+            "stateMutability": "view"
+        }]"#;
+
+        let adapter = offline_adapter().await;
+        let logger = graph::log::logger(true);
+        let block_ptr = BlockPtr::from((hash(2), 2));
+        let contract = ethabi::Contract::load(ABI.as_bytes()).unwrap();
+        let function = contract.function("balanceOf").unwrap();
+
+        let calls: Vec<_> = (1..=3)
+            .map(|owner| ContractCall {
+                contract_name: "Token".to_string(),
+                address: address(1),
+                block_ptr: block_ptr.clone(),
+                function: function.clone(),
+                args: vec![Token::Address(address(owner))],
+                gas: None,
+            })
+            .collect();
+        let req = |call: &ContractCall| {
+            call::Request::new(call.address, function.encode_input(&call.args).unwrap(), 0)
+        };
+
+        // A balance, a revert, and output that can not be decoded
+        let balance = ethabi::encode(&[Token::Uint(ethabi::Uint::from(100))]);
+        let cache = TestCallCache::with(vec![
+            (
+                req(&calls[0]),
+                call::Retval::Value(scalar::Bytes::from(balance)),
+            ),
+            (req(&calls[1]), call::Retval::Null),
+            (
+                req(&calls[2]),
+                call::Retval::Value(scalar::Bytes::from(vec![1, 2])),
+            ),
+        ]);
+
+        let call_refs: Vec<_> = calls.iter().collect();
+        let results = adapter
+            .contract_calls(&logger, &call_refs, cache.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                (
+                    Some(vec![Token::Uint(ethabi::Uint::from(100))]),
+                    call::Source::Store
+                ),
+                (None, call::Source::Rpc),
+                (None, call::Source::Rpc),
+            ],
+            results
+        );
+        // All calls were looked up in the cache with their ABI-encoded call data
+        assert_eq!(
+            calls.iter().map(req).collect::<Vec<_>>(),
+            *cache.requests.lock().unwrap()
+        );
+    }
+
     fn address(id: u64) -> Address {
         Address::from_low_u64_be(id)
     }
@@ -5,7 +5,7 @@ use crate::trigger::{
 use graph::{
     prelude::{
         ethabi,
-        web3::types::{Log, TransactionReceipt, H256},
+        web3::types::{Log, TransactionReceipt, H160, H256},
         BigInt,
     },
     runtime::{
@@ -176,6 +176,110 @@ impl FromAscObj<AscUnresolvedContractCall> for UnresolvedContractCall {
     }
 }
 
+pub struct AscUnresolvedContractCallArray(Array<AscPtr<AscUnresolvedContractCall_0_0_4>>);
+
+impl AscUnresolvedContractCallArray {
+    /// The number of calls in the array, without reading the calls
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl AscType for AscUnresolvedContractCallArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl FromAscObj<AscUnresolvedContractCallArray> for Vec<UnresolvedContractCall> {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_calls: AscUnresolvedContractCallArray,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        Vec::from_asc_obj(asc_calls.0, heap, gas, depth)
+    }
+}
+
+impl AscIndexId for AscUnresolvedContractCallArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArraySmartContractCall;
+}
+
+/// The results of `ethereum.multicall`, one entry per call. Calls that
+/// reverted are represented by a null entry.
+pub struct AscMulticallResultArray(Array<AscPtr<Array<AscPtr<AscEnum<EthereumValueKind>>>>>);
+
+impl AscType for AscMulticallResultArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl ToAscObj<AscMulticallResultArray> for Vec<Option<Vec<ethabi::Token>>> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscMulticallResultArray, HostExportError> {
+        let results = self
+            .iter()
+            .map(|result| match result {
+                Some(tokens) => asc_new(heap, tokens.as_slice(), gas),
+                None => Ok(AscPtr::null()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscMulticallResultArray(Array::new(&results, heap, gas)?))
+    }
+}
+
+impl AscIndexId for AscMulticallResultArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayArrayEthereumValue;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscStorageSlot {
+    pub address: AscPtr<AscAddress>,
+    pub slot: AscPtr<AscH256>,
+}
+
+impl AscIndexId for AscStorageSlot {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::StorageSlot;
+}
+
+impl FromAscObj<AscStorageSlot> for (H160, H256) {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_slot: AscStorageSlot,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok((
+            asc_get(heap, asc_slot.address, gas, depth)?,
+            asc_get(heap, asc_slot.slot, gas, depth)?,
+        ))
+    }
+}
+
 #[repr(C)]
 #[derive(AscType)]
 pub(crate) struct AscEthereumBlock {
@@ -14,7 +14,7 @@ use graph::data::store::ethereum::call;
 use graph::data::store::scalar::BigInt;
 use graph::data::subgraph::API_VERSION_0_0_9;
 use graph::futures03::compat::Future01CompatExt;
-use graph::prelude::web3::types::H160;
+use graph::prelude::web3::types::{H160, H256};
 use graph::runtime::gas::Gas;
 use graph::runtime::{AscIndexId, IndexForAscTypeId};
 use graph::slog::debug;
@@ -29,10 +29,15 @@ use graph::{
     semver::Version,
     slog::Logger,
 };
-use graph_runtime_wasm::asc_abi::class::{AscBigInt, AscEnumArray, AscWrapped, EthereumValueKind};
+use graph_runtime_wasm::asc_abi::class::{
+    AscBigInt, AscEnumArray, AscWrapped, EthereumValueKind, Uint8Array,
+};
 use itertools::Itertools;
 
-use super::abi::{AscUnresolvedContractCall, AscUnresolvedContractCall_0_0_4};
+use super::abi::{
+    AscMulticallResultArray, AscStorageSlot, AscUnresolvedContractCall,
+    AscUnresolvedContractCallArray, AscUnresolvedContractCall_0_0_4,
+};
 
 /// Gas limit for `eth_call`. The value of 50_000_000 is a protocol-wide parameter so this
 /// should be changed only for debugging purposes and never on an indexer in the network. This
@@ -60,6 +65,9 @@ pub const ETH_GET_BALANCE: Gas = Gas::new(5_000_000_000);
 // TODO: Determine the appropriate gas cost for `ETH_HAS_CODE`, initially aligned with `ETHEREUM_CALL`.
 pub const ETH_HAS_CODE: Gas = Gas::new(5_000_000_000);
 
+// TODO: Determine the appropriate gas cost for `ETH_GET_STORAGE_AT`, initially aligned with `ETHEREUM_CALL`.
+pub const ETH_GET_STORAGE_AT: Gas = Gas::new(5_000_000_000);
+
 pub struct RuntimeAdapter {
     pub eth_adapters: Arc<EthereumNetworkAdapters>,
     pub call_cache: Arc<dyn EthereumCallCache>,
@@ -131,7 +139,49 @@ impl blockchain::RuntimeAdapter<Chain> for RuntimeAdapter {
             }),
         };
 
-        Ok(vec![ethereum_call, ethereum_get_balance, ethereum_get_code])
+        let call_cache = self.call_cache.cheap_clone();
+        let eth_adapters = self.eth_adapters.cheap_clone();
+        let ethereum_get_storage_at = HostFn {
+            name: "ethereum.getStorageAt",
+            func: Arc::new(move |ctx, wasm_ptr| {
+                let eth_adapter = eth_adapters.call_or_cheapest(Some(&NodeCapabilities {
+                    archive,
+                    traces: false,
+                }))?;
+                eth_get_storage_at(&eth_adapter, call_cache.cheap_clone(), ctx, wasm_ptr)
+                    .map(|ptr| ptr.wasm_ptr())
+            }),
+        };
+
+        let abis = ds.mapping.abis.clone();
+        let call_cache = self.call_cache.cheap_clone();
+        let eth_adapters = self.eth_adapters.cheap_clone();
+        let ethereum_multicall = HostFn {
+            name: "ethereum.multicall",
+            func: Arc::new(move |ctx, wasm_ptr| {
+                let eth_adapter = eth_adapters.call_or_cheapest(Some(&NodeCapabilities {
+                    archive,
+                    traces: false,
+                }))?;
+                ethereum_multicall(
+                    &eth_adapter,
+                    call_cache.cheap_clone(),
+                    ctx,
+                    wasm_ptr,
+                    &abis,
+                    eth_call_gas,
+                )
+                .map(|ptr| ptr.wasm_ptr())
+            }),
+        };
+
+        Ok(vec![
+            ethereum_call,
+            ethereum_get_balance,
+            ethereum_get_code,
+            ethereum_get_storage_at,
+            ethereum_multicall,
+        ])
     }
 }
 
@@ -246,32 +296,141 @@ fn eth_has_code(
     }
 }
 
-/// Returns `Ok(None)` if the call was reverted.
-fn eth_call(
+/// function ethereum.getStorageAt(slot: StorageSlot): Bytes
+fn eth_get_storage_at(
     eth_adapter: &EthereumAdapter,
     call_cache: Arc<dyn EthereumCallCache>,
-    logger: &Logger,
-    block_ptr: &BlockPtr,
-    unresolved_call: UnresolvedContractCall,
+    ctx: HostFnCtx<'_>,
+    wasm_ptr: u32,
+) -> Result<AscPtr<Uint8Array>, HostExportError> {
+    ctx.gas
+        .consume_host_fn_with_metrics(ETH_GET_STORAGE_AT, "eth_get_storage_at")?;
+
+    if ctx.heap.api_version() < API_VERSION_0_0_9 {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "ethereum.getStorageAt call is not supported before API version 0.0.9"
+        )));
+    }
+
+    let (address, slot): (H160, H256) =
+        asc_get::<_, AscStorageSlot, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?;
+
+    let result = graph::block_on(eth_adapter.get_storage_at(
+        &ctx.logger,
+        address,
+        slot,
+        ctx.block_ptr.cheap_clone(),
+        call_cache,
+    ));
+
+    match result {
+        Ok((value, _)) => Ok(asc_new(ctx.heap, &value, &ctx.gas)?),
+        Err(e) => Err(storage_at_error(e)),
+    }
+}
+
+/// Any error reading a storage slot is retried since it could be due to the
+/// block no longer being on the main chain
+fn storage_at_error(e: EthereumRpcError) -> HostExportError {
+    match e {
+        EthereumRpcError::Web3Error(e) => HostExportError::PossibleReorg(e.into()),
+        EthereumRpcError::Timeout => {
+            HostExportError::PossibleReorg(EthereumRpcError::Timeout.into())
+        }
+    }
+}
+
+/// function ethereum.multicall(calls: Array<SmartContractCall>): Array<Array<Token> | null>
+///
+/// The calls are made at the same block; results are looked up in the call
+/// cache together, and the calls that are not cached are sent to the
+/// Ethereum node in a single JSON-RPC batch request
+fn ethereum_multicall(
+    eth_adapter: &EthereumAdapter,
+    call_cache: Arc<dyn EthereumCallCache>,
+    ctx: HostFnCtx,
+    wasm_ptr: u32,
     abis: &[Arc<MappingABI>],
     eth_call_gas: Option<u32>,
-    metrics: Arc<HostMetrics>,
-) -> Result<Option<Vec<Token>>, HostExportError> {
-    // Helpers to log the result of the call at the end
-    fn tokens_as_string(tokens: &[Token]) -> String {
-        tokens.iter().map(|arg| arg.to_string()).join(", ")
+) -> Result<AscPtr<AscMulticallResultArray>, HostExportError> {
+    if ctx.heap.api_version() < API_VERSION_0_0_9 {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "ethereum.multicall call is not supported before API version 0.0.9"
+        )));
     }
 
-    fn result_as_string(result: &Result<Option<Vec<Token>>, HostExportError>) -> String {
-        match result {
-            Ok(Some(tokens)) => format!("({})", tokens_as_string(&tokens)),
-            Ok(None) => "none".to_string(),
-            Err(_) => "error".to_string(),
+    // Every call in the batch costs as much as a single `ethereum.call`;
+    // charge for them based on the array header before decoding the calls
+    let calls_ptr: AscPtr<AscUnresolvedContractCallArray> = wasm_ptr.into();
+    let call_count = calls_ptr.read_ptr(ctx.heap, &ctx.gas)?.len();
+    ctx.gas
+        .consume_host_fn_with_metrics(ETHEREUM_CALL * call_count, "ethereum_multicall")?;
+
+    let unresolved_calls: Vec<UnresolvedContractCall> =
+        asc_get::<_, AscUnresolvedContractCallArray, _>(ctx.heap, calls_ptr, &ctx.gas, 0)?;
+
+    let start_time = Instant::now();
+
+    let calls = unresolved_calls
+        .iter()
+        .map(|call| resolve_contract_call(call, abis, &ctx.block_ptr, eth_call_gas))
+        .collect::<Result<Vec<_>, _>>()?;
+    let call_refs = calls.iter().collect::<Vec<_>>();
+
+    let results =
+        graph::block_on(eth_adapter.contract_calls_in_batch(&ctx.logger, &call_refs, call_cache))
+            .map_err(|e| multicall_error(e, calls.len()))?;
+
+    let elapsed = start_time.elapsed();
+
+    for (call, (_, source)) in unresolved_calls.iter().zip(results.iter()) {
+        if source.observe() {
+            ctx.metrics.observe_eth_call_execution_time(
+                elapsed.as_secs_f64(),
+                &call.contract_name,
+                &call.function_name,
+            );
         }
     }
 
-    let start_time = Instant::now();
+    debug!(ctx.logger, "Multicall finished";
+              "calls" => calls.len(),
+              "time_ms" => format!("{}ms", elapsed.as_millis()),
+              "block_hash" => ctx.block_ptr.hash_hex(),
+              "block_number" => ctx.block_ptr.block_number());
+
+    let results: Vec<Option<Vec<Token>>> = results.into_iter().map(|(tokens, _)| tokens).collect();
+    Ok(asc_new(ctx.heap, &results, &ctx.gas)?)
+}
+
+fn multicall_error(e: ContractCallError, calls: usize) -> HostExportError {
+    match e {
+        // Like for `ethereum.call`, errors reported by the Ethereum node could be due to the
+        // block no longer being on the main chain
+        ContractCallError::Web3Error(e) => HostExportError::PossibleReorg(anyhow!(
+            "Ethereum node returned an error during a multicall of {} calls: {}",
+            calls,
+            e
+        )),
+        ContractCallError::Timeout => HostExportError::PossibleReorg(anyhow!(
+            "Ethereum node did not respond during a multicall of {} calls",
+            calls
+        )),
+        e => HostExportError::Unknown(anyhow!(
+            "Failed to make a multicall of {} calls: {}",
+            calls,
+            e
+        )),
+    }
+}
 
+/// Looks up the ABI of the function that `unresolved_call` calls
+fn resolve_contract_call(
+    unresolved_call: &UnresolvedContractCall,
+    abis: &[Arc<MappingABI>],
+    block_ptr: &BlockPtr,
+    eth_call_gas: Option<u32>,
+) -> Result<ContractCall, HostExportError> {
     // Obtain the path to the contract ABI
     let abi = abis
         .iter()
@@ -293,14 +452,43 @@ fn eth_call(
         )
         .map_err(HostExportError::Deterministic)?;
 
-    let call = ContractCall {
+    Ok(ContractCall {
         contract_name: unresolved_call.contract_name.clone(),
         address: unresolved_call.contract_address,
         block_ptr: block_ptr.cheap_clone(),
         function: function.clone(),
         args: unresolved_call.function_args.clone(),
         gas: eth_call_gas,
-    };
+    })
+}
+
+/// Returns `Ok(None)` if the call was reverted.
+fn eth_call(
+    eth_adapter: &EthereumAdapter,
+    call_cache: Arc<dyn EthereumCallCache>,
+    logger: &Logger,
+    block_ptr: &BlockPtr,
+    unresolved_call: UnresolvedContractCall,
+    abis: &[Arc<MappingABI>],
+    eth_call_gas: Option<u32>,
+    metrics: Arc<HostMetrics>,
+) -> Result<Option<Vec<Token>>, HostExportError> {
+    // Helpers to log the result of the call at the end
+    fn tokens_as_string(tokens: &[Token]) -> String {
+        tokens.iter().map(|arg| arg.to_string()).join(", ")
+    }
+
+    fn result_as_string(result: &Result<Option<Vec<Token>>, HostExportError>) -> String {
+        match result {
+            Ok(Some(tokens)) => format!("({})", tokens_as_string(&tokens)),
+            Ok(None) => "none".to_string(),
+            Err(_) => "error".to_string(),
+        }
+    }
+
+    let start_time = Instant::now();
+
+    let call = resolve_contract_call(&unresolved_call, abis, block_ptr, eth_call_gas)?;
 
     // Run Ethereum call in tokio runtime
     let logger1 = logger.clone();
@@ -374,3 +562,89 @@ pub struct UnresolvedContractCall {
 impl AscIndexId for AscUnresolvedContractCall {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SmartContractCall;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use graph::blockchain::BlockPtr;
+    use graph::prelude::ethabi::{Address, Contract, Token};
+    use graph::prelude::web3;
+    use graph::runtime::HostExportError;
+
+    use super::{multicall_error, resolve_contract_call, storage_at_error, UnresolvedContractCall};
+    use crate::adapter::EthereumRpcError;
+    use crate::data_source::MappingABI;
+    use crate::ContractCallError;
+
+    const ABI: &str = r#"[{
+        "type": "function",
+        "name": "balanceOf",
+        "inputs": [{ "name": "owner", "type": "address" }],
+        "outputs": [{ "name": "", "type": "uint256" }],
+        "stateMutability": "view"
+    }]"#;
+
+    fn abis() -> Vec<Arc<MappingABI>> {
+        vec![Arc::new(MappingABI {
+            name: "Token".to_string(),
+            contract: Contract::load(ABI.as_bytes()).unwrap(),
+        })]
+    }
+
+    fn balance_of(contract_name: &str) -> UnresolvedContractCall {
+        UnresolvedContractCall {
+            contract_name: contract_name.to_string(),
+            contract_address: Address::from_low_u64_be(1),
+            function_name: "balanceOf".to_string(),
+            function_signature: Some("balanceOf(address):(uint256)".to_string()),
+            function_args: vec![Token::Address(Address::from_low_u64_be(2))],
+        }
+    }
+
+    #[test]
+    fn storage_at_errors_are_retried() {
+        let err = storage_at_error(EthereumRpcError::Timeout);
+        assert!(matches!(err, HostExportError::PossibleReorg(_)));
+
+        let err = storage_at_error(EthereumRpcError::Web3Error(web3::Error::Unreachable));
+        assert!(matches!(err, HostExportError::PossibleReorg(_)));
+    }
+
+    #[test]
+    fn multicall_errors_from_the_node_are_retried() {
+        let err = multicall_error(ContractCallError::Timeout, 3);
+        assert!(matches!(err, HostExportError::PossibleReorg(_)));
+        assert_eq!(
+            "Ethereum node did not respond during a multicall of 3 calls",
+            err.to_string()
+        );
+
+        let err = multicall_error(ContractCallError::Web3Error(web3::Error::Unreachable), 3);
+        assert!(matches!(err, HostExportError::PossibleReorg(_)));
+
+        let err = multicall_error(ContractCallError::Internal("oops".to_string()), 3);
+        assert!(matches!(err, HostExportError::Unknown(_)));
+        assert_eq!(
+            "Failed to make a multicall of 3 calls: internal error: oops",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn resolve_contract_call_uses_mapping_abis() {
+        let block_ptr = BlockPtr::from((web3::types::H256::from([1; 32]), 1));
+
+        let call =
+            resolve_contract_call(&balance_of("Token"), &abis(), &block_ptr, Some(7)).unwrap();
+        assert_eq!("Token", call.contract_name);
+        assert_eq!("balanceOf", call.function.name);
+        assert_eq!(block_ptr, call.block_ptr);
+        assert_eq!(Some(7), call.gas);
+
+        // A missing ABI is a problem with the subgraph, not the node
+        let err =
+            resolve_contract_call(&balance_of("Missing"), &abis(), &block_ptr, None).unwrap_err();
+        assert!(matches!(err, HostExportError::Deterministic(_)));
+    }
+}
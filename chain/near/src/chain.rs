@@ -572,6 +572,7 @@ mod test {
 
     use graph::{
         blockchain::{block_stream::BlockWithTriggers, DataSource as _, TriggersAdapter as _},
+        components::subgraph::HostMetrics,
         data::subgraph::LATEST_VERSION,
         prelude::{tokio, Link},
         semver::Version,
@@ -869,6 +870,7 @@ mod test {
         ];
 
         let logger = Logger::root(slog::Discard, o!());
+        let metrics = HostMetrics::mock();
         for case in cases.into_iter() {
             let ds = new_data_source(case.account, case.partial_accounts);
             let filter = NearReceiptFilter::from_data_sources(vec![&ds]);
@@ -884,7 +886,12 @@ mod test {
                 let block = Arc::new(new_success_block(11, &req.account));
                 let receipt = Arc::new(new_receipt_with_outcome(&req.account, block.clone()));
                 let res = ds
-                    .match_and_decode(&NearTrigger::Receipt(receipt.clone()), &block, &logger)
+                    .match_and_decode(
+                        &NearTrigger::Receipt(receipt.clone()),
+                        &block,
+                        &logger,
+                        &metrics,
+                    )
                     .expect("unable to process block");
                 assert_eq!(
                     req.matches,
@@ -1,7 +1,7 @@
 use graph::anyhow::Context;
 use graph::blockchain::{Block, TriggerWithHandler};
 use graph::components::store::StoredDynamicDataSource;
-use graph::components::subgraph::InstanceDSTemplateInfo;
+use graph::components::subgraph::{HostMetrics, InstanceDSTemplateInfo};
 use graph::data::subgraph::DataSourceContext;
 use graph::prelude::SubgraphManifestValidationError;
 use graph::{
@@ -102,6 +102,7 @@ impl blockchain::DataSource<Chain> for DataSource {
         trigger: &<Chain as Blockchain>::TriggerData,
         block: &Arc<<Chain as Blockchain>::Block>,
         _logger: &Logger,
+        _metrics: &HostMetrics,
     ) -> Result<Option<TriggerWithHandler<Chain>>, Error> {
         if self.source.start_block > block.number() {
             return Ok(None);
@@ -2,8 +2,9 @@ use graph::{
     anyhow::{anyhow, Error},
     blockchain::{self, Block as BlockchainBlock, TriggerWithHandler},
     components::{
-        link_resolver::LinkResolver, store::StoredDynamicDataSource,
-        subgraph::InstanceDSTemplateInfo,
+        link_resolver::LinkResolver,
+        store::StoredDynamicDataSource,
+        subgraph::{HostMetrics, InstanceDSTemplateInfo},
     },
     data::subgraph::{DataSourceContext, SubgraphManifestValidationError},
     prelude::{async_trait, BlockNumber, Deserialize, Link, Logger},
@@ -134,6 +135,7 @@ impl blockchain::DataSource<Chain> for DataSource {
         trigger: &StarknetTrigger,
         block: &Arc<codec::Block>,
         _logger: &Logger,
+        _metrics: &HostMetrics,
     ) -> Result<Option<TriggerWithHandler<Chain>>, Error> {
         if self.start_block() > block.number() {
             return Ok(None);
@@ -4,7 +4,10 @@ use anyhow::{anyhow, Context, Error};
 use graph::{
     blockchain,
     cheap_clone::CheapClone,
-    components::{link_resolver::LinkResolver, subgraph::InstanceDSTemplateInfo},
+    components::{
+        link_resolver::LinkResolver,
+        subgraph::{HostMetrics, InstanceDSTemplateInfo},
+    },
     prelude::{async_trait, BlockNumber, Link},
     slog::Logger,
 };
@@ -94,6 +97,7 @@ impl blockchain::DataSource<Chain> for DataSource {
         _trigger: &TriggerData,
         _block: &Arc<Block>,
         _logger: &Logger,
+        _metrics: &HostMetrics,
     ) -> Result<Option<blockchain::TriggerWithHandler<Chain>>, Error> {
         unimplemented!()
     }
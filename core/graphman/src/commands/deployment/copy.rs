@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use graph::blockchain::BlockPtr;
+use graph::components::store::BlockNumber;
+use graph::components::store::BlockStore as _;
+use graph::components::store::DeploymentLocator;
+use graph::data::query::QueryTarget;
+use graph::prelude::ChainStore as _;
+use graph::prelude::NodeId;
+use graph::prelude::QueryStoreManager as _;
+use graph_store_postgres::command_support::OnSync;
+use graph_store_postgres::connection_pool::ConnectionPool;
+use graph_store_postgres::Shard;
+use graph_store_postgres::Store;
+use thiserror::Error;
+
+use crate::deployment::DeploymentSelector;
+use crate::deployment::DeploymentVersionSelector;
+use crate::GraphmanError;
+
+pub struct CopySource {
+    locator: DeploymentLocator,
+    base_ptr: BlockPtr,
+}
+
+#[derive(Debug, Error)]
+pub enum CopyDeploymentError {
+    #[error(
+        "deployment '{0}' has not indexed any blocks yet \
+         and can not be used as the source of a copy"
+    )]
+    NotIndexed(String),
+
+    #[error(
+        "deployment '{deployment}' has only indexed up to block {latest_block_number}, \
+         but at least block {block_offset} is needed before it can be copied"
+    )]
+    NotEnoughBlocks {
+        deployment: String,
+        latest_block_number: BlockNumber,
+        block_offset: BlockNumber,
+    },
+
+    #[error(
+        "found {0} block hashes for block number {1} in the chain store, expected exactly one"
+    )]
+    BlockHashNotUnique(usize, BlockNumber),
+
+    #[error("invalid node id '{0}'")]
+    InvalidNodeId(String),
+
+    #[error(transparent)]
+    Common(#[from] GraphmanError),
+}
+
+impl CopySource {
+    pub fn locator(&self) -> &DeploymentLocator {
+        &self.locator
+    }
+
+    /// The block the copy starts from.
+    pub fn base_ptr(&self) -> &BlockPtr {
+        &self.base_ptr
+    }
+}
+
+/// Loads the deployment that should be copied and determines the block
+/// the copy should start from, which is `block_offset` blocks behind the
+/// latest block of the source deployment.
+pub async fn load_copy_source(
+    primary_pool: ConnectionPool,
+    store: Arc<Store>,
+    deployment: &DeploymentSelector,
+    block_offset: u32,
+) -> Result<CopySource, CopyDeploymentError> {
+    let block_offset = block_offset as BlockNumber;
+
+    let locator = {
+        let mut primary_conn = primary_pool.get().map_err(GraphmanError::from)?;
+
+        crate::deployment::load_deployment_locator(
+            &mut primary_conn,
+            deployment,
+            &DeploymentVersionSelector::All,
+        )?
+    };
+
+    let query_store = store
+        .query_store(
+            QueryTarget::Deployment(locator.hash.clone(), Default::default()),
+            true,
+        )
+        .await
+        .map_err(|err| GraphmanError::Store(err.into()))?;
+
+    let network = query_store.network_name();
+
+    let latest_ptr = query_store
+        .block_ptr()
+        .await
+        .map_err(GraphmanError::from)?
+        .ok_or_else(|| CopyDeploymentError::NotIndexed(locator.to_string()))?;
+
+    if latest_ptr.number <= block_offset {
+        return Err(CopyDeploymentError::NotEnoughBlocks {
+            deployment: locator.to_string(),
+            latest_block_number: latest_ptr.number,
+            block_offset,
+        });
+    }
+
+    let base_number = latest_ptr.number - block_offset;
+
+    let chain_store = store.block_store().chain_store(network).ok_or_else(|| {
+        GraphmanError::Store(anyhow!("chain store not found for network '{network}'"))
+    })?;
+
+    let mut hashes = chain_store
+        .block_hashes_by_block_number(base_number)
+        .map_err(GraphmanError::Store)?;
+
+    if hashes.len() != 1 {
+        return Err(CopyDeploymentError::BlockHashNotUnique(
+            hashes.len(),
+            base_number,
+        ));
+    }
+
+    let base_ptr = BlockPtr::new(hashes.pop().unwrap(), base_number);
+
+    Ok(CopySource { locator, base_ptr })
+}
+
+/// Sets up a copy of the source deployment in the specified shard and assigns it to `node`.
+///
+/// The data is copied by the node the copy is assigned to, the progress
+/// can be followed with `graphman copy status`.
+pub fn copy_deployment(
+    store: Arc<Store>,
+    source: CopySource,
+    shard: &str,
+    node: &str,
+    on_sync: OnSync,
+) -> Result<DeploymentLocator, CopyDeploymentError> {
+    let shard = Shard::new(shard.to_owned()).map_err(GraphmanError::from)?;
+    let node =
+        NodeId::new(node).map_err(|()| CopyDeploymentError::InvalidNodeId(node.to_owned()))?;
+
+    let CopySource { locator, base_ptr } = source;
+
+    let copy = store
+        .subgraph_store()
+        .copy_deployment(&locator, shard, node, base_ptr, on_sync)
+        .map_err(GraphmanError::from)?;
+
+    Ok(copy)
+}
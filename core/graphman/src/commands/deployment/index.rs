@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use graph::components::store::BlockNumber;
+use graph::components::store::DeploymentLocator;
+use graph::components::store::StoreError;
+use graph_store_postgres::command_support::index::Method;
+use graph_store_postgres::connection_pool::ConnectionPool;
+use graph_store_postgres::SubgraphStore;
+use thiserror::Error;
+
+use crate::deployment::DeploymentSelector;
+use crate::deployment::DeploymentVersionSelector;
+use crate::GraphmanError;
+
+/// Indexes that include this column default to GiST instead of B-tree.
+pub const BLOCK_RANGE_COLUMN: &str = "block_range";
+
+#[derive(Debug, Error)]
+pub enum CreateIndexError {
+    #[error("at least one field must be specified")]
+    NoFields,
+
+    #[error("entity fields must be unique")]
+    DuplicateFields,
+
+    #[error("unknown index method '{0}'")]
+    UnknownMethod(String),
+
+    #[error("index creation was canceled, please retry")]
+    Canceled,
+
+    #[error(transparent)]
+    Common(#[from] GraphmanError),
+}
+
+/// Describes an index that should be created manually on an entity table.
+#[derive(Clone, Debug)]
+pub struct IndexDefinition {
+    pub entity_name: String,
+    pub field_names: Vec<String>,
+
+    /// When not specified, defaults to `gist` if the fields contain the block range column,
+    /// and to `btree` otherwise.
+    pub method: Option<String>,
+
+    /// Creates a partial index that only covers entity versions after this block.
+    /// This can improve performance for queries that are close to the subgraph head.
+    pub after: Option<BlockNumber>,
+}
+
+pub fn load_deployment(
+    primary_pool: ConnectionPool,
+    deployment: &DeploymentSelector,
+) -> Result<DeploymentLocator, GraphmanError> {
+    let mut primary_conn = primary_pool.get()?;
+
+    crate::deployment::load_deployment_locator(
+        &mut primary_conn,
+        deployment,
+        &DeploymentVersionSelector::All,
+    )
+}
+
+pub async fn create_index(
+    subgraph_store: Arc<SubgraphStore>,
+    deployment: &DeploymentLocator,
+    index: IndexDefinition,
+) -> Result<(), CreateIndexError> {
+    let IndexDefinition {
+        entity_name,
+        field_names,
+        method,
+        after,
+    } = index;
+
+    if field_names.is_empty() {
+        return Err(CreateIndexError::NoFields);
+    }
+
+    let unique_field_names: HashSet<_> = field_names.iter().collect();
+
+    if unique_field_names.len() != field_names.len() {
+        return Err(CreateIndexError::DuplicateFields);
+    }
+
+    let method = method.unwrap_or_else(|| {
+        if field_names.iter().any(|name| name == BLOCK_RANGE_COLUMN) {
+            "gist".to_owned()
+        } else {
+            "btree".to_owned()
+        }
+    });
+
+    let method = method
+        .parse::<Method>()
+        .map_err(|_| CreateIndexError::UnknownMethod(method))?;
+
+    match subgraph_store
+        .create_manual_index(deployment, &entity_name, field_names, method, after)
+        .await
+    {
+        Ok(()) => Ok(()),
+        Err(StoreError::Canceled) => Err(CreateIndexError::Canceled),
+        Err(err) => Err(GraphmanError::from(err).into()),
+    }
+}
+
+pub async fn drop_index(
+    subgraph_store: Arc<SubgraphStore>,
+    deployment: &DeploymentLocator,
+    index_name: &str,
+) -> Result<(), GraphmanError> {
+    subgraph_store
+        .drop_index_for_deployment(deployment, index_name)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use graph::components::store::StatusStore;
+use graph::data::subgraph::status;
+use graph_store_postgres::connection_pool::ConnectionPool;
+use graph_store_postgres::Store;
+use itertools::Itertools;
+
+use crate::deployment::Deployment;
+use crate::deployment::DeploymentSelector;
+use crate::deployment::DeploymentVersionSelector;
+use crate::GraphmanError;
+
+/// The number of deployments whose indexing status is loaded at once when the filter
+/// has criteria that can only be checked with the indexing status.
+const STATUS_BATCH_SIZE: usize = 100;
+
+/// Loads a page of the active deployments that match the filter, ordered by deployment id.
+///
+/// Each deployment is returned once; when it has multiple names,
+/// the name that uses it as the current version is preferred.
+pub fn load_matching_deployments(
+    primary_pool: ConnectionPool,
+    store: Arc<Store>,
+    filter: status::DeploymentFilter,
+    first: usize,
+    skip: usize,
+) -> Result<Vec<Deployment>, GraphmanError> {
+    let mut primary_conn = primary_pool.get()?;
+
+    let ids = if filter.needs_status() {
+        load_matching_ids_by_status(&store, &filter, first, skip)?
+    } else {
+        load_matching_ids(&store, &filter, first, skip)?.1
+    };
+
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let deployments = crate::deployment::load_deployments(
+        &mut primary_conn,
+        &DeploymentSelector::Ids(ids),
+        &DeploymentVersionSelector::All,
+    )?;
+
+    let deployments = deployments
+        .into_iter()
+        .sorted_by_key(|deployment| (deployment.id, version_rank(&deployment.version_status)))
+        .unique_by(|deployment| deployment.id)
+        .collect();
+
+    Ok(deployments)
+}
+
+/// Loads the ids of a page of the active deployments that match the filter.
+///
+/// The criteria that the primary database can check select the page; the
+/// remaining criteria are checked against the indexing status of the deployments
+/// in the page.
+fn load_matching_ids(
+    store: &Store,
+    filter: &status::DeploymentFilter,
+    first: usize,
+    skip: usize,
+) -> Result<(usize, Vec<i32>), GraphmanError> {
+    let statuses = store.status(status::Filter::Matching {
+        filter: filter.clone(),
+        first,
+        skip,
+    })?;
+
+    let page_len = statuses.len();
+    let ids = statuses
+        .into_iter()
+        .filter(|status| filter.matches(status))
+        .map(|status| status.id.0)
+        .sorted_unstable()
+        .collect();
+
+    Ok((page_len, ids))
+}
+
+/// Loads the ids of a page of the active deployments that match the filter,
+/// when some of its criteria can only be checked with the indexing status.
+///
+/// The deployments that match the other criteria are checked in batches,
+/// and loading stops as soon as the page is complete.
+fn load_matching_ids_by_status(
+    store: &Store,
+    filter: &status::DeploymentFilter,
+    first: usize,
+    skip: usize,
+) -> Result<Vec<i32>, GraphmanError> {
+    let mut ids = Vec::new();
+    let mut offset = 0;
+
+    while ids.len() < skip + first {
+        let (batch_len, matching) = load_matching_ids(store, filter, STATUS_BATCH_SIZE, offset)?;
+        offset += batch_len;
+
+        ids.extend(matching);
+
+        if batch_len < STATUS_BATCH_SIZE {
+            break;
+        }
+    }
+
+    Ok(ids.into_iter().skip(skip).take(first).collect())
+}
+
+fn version_rank(version_status: &str) -> u8 {
+    match version_status {
+        "current" => 0,
+        "pending" => 1,
+        _ => 2,
+    }
+}
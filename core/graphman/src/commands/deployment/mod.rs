@@ -1,3 +1,10 @@
+pub mod copy;
+pub mod index;
 pub mod info;
+pub mod list;
 pub mod pause;
+pub mod prune;
+pub mod reassign;
 pub mod resume;
+pub mod rewind;
+pub mod unassign;
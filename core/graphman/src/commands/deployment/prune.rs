@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use graph::components::store::BlockNumber;
+use graph::components::store::DeploymentLocator;
+use graph::components::store::PruneReporter;
+use graph::components::store::PruneRequest;
+use graph::components::store::StatusStore;
+use graph::data::subgraph::status;
+use graph::env::ENV_VARS;
+use graph_store_postgres::connection_pool::ConnectionPool;
+use graph_store_postgres::Store;
+use thiserror::Error;
+
+use crate::deployment::DeploymentSelector;
+use crate::deployment::DeploymentVersionSelector;
+use crate::GraphmanError;
+
+pub struct PrunableDeployment {
+    locator: DeploymentLocator,
+    earliest_block_number: BlockNumber,
+    latest_block_number: BlockNumber,
+}
+
+#[derive(Debug, Error)]
+pub enum PruneDeploymentError {
+    #[error("deployment '{0}' not found")]
+    NotFound(String),
+
+    #[error("deployment '{0}' does not index any chain")]
+    NoChain(String),
+
+    #[error("deployment '{0}' indexes {1} chains, pruning is only supported for a single chain")]
+    MultipleChains(String, usize),
+
+    #[error(
+        "deployment '{deployment}' has only indexed up to block {latest_block_number} \
+         and can not preserve {history_blocks} blocks of history"
+    )]
+    NotEnoughBlocks {
+        deployment: String,
+        latest_block_number: BlockNumber,
+        history_blocks: BlockNumber,
+    },
+
+    #[error(transparent)]
+    Common(#[from] GraphmanError),
+}
+
+/// Settings that control how a deployment is pruned.
+#[derive(Clone, Debug)]
+pub struct PruneOptions {
+    /// The number of blocks of history that should be kept.
+    pub history_blocks: BlockNumber,
+
+    /// Prune by rebuilding tables when removing more than this fraction of history.
+    pub rebuild_threshold: Option<f64>,
+
+    /// Prune by deleting when removing more than this fraction of history.
+    pub delete_threshold: Option<f64>,
+
+    /// When set, the history setting of the deployment is not changed,
+    /// and the deployment is only pruned this once.
+    pub once: bool,
+}
+
+impl PrunableDeployment {
+    pub fn locator(&self) -> &DeploymentLocator {
+        &self.locator
+    }
+
+    pub fn earliest_block_number(&self) -> BlockNumber {
+        self.earliest_block_number
+    }
+
+    pub fn latest_block_number(&self) -> BlockNumber {
+        self.latest_block_number
+    }
+}
+
+pub fn load_prunable_deployment(
+    primary_pool: ConnectionPool,
+    store: Arc<Store>,
+    deployment: &DeploymentSelector,
+    history_blocks: BlockNumber,
+) -> Result<PrunableDeployment, PruneDeploymentError> {
+    let mut primary_conn = primary_pool.get().map_err(GraphmanError::from)?;
+
+    let locator = crate::deployment::load_deployment_locator(
+        &mut primary_conn,
+        deployment,
+        &DeploymentVersionSelector::All,
+    )?;
+
+    let mut status = store
+        .status(status::Filter::DeploymentIds(vec![locator.id]))
+        .map_err(GraphmanError::from)?
+        .pop()
+        .ok_or_else(|| PruneDeploymentError::NotFound(locator.to_string()))?;
+
+    if status.chains.len() > 1 {
+        return Err(PruneDeploymentError::MultipleChains(
+            locator.to_string(),
+            status.chains.len(),
+        ));
+    }
+
+    let chain = status
+        .chains
+        .pop()
+        .ok_or_else(|| PruneDeploymentError::NoChain(locator.to_string()))?;
+
+    let latest_block_number = chain.latest_block.map(|ptr| ptr.number()).unwrap_or(0);
+
+    if latest_block_number <= history_blocks {
+        return Err(PruneDeploymentError::NotEnoughBlocks {
+            deployment: locator.to_string(),
+            latest_block_number,
+            history_blocks,
+        });
+    }
+
+    Ok(PrunableDeployment {
+        locator,
+        earliest_block_number: chain.earliest_block_number,
+        latest_block_number,
+    })
+}
+
+pub async fn prune_deployment(
+    store: Arc<Store>,
+    prunable_deployment: PrunableDeployment,
+    options: PruneOptions,
+    reporter: Box<dyn PruneReporter>,
+) -> Result<(), GraphmanError> {
+    let PrunableDeployment {
+        locator,
+        earliest_block_number,
+        latest_block_number,
+    } = prunable_deployment;
+
+    let PruneOptions {
+        history_blocks,
+        rebuild_threshold,
+        delete_threshold,
+        once,
+    } = options;
+
+    let mut req = PruneRequest::new(
+        &locator,
+        history_blocks,
+        ENV_VARS.reorg_threshold,
+        earliest_block_number,
+        latest_block_number,
+    )?;
+
+    if let Some(rebuild_threshold) = rebuild_threshold {
+        req.rebuild_threshold = rebuild_threshold;
+    }
+
+    if let Some(delete_threshold) = delete_threshold {
+        req.delete_threshold = delete_threshold;
+    }
+
+    let subgraph_store = store.subgraph_store();
+
+    subgraph_store.prune(reporter, &locator, req).await?;
+
+    // Only after everything worked out, make the history setting permanent
+    if !once {
+        subgraph_store.set_history_blocks(&locator, history_blocks, ENV_VARS.reorg_threshold)?;
+    }
+
+    Ok(())
+}
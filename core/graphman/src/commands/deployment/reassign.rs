@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use graph::components::store::DeploymentLocator;
+use graph::components::store::StoreEvent;
+use graph::prelude::NodeId;
+use graph_store_postgres::command_support::catalog;
+use graph_store_postgres::command_support::catalog::Site;
+use graph_store_postgres::connection_pool::ConnectionPool;
+use graph_store_postgres::NotificationSender;
+use thiserror::Error;
+
+use crate::deployment::DeploymentSelector;
+use crate::deployment::DeploymentVersionSelector;
+use crate::GraphmanError;
+
+pub struct SelectedDeployment {
+    locator: DeploymentLocator,
+    site: Site,
+    assigned_node: Option<NodeId>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReassignDeploymentError {
+    #[error("invalid node id '{0}'")]
+    InvalidNodeId(String),
+
+    #[error("deployment '{0}' is already assigned to node '{1}'")]
+    AlreadyAssigned(String, String),
+
+    #[error(transparent)]
+    Common(#[from] GraphmanError),
+}
+
+/// The outcome of a successful reassignment.
+pub enum ReassignResult {
+    Ok,
+
+    /// The deployment was reassigned, but the operation looks suspicious,
+    /// for example, when it is the only deployment assigned to the node.
+    CompletedWithWarnings(Vec<String>),
+}
+
+impl SelectedDeployment {
+    pub fn locator(&self) -> &DeploymentLocator {
+        &self.locator
+    }
+
+    /// Returns the node the deployment was assigned to when it was loaded.
+    pub fn assigned_node(&self) -> Option<&NodeId> {
+        self.assigned_node.as_ref()
+    }
+}
+
+pub fn load_deployment(
+    primary_pool: ConnectionPool,
+    deployment: &DeploymentSelector,
+) -> Result<SelectedDeployment, GraphmanError> {
+    let mut primary_conn = primary_pool.get()?;
+
+    let locator = crate::deployment::load_deployment_locator(
+        &mut primary_conn,
+        deployment,
+        &DeploymentVersionSelector::All,
+    )?;
+
+    let mut catalog_conn = catalog::Connection::new(primary_conn);
+
+    let site = crate::deployment::load_deployment_site(&mut catalog_conn, &locator)?;
+    let assigned_node = catalog_conn.assigned_node(&site)?;
+
+    Ok(SelectedDeployment {
+        locator,
+        site,
+        assigned_node,
+    })
+}
+
+pub fn reassign_deployment(
+    primary_pool: ConnectionPool,
+    notification_sender: Arc<NotificationSender>,
+    deployment: SelectedDeployment,
+    node: &str,
+) -> Result<ReassignResult, ReassignDeploymentError> {
+    let node =
+        NodeId::new(node).map_err(|()| ReassignDeploymentError::InvalidNodeId(node.to_owned()))?;
+
+    let primary_conn = primary_pool.get().map_err(GraphmanError::from)?;
+    let mut catalog_conn = catalog::Connection::new(primary_conn);
+
+    let changes = match &deployment.assigned_node {
+        Some(current) if *current == node => {
+            return Err(ReassignDeploymentError::AlreadyAssigned(
+                deployment.locator.to_string(),
+                node.to_string(),
+            ));
+        }
+        Some(_) => catalog_conn.reassign_subgraph(&deployment.site, &node),
+        None => catalog_conn.assign_subgraph(&deployment.site, &node),
+    }
+    .map_err(GraphmanError::from)?;
+
+    catalog_conn
+        .send_store_event(&notification_sender, &StoreEvent::new(changes))
+        .map_err(GraphmanError::from)?;
+
+    // It's easy to make a typo in the name of the node; if this operation
+    // assigns to a node that wasn't used before, warn the user that they
+    // might have mistyped the node name
+    let mirror = catalog::Mirror::primary_only(primary_pool);
+    let count = mirror
+        .assignments(&node)
+        .map_err(GraphmanError::from)?
+        .len();
+
+    if count == 1 {
+        return Ok(ReassignResult::CompletedWithWarnings(vec![format!(
+            "this is the only deployment assigned to '{node}'; \
+             please make sure that the node ID is spelled correctly"
+        )]));
+    }
+
+    Ok(ReassignResult::Ok)
+}
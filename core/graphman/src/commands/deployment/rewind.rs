@@ -0,0 +1,291 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use graph::blockchain::BlockPtr;
+use graph::components::store::BlockNumber;
+use graph::components::store::BlockStore as _;
+use graph::components::store::ChainStore as _;
+use graph::components::store::DeploymentLocator;
+use graph::components::store::StoreEvent;
+use graph::env::ENV_VARS;
+use graph_store_postgres::command_support::catalog;
+use graph_store_postgres::connection_pool::ConnectionPool;
+use graph_store_postgres::NotificationSender;
+use graph_store_postgres::Store;
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::deployment::Deployment;
+use crate::deployment::DeploymentSelector;
+use crate::deployment::DeploymentVersionSelector;
+use crate::GraphmanError;
+
+#[derive(Debug, Error)]
+pub enum RewindDeploymentError {
+    #[error("deployments are on different chains: {}", .0.join(", "))]
+    DifferentChains(Vec<String>),
+
+    #[error("invalid block pointer: {0:#}")]
+    InvalidBlock(#[source] anyhow::Error),
+
+    #[error(
+        "block hash '{hash}' is for block number {actual}, \
+         but block number {expected} was specified"
+    )]
+    BlockNumberMismatch {
+        hash: String,
+        expected: BlockNumber,
+        actual: BlockNumber,
+    },
+
+    #[error("chain '{chain}' does not have a block with hash '{hash}'")]
+    BlockNotFound { chain: String, hash: String },
+
+    #[error(
+        "block number {block_number} is not safe to rewind to for deployment '{deployment}', \
+         the earliest block number it can be safely rewound to is {earliest_safe_block_number}"
+    )]
+    UnsafeBlock {
+        deployment: String,
+        block_number: BlockNumber,
+        earliest_safe_block_number: BlockNumber,
+    },
+
+    #[error("failed to find the start block of deployment '{0}'")]
+    StartBlockNotFound(String),
+
+    #[error(transparent)]
+    Common(#[from] GraphmanError),
+}
+
+/// Describes what happened to a deployment after it was rewound.
+pub enum RewindResult {
+    /// The deployment was rewound to the specified block.
+    Rewound,
+
+    /// The deployment was rewound to its start block and all its data was removed.
+    Truncated,
+}
+
+/// Loads the deployments that should be rewound.
+///
+/// Each selector must match exactly one deployment,
+/// and all deployments must be on the same chain.
+pub fn load_deployments(
+    primary_pool: ConnectionPool,
+    deployments: &[DeploymentSelector],
+) -> Result<Vec<Deployment>, RewindDeploymentError> {
+    let mut primary_conn = primary_pool.get().map_err(GraphmanError::from)?;
+
+    let deployments = deployments
+        .iter()
+        .map(|deployment| {
+            crate::deployment::load_deployments(
+                &mut primary_conn,
+                deployment,
+                &DeploymentVersionSelector::All,
+            )?
+            .into_iter()
+            .unique_by(|deployment| deployment.id)
+            .exactly_one()
+            .map_err(|err| {
+                let count = err.into_iter().count();
+                GraphmanError::Store(anyhow!(
+                    "expected exactly one deployment for '{deployment:?}', found {count}"
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unique_by(|deployment| deployment.id)
+        .collect_vec();
+
+    let chains = deployments
+        .iter()
+        .map(|deployment| deployment.chain.clone())
+        .unique()
+        .collect_vec();
+
+    if chains.len() > 1 {
+        return Err(RewindDeploymentError::DifferentChains(chains));
+    }
+
+    Ok(deployments)
+}
+
+/// Validates the block the deployments should be rewound to against the chain store.
+///
+/// When `force` is set, blocks that are not in the chain store are accepted.
+pub async fn load_block_ptr(
+    store: Arc<Store>,
+    deployments: &[Deployment],
+    block_hash: &str,
+    block_number: BlockNumber,
+    force: bool,
+) -> Result<BlockPtr, RewindDeploymentError> {
+    let block_ptr = BlockPtr::try_from((block_hash, block_number as i64))
+        .map_err(RewindDeploymentError::InvalidBlock)?;
+
+    let Some(chain) = deployments.first().map(|deployment| &deployment.chain) else {
+        return Ok(block_ptr);
+    };
+
+    let chain_store = store.block_store().chain_store(chain).ok_or_else(|| {
+        GraphmanError::Store(anyhow!("chain store not found for chain '{chain}'"))
+    })?;
+
+    let block = chain_store
+        .block_number(&block_ptr.hash)
+        .await
+        .map_err(GraphmanError::from)?;
+
+    match block {
+        Some((_, number, _, _)) if number != block_ptr.number => {
+            Err(RewindDeploymentError::BlockNumberMismatch {
+                hash: block_hash.to_owned(),
+                expected: block_ptr.number,
+                actual: number,
+            })
+        }
+        Some(_) => Ok(block_ptr),
+        None if force => Ok(block_ptr),
+        None => Err(RewindDeploymentError::BlockNotFound {
+            chain: chain.clone(),
+            hash: block_hash.to_owned(),
+        }),
+    }
+}
+
+/// Makes sure that none of the deployments would be rewound past the point
+/// that is still covered by their history.
+///
+/// When `block_ptr` is `None`, the deployments are rewound to their start blocks.
+pub fn check_rewind_is_safe(
+    primary_pool: ConnectionPool,
+    store: Arc<Store>,
+    deployments: &[Deployment],
+    block_ptr: Option<&BlockPtr>,
+) -> Result<(), RewindDeploymentError> {
+    let primary_conn = primary_pool.get().map_err(GraphmanError::from)?;
+    let mut catalog_conn = catalog::Connection::new(primary_conn);
+    let subgraph_store = store.subgraph_store();
+
+    let block_number = block_ptr.map(|ptr| ptr.number).unwrap_or(0);
+
+    for deployment in deployments {
+        let locator = deployment.locator();
+        let site = crate::deployment::load_deployment_site(&mut catalog_conn, &locator)?;
+
+        let details = subgraph_store
+            .for_site(&site)
+            .and_then(|store| store.deployment_details_for_id(&locator))
+            .map_err(GraphmanError::from)?;
+
+        let earliest_safe_block_number = details.earliest_block_number + ENV_VARS.reorg_threshold;
+
+        if block_number < earliest_safe_block_number {
+            return Err(RewindDeploymentError::UnsafeBlock {
+                deployment: locator.to_string(),
+                block_number,
+                earliest_safe_block_number,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Pauses all deployments that are not already paused.
+pub fn pause_deployments(
+    primary_pool: ConnectionPool,
+    notification_sender: Arc<NotificationSender>,
+    deployments: &[Deployment],
+) -> Result<(), GraphmanError> {
+    set_paused(primary_pool, notification_sender, deployments, true)
+}
+
+/// Resumes all deployments, including the ones that were paused before the rewind.
+pub fn resume_deployments(
+    primary_pool: ConnectionPool,
+    notification_sender: Arc<NotificationSender>,
+    deployments: &[Deployment],
+) -> Result<(), GraphmanError> {
+    set_paused(primary_pool, notification_sender, deployments, false)
+}
+
+fn set_paused(
+    primary_pool: ConnectionPool,
+    notification_sender: Arc<NotificationSender>,
+    deployments: &[Deployment],
+    pause: bool,
+) -> Result<(), GraphmanError> {
+    let primary_conn = primary_pool.get()?;
+    let mut catalog_conn = catalog::Connection::new(primary_conn);
+
+    let locators: HashSet<DeploymentLocator> = deployments
+        .iter()
+        .map(|deployment| deployment.locator())
+        .collect();
+
+    for locator in locators {
+        let site = crate::deployment::load_deployment_site(&mut catalog_conn, &locator)?;
+
+        let Some((_, is_paused)) = catalog_conn.assignment_status(&site)? else {
+            // Unassigned deployments are not indexing and do not need to be paused.
+            continue;
+        };
+
+        let changes = match (pause, is_paused) {
+            (true, false) => catalog_conn.pause_subgraph(&site)?,
+            (false, _) => catalog_conn.resume_subgraph(&site)?,
+            (true, true) => continue,
+        };
+
+        catalog_conn.send_store_event(&notification_sender, &StoreEvent::new(changes))?;
+    }
+
+    Ok(())
+}
+
+/// Rewinds the deployment to the specified block, or truncates it to its start block
+/// if no block is specified.
+///
+/// The deployment should be paused, and enough time should have passed
+/// for the pause to take effect, before this is called.
+pub fn rewind_deployment(
+    store: Arc<Store>,
+    deployment: &Deployment,
+    block_ptr: Option<BlockPtr>,
+) -> Result<RewindResult, RewindDeploymentError> {
+    let subgraph_store = store.subgraph_store();
+    let locator = deployment.locator();
+
+    if let Some(block_ptr) = block_ptr {
+        subgraph_store
+            .rewind(locator.hash, block_ptr)
+            .map_err(GraphmanError::from)?;
+
+        return Ok(RewindResult::Rewound);
+    }
+
+    let details = subgraph_store
+        .load_deployment_by_id(locator.id)
+        .map_err(GraphmanError::from)?;
+
+    let start_block_ptr = details
+        .start_block
+        .or_else(|| {
+            store
+                .block_store()
+                .chain_store(&deployment.chain)
+                .and_then(|chain_store| chain_store.genesis_block_ptr().ok())
+        })
+        .ok_or_else(|| RewindDeploymentError::StartBlockNotFound(locator.to_string()))?;
+
+    subgraph_store
+        .truncate(locator.hash, start_block_ptr)
+        .map_err(GraphmanError::from)?;
+
+    Ok(RewindResult::Truncated)
+}
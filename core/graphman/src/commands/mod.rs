@@ -1 +1,2 @@
 pub mod deployment;
+pub mod subgraph;
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use graph::prelude::SubgraphName;
+use graph::prelude::SubgraphStore as _;
+use graph_store_postgres::command_support::catalog;
+use graph_store_postgres::connection_pool::ConnectionPool;
+use graph_store_postgres::SubgraphStore;
+use thiserror::Error;
+
+use crate::GraphmanError;
+
+pub struct ExistingSubgraph {
+    name: SubgraphName,
+}
+
+#[derive(Debug, Error)]
+pub enum RemoveSubgraphError {
+    #[error("invalid subgraph name '{0}'")]
+    InvalidName(String),
+
+    #[error("subgraph '{0}' does not exist")]
+    NotFound(String),
+
+    #[error(transparent)]
+    Common(#[from] GraphmanError),
+}
+
+impl ExistingSubgraph {
+    pub fn name(&self) -> &SubgraphName {
+        &self.name
+    }
+}
+
+pub fn load_existing_subgraph(
+    primary_pool: ConnectionPool,
+    name: &str,
+) -> Result<ExistingSubgraph, RemoveSubgraphError> {
+    let name =
+        SubgraphName::new(name).map_err(|()| RemoveSubgraphError::InvalidName(name.to_owned()))?;
+
+    let mirror = catalog::Mirror::primary_only(primary_pool);
+    let exists = mirror.subgraph_exists(&name).map_err(GraphmanError::from)?;
+
+    if !exists {
+        return Err(RemoveSubgraphError::NotFound(name.to_string()));
+    }
+
+    Ok(ExistingSubgraph { name })
+}
+
+/// Removes the subgraph name and all its versions. Deployments that are no longer
+/// used by any subgraph are unassigned, but their data is kept until they are removed
+/// with `graphman unused`.
+pub fn remove_existing_subgraph(
+    subgraph_store: Arc<SubgraphStore>,
+    existing_subgraph: ExistingSubgraph,
+) -> Result<(), GraphmanError> {
+    subgraph_store.remove_subgraph(existing_subgraph.name)?;
+
+    Ok(())
+}
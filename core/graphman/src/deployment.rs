@@ -6,6 +6,7 @@ use graph::components::store::DeploymentId;
 use graph::components::store::DeploymentLocator;
 use graph::data::subgraph::DeploymentHash;
 use graph_store_postgres::command_support::catalog;
+use graph_store_postgres::command_support::catalog::Site;
 use itertools::Itertools;
 
 use crate::GraphmanError;
@@ -28,6 +29,7 @@ pub enum DeploymentSelector {
     Name(String),
     Subgraph { hash: String, shard: Option<String> },
     Schema(String),
+    Ids(Vec<i32>),
     All,
 }
 
@@ -101,6 +103,9 @@ pub(crate) fn load_deployments(
         DeploymentSelector::Schema(name) => {
             query = query.filter(ds::name.eq(name));
         }
+        DeploymentSelector::Ids(ids) => {
+            query = query.filter(ds::id.eq_any(ids));
+        }
         DeploymentSelector::All => {
             // No query changes required.
         }
@@ -146,3 +151,12 @@ pub(crate) fn load_deployment_locator(
 
     Ok(deployment_locator)
 }
+
+pub(crate) fn load_deployment_site(
+    catalog_conn: &mut catalog::Connection,
+    locator: &DeploymentLocator,
+) -> Result<Site, GraphmanError> {
+    catalog_conn
+        .locate_site(locator.clone())?
+        .ok_or_else(|| GraphmanError::Store(anyhow!("deployment site not found for '{locator}'")))
+}
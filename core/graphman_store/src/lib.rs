@@ -28,7 +28,9 @@ pub trait GraphmanStore {
     /// The implementation is expected to manage execution IDs and return unique IDs on each call.
     ///
     /// Creating a new execution does not mean that a command is actually running or will run.
-    fn new_execution(&self, kind: CommandKind) -> Result<ExecutionId>;
+    ///
+    /// The `created_by` value identifies who requested the execution.
+    fn new_execution(&self, kind: CommandKind, created_by: &str) -> Result<ExecutionId>;
 
     /// Returns all stored execution data.
     fn load_execution(&self, id: ExecutionId) -> Result<Execution>;
@@ -64,6 +66,7 @@ pub struct Execution {
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub created_by: Option<String>,
 }
 
 /// A unique ID of a command execution.
@@ -76,7 +79,17 @@ pub struct ExecutionId(pub i64);
 #[diesel(sql_type = Varchar)]
 #[strum(serialize_all = "snake_case")]
 pub enum CommandKind {
+    PauseDeployment,
+    ResumeDeployment,
     RestartDeployment,
+    ReassignDeployment,
+    UnassignDeployment,
+    RewindDeployment,
+    PruneDeployment,
+    CopyDeployment,
+    CreateIndex,
+    DropIndex,
+    RemoveSubgraph,
 }
 
 /// All possible states of a command execution.
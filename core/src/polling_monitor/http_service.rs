@@ -0,0 +1,235 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Error};
+use bytes::Bytes;
+use graph::data_source::offchain::HttpUrl;
+use graph::futures03::future::BoxFuture;
+use graph::futures03::StreamExt;
+use graph::prelude::reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use graph::prelude::reqwest::{redirect, Client, StatusCode, Url};
+use graph::prelude::tokio;
+use graph::url::Host;
+use graph::{derive::CheapClone, prelude::CheapClone};
+use tower::{buffer::Buffer, ServiceBuilder, ServiceExt};
+
+pub type HttpService = Buffer<HttpUrl, BoxFuture<'static, Result<Option<Bytes>, Error>>>;
+
+/// The number of redirects we follow for one request
+const MAX_REDIRECTS: usize = 10;
+
+/// Create the service that fetches `file/http` data sources. Unless
+/// `allow_private_addresses` is set, the service refuses to connect to
+/// loopback, private, link-local and other non-public addresses, including
+/// when a redirect points to one, so that subgraphs can not make graph-node
+/// fetch from the network it runs in.
+pub fn http_service(
+    max_file_size: usize,
+    timeout: Duration,
+    rate_limit: u16,
+    allow_private_addresses: bool,
+) -> HttpService {
+    let redirects = redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error(anyhow!("too many redirects"));
+        }
+        match check_url(attempt.url(), allow_private_addresses) {
+            Ok(()) => attempt.follow(),
+            Err(e) => attempt.error(e),
+        }
+    });
+    let mut client = Client::builder().redirect(redirects);
+    if !allow_private_addresses {
+        client = client.dns_resolver(Arc::new(PublicResolver));
+    }
+    let client = client
+        .build()
+        .expect("failed to build the HTTP client for file data sources");
+
+    let http = HttpServiceInner {
+        client,
+        timeout,
+        max_file_size,
+        allow_private_addresses,
+    };
+
+    let svc = ServiceBuilder::new()
+        .rate_limit(rate_limit.into(), Duration::from_secs(1))
+        .service_fn(move |req| http.cheap_clone().call_inner(req))
+        .boxed();
+
+    // The `Buffer` makes it so the rate limit is shared among clones.
+    // Make it unbounded to avoid any risk of starvation.
+    Buffer::new(svc, u32::MAX as usize)
+}
+
+#[derive(Clone, CheapClone)]
+struct HttpServiceInner {
+    client: Client,
+    timeout: Duration,
+    max_file_size: usize,
+    allow_private_addresses: bool,
+}
+
+impl HttpServiceInner {
+    async fn call_inner(self, url: HttpUrl) -> Result<Option<Bytes>, Error> {
+        // Host names are checked when they are resolved, but addresses in
+        // the URL are used as is
+        check_url(&Url::parse(url.as_str())?, self.allow_private_addresses)?;
+
+        let rsp = match self
+            .client
+            .get(url.as_str())
+            .timeout(self.timeout)
+            .send()
+            .await
+        {
+            Ok(rsp) => rsp,
+            // Timeouts mean that the content is not available right now, so we return `None`.
+            Err(err) if err.is_timeout() => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        if rsp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let rsp = rsp.error_for_status()?;
+
+        if let Some(len) = rsp.content_length() {
+            if len > self.max_file_size as u64 {
+                return Err(self.too_large(&url, len));
+            }
+        }
+
+        // The content length is not always known up front, so the limit is
+        // also checked while the body is read.
+        let mut data = Vec::new();
+        let mut body = rsp.bytes_stream();
+
+        while let Some(chunk) = body.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) if err.is_timeout() => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+
+            if data.len() + chunk.len() > self.max_file_size {
+                return Err(self.too_large(&url, (data.len() + chunk.len()) as u64));
+            }
+
+            data.extend_from_slice(&chunk);
+        }
+
+        Ok(Some(data.into()))
+    }
+
+    fn too_large(&self, url: &HttpUrl, len: u64) -> Error {
+        anyhow!(
+            "HTTP file `{}` is too large, the limit is {} bytes but got at least {} bytes",
+            url,
+            self.max_file_size,
+            len
+        )
+    }
+}
+
+/// Check that we may fetch `url`
+fn check_url(url: &Url, allow_private_addresses: bool) -> Result<(), Error> {
+    match url.scheme() {
+        "http" | "https" => {}
+        scheme => bail!("unsupported URL scheme `{scheme}` in `{url}`"),
+    }
+    let addr = match url.host() {
+        Some(Host::Ipv4(addr)) => IpAddr::V4(addr),
+        Some(Host::Ipv6(addr)) => IpAddr::V6(addr),
+        Some(Host::Domain(_)) => return Ok(()),
+        None => bail!("URL `{url}` has no host"),
+    };
+    if !allow_private_addresses && !is_public(addr) {
+        bail!("refusing to fetch `{url}` from non-public address {addr}");
+    }
+    Ok(())
+}
+
+/// Whether `addr` is an address on the public internet
+fn is_public(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(addr) => {
+            let [a, b, ..] = addr.octets();
+            // 100.64.0.0/10 is shared address space for carrier-grade NAT
+            let shared = a == 100 && (b & 0xc0) == 64;
+            !(addr.is_private()
+                || addr.is_loopback()
+                || addr.is_link_local()
+                || addr.is_broadcast()
+                || addr.is_unspecified()
+                || addr.is_documentation()
+                || shared)
+        }
+        IpAddr::V6(addr) => {
+            if let Some(addr) = addr.to_ipv4_mapped() {
+                return is_public(IpAddr::V4(addr));
+            }
+            let first = addr.segments()[0];
+            // fc00::/7 are unique local and fe80::/10 link-local addresses
+            let unique_local = (first & 0xfe00) == 0xfc00;
+            let link_local = (first & 0xffc0) == 0xfe80;
+            !(addr.is_loopback() || addr.is_unspecified() || unique_local || link_local)
+        }
+    }
+}
+
+/// A DNS resolver that only returns public addresses
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(resolve_public(name.as_str().to_string()))
+    }
+}
+
+async fn resolve_public(host: String) -> Result<Addrs, Box<dyn std::error::Error + Send + Sync>> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+        .await?
+        .filter(|addr| is_public(addr.ip()))
+        .collect();
+    if addrs.is_empty() {
+        return Err(anyhow!("`{host}` does not resolve to a public address").into());
+    }
+    Ok(Box::new(addrs.into_iter()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_public_addresses() {
+        let check = |url: &str| check_url(&Url::parse(url).unwrap(), false).is_ok();
+
+        assert!(check("https://example.com/1.json"));
+        assert!(check("http://1.1.1.1/1.json"));
+        assert!(check("http://[2606:4700::1111]/1.json"));
+
+        for url in [
+            "http://127.0.0.1/1.json",
+            "http://10.0.0.1/1.json",
+            "http://172.16.0.1/1.json",
+            "http://192.168.1.1/1.json",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/1.json",
+            "http://0.0.0.0/1.json",
+            "http://[::1]/1.json",
+            "http://[fd00::1]/1.json",
+            "http://[fe80::1]/1.json",
+            "http://[::ffff:127.0.0.1]/1.json",
+            "ftp://example.com/1.json",
+        ] {
+            assert!(!check(url), "{url} should be rejected");
+        }
+
+        assert!(check_url(&Url::parse("http://127.0.0.1/1.json").unwrap(), true).is_ok());
+    }
+}
@@ -1,4 +1,5 @@
 mod arweave_service;
+mod http_service;
 mod ipfs_service;
 mod metrics;
 
@@ -16,7 +17,7 @@ use graph::futures03::{stream, Future, FutureExt, TryFutureExt};
 use graph::parking_lot::Mutex;
 use graph::prelude::tokio;
 use graph::prometheus::{Counter, Gauge};
-use graph::slog::{debug, Logger};
+use graph::slog::{debug, warn, Logger};
 use graph::util::monitored::MonitoredVecDeque as VecDeque;
 use tokio::sync::{mpsc, watch};
 use tower::retry::backoff::{Backoff, ExponentialBackoff, ExponentialBackoffMaker, MakeBackoff};
@@ -25,6 +26,7 @@ use tower::{Service, ServiceExt};
 
 pub use self::metrics::PollingMonitorMetrics;
 pub use arweave_service::{arweave_service, ArweaveService};
+pub use http_service::{http_service, HttpService};
 pub use ipfs_service::{ipfs_service, IpfsService};
 
 const MIN_BACKOFF: Duration = Duration::from_secs(5);
@@ -33,11 +35,13 @@ const MAX_BACKOFF: Duration = Duration::from_secs(600);
 
 struct Backoffs<ID> {
     backoff_maker: ExponentialBackoffMaker,
-    backoffs: HashMap<ID, ExponentialBackoff>,
+    /// The backoff for each id and the number of failed attempts to poll it
+    backoffs: HashMap<ID, (ExponentialBackoff, usize)>,
+    max_attempts: Option<usize>,
 }
 
 impl<ID: Eq + Hash> Backoffs<ID> {
-    fn new() -> Self {
+    fn new(max_attempts: Option<usize>) -> Self {
         // Unwrap: Config is constant and valid.
         Self {
             backoff_maker: ExponentialBackoffMaker::new(
@@ -48,14 +52,22 @@ impl<ID: Eq + Hash> Backoffs<ID> {
             )
             .unwrap(),
             backoffs: HashMap::new(),
+            max_attempts,
         }
     }
 
-    fn next_backoff(&mut self, id: ID) -> impl Future<Output = ()> {
-        self.backoffs
+    /// Record a failed attempt to poll `id` and return the backoff before
+    /// polling it again, or `None` if it should not be polled again
+    fn next_backoff(&mut self, id: ID) -> Option<impl Future<Output = ()>> {
+        let (backoff, attempts) = self
+            .backoffs
             .entry(id)
-            .or_insert_with(|| self.backoff_maker.make_backoff())
-            .next_backoff()
+            .or_insert_with(|| (self.backoff_maker.make_backoff(), 0));
+        *attempts += 1;
+        if self.max_attempts.is_some_and(|max| *attempts >= max) {
+            return None;
+        }
+        Some(backoff.next_backoff())
     }
 
     fn remove(&mut self, id: &ID) {
@@ -98,11 +110,16 @@ impl<T> Queue<T> {
 ///
 /// The service returns the request ID along with errors or responses. The response is an
 /// `Option`, to represent the object not being found.
+///
+/// With `max_attempts`, objects that are not found are also polled again after a backoff, and an
+/// object is given up on once it could not be fetched `max_attempts` times. Without it, objects
+/// are polled until they are found.
 pub fn spawn_monitor<ID, S, E, Res: Send + 'static>(
     service: S,
     response_sender: mpsc::UnboundedSender<(ID, Res)>,
     logger: Logger,
     metrics: Arc<PollingMonitorMetrics>,
+    max_attempts: Option<usize>,
 ) -> PollingMonitor<ID>
 where
     S: Service<ID, Response = Option<Res>, Error = E> + Send + 'static,
@@ -148,7 +165,7 @@ where
     {
         let queue = queue.cheap_clone();
         graph::spawn(async move {
-            let mut backoffs = Backoffs::new();
+            let mut backoffs = Backoffs::new(max_attempts);
             let mut responses = service.call_all(queue_to_stream).unordered().boxed();
             while let Some(response) = responses.next().await {
                 // Note: Be careful not to `await` within this loop, as that could block requests in
@@ -165,13 +182,22 @@ where
                     }
 
                     // Object not found, push the id to the back of the queue.
-                    Ok((id, None)) => {
+                    Ok((id, None)) if max_attempts.is_none() => {
                         debug!(logger, "not found on polling"; "object_id" => id.to_string());
 
                         metrics.not_found.inc();
                         queue.push_back(id);
                     }
 
+                    // Object not found, but the number of attempts is bounded, so back off as
+                    // for errors.
+                    Ok((id, None)) => {
+                        debug!(logger, "not found on polling"; "object_id" => id.to_string());
+
+                        metrics.not_found.inc();
+                        retry_later(&logger, &queue, &mut backoffs, id);
+                    }
+
                     // Error polling, log it and push the id to the back of the queue.
                     Err((id, e)) => {
                         debug!(logger, "error polling";
@@ -182,12 +208,7 @@ where
                         // Requests that return errors could mean there is a permanent issue with
                         // fetching the given item, or could signal the endpoint is overloaded.
                         // Either way a backoff makes sense.
-                        let queue = queue.cheap_clone();
-                        let backoff = backoffs.next_backoff(id.clone());
-                        graph::spawn(async move {
-                            backoff.await;
-                            queue.push_back(id);
-                        });
+                        retry_later(&logger, &queue, &mut backoffs, id);
                     }
                 }
             }
@@ -197,6 +218,28 @@ where
     PollingMonitor { queue }
 }
 
+/// Push `id` to the back of the queue after a backoff, unless we have tried
+/// to poll it too often
+fn retry_later<ID>(logger: &Logger, queue: &Arc<Queue<ID>>, backoffs: &mut Backoffs<ID>, id: ID)
+where
+    ID: Display + Clone + Eq + Send + Hash + 'static,
+{
+    match backoffs.next_backoff(id.clone()) {
+        Some(backoff) => {
+            let queue = queue.cheap_clone();
+            graph::spawn(async move {
+                backoff.await;
+                queue.push_back(id);
+            });
+        }
+        None => {
+            warn!(logger, "giving up on polling after too many attempts";
+                        "object_id" => id.to_string());
+            backoffs.remove(&id);
+        }
+    }
+}
+
 /// Handle for adding objects to be monitored.
 pub struct PollingMonitor<ID> {
     queue: Arc<Queue<ID>>,
@@ -264,6 +307,7 @@ mod tests {
             tx,
             log::discard(),
             Arc::new(PollingMonitorMetrics::mock()),
+            None,
         );
         (handle, monitor, rx)
     }
@@ -275,7 +319,7 @@ mod tests {
         let make_monitor = |svc| {
             let (tx, rx) = mpsc::unbounded_channel();
             let metrics = Arc::new(PollingMonitorMetrics::mock());
-            let monitor = spawn_monitor(svc, tx, log::discard(), metrics);
+            let monitor = spawn_monitor(svc, tx, log::discard(), metrics, None);
             (monitor, rx)
         };
 
@@ -341,6 +385,32 @@ mod tests {
         assert_eq!(rx.recv().await, Some(("req-1", "res-1")));
     }
 
+    #[tokio::test]
+    async fn polling_monitor_gives_up() {
+        let (svc, mut handle) = mock::pair();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let monitor = spawn_monitor(
+            svc,
+            tx,
+            log::discard(),
+            Arc::new(PollingMonitorMetrics::mock()),
+            Some(1),
+        );
+
+        // Objects that are not found or fail are not polled again.
+        monitor.monitor("req-0");
+        send_response(&mut handle, None).await;
+        monitor.monitor("req-1");
+        let (req, send) = handle.next_request().await.unwrap();
+        assert_eq!(req, "req-1");
+        send.send_error(anyhow!("e"));
+        monitor.monitor("req-2");
+        let (req, send) = handle.next_request().await.unwrap();
+        assert_eq!(req, "req-2");
+        send.send_response(Some("res-2"));
+        assert_eq!(rx.recv().await, Some(("req-2", "res-2")));
+    }
+
     #[tokio::test]
     async fn polling_monitor_cancelation() {
         // Cancelation on receiver drop, no pending request.
@@ -103,7 +103,7 @@ where
             if let Some(sender) = self.module_cache.get(&module_hash) {
                 sender.clone()
             } else {
-                let sender = T::spawn_mapping(
+                let sender = self.host_builder.spawn_mapping(
                     module_bytes.as_ref(),
                     logger,
                     self.subgraph_id.clone(),
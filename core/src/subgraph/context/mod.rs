@@ -1,7 +1,7 @@
 mod instance;
 
 use crate::polling_monitor::{
-    spawn_monitor, ArweaveService, IpfsService, PollingMonitor, PollingMonitorMetrics,
+    spawn_monitor, ArweaveService, HttpService, IpfsService, PollingMonitor, PollingMonitorMetrics,
 };
 use anyhow::{self, Error};
 use bytes::Bytes;
@@ -14,10 +14,11 @@ use graph::{
     data::subgraph::SubgraphManifest,
     data_source::{
         causality_region::CausalityRegionSeq,
-        offchain::{self, Base64},
+        offchain::{self, Base64, HttpUrl},
         CausalityRegion, DataSource, DataSourceTemplate,
     },
     derive::CheapClone,
+    env::ENV_VARS,
     ipfs::ContentPath,
     prelude::{
         BlockNumber, BlockPtr, BlockState, CancelGuard, CheapClone, DeploymentHash,
@@ -232,6 +233,8 @@ pub struct OffchainMonitor {
     ipfs_monitor_rx: mpsc::UnboundedReceiver<(ContentPath, Bytes)>,
     arweave_monitor: PollingMonitor<Base64>,
     arweave_monitor_rx: mpsc::UnboundedReceiver<(Base64, Bytes)>,
+    http_monitor: PollingMonitor<HttpUrl>,
+    http_monitor_rx: mpsc::UnboundedReceiver<(HttpUrl, Bytes)>,
 }
 
 impl OffchainMonitor {
@@ -241,26 +244,47 @@ impl OffchainMonitor {
         subgraph_hash: &DeploymentHash,
         ipfs_service: IpfsService,
         arweave_service: ArweaveService,
+        http_service: HttpService,
     ) -> Self {
         let metrics = Arc::new(PollingMonitorMetrics::new(registry, subgraph_hash));
         // The channel is unbounded, as it is expected that `fn ready_offchain_events` is called
         // frequently, or at least with the same frequency that requests are sent.
         let (ipfs_monitor_tx, ipfs_monitor_rx) = mpsc::unbounded_channel();
         let (arweave_monitor_tx, arweave_monitor_rx) = mpsc::unbounded_channel();
+        let (http_monitor_tx, http_monitor_rx) = mpsc::unbounded_channel();
 
         let ipfs_monitor = spawn_monitor(
             ipfs_service,
             ipfs_monitor_tx,
             logger.cheap_clone(),
             metrics.cheap_clone(),
+            None,
         );
 
-        let arweave_monitor = spawn_monitor(arweave_service, arweave_monitor_tx, logger, metrics);
+        let arweave_monitor = spawn_monitor(
+            arweave_service,
+            arweave_monitor_tx,
+            logger.cheap_clone(),
+            metrics.cheap_clone(),
+            None,
+        );
+
+        // Unlike IPFS and Arweave content, a URL that is not available might never become
+        // available, so we only try a limited number of times.
+        let http_monitor = spawn_monitor(
+            http_service,
+            http_monitor_tx,
+            logger,
+            metrics,
+            Some(ENV_VARS.mappings.http_file_max_attempts),
+        );
         Self {
             ipfs_monitor,
             ipfs_monitor_rx,
             arweave_monitor,
             arweave_monitor_rx,
+            http_monitor,
+            http_monitor_rx,
         }
     }
 
@@ -268,6 +292,7 @@ impl OffchainMonitor {
         match source {
             offchain::Source::Ipfs(cid_file) => self.ipfs_monitor.monitor(cid_file),
             offchain::Source::Arweave(base64) => self.arweave_monitor.monitor(base64),
+            offchain::Source::Http(url) => self.http_monitor.monitor(url),
         };
         Ok(())
     }
@@ -302,6 +327,19 @@ impl OffchainMonitor {
             }
         }
 
+        loop {
+            match self.http_monitor_rx.try_recv() {
+                Ok((url, data)) => triggers.push(offchain::TriggerData {
+                    source: offchain::Source::Http(url),
+                    data: Arc::new(data),
+                }),
+                Err(TryRecvError::Disconnected) => {
+                    anyhow::bail!("http monitor unexpectedly terminated")
+                }
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
         Ok(triggers)
     }
 }
@@ -1,7 +1,8 @@
-use crate::polling_monitor::{ArweaveService, IpfsService};
+use crate::polling_monitor::{ArweaveService, HttpService, IpfsService};
 use crate::subgraph::context::{IndexingContext, SubgraphKeepAlive};
 use crate::subgraph::inputs::IndexingInputs;
 use crate::subgraph::loader::load_dynamic_data_sources;
+use crate::subgraph::replay::ReadOnlyStore;
 use crate::subgraph::Decoder;
 use std::collections::BTreeSet;
 
@@ -9,13 +10,16 @@ use crate::subgraph::runner::SubgraphRunner;
 use graph::blockchain::block_stream::BlockStreamMetrics;
 use graph::blockchain::{Blockchain, BlockchainKind, DataSource, NodeCapabilities};
 use graph::components::metrics::gas::GasMetrics;
-use graph::components::subgraph::ProofOfIndexingVersion;
+use graph::components::subgraph::{BlockTrace, ProofOfIndexingVersion};
 use graph::data::subgraph::{UnresolvedSubgraphManifest, SPEC_VERSION_0_0_6};
 use graph::data::value::Word;
 use graph::data_source::causality_region::CausalityRegionSeq;
 use graph::env::EnvVars;
 use graph::prelude::{SubgraphInstanceManager as SubgraphInstanceManagerTrait, *};
-use graph::{blockchain::BlockchainMap, components::store::DeploymentLocator};
+use graph::{
+    blockchain::BlockchainMap,
+    components::store::{DeploymentLocator, WritableStore},
+};
 use graph_runtime_wasm::module::ToAscPtr;
 use graph_runtime_wasm::RuntimeHostBuilder;
 use tokio::task;
@@ -33,6 +37,7 @@ pub struct SubgraphInstanceManager<S: SubgraphStore> {
     link_resolver: Arc<dyn LinkResolver>,
     ipfs_service: IpfsService,
     arweave_service: ArweaveService,
+    http_service: HttpService,
     static_filters: bool,
     env_vars: Arc<EnvVars>,
 }
@@ -183,6 +188,7 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
         link_resolver: Arc<dyn LinkResolver>,
         ipfs_service: IpfsService,
         arweave_service: ArweaveService,
+        http_service: HttpService,
         static_filters: bool,
     ) -> Self {
         let logger = logger_factory.component_logger("SubgraphInstanceManager", None);
@@ -199,6 +205,43 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             static_filters,
             env_vars,
             arweave_service,
+            http_service,
+        }
+    }
+
+    /// Run the handlers of the deployment `loc` for the blocks `from..=to`
+    /// without writing anything to the store, and return a trace of what
+    /// they did. This is only possible for chains whose triggers can be
+    /// scanned for a range of blocks
+    pub async fn replay_blocks(
+        &self,
+        loc: DeploymentLocator,
+        manifest: serde_yaml::Mapping,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<BlockTrace>, Error> {
+        let logger = self.logger_factory.subgraph_logger(&loc);
+
+        match BlockchainKind::from_manifest(&manifest)? {
+            BlockchainKind::Ethereum => {
+                let runner = self
+                    .build_runner::<graph_chain_ethereum::Chain>(
+                        logger,
+                        self.env_vars.cheap_clone(),
+                        loc,
+                        manifest,
+                        None,
+                        Box::new(SubgraphTriggerProcessor {}),
+                        true,
+                    )
+                    .await?;
+
+                runner.replay(from, to).await
+            }
+            kind => Err(anyhow!(
+                "replaying blocks is not supported for {} subgraphs",
+                kind
+            )),
         }
     }
 
@@ -211,6 +254,30 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
         stop_block: Option<BlockNumber>,
         tp: Box<dyn TriggerProcessor<C, RuntimeHostBuilder<C>>>,
     ) -> anyhow::Result<SubgraphRunner<C, RuntimeHostBuilder<C>>>
+    where
+        C: Blockchain,
+        <C as Blockchain>::MappingTrigger: ToAscPtr,
+    {
+        self.build_runner(
+            logger, env_vars, deployment, manifest, stop_block, tp, false,
+        )
+        .await
+    }
+
+    /// Build a runner for `deployment`. A `read_only` runner does not
+    /// change anything about the deployment, neither while it is set up
+    /// nor when it processes blocks; it can only be used to replay blocks
+    #[allow(clippy::too_many_arguments)]
+    async fn build_runner<C>(
+        &self,
+        logger: Logger,
+        env_vars: Arc<EnvVars>,
+        deployment: DeploymentLocator,
+        manifest: serde_yaml::Mapping,
+        stop_block: Option<BlockNumber>,
+        tp: Box<dyn TriggerProcessor<C, RuntimeHostBuilder<C>>>,
+        read_only: bool,
+    ) -> anyhow::Result<SubgraphRunner<C, RuntimeHostBuilder<C>>>
     where
         C: Blockchain,
         <C as Blockchain>::MappingTrigger: ToAscPtr,
@@ -225,10 +292,12 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
         let link_resolver = Arc::from(self.link_resolver.with_retries());
 
         // Make sure the `raw_yaml` is present on both this subgraph and the graft base.
-        self.subgraph_store
-            .set_manifest_raw_yaml(&deployment.hash, raw_yaml)
-            .await?;
-        if let Some(graft) = &manifest.graft {
+        if !read_only {
+            self.subgraph_store
+                .set_manifest_raw_yaml(&deployment.hash, raw_yaml)
+                .await?;
+        }
+        if let Some(graft) = manifest.graft.as_ref().filter(|_| !read_only) {
             if self.subgraph_store.is_deployed(&graft.base)? {
                 let file_bytes = self
                     .link_resolver
@@ -279,17 +348,22 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             )
             .await?;
 
-        // Create deployment features from the manifest
-        // Write it to the database
-        let deployment_features = manifest.deployment_features();
-        self.subgraph_store
-            .create_subgraph_features(deployment_features)?;
-
-        // Start the subgraph deployment before reading dynamic data
-        // sources; if the subgraph is a graft or a copy, starting it will
-        // do the copying and dynamic data sources won't show up until after
-        // that is done
-        store.start_subgraph_deployment(&logger).await?;
+        let store: Arc<dyn WritableStore> = if read_only {
+            Arc::new(ReadOnlyStore::new(store))
+        } else {
+            // Create deployment features from the manifest
+            // Write it to the database
+            let deployment_features = manifest.deployment_features();
+            self.subgraph_store
+                .create_subgraph_features(deployment_features)?;
+
+            // Start the subgraph deployment before reading dynamic data
+            // sources; if the subgraph is a graft or a copy, starting it will
+            // do the copying and dynamic data sources won't show up until after
+            // that is done
+            store.start_subgraph_deployment(&logger).await?;
+            store
+        };
 
         let dynamic_data_sources =
             load_dynamic_data_sources(store.clone(), logger.clone(), &manifest)
@@ -384,6 +458,7 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             &manifest.id,
             self.ipfs_service.clone(),
             self.arweave_service.clone(),
+            self.http_service.clone(),
         );
 
         // Initialize deployment_head with current deployment head. Any sort of trouble in
@@ -396,6 +471,7 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             runtime_adapter,
             self.link_resolver.cheap_clone(),
             subgraph_store.ens_lookup(),
+            store.settings(),
         );
 
         let features = manifest.features.clone();
@@ -5,6 +5,7 @@ mod instance_manager;
 mod loader;
 mod provider;
 mod registrar;
+mod replay;
 mod runner;
 mod state;
 mod stream;
@@ -6,7 +6,7 @@ use graph::blockchain::Blockchain;
 use graph::blockchain::BlockchainKind;
 use graph::blockchain::BlockchainMap;
 use graph::components::store::{DeploymentId, DeploymentLocator, SubscriptionManager};
-use graph::components::subgraph::Settings;
+use graph::components::subgraph::{Settings, SettingsTarget};
 use graph::data::subgraph::schema::DeploymentCreate;
 use graph::data::subgraph::Graft;
 use graph::data::value::Word;
@@ -322,10 +322,6 @@ where
             SubgraphRegistrarError::ResolveError(SubgraphManifestResolveError::ResolveError(e))
         })?;
 
-        // Give priority to deployment specific history_blocks value.
-        let history_blocks =
-            history_blocks.or(self.settings.for_name(&name).map(|c| c.history_blocks));
-
         let deployment_locator = match kind {
             BlockchainKind::Arweave => {
                 create_subgraph_version::<graph_chain_arweave::Chain, _>(
@@ -341,6 +337,7 @@ where
                     debug_fork,
                     self.version_switching_mode,
                     &self.resolver,
+                    &self.settings,
                     history_blocks,
                 )
                 .await?
@@ -359,6 +356,7 @@ where
                     debug_fork,
                     self.version_switching_mode,
                     &self.resolver,
+                    &self.settings,
                     history_blocks,
                 )
                 .await?
@@ -377,6 +375,7 @@ where
                     debug_fork,
                     self.version_switching_mode,
                     &self.resolver,
+                    &self.settings,
                     history_blocks,
                 )
                 .await?
@@ -395,6 +394,7 @@ where
                     debug_fork,
                     self.version_switching_mode,
                     &self.resolver,
+                    &self.settings,
                     history_blocks,
                 )
                 .await?
@@ -413,6 +413,7 @@ where
                     debug_fork,
                     self.version_switching_mode,
                     &self.resolver,
+                    &self.settings,
                     history_blocks,
                 )
                 .await?
@@ -431,6 +432,7 @@ where
                     debug_fork,
                     self.version_switching_mode,
                     &self.resolver,
+                    &self.settings,
                     history_blocks,
                 )
                 .await?
@@ -617,6 +619,7 @@ async fn create_subgraph_version<C: Blockchain, S: SubgraphStore>(
     debug_fork: Option<DeploymentHash>,
     version_switching_mode: SubgraphVersionSwitchingMode,
     resolver: &Arc<dyn LinkResolver>,
+    settings: &Settings,
     history_blocks_override: Option<i32>,
 ) -> Result<DeploymentLocator, SubgraphRegistrarError> {
     let raw_string = serde_yaml::to_string(&raw).unwrap();
@@ -710,6 +713,17 @@ async fn create_subgraph_version<C: Blockchain, S: SubgraphStore>(
         .cloned()
         .collect();
 
+    // Give priority to deployment specific history_blocks value.
+    let history_blocks = history_blocks_override.or_else(|| {
+        let target = SettingsTarget {
+            names: std::slice::from_ref(&name),
+            deployment: Some(&deployment),
+            network: Some(network_name.as_str()),
+            shard: None,
+        };
+        settings.for_target(&target).history_blocks
+    });
+
     // Apply the subgraph versioning and deployment operations,
     // creating a new subgraph deployment if one doesn't exist.
     let mut deployment = DeploymentCreate::new(raw_string, &manifest, start_block)
@@ -717,7 +731,7 @@ async fn create_subgraph_version<C: Blockchain, S: SubgraphStore>(
         .debug(debug_fork)
         .entities_with_causality_region(needs_causality_region);
 
-    if let Some(history_blocks) = history_blocks_override {
+    if let Some(history_blocks) = history_blocks {
         deployment = deployment.with_history_blocks_override(history_blocks);
     }
 
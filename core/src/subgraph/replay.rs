@@ -0,0 +1,279 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+use graph::blockchain::block_stream::FirehoseCursor;
+use graph::blockchain::BlockTime;
+use graph::components::store::{
+    DeploymentCursorTracker, DerivedEntityQuery, ReadStore, StoredDynamicDataSource, WritableStore,
+};
+use graph::components::subgraph::{BlockTrace, EntityChange, HandlerTrace, SubgraphSettings};
+use graph::data::subgraph::schema::{SubgraphError, SubgraphHealth};
+use graph::data_source::CausalityRegion;
+use graph::prelude::{
+    anyhow, async_trait, BlockNumber, BlockPtr, Entity, EntityModification, Logger,
+    StopwatchMetrics, StoreError, UnfailOutcome,
+};
+use graph::schema::{EntityKey, InputSchema};
+
+/// The `WritableStore` that the runner uses when blocks are replayed. Reads
+/// go to the store of the deployment, but all attempts to change the
+/// deployment fail, so that replaying blocks can never modify it
+pub(crate) struct ReadOnlyStore {
+    store: Arc<dyn WritableStore>,
+}
+
+impl ReadOnlyStore {
+    pub fn new(store: Arc<dyn WritableStore>) -> Self {
+        ReadOnlyStore { store }
+    }
+}
+
+fn refuse(what: &str) -> StoreError {
+    StoreError::Unknown(anyhow!("refusing to {} while replaying blocks", what))
+}
+
+impl ReadStore for ReadOnlyStore {
+    fn get(&self, key: &EntityKey) -> Result<Option<Entity>, StoreError> {
+        self.store.get(key)
+    }
+
+    fn get_many(
+        &self,
+        keys: BTreeSet<EntityKey>,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        self.store.get_many(keys)
+    }
+
+    fn get_derived(
+        &self,
+        query: &DerivedEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        self.store.get_derived(query)
+    }
+
+    fn input_schema(&self) -> InputSchema {
+        ReadStore::input_schema(&self.store)
+    }
+}
+
+impl DeploymentCursorTracker for ReadOnlyStore {
+    fn block_ptr(&self) -> Option<BlockPtr> {
+        self.store.block_ptr()
+    }
+
+    fn firehose_cursor(&self) -> FirehoseCursor {
+        self.store.firehose_cursor()
+    }
+
+    fn input_schema(&self) -> InputSchema {
+        DeploymentCursorTracker::input_schema(&self.store)
+    }
+}
+
+#[async_trait]
+impl WritableStore for ReadOnlyStore {
+    async fn start_subgraph_deployment(&self, _: &Logger) -> Result<(), StoreError> {
+        Err(refuse("start the deployment"))
+    }
+
+    async fn revert_block_operations(
+        &self,
+        _: BlockPtr,
+        _: FirehoseCursor,
+    ) -> Result<(), StoreError> {
+        Err(refuse("revert blocks"))
+    }
+
+    async fn unfail_deterministic_error(
+        &self,
+        _: &BlockPtr,
+        _: &BlockPtr,
+    ) -> Result<UnfailOutcome, StoreError> {
+        Err(refuse("unfail the deployment"))
+    }
+
+    fn unfail_non_deterministic_error(&self, _: &BlockPtr) -> Result<UnfailOutcome, StoreError> {
+        Err(refuse("unfail the deployment"))
+    }
+
+    async fn fail_subgraph(&self, _: SubgraphError) -> Result<(), StoreError> {
+        Err(refuse("fail the deployment"))
+    }
+
+    async fn supports_proof_of_indexing(&self) -> Result<bool, StoreError> {
+        self.store.supports_proof_of_indexing().await
+    }
+
+    async fn transact_block_operations(
+        &self,
+        _: BlockPtr,
+        _: BlockTime,
+        _: FirehoseCursor,
+        _: Vec<EntityModification>,
+        _: &StopwatchMetrics,
+        _: Vec<StoredDynamicDataSource>,
+        _: Vec<SubgraphError>,
+        _: Vec<StoredDynamicDataSource>,
+        _: bool,
+        _: bool,
+    ) -> Result<(), StoreError> {
+        Err(refuse("write changes"))
+    }
+
+    fn deployment_synced(&self, _: BlockPtr) -> Result<(), StoreError> {
+        Err(refuse("mark the deployment as synced"))
+    }
+
+    fn is_deployment_synced(&self) -> bool {
+        self.store.is_deployment_synced()
+    }
+
+    fn unassign_subgraph(&self) -> Result<(), StoreError> {
+        Err(refuse("unassign the deployment"))
+    }
+
+    async fn load_dynamic_data_sources(
+        &self,
+        manifest_idx_and_name: Vec<(u32, String)>,
+    ) -> Result<Vec<StoredDynamicDataSource>, StoreError> {
+        self.store
+            .load_dynamic_data_sources(manifest_idx_and_name)
+            .await
+    }
+
+    async fn causality_region_curr_val(&self) -> Result<Option<CausalityRegion>, StoreError> {
+        self.store.causality_region_curr_val().await
+    }
+
+    fn shard(&self) -> &str {
+        self.store.shard()
+    }
+
+    fn settings(&self) -> &SubgraphSettings {
+        self.store.settings()
+    }
+
+    async fn health(&self) -> Result<SubgraphHealth, StoreError> {
+        self.store.health().await
+    }
+
+    async fn flush(&self) -> Result<(), StoreError> {
+        // Nothing is ever written
+        Ok(())
+    }
+
+    async fn entities_at(&self, block: BlockNumber) -> Result<Arc<dyn ReadStore>, StoreError> {
+        self.store.entities_at(block).await
+    }
+
+    async fn restart(self: Arc<Self>) -> Result<Option<Arc<dyn WritableStore>>, StoreError> {
+        Ok(None)
+    }
+}
+
+/// The store that handlers use when blocks are replayed for debugging.
+/// Reads see the entities as they were before the first replayed block
+/// together with the changes of the blocks that were replayed since then;
+/// nothing is ever written to the database.
+pub(crate) struct ReplayStore {
+    base: Arc<dyn ReadStore>,
+    /// The changes of the blocks replayed so far; `None` marks entities
+    /// that were removed
+    changes: Mutex<BTreeMap<EntityKey, Option<Entity>>>,
+    traces: Mutex<Vec<BlockTrace>>,
+}
+
+impl ReplayStore {
+    pub fn new(base: Arc<dyn ReadStore>) -> Self {
+        ReplayStore {
+            base,
+            changes: Mutex::new(BTreeMap::new()),
+            traces: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Apply the modifications `mods` of `block` so that later blocks see
+    /// them, and remember the trace for the block
+    pub fn finish_block(
+        &self,
+        block: &BlockPtr,
+        handlers: Vec<HandlerTrace>,
+        mods: Vec<EntityModification>,
+    ) -> Result<(), StoreError> {
+        let keys = mods.iter().map(|m| m.key().clone()).collect();
+        let mut before = self.get_many(keys)?;
+
+        let mut changes = self.changes.lock().unwrap();
+        let mut entity_changes = Vec::with_capacity(mods.len());
+        for m in mods {
+            let (key, after) = match m {
+                EntityModification::Insert { key, data, .. }
+                | EntityModification::Overwrite { key, data, .. } => (key, Some((*data).clone())),
+                EntityModification::Remove { key, .. } => (key, None),
+            };
+            entity_changes.push(EntityChange::new(
+                &key,
+                before.remove(&key).as_ref(),
+                after.as_ref(),
+            ));
+            changes.insert(key, after);
+        }
+
+        self.traces
+            .lock()
+            .unwrap()
+            .push(BlockTrace::new(block, handlers, entity_changes));
+        Ok(())
+    }
+
+    pub fn take_traces(&self) -> Vec<BlockTrace> {
+        std::mem::take(&mut *self.traces.lock().unwrap())
+    }
+}
+
+impl ReadStore for ReplayStore {
+    fn get(&self, key: &EntityKey) -> Result<Option<Entity>, StoreError> {
+        match self.changes.lock().unwrap().get(key) {
+            Some(entity) => Ok(entity.clone()),
+            None => self.base.get(key),
+        }
+    }
+
+    fn get_many(
+        &self,
+        keys: BTreeSet<EntityKey>,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        let changes = self.changes.lock().unwrap();
+        let (changed, unchanged): (BTreeSet<_>, BTreeSet<_>) =
+            keys.into_iter().partition(|key| changes.contains_key(key));
+
+        let mut entities = self.base.get_many(unchanged)?;
+        for key in changed {
+            if let Some(Some(entity)) = changes.get(&key) {
+                entities.insert(key, entity.clone());
+            }
+        }
+        Ok(entities)
+    }
+
+    fn get_derived(
+        &self,
+        query: &DerivedEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        let changes = self.changes.lock().unwrap();
+        let mut entities = self.base.get_derived(query)?;
+        entities.retain(|key, _| !changes.contains_key(key));
+        for (key, entity) in changes.iter() {
+            if let Some(entity) = entity {
+                if query.matches(key, entity) {
+                    entities.insert(key.clone(), entity.clone());
+                }
+            }
+        }
+        Ok(entities)
+    }
+
+    fn input_schema(&self) -> InputSchema {
+        self.base.input_schema()
+    }
+}
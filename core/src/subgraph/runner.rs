@@ -1,6 +1,7 @@
 use crate::subgraph::context::IndexingContext;
 use crate::subgraph::error::BlockProcessingError;
 use crate::subgraph::inputs::IndexingInputs;
+use crate::subgraph::replay::ReplayStore;
 use crate::subgraph::state::IndexingState;
 use crate::subgraph::stream::new_block_stream;
 use atomic_refcell::AtomicRefCell;
@@ -9,7 +10,7 @@ use graph::blockchain::block_stream::{
 };
 use graph::blockchain::{Block, BlockTime, Blockchain, DataSource as _, TriggerFilter as _};
 use graph::components::store::{EmptyStore, GetScope, ReadStore, StoredDynamicDataSource};
-use graph::components::subgraph::InstanceDSTemplate;
+use graph::components::subgraph::{BlockTrace, InstanceDSTemplate};
 use graph::components::{
     store::ModificationsAndCache,
     subgraph::{MappingError, PoICausalityRegion, ProofOfIndexing, SharedProofOfIndexing},
@@ -77,6 +78,7 @@ where
                 ),
                 entity_lfu_cache: LfuCache::new(),
                 cached_head_ptr: None,
+                replay: None,
             },
             logger,
             metrics,
@@ -170,6 +172,83 @@ where
         self.run_inner(false).await.map(|_| ())
     }
 
+    /// Run the handlers for the blocks `from..=to` against the entities as
+    /// they were at block `from - 1` without writing anything to the store,
+    /// and return a trace of what they did for every block with triggers.
+    /// The blocks must already have been indexed.
+    pub async fn replay(
+        mut self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<BlockTrace>, Error> {
+        if from > to {
+            return Err(anyhow!(
+                "the first block {} is after the last block {}",
+                from,
+                to
+            ));
+        }
+        match self.inputs.store.block_ptr() {
+            Some(ptr) if ptr.number >= to => {}
+            _ => {
+                return Err(anyhow!(
+                    "deployment {} has not indexed block {} yet",
+                    self.inputs.deployment,
+                    to
+                ))
+            }
+        }
+
+        // This fails if the deployment has been pruned past `from - 1`
+        let base = self.inputs.store.entities_at(from - 1).await?;
+
+        // Forget about data sources that were created by the blocks we
+        // are about to replay
+        self.revert_state_to(from - 1)?;
+        let replay = Arc::new(ReplayStore::new(base));
+        self.state.replay = Some(replay.cheap_clone());
+
+        let canceler = CancelGuard::new();
+        let mut next = from;
+        while next <= to {
+            let filter = self.build_filter();
+            let (blocks, scanned_to) = self
+                .inputs
+                .triggers_adapter
+                .scan_triggers(next, to, &filter)
+                .await?;
+            next = scanned_to + 1;
+
+            for block in blocks {
+                let block_number = block.block.number();
+                debug!(self.logger, "Replaying block"; "block_number" => block_number);
+
+                let action = self
+                    .process_block(&canceler.handle(), block, FirehoseCursor::None)
+                    .await
+                    .map_err(|e| anyhow!("failed to replay block {}: {}", block_number, e))?;
+                if let Action::Restart = action {
+                    // New data sources were created; scan the following
+                    // blocks with a filter that includes them
+                    next = block_number + 1;
+                    break;
+                }
+            }
+        }
+
+        Ok(replay.take_traces())
+    }
+
+    /// A new block state for processing a block; when we are replaying
+    /// blocks, handlers read from the replay store and are traced
+    fn new_block_state(&mut self) -> BlockState {
+        let lfu_cache = std::mem::take(&mut self.state.entity_lfu_cache);
+        match &self.state.replay {
+            Some(replay) => BlockState::new(replay.cheap_clone(), lfu_cache).with_trace(),
+            None => BlockState::new(self.inputs.store.clone(), lfu_cache),
+        }
+    }
+
     async fn run_inner(mut self, break_on_restart: bool) -> Result<Self, Error> {
         // If a subgraph failed for deterministic reasons, before start indexing, we first
         // revert the deployment head. It should lead to the same result since the error was
@@ -292,7 +371,10 @@ where
         debug!(logger, "Start processing block";
                "triggers" => triggers.len());
 
-        let proof_of_indexing = if self.inputs.store.supports_proof_of_indexing().await? {
+        // The PoI is not needed when we only replay blocks
+        let proof_of_indexing = if self.state.replay.is_none()
+            && self.inputs.store.supports_proof_of_indexing().await?
+        {
             Some(Arc::new(AtomicRefCell::new(ProofOfIndexing::new(
                 block_ptr.number,
                 self.inputs.poi_version,
@@ -304,10 +386,7 @@ where
         // Causality region for onchain triggers.
         let causality_region = PoICausalityRegion::from_network(&self.inputs.network);
 
-        let mut block_state = BlockState::new(
-            self.inputs.store.clone(),
-            std::mem::take(&mut self.state.entity_lfu_cache),
-        );
+        let mut block_state = self.new_block_state();
 
         let _section = self
             .metrics
@@ -571,7 +650,10 @@ where
             evict_stats,
         } = block_state
             .entity_cache
-            .as_modifications(block.number())
+            .as_modifications_with_cache_size(
+                block.number(),
+                self.inputs.store.settings().entity_cache_size(),
+            )
             .map_err(|e| BlockProcessingError::Unknown(e.into()))?;
         section.end();
 
@@ -585,6 +667,20 @@ where
             "accesses" => evict_stats.accesses,
             "evict_time_ms" => evict_stats.evict_time.as_millis());
 
+        // Put the cache back in the state, asserting that the placeholder cache was not used.
+        assert!(self.state.entity_lfu_cache.is_empty());
+        self.state.entity_lfu_cache = cache;
+
+        // When replaying, offchain events are ignored since they are not
+        // tied to the block, and nothing is written
+        if let Some(replay) = &self.state.replay {
+            replay.finish_block(&block_ptr, block_state.take_trace(), mods)?;
+            return match needs_restart {
+                true => Ok(Action::Restart),
+                false => Ok(Action::Continue),
+            };
+        }
+
         // Check for offchain events and process them, including their entity modifications in the
         // set to be transacted.
         let offchain_events = self.ctx.offchain_monitor.ready_offchain_events()?;
@@ -593,10 +689,6 @@ where
                 .await?;
         mods.extend(offchain_mods);
 
-        // Put the cache back in the state, asserting that the placeholder cache was not used.
-        assert!(self.state.entity_lfu_cache.is_empty());
-        self.state.entity_lfu_cache = cache;
-
         if !mods.is_empty() {
             info!(&logger, "Applying {} entity operation(s)", mods.len());
         }
@@ -708,10 +800,7 @@ where
         handler: String,
         causality_region: &str,
     ) -> Result<BlockState, MappingError> {
-        let block_state = BlockState::new(
-            self.inputs.store.clone(),
-            std::mem::take(&mut self.state.entity_lfu_cache),
-        );
+        let block_state = self.new_block_state();
 
         self.ctx
             .process_block(
@@ -1064,6 +1153,22 @@ where
             let proof_of_indexing = None;
             let causality_region = "";
 
+            // Content fetched over HTTP is not content-addressed, so the hash of what was
+            // actually processed is stored with the data source to make it possible to compare
+            // results between indexers.
+            let content_hash = match &trigger.source {
+                offchain::Source::Http(url) => {
+                    let content_hash = Bytes::from(tiny_keccak::keccak256(&trigger.data[..]));
+                    info!(self.logger, "Processing HTTP file data source";
+                        "url" => url.as_str(),
+                        "content_hash" => content_hash.to_string(),
+                        "size" => trigger.data.len(),
+                    );
+                    Some(content_hash)
+                }
+                offchain::Source::Ipfs(_) | offchain::Source::Arweave(_) => None,
+            };
+
             let trigger = TriggerData::Offchain(trigger);
             let process_res = {
                 let hosts = self.ctx.instance.hosts_for_trigger(&trigger);
@@ -1106,6 +1211,12 @@ where
                 }
             }
 
+            // The block state is fresh for each trigger, so all data sources it processed were
+            // processed with the data of this trigger
+            for ds in &mut block_state.processed_data_sources {
+                ds.content_hash = content_hash.clone();
+            }
+
             anyhow::ensure!(
                 !block_state.has_created_on_chain_data_sources(),
                 "Attempted to create on-chain data source in offchain data source handler. This is not yet supported.",
@@ -1212,7 +1323,10 @@ where
             .deployment_head
             .set(block_ptr.number as f64);
 
-        let proof_of_indexing = if self.inputs.store.supports_proof_of_indexing().await? {
+        // The PoI is not needed when we only replay blocks
+        let proof_of_indexing = if self.state.replay.is_none()
+            && self.inputs.store.supports_proof_of_indexing().await?
+        {
             Some(Arc::new(AtomicRefCell::new(ProofOfIndexing::new(
                 block_ptr.number,
                 self.inputs.poi_version,
@@ -1296,7 +1410,10 @@ where
             evict_stats,
         } = block_state
             .entity_cache
-            .as_modifications(block_ptr.number)
+            .as_modifications_with_cache_size(
+                block_ptr.number,
+                self.inputs.store.settings().entity_cache_size(),
+            )
             .map_err(|e| BlockProcessingError::Unknown(e.into()))?;
         section.end();
 
@@ -1,8 +1,11 @@
 use graph::{
     components::store::EntityLfuCache, prelude::BlockPtr, util::backoff::ExponentialBackoff,
 };
+use std::sync::Arc;
 use std::time::Instant;
 
+use super::replay::ReplayStore;
+
 pub struct IndexingState {
     /// `true` -> `false` on the first run
     pub should_try_unfail_non_deterministic: bool,
@@ -16,4 +19,7 @@ pub struct IndexingState {
     pub skip_ptr_updates_timer: Instant,
     pub entity_lfu_cache: EntityLfuCache,
     pub cached_head_ptr: Option<BlockPtr>,
+    /// Set when blocks are replayed for debugging; handlers then read from
+    /// it and changes are not written to the store
+    pub(crate) replay: Option<Arc<ReplayStore>>,
 }
@@ -3,7 +3,7 @@ use crate::{
     components::{
         link_resolver::LinkResolver,
         store::{BlockNumber, DeploymentCursorTracker, DeploymentLocator},
-        subgraph::InstanceDSTemplateInfo,
+        subgraph::{HostMetrics, InstanceDSTemplateInfo},
     },
     data::subgraph::UnifiedMappingApiVersion,
     prelude::{BlockHash, DataSourceTemplateInfo},
@@ -126,6 +126,7 @@ impl<C: Blockchain> DataSource<C> for MockDataSource {
         _trigger: &C::TriggerData,
         _block: &std::sync::Arc<C::Block>,
         _logger: &slog::Logger,
+        _metrics: &HostMetrics,
     ) -> Result<Option<TriggerWithHandler<C>>, anyhow::Error> {
         todo!()
     }
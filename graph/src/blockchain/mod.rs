@@ -305,11 +305,15 @@ pub trait DataSource<C: Blockchain>: 'static + Sized + Send + Sync + Clone {
     /// This is typicaly reduced by the triggers being pre-filtered in the block stream. But with
     /// dynamic data sources the block stream does not filter on the dynamic parameters, so the
     /// matching should efficently discard false positives.
+    ///
+    /// Triggers that match a handler but are rejected by a filter on that handler should be
+    /// counted in `metrics`.
     fn match_and_decode(
         &self,
         trigger: &C::TriggerData,
         block: &Arc<C::Block>,
         logger: &Logger,
+        metrics: &HostMetrics,
     ) -> Result<Option<TriggerWithHandler<C>>, Error>;
 
     fn is_duplicate_of(&self, other: &Self) -> bool;
@@ -7,4 +7,7 @@ pub mod subscription;
 /// Component for the index node server.
 pub mod index_node;
 
+/// Types for persisted queries that the GraphQL servers share.
+pub mod persisted;
+
 pub mod server;
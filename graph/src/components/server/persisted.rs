@@ -0,0 +1,37 @@
+//! Types shared by the GraphQL servers for persisted queries. The HTTP server
+//! keeps track of persisted queries and their allowlist; the WebSocket server
+//! only needs to check queries against the allowlist.
+
+#[derive(Debug, PartialEq)]
+pub enum PersistedQueryError {
+    /// The hash is not known; the client should resend it with the query
+    NotFound,
+    /// The query is not in the allowlist
+    NotAllowed,
+    /// The hash that was sent is not the hash of the query
+    HashMismatch,
+    /// The `persistedQuery` extension is malformed
+    Invalid(String),
+}
+
+impl PersistedQueryError {
+    pub fn message(&self) -> String {
+        match self {
+            PersistedQueryError::NotFound => "PersistedQueryNotFound".to_string(),
+            PersistedQueryError::NotAllowed => {
+                "The query is not in the allowlist of persisted queries".to_string()
+            }
+            PersistedQueryError::HashMismatch => {
+                "The provided sha256Hash does not match the query".to_string()
+            }
+            PersistedQueryError::Invalid(msg) => msg.clone(),
+        }
+    }
+}
+
+/// Decides whether a query that is sent in full, without a hash, may be run
+pub trait QueryAllowlist: Send + Sync {
+    /// Check that we may run `query`. That is always the case unless there
+    /// is an allowlist
+    fn check(&self, query: &str) -> Result<(), PersistedQueryError>;
+}
@@ -3,8 +3,8 @@ use std::net::SocketAddr;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
-use hyper::body::Incoming;
-use hyper::Request;
+use hyper::body::{Body, Incoming};
+use hyper::{Request, Response};
 
 use crate::cheap_clone::CheapClone;
 use crate::hyper::server::conn::http1;
@@ -17,7 +17,7 @@ use crate::{anyhow, tokio};
 
 use crate::prelude::Logger;
 
-use super::query::ServerResult;
+use super::query::ServerError;
 
 /// A handle to the server that can be used to shut it down. The `accepting`
 /// field is only used in tests to check if the server is running
@@ -26,14 +26,22 @@ pub struct ServerHandle {
     pub accepting: Arc<AtomicBool>,
 }
 
-pub async fn start<F, S>(
+/// The address of the client that sent a request. It is available as an
+/// extension on all requests that `start` passes to its handler
+#[derive(Clone, Copy, Debug)]
+pub struct RemoteAddr(pub SocketAddr);
+
+pub async fn start<F, S, B>(
     logger: Logger,
     port: u16,
     handler: F,
 ) -> Result<ServerHandle, anyhow::Error>
 where
     F: Fn(Request<Incoming>) -> S + Send + Clone + 'static,
-    S: Future<Output = ServerResult> + Send + 'static,
+    S: Future<Output = Result<Response<B>, ServerError>> + Send + 'static,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     let listener = TcpListener::bind(addr).await?;
@@ -42,7 +50,7 @@ where
     let handle = crate::spawn(async move {
         accepting2.store(true, std::sync::atomic::Ordering::SeqCst);
         loop {
-            let (stream, _) = match listener.accept().await {
+            let (stream, remote_addr) = match listener.accept().await {
                 Ok(res) => res,
                 Err(e) => {
                     error!(logger, "Error accepting connection"; "error" => e.to_string());
@@ -57,7 +65,10 @@ where
             let handler = handler.clone();
             // Spawn a tokio task to serve multiple connections concurrently
             tokio::task::spawn(async move {
-                let new_service = service_fn(handler);
+                let new_service = service_fn(move |mut req: Request<Incoming>| {
+                    req.extensions_mut().insert(RemoteAddr(remote_addr));
+                    handler(req)
+                });
                 // Finally, we bind the incoming connection to our `hello` service
                 http1::Builder::new()
                     // `service_fn` converts our function in a `Service`
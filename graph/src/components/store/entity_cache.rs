@@ -422,10 +422,18 @@ impl EntityCache {
     /// `EntityModification`, making sure to only produce one when a change
     /// to the current state is actually needed.
     ///
-    /// Also returns the updated `LfuCache`.
-    pub fn as_modifications(
+    /// Also returns the updated `LfuCache`, evicted down to the size set
+    /// with `GRAPH_ENTITY_CACHE_SIZE`
+    pub fn as_modifications(self, block: BlockNumber) -> Result<ModificationsAndCache, StoreError> {
+        self.as_modifications_with_cache_size(block, ENV_VARS.mappings.entity_cache_size)
+    }
+
+    /// Like `as_modifications`, but evict the returned `LfuCache` down to
+    /// `cache_size` bytes
+    pub fn as_modifications_with_cache_size(
         mut self,
         block: BlockNumber,
+        cache_size: usize,
     ) -> Result<ModificationsAndCache, StoreError> {
         assert!(!self.in_handler);
 
@@ -513,9 +521,7 @@ impl EntityCache {
                 mods.push(modification)
             }
         }
-        let evict_stats = self
-            .current
-            .evict_and_stats(ENV_VARS.mappings.entity_cache_size);
+        let evict_stats = self.current.evict_and_stats(cache_size);
 
         Ok(ModificationsAndCache {
             modifications: mods,
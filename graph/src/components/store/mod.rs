@@ -134,6 +134,15 @@ pub enum EntityFilter {
     ChangeBlockGte(BlockNumber),
     Child(Child),
     Fulltext(Attribute, Value),
+    /// Fuzzy fulltext search; the word similarity between the value and
+    /// the fulltext attribute must be at least the given threshold
+    FuzzyFulltext(Attribute, Value, f64),
+    /// The value at the given path inside a JSON attribute is equal to the
+    /// given value
+    JsonPathEqual(Attribute, Vec<String>, Value),
+    /// The value at the given path inside a JSON attribute contains the
+    /// given value
+    JsonPathContains(Attribute, Vec<String>, Value),
 }
 
 // A somewhat concise string representation of a filter
@@ -173,6 +182,9 @@ impl fmt::Display for EntityFilter {
             NotEndsWith(a, v) => write!(f, "{a} !~ *{v}$"),
             NotEndsWithNoCase(a, v) => write!(f, "{a} !~ *{v}$i"),
             ChangeBlockGte(b) => write!(f, "block >= {b}"),
+            FuzzyFulltext(a, v, t) => write!(f, "{a} % {v} >= {t}"),
+            JsonPathEqual(a, p, v) => write!(f, "{a}#>{{{}}} = {v}", p.join(",")),
+            JsonPathContains(a, p, v) => write!(f, "{a}#>{{{}}} @> {v}", p.join(",")),
             Child(child /* a, et, cf, _ */) => write!(
                 f,
                 "join on {} with {}({})",
@@ -242,6 +254,14 @@ pub enum EntityOrderByChild {
     Interface(EntityOrderByChildInfo, Vec<EntityType>),
 }
 
+/// The direction in which to sort by one attribute of an
+/// `EntityOrder::Multiple`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrderDirection {
+    Ascending,
+    Descending,
+}
+
 /// The order in which entities should be restored from a store.
 #[derive(Clone, Debug, PartialEq)]
 pub enum EntityOrder {
@@ -253,6 +273,9 @@ pub enum EntityOrder {
     ChildAscending(EntityOrderByChild),
     /// Order descending by the given attribute of a child entity. Use `id` as a tie-breaker
     ChildDescending(EntityOrderByChild),
+    /// Order by several attributes in turn, each in its own direction. Use
+    /// `id` as a tie-breaker in the direction of the last attribute
+    Multiple(Vec<(String, ValueType, OrderDirection)>),
     /// Order by the `id` of the entities
     Default,
     /// Do not order at all. This speeds up queries where we know that
@@ -293,6 +316,22 @@ impl std::default::Default for EntityRange {
     }
 }
 
+/// The position of an entity in a collection, used for keyset pagination.
+/// A query with a cursor only returns entities that sort strictly after the
+/// entity at the cursor according to the query's `order`
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntityCursor {
+    /// The values of the attributes the collection is ordered by, in the
+    /// order in which they are used for sorting. Attributes from `id` on
+    /// do not have a value since `id` is unique; the list is therefore
+    /// empty when the collection is ordered by `id`. A value is
+    /// `Value::Null` when the entity at the cursor has no value for the
+    /// attribute
+    pub values: Vec<Value>,
+    /// The id of the entity at the cursor
+    pub id: Value,
+}
+
 /// The attribute we want to window by in an `EntityWindow`. We have to
 /// distinguish between scalar and list attributes since we need to use
 /// different queries for them, and the JSONB storage scheme can not
@@ -453,6 +492,9 @@ pub struct EntityQuery {
     /// A range to limit the size of the result.
     pub range: EntityRange,
 
+    /// Only return entities that come after this cursor in `order`
+    pub after: Option<EntityCursor>,
+
     /// Optional logger for anything related to this query
     pub logger: Option<Logger>,
 
@@ -476,6 +518,7 @@ impl EntityQuery {
             filter: None,
             order: EntityOrder::Default,
             range: EntityRange::default(),
+            after: None,
             logger: None,
             query_id: None,
             trace: false,
@@ -508,6 +551,11 @@ impl EntityQuery {
         self
     }
 
+    pub fn after(mut self, cursor: EntityCursor) -> Self {
+        self.after = Some(cursor);
+        self
+    }
+
     pub fn simplify(mut self) -> Self {
         // If there is one window, with one id, in a direct relation to the
         // entities, we can simplify the query by changing the filter and
@@ -539,6 +587,31 @@ impl EntityQuery {
     }
 }
 
+/// An aggregate computed over all the entities that match an
+/// `EntityQuery`. Except for `Count`, the attribute must be numeric
+#[derive(Clone, Debug, PartialEq)]
+pub enum EntityAggregate {
+    Count,
+    Sum(Attribute),
+    Min(Attribute),
+    Max(Attribute),
+    Avg(Attribute),
+}
+
+impl fmt::Display for EntityAggregate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use EntityAggregate::*;
+
+        match self {
+            Count => write!(f, "count(*)"),
+            Sum(a) => write!(f, "sum({a})"),
+            Min(a) => write!(f, "min({a})"),
+            Max(a) => write!(f, "max({a})"),
+            Avg(a) => write!(f, "avg({a})"),
+        }
+    }
+}
+
 /// Operation types that lead to entity changes.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
@@ -835,6 +908,44 @@ pub enum EntityOperation {
     Remove { key: EntityKey },
 }
 
+/// How an entity differs between two blocks. `old` is the entity at the
+/// earlier block and `new` the one at the later block; `old` is `None` for
+/// entities that were created and `new` is `None` for entities that were
+/// deleted between the two blocks
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntityDiff {
+    pub key: EntityKey,
+    pub old: Option<Entity>,
+    pub new: Option<Entity>,
+}
+
+impl EntityDiff {
+    pub fn kind(&self) -> EntityDiffKind {
+        match (&self.old, &self.new) {
+            (None, _) => EntityDiffKind::Created,
+            (Some(_), Some(_)) => EntityDiffKind::Updated,
+            (Some(_), None) => EntityDiffKind::Deleted,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityDiffKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl EntityDiffKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityDiffKind::Created => "created",
+            EntityDiffKind::Updated => "updated",
+            EntityDiffKind::Deleted => "deleted",
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum UnfailOutcome {
     Noop,
@@ -849,6 +960,10 @@ pub struct StoredDynamicDataSource {
     pub creation_block: Option<BlockNumber>,
     pub done_at: Option<i32>,
     pub causality_region: CausalityRegion,
+    /// The keccak256 hash of the content that a processed `file/http` data
+    /// source was processed with. Unlike the content of other file data
+    /// sources, it is not determined by the source
+    pub content_hash: Option<Bytes>,
 }
 
 /// An internal identifer for the specific instance of a deployment. The
@@ -9,7 +9,7 @@ use crate::blockchain::block_stream::FirehoseCursor;
 use crate::blockchain::{BlockTime, ChainIdentifier};
 use crate::components::metrics::stopwatch::StopwatchMetrics;
 use crate::components::server::index_node::VersionInfo;
-use crate::components::subgraph::SubgraphVersionSwitchingMode;
+use crate::components::subgraph::{SubgraphSettings, SubgraphVersionSwitchingMode};
 use crate::components::transaction_receipt;
 use crate::components::versions::ApiVersion;
 use crate::data::query::Trace;
@@ -144,6 +144,22 @@ pub trait SubgraphStore: Send + Sync + 'static {
         block_number: BlockNumber,
     ) -> Result<Vec<EntityOperation>, StoreError>;
 
+    /// Returns how the entities at block `to` differ from the entities at
+    /// block `from`. Only entities whose type is in `entity_types` are
+    /// considered, unless `entity_types` is empty. The diffs are sorted by
+    /// entity type and id, and `first` and `skip` select a page of them.
+    /// Fails if `from` or `to` is before the earliest block of the
+    /// deployment or after its head
+    fn entity_diff(
+        &self,
+        subgraph_id: &DeploymentHash,
+        from: BlockNumber,
+        to: BlockNumber,
+        entity_types: &[String],
+        first: usize,
+        skip: usize,
+    ) -> Result<Vec<EntityDiff>, StoreError>;
+
     /// Return the GraphQL schema supplied by the user
     fn input_schema(&self, subgraph_id: &DeploymentHash) -> Result<InputSchema, StoreError>;
 
@@ -371,11 +387,20 @@ pub trait WritableStore: ReadStore + DeploymentCursorTracker {
     /// should only be used for reporting and monitoring
     fn shard(&self) -> &str;
 
+    /// The subgraph-specific settings that apply to this deployment
+    fn settings(&self) -> &SubgraphSettings;
+
     async fn health(&self) -> Result<SubgraphHealth, StoreError>;
 
     /// Wait for the background writer to finish processing its queue
     async fn flush(&self) -> Result<(), StoreError>;
 
+    /// A read-only view of the entities of this deployment as they were
+    /// at `block`. Changes that are still waiting to be written are not
+    /// visible in it. Fails if `block` is before the earliest block for
+    /// which the deployment still has data
+    async fn entities_at(&self, block: BlockNumber) -> Result<Arc<dyn ReadStore>, StoreError>;
+
     /// Restart the `WritableStore`. This will clear any errors that have
     /// been encountered. Code that calls this must not make any assumptions
     /// about what has been written already, as the write queue might
@@ -531,6 +556,18 @@ pub trait ChainStore: Send + Sync + 'static {
         hashes: Vec<BlockHash>,
     ) -> Result<HashMap<BlockHash, BlockNumber>, StoreError>;
 
+    /// Find the block with the highest number between `earliest` and
+    /// `head` (inclusive) whose timestamp, in seconds since the epoch, is
+    /// at or before `timestamp`. Only blocks that are in the block cache,
+    /// have a timestamp and are on the chain that ends in `head` are
+    /// considered.
+    async fn block_for_timestamp(
+        &self,
+        timestamp: u64,
+        earliest: BlockNumber,
+        head: BlockPtr,
+    ) -> Result<Option<BlockPtr>, StoreError>;
+
     /// Tries to retrieve all transactions receipts for a given block.
     async fn transaction_receipts_in_block(
         &self,
@@ -593,6 +630,15 @@ pub trait QueryStore: Send + Sync {
         query: EntityQuery,
     ) -> Result<(Vec<QueryObject>, Trace), QueryExecutionError>;
 
+    /// Compute `aggregates` over all entities that match `query`, ignoring
+    /// its order and range. The result has one value for each entry in
+    /// `aggregates`, in the same order
+    fn aggregate_query_values(
+        &self,
+        query: EntityQuery,
+        aggregates: Vec<EntityAggregate>,
+    ) -> Result<(Vec<Value>, Trace), QueryExecutionError>;
+
     async fn is_deployment_synced(&self) -> Result<bool, Error>;
 
     async fn block_ptr(&self) -> Result<Option<BlockPtr>, StoreError>;
@@ -613,6 +659,16 @@ pub trait QueryStore: Send + Sync {
         block_hash: &BlockHash,
     ) -> Result<Option<(BlockNumber, Option<u64>, Option<BlockHash>)>, StoreError>;
 
+    /// Find the latest block between `earliest` and `head` (inclusive) on
+    /// the chain that ends in `head` whose timestamp, in seconds since the
+    /// epoch, is at or before `timestamp`
+    async fn block_for_timestamp(
+        &self,
+        timestamp: u64,
+        earliest: BlockNumber,
+        head: BlockPtr,
+    ) -> Result<Option<BlockPtr>, StoreError>;
+
     fn wait_stats(&self) -> Result<PoolWaitStats, StoreError>;
 
     /// Find the current state for the subgraph deployment `id` and
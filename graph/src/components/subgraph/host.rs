@@ -114,6 +114,7 @@ pub struct HostMetrics {
     handler_execution_time: Box<HistogramVec>,
     host_fn_execution_time: Box<HistogramVec>,
     eth_call_execution_time: Box<HistogramVec>,
+    filtered_trigger_count: Box<CounterVec>,
     pub gas_metrics: GasMetrics,
     pub stopwatch: StopwatchMetrics,
 }
@@ -153,15 +154,43 @@ impl HostMetrics {
                 vec![0.025, 0.05, 0.2, 2.0, 8.0, 20.0],
             )
             .expect("failed to create `deployment_host_fn_execution_time` histogram");
+
+        let filtered_trigger_count = registry
+            .new_deployment_counter_vec(
+                "deployment_filtered_trigger_count",
+                "Counts the triggers that matched a handler but were skipped by its filter",
+                subgraph,
+                vec![String::from("handler")],
+            )
+            .expect("failed to create `deployment_filtered_trigger_count` counter");
         Self {
             handler_execution_time,
             host_fn_execution_time,
             stopwatch,
             gas_metrics,
             eth_call_execution_time,
+            filtered_trigger_count,
         }
     }
 
+    pub fn mock() -> Self {
+        let registry = Arc::new(MetricsRegistry::mock());
+        let stopwatch = StopwatchMetrics::new(
+            Logger::root(slog::Discard, o!()),
+            DeploymentHash::default(),
+            "test",
+            registry.cheap_clone(),
+            "test_shard".to_string(),
+        );
+
+        Self::new(
+            registry,
+            DeploymentHash::default().as_str(),
+            stopwatch,
+            GasMetrics::mock(),
+        )
+    }
+
     pub fn observe_handler_execution_time(&self, duration: f64, handler: &str) {
         self.handler_execution_time
             .with_label_values(&[handler][..])
@@ -185,6 +214,12 @@ impl HostMetrics {
             .observe(duration);
     }
 
+    pub fn inc_filtered_trigger_count(&self, handler: &str) {
+        self.filtered_trigger_count
+            .with_label_values(&[handler][..])
+            .inc();
+    }
+
     pub fn time_host_fn_execution_region(
         self: Arc<HostMetrics>,
         fn_name: &'static str,
@@ -230,6 +265,7 @@ pub trait RuntimeHostBuilder<C: Blockchain>: Clone + Send + Sync + 'static {
     /// Spawn a mapping and return a channel for mapping requests. The sender should be able to be
     /// cached and shared among mappings that use the same wasm file.
     fn spawn_mapping(
+        &self,
         raw_module: &[u8],
         logger: Logger,
         subgraph_id: DeploymentHash,
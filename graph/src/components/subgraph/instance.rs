@@ -1,5 +1,6 @@
 use crate::{
     blockchain::{Blockchain, DataSourceTemplate as _},
+    components::subgraph::{HandlerTrace, TraceEvent},
     components::{
         metrics::block_state::BlockStateMetrics,
         store::{EntityLfuCache, ReadStore, StoredDynamicDataSource},
@@ -81,6 +82,10 @@ pub struct BlockState {
     in_handler: bool,
 
     pub metrics: BlockStateMetrics,
+
+    // The handlers that ran and what they did; only collected when
+    // blocks are replayed for debugging.
+    trace: Option<Vec<HandlerTrace>>,
 }
 
 impl BlockState {
@@ -94,8 +99,15 @@ impl BlockState {
             processed_data_sources: Vec::new(),
             in_handler: false,
             metrics: BlockStateMetrics::new(),
+            trace: None,
         }
     }
+
+    /// Record what each handler does in this block state
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
 }
 
 impl BlockState {
@@ -111,6 +123,7 @@ impl BlockState {
             processed_data_sources,
             in_handler,
             metrics,
+            trace,
         } = self;
 
         match in_handler {
@@ -121,7 +134,10 @@ impl BlockState {
         entity_cache.extend(other.entity_cache);
         processed_data_sources.extend(other.processed_data_sources);
         persisted_data_sources.extend(other.persisted_data_sources);
-        metrics.extend(other.metrics)
+        metrics.extend(other.metrics);
+        if let (Some(trace), Some(other_trace)) = (trace, other.trace) {
+            trace.extend(other_trace);
+        }
     }
 
     pub fn has_errors(&self) -> bool {
@@ -167,6 +183,9 @@ impl BlockState {
         self.in_handler = false;
         self.handler_created_data_sources.clear();
         self.entity_cache.exit_handler_and_discard_changes();
+        if let Some(handler) = self.trace.as_mut().and_then(|trace| trace.last_mut()) {
+            handler.error = Some(e.message.clone());
+        }
         self.deterministic_errors.push(e);
     }
 
@@ -178,4 +197,62 @@ impl BlockState {
     pub fn persist_data_source(&mut self, ds: StoredDynamicDataSource) {
         self.persisted_data_sources.push(ds)
     }
+
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    /// Start the trace for an invocation of `handler`; events are recorded
+    /// for the handler that was started last
+    pub fn trace_handler(&mut self, handler: &str) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(HandlerTrace::new(handler));
+        }
+    }
+
+    /// Record `event` if we are tracing. The event is only constructed
+    /// when it is needed
+    pub fn trace_event(&mut self, event: impl FnOnce() -> TraceEvent) {
+        if let Some(handler) = self.trace.as_mut().and_then(|trace| trace.last_mut()) {
+            handler.events.push(event());
+        }
+    }
+
+    /// Record the start of a call of the host function `name` and return
+    /// the position of its event, or `None` if we are not tracing. The
+    /// event is recorded before the call so that it comes before anything
+    /// the host function records; its arguments and result are filled in
+    /// by `trace_host_return`
+    pub fn trace_host_call(&mut self, name: &str) -> Option<usize> {
+        let handler = self.trace.as_mut().and_then(|trace| trace.last_mut())?;
+        handler.events.push(TraceEvent::HostCall {
+            name: name.to_string(),
+            args: Vec::new(),
+            result: None,
+        });
+        Some(handler.events.len() - 1)
+    }
+
+    /// Set the arguments and the result of the host call at position
+    /// `event`; `result` is `None` if the call failed
+    pub fn trace_host_return(
+        &mut self,
+        event: usize,
+        call_args: Vec<r::Value>,
+        call_result: Option<r::Value>,
+    ) {
+        let event = self
+            .trace
+            .as_mut()
+            .and_then(|trace| trace.last_mut())
+            .and_then(|handler| handler.events.get_mut(event));
+        if let Some(TraceEvent::HostCall { args, result, .. }) = event {
+            *args = call_args;
+            *result = call_result;
+        }
+    }
+
+    pub fn take_trace(&mut self) -> Vec<HandlerTrace> {
+        self.trace.take().unwrap_or_default()
+    }
 }
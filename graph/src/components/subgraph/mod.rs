@@ -5,6 +5,7 @@ mod proof_of_indexing;
 mod provider;
 mod registrar;
 mod settings;
+mod trace;
 
 pub use crate::prelude::Entity;
 
@@ -17,4 +18,5 @@ pub use self::proof_of_indexing::{
 };
 pub use self::provider::SubgraphAssignmentProvider;
 pub use self::registrar::{SubgraphRegistrar, SubgraphVersionSwitchingMode};
-pub use self::settings::{Setting, Settings};
+pub use self::settings::{Setting, Settings, SettingsTarget, SubgraphSettings};
+pub use self::trace::{BlockTrace, EntityChange, HandlerTrace, TraceEvent};
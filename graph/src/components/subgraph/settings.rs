@@ -1,36 +1,134 @@
 //! Facilities for dealing with subgraph-specific settings
-use std::fs::read_to_string;
+use std::{fs::read_to_string, time::Duration};
 
 use crate::{
     anyhow,
-    prelude::{regex::Regex, SubgraphName},
+    prelude::{regex::Regex, DeploymentHash, SubgraphName, ENV_VARS},
 };
 use serde::{Deserialize, Serialize};
 
+/// The attributes of a deployment that predicates are matched against.
+/// Attributes that are not known where settings are looked up are left
+/// empty, and predicates on them never match
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SettingsTarget<'a> {
+    /// All the names that point to the deployment
+    pub names: &'a [SubgraphName],
+    pub deployment: Option<&'a DeploymentHash>,
+    pub network: Option<&'a str>,
+    pub shard: Option<&'a str>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum Predicate {
     #[serde(alias = "name", with = "serde_regex")]
     Name(Regex),
+    #[serde(alias = "deployment", with = "serde_regex")]
+    Deployment(Regex),
+    #[serde(alias = "network", with = "serde_regex")]
+    Network(Regex),
+    #[serde(alias = "shard", with = "serde_regex")]
+    Shard(Regex),
 }
 
 impl Predicate {
-    fn matches(&self, name: &SubgraphName) -> bool {
+    fn matches(&self, target: &SettingsTarget) -> bool {
         match self {
-            Predicate::Name(rx) => rx.is_match(name.as_str()),
+            Predicate::Name(rx) => target.names.iter().any(|name| rx.is_match(name.as_str())),
+            Predicate::Deployment(rx) => target
+                .deployment
+                .map_or(false, |hash| rx.is_match(hash.as_str())),
+            Predicate::Network(rx) => target.network.map_or(false, |network| rx.is_match(network)),
+            Predicate::Shard(rx) => target.shard.map_or(false, |shard| rx.is_match(shard)),
         }
     }
 }
 
+/// The values that a setting can override. Any value that is not set
+/// falls back to the node-wide default from the corresponding environment
+/// variable. Sizes are in kilobytes and durations in seconds, the same as
+/// for the environment variables
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct SubgraphSettings {
+    pub history_blocks: Option<i32>,
+    /// Overrides `GRAPH_ENTITY_CACHE_SIZE`
+    pub entity_cache_size: Option<usize>,
+    /// Overrides `GRAPH_MAPPING_HANDLER_TIMEOUT`
+    pub handler_timeout: Option<u64>,
+    /// Overrides `GRAPH_MAX_GAS_PER_HANDLER`, but can only lower it. Since
+    /// other indexers use the default, running out of this limit is not a
+    /// deterministic error and does not fail the subgraph deterministically
+    pub gas_limit: Option<u64>,
+    /// Overrides `GRAPH_STORE_WRITE_BATCH_SIZE`
+    pub write_batch_size: Option<usize>,
+    /// Overrides `GRAPH_GRAPHQL_MAX_FIRST`
+    pub max_first: Option<u32>,
+    /// Overrides `GRAPH_GRAPHQL_MAX_SKIP`
+    pub max_skip: Option<u32>,
+    /// Overrides `GRAPH_GRAPHQL_MAX_COMPLEXITY`
+    pub max_complexity: Option<u64>,
+}
+
+impl SubgraphSettings {
+    /// Fill in any values that are not set in `self` from `other`
+    fn merge(&mut self, other: &SubgraphSettings) {
+        let SubgraphSettings {
+            history_blocks,
+            entity_cache_size,
+            handler_timeout,
+            gas_limit,
+            write_batch_size,
+            max_first,
+            max_skip,
+            max_complexity,
+        } = other;
+
+        self.history_blocks = self.history_blocks.or(*history_blocks);
+        self.entity_cache_size = self.entity_cache_size.or(*entity_cache_size);
+        self.handler_timeout = self.handler_timeout.or(*handler_timeout);
+        self.gas_limit = self.gas_limit.or(*gas_limit);
+        self.write_batch_size = self.write_batch_size.or(*write_batch_size);
+        self.max_first = self.max_first.or(*max_first);
+        self.max_skip = self.max_skip.or(*max_skip);
+        self.max_complexity = self.max_complexity.or(*max_complexity);
+    }
+
+    /// The size limit of the entity cache in bytes
+    pub fn entity_cache_size(&self) -> usize {
+        self.entity_cache_size
+            .map(|kb| kb * 1000)
+            .unwrap_or(ENV_VARS.mappings.entity_cache_size)
+    }
+
+    pub fn handler_timeout(&self) -> Option<Duration> {
+        self.handler_timeout
+            .map(Duration::from_secs)
+            .or(ENV_VARS.mappings.timeout)
+    }
+
+    pub fn gas_limit(&self) -> u64 {
+        self.gas_limit.unwrap_or(ENV_VARS.max_gas_per_handler)
+    }
+
+    /// The size of write batches in bytes
+    pub fn write_batch_size(&self) -> usize {
+        self.write_batch_size
+            .map(|kb| kb * 1_000)
+            .unwrap_or(ENV_VARS.store.write_batch_size)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Setting {
     #[serde(alias = "match")]
     pred: Predicate,
-    pub history_blocks: i32,
+    #[serde(flatten)]
+    pub values: SubgraphSettings,
 }
 
 impl Setting {
-    fn matches(&self, name: &SubgraphName) -> bool {
-        self.pred.matches(name)
+    fn matches(&self, target: &SettingsTarget) -> bool {
+        self.pred.matches(target)
     }
 }
 
@@ -46,17 +144,47 @@ impl Settings {
     }
 
     pub fn from_str(toml: &str) -> Result<Self, anyhow::Error> {
-        toml::from_str::<Self>(toml).map_err(anyhow::Error::from)
+        let settings = toml::from_str::<Self>(toml)?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        for setting in &self.settings {
+            if let Some(gas_limit) = setting.values.gas_limit {
+                if gas_limit > ENV_VARS.max_gas_per_handler {
+                    return Err(anyhow::anyhow!(
+                        "the gas_limit {} for the setting matching {:?} is higher than \
+                         GRAPH_MAX_GAS_PER_HANDLER ({}); settings can only lower the gas limit",
+                        gas_limit,
+                        setting.pred,
+                        ENV_VARS.max_gas_per_handler
+                    ));
+                }
+            }
+        }
+        Ok(())
     }
 
-    pub fn for_name(&self, name: &SubgraphName) -> Option<&Setting> {
-        self.settings.iter().find(|setting| setting.matches(name))
+    /// Combine all settings that match `target`. When several matching
+    /// settings set the same value, the one that comes first in the file
+    /// wins
+    pub fn for_target(&self, target: &SettingsTarget) -> SubgraphSettings {
+        self.settings
+            .iter()
+            .filter(|setting| setting.matches(target))
+            .fold(SubgraphSettings::default(), |mut values, setting| {
+                values.merge(&setting.values);
+                values
+            })
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{Predicate, Settings};
+    use crate::prelude::{DeploymentHash, SubgraphName, ENV_VARS};
+
+    use super::{Predicate, Settings, SettingsTarget, SubgraphSettings};
 
     #[test]
     fn parses_correctly() {
@@ -79,16 +207,115 @@ mod test {
 
         let rule1 = match &section.settings[0].pred {
             Predicate::Name(name) => name,
+            _ => unreachable!(),
         };
         assert_eq!(rule1.as_str(), ".*");
 
         let rule2 = match &section.settings[1].pred {
             Predicate::Name(name) => name,
+            _ => unreachable!(),
         };
         assert_eq!(rule2.as_str(), "xxxxx");
         let rule1 = match &section.settings[2].pred {
             Predicate::Name(name) => name,
+            _ => unreachable!(),
         };
         assert_eq!(rule1.as_str(), ".*!$");
     }
+
+    #[test]
+    fn resolves_settings() {
+        let content = r#"
+        [[setting]]
+        match = { deployment = "^QmNoisy$" }
+        entity_cache_size = 50000
+        write_batch_size = 100
+
+        [[setting]]
+        match = { name = "^noisy/" }
+        entity_cache_size = 1000
+        handler_timeout = 30
+        max_first = 100
+
+        [[setting]]
+        match = { network = "^mainnet$" }
+        history_blocks = 10000
+        gas_limit = 1000
+
+        [[setting]]
+        match = { shard = "^primary$" }
+        max_skip = 1000
+        max_complexity = 50
+        "#;
+
+        let settings = Settings::from_str(content).unwrap();
+
+        let name = SubgraphName::new("noisy/subgraph").unwrap();
+        let hash = DeploymentHash::new("QmNoisy").unwrap();
+        let names = [name];
+        let target = SettingsTarget {
+            names: &names,
+            deployment: Some(&hash),
+            network: Some("mainnet"),
+            shard: Some("primary"),
+        };
+        let exp = SubgraphSettings {
+            history_blocks: Some(10000),
+            entity_cache_size: Some(50000),
+            handler_timeout: Some(30),
+            gas_limit: Some(1000),
+            write_batch_size: Some(100),
+            max_first: Some(100),
+            max_skip: Some(1000),
+            max_complexity: Some(50),
+        };
+        assert_eq!(exp, settings.for_target(&target));
+        assert_eq!(50_000_000, exp.entity_cache_size());
+        assert_eq!(100_000, exp.write_batch_size());
+
+        // Predicates on attributes that are not known never match
+        let target = SettingsTarget {
+            names: &names,
+            ..Default::default()
+        };
+        let exp = SubgraphSettings {
+            entity_cache_size: Some(1000),
+            handler_timeout: Some(30),
+            max_first: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(exp, settings.for_target(&target));
+
+        let other = DeploymentHash::new("QmOther").unwrap();
+        let target = SettingsTarget {
+            deployment: Some(&other),
+            network: Some("gnosis"),
+            shard: Some("sharda"),
+            ..Default::default()
+        };
+        assert_eq!(SubgraphSettings::default(), settings.for_target(&target));
+    }
+
+    #[test]
+    fn rejects_raising_gas_limit() {
+        let content = format!(
+            r#"
+        [[setting]]
+        match = {{ name = ".*" }}
+        gas_limit = {}
+        "#,
+            ENV_VARS.max_gas_per_handler + 1
+        );
+        assert!(Settings::from_str(&content).is_err());
+
+        let content = format!(
+            r#"
+        [[setting]]
+        match = {{ name = ".*" }}
+        gas_limit = {}
+        "#,
+            ENV_VARS.max_gas_per_handler
+        );
+        assert!(Settings::from_str(&content).is_ok());
+    }
 }
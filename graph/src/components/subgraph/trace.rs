@@ -0,0 +1,201 @@
+//! Traces of what mappings do while processing a block. Traces are only
+//! collected when blocks are replayed for debugging with `graphman replay`,
+//! never during normal indexing.
+
+use serde::Serialize;
+
+use crate::prelude::{r, BlockNumber, BlockPtr, Entity};
+use crate::schema::EntityKey;
+
+/// The entity as plain JSON, without the type tags that the serialization
+/// of `store::Value` adds
+fn entity_value(entity: &Entity) -> r::Value {
+    r::Value::Object(
+        entity
+            .clone()
+            .sorted()
+            .into_iter()
+            .map(|(name, value)| (name, value.into()))
+            .collect(),
+    )
+}
+
+/// Something a handler did
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// A call of the host function `name`. The arguments and the result
+    /// are decoded from wasm memory where their type is known; `result`
+    /// is `None` if the call failed
+    HostCall {
+        name: String,
+        args: Vec<r::Value>,
+        result: Option<r::Value>,
+    },
+    /// A lookup of an entity; `entity` is `None` if it was not found
+    StoreGet {
+        entity_type: String,
+        id: String,
+        entity: Option<r::Value>,
+    },
+    StoreSet {
+        entity_type: String,
+        id: String,
+        entity: r::Value,
+    },
+    StoreRemove {
+        entity_type: String,
+        id: String,
+    },
+}
+
+impl TraceEvent {
+    pub fn store_get(key: &EntityKey, entity: Option<&Entity>) -> Self {
+        TraceEvent::StoreGet {
+            entity_type: key.entity_type.to_string(),
+            id: key.entity_id.to_string(),
+            entity: entity.map(entity_value),
+        }
+    }
+
+    pub fn store_set(key: &EntityKey, entity: &Entity) -> Self {
+        TraceEvent::StoreSet {
+            entity_type: key.entity_type.to_string(),
+            id: key.entity_id.to_string(),
+            entity: entity_value(entity),
+        }
+    }
+
+    pub fn store_remove(key: &EntityKey) -> Self {
+        TraceEvent::StoreRemove {
+            entity_type: key.entity_type.to_string(),
+            id: key.entity_id.to_string(),
+        }
+    }
+}
+
+/// The events of one invocation of a handler, in the order in which they
+/// happened
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct HandlerTrace {
+    pub handler: String,
+    pub events: Vec<TraceEvent>,
+    /// The deterministic error with which the handler failed. The changes
+    /// the handler made were discarded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl HandlerTrace {
+    pub fn new(handler: &str) -> Self {
+        HandlerTrace {
+            handler: handler.to_string(),
+            events: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+/// How processing a block changed an entity
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EntityChange {
+    pub entity_type: String,
+    pub id: String,
+    /// The entity before the block; `None` if it did not exist
+    pub before: Option<r::Value>,
+    /// The entity after the block; `None` if it was removed
+    pub after: Option<r::Value>,
+}
+
+impl EntityChange {
+    pub fn new(key: &EntityKey, before: Option<&Entity>, after: Option<&Entity>) -> Self {
+        EntityChange {
+            entity_type: key.entity_type.to_string(),
+            id: key.entity_id.to_string(),
+            before: before.map(entity_value),
+            after: after.map(entity_value),
+        }
+    }
+}
+
+/// Everything the handlers did for one block
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BlockTrace {
+    pub block_number: BlockNumber,
+    pub block_hash: String,
+    pub handlers: Vec<HandlerTrace>,
+    pub changes: Vec<EntityChange>,
+}
+
+impl BlockTrace {
+    pub fn new(block: &BlockPtr, handlers: Vec<HandlerTrace>, changes: Vec<EntityChange>) -> Self {
+        BlockTrace {
+            block_number: block.number,
+            block_hash: block.hash_hex(),
+            handlers,
+            changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::value::Word;
+    use crate::prelude::serde_json::{self, json};
+    use crate::prelude::Value;
+    use crate::schema::InputSchema;
+
+    use super::*;
+
+    const SCHEMA: &str = "type Thing @entity { id: ID!, count: Int! }";
+
+    #[test]
+    fn serializes_events() {
+        let schema = InputSchema::raw(SCHEMA, "trace");
+        let key = schema
+            .entity_type("Thing")
+            .unwrap()
+            .parse_key("one")
+            .unwrap();
+        let entity = schema
+            .make_entity(vec![
+                (Word::from("id"), Value::from("one")),
+                (Word::from("count"), Value::from(1)),
+            ])
+            .unwrap();
+
+        let mut handler = HandlerTrace::new("handleThing");
+        handler.events.push(TraceEvent::HostCall {
+            name: "store.get".to_string(),
+            args: vec![
+                r::Value::String("Thing".to_string()),
+                r::Value::String("one".to_string()),
+            ],
+            result: Some(r::Value::Null),
+        });
+        handler.events.push(TraceEvent::store_get(&key, None));
+        handler.events.push(TraceEvent::store_set(&key, &entity));
+
+        assert_eq!(
+            json!({
+                "handler": "handleThing",
+                "events": [
+                    {
+                        "type": "host_call",
+                        "name": "store.get",
+                        "args": ["Thing", "one"],
+                        "result": null
+                    },
+                    { "type": "store_get", "entity_type": "Thing", "id": "one", "entity": null },
+                    {
+                        "type": "store_set",
+                        "entity_type": "Thing",
+                        "id": "one",
+                        "entity": { "id": "one", "count": 1 }
+                    }
+                ]
+            }),
+            serde_json::to_value(&handler).unwrap()
+        );
+    }
+}
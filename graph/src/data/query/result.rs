@@ -4,7 +4,7 @@ use crate::cheap_clone::CheapClone;
 use crate::components::server::query::ServerResponse;
 use crate::data::value::Object;
 use crate::derive::CacheWeight;
-use crate::prelude::{r, CacheWeight, DeploymentHash};
+use crate::prelude::{r, BlockPtr, CacheWeight, DeploymentHash};
 use http_body_util::Full;
 use hyper::header::{
     ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
@@ -54,6 +54,10 @@ pub type Data = Object;
 /// A collection of query results that is serialized as a single result.
 pub struct QueryResults {
     results: Vec<Arc<QueryResult>>,
+    /// The blocks at which the results were computed
+    blocks: Vec<BlockPtr>,
+    /// The complexity of the query that produced the results
+    complexity: u64,
     pub trace: Trace,
 }
 
@@ -61,6 +65,8 @@ impl QueryResults {
     pub fn empty(trace: Trace) -> Self {
         QueryResults {
             results: Vec::new(),
+            blocks: Vec::new(),
+            complexity: 0,
             trace,
         }
     }
@@ -91,6 +97,27 @@ impl QueryResults {
     pub fn is_attestable(&self) -> bool {
         self.results.iter().all(|r| r.is_attestable())
     }
+
+    /// The blocks at which the results were computed. This is empty if the
+    /// results did not come from running a query against a deployment.
+    pub fn blocks(&self) -> &[BlockPtr] {
+        &self.blocks
+    }
+
+    pub fn record_block(&mut self, block: BlockPtr) {
+        self.blocks.push(block);
+    }
+
+    /// The complexity of the query that produced the results. This is 0 if
+    /// the results did not come from running a query, e.g., because the
+    /// query was invalid
+    pub fn complexity(&self) -> u64 {
+        self.complexity
+    }
+
+    pub fn record_complexity(&mut self, complexity: u64) {
+        self.complexity = complexity;
+    }
 }
 
 impl Serialize for QueryResults {
@@ -154,6 +181,8 @@ impl From<Data> for QueryResults {
     fn from(x: Data) -> Self {
         QueryResults {
             results: vec![Arc::new(x.into())],
+            blocks: Vec::new(),
+            complexity: 0,
             trace: Trace::None,
         }
     }
@@ -163,6 +192,8 @@ impl From<QueryResult> for QueryResults {
     fn from(x: QueryResult) -> Self {
         QueryResults {
             results: vec![Arc::new(x)],
+            blocks: Vec::new(),
+            complexity: 0,
             trace: Trace::None,
         }
     }
@@ -172,6 +203,8 @@ impl From<Arc<QueryResult>> for QueryResults {
     fn from(x: Arc<QueryResult>) -> Self {
         QueryResults {
             results: vec![x],
+            blocks: Vec::new(),
+            complexity: 0,
             trace: Trace::None,
         }
     }
@@ -181,6 +214,8 @@ impl From<QueryExecutionError> for QueryResults {
     fn from(x: QueryExecutionError) -> Self {
         QueryResults {
             results: vec![Arc::new(x.into())],
+            blocks: Vec::new(),
+            complexity: 0,
             trace: Trace::None,
         }
     }
@@ -190,6 +225,8 @@ impl From<Vec<QueryExecutionError>> for QueryResults {
     fn from(x: Vec<QueryExecutionError>) -> Self {
         QueryResults {
             results: vec![Arc::new(x.into())],
+            blocks: Vec::new(),
+            complexity: 0,
             trace: Trace::None,
         }
     }
@@ -141,6 +141,7 @@ pub const BIG_INT_SCALAR: &str = "BigInt";
 pub const BIG_DECIMAL_SCALAR: &str = "BigDecimal";
 pub const INT8_SCALAR: &str = "Int8";
 pub const TIMESTAMP_SCALAR: &str = "Timestamp";
+pub const JSON_SCALAR: &str = "JSON";
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ValueType {
@@ -152,6 +153,7 @@ pub enum ValueType {
     Int8,
     String,
     Timestamp,
+    Json,
 }
 
 impl FromStr for ValueType {
@@ -166,6 +168,7 @@ impl FromStr for ValueType {
             "Int" => Ok(ValueType::Int),
             "Int8" => Ok(ValueType::Int8),
             "Timestamp" => Ok(ValueType::Timestamp),
+            "JSON" => Ok(ValueType::Json),
             "String" | "ID" => Ok(ValueType::String),
             s => Err(anyhow!("Type not available in this context: {}", s)),
         }
@@ -181,9 +184,11 @@ impl ValueType {
     pub fn is_numeric(&self) -> bool {
         match self {
             ValueType::BigInt | ValueType::BigDecimal | ValueType::Int | ValueType::Int8 => true,
-            ValueType::Boolean | ValueType::Bytes | ValueType::String | ValueType::Timestamp => {
-                false
-            }
+            ValueType::Boolean
+            | ValueType::Bytes
+            | ValueType::String
+            | ValueType::Timestamp
+            | ValueType::Json => false,
         }
     }
 
@@ -196,6 +201,7 @@ impl ValueType {
             ValueType::Int => "Int",
             ValueType::Int8 => "Int8",
             ValueType::Timestamp => "Timestamp",
+            ValueType::Json => "JSON",
             ValueType::String => "String",
         }
     }
@@ -215,6 +221,7 @@ impl PartialOrd for ValueType {
             | (BigDecimal, BigDecimal)
             | (Int, Int)
             | (Int8, Int8)
+            | (Json, Json)
             | (String, String) => Some(Equal),
             (BigInt, BigDecimal)
             | (Int, BigInt)
@@ -235,7 +242,9 @@ impl PartialOrd for ValueType {
             | (Bytes, _)
             | (_, Bytes)
             | (String, _)
-            | (_, String) => None,
+            | (_, String)
+            | (Json, _)
+            | (_, Json) => None,
         }
     }
 }
@@ -262,6 +271,7 @@ pub enum Value {
     Null,
     Bytes(scalar::Bytes),
     BigInt(scalar::BigInt),
+    Json(serde_json::Value),
 }
 
 pub const NULL: Value = Value::Null;
@@ -314,6 +324,11 @@ impl stable_hash_legacy::StableHash for Value {
             Timestamp(inner) => {
                 stable_hash_legacy::StableHash::stable_hash(inner, sequence_number, state)
             }
+            Json(inner) => stable_hash_legacy::StableHash::stable_hash(
+                &canonical_json(inner),
+                sequence_number,
+                state,
+            ),
         }
     }
 }
@@ -365,12 +380,40 @@ impl StableHash for Value {
                 inner.stable_hash(field_address.child(0), state);
                 9
             }
+            Json(inner) => {
+                canonical_json(inner).stable_hash(field_address.child(0), state);
+                10
+            }
         };
 
         state.write(field_address, &[variant])
     }
 }
 
+/// Serialize `value` with the keys of all objects in sorted order so that
+/// the result does not depend on the order in which keys were inserted
+fn canonical_json(value: &serde_json::Value) -> String {
+    fn sorted(value: &serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Array(values) => {
+                serde_json::Value::Array(values.iter().map(sorted).collect())
+            }
+            serde_json::Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                serde_json::Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key.clone(), sorted(value)))
+                        .collect(),
+                )
+            }
+            value => value.clone(),
+        }
+    }
+    sorted(value).to_string()
+}
+
 impl NullValue for Value {
     fn null() -> Self {
         Value::Null
@@ -385,6 +428,14 @@ impl Value {
             // When dealing with non-null types, use the inner type to convert the value
             (value, NonNullType(t)) => Value::from_query_value(value, t)?,
 
+            // JSON values are taken as they are, including lists and objects
+            (value, NamedType(n)) if n == JSON_SCALAR => {
+                let json = serde_json::to_value(value).map_err(|e| {
+                    QueryExecutionError::ValueParseError("JSON".to_string(), e.to_string())
+                })?;
+                Value::from_json(json)
+            }
+
             (r::Value::List(values), ListType(ty)) => Value::List(
                 values
                     .iter()
@@ -511,6 +562,24 @@ impl Value {
         }
     }
 
+    /// Convert a JSON value into a `Value`, mapping JSON `null` to
+    /// `Value::Null` so that there is only one way to represent a missing
+    /// value
+    pub fn from_json(json: serde_json::Value) -> Value {
+        match json {
+            serde_json::Value::Null => Value::Null,
+            json => Value::Json(json),
+        }
+    }
+
+    pub fn as_json(&self) -> Option<&serde_json::Value> {
+        if let Value::Json(json) = self {
+            Some(json)
+        } else {
+            None
+        }
+    }
+
     /// Return the name of the type of this value for display to the user
     pub fn type_name(&self) -> String {
         match self {
@@ -521,6 +590,7 @@ impl Value {
             Value::Int(_) => "Int".to_owned(),
             Value::Int8(_) => "Int8".to_owned(),
             Value::Timestamp(_) => "Timestamp".to_owned(),
+            Value::Json(_) => "JSON".to_owned(),
             Value::List(values) => {
                 if let Some(v) = values.first() {
                     format!("[{}]", v.type_name())
@@ -543,6 +613,7 @@ impl Value {
             | (Value::Int(_), ValueType::Int)
             | (Value::Int8(_), ValueType::Int8)
             | (Value::Timestamp(_), ValueType::Timestamp)
+            | (Value::Json(_), ValueType::Json)
             | (Value::Null, _) => true,
             (Value::List(values), _) if is_list => values
                 .iter()
@@ -573,6 +644,7 @@ impl fmt::Display for Value {
                     format!("[{}]", values.iter().map(ToString::to_string).join(", ")),
                 Value::Bytes(ref bytes) => bytes.to_string(),
                 Value::BigInt(ref number) => number.to_string(),
+                Value::Json(ref json) => json.to_string(),
             }
         )
     }
@@ -591,6 +663,7 @@ impl fmt::Debug for Value {
             Self::Null => write!(f, "Null"),
             Self::Bytes(bytes) => bytes.fmt(f),
             Self::BigInt(number) => number.fmt(f),
+            Self::Json(json) => f.debug_tuple("Json").field(json).finish(),
         }
     }
 }
@@ -610,6 +683,7 @@ impl From<Value> for q::Value {
             }
             Value::Bytes(bytes) => q::Value::String(bytes.to_string()),
             Value::BigInt(number) => q::Value::String(number.to_string()),
+            Value::Json(json) => r::Value::from(json).into(),
         }
     }
 }
@@ -629,6 +703,7 @@ impl From<Value> for r::Value {
             }
             Value::Bytes(bytes) => r::Value::String(bytes.to_string()),
             Value::BigInt(number) => r::Value::String(number.to_string()),
+            Value::Json(json) => r::Value::from(json),
         }
     }
 }
@@ -16,6 +16,82 @@ pub enum Filter {
     Deployments(Vec<String>),
     /// Get the status of all deployments with the given ids
     DeploymentIds(Vec<DeploymentId>),
+    /// Get the status of a page of the active deployments that match the
+    /// criteria of `filter` that do not need the indexing status, ordered
+    /// by their id. The remaining criteria have to be checked with
+    /// `DeploymentFilter::matches`
+    Matching {
+        filter: DeploymentFilter,
+        first: usize,
+        skip: usize,
+    },
+}
+
+/// Criteria for selecting deployments by where they are stored and indexed
+/// and by how they are doing. Criteria that are not set match all
+/// deployments
+#[derive(Clone, Debug, Default)]
+pub struct DeploymentFilter {
+    /// Only deployments with one of these health values; if empty, the
+    /// health is not checked
+    pub health: Vec<SubgraphHealth>,
+    /// Only deployments assigned to this node
+    pub node: Option<String>,
+    /// Only deployments stored in this shard
+    pub shard: Option<String>,
+    /// Only deployments indexing this network
+    pub network: Option<String>,
+    /// Only deployments that are (`true`) or are not (`false`) paused.
+    /// Unassigned deployments count as not paused
+    pub paused: Option<bool>,
+    /// Only deployments that are at least this many blocks behind the
+    /// chain head
+    pub min_lag: Option<BlockNumber>,
+    /// Only deployments with one of these IPFS hashes or with one of the
+    /// database namespaces in `namespaces`; if both are empty, all
+    /// deployments match
+    pub hashes: Vec<String>,
+    /// Only deployments with one of these database namespaces or with one
+    /// of the IPFS hashes in `hashes`
+    pub namespaces: Vec<String>,
+}
+
+impl DeploymentFilter {
+    /// Return `true` if some criteria can only be checked with the indexing
+    /// status of the deployment
+    pub fn needs_status(&self) -> bool {
+        !self.health.is_empty() || self.min_lag.is_some()
+    }
+
+    /// Check the criteria that depend on the indexing status of the
+    /// deployment. The `shard`, `network`, `hashes` and `namespaces` are
+    /// checked when deployments are looked up and are not checked here
+    pub fn matches(&self, info: &Info) -> bool {
+        if !self.health.is_empty() && !self.health.contains(&info.health) {
+            return false;
+        }
+
+        if let Some(node) = &self.node {
+            if info.node.as_ref() != Some(node) {
+                return false;
+            }
+        }
+
+        if let Some(paused) = self.paused {
+            if info.paused.unwrap_or(false) != paused {
+                return false;
+            }
+        }
+
+        if let Some(min_lag) = self.min_lag {
+            match info.chains.first().and_then(ChainInfo::lag) {
+                Some(lag) if lag >= min_lag => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
 }
 
 /// Light wrapper around `EthereumBlockPointer` that is compatible with GraphQL values.
@@ -67,6 +143,17 @@ pub struct ChainInfo {
     pub latest_block: Option<EthereumBlock>,
 }
 
+impl ChainInfo {
+    /// The number of blocks the deployment is behind the chain head, or
+    /// `None` if the chain head is not known. A deployment that has not
+    /// indexed any blocks yet is behind by the number of the chain head
+    pub fn lag(&self) -> Option<BlockNumber> {
+        let head = self.chain_head_block.as_ref()?.number();
+        let latest = self.latest_block.as_ref().map(|block| block.number());
+        Some((head - latest.unwrap_or(0)).max(0))
+    }
+}
+
 impl IntoValue for ChainInfo {
     fn into_value(self) -> r::Value {
         let ChainInfo {
@@ -356,6 +356,8 @@ impl Value {
                     Err(Value::Int(num))
                 }
             }
+            ("Float", Value::Float(f)) => Ok(Value::Float(f)),
+            ("Float", Value::Int(num)) => Ok(Value::Float(num as f64)),
             ("Int8", Value::Int(num)) => Ok(Value::String(num.to_string())),
             ("Int8", Value::String(num)) => Ok(Value::String(num)),
             ("Timestamp", Value::Timestamp(ts)) => Ok(Value::Timestamp(ts)),
@@ -369,6 +371,7 @@ impl Value {
             ("BigInt", Value::String(s)) => Ok(Value::String(s)),
             ("BigInt", Value::Int(n)) => Ok(Value::String(n.to_string())),
             ("JSONObject", Value::Object(obj)) => Ok(Value::Object(obj)),
+            ("JSON", v) => Ok(v),
             ("Date", Value::String(obj)) => Ok(Value::String(obj)),
             (_, v) => Err(v),
         }
@@ -15,6 +15,7 @@ use crate::{
     components::{
         link_resolver::LinkResolver,
         store::{BlockNumber, StoredDynamicDataSource},
+        subgraph::HostMetrics,
     },
     data_source::offchain::OFFCHAIN_KINDS,
     prelude::{CheapClone as _, DataSourceContext},
@@ -198,11 +199,12 @@ impl<C: Blockchain> DataSource<C> {
         trigger: &TriggerData<C>,
         block: &Arc<C::Block>,
         logger: &Logger,
+        metrics: &HostMetrics,
     ) -> Result<Option<TriggerWithHandler<MappingTrigger<C>>>, Error> {
         match (self, trigger) {
             (Self::Onchain(ds), _) if ds.has_expired(block.number()) => Ok(None),
             (Self::Onchain(ds), TriggerData::Onchain(trigger)) => ds
-                .match_and_decode(trigger, block, logger)
+                .match_and_decode(trigger, block, logger, metrics)
                 .map(|t| t.map(|t| t.map(MappingTrigger::Onchain))),
             (Self::Offchain(ds), TriggerData::Offchain(trigger)) => {
                 Ok(ds.match_and_decode(trigger))
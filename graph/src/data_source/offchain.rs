@@ -30,6 +30,7 @@ lazy_static! {
     pub static ref OFFCHAIN_KINDS: HashMap<&'static str, OffchainDataSourceKind> = [
         ("file/ipfs", OffchainDataSourceKind::Ipfs),
         ("file/arweave", OffchainDataSourceKind::Arweave),
+        ("file/http", OffchainDataSourceKind::Http),
     ]
     .into_iter()
     .collect();
@@ -42,6 +43,7 @@ const NOT_DONE_VALUE: i32 = -1;
 pub enum OffchainDataSourceKind {
     Ipfs,
     Arweave,
+    Http,
 }
 impl OffchainDataSourceKind {
     pub fn try_parse_source(&self, bs: Bytes) -> Result<Source, anyhow::Error> {
@@ -54,6 +56,10 @@ impl OffchainDataSourceKind {
                 let base64 = Word::from(String::from_utf8(bs.to_vec())?);
                 Source::Arweave(base64)
             }
+            OffchainDataSourceKind::Http => {
+                let url = parse_http_url(&String::from_utf8(bs.to_vec())?)?;
+                Source::Http(url)
+            }
         };
         Ok(source)
     }
@@ -190,6 +196,11 @@ impl DataSource {
                 Err(e) => return Err(DataSourceCreationError::Ignore(source, e.into())),
             },
             OffchainDataSourceKind::Arweave => Source::Arweave(Word::from(source)),
+            OffchainDataSourceKind::Http => match parse_http_url(&source) {
+                Ok(url) => Source::Http(url),
+                // Ignore data sources created with an invalid URL.
+                Err(e) => return Err(DataSourceCreationError::Ignore(source, e)),
+            },
         };
 
         Ok(Self {
@@ -242,6 +253,9 @@ impl DataSource {
             creation_block: self.creation_block,
             done_at,
             causality_region: self.causality_region,
+            // Only known while the data source is processed, see
+            // `SubgraphRunner::handle_offchain_triggers`
+            content_hash: None,
         }
     }
 
@@ -256,6 +270,7 @@ impl DataSource {
             creation_block,
             done_at,
             causality_region,
+            content_hash: _,
         } = stored;
 
         let param = param.context("no param on stored data source")?;
@@ -311,10 +326,24 @@ impl DataSource {
 
 pub type Base64 = Word;
 
+/// An absolute `http` or `https` URL.
+pub type HttpUrl = Word;
+
+/// Checks that `url` is an absolute URL that can be fetched over HTTP(S).
+fn parse_http_url(url: &str) -> Result<HttpUrl, Error> {
+    let parsed = url::Url::parse(url).with_context(|| format!("invalid URL `{url}`"))?;
+
+    match parsed.scheme() {
+        "http" | "https" => Ok(Word::from(url)),
+        scheme => bail!("unsupported URL scheme `{scheme}` in `{url}`, expected http or https"),
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Source {
     Ipfs(ContentPath),
     Arweave(Base64),
+    Http(HttpUrl),
 }
 
 impl Source {
@@ -328,6 +357,7 @@ impl Source {
         match self {
             Source::Ipfs(ref path) => Some(path.to_string().as_bytes().to_vec()),
             Source::Arweave(ref base64) => Some(base64.as_bytes().to_vec()),
+            Source::Http(ref url) => Some(url.as_bytes().to_vec()),
         }
     }
 }
@@ -337,6 +367,7 @@ impl Into<Bytes> for Source {
         match self {
             Source::Ipfs(ref path) => Bytes::from(path.to_string().as_bytes().to_vec()),
             Source::Arweave(ref base64) => Bytes::from(base64.as_bytes()),
+            Source::Http(ref url) => Bytes::from(url.as_bytes()),
         }
     }
 }
@@ -549,5 +580,26 @@ mod test {
             .try_parse_source(arweave_source.into())
             .unwrap();
         assert! { matches!(s, Source::Arweave(b64) if b64.eq(&base64))};
+
+        let url = "https://example.com/metadata/1.json";
+        let http_source = Source::Http(Word::from(url));
+        let s = OffchainDataSourceKind::Http
+            .try_parse_source(http_source.into())
+            .unwrap();
+        assert! { matches!(s, Source::Http(u) if u.eq(&url))};
+    }
+
+    #[test]
+    fn test_http_source_requires_http_url() {
+        for url in [
+            "example.com/1.json",
+            "ftp://example.com/1.json",
+            "file:///etc/passwd",
+        ] {
+            let source = Bytes::from(url.as_bytes());
+            assert!(OffchainDataSourceKind::Http
+                .try_parse_source(source)
+                .is_err());
+        }
     }
 }
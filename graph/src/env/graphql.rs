@@ -1,4 +1,5 @@
 use std::fmt;
+use std::path::PathBuf;
 
 use super::*;
 
@@ -89,12 +90,45 @@ pub struct EnvVarsGraphQl {
     /// Set by the flag `GRAPH_GRAPHQL_MAX_OPERATIONS_PER_CONNECTION`.
     /// Defaults to 1000.
     pub max_operations_per_connection: usize,
+    /// How long a client that speaks the `graphql-transport-ws` protocol has
+    /// to send `connection_init` before the connection is closed. Set by
+    /// `GRAPH_GRAPHQL_WS_CONNECTION_INIT_TIMEOUT` (in seconds). Defaults to 3.
+    pub ws_connection_init_timeout: Duration,
+    /// Set by `GRAPH_GRAPHQL_MAX_SSE_SUBSCRIPTIONS`, the maximum number of
+    /// subscriptions that the HTTP server streams as server-sent events at
+    /// the same time. Defaults to 1000.
+    pub max_sse_subscriptions: usize,
+    /// How often a comment is sent on idle server-sent event streams to keep
+    /// proxies from closing them. Set by `GRAPH_GRAPHQL_SSE_HEARTBEAT_INTERVAL`
+    /// (in seconds). Defaults to 15.
+    pub sse_heartbeat_interval: Duration,
+    /// Set by the flag `GRAPH_GRAPHQL_PERSISTED_QUERIES`. Off by default.
+    /// Lets clients send the SHA-256 hash of a query that they sent before
+    /// instead of the full query text
+    pub persisted_queries: bool,
+    /// Maximum memory used to remember persisted queries. Set by
+    /// `GRAPH_GRAPHQL_PERSISTED_QUERIES_MAX_MEM` (expressed in MB). The
+    /// default value is 50MB.
+    pub persisted_queries_max_mem: usize,
+    /// Set by `GRAPH_GRAPHQL_PERSISTED_QUERIES_ALLOWLIST`, the path to a JSON
+    /// file that maps SHA-256 hashes to queries. When this is set, only the
+    /// queries in that file can be run.
+    pub persisted_queries_allowlist: Option<PathBuf>,
     /// Set by the flag `GRAPH_GRAPHQL_DISABLE_BOOL_FILTERS`. Off by default.
     /// Disables AND/OR filters
     pub disable_bool_filters: bool,
     /// Set by the flag `GRAPH_GRAPHQL_DISABLE_CHILD_SORTING`. Off by default.
     /// Disables child-based sorting
     pub disable_child_sorting: bool,
+    /// Set by the flag `GRAPH_GRAPHQL_ENTITY_AGGREGATES`. Off by default,
+    /// but enabled anyway if debug assertions are enabled so that tests can
+    /// use it. Adds a `<entity>Aggregate` field to the `Query` type for each
+    /// entity type that computes counts and sums etc. over matching entities
+    pub entity_aggregates: bool,
+    /// The complexity that an `<entity>Aggregate` field adds to a query
+    /// since it has to scan all matching entities. Set by
+    /// `GRAPH_GRAPHQL_AGGREGATE_COMPLEXITY`. The default value is 1000.
+    pub aggregate_complexity: u64,
     /// Set by `GRAPH_GRAPHQL_TRACE_TOKEN`, the token to use to enable query
     /// tracing for a GraphQL request. If this is set, requests that have a
     /// header `X-GraphTraceQuery` set to this value will include a trace of
@@ -145,8 +179,16 @@ impl From<InnerGraphQl> for EnvVarsGraphQl {
             warn_result_size: x.warn_result_size.0 .0,
             error_result_size: x.error_result_size.0 .0,
             max_operations_per_connection: x.max_operations_per_connection,
+            ws_connection_init_timeout: Duration::from_secs(x.ws_connection_init_timeout_in_secs),
+            max_sse_subscriptions: x.max_sse_subscriptions,
+            sse_heartbeat_interval: Duration::from_secs(x.sse_heartbeat_interval_in_secs),
+            persisted_queries: x.persisted_queries.0,
+            persisted_queries_max_mem: x.persisted_queries_max_mem_in_mb.0 * 1000 * 1000,
+            persisted_queries_allowlist: x.persisted_queries_allowlist,
             disable_bool_filters: x.disable_bool_filters.0,
             disable_child_sorting: x.disable_child_sorting.0,
+            entity_aggregates: x.entity_aggregates.0 || cfg!(debug_assertions),
+            aggregate_complexity: x.aggregate_complexity.0,
             query_trace_token: x.query_trace_token,
             parallel_block_constraints: x.parallel_block_constraints.0,
         }
@@ -194,10 +236,26 @@ pub struct InnerGraphQl {
     error_result_size: WithDefaultUsize<NoUnderscores<usize>, { usize::MAX }>,
     #[envconfig(from = "GRAPH_GRAPHQL_MAX_OPERATIONS_PER_CONNECTION", default = "1000")]
     max_operations_per_connection: usize,
+    #[envconfig(from = "GRAPH_GRAPHQL_WS_CONNECTION_INIT_TIMEOUT", default = "3")]
+    ws_connection_init_timeout_in_secs: u64,
+    #[envconfig(from = "GRAPH_GRAPHQL_MAX_SSE_SUBSCRIPTIONS", default = "1000")]
+    max_sse_subscriptions: usize,
+    #[envconfig(from = "GRAPH_GRAPHQL_SSE_HEARTBEAT_INTERVAL", default = "15")]
+    sse_heartbeat_interval_in_secs: u64,
+    #[envconfig(from = "GRAPH_GRAPHQL_PERSISTED_QUERIES", default = "false")]
+    persisted_queries: EnvVarBoolean,
+    #[envconfig(from = "GRAPH_GRAPHQL_PERSISTED_QUERIES_MAX_MEM", default = "50")]
+    persisted_queries_max_mem_in_mb: NoUnderscores<usize>,
+    #[envconfig(from = "GRAPH_GRAPHQL_PERSISTED_QUERIES_ALLOWLIST")]
+    persisted_queries_allowlist: Option<PathBuf>,
     #[envconfig(from = "GRAPH_GRAPHQL_DISABLE_BOOL_FILTERS", default = "false")]
     pub disable_bool_filters: EnvVarBoolean,
     #[envconfig(from = "GRAPH_GRAPHQL_DISABLE_CHILD_SORTING", default = "false")]
     pub disable_child_sorting: EnvVarBoolean,
+    #[envconfig(from = "GRAPH_GRAPHQL_ENTITY_AGGREGATES", default = "false")]
+    pub entity_aggregates: EnvVarBoolean,
+    #[envconfig(from = "GRAPH_GRAPHQL_AGGREGATE_COMPLEXITY", default = "1000")]
+    aggregate_complexity: NoUnderscores<u64>,
     #[envconfig(from = "GRAPH_GRAPHQL_TRACE_TOKEN", default = "")]
     query_trace_token: String,
     #[envconfig(from = "GRAPH_PARALLEL_BLOCK_CONSTRAINTS", default = "false")]
@@ -54,6 +54,31 @@ pub struct EnvVarsMapping {
     /// Set by the environment variable `GRAPH_IPFS_REQUEST_LIMIT`. Defaults to 100.
     pub ipfs_request_limit: u16,
 
+    /// Sets the size limit for `file/http` data sources.
+    ///
+    /// Set by the environment variable `GRAPH_MAX_HTTP_FILE_BYTES` (expressed in
+    /// bytes). Defaults to 25 MiB.
+    pub max_http_file_bytes: usize,
+    /// The timeout for requests made by `file/http` data sources.
+    ///
+    /// Set by the environment variable `GRAPH_HTTP_FILE_TIMEOUT` (expressed in
+    /// seconds). The default value is 60s.
+    pub http_file_timeout: Duration,
+    /// Limits per second requests to HTTP servers for file data sources.
+    ///
+    /// Set by the environment variable `GRAPH_HTTP_FILE_REQUEST_LIMIT`. Defaults to 100.
+    pub http_file_request_limit: u16,
+    /// How often a `file/http` data source is fetched before giving up on
+    /// it when it is not found or the request fails.
+    ///
+    /// Set by the environment variable `GRAPH_HTTP_FILE_MAX_ATTEMPTS`. Defaults to 10.
+    pub http_file_max_attempts: usize,
+    /// Allow `file/http` data sources to fetch from loopback, private and
+    /// link-local addresses. Only meant for local development.
+    ///
+    /// Set by the flag `GRAPH_HTTP_FILE_ALLOW_PRIVATE_ADDRESSES`. Off by default.
+    pub http_file_allow_private_addresses: bool,
+
     /// Set by the flag `GRAPH_ALLOW_NON_DETERMINISTIC_IPFS`. Off by
     /// default.
     pub allow_non_deterministic_ipfs: bool,
@@ -87,6 +112,11 @@ impl From<InnerMappingHandlers> for EnvVarsMapping {
             max_ipfs_map_file_size: x.max_ipfs_map_file_size.0,
             max_ipfs_file_bytes: x.max_ipfs_file_bytes.0,
             ipfs_request_limit: x.ipfs_request_limit,
+            max_http_file_bytes: x.max_http_file_bytes.0,
+            http_file_timeout: Duration::from_secs(x.http_file_timeout_in_secs),
+            http_file_request_limit: x.http_file_request_limit,
+            http_file_max_attempts: x.http_file_max_attempts,
+            http_file_allow_private_addresses: x.http_file_allow_private_addresses.0,
             allow_non_deterministic_ipfs: x.allow_non_deterministic_ipfs.0,
             disable_declared_calls: x.disable_declared_calls.0,
         }
@@ -119,6 +149,19 @@ pub struct InnerMappingHandlers {
     max_ipfs_file_bytes: WithDefaultUsize<usize, { 25 * 1024 * 1024 }>,
     #[envconfig(from = "GRAPH_IPFS_REQUEST_LIMIT", default = "100")]
     ipfs_request_limit: u16,
+
+    // HTTP file data sources.
+    #[envconfig(from = "GRAPH_MAX_HTTP_FILE_BYTES", default = "")]
+    max_http_file_bytes: WithDefaultUsize<usize, { 25 * 1024 * 1024 }>,
+    #[envconfig(from = "GRAPH_HTTP_FILE_TIMEOUT", default = "60")]
+    http_file_timeout_in_secs: u64,
+    #[envconfig(from = "GRAPH_HTTP_FILE_REQUEST_LIMIT", default = "100")]
+    http_file_request_limit: u16,
+    #[envconfig(from = "GRAPH_HTTP_FILE_MAX_ATTEMPTS", default = "10")]
+    http_file_max_attempts: usize,
+    #[envconfig(from = "GRAPH_HTTP_FILE_ALLOW_PRIVATE_ADDRESSES", default = "false")]
+    http_file_allow_private_addresses: EnvVarBoolean,
+
     #[envconfig(from = "GRAPH_ALLOW_NON_DETERMINISTIC_IPFS", default = "false")]
     allow_non_deterministic_ipfs: EnvVarBoolean,
     #[envconfig(from = "GRAPH_DISABLE_DECLARED_CALLS", default = "false")]
@@ -113,7 +113,7 @@ pub struct EnvVarsStore {
     /// is 10_000 which corresponds to 10MB. Setting this to 0 disables
     /// write batching.
     pub write_batch_size: usize,
-    /// Whether to create GIN indexes for array attributes. Set by
+    /// Whether to create GIN indexes for array and JSON attributes. Set by
     /// `GRAPH_STORE_CREATE_GIN_INDEXES`. The default is `false`
     pub create_gin_indexes: bool,
     /// Temporary env var in case we need to quickly rollback PR #5010
@@ -81,16 +81,34 @@ impl Display for Gas {
 pub struct GasCounter {
     counter: Arc<AtomicU64>,
     metrics: GasMetrics,
+    limit: u64,
 }
 
 impl GasCounter {
     pub fn new(metrics: GasMetrics) -> Self {
+        Self::with_limit(metrics, ENV_VARS.max_gas_per_handler)
+    }
+
+    /// Create a counter that fails once more than `limit` gas has been
+    /// used, instead of the default `GRAPH_MAX_GAS_PER_HANDLER`. The
+    /// `limit` can not be higher than the default
+    pub fn with_limit(metrics: GasMetrics, limit: u64) -> Self {
         Self {
             counter: Arc::new(AtomicU64::new(0)),
             metrics,
+            limit: limit.min(ENV_VARS.max_gas_per_handler),
         }
     }
 
+    /// Whether the gas used has exceeded a limit that is lower than
+    /// `GRAPH_MAX_GAS_PER_HANDLER`, but not the default limit. Since other
+    /// indexers use the default limit, running out of gas then is not a
+    /// deterministic error
+    pub fn exceeded_lowered_limit(&self) -> bool {
+        let used = self.counter.load(SeqCst);
+        used >= self.limit && used < ENV_VARS.max_gas_per_handler
+    }
+
     /// This should be called once per host export
     pub fn consume_host_fn_inner(
         &self,
@@ -112,7 +130,7 @@ impl GasCounter {
             .fetch_update(SeqCst, SeqCst, |v| Some(v.saturating_add(amount.0)))
             .unwrap();
         let new = old.saturating_add(amount.0);
-        if new >= ENV_VARS.max_gas_per_handler {
+        if new >= self.limit {
             Err(DeterministicHostError::gas(anyhow::anyhow!(
                 "Gas limit exceeded. Used: {}",
                 new
@@ -138,3 +156,25 @@ impl GasCounter {
         Gas(self.counter.load(SeqCst))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exceeding_lowered_limit() {
+        let limit = 2 * costs::HOST_EXPORT_GAS.0;
+        let gas = GasCounter::with_limit(GasMetrics::mock(), limit);
+        assert!(gas.consume_host_fn(Gas::ZERO).is_ok());
+        assert!(!gas.exceeded_lowered_limit());
+        assert!(gas.consume_host_fn(Gas::ZERO).is_err());
+        assert!(gas.exceeded_lowered_limit());
+
+        // The limit can not be raised above the default
+        let gas = GasCounter::with_limit(GasMetrics::mock(), u64::MAX);
+        assert!(gas
+            .consume_host_fn(Gas(ENV_VARS.max_gas_per_handler))
+            .is_err());
+        assert!(!gas.exceeded_lowered_limit());
+    }
+}
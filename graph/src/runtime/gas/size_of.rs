@@ -21,6 +21,7 @@ impl GasSizeOf for Value {
             Value::Bytes(bytes) => bytes.gas_size_of(),
             Value::Bool(bool) => bool.gas_size_of(),
             Value::BigInt(big_int) => big_int.gas_size_of(),
+            Value::Json(json) => json.to_string().gas_size_of(),
         };
         Gas(4) + inner
     }
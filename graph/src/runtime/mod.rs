@@ -261,10 +261,13 @@ pub enum IndexForAscTypeId {
     ArrayH256 = 1002,
     ArrayLog = 1003,
     ArrayTypedMapStringStoreValue = 1004,
+    ArraySmartContractCall = 1005,
+    ArrayArrayEthereumValue = 1006,
+    StorageSlot = 1007,
     // Continue to add more Ethereum type IDs here.
     // e.g.:
-    // NextEthereumType = 1004,
-    // AnotherEthereumType = 1005,
+    // NextEthereumType = 1008,
+    // AnotherEthereumType = 1009,
     // ...
     // LastEthereumType = 1499,
 
@@ -9,9 +9,12 @@ use thiserror::Error;
 
 use crate::cheap_clone::CheapClone;
 use crate::data::graphql::{ObjectOrInterface, ObjectTypeExt, TypeExt};
-use crate::data::store::IdType;
+use crate::data::store::{IdType, ValueType};
 use crate::env::ENV_VARS;
-use crate::schema::{ast, META_FIELD_NAME, META_FIELD_TYPE, SCHEMA_TYPE_NAME};
+use crate::schema::{
+    ast, FulltextAlgorithm, FUZZY_DEFAULT_THRESHOLD, META_FIELD_NAME, META_FIELD_TYPE,
+    SCHEMA_TYPE_NAME,
+};
 
 use crate::data::graphql::ext::{
     camel_cased_names, DefinitionExt, DirectiveExt, DocumentExt, ValueExt,
@@ -38,7 +41,18 @@ pub enum APISchemaError {
 // The followoing types are defined in meta.graphql
 const BLOCK_HEIGHT: &str = "Block_height";
 const CHANGE_BLOCK_FILTER_NAME: &str = "BlockChangedFilter";
+const JSON_PATH_FILTER_NAME: &str = "JSONPathFilter";
 const ERROR_POLICY_TYPE: &str = "_SubgraphErrorPolicy_";
+const PAGE_INFO_TYPE: &str = "_PageInfo_";
+
+/// The suffix of the type returned by `<entity>Aggregate` query fields.
+/// The type for entity `Token` is `Token_aggregate`, and the types for its
+/// `sum`, `min`, `max`, and `avg` fields are `Token_aggregate_sum` etc.
+pub const ENTITY_AGGREGATE_SUFFIX: &str = "_aggregate";
+
+/// The suffix of the type returned by `<entities>Connection` query fields.
+/// The type for entity `Token` is `Token_connection`
+pub const ENTITY_CONNECTION_SUFFIX: &str = "_connection";
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, CheapClone)]
 pub enum ErrorPolicy {
@@ -352,6 +366,15 @@ fn add_introspection_schema(schema: &mut s::Document) {
 /// all its fields and their input arguments, based on the existing types.
 pub(in crate::schema) fn api_schema(
     input_schema: &InputSchema,
+) -> Result<s::Document, APISchemaError> {
+    derive_api_schema(input_schema, ENV_VARS.graphql.entity_aggregates)
+}
+
+/// Derive the API schema; if `entity_aggregates` is `true`, the `Query`
+/// type gets an `<entity>Aggregate` field for each entity type
+fn derive_api_schema(
+    input_schema: &InputSchema,
+    entity_aggregates: bool,
 ) -> Result<s::Document, APISchemaError> {
     // Refactor: Don't clone the schema.
     let mut api = init_api_schema(input_schema)?;
@@ -359,7 +382,11 @@ pub(in crate::schema) fn api_schema(
     add_types_for_object_types(&mut api, input_schema)?;
     add_types_for_interface_types(&mut api, input_schema)?;
     add_types_for_aggregation_types(&mut api, input_schema)?;
-    add_query_type(&mut api.document, input_schema)?;
+    let mut extra_fields = add_connection_types(&mut api.document, input_schema);
+    if entity_aggregates {
+        extra_fields.extend(add_entity_aggregate_types(&mut api.document, input_schema));
+    }
+    add_query_type(&mut api.document, input_schema, extra_fields)?;
     add_subscription_type(&mut api.document, input_schema)?;
     Ok(api.document)
 }
@@ -498,6 +525,167 @@ fn add_types_for_aggregation_types(
     Ok(())
 }
 
+/// Adds a `<type>_aggregate` type for each object type to the schema and
+/// returns the `<type>Aggregate` query fields that return them. Object
+/// types whose aggregate type name is already taken are skipped
+fn add_entity_aggregate_types(api: &mut s::Document, input_schema: &InputSchema) -> Vec<s::Field> {
+    fn object_type(name: String, description: String, fields: Vec<s::Field>) -> s::Definition {
+        s::Definition::TypeDefinition(s::TypeDefinition::Object(s::ObjectType {
+            position: Pos::default(),
+            description: Some(description),
+            name,
+            implements_interfaces: vec![],
+            directives: vec![],
+            fields,
+        }))
+    }
+
+    fn field(name: &str, field_type: s::Type) -> s::Field {
+        s::Field {
+            position: Pos::default(),
+            description: None,
+            name: name.to_owned(),
+            arguments: vec![],
+            field_type,
+            directives: vec![],
+        }
+    }
+
+    fn non_null(type_name: &str) -> s::Type {
+        s::Type::NonNullType(Box::new(s::Type::NamedType(type_name.to_owned())))
+    }
+
+    let mut query_fields = vec![];
+    for (name, obj_type) in input_schema.object_types() {
+        let agg_name = format!("{}{}", name, ENTITY_AGGREGATE_SUFFIX);
+        if api.get_named_type(&agg_name).is_some() {
+            continue;
+        }
+
+        let numeric: Vec<_> = obj_type
+            .fields
+            .iter()
+            .filter(|field| field.name.as_str() != "id" && !field.is_list())
+            .filter(|field| field.value_type.is_numeric())
+            .collect();
+
+        let mut agg_fields = vec![s::Field {
+            description: Some(format!("The number of matching `{}` entities", name)),
+            ..field("count", non_null("Int8"))
+        }];
+        if !numeric.is_empty() {
+            for op in ["sum", "min", "max", "avg"] {
+                let op_name = format!("{}_{}", agg_name, op);
+                let fields = numeric
+                    .iter()
+                    .map(|attr| {
+                        let value_type = match (op, attr.value_type) {
+                            ("avg", _) => ValueType::BigDecimal,
+                            ("sum", ValueType::Int) => ValueType::Int8,
+                            ("sum", ValueType::Int8) => ValueType::BigInt,
+                            (_, value_type) => value_type,
+                        };
+                        let field_type = s::Type::NamedType(value_type.to_str().to_owned());
+                        field(&attr.name, field_type)
+                    })
+                    .collect();
+                let description = format!(
+                    "The `{}` of the numeric fields of matching `{}` entities; \
+                     it is `null` if there are none",
+                    op, name
+                );
+                api.definitions
+                    .push(object_type(op_name.clone(), description, fields));
+                agg_fields.push(field(op, non_null(&op_name)));
+            }
+        }
+        let description = format!("Aggregates over `{}` entities", name);
+        api.definitions
+            .push(object_type(agg_name.clone(), description, agg_fields));
+
+        let (singular, _) = camel_cased_names(name);
+        query_fields.push(s::Field {
+            position: Pos::default(),
+            description: Some(format!(
+                "Aggregates over all `{}` entities that match `where`",
+                name
+            )),
+            name: format!("{}Aggregate", singular),
+            arguments: vec![
+                input_value("where", "", s::Type::NamedType(format!("{}_filter", name))),
+                block_argument(),
+                subgraph_error_argument(),
+            ],
+            field_type: non_null(&agg_name),
+            directives: vec![],
+        });
+    }
+    query_fields
+}
+
+/// Adds a `<type>_connection` type for each object and interface type to
+/// the schema and returns the `<types>Connection` query fields that return
+/// them. Types whose connection type name is already taken are skipped
+fn add_connection_types(api: &mut s::Document, input_schema: &InputSchema) -> Vec<s::Field> {
+    fn field(name: &str, description: &str, field_type: s::Type) -> s::Field {
+        s::Field {
+            position: Pos::default(),
+            description: Some(description.to_owned()),
+            name: name.to_owned(),
+            arguments: vec![],
+            field_type,
+            directives: vec![],
+        }
+    }
+
+    let mut query_fields = vec![];
+    for name in input_schema
+        .object_types()
+        .map(|(name, _)| name)
+        .chain(input_schema.interface_types().map(|(name, _)| name))
+    {
+        let conn_name = format!("{}{}", name, ENTITY_CONNECTION_SUFFIX);
+        if api.get_named_type(&conn_name).is_some() {
+            continue;
+        }
+
+        let nodes_type = s::Type::NonNullType(Box::new(s::Type::ListType(Box::new(
+            s::Type::NonNullType(Box::new(s::Type::NamedType(name.to_owned()))),
+        ))));
+        let page_info_type =
+            s::Type::NonNullType(Box::new(s::Type::NamedType(PAGE_INFO_TYPE.to_owned())));
+        let fields = vec![
+            field("nodes", "The entities on this page", nodes_type),
+            field("pageInfo", "Information for paging", page_info_type),
+        ];
+        api.definitions
+            .push(s::Definition::TypeDefinition(s::TypeDefinition::Object(
+                s::ObjectType {
+                    position: Pos::default(),
+                    description: Some(format!("A page of `{}` entities", name)),
+                    name: conn_name.clone(),
+                    implements_interfaces: vec![],
+                    directives: vec![],
+                    fields,
+                },
+            )));
+
+        let (_, plural) = camel_cased_names(name);
+        query_fields.push(s::Field {
+            position: Pos::default(),
+            description: Some(format!(
+                "A page of `{}` entities together with cursors for the pages around it",
+                name
+            )),
+            name: format!("{}Connection", plural),
+            arguments: collection_arguments_with_cursors(name),
+            field_type: s::Type::NonNullType(Box::new(s::Type::NamedType(conn_name))),
+            directives: vec![],
+        });
+    }
+    query_fields
+}
+
 /// Adds a `<type_name>_orderBy` enum type for the given fields to the schema.
 fn add_order_by_type(
     api: &mut s::Document,
@@ -674,17 +862,33 @@ impl FilterOps {
         let filter = input_value("where", "", filter_type);
 
         let order_by = match self {
+            // `orderBy` and `orderDirection` are lists so that entities can
+            // be sorted by several attributes; since GraphQL turns a single
+            // value into a list, they still accept one attribute and
+            // direction
             FilterOps::Object => vec![
-                input_value(
-                    "orderBy",
-                    "",
-                    s::Type::NamedType(format!("{}_orderBy", type_name)),
-                ),
-                input_value(
-                    "orderDirection",
-                    "",
-                    s::Type::NamedType("OrderDirection".to_string()),
-                ),
+                s::InputValue {
+                    description: Some(
+                        "The attributes to sort by, in order of precedence".to_owned(),
+                    ),
+                    ..input_value(
+                        "orderBy",
+                        "",
+                        list_of_non_null(format!("{}_orderBy", type_name)),
+                    )
+                },
+                s::InputValue {
+                    description: Some(
+                        "The direction for the attribute at the same position in `orderBy`; \
+                         the last direction also applies to any remaining attributes"
+                            .to_owned(),
+                    ),
+                    ..input_value(
+                        "orderDirection",
+                        "",
+                        list_of_non_null("OrderDirection".to_string()),
+                    )
+                },
             ],
             FilterOps::Aggregation => vec![input_value(
                 "interval",
@@ -865,6 +1069,7 @@ fn field_filter_ops(set: FilterOpsSet<'_>) -> &'static [&'static str] {
             "not_contains",
         ],
         Object("ID") => &["", "not", "gt", "lt", "gte", "lte", "in", "not_in"],
+        Object("JSON") => &["", "not", "contains", "not_contains", "path"],
         Object("BigInt") | Object("BigDecimal") | Object("Int") | Object("Int8")
         | Object("Timestamp") => &["", "not", "gt", "lt", "gte", "lte", "in", "not_in"],
         Object("String") => &[
@@ -913,6 +1118,7 @@ fn field_scalar_filter_input_values(
                 "in" | "not_in" => {
                     s::Type::ListType(Box::new(s::Type::NonNullType(Box::new(field_type))))
                 }
+                "path" => s::Type::NamedType(JSON_PATH_FILTER_NAME.to_string()),
                 _ => field_type,
             };
             input_value(&field.name, filter_type, value_type)
@@ -1017,6 +1223,13 @@ fn field_list_filter_input_values(
     Ok(Some(input_values))
 }
 
+/// The type `[<type_name>!]`
+fn list_of_non_null(type_name: String) -> s::Type {
+    s::Type::ListType(Box::new(s::Type::NonNullType(Box::new(
+        s::Type::NamedType(type_name),
+    ))))
+}
+
 /// Generates a `*_filter` input value for the given field name, suffix and value type.
 fn input_value(name: &str, suffix: &'static str, value_type: s::Type) -> s::InputValue {
     s::InputValue {
@@ -1033,8 +1246,14 @@ fn input_value(name: &str, suffix: &'static str, value_type: s::Type) -> s::Inpu
     }
 }
 
-/// Adds a root `Query` object type to the schema.
-fn add_query_type(api: &mut s::Document, input_schema: &InputSchema) -> Result<(), APISchemaError> {
+/// Adds a root `Query` object type to the schema. The `extra_fields` are
+/// added after the fields for querying entities, except for the ones
+/// whose names are already taken by those fields
+fn add_query_type(
+    api: &mut s::Document,
+    input_schema: &InputSchema,
+    mut extra_fields: Vec<s::Field>,
+) -> Result<(), APISchemaError> {
     let type_name = String::from("Query");
 
     if api.get_named_type(&type_name).is_some() {
@@ -1045,7 +1264,7 @@ fn add_query_type(api: &mut s::Document, input_schema: &InputSchema) -> Result<(
         .object_types()
         .map(|(name, _)| name)
         .chain(input_schema.interface_types().map(|(name, _)| name))
-        .flat_map(|name| query_fields_for_type(name, FilterOps::Object))
+        .flat_map(query_fields_for_type)
         .collect::<Vec<s::Field>>();
     let mut agg_fields = input_schema
         .aggregation_types()
@@ -1060,6 +1279,8 @@ fn add_query_type(api: &mut s::Document, input_schema: &InputSchema) -> Result<(
         .collect();
     fields.append(&mut agg_fields);
     fields.append(&mut fulltext_fields);
+    extra_fields.retain(|extra| !fields.iter().any(|field| field.name == extra.name));
+    fields.append(&mut extra_fields);
     fields.push(meta_field());
 
     let typedef = s::TypeDefinition::Object(s::ObjectType {
@@ -1121,6 +1342,22 @@ fn query_field_for_fulltext(fulltext: &s::Directive) -> Option<s::Field> {
         ),
     ];
 
+    let algorithm = fulltext.argument("algorithm").unwrap().as_enum().unwrap();
+    if FulltextAlgorithm::try_from(algorithm) == Ok(FulltextAlgorithm::Fuzzy) {
+        // threshold: Float
+        arguments.push(s::InputValue {
+            position: Pos::default(),
+            description: Some(
+                "Minimum word similarity between `text` and the indexed fields, between 0 and 1"
+                    .to_string(),
+            ),
+            name: String::from("threshold"),
+            value_type: s::Type::NamedType(String::from("Float")),
+            default_value: Some(s::Value::Float(FUZZY_DEFAULT_THRESHOLD)),
+            directives: vec![],
+        });
+    }
+
     arguments.push(subgraph_error_argument());
 
     Some(s::Field {
@@ -1150,7 +1387,7 @@ fn add_subscription_type(
         .object_types()
         .map(|(name, _)| name)
         .chain(input_schema.interface_types().map(|(name, _)| name))
-        .flat_map(|name| query_fields_for_type(name, FilterOps::Object))
+        .flat_map(query_fields_for_type)
         .collect();
     let mut agg_fields = input_schema
         .aggregation_types()
@@ -1180,9 +1417,12 @@ fn block_argument() -> s::InputValue {
             "The block at which the query should be executed. \
              Can either be a `{ hash: Bytes }` value containing a block hash, \
              a `{ number: Int }` containing the block number, \
-             or a `{ number_gte: Int }` containing the minimum block number. \
+             a `{ number_gte: Int }` containing the minimum block number, \
+             or a `{ timestamp_lte: Int8 }` containing the maximum block timestamp. \
              In the case of `number_gte`, the query will be executed on the latest block only if \
              the subgraph has progressed to or past the minimum block number. \
+             In the case of `timestamp_lte`, the query will be executed on the latest indexed \
+             block whose timestamp is at or before the given timestamp. \
              Defaults to the latest block when omitted."
                 .to_owned(),
         ),
@@ -1218,10 +1458,31 @@ fn subgraph_error_argument() -> s::InputValue {
     }
 }
 
+/// Generates the arguments for top-level collection fields of an object or
+/// interface type, which, unlike nested collection fields, support paging
+/// with the `after` and `before` cursors
+fn collection_arguments_with_cursors(type_name: &str) -> Vec<s::InputValue> {
+    let mut arguments = FilterOps::Object.collection_arguments(type_name);
+    arguments.push(s::InputValue {
+        description: Some(
+            "Only return entities that come after the entity with this cursor".to_owned(),
+        ),
+        ..input_value("after", "", s::Type::NamedType("String".to_owned()))
+    });
+    arguments.push(s::InputValue {
+        description: Some(
+            "Only return entities that come before the entity with this cursor".to_owned(),
+        ),
+        ..input_value("before", "", s::Type::NamedType("String".to_owned()))
+    });
+    arguments.push(block_argument());
+    arguments.push(subgraph_error_argument());
+    arguments
+}
+
 /// Generates `Query` fields for the given type name (e.g. `users` and `user`).
-fn query_fields_for_type(type_name: &str, ops: FilterOps) -> Vec<s::Field> {
-    let mut collection_arguments = ops.collection_arguments(type_name);
-    collection_arguments.push(block_argument());
+fn query_fields_for_type(type_name: &str) -> Vec<s::Field> {
+    let collection_arguments = collection_arguments_with_cursors(type_name);
 
     let mut by_id_arguments = vec![
         s::InputValue {
@@ -1235,7 +1496,6 @@ fn query_fields_for_type(type_name: &str, ops: FilterOps) -> Vec<s::Field> {
         block_argument(),
     ];
 
-    collection_arguments.push(subgraph_error_argument());
     by_id_arguments.push(subgraph_error_argument());
 
     // Name formatting must be updated in sync with `graph::data::schema::validate_fulltext_directive_name()`
@@ -1373,6 +1633,9 @@ mod tests {
         schema
             .get_named_type("Timestamp")
             .expect("Timestamp type is missing in API schema");
+        schema
+            .get_named_type("JSON")
+            .expect("JSON type is missing in API schema");
     }
 
     #[test]
@@ -1598,6 +1861,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn api_schema_contains_json_filters() {
+        let schema = parse(
+            r#"
+              type Token @entity {
+                  id: ID!
+                  metadata: JSON
+              }
+            "#,
+        );
+
+        let Some(TypeDefinition::InputObject(token_filter)) = schema.get_named_type("Token_filter")
+        else {
+            panic!("Token_filter type is missing in derived API schema");
+        };
+
+        let metadata_filters = token_filter
+            .fields
+            .iter()
+            .filter(|field| field.name.starts_with("metadata"))
+            .map(|field| (field.name.as_str(), field.value_type.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            metadata_filters,
+            [
+                ("metadata", "JSON".to_string()),
+                ("metadata_not", "JSON".to_string()),
+                ("metadata_contains", "JSON".to_string()),
+                ("metadata_not_contains", "JSON".to_string()),
+                ("metadata_path", "JSONPathFilter".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn api_schema_contains_object_type_filter_enum() {
         let schema = parse(
@@ -2005,6 +2302,8 @@ mod tests {
                 "orderBy",
                 "orderDirection",
                 "where",
+                "after",
+                "before",
                 "block",
                 "subgraphError",
             ]
@@ -2100,6 +2399,8 @@ mod tests {
                 "orderBy",
                 "orderDirection",
                 "where",
+                "after",
+                "before",
                 "block",
                 "subgraphError"
             ]
@@ -2149,6 +2450,198 @@ type Gravatar @entity {
         .expect("\"metadata\" field is missing on Query type");
     }
 
+    #[test]
+    fn api_schema_contains_threshold_for_fuzzy_fulltext() {
+        const SCHEMA: &str = r#"
+type _Schema_ @fulltext(
+  name: "tokenSearch"
+  language: simple
+  algorithm: fuzzy
+  include: [
+    {
+      entity: "Token",
+      fields: [
+        { name: "name"},
+        { name: "symbol"},
+      ]
+    }
+  ]
+)
+type Token @entity {
+  id: ID!
+  name: String!
+  symbol: String!
+}
+"#;
+        let schema = parse(SCHEMA);
+        let field = query_field(&schema, "tokenSearch");
+        let threshold = field
+            .arguments
+            .iter()
+            .find(|arg| arg.name == "threshold")
+            .expect("fuzzy fulltext field has a threshold argument");
+        assert_eq!(threshold.value_type.to_string(), "Float");
+        assert_eq!(
+            threshold.default_value,
+            Some(s::Value::Float(FUZZY_DEFAULT_THRESHOLD))
+        );
+    }
+
+    #[test]
+    fn api_schema_contains_entity_aggregates() {
+        const SCHEMA: &str = r#"
+type Token @entity {
+  id: ID!
+  name: String!
+  decimals: Int!
+  supply: BigInt!
+  price: BigDecimal
+  holders: [Int!]!
+}
+"#;
+        let input_schema = InputSchema::parse(LATEST_VERSION, SCHEMA, ID.clone())
+            .expect("Failed to parse input schema");
+        let mut schema = input_schema.schema().clone();
+        schema.document = super::derive_api_schema(&input_schema, true)
+            .expect("Failed to derive API schema with aggregates");
+        let schema = ApiSchema::from_api_schema(schema).unwrap();
+
+        let field = query_field(&schema, "tokenAggregate");
+        assert_eq!(field.field_type.to_string(), "Token_aggregate!");
+        let args: Vec<_> = field
+            .arguments
+            .iter()
+            .map(|arg| arg.name.as_str())
+            .collect();
+        assert_eq!(args, ["where", "block", "subgraphError"]);
+
+        let fields = |type_name: &str| -> Vec<String> {
+            match schema.get_named_type(type_name) {
+                Some(TypeDefinition::Object(t)) => t
+                    .fields
+                    .iter()
+                    .map(|field| format!("{}: {}", field.name, field.field_type))
+                    .collect(),
+                _ => panic!("expected an object type `{}`", type_name),
+            }
+        };
+        assert_eq!(
+            fields("Token_aggregate"),
+            [
+                "count: Int8!",
+                "sum: Token_aggregate_sum!",
+                "min: Token_aggregate_min!",
+                "max: Token_aggregate_max!",
+                "avg: Token_aggregate_avg!"
+            ]
+        );
+        assert_eq!(
+            fields("Token_aggregate_sum"),
+            ["decimals: Int8", "supply: BigInt", "price: BigDecimal"]
+        );
+        assert_eq!(
+            fields("Token_aggregate_max"),
+            ["decimals: Int", "supply: BigInt", "price: BigDecimal"]
+        );
+        assert_eq!(
+            fields("Token_aggregate_avg"),
+            [
+                "decimals: BigDecimal",
+                "supply: BigDecimal",
+                "price: BigDecimal"
+            ]
+        );
+
+        // Aggregates are only added when they are turned on
+        let mut schema = input_schema.schema().clone();
+        schema.document = super::derive_api_schema(&input_schema, false)
+            .expect("Failed to derive API schema without aggregates");
+        let schema = ApiSchema::from_api_schema(schema).unwrap();
+        assert!(schema.get_named_type("Token_aggregate").is_none());
+    }
+
+    #[test]
+    fn api_schema_contains_connections() {
+        const SCHEMA: &str = r#"
+interface Named {
+  id: ID!
+  name: String!
+}
+
+type Token implements Named @entity {
+  id: ID!
+  name: String!
+}
+"#;
+        let schema = parse(SCHEMA);
+
+        for (field_name, entity_name) in
+            [("tokensConnection", "Token"), ("namedsConnection", "Named")]
+        {
+            let type_name = format!("{}_connection", entity_name);
+            let field = query_field(&schema, field_name);
+            assert_eq!(field.field_type.to_string(), format!("{}!", type_name));
+            let args: Vec<_> = field
+                .arguments
+                .iter()
+                .map(|arg| arg.name.as_str())
+                .collect();
+            assert_eq!(
+                args,
+                [
+                    "skip",
+                    "first",
+                    "orderBy",
+                    "orderDirection",
+                    "where",
+                    "after",
+                    "before",
+                    "block",
+                    "subgraphError"
+                ]
+            );
+
+            let fields: Vec<_> = match schema.get_named_type(&type_name) {
+                Some(TypeDefinition::Object(t)) => t
+                    .fields
+                    .iter()
+                    .map(|field| format!("{}: {}", field.name, field.field_type))
+                    .collect(),
+                _ => panic!("expected an object type `{}`", type_name),
+            };
+            assert_eq!(
+                fields,
+                [
+                    format!("nodes: [{}!]!", entity_name),
+                    "pageInfo: _PageInfo_!".to_string()
+                ]
+            );
+        }
+        assert!(schema.get_named_type("_PageInfo_").is_some());
+    }
+
+    #[test]
+    fn api_schema_order_by_accepts_lists() {
+        let schema = parse(
+            "type User @entity { id: ID!, name: String!, age: Int! }
+             type Post @entity { id: ID!, title: String!, author: User! }",
+        );
+
+        for (field_name, entity_name) in [("users", "User"), ("posts", "Post")] {
+            let field = query_field(&schema, field_name);
+            let arg_type = |name: &str| {
+                field
+                    .arguments
+                    .iter()
+                    .find(|arg| arg.name == name)
+                    .map(|arg| arg.value_type.to_string())
+                    .unwrap_or_else(|| panic!("`{}` has no argument `{}`", field_name, name))
+            };
+            assert_eq!(arg_type("orderBy"), format!("[{}_orderBy!]", entity_name));
+            assert_eq!(arg_type("orderDirection"), "[OrderDirection!]");
+        }
+    }
+
     #[test]
     fn intf_implements_intf() {
         const SCHEMA: &str = r#"
@@ -2229,6 +2722,8 @@ type Gravatar @entity {
             id: Int8!
             timestamp: Timestamp!
             sum: BigDecimal! @aggregate(fn: "sum", arg: "value")
+            avg: BigDecimal! @aggregate(fn: "avg", arg: "value")
+            median: BigDecimal! @aggregate(fn: "percentile", arg: "value", fraction: 0.5)
         }
 
         type Stuff @entity {
@@ -2303,6 +2798,19 @@ type Gravatar @entity {
         };
         let stats = stuff.field("stats").unwrap();
         assert_aggregation_field(&schema, stats, "Stats");
+
+        // All aggregates are exposed as fields on the aggregation type,
+        // regardless of the aggregation function
+        let s::TypeDefinition::Object(stats) = schema
+            .get_type_definition_from_type(&s::Type::NamedType("Stats".to_string()))
+            .unwrap()
+        else {
+            panic!("Stats type is missing")
+        };
+        for name in ["sum", "avg", "median"] {
+            let field = stats.field(name).unwrap();
+            assert_eq!("BigDecimal", field.field_type.get_base_type());
+        }
     }
 
     #[test]
@@ -36,6 +36,7 @@ pub enum FilterOp {
     Child,
     And,
     Or,
+    JsonPath,
 }
 
 /// Split a "name_eq" style name into an attribute ("name") and a filter op (`Equal`).
@@ -69,6 +70,7 @@ pub fn parse_field_as_filter(key: &str) -> (String, FilterOp) {
         k if k.ends_with("_ends_with") => ("_ends_with", FilterOp::EndsWith),
         k if k.ends_with("_ends_with_nocase") => ("_ends_with_nocase", FilterOp::EndsWithNoCase),
         k if k.ends_with('_') => ("_", FilterOp::Child),
+        k if k.ends_with("_path") => ("_path", FilterOp::JsonPath),
         k if k.eq("and") => ("and", FilterOp::And),
         k if k.eq("or") => ("or", FilterOp::Or),
         _ => ("", FilterOp::Equal),
@@ -78,10 +78,19 @@ impl FulltextLanguage {
     }
 }
 
+/// The default value for the `threshold` argument of fuzzy fulltext
+/// queries. It is the same as the default for Postgres'
+/// `pg_trgm.word_similarity_threshold`, which the database must not set
+/// any higher
+pub const FUZZY_DEFAULT_THRESHOLD: f64 = 0.6;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum FulltextAlgorithm {
     Rank,
     ProximityRank,
+    /// Match with trigram similarity from `pg_trgm` instead of a
+    /// `tsvector`. That tolerates typos and partial words
+    Fuzzy,
 }
 
 impl TryFrom<&str> for FulltextAlgorithm {
@@ -90,8 +99,9 @@ impl TryFrom<&str> for FulltextAlgorithm {
         match algorithm {
             "rank" => Ok(FulltextAlgorithm::Rank),
             "proximityRank" => Ok(FulltextAlgorithm::ProximityRank),
+            "fuzzy" => Ok(FulltextAlgorithm::Fuzzy),
             invalid => Err(format!(
-                "The provided fulltext search algorithm {} is invalid. It must be one of: rank, proximityRank",
+                "The provided fulltext search algorithm {} is invalid. It must be one of: rank, proximityRank, fuzzy",
                 invalid,
             )),
         }
@@ -104,6 +114,12 @@ pub struct FulltextConfig {
     pub algorithm: FulltextAlgorithm,
 }
 
+impl FulltextConfig {
+    pub fn is_fuzzy(&self) -> bool {
+        self.algorithm == FulltextAlgorithm::Fuzzy
+    }
+}
+
 pub struct FulltextDefinition {
     pub config: FulltextConfig,
     pub included_fields: HashSet<String>,
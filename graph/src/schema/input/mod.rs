@@ -5,6 +5,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{anyhow, Error};
+use chrono::{DateTime, Datelike, Months, NaiveDate};
 use semver::Version;
 use store::Entity;
 
@@ -49,6 +50,7 @@ pub mod kw {
     pub const INTERVALS: &str = "intervals";
     pub const INTERVAL: &str = "interval";
     pub const CUMULATIVE: &str = "cumulative";
+    pub const FRACTION: &str = "fraction";
 }
 
 /// The internal representation of a subgraph schema, i.e., the
@@ -610,6 +612,11 @@ pub enum AggregateFn {
     Count,
     First,
     Last,
+    Avg,
+    Stddev,
+    Variance,
+    CountDistinct,
+    Percentile,
 }
 
 impl FromStr for AggregateFn {
@@ -623,6 +630,11 @@ impl FromStr for AggregateFn {
             "count" => Ok(AggregateFn::Count),
             "first" => Ok(AggregateFn::First),
             "last" => Ok(AggregateFn::Last),
+            "avg" => Ok(AggregateFn::Avg),
+            "stddev" => Ok(AggregateFn::Stddev),
+            "variance" => Ok(AggregateFn::Variance),
+            "countDistinct" => Ok(AggregateFn::CountDistinct),
+            "percentile" => Ok(AggregateFn::Percentile),
             _ => Err(anyhow!("invalid aggregate function `{}`", s)),
         }
     }
@@ -632,11 +644,44 @@ impl AggregateFn {
     pub fn has_arg(&self) -> bool {
         use AggregateFn::*;
         match self {
-            Sum | Max | Min | First | Last => true,
+            Sum | Max | Min | First | Last | Avg | Stddev | Variance | CountDistinct
+            | Percentile => true,
             Count => false,
         }
     }
 
+    /// Return `true` if the aggregate over a set of values can be computed
+    /// from the aggregates over a partition of that set. Only such
+    /// functions can be used for cumulative aggregates
+    pub fn is_decomposable(&self) -> bool {
+        use AggregateFn::*;
+        match self {
+            Sum | Max | Min | Count | First | Last => true,
+            Avg | Stddev | Variance | CountDistinct | Percentile => false,
+        }
+    }
+
+    /// Return `true` if the argument for this function must be numeric.
+    /// `countDistinct` can count values of any type
+    pub fn has_numeric_arg(&self) -> bool {
+        use AggregateFn::*;
+        match self {
+            Sum | Max | Min | Count | First | Last | Avg | Stddev | Variance | Percentile => true,
+            CountDistinct => false,
+        }
+    }
+
+    /// The value types that a field aggregated with this function can
+    /// have. Functions that return `None` accept any numeric type
+    fn field_types(&self) -> Option<&'static [ValueType]> {
+        use AggregateFn::*;
+        match self {
+            Sum | Max | Min | Count | First | Last => None,
+            Avg | Stddev | Variance | Percentile => Some(&[ValueType::BigDecimal]),
+            CountDistinct => Some(&[ValueType::Int, ValueType::Int8]),
+        }
+    }
+
     fn as_str(&self) -> &'static str {
         use AggregateFn::*;
         match self {
@@ -646,32 +691,111 @@ impl AggregateFn {
             Count => "count",
             First => "first",
             Last => "last",
+            Avg => "avg",
+            Stddev => "stddev",
+            Variance => "variance",
+            CountDistinct => "countDistinct",
+            Percentile => "percentile",
         }
     }
 }
 
 /// The supported intervals for timeseries in order of decreasing
-/// granularity. The intervals must all be divisible by the smallest
-/// interval
+/// granularity. All intervals except for `Month` have a fixed length; the
+/// buckets for `Week` start on Mondays and the buckets for `Month` on the
+/// first day of each calendar month (all in UTC)
 #[derive(Clone, Copy, PartialEq, Eq, Debug, PartialOrd, Ord, Hash)]
 pub enum AggregationInterval {
+    Minute,
+    FiveMinutes,
+    FifteenMinutes,
     Hour,
     Day,
+    Week,
+    Month,
 }
 
+/// The number of seconds between the Unix epoch, which was a Thursday, and
+/// the first Monday after it, 1970-01-05
+const WEEK_OFFSET_SECS: i64 = 4 * 24 * 3600;
+
 impl AggregationInterval {
     pub fn as_str(&self) -> &'static str {
         match self {
+            AggregationInterval::Minute => "minute",
+            AggregationInterval::FiveMinutes => "five_minutes",
+            AggregationInterval::FifteenMinutes => "fifteen_minutes",
             AggregationInterval::Hour => "hour",
             AggregationInterval::Day => "day",
+            AggregationInterval::Week => "week",
+            AggregationInterval::Month => "month",
         }
     }
 
-    pub fn as_duration(&self) -> Duration {
+    /// The length of the interval, or `None` if the length of the interval
+    /// varies as it does for `Month`
+    pub fn as_duration(&self) -> Option<Duration> {
         use AggregationInterval::*;
         match self {
-            Hour => Duration::from_secs(3600),
-            Day => Duration::from_secs(3600 * 24),
+            Minute => Some(Duration::from_secs(60)),
+            FiveMinutes => Some(Duration::from_secs(5 * 60)),
+            FifteenMinutes => Some(Duration::from_secs(15 * 60)),
+            Hour => Some(Duration::from_secs(3600)),
+            Day => Some(Duration::from_secs(3600 * 24)),
+            Week => Some(Duration::from_secs(3600 * 24 * 7)),
+            Month => None,
+        }
+    }
+
+    /// Return `true` if every bucket for `self` consists of complete
+    /// buckets for `finer` so that aggregations for `self` can be computed
+    /// from the aggregations for `finer`. Weeks do not line up with months;
+    /// all other shorter intervals are nested in the longer ones
+    pub fn is_composed_of(&self, finer: AggregationInterval) -> bool {
+        use AggregationInterval::*;
+        match (finer, self) {
+            (Week, Month) => false,
+            (_, _) => finer < *self,
+        }
+    }
+
+    /// Return the start of the bucket that contains `time`
+    pub fn bucket_start(&self, time: BlockTime) -> BlockTime {
+        use AggregationInterval::*;
+        match self {
+            Minute | FiveMinutes | FifteenMinutes | Hour | Day => {
+                let length = self.as_duration().unwrap();
+                BlockTime::from(length * time.bucket(length) as u32)
+            }
+            Week => {
+                let length = self.as_duration().unwrap().as_secs() as i64;
+                let secs = time.as_secs_since_epoch().max(0) - WEEK_OFFSET_SECS;
+                BlockTime::since_epoch(secs.div_euclid(length) * length + WEEK_OFFSET_SECS, 0)
+            }
+            Month => {
+                let dt = DateTime::from_timestamp(time.as_secs_since_epoch().max(0), 0).unwrap();
+                let start = NaiveDate::from_ymd_opt(dt.year(), dt.month(), 1)
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .unwrap()
+                    .and_utc();
+                BlockTime::since_epoch(start.timestamp(), 0)
+            }
+        }
+    }
+
+    /// Return the start of the bucket that follows the bucket that starts
+    /// at `start`
+    fn next_bucket_start(&self, start: BlockTime) -> BlockTime {
+        match self.as_duration() {
+            Some(length) => {
+                BlockTime::since_epoch(start.as_secs_since_epoch() + length.as_secs() as i64, 0)
+            }
+            None => {
+                let dt = DateTime::from_timestamp(start.as_secs_since_epoch(), 0)
+                    .and_then(|dt| dt.checked_add_months(Months::new(1)))
+                    .unwrap();
+                BlockTime::since_epoch(dt.timestamp(), 0)
+            }
         }
     }
 
@@ -680,16 +804,15 @@ impl AggregationInterval {
     /// that overlap `from..to` and end before `to`. The ranges are in
     /// increasing order of the start time
     pub fn buckets(&self, from: BlockTime, to: BlockTime) -> Vec<Range<BlockTime>> {
-        let first = from.bucket(self.as_duration());
-        let last = to.bucket(self.as_duration());
-        (first..last)
-            .map(|nr| self.as_duration() * nr as u32)
-            .map(|start| {
-                let lower = BlockTime::from(start);
-                let upper = BlockTime::from(start + self.as_duration());
-                lower..upper
-            })
-            .collect()
+        let last = self.bucket_start(to);
+        let mut start = self.bucket_start(from);
+        let mut buckets = Vec::new();
+        while start < last {
+            let end = self.next_bucket_start(start);
+            buckets.push(start..end);
+            start = end;
+        }
+        buckets
     }
 }
 
@@ -701,7 +824,7 @@ impl std::fmt::Display for AggregationInterval {
 
 #[test]
 fn buckets() {
-    // 2006-07-16 07:40Z
+    // 2006-07-16 07:40Z, a Sunday
     const START: i64 = 1153035600;
     // 2006-07-16 08:00Z, the start of the next hourly bucket after `START`
     const EIGHT_AM: i64 = 1153036800;
@@ -724,6 +847,53 @@ fn buckets() {
     );
     assert_eq!(vec![eight_am..nine_am], Hour.buckets(one_hour, two_hour));
     assert_eq!(Vec::<Range<BlockTime>>::new(), Day.buckets(start, two_hour));
+
+    assert!(Day.is_composed_of(Hour));
+    assert!(Month.is_composed_of(Day));
+    assert!(Week.is_composed_of(FifteenMinutes));
+    assert!(!Month.is_composed_of(Week));
+    assert!(!Hour.is_composed_of(Hour));
+    assert!(!Hour.is_composed_of(Day));
+
+    let minute = BlockTime::since_epoch(START + 60, 0);
+    let seven_thirty = BlockTime::since_epoch(START - 10 * 60, 0);
+    let seven_forty_five = BlockTime::since_epoch(START + 5 * 60, 0);
+    assert_eq!(
+        vec![start..minute],
+        Minute.buckets(start, BlockTime::since_epoch(START + 90, 0))
+    );
+    assert_eq!(
+        vec![seven_thirty..seven_forty_five, seven_forty_five..eight_am],
+        FifteenMinutes.buckets(start, eight_am)
+    );
+
+    // Weeks start on Monday, months on the first of the month
+    let one_day = BlockTime::since_epoch(START + 24 * 3600, 0);
+    // 2006-07-10 and 2006-07-17, both Mondays
+    let monday = BlockTime::since_epoch(1152489600, 0);
+    let next_monday = BlockTime::since_epoch(1153094400, 0);
+    assert_eq!(vec![monday..next_monday], Week.buckets(start, one_day));
+    assert_eq!(
+        Vec::<Range<BlockTime>>::new(),
+        Week.buckets(start, one_hour)
+    );
+
+    // 2006-07-01, 2006-08-01, and 2006-09-01
+    let july = BlockTime::since_epoch(1151712000, 0);
+    let august = BlockTime::since_epoch(1154390400, 0);
+    let september = BlockTime::since_epoch(1154390400 + 31 * 24 * 3600, 0);
+    assert_eq!(
+        Vec::<Range<BlockTime>>::new(),
+        Month.buckets(start, one_day)
+    );
+    assert_eq!(vec![july..august], Month.buckets(start, august));
+    assert_eq!(
+        vec![july..august, august..september],
+        Month.buckets(
+            start,
+            BlockTime::since_epoch(1154390400 + 40 * 24 * 3600, 0)
+        )
+    );
 }
 
 impl FromStr for AggregationInterval {
@@ -731,8 +901,13 @@ impl FromStr for AggregationInterval {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
+            "minute" => Ok(AggregationInterval::Minute),
+            "five_minutes" => Ok(AggregationInterval::FiveMinutes),
+            "fifteen_minutes" => Ok(AggregationInterval::FifteenMinutes),
             "hour" => Ok(AggregationInterval::Hour),
             "day" => Ok(AggregationInterval::Day),
+            "week" => Ok(AggregationInterval::Week),
+            "month" => Ok(AggregationInterval::Month),
             _ => Err(anyhow!("invalid aggregation interval `{}`", s)),
         }
     }
@@ -765,6 +940,18 @@ impl AggregationMapping {
         let agg_type = self.aggregation(schema).obj_types[self.agg_type].name;
         EntityType::new(schema.cheap_clone(), agg_type)
     }
+
+    /// Return the type for the coarsest interval of the same aggregation
+    /// from which the aggregations for this interval can be computed, or
+    /// `None` if there is no such interval
+    pub fn finer_agg_type(&self, schema: &InputSchema) -> Option<EntityType> {
+        let aggregation = self.aggregation(schema);
+        aggregation
+            .intervals
+            .iter()
+            .rposition(|finer| self.interval.is_composed_of(*finer))
+            .map(|pos| EntityType::new(schema.cheap_clone(), aggregation.obj_types[pos].name))
+    }
 }
 
 /// The `@aggregate` annotation in an aggregation. The annotation controls
@@ -783,6 +970,9 @@ pub struct Aggregate {
     pub value_type: ValueType,
     /// Whether the aggregation is cumulative
     pub cumulative: bool,
+    /// The fraction for `percentile` aggregates, a number between 0 and 1.
+    /// It is `None` for all other functions
+    pub fraction: Option<f64>,
 }
 
 impl Aggregate {
@@ -809,12 +999,17 @@ impl Aggregate {
                 _ => unreachable!("validation ensures this is a boolean"),
             })
             .unwrap_or(false);
+        let fraction = dir.argument(kw::FRACTION).map(|arg| match arg {
+            Value::Float(f) => *f,
+            _ => unreachable!("validation ensures this is a float"),
+        });
 
         Aggregate {
             name: Word::from(name),
             func,
             arg,
             cumulative,
+            fraction,
             field_type: field_type.clone(),
             value_type: field_type.get_base_type().parse().unwrap(),
         }
@@ -1675,7 +1870,7 @@ mod validations {
                 ext::{DirectiveFinder, FieldExt},
                 DirectiveExt, DocumentExt, ObjectTypeExt, TypeExt, ValueExt,
             },
-            store::{IdType, ValueType, ID},
+            store::{IdType, ValueType, ID, JSON_SCALAR},
             subgraph::SPEC_VERSION_1_1_0,
         },
         prelude::s,
@@ -1954,6 +2149,23 @@ mod validations {
                 .fold(vec![], |errors, (type_name, fields)| {
                     fields.iter().fold(errors, |mut errors, field| {
                         let base = field.field_type.get_base_type();
+                        if base == JSON_SCALAR && field.field_type.is_list() {
+                            errors.push(SchemaValidationError::JsonListField(
+                                type_name.to_string(),
+                                field.name.to_string(),
+                            ));
+                            return errors;
+                        }
+                        if base == JSON_SCALAR {
+                            let path_filter = format!("{}_path", field.name);
+                            if fields.iter().any(|other| other.name == path_filter) {
+                                errors.push(SchemaValidationError::JsonPathFilterCollision(
+                                    type_name.to_string(),
+                                    field.name.to_string(),
+                                    path_filter,
+                                ));
+                            }
+                        }
                         if ValueType::is_scalar(base) {
                             return errors;
                         }
@@ -2464,6 +2676,40 @@ mod validations {
                                     continue;
                                 }
                             };
+                            if let Some(field_types) = func.field_types() {
+                                match field.field_type.value_type() {
+                                    Ok(vt) if field_types.contains(&vt) => { /* ok */ }
+                                    Ok(_) | Err(_) => {
+                                        errors.push(Err::AggregationInvalidFnType(
+                                            agg_type.name.to_owned(),
+                                            field.name.to_owned(),
+                                            func.as_str().to_owned(),
+                                            field_types.iter().map(|vt| vt.to_str()).join(", "),
+                                        ));
+                                        continue;
+                                    }
+                                }
+                            }
+                            match (&func, agg.argument(kw::FRACTION)) {
+                                (AggregateFn::Percentile, Some(s::Value::Float(f)))
+                                    if (0.0..=1.0).contains(f) =>
+                                { /* ok */ }
+                                (AggregateFn::Percentile, None) => {
+                                    errors.push(Err::AggregationMissingFraction(
+                                        agg_type.name.to_owned(),
+                                        field.name.to_owned(),
+                                    ));
+                                    continue;
+                                }
+                                (_, None) => { /* ok */ }
+                                (_, Some(_)) => {
+                                    errors.push(Err::AggregationInvalidFraction(
+                                        agg_type.name.to_owned(),
+                                        field.name.to_owned(),
+                                    ));
+                                    continue;
+                                }
+                            }
                             let arg = match agg.argument(kw::ARG) {
                                 Some(s::Value::String(arg)) => arg,
                                 Some(_) => {
@@ -2489,6 +2735,14 @@ mod validations {
                                 }
                             };
                             match agg.argument(kw::CUMULATIVE) {
+                                Some(s::Value::Boolean(true)) if !func.is_decomposable() => {
+                                    errors.push(Err::AggregationNonCumulativeFn(
+                                        agg_type.name.to_owned(),
+                                        field.name.to_owned(),
+                                        func.as_str().to_owned(),
+                                    ));
+                                    continue;
+                                }
                                 Some(s::Value::Boolean(_)) | None => { /* ok */ }
                                 Some(_) => {
                                     errors.push(Err::AggregationInvalidCumulative(
@@ -2516,6 +2770,12 @@ mod validations {
                             // use a closure instead
                             let check_ident = |ident: &str| -> Result<(), SchemaValidationError> {
                                 let arg_type = match source.field(ident) {
+                                    Some(_) if !func.has_numeric_arg() => {
+                                        // The argument is only used to
+                                        // tell values apart and can have
+                                        // any type
+                                        return Ok(());
+                                    }
                                     Some(arg_field) => match arg_field.field_type.value_type() {
                                         Ok(arg_type) if arg_type.is_numeric() => arg_type,
                                         Ok(_) | Err(_) => {
@@ -2872,6 +3132,48 @@ type A @entity {
             assert_eq!(schema.validate_fields().len(), 0);
         }
 
+        #[test]
+        fn test_json_field_validation() {
+            const ROOT_SCHEMA: &str = r#"
+type A @entity {
+  id: ID!
+  metadata: JSON
+  attributes: [JSON!]!
+}"#;
+
+            let document =
+                graphql_parser::parse_schema(ROOT_SCHEMA).expect("Failed to parse root schema");
+            let schema = BaseSchema::new(DeploymentHash::new("id").unwrap(), document).unwrap();
+            let schema = Schema::new(LATEST_VERSION, &schema);
+            assert_eq!(
+                schema.validate_fields(),
+                vec![SchemaValidationError::JsonListField(
+                    "A".to_string(),
+                    "attributes".to_string()
+                )]
+            );
+
+            const COLLIDING_SCHEMA: &str = r#"
+type A @entity {
+  id: ID!
+  metadata: JSON
+  metadata_path: String
+}"#;
+
+            let document = graphql_parser::parse_schema(COLLIDING_SCHEMA)
+                .expect("Failed to parse root schema");
+            let schema = BaseSchema::new(DeploymentHash::new("id").unwrap(), document).unwrap();
+            let schema = Schema::new(LATEST_VERSION, &schema);
+            assert_eq!(
+                schema.validate_fields(),
+                vec![SchemaValidationError::JsonPathFilterCollision(
+                    "A".to_string(),
+                    "metadata".to_string(),
+                    "metadata_path".to_string()
+                )]
+            );
+        }
+
         #[test]
         fn test_reserved_types_validation() {
             let reserved_types = [
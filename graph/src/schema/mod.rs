@@ -25,10 +25,13 @@ mod input;
 
 pub use api::{is_introspection_field, APISchemaError, INTROSPECTION_QUERY_TYPE};
 
-pub use api::{ApiSchema, ErrorPolicy};
+pub use api::{ApiSchema, ErrorPolicy, ENTITY_AGGREGATE_SUFFIX, ENTITY_CONNECTION_SUFFIX};
 pub use entity_key::EntityKey;
 pub use entity_type::{AsEntityTypeName, EntityType};
-pub use fulltext::{FulltextAlgorithm, FulltextConfig, FulltextDefinition, FulltextLanguage};
+pub use fulltext::{
+    FulltextAlgorithm, FulltextConfig, FulltextDefinition, FulltextLanguage,
+    FUZZY_DEFAULT_THRESHOLD,
+};
 pub use input::sqlexpr::{ExprVisitor, VisitExpr};
 pub(crate) use input::POI_OBJECT;
 pub use input::{
@@ -83,6 +86,10 @@ pub enum SchemaValidationError {
     InvalidSchemaTypeDirectives,
     #[error("Type `{0}`, field `{1}`: type `{2}` is not defined")]
     FieldTypeUnknown(String, String, String), // (type_name, field_name, field_type)
+    #[error("Type `{0}`, field `{1}`: lists of JSON are not supported, use a JSON array instead")]
+    JsonListField(String, String), // (type_name, field_name)
+    #[error("Type `{0}`, field `{1}`: the `{2}` filter for the JSON field collides with the field `{2}`, rename one of them")]
+    JsonPathFilterCollision(String, String, String), // (type_name, field_name, path_filter)
     #[error("Imported type `{0}` does not exist in the `{1}` schema")]
     ImportedTypeUndefined(String, String), // (type_name, schema)
     #[error("Fulltext directive name undefined")]
@@ -177,6 +184,16 @@ pub enum SchemaValidationError {
     AggregationNonNumericArg(String, String, String, String),
     #[error("Field {1} in aggregation {0} has an invalid value for `cumulative`. It needs to be a boolean")]
     AggregationInvalidCumulative(String, String),
+    #[error("Field {1} in aggregation {0} uses the function {2} which can not be used for cumulative aggregations")]
+    AggregationNonCumulativeFn(String, String, String),
+    #[error(
+        "Field {1} in aggregation {0} uses the function {2} and must have one of the types {3}"
+    )]
+    AggregationInvalidFnType(String, String, String, String),
+    #[error("Field {1} in aggregation {0} uses the function `percentile` but is missing the `fraction` argument")]
+    AggregationMissingFraction(String, String),
+    #[error("Field {1} in aggregation {0} has an invalid `fraction`: it must be a number between 0 and 1 and can only be used with `percentile`")]
+    AggregationInvalidFraction(String, String),
     #[error("Aggregations are not supported with spec version {0}; please migrate the subgraph to the latest version")]
     AggregationsNotSupported(Version),
     #[error("Using Int8 as the type for the `id` field is not supported with spec version {0}; please migrate the subgraph to the latest version")]
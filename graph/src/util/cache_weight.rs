@@ -158,6 +158,7 @@ impl CacheWeight for Value {
             Value::List(values) => values.indirect_weight(),
             Value::Bytes(bytes) => bytes.indirect_weight(),
             Value::BigInt(n) => n.indirect_weight(),
+            Value::Json(json) => json.indirect_weight(),
             Value::Timestamp(_) | Value::Int8(_) | Value::Int(_) | Value::Bool(_) | Value::Null => {
                 0
             }
@@ -165,6 +166,21 @@ impl CacheWeight for Value {
     }
 }
 
+impl CacheWeight for serde_json::Value {
+    fn indirect_weight(&self) -> usize {
+        match self {
+            serde_json::Value::Null | serde_json::Value::Bool(_) => 0,
+            serde_json::Value::Number(n) => n.to_string().indirect_weight(),
+            serde_json::Value::String(s) => s.indirect_weight(),
+            serde_json::Value::Array(values) => values.indirect_weight(),
+            serde_json::Value::Object(map) => map
+                .iter()
+                .map(|(k, v)| k.indirect_weight() + v.weight())
+                .sum(),
+        }
+    }
+}
+
 impl CacheWeight for q::Value {
     fn indirect_weight(&self) -> usize {
         match self {
@@ -326,20 +326,19 @@ impl Field {
             })
             .collect();
 
-        // We need to also select the `orderBy` field if there is one
+        // We need to also select the `orderBy` fields if there are any
         use EntityOrder::*;
-        let order_field = match order {
-            Ascending(name, _) | Descending(name, _) => Some(name.as_str()),
-            Default => Some(ID.as_str()),
+        let order_fields = match order {
+            Ascending(name, _) | Descending(name, _) => vec![name.as_str()],
+            Multiple(attrs) => attrs.iter().map(|(name, _, _)| name.as_str()).collect(),
+            Default => vec![ID.as_str()],
             ChildAscending(_) | ChildDescending(_) | Unordered => {
                 // No need to select anything for these
-                None
+                vec![]
             }
         };
-        if let Some(order_field) = order_field {
-            // We assume that `order` only contains valid field names
-            column_names.insert(order_field.to_string());
-        }
+        // We assume that `order` only contains valid field names
+        column_names.extend(order_fields.into_iter().map(str::to_string));
         Ok(AttributeNames::Select(column_names))
     }
 
@@ -20,7 +20,7 @@ use graph::prelude::{
     Logger, TryFromValue, ENV_VARS,
 };
 use graph::schema::ast::{self as sast};
-use graph::schema::ErrorPolicy;
+use graph::schema::{ErrorPolicy, ENTITY_AGGREGATE_SUFFIX, ENTITY_CONNECTION_SUFFIX};
 
 use crate::execution::ast as a;
 use crate::execution::get_field;
@@ -123,6 +123,8 @@ pub struct Query {
     pub selection_set: Arc<a::SelectionSet>,
     /// The ShapeHash of the original query
     pub shape_hash: u64,
+    /// The complexity of the query as computed by `check_complexity`
+    pub complexity: u64,
 
     pub network: Option<String>,
 
@@ -139,6 +141,35 @@ pub struct Query {
     pub query_id: String,
 }
 
+/// Single values are coerced into a list with just that value, so that a
+/// variable of type `T` can be used where `[T]` or `[T!]` is expected.
+/// `VariablesInAllowedPosition` does not allow that; this checks whether it
+/// reported an error for such a variable
+fn is_single_value_for_list(error_code: &str, message: &str) -> bool {
+    if error_code != "VariablesInAllowedPosition" {
+        return false;
+    }
+    // The message has the form `Variable "$v" of type "T" used in position
+    // expecting type "[T!]".`
+    let quoted = |prefix: &str| {
+        message
+            .split_once(prefix)
+            .and_then(|(_, rest)| rest.split('"').next())
+    };
+    let (var_type, expected) = match (quoted("of type \""), quoted("expecting type \"")) {
+        (Some(var_type), Some(expected)) => (var_type, expected),
+        _ => return false,
+    };
+    let expected = match expected.strip_suffix('!') {
+        // A nullable variable can not be used for a non-null list
+        Some(_) if !var_type.ends_with('!') => return false,
+        Some(expected) => expected,
+        None => expected,
+    };
+    let item = var_type.trim_end_matches('!');
+    expected == format!("[{item}]") || expected == format!("[{item}!]")
+}
+
 fn validate_query(
     logger: &Logger,
     query: &GraphDataQuery,
@@ -146,7 +177,10 @@ fn validate_query(
     metrics: &Arc<dyn GraphQLMetrics>,
     id: &DeploymentHash,
 ) -> Result<(), Vec<QueryExecutionError>> {
-    let validation_errors = validate(document, &query.document, &GRAPHQL_VALIDATION_PLAN);
+    let validation_errors: Vec<_> = validate(document, &query.document, &GRAPHQL_VALIDATION_PLAN)
+        .into_iter()
+        .filter(|e| !is_single_value_for_list(e.error_code, &e.message))
+        .collect();
 
     if !validation_errors.is_empty() {
         if !ENV_VARS.graphql.silent_graphql_validations {
@@ -258,10 +292,9 @@ impl Query {
         };
 
         // It's important to check complexity first, so `validate_fields`
-        // doesn't risk a stack overflow from invalid queries. We don't
-        // really care about the resulting complexity, only that all the
-        // checks that `check_complexity` performs pass successfully
-        let _ = raw_query.check_complexity(max_complexity, max_depth)?;
+        // doesn't risk a stack overflow from invalid queries. The
+        // complexity is only remembered so that callers can account for it
+        let complexity = raw_query.check_complexity(max_complexity, max_depth)?;
         raw_query.validate_fields()?;
         let selection_set = raw_query.convert()?;
 
@@ -269,6 +302,7 @@ impl Query {
             schema,
             selection_set: Arc::new(selection_set),
             shape_hash: query.shape_hash,
+            complexity,
             kind,
             network,
             logger,
@@ -558,9 +592,18 @@ impl<'s> RawQuery<'s> {
                             visited_fragments,
                         )?;
 
-                        // Non-collection queries pass through.
-                        if !sast::is_list_or_non_null_list_field(&s_field) {
-                            return Ok(total_complexity + field_complexity);
+                        // Aggregates scan all matching entities, which
+                        // makes them as expensive as a big collection query
+                        if s_field.name.ends_with("Aggregate")
+                            && s_field
+                                .field_type
+                                .get_base_type()
+                                .ends_with(ENTITY_AGGREGATE_SUFFIX)
+                        {
+                            return total_complexity
+                                .checked_add(ENV_VARS.graphql.aggregate_complexity)
+                                .and_then(|total| total.checked_add(field_complexity))
+                                .ok_or(Overflow);
                         }
 
                         // For collection queries, check the `first` argument.
@@ -570,6 +613,27 @@ impl<'s> RawQuery<'s> {
                                 _ => None,
                             })
                             .unwrap_or(EntityRange::FIRST as u64);
+
+                        // The `nodes` of a connection are counted as a
+                        // collection of the default size; scale that to
+                        // the `first` argument of the connection field
+                        if s_field.name.ends_with("Connection")
+                            && s_field
+                                .field_type
+                                .get_base_type()
+                                .ends_with(ENTITY_CONNECTION_SUFFIX)
+                        {
+                            return field_complexity
+                                .checked_mul(max_entities)
+                                .map(|complexity| complexity / EntityRange::FIRST as u64)
+                                .and_then(|complexity| total_complexity.checked_add(complexity))
+                                .ok_or(Overflow);
+                        }
+
+                        // Non-collection queries pass through.
+                        if !sast::is_list_or_non_null_list_field(&s_field) {
+                            return Ok(total_complexity + field_complexity);
+                        }
                         max_entities
                             .checked_add(
                                 max_entities.checked_mul(field_complexity).ok_or(Overflow)?,
@@ -1068,3 +1132,62 @@ impl Transform {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use graph::prelude::{q, s};
+    use graphql_tools::validation::rules::{ValidationRule, VariablesInAllowedPosition};
+    use graphql_tools::validation::validate::{validate, ValidationPlan};
+
+    use super::is_single_value_for_list;
+
+    const SCHEMA: &str = "
+        enum OrderDirection { asc, desc }
+        type Song { title: String! }
+        type Query {
+            songs(orderBy: [String!], orderDirection: [OrderDirection!]): [Song!]!
+            song(title: String!): Song
+        }";
+
+    /// The messages of the validation errors for `query` that are not
+    /// for single values used as lists
+    fn errors(query: &str) -> Vec<String> {
+        let schema: s::Document = s::parse_schema(SCHEMA).unwrap();
+        let query = q::parse_query(query).unwrap().into_static();
+        let rules: Vec<Box<dyn ValidationRule>> = vec![Box::new(VariablesInAllowedPosition::new())];
+        let plan = ValidationPlan::from(rules);
+        validate(&schema, &query, &plan)
+            .into_iter()
+            .filter(|e| !is_single_value_for_list(e.error_code, &e.message))
+            .map(|e| e.message)
+            .collect()
+    }
+
+    #[test]
+    fn single_valued_variables_can_be_used_as_lists() {
+        let query = "
+        query songs($orderBy: String, $dir: OrderDirection!) {
+            songs(orderBy: $orderBy, orderDirection: $dir) { title }
+        }";
+        assert_eq!(Vec::<String>::new(), errors(query));
+
+        let query = "
+        query songs($orderBy: [String!], $dir: [OrderDirection!]) {
+            songs(orderBy: $orderBy, orderDirection: $dir) { title }
+        }";
+        assert_eq!(Vec::<String>::new(), errors(query));
+
+        // Other mismatches are still reported
+        let query = "
+        query song($title: String) {
+            song(title: $title) { title }
+        }";
+        assert_eq!(1, errors(query).len());
+
+        let query = "
+        query songs($orderBy: [String!]) {
+            song(title: $orderBy) { title }
+        }";
+        assert_eq!(1, errors(query).len());
+    }
+}
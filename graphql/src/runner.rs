@@ -9,10 +9,11 @@ use graph::futures03::future;
 use graph::prelude::MetricsRegistry;
 use graph::{
     components::store::SubscriptionManager,
+    components::subgraph::{Settings, SettingsTarget, SubgraphSettings},
     prelude::{
         async_trait, o, CheapClone, DeploymentState, GraphQLMetrics as GraphQLMetricsTrait,
-        GraphQlRunner as GraphQlRunnerTrait, Logger, Query, QueryExecutionError, Subscription,
-        SubscriptionError, SubscriptionResult, ENV_VARS,
+        GraphQlRunner as GraphQlRunnerTrait, Logger, Query, QueryExecutionError, SubgraphName,
+        Subscription, SubscriptionError, SubscriptionResult, ENV_VARS,
     },
 };
 use graph::{data::graphql::load_manager::LoadManager, prelude::QueryStoreManager};
@@ -28,6 +29,7 @@ pub struct GraphQlRunner<S, SM> {
     subscription_manager: Arc<SM>,
     load_manager: Arc<LoadManager>,
     graphql_metrics: Arc<GraphQLMetrics>,
+    settings: Arc<Settings>,
 }
 
 #[cfg(debug_assertions)]
@@ -48,6 +50,7 @@ where
         subscription_manager: Arc<SM>,
         load_manager: Arc<LoadManager>,
         registry: Arc<MetricsRegistry>,
+        settings: Arc<Settings>,
     ) -> Self {
         let logger = logger.new(o!("component" => "GraphQlRunner"));
         let graphql_metrics = Arc::new(GraphQLMetrics::new(registry));
@@ -57,9 +60,32 @@ where
             subscription_manager,
             load_manager,
             graphql_metrics,
+            settings,
         }
     }
 
+    /// Look up the subgraph-specific settings for queries against `store`.
+    /// Settings that match on a subgraph name only apply when the subgraph
+    /// is queried by that name
+    fn settings(
+        &self,
+        target: &QueryTarget,
+        store: &dyn QueryStore,
+        state: &DeploymentState,
+    ) -> SubgraphSettings {
+        let names: &[SubgraphName] = match target {
+            QueryTarget::Name(name, _) => std::slice::from_ref(name),
+            QueryTarget::Deployment(_, _) => &[],
+        };
+        let target = SettingsTarget {
+            names,
+            deployment: Some(&state.id),
+            network: Some(store.network_name()),
+            shard: Some(store.shard()),
+        };
+        self.settings.for_target(&target)
+    }
+
     /// Check if the subgraph state differs from `state` now in a way that
     /// would affect a query that looked at data as fresh as `latest_block`.
     /// If the subgraph did change, return the `Err` that should be sent back
@@ -125,6 +151,13 @@ where
             .clone()
             .unwrap_or(state);
 
+        // Subgraph-specific limits take precedence over the ones we were
+        // passed, which are usually the node-wide defaults
+        let settings = self.settings(&target, store.as_ref(), &state);
+        let max_complexity = settings.max_complexity.or(max_complexity);
+        let max_first = settings.max_first.or(max_first);
+        let max_skip = settings.max_skip.or(max_skip);
+
         let max_depth = max_depth.unwrap_or(ENV_VARS.graphql.max_depth);
         let do_trace = query.trace;
         let query = crate::execution::Query::new(
@@ -149,6 +182,7 @@ where
             StoreResolver::locate_blocks(store.as_ref(), &state, &query).await?;
         let mut max_block = 0;
         let mut result: QueryResults = QueryResults::empty(query.root_trace(do_trace));
+        result.record_complexity(query.complexity);
         let mut query_res_futures: Vec<_> = vec![];
         let setup_elapsed = execute_start.elapsed();
 
@@ -167,6 +201,9 @@ where
             )
             .await?;
             max_block = max_block.max(resolver.block_number());
+            if let Some(block_ptr) = &resolver.block_ptr {
+                result.record_block(block_ptr.clone());
+            }
             query_res_futures.push(execute_query(
                 query.clone(),
                 Some(selection_set),
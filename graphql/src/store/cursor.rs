@@ -0,0 +1,183 @@
+//! Opaque cursors for keyset pagination of collection queries. A cursor
+//! records the position of an entity in a collection by the values of the
+//! attributes the collection is ordered by and the entity's `id`
+
+use std::str::FromStr;
+
+use graph::components::store::{EntityCursor, EntityOrder, EntityQuery, OrderDirection};
+use graph::data::query::QueryExecutionError;
+use graph::data::store::{scalar, Value, ID};
+use graph::data::value::{Object, Word};
+use graph::prelude::{q, r, serde_json};
+use graph::schema::ObjectOrInterface;
+
+use crate::execution::ast as a;
+
+pub(crate) const ARG_AFTER: &str = "after";
+pub(crate) const ARG_BEFORE: &str = "before";
+
+const ORDER_BY: &str = "orderBy";
+const VALUES: &str = "values";
+
+/// Return the cursor argument of `field` if it has a non-null one, together
+/// with the name of the argument
+fn cursor_argument(field: &a::Field) -> Result<Option<(&'static str, &str)>, QueryExecutionError> {
+    let arg = |name| match field.argument_value(name) {
+        Some(r::Value::String(cursor)) => Some((name, cursor.as_str())),
+        _ => None,
+    };
+    match (arg(ARG_AFTER), arg(ARG_BEFORE)) {
+        (Some(_), Some(_)) => Err(QueryExecutionError::ValidationError(
+            Some(field.position),
+            format!(
+                "only one of `{}` and `{}` can be used in field `{}`",
+                ARG_AFTER, ARG_BEFORE, field.name
+            ),
+        )),
+        (after, before) => Ok(after.or(before)),
+    }
+}
+
+/// Return `true` if `field` pages backwards from a `before` cursor. The
+/// query for such a field returns entities in reverse order, and the
+/// caller needs to reverse them to get them in the requested order
+pub(crate) fn is_backward(field: &a::Field) -> bool {
+    matches!(field.argument_value(ARG_BEFORE), Some(r::Value::String(_)))
+}
+
+/// The attributes that a query with `order` is ordered by, if the order
+/// allows using cursors. Since `id` is unique, it and any attributes after
+/// it do not need to be recorded in a cursor and are left out
+pub(crate) fn order_attributes(order: &EntityOrder) -> Option<Vec<&str>> {
+    let attrs = match order {
+        EntityOrder::Ascending(attr, _) | EntityOrder::Descending(attr, _) => vec![attr.as_str()],
+        EntityOrder::Multiple(attrs) => attrs.iter().map(|(attr, _, _)| attr.as_str()).collect(),
+        EntityOrder::Default => vec![],
+        EntityOrder::ChildAscending(_)
+        | EntityOrder::ChildDescending(_)
+        | EntityOrder::Unordered => return None,
+    };
+    Some(
+        attrs
+            .into_iter()
+            .take_while(|attr| *attr != ID.as_str())
+            .collect(),
+    )
+}
+
+/// Encode the position of `entity` in a collection ordered by `order_by`
+/// as an opaque string
+pub(crate) fn encode(order_by: &[String], entity: &Object) -> Result<String, QueryExecutionError> {
+    let values = order_by
+        .iter()
+        .map(|attr| entity.get(attr).cloned().unwrap_or(r::Value::Null))
+        .collect();
+    let id = entity.get(ID.as_str()).cloned().unwrap_or(r::Value::Null);
+    let cursor = r::Value::Object(Object::from_iter([
+        (
+            Word::from(ORDER_BY),
+            r::Value::List(
+                order_by
+                    .iter()
+                    .map(|attr| r::Value::String(attr.clone()))
+                    .collect(),
+            ),
+        ),
+        (Word::from(VALUES), r::Value::List(values)),
+        (Word::from(ID.as_str()), id),
+    ]));
+    let json = serde_json::to_vec(&cursor)
+        .map_err(|e| QueryExecutionError::ValueParseError("cursor".to_string(), e.to_string()))?;
+    Ok(scalar::Bytes::from(json).to_string())
+}
+
+/// Decode a cursor produced by `encode` into the names of the attributes
+/// the collection is ordered by, the values of those attributes, and the id
+/// of the entity at the cursor. Return `None` if `cursor` is not a valid
+/// cursor
+fn decode(cursor: &str) -> Option<(Vec<String>, Vec<r::Value>, r::Value)> {
+    let bytes = scalar::Bytes::from_str(cursor).ok()?;
+    let json: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    let r::Value::Object(mut obj) = r::Value::from(json) else {
+        return None;
+    };
+    let order_by = match obj.remove(ORDER_BY)? {
+        r::Value::List(order_by) => order_by
+            .into_iter()
+            .map(|attr| match attr {
+                r::Value::String(attr) => Some(attr),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?,
+        _ => return None,
+    };
+    let values = match obj.remove(VALUES)? {
+        r::Value::List(values) if values.len() == order_by.len() => values,
+        _ => return None,
+    };
+    let id = obj.remove(ID.as_str())?;
+    Some((order_by, values, id))
+}
+
+/// Restrict `query` to the entities after the `after` cursor, or before
+/// the `before` cursor of `field`. For `before`, the order of the query is
+/// reversed so that it returns the entities closest to the cursor first
+pub(crate) fn apply_cursor(
+    entity: &ObjectOrInterface<'_>,
+    field: &a::Field,
+    mut query: EntityQuery,
+) -> Result<EntityQuery, QueryExecutionError> {
+    let Some((arg, cursor)) = cursor_argument(field)? else {
+        return Ok(query);
+    };
+    let invalid = || {
+        QueryExecutionError::InvalidArgumentError(
+            field.position,
+            arg.to_string(),
+            q::Value::String(cursor.to_string()),
+        )
+    };
+
+    let order_by = order_attributes(&query.order).ok_or_else(|| {
+        QueryExecutionError::NotSupported(
+            "Cursors can not be used when sorting by child attributes".to_string(),
+        )
+    })?;
+    let (cursor_order_by, values, id) = decode(cursor).ok_or_else(invalid)?;
+    if cursor_order_by != order_by {
+        return Err(invalid());
+    }
+
+    let id_field = entity.field(ID.as_str()).ok_or_else(invalid)?;
+    let values = order_by
+        .iter()
+        .zip(values.iter())
+        .map(|(attr, value)| {
+            let field = entity.field(attr).ok_or_else(invalid)?;
+            Value::from_query_value(value, &field.field_type).map_err(|_| invalid())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let id = Value::from_query_value(&id, &id_field.field_type).map_err(|_| invalid())?;
+
+    if arg == ARG_BEFORE {
+        query.order = match query.order {
+            EntityOrder::Ascending(attr, value_type) => EntityOrder::Descending(attr, value_type),
+            EntityOrder::Descending(attr, value_type) => EntityOrder::Ascending(attr, value_type),
+            EntityOrder::Multiple(attrs) => EntityOrder::Multiple(
+                attrs
+                    .into_iter()
+                    .map(|(attr, value_type, direction)| {
+                        let direction = match direction {
+                            OrderDirection::Ascending => OrderDirection::Descending,
+                            OrderDirection::Descending => OrderDirection::Ascending,
+                        };
+                        (attr, value_type, direction)
+                    })
+                    .collect(),
+            ),
+            EntityOrder::Default => EntityOrder::Descending(ID.to_string(), id_field.value_type),
+            order => order,
+        };
+    }
+    Ok(query.after(EntityCursor { values, id }))
+}
@@ -20,14 +20,18 @@ use std::time::Instant;
 
 use graph::data::graphql::TypeExt;
 use graph::prelude::{
-    AttributeNames, ChildMultiplicity, EntityCollection, EntityFilter, EntityLink, EntityOrder,
-    EntityWindow, ParentLink, QueryExecutionError, Value as StoreValue, WindowAttribute, ENV_VARS,
+    AttributeNames, ChildMultiplicity, EntityAggregate, EntityCollection, EntityFilter, EntityLink,
+    EntityOrder, EntityRange, EntityWindow, ParentLink, QueryExecutionError, Value as StoreValue,
+    WindowAttribute, ENV_VARS,
+};
+use graph::schema::{
+    EntityType, InputSchema, ObjectOrInterface, ENTITY_AGGREGATE_SUFFIX, ENTITY_CONNECTION_SUFFIX,
 };
-use graph::schema::{EntityType, InputSchema, ObjectOrInterface};
 
-use crate::execution::ast as a;
+use crate::execution::ast::{self as a, resolve_object_types};
 use crate::metrics::GraphQLMetrics;
-use crate::store::query::build_query;
+use crate::store::cursor::{self, is_backward, order_attributes, ARG_AFTER, ARG_BEFORE};
+use crate::store::query::{aggregate_op, build_aggregate_query, build_query};
 use crate::store::StoreResolver;
 
 pub const ARG_ID: &str = "id";
@@ -103,6 +107,17 @@ impl From<QueryObject> for Node {
     }
 }
 
+impl From<Object> for Node {
+    fn from(entity: Object) -> Self {
+        Node {
+            children_weight: entity.weight(),
+            parent: None,
+            entity,
+            children: BTreeMap::default(),
+        }
+    }
+}
+
 impl CacheWeight for Node {
     fn indirect_weight(&self) -> usize {
         self.children_weight + cache_weight::btree::node_size(&self.children)
@@ -139,13 +154,23 @@ fn is_root_node<'a>(mut nodes: impl Iterator<Item = &'a Node>) -> bool {
 }
 
 fn make_root_node() -> Vec<Node> {
-    let entity = Object::empty();
-    vec![Node {
-        children_weight: entity.weight(),
-        parent: None,
-        entity,
-        children: BTreeMap::default(),
-    }]
+    vec![Node::from(Object::empty())]
+}
+
+/// If `type_name` is the type of an `<entity>Aggregate` or an
+/// `<entities>Connection` query field, i.e., the name of an entity type
+/// followed by `suffix`, return that entity type
+fn entity_type_with_suffix<'a>(
+    schema: &'a InputSchema,
+    type_name: &str,
+    suffix: &str,
+) -> Option<ObjectOrInterface<'a>> {
+    if schema.object_or_interface(type_name, None).is_some() {
+        return None;
+    }
+    type_name
+        .strip_suffix(suffix)
+        .and_then(|name| schema.object_or_interface(name, None))
 }
 
 /// Recursively convert a `Node` into the corresponding `q::Value`, which is
@@ -622,6 +647,44 @@ impl<'a> Loader<'a> {
                 let field_type = object_type
                     .field(&field.name)
                     .expect("field names are valid");
+                if at_root {
+                    let base_type = field_type.field_type.get_base_type();
+                    if let Some(entity_type) =
+                        entity_type_with_suffix(&input_schema, base_type, ENTITY_CONNECTION_SUFFIX)
+                    {
+                        match self.fetch_connection(&entity_type, field) {
+                            Ok((node, trace)) => {
+                                add_children(
+                                    &input_schema,
+                                    &mut parents,
+                                    vec![node],
+                                    field.response_key(),
+                                )?;
+                                self.check_result_size(&parents)?;
+                                parent_trace.push(field.response_key(), trace);
+                            }
+                            Err(mut e) => errors.append(&mut e),
+                        }
+                        continue;
+                    }
+                    if let Some(entity_type) =
+                        entity_type_with_suffix(&input_schema, base_type, ENTITY_AGGREGATE_SUFFIX)
+                    {
+                        match self.fetch_aggregates(&entity_type, field) {
+                            Ok((node, trace)) => {
+                                add_children(
+                                    &input_schema,
+                                    &mut parents,
+                                    vec![node],
+                                    field.response_key(),
+                                )?;
+                                parent_trace.push(field.response_key(), trace);
+                            }
+                            Err(e) => errors.push(e),
+                        }
+                        continue;
+                    }
+                }
                 let child_type = input_schema
                     .object_or_interface(field_type.field_type.get_base_type(), child_interval)
                     .expect("we only collect fields that are objects or interfaces");
@@ -743,7 +806,224 @@ impl<'a> Loader<'a> {
         self.resolver
             .store
             .find_query_values(query)
-            .map(|(values, trace)| (values.into_iter().map(Node::from).collect(), trace))
+            .map(|(values, trace)| {
+                let mut nodes: Vec<Node> = values.into_iter().map(Node::from).collect();
+                if is_backward(field) {
+                    nodes.reverse();
+                }
+                (nodes, trace)
+            })
+    }
+
+    /// Fetch a page of entities for an `<entities>Connection` field and
+    /// turn it into a node that has a child for each of the `nodes` and
+    /// `pageInfo` fields in the selection
+    fn fetch_connection(
+        &self,
+        entity_type: &ObjectOrInterface<'_>,
+        field: &a::Field,
+    ) -> Result<(Node, Trace), Vec<QueryExecutionError>> {
+        let input_schema = self.resolver.store.input_schema()?;
+
+        // All `nodes` fields share one query whose selection set is the
+        // union of their selection sets
+        let object_types = resolve_object_types(&self.ctx.query.schema, entity_type.typename())?;
+        let mut selection_set = a::SelectionSet::new(object_types.into_iter().collect());
+        let mut nodes_keys = Vec::new();
+        let mut page_info_keys = Vec::new();
+        for (_, fields) in field.selection_set.fields() {
+            for field in fields {
+                match field.name.as_str() {
+                    "nodes" => {
+                        selection_set.merge(field.selection_set.clone(), vec![])?;
+                        nodes_keys.push(field.response_key().to_owned());
+                    }
+                    "pageInfo" => page_info_keys.push(field.response_key().to_owned()),
+                    _ => {}
+                }
+            }
+        }
+        let nodes_field = a::Field {
+            selection_set,
+            multiplicity: ChildMultiplicity::Many,
+            ..field.clone()
+        };
+
+        let mut query = build_query(
+            entity_type,
+            self.resolver.block_number(),
+            &nodes_field,
+            self.ctx.max_first,
+            self.ctx.max_skip,
+            &input_schema,
+        )?;
+        query.trace = self.ctx.trace;
+        query.query_id = Some(self.ctx.query.query_id.clone());
+        query.logger = Some(self.ctx.logger.cheap_clone());
+
+        let order_by: Option<Vec<String>> = order_attributes(&query.order)
+            .map(|attrs| attrs.into_iter().map(str::to_owned).collect());
+        let skip = query.range.skip;
+        let page_size = query.range.first.unwrap_or(EntityRange::FIRST);
+        // Fetch one more entity than we need to find out whether there is
+        // another page
+        query.range.first = Some(page_size + 1);
+        let (values, trace) = self.resolver.store.find_query_values(query)?;
+        let has_more = values.len() > page_size as usize;
+        let mut nodes: Vec<Node> = values
+            .into_iter()
+            .take(page_size as usize)
+            .map(Node::from)
+            .collect();
+
+        let backward = is_backward(field);
+        if backward {
+            nodes.reverse();
+        }
+        let (nodes, trace) =
+            self.execute_selection_set(nodes, trace, &nodes_field.selection_set, None)?;
+
+        let cursor_of = |node: Option<&Node>| -> Result<r::Value, QueryExecutionError> {
+            match (node, &order_by) {
+                (Some(node), Some(order_by)) => {
+                    cursor::encode(order_by, &node.entity).map(r::Value::String)
+                }
+                _ => Ok(r::Value::Null),
+            }
+        };
+        // When paging forward, the entity at the `after` cursor and any
+        // skipped entities come before this page
+        let (has_next_page, has_previous_page) = if backward {
+            (
+                self.has_entities_after(entity_type, &nodes_field, cursor_of(nodes.last())?)?,
+                has_more,
+            )
+        } else {
+            let after = matches!(field.argument_value(ARG_AFTER), Some(r::Value::String(_)));
+            (has_more, after || skip > 0)
+        };
+        let page_info = Object::from_iter([
+            (Word::from("startCursor"), cursor_of(nodes.first())?),
+            (Word::from("endCursor"), cursor_of(nodes.last())?),
+            (Word::from("hasNextPage"), r::Value::Boolean(has_next_page)),
+            (
+                Word::from("hasPreviousPage"),
+                r::Value::Boolean(has_previous_page),
+            ),
+        ]);
+
+        let nodes: Vec<_> = nodes.into_iter().map(Rc::new).collect();
+        let page_info = Rc::new(Node::from(page_info));
+        let mut node = Node::from(Object::empty());
+        for response_key in nodes_keys {
+            node.set_children(response_key, nodes.clone());
+        }
+        for response_key in page_info_keys {
+            node.set_children(response_key, vec![page_info.clone()]);
+        }
+        Ok((node, trace))
+    }
+
+    /// Check whether there are entities after the `end` cursor of a page
+    /// of `field` that was fetched backwards from a `before` cursor. Those
+    /// are the entity at the `before` cursor, if it still matches, and all
+    /// entities after it. When the page is empty and `end` is therefore
+    /// `Null`, any entity in the collection comes after the page
+    fn has_entities_after(
+        &self,
+        entity_type: &ObjectOrInterface<'_>,
+        field: &a::Field,
+        end: r::Value,
+    ) -> Result<bool, QueryExecutionError> {
+        let input_schema = self.resolver.store.input_schema()?;
+
+        let mut field = field.clone();
+        field.arguments.retain(|(name, _)| name != ARG_BEFORE);
+        if let r::Value::String(_) = end {
+            field.arguments.push((ARG_AFTER.to_string(), end));
+        }
+
+        let mut query = build_query(
+            entity_type,
+            self.resolver.block_number(),
+            &field,
+            self.ctx.max_first,
+            self.ctx.max_skip,
+            &input_schema,
+        )?;
+        query.range.first = Some(1);
+        query.range.skip = 0;
+        query.query_id = Some(self.ctx.query.query_id.clone());
+        query.logger = Some(self.ctx.logger.cheap_clone());
+
+        let (values, _) = self.resolver.store.find_query_values(query)?;
+        Ok(!values.is_empty())
+    }
+
+    /// Compute the aggregates for an `<entity>Aggregate` field and turn
+    /// them into a node that has `count` as an attribute and a child for
+    /// each of the `sum`, `min`, `max`, and `avg` fields in the selection
+    fn fetch_aggregates(
+        &self,
+        entity_type: &ObjectOrInterface<'_>,
+        field: &a::Field,
+    ) -> Result<(Node, Trace), QueryExecutionError> {
+        let input_schema = self.resolver.store.input_schema()?;
+        let (mut query, aggregates) = build_aggregate_query(
+            entity_type,
+            self.resolver.block_number(),
+            field,
+            &input_schema,
+        )?;
+        query.trace = self.ctx.trace;
+        query.query_id = Some(self.ctx.query.query_id.clone());
+        query.logger = Some(self.ctx.logger.cheap_clone());
+
+        let (values, trace) = if aggregates.is_empty() {
+            (vec![], Trace::None)
+        } else {
+            self.resolver
+                .store
+                .aggregate_query_values(query, aggregates.clone())?
+        };
+        let value_of = |agg: EntityAggregate| -> r::Value {
+            match aggregates.iter().position(|a| a == &agg) {
+                Some(pos) => r::Value::from(values[pos].clone()),
+                None => r::Value::Null,
+            }
+        };
+
+        let mut entity = Vec::new();
+        let mut children = Vec::new();
+        for (_, fields) in field.selection_set.fields() {
+            for field in fields {
+                if field.name == "count" {
+                    entity.push((Word::from("count"), value_of(EntityAggregate::Count)));
+                    continue;
+                }
+                let Some(agg) = aggregate_op(&field.name) else {
+                    continue;
+                };
+                let attrs = field
+                    .selection_set
+                    .fields()
+                    .flat_map(|(_, attrs)| attrs)
+                    .filter(|attr| attr.name != "__typename")
+                    .map(|attr| {
+                        (
+                            Word::from(attr.name.as_str()),
+                            value_of(agg(attr.name.clone())),
+                        )
+                    });
+                children.push((field.response_key(), Node::from(Object::from_iter(attrs))));
+            }
+        }
+
+        let mut node = Node::from(Object::from_iter(entity));
+        for (response_key, child) in children {
+            node.set_children(response_key.to_owned(), vec![Rc::new(child)]);
+        }
+        Ok((node, trace))
     }
 
     fn check_result_size(&self, parents: &[&mut Node]) -> Result<(), QueryExecutionError> {
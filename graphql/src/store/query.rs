@@ -3,25 +3,21 @@ use std::mem::discriminant;
 
 use graph::cheap_clone::CheapClone;
 use graph::components::store::{
-    BlockNumber, Child, EntityCollection, EntityFilter, EntityOrder, EntityOrderByChild,
-    EntityOrderByChildInfo, EntityQuery, EntityRange,
+    AttributeNames, BlockNumber, Child, EntityAggregate, EntityCollection, EntityFilter,
+    EntityOrder, EntityOrderByChild, EntityOrderByChildInfo, EntityQuery, EntityRange,
+    OrderDirection,
 };
 use graph::data::graphql::TypeExt as _;
 use graph::data::query::QueryExecutionError;
-use graph::data::store::{Attribute, SubscriptionFilter, Value, ValueType};
+use graph::data::store::{Attribute, SubscriptionFilter, Value, ValueType, JSON_SCALAR};
 use graph::data::value::Object;
 use graph::data::value::Value as DataValue;
-use graph::prelude::{r, s, TryFromValue, ENV_VARS};
+use graph::prelude::{q, r, s, TryFromValue, ENV_VARS};
 use graph::schema::ast::{self as sast, FilterOp};
 use graph::schema::{ApiSchema, EntityType, InputSchema, ObjectOrInterface};
 
 use crate::execution::ast as a;
-
-#[derive(Debug)]
-enum OrderDirection {
-    Ascending,
-    Descending,
-}
+use crate::store::cursor::apply_cursor;
 
 /// Builds a EntityQuery from GraphQL arguments.
 ///
@@ -50,7 +46,67 @@ pub(crate) fn build_query<'a>(
         query = query.filter(filter);
     }
     query = query.order(order);
-    Ok(query)
+    apply_cursor(entity, field, query)
+}
+
+/// Builds the query for an `<entity>Aggregate` field together with the
+/// aggregates that its selection set asks for. The aggregates are in the
+/// order in which they first appear in the selection set
+pub(crate) fn build_aggregate_query(
+    entity: &ObjectOrInterface<'_>,
+    block: BlockNumber,
+    field: &a::Field,
+    schema: &InputSchema,
+) -> Result<(EntityQuery, Vec<EntityAggregate>), QueryExecutionError> {
+    let object_types = entity
+        .object_types()
+        .into_iter()
+        .map(|entity_type| (entity_type, AttributeNames::All))
+        .collect();
+    let mut query = EntityQuery::new(
+        schema.id().cheap_clone(),
+        block,
+        EntityCollection::All(object_types),
+    );
+    if let Some(filter) = build_filter(entity, field, schema)? {
+        query = query.filter(filter);
+    }
+
+    let mut aggregates = Vec::new();
+    let mut push = |agg: EntityAggregate| {
+        if !aggregates.contains(&agg) {
+            aggregates.push(agg);
+        }
+    };
+    for (_, fields) in field.selection_set.fields() {
+        for field in fields {
+            if field.name == "count" {
+                push(EntityAggregate::Count);
+                continue;
+            }
+            let Some(agg) = aggregate_op(&field.name) else {
+                continue;
+            };
+            for (_, attrs) in field.selection_set.fields() {
+                for attr in attrs.filter(|attr| attr.name != "__typename") {
+                    push(agg(attr.name.clone()));
+                }
+            }
+        }
+    }
+    Ok((query, aggregates))
+}
+
+/// Map the name of a field of an `<entity>_aggregate` type other than
+/// `count` to the aggregate it computes for each of its attributes
+pub(crate) fn aggregate_op(name: &str) -> Option<fn(Attribute) -> EntityAggregate> {
+    match name {
+        "sum" => Some(EntityAggregate::Sum),
+        "min" => Some(EntityAggregate::Min),
+        "max" => Some(EntityAggregate::Max),
+        "avg" => Some(EntityAggregate::Avg),
+        _ => None,
+    }
 }
 
 /// Parses GraphQL arguments into a EntityRange, if present.
@@ -110,8 +166,21 @@ fn build_filter(
         _ => Err(QueryExecutionError::InvalidFilterError),
     }?;
 
+    let threshold = match field.argument_value("threshold") {
+        Some(r::Value::Float(threshold)) if (0.0..=1.0).contains(threshold) => Some(*threshold),
+        Some(r::Value::Float(threshold)) => {
+            return Err(QueryExecutionError::InvalidArgumentError(
+                field.position,
+                "threshold".to_string(),
+                q::Value::Float(*threshold),
+            ))
+        }
+        Some(r::Value::Null) | None => None,
+        _ => return Err(QueryExecutionError::InvalidFilterError),
+    };
+
     let text_filter = match field.argument_value("text") {
-        Some(r::Value::Object(filter)) => build_fulltext_filter_from_object(filter),
+        Some(r::Value::Object(filter)) => build_fulltext_filter_from_object(filter, threshold),
         None => Ok(None),
         _ => Err(QueryExecutionError::InvalidFilterError),
     }?;
@@ -123,17 +192,24 @@ fn build_filter(
     }
 }
 
+/// Only fuzzy fulltext queries have a `threshold` argument; when it is
+/// present, build a fuzzy fulltext filter
 fn build_fulltext_filter_from_object(
     object: &Object,
+    threshold: Option<f64>,
 ) -> Result<Option<EntityFilter>, QueryExecutionError> {
     object.iter().next().map_or(
         Err(QueryExecutionError::FulltextQueryRequiresFilter),
         |(key, value)| {
             if let r::Value::String(s) = value {
-                Ok(Some(EntityFilter::Fulltext(
-                    key.to_string(),
-                    Value::String(s.clone()),
-                )))
+                let attr = key.to_string();
+                let value = Value::String(s.clone());
+                match threshold {
+                    Some(threshold) => {
+                        Ok(Some(EntityFilter::FuzzyFulltext(attr, value, threshold)))
+                    }
+                    None => Ok(Some(EntityFilter::Fulltext(attr, value))),
+                }
             } else {
                 Err(QueryExecutionError::FulltextQueryRequiresFilter)
             }
@@ -253,7 +329,14 @@ fn build_filter_from_object<'a>(
                 };
             }
             use self::sast::FilterOp::*;
-            let (field_name, op) = sast::parse_field_as_filter(key);
+            let (field_name, op) = match sast::parse_field_as_filter(key) {
+                // Only JSON fields have a `_path` filter; other fields
+                // might just have a name that ends in `_path`
+                (field_name, JsonPath) if !is_json_field(entity, &field_name) => {
+                    (key.to_string(), Equal)
+                }
+                (field_name, op) => (field_name, op),
+            };
 
             Ok(match op {
                 And => {
@@ -278,6 +361,7 @@ fn build_filter_from_object<'a>(
                         entity, object, schema,
                     )?));
                 }
+                JsonPath => build_json_path_filter(field_name, value)?,
                 Child => match value {
                     DataValue::Object(obj) => {
                         build_child_filter_from_object(entity, field_name, obj, schema)?
@@ -312,6 +396,54 @@ fn build_filter_from_object<'a>(
         .collect::<Result<Vec<EntityFilter>, QueryExecutionError>>()
 }
 
+fn is_json_field(entity: &ObjectOrInterface, field_name: &str) -> bool {
+    entity
+        .field(field_name)
+        .map(|field| field.field_type.get_base_type() == JSON_SCALAR)
+        .unwrap_or(false)
+}
+
+/// Build the filter for `<field>_path: { path: [..], equals: .., contains: .. }`
+fn build_json_path_filter(
+    field_name: String,
+    value: &r::Value,
+) -> Result<EntityFilter, QueryExecutionError> {
+    let object = match value {
+        r::Value::Object(object) => object,
+        _ => return Err(QueryExecutionError::InvalidFilterError),
+    };
+    let path = match object.get("path") {
+        Some(r::Value::List(keys)) => keys
+            .iter()
+            .map(|key| match key {
+                r::Value::String(key) => Ok(key.clone()),
+                _ => Err(QueryExecutionError::InvalidFilterError),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => return Err(QueryExecutionError::InvalidFilterError),
+    };
+    let json_type = s::Type::NamedType(JSON_SCALAR.to_string());
+
+    let mut filters = Vec::new();
+    if let Some(value) = object.get("equals") {
+        let value = Value::from_query_value(value, &json_type)?;
+        filters.push(EntityFilter::JsonPathEqual(
+            field_name.clone(),
+            path.clone(),
+            value,
+        ));
+    }
+    if let Some(value) = object.get("contains") {
+        let value = Value::from_query_value(value, &json_type)?;
+        filters.push(EntityFilter::JsonPathContains(field_name, path, value));
+    }
+    match filters.len() {
+        0 => Err(QueryExecutionError::InvalidFilterError),
+        1 => Ok(filters.pop().unwrap()),
+        _ => Ok(EntityFilter::And(filters)),
+    }
+}
+
 fn build_child_filter_from_object(
     entity: &ObjectOrInterface,
     field_name: String,
@@ -460,9 +592,14 @@ fn build_order(
     field: &a::Field,
     schema: &InputSchema,
 ) -> Result<EntityOrder, QueryExecutionError> {
+    let order_by = order_by_values(field);
+    let directions = build_order_directions(field);
+    if order_by.len() > 1 {
+        return build_multiple_order(entity, &order_by, &directions);
+    }
     let order = match (
-        build_order_by(entity, field, schema)?,
-        build_order_direction(field)?,
+        build_order_by(entity, order_by.first().copied(), field, schema)?,
+        order_direction(&directions, 0),
     ) {
         (Some((attr, value_type, None)), OrderDirection::Ascending) => {
             EntityOrder::Ascending(attr, value_type)
@@ -533,29 +670,70 @@ fn build_order(
     Ok(order)
 }
 
+/// Build the order for an `orderBy` argument with several entries, each
+/// of which sorts in the direction at the same position in
+/// `orderDirection`. Child attributes can only be used on their own
+fn build_multiple_order(
+    entity: &ObjectOrInterface,
+    order_by: &[&String],
+    directions: &[OrderDirection],
+) -> Result<EntityOrder, QueryExecutionError> {
+    order_by
+        .iter()
+        .enumerate()
+        .map(|(i, name)| match parse_order_by(name)? {
+            OrderByValue::Direct(name) => {
+                let value_type = order_by_value_type(entity, &name)?;
+                Ok((name, value_type, order_direction(directions, i)))
+            }
+            OrderByValue::Child(_, _) => Err(QueryExecutionError::NotSupported(
+                "Sorting by child attributes together with other attributes".to_string(),
+            )),
+        })
+        .collect::<Result<_, _>>()
+        .map(EntityOrder::Multiple)
+}
+
+/// The names of the attributes in the `orderBy` argument of `field`. The
+/// argument is a list, but we also accept a single name
+fn order_by_values(field: &a::Field) -> Vec<&String> {
+    match field.argument_value("orderBy") {
+        Some(r::Value::Enum(name)) => vec![name],
+        Some(r::Value::List(values)) => values
+            .iter()
+            .filter_map(|value| match value {
+                r::Value::Enum(name) => Some(name),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The value type of the attribute `name` of `entity` if we can sort by it
+fn order_by_value_type(
+    entity: &ObjectOrInterface,
+    name: &str,
+) -> Result<ValueType, QueryExecutionError> {
+    let field = entity.field(name).ok_or_else(|| {
+        QueryExecutionError::EntityFieldError(entity.typename().to_owned(), name.to_owned())
+    })?;
+    sast::get_field_value_type(&field.field_type).map_err(|_| {
+        QueryExecutionError::OrderByNotSupportedError(entity.typename().to_owned(), name.to_owned())
+    })
+}
+
 /// Parses GraphQL arguments into an field name to order by, if present.
 fn build_order_by(
     entity: &ObjectOrInterface,
+    order_by: Option<&String>,
     field: &a::Field,
     schema: &InputSchema,
 ) -> Result<Option<(String, ValueType, Option<OrderByChild>)>, QueryExecutionError> {
-    match field.argument_value("orderBy") {
-        Some(r::Value::Enum(name)) => match parse_order_by(name)? {
+    match order_by {
+        Some(name) => match parse_order_by(name)? {
             OrderByValue::Direct(name) => {
-                let field = entity.field(&name).ok_or_else(|| {
-                    QueryExecutionError::EntityFieldError(
-                        entity.typename().to_owned(),
-                        name.clone(),
-                    )
-                })?;
-                sast::get_field_value_type(&field.field_type)
-                    .map(|value_type| Some((name.clone(), value_type, None)))
-                    .map_err(|_| {
-                        QueryExecutionError::OrderByNotSupportedError(
-                            entity.typename().to_owned(),
-                            name.clone(),
-                        )
-                    })
+                order_by_value_type(entity, &name).map(|value_type| Some((name, value_type, None)))
             }
             OrderByValue::Child(parent_field_name, child_field_name) => {
                 // Finds the field that connects the parent entity with the
@@ -616,7 +794,7 @@ fn build_order_by(
                     })
             }
         },
-        _ => match field.argument_value("text") {
+        None => match field.argument_value("text") {
             Some(r::Value::Object(filter)) => build_fulltext_order_by_from_object(filter)
                 .map(|order_by| order_by.map(|(attr, value)| (attr, value, None))),
             None => Ok(None),
@@ -640,16 +818,33 @@ fn build_fulltext_order_by_from_object(
     )
 }
 
-/// Parses GraphQL arguments into a EntityOrder, if present.
-fn build_order_direction(field: &a::Field) -> Result<OrderDirection, QueryExecutionError> {
-    Ok(field
-        .argument_value("orderDirection")
-        .map(|value| match value {
-            r::Value::Enum(name) if name == "asc" => OrderDirection::Ascending,
+/// Parses the `orderDirection` argument into the directions for the
+/// entries of `orderBy`. The argument is a list, but we also accept a
+/// single direction
+fn build_order_directions(field: &a::Field) -> Vec<OrderDirection> {
+    fn direction(value: &r::Value) -> OrderDirection {
+        match value {
             r::Value::Enum(name) if name == "desc" => OrderDirection::Descending,
             _ => OrderDirection::Ascending,
-        })
-        .unwrap_or(OrderDirection::Ascending))
+        }
+    }
+
+    match field.argument_value("orderDirection") {
+        Some(r::Value::List(values)) => values.iter().map(direction).collect(),
+        Some(value) => vec![direction(value)],
+        None => vec![],
+    }
+}
+
+/// The direction for the `i`th entry of `orderBy`. If `orderDirection` has
+/// fewer entries than `orderBy`, its last entry applies to the remaining
+/// entries of `orderBy`
+fn order_direction(directions: &[OrderDirection], i: usize) -> OrderDirection {
+    directions
+        .get(i)
+        .or(directions.last())
+        .copied()
+        .unwrap_or(OrderDirection::Ascending)
 }
 
 /// Recursively collects entities involved in a query field as `(subgraph ID, name)` tuples.
@@ -706,7 +901,8 @@ pub(crate) fn collect_entities_from_query_field(
 
 #[cfg(test)]
 mod tests {
-    use graph::components::store::EntityQuery;
+    use graph::components::store::{EntityQuery, OrderDirection};
+    use graph::data::query::QueryExecutionError;
     use graph::data::store::ID;
     use graph::env::ENV_VARS;
     use graph::{
@@ -962,6 +1158,69 @@ mod tests {
         assert_eq!(query(&field).order, EntityOrder::Default);
     }
 
+    #[test]
+    fn build_query_parses_multiple_order_by() {
+        let enums = |names: &[&str]| {
+            r::Value::List(
+                names
+                    .iter()
+                    .map(|name| r::Value::Enum(name.to_string()))
+                    .collect(),
+            )
+        };
+
+        let field = default_field_with_vec(vec![
+            ("orderBy", enums(&["name", "email"])),
+            ("orderDirection", enums(&["desc", "asc"])),
+        ]);
+        assert_eq!(
+            query(&field).order,
+            EntityOrder::Multiple(vec![
+                (
+                    "name".to_string(),
+                    ValueType::String,
+                    OrderDirection::Descending
+                ),
+                (
+                    "email".to_string(),
+                    ValueType::String,
+                    OrderDirection::Ascending
+                ),
+            ])
+        );
+
+        // The last direction applies to the remaining attributes
+        let field = default_field_with_vec(vec![
+            ("orderBy", enums(&["name", "email"])),
+            ("orderDirection", r::Value::Enum("desc".to_string())),
+        ]);
+        assert_eq!(
+            query(&field).order,
+            EntityOrder::Multiple(vec![
+                (
+                    "name".to_string(),
+                    ValueType::String,
+                    OrderDirection::Descending
+                ),
+                (
+                    "email".to_string(),
+                    ValueType::String,
+                    OrderDirection::Descending
+                ),
+            ])
+        );
+
+        // A list with one attribute is the same as just that attribute
+        let field = default_field_with_vec(vec![
+            ("orderBy", enums(&["name"])),
+            ("orderDirection", enums(&["desc"])),
+        ]);
+        assert_eq!(
+            query(&field).order,
+            EntityOrder::Descending("name".to_string(), ValueType::String)
+        );
+    }
+
     #[test]
     fn build_query_yields_default_range_if_none_is_present() {
         assert_eq!(query(&default_field()).range, EntityRange::first(100));
@@ -1016,4 +1275,47 @@ mod tests {
             Some(EntityFilter::And(vec![EntityFilter::ChangeBlockGte(10)]))
         )
     }
+
+    #[test]
+    fn build_query_yields_fuzzy_fulltext_filter() {
+        let text = r::Value::Object(Object::from_iter(vec![(
+            "name".into(),
+            r::Value::String("ello".to_string()),
+        )]));
+
+        let query_field = default_field_with_vec(vec![
+            ("text", text.clone()),
+            ("threshold", r::Value::Float(0.3)),
+        ]);
+        assert_eq!(
+            query(&query_field).filter,
+            Some(EntityFilter::FuzzyFulltext(
+                "name".to_string(),
+                Value::String("ello".to_string()),
+                0.3
+            ))
+        );
+
+        for threshold in [-0.1, 1.5] {
+            let query_field = default_field_with_vec(vec![
+                ("text", text.clone()),
+                ("threshold", r::Value::Float(threshold)),
+            ]);
+            let object = INPUT_SCHEMA
+                .object_or_interface(DEFAULT_OBJECT, None)
+                .unwrap();
+            let res = build_query(
+                &object,
+                BLOCK_NUMBER_MAX,
+                &query_field,
+                std::u32::MAX,
+                std::u32::MAX,
+                &*&INPUT_SCHEMA,
+            );
+            assert!(
+                matches!(res, Err(QueryExecutionError::InvalidArgumentError(_, ref name, _)) if name == "threshold"),
+                "threshold {threshold} is rejected"
+            );
+        }
+    }
 }
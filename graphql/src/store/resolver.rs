@@ -174,6 +174,29 @@ impl StoreResolver {
                     }
                     ptr
                 }
+                BlockConstraint::Timestamp(timestamp) => {
+                    let ptr = store
+                        .block_for_timestamp(
+                            timestamp,
+                            state.earliest_block_number,
+                            state.latest_block.cheap_clone(),
+                        )
+                        .await
+                        .map_err(QueryExecutionError::from)?;
+                    let Some(ptr) = ptr else {
+                        return Err(QueryExecutionError::ValueParseError(
+                            "block.timestamp_lte".to_owned(),
+                            format!(
+                                "subgraph {} has no indexed block with a timestamp \
+                                    at or before {}",
+                                state.id, timestamp
+                            ),
+                        )
+                        .into());
+                    };
+                    block_queryable(state, ptr.number)?;
+                    ptr
+                }
                 BlockConstraint::Latest => state.latest_block.cheap_clone(),
             };
             ptrs_and_sels.push((ptr, sel));
@@ -43,6 +43,11 @@ impl MaybeCoercible<ScalarType> for q::Value {
                     Err(q::Value::Int(num))
                 }
             }
+            ("Float", q::Value::Float(f)) => Ok(r::Value::Float(f)),
+            ("Float", q::Value::Int(num)) => {
+                let n = num.as_i64().ok_or_else(|| q::Value::Int(num.clone()))?;
+                Ok(r::Value::Float(n as f64))
+            }
             ("Int8", q::Value::Int(num)) => {
                 let n = num.as_i64().ok_or_else(|| q::Value::Int(num.clone()))?;
                 Ok(r::Value::Int(n))
@@ -61,6 +66,7 @@ impl MaybeCoercible<ScalarType> for q::Value {
             ("BigInt", q::Value::Int(n)) => Ok(r::Value::String(
                 n.as_i64().ok_or(q::Value::Int(n))?.to_string(),
             )),
+            ("JSON", v) => r::Value::try_from(v),
             (_, v) => Err(v),
         }
     }
@@ -204,8 +210,11 @@ pub(crate) fn coerce_value<'a>(
             Ok(r::Value::List(coerced_values))
         }
 
-        // Otherwise the list type is not coercible.
-        (Type::ListType(_), value) => Err(value),
+        // A single value is coercible into a list type if it is coercible
+        // into the inner type; it becomes a list with that one value
+        (Type::ListType(t), value) => {
+            coerce_value(value, t, resolver).map(|v| r::Value::List(vec![v]))
+        }
     }
 }
 
@@ -213,7 +222,7 @@ pub(crate) fn coerce_value<'a>(
 mod tests {
     use graph::prelude::{r::Value, s};
 
-    use super::coerce_to_definition;
+    use super::{coerce_to_definition, coerce_value};
 
     #[test]
     fn coercion_using_enum_type_definitions_is_correct() {
@@ -350,6 +359,27 @@ mod tests {
         assert!(coerce_to_definition(Value::Float(-5.879), "", &resolver).is_err());
     }
 
+    #[test]
+    fn coercion_using_float_type_definitions_is_correct() {
+        let float_type = s::TypeDefinition::Scalar(s::ScalarType::new("Float".to_string()));
+        let resolver = |_: &str| Some(&float_type);
+
+        // We can coerce from Value::Float -> TypeDefinition::Scalar(Float)
+        assert_eq!(
+            coerce_to_definition(Value::Float(0.25), "", &resolver),
+            Ok(Value::Float(0.25))
+        );
+
+        // And also from Value::Int
+        assert_eq!(
+            coerce_to_definition(Value::Int(1), "", &resolver),
+            Ok(Value::Float(1.0))
+        );
+
+        // We don't support going from Value::String -> TypeDefinition::Scalar(Float)
+        assert!(coerce_to_definition(Value::String("0.5".to_string()), "", &resolver).is_err());
+    }
+
     #[test]
     fn coercion_using_id_type_definitions_is_correct() {
         let string_type = s::TypeDefinition::Scalar(s::ScalarType::new("ID".to_owned()));
@@ -444,4 +474,43 @@ mod tests {
             Ok(Value::Int((-13289123_i32).into()))
         );
     }
+
+    #[test]
+    fn coerce_single_value_to_list() {
+        let enum_type = s::TypeDefinition::Enum(s::EnumType {
+            name: "Musician_orderBy".to_string(),
+            description: None,
+            directives: vec![],
+            position: s::Pos::default(),
+            values: vec![s::EnumValue {
+                name: "name".to_string(),
+                position: s::Pos::default(),
+                description: None,
+                directives: vec![],
+            }],
+        });
+        let resolver = |_: &str| Some(&enum_type);
+        let list_type = s::Type::ListType(Box::new(s::Type::NonNullType(Box::new(
+            s::Type::NamedType("Musician_orderBy".to_string()),
+        ))));
+
+        // A single value is coerced into a list with that value
+        assert_eq!(
+            coerce_value(Value::Enum("name".to_string()), &list_type, &resolver),
+            Ok(Value::List(vec![Value::Enum("name".to_string())]))
+        );
+
+        // Lists are coerced element by element
+        assert_eq!(
+            coerce_value(
+                Value::List(vec![Value::Enum("name".to_string())]),
+                &list_type,
+                &resolver
+            ),
+            Ok(Value::List(vec![Value::Enum("name".to_string())]))
+        );
+
+        // A single value that can't be coerced into the inner type is an error
+        assert!(coerce_value(Value::Enum("age".to_string()), &list_type, &resolver).is_err());
+    }
 }
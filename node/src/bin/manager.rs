@@ -5,6 +5,7 @@ use graph::bail;
 use graph::blockchain::BlockHash;
 use graph::cheap_clone::CheapClone;
 use graph::components::adapter::ChainId;
+use graph::components::subgraph::Settings;
 use graph::endpoint::EndpointMetrics;
 use graph::env::ENV_VARS;
 use graph::log::logger_with_levels;
@@ -253,6 +254,96 @@ pub enum Command {
         /// Prometheus push gateway endpoint.
         prometheus_host: Option<String>,
     },
+    /// Replay the handlers of a deployment for a block or range of blocks
+    ///
+    /// The handlers run against the entities as they were before the
+    /// first block, and nothing is written to the database. Every host
+    /// function call, every `store.get`, `store.set` and `store.remove`,
+    /// and the resulting changes to entities are written as a JSON trace.
+    /// Only subgraphs on Ethereum can be replayed
+    Replay {
+        /// Save the trace in this file instead of printing it
+        #[clap(long, short)]
+        output: Option<String>,
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// The first block to replay
+        from: i32,
+        /// The last block to replay. Defaults to the first block
+        to: Option<i32>,
+    },
+    /// Write all entities of a deployment at a block to files
+    ///
+    /// Create one file per entity type in `DIR`, named after the entity
+    /// type, that contains the entities as they were at `BLOCK`, and save
+    /// the GraphQL schema of the deployment in `DIR/schema.graphql`
+    Dump {
+        /// The file format, `parquet` or `csv`
+        #[clap(long, short, default_value = "parquet")]
+        format: commands::dump::Format,
+        /// The number of entities to read from the database at once
+        #[clap(long, default_value = "10000")]
+        batch_size: usize,
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// The block at which to dump the entities
+        block: i32,
+        /// The directory for the files. It is created if it does not exist
+        dir: String,
+    },
+    /// Export how the entities of a deployment changed between two blocks
+    ///
+    /// Print one JSON object per entity that differs between the blocks
+    /// `from` and `to`, with its kind of change (`created`, `updated` or
+    /// `deleted`) and its old and new values. The output is sorted by
+    /// entity type and id
+    Diff {
+        /// Save the diff in this file instead of printing it
+        #[clap(long, short)]
+        output: Option<String>,
+        /// Only include entities of this type. Can be given multiple times
+        #[clap(long = "entity-type", short = 't')]
+        entity_types: Vec<String>,
+        /// Print at most this many entities
+        #[clap(long)]
+        first: Option<usize>,
+        /// Skip this many entities
+        #[clap(long, default_value = "0")]
+        skip: usize,
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// The earlier block
+        from: i32,
+        /// The later block
+        to: i32,
+    },
+    /// Write a snapshot of a deployment to a directory
+    ///
+    /// The snapshot contains the schema, manifest and metadata of the
+    /// deployment, all versions of its entities, its Proof of Indexing and
+    /// its data sources as of its current head. It can be loaded into
+    /// another installation of graph-node with `graphman restore`
+    Snapshot {
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// The directory for the snapshot. It must be empty or not exist
+        dir: String,
+    },
+    /// Restore a deployment from a snapshot
+    ///
+    /// Create a new deployment in `shard` from a snapshot that was written
+    /// with `graphman snapshot` and assign it to `node`. The deployment
+    /// continues indexing from the block at which the snapshot was taken
+    Restore {
+        /// The name of the database shard for the deployment
+        #[clap(long, short)]
+        shard: String,
+        /// The name of the node that should index the deployment
+        #[clap(long, short)]
+        node: String,
+        /// The directory that contains the snapshot
+        dir: String,
+    },
     /// Check and interrogate the configuration
     ///
     /// Print information about a configuration file without
@@ -448,10 +539,21 @@ pub enum ConfigCommand {
     ///
     /// GRAPH_EXPERIMENTAL_SUBGRAPH_SETTINGS can add a file that contains
     /// subgraph-specific settings. This command determines which settings
-    /// would apply when a subgraph <name> is deployed and prints the result
+    /// would apply when a subgraph <name> is deployed and prints the result.
+    /// Settings that match on the deployment hash, network or shard are
+    /// only considered if the corresponding option is given
     Setting {
         /// The subgraph name for which to print settings
         name: String,
+        /// The IPFS hash of the deployment
+        #[clap(long, short)]
+        deployment: Option<String>,
+        /// The network the deployment indexes
+        #[clap(long, short)]
+        network: Option<String>,
+        /// The shard in which the deployment is stored
+        #[clap(long, short)]
+        shard: Option<String>,
     },
 }
 
@@ -704,7 +806,8 @@ pub enum IndexCommand {
         /// The Field names.
         ///
         /// Each field can be expressed either in camel case (as its GraphQL definition) or in snake
-        /// case (as its SQL colmun name).
+        /// case (as its SQL colmun name). Append `:desc` to a field to sort it in descending order
+        /// in the index, e.g., to match an `orderBy` with mixed directions.
         #[clap(required = true)]
         fields: Vec<String>,
         /// The index method. Defaults to `btree` in general, and to `gist` when the index includes the `block_range` column
@@ -946,6 +1049,7 @@ impl Context {
             &self.config,
             self.fork_base.clone(),
             self.registry.clone(),
+            Arc::new(Settings::default()),
         )
         .await
     }
@@ -957,6 +1061,7 @@ impl Context {
             &self.config,
             self.fork_base.clone(),
             self.registry.clone(),
+            Arc::new(Settings::default()),
         );
 
         for pool in pools.values() {
@@ -1003,6 +1108,7 @@ impl Context {
             subscription_manager,
             load_manager,
             registry,
+            Arc::new(Settings::default()),
         ))
     }
 
@@ -1183,18 +1289,37 @@ async fn main() -> anyhow::Result<()> {
                     commands::config::provider(logger, &ctx.config, registry, features, network)
                         .await
                 }
-                Setting { name } => commands::config::setting(&name),
+                Setting {
+                    name,
+                    deployment,
+                    network,
+                    shard,
+                } => commands::config::setting(&name, deployment, network, shard),
             }
         }
-        Remove { name } => commands::remove::run(ctx.subgraph_store(), &name),
+        Remove { name } => {
+            let (store, primary_pool) = ctx.store_and_primary();
+            commands::remove::run(primary_pool, store.subgraph_store(), &name)
+        }
         Create { name } => commands::create::run(ctx.subgraph_store(), name),
         Unassign { deployment } => {
-            let sender = ctx.notification_sender();
-            commands::assign::unassign(ctx.primary_pool(), &sender, &deployment).await
+            let notifications_sender = ctx.notification_sender();
+            let primary_pool = ctx.primary_pool();
+            let deployment = make_deployment_selector(deployment);
+
+            commands::deployment::unassign::run(primary_pool, notifications_sender, deployment)
         }
         Reassign { deployment, node } => {
-            let sender = ctx.notification_sender();
-            commands::assign::reassign(ctx.primary_pool(), &sender, &deployment, node)
+            let notifications_sender = ctx.notification_sender();
+            let primary_pool = ctx.primary_pool();
+            let deployment = make_deployment_selector(deployment);
+
+            commands::deployment::reassign::run(
+                primary_pool,
+                notifications_sender,
+                deployment,
+                node,
+            )
         }
         Pause { deployment } => {
             let notifications_sender = ctx.notification_sender();
@@ -1237,6 +1362,10 @@ async fn main() -> anyhow::Result<()> {
         } => {
             let notification_sender = ctx.notification_sender();
             let (store, primary) = ctx.store_and_primary();
+            let deployments = deployments
+                .into_iter()
+                .map(make_deployment_selector)
+                .collect();
 
             commands::rewind::run(
                 primary,
@@ -1244,7 +1373,7 @@ async fn main() -> anyhow::Result<()> {
                 deployments,
                 block_hash,
                 block_number,
-                &notification_sender,
+                notification_sender,
                 force,
                 sleep,
                 start_block,
@@ -1286,6 +1415,76 @@ async fn main() -> anyhow::Result<()> {
             )
             .await
         }
+        Replay {
+            output,
+            deployment,
+            from,
+            to,
+        } => {
+            let logger = ctx.logger.clone();
+            let config = ctx.config();
+            let registry = ctx.metrics_registry().clone();
+            let node_id = ctx.node_id().clone();
+            let store_builder = ctx.store_builder().await;
+            let ipfs_url = ctx.ipfs_url.clone();
+            let arweave_url = ctx.arweave_url.clone();
+
+            commands::replay::run(
+                logger,
+                store_builder,
+                ipfs_url,
+                arweave_url,
+                config,
+                registry,
+                node_id,
+                deployment,
+                from,
+                to,
+                output,
+            )
+            .await
+        }
+        Dump {
+            format,
+            batch_size,
+            deployment,
+            block,
+            dir,
+        } => {
+            let (store, primary) = ctx.store_and_primary();
+            commands::dump::run(store, primary, deployment, block, format, dir, batch_size)
+        }
+        Diff {
+            output,
+            entity_types,
+            first,
+            skip,
+            deployment,
+            from,
+            to,
+        } => {
+            let (store, primary) = ctx.store_and_primary();
+            commands::diff::run(
+                store,
+                primary,
+                deployment,
+                from,
+                to,
+                entity_types,
+                first,
+                skip,
+                output,
+            )
+        }
+        Snapshot { deployment, dir } => {
+            let (store, primary) = ctx.store_and_primary();
+            commands::snapshot::export(store, primary, deployment, dir)
+        }
+        Restore { shard, node, dir } => {
+            let shards: Vec<_> = ctx.config.stores.keys().cloned().collect();
+            let store = ctx.store();
+            commands::snapshot::restore(store, dir, shard, shards, node)
+        }
         Listen(cmd) => {
             use ListenCommand::*;
             match cmd {
@@ -1312,6 +1511,7 @@ async fn main() -> anyhow::Result<()> {
                 } => {
                     let shards: Vec<_> = ctx.config.stores.keys().cloned().collect();
                     let (store, primary) = ctx.store_and_primary();
+                    let src = make_deployment_selector(src);
                     commands::copy::create(
                         store, primary, src, shard, shards, node, offset, activate, replace,
                     )
@@ -1520,6 +1720,7 @@ async fn main() -> anyhow::Result<()> {
                     method,
                     after,
                 } => {
+                    let deployment = make_deployment_selector(deployment);
                     commands::index::create(
                         subgraph_store,
                         primary_pool,
@@ -1557,6 +1758,7 @@ async fn main() -> anyhow::Result<()> {
                     deployment,
                     index_name,
                 } => {
+                    let deployment = make_deployment_selector(deployment);
                     commands::index::drop(subgraph_store, primary_pool, deployment, &index_name)
                         .await
                 }
@@ -1589,6 +1791,7 @@ async fn main() -> anyhow::Result<()> {
         } => {
             let (store, primary_pool) = ctx.store_and_primary();
             let history = history.unwrap_or(ENV_VARS.min_history_blocks.try_into()?);
+            let deployment = make_deployment_selector(deployment);
             commands::prune::run(
                 store,
                 primary_pool,
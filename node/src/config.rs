@@ -18,7 +18,9 @@ use graph::{
 };
 use graph_chain_ethereum as ethereum;
 use graph_chain_ethereum::NodeCapabilities;
+use graph_server_http::{ClientLimit, ComplexityBudget, QueryClient, QueryLimits};
 use graph_store_postgres::{DeploymentPlacer, Shard as ShardName, PRIMARY_SHARD};
+use graphman_server::{GraphmanAccess, GraphmanAuthToken};
 
 use graph::http::{HeaderMap, Uri};
 use serde::Serialize;
@@ -73,6 +75,8 @@ pub struct Config {
     #[serde(skip, default = "default_node_id")]
     pub node: NodeId,
     pub general: Option<GeneralSection>,
+    pub graphman: Option<GraphmanSection>,
+    pub query_limits: Option<QueryLimitsSection>,
     #[serde(rename = "store")]
     pub stores: BTreeMap<String, Shard>,
     pub chains: ChainSection,
@@ -157,6 +161,14 @@ impl Config {
 
         self.chains.validate()?;
 
+        if let Some(graphman) = &mut self.graphman {
+            graphman.validate()?;
+        }
+
+        if let Some(query_limits) = &mut self.query_limits {
+            query_limits.validate()?;
+        }
+
         Ok(())
     }
 
@@ -196,6 +208,8 @@ impl Config {
         Ok(Config {
             node,
             general: None,
+            graphman: None,
+            query_limits: None,
             stores,
             chains,
             deployment,
@@ -235,6 +249,215 @@ pub struct GeneralSection {
     query: Regex,
 }
 
+/// Settings for the graphman server
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GraphmanSection {
+    #[serde(rename = "token", default)]
+    tokens: Vec<GraphmanToken>,
+}
+
+impl GraphmanSection {
+    fn validate(&mut self) -> Result<()> {
+        for token in &mut self.tokens {
+            token.validate()?;
+        }
+        let names: BTreeSet<_> = self.tokens.iter().map(|token| &token.name).collect();
+        if names.len() != self.tokens.len() {
+            bail!("graphman token names must be unique");
+        }
+        Ok(())
+    }
+
+    /// The auth tokens the graphman server should accept in addition to
+    /// the one set with `GRAPHMAN_SERVER_AUTH_TOKEN`
+    pub fn auth_tokens(&self) -> Vec<GraphmanAuthToken> {
+        self.tokens
+            .iter()
+            .map(|token| GraphmanAuthToken {
+                name: token.name.clone(),
+                token: token.token.clone(),
+                access: match token.access {
+                    GraphmanTokenAccess::Read => GraphmanAccess::Read,
+                    GraphmanTokenAccess::Write => GraphmanAccess::Write,
+                },
+                deployments: token.deployments.clone(),
+                namespaces: token.namespaces.clone(),
+            })
+            .collect()
+    }
+}
+
+/// A named token for the graphman server. If `deployments` or
+/// `namespaces` are set, the token only grants access to the deployments
+/// with these IPFS hashes or database namespaces
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GraphmanToken {
+    name: String,
+    #[serde(skip_serializing)]
+    token: String,
+    #[serde(default)]
+    access: GraphmanTokenAccess,
+    #[serde(default)]
+    deployments: Vec<String>,
+    #[serde(default)]
+    namespaces: Vec<String>,
+}
+
+impl GraphmanToken {
+    fn validate(&mut self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            bail!("graphman tokens must have a name");
+        }
+        self.token = shellexpand::env(&self.token)?.into_owned();
+        if self.token.trim().is_empty() {
+            bail!("graphman token `{}` must not be empty", self.name);
+        }
+        Ok(())
+    }
+}
+
+/// Whether a graphman token only allows queries or also mutations
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphmanTokenAccess {
+    #[default]
+    Read,
+    Write,
+}
+
+/// Per-client rate limits for the GraphQL query server
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct QueryLimitsSection {
+    /// The number of proxies in front of graph-node that append to the
+    /// `X-Forwarded-For` header
+    #[serde(default)]
+    trusted_proxies: usize,
+    /// The limit for each IP address that does not send a known API key
+    anonymous: Option<QueryLimit>,
+    #[serde(rename = "client", default)]
+    clients: Vec<QueryLimitClient>,
+}
+
+impl QueryLimitsSection {
+    fn validate(&mut self) -> Result<()> {
+        if let Some(anonymous) = &self.anonymous {
+            anonymous
+                .validate()
+                .with_context(|| "invalid limit for anonymous query clients")?;
+        }
+        for client in &mut self.clients {
+            client.validate()?;
+        }
+        let names: BTreeSet<_> = self.clients.iter().map(|client| &client.name).collect();
+        if names.len() != self.clients.len() {
+            bail!("query client names must be unique");
+        }
+        let keys: BTreeSet<_> = self.clients.iter().map(|client| &client.api_key).collect();
+        if keys.len() != self.clients.len() {
+            bail!("query client API keys must be unique");
+        }
+        Ok(())
+    }
+
+    pub fn query_limits(&self) -> QueryLimits {
+        QueryLimits {
+            clients: self
+                .clients
+                .iter()
+                .map(|client| QueryClient {
+                    name: client.name.clone(),
+                    api_key: client.api_key.clone(),
+                    limit: client.limit.client_limit(),
+                })
+                .collect(),
+            anonymous: self.anonymous.as_ref().map(QueryLimit::client_limit),
+            trusted_proxies: self.trusted_proxies,
+        }
+    }
+}
+
+/// How many queries a client can send. `burst` defaults to one second's
+/// worth of requests, and `complexity_burst` to one second's worth of
+/// complexity
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QueryLimit {
+    requests_per_second: f64,
+    burst: Option<u32>,
+    max_complexity: Option<u64>,
+    complexity_per_second: Option<f64>,
+    complexity_burst: Option<u64>,
+}
+
+impl QueryLimit {
+    fn validate(&self) -> Result<()> {
+        if self.requests_per_second.is_nan() || self.requests_per_second <= 0.0 {
+            bail!("`requests_per_second` must be a positive number");
+        }
+        if self.burst == Some(0) {
+            bail!("`burst` must be at least 1");
+        }
+        match self.complexity_per_second {
+            Some(rate) if rate.is_nan() || rate <= 0.0 => {
+                bail!("`complexity_per_second` must be a positive number")
+            }
+            None if self.complexity_burst.is_some() => {
+                bail!("`complexity_burst` requires `complexity_per_second`")
+            }
+            _ => {}
+        }
+        if self.complexity_burst == Some(0) {
+            bail!("`complexity_burst` must be at least 1");
+        }
+        Ok(())
+    }
+
+    fn client_limit(&self) -> ClientLimit {
+        ClientLimit {
+            requests_per_second: self.requests_per_second,
+            burst: self
+                .burst
+                .unwrap_or_else(|| self.requests_per_second.ceil() as u32),
+            max_complexity: self.max_complexity,
+            complexity_budget: self
+                .complexity_per_second
+                .map(|per_second| ComplexityBudget {
+                    per_second,
+                    burst: self
+                        .complexity_burst
+                        .unwrap_or_else(|| per_second.ceil() as u64),
+                }),
+        }
+    }
+}
+
+/// A query client that identifies itself with an API key
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QueryLimitClient {
+    name: String,
+    #[serde(skip_serializing)]
+    api_key: String,
+    #[serde(flatten)]
+    limit: QueryLimit,
+}
+
+impl QueryLimitClient {
+    fn validate(&mut self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            bail!("query clients must have a name");
+        }
+        self.api_key = shellexpand::env(&self.api_key)?.into_owned();
+        if self.api_key.trim().is_empty() {
+            bail!(
+                "the API key of query client `{}` must not be empty",
+                self.name
+            );
+        }
+        self.limit
+            .validate()
+            .with_context(|| format!("invalid limit for query client `{}`", self.name))
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Shard {
     pub connection: String,
@@ -1216,13 +1439,16 @@ mod tests {
     use crate::config::{default_polling_interval, ChainSection, Web3Rule};
 
     use super::{
-        Chain, Config, FirehoseProvider, Provider, ProviderDetails, Transport, Web3Provider,
+        Chain, Config, FirehoseProvider, GraphmanSection, Provider, ProviderDetails,
+        QueryLimitsSection, Transport, Web3Provider,
     };
     use graph::blockchain::BlockchainKind;
     use graph::firehose::SubgraphLimit;
     use graph::http::{HeaderMap, HeaderValue};
     use graph::prelude::regex::Regex;
     use graph::prelude::{toml, NodeId};
+    use graph_server_http::{ClientLimit, ComplexityBudget, QueryClient};
+    use graphman_server::GraphmanAccess;
     use std::collections::BTreeSet;
     use std::fs::read_to_string;
     use std::path::{Path, PathBuf};
@@ -1937,4 +2163,119 @@ mod tests {
             actual.chains.get("mainnet").unwrap().polling_interval
         );
     }
+
+    #[test]
+    fn graphman_tokens() {
+        let mut actual = toml::from_str::<GraphmanSection>(
+            r#"
+            [[token]]
+            name = "dashboard"
+            token = "abc"
+
+            [[token]]
+            name = "team-a"
+            token = "def"
+            access = "write"
+            namespaces = ["sgd1"]
+            "#,
+        )
+        .unwrap();
+
+        actual.validate().unwrap();
+
+        let tokens = actual.auth_tokens();
+        assert_eq!(2, tokens.len());
+        assert_eq!(GraphmanAccess::Read, tokens[0].access);
+        assert_eq!(GraphmanAccess::Write, tokens[1].access);
+        assert_eq!(vec!["sgd1".to_string()], tokens[1].namespaces);
+
+        let mut actual = toml::from_str::<GraphmanSection>(
+            r#"
+            [[token]]
+            name = "dashboard"
+            token = "abc"
+
+            [[token]]
+            name = "dashboard"
+            token = "def"
+            "#,
+        )
+        .unwrap();
+
+        assert!(actual.validate().is_err());
+    }
+
+    #[test]
+    fn query_limits() {
+        let mut actual = toml::from_str::<QueryLimitsSection>(
+            r#"
+            trusted_proxies = 2
+            anonymous = { requests_per_second = 2.5 }
+
+            [[client]]
+            name = "dashboard"
+            api_key = "abc"
+            requests_per_second = 100
+            burst = 500
+            max_complexity = 1000000
+            complexity_per_second = 5000000
+            "#,
+        )
+        .unwrap();
+
+        actual.validate().unwrap();
+
+        let limits = actual.query_limits();
+        assert_eq!(2, limits.trusted_proxies);
+        assert_eq!(
+            Some(ClientLimit {
+                requests_per_second: 2.5,
+                burst: 3,
+                max_complexity: None,
+                complexity_budget: None,
+            }),
+            limits.anonymous
+        );
+        assert_eq!(
+            vec![QueryClient {
+                name: "dashboard".to_string(),
+                api_key: "abc".to_string(),
+                limit: ClientLimit {
+                    requests_per_second: 100.0,
+                    burst: 500,
+                    max_complexity: Some(1000000),
+                    complexity_budget: Some(ComplexityBudget {
+                        per_second: 5000000.0,
+                        burst: 5000000,
+                    }),
+                },
+            }],
+            limits.clients
+        );
+
+        let mut actual = toml::from_str::<QueryLimitsSection>(
+            r#"
+            [[client]]
+            name = "dashboard"
+            api_key = "abc"
+            requests_per_second = 0
+            "#,
+        )
+        .unwrap();
+
+        assert!(actual.validate().is_err());
+
+        let mut actual = toml::from_str::<QueryLimitsSection>(
+            r#"
+            [[client]]
+            name = "dashboard"
+            api_key = "abc"
+            requests_per_second = 10
+            complexity_burst = 1000
+            "#,
+        )
+        .unwrap();
+
+        assert!(actual.validate().is_err());
+    }
 }
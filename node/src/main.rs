@@ -7,6 +7,7 @@ use graph::futures03::future::TryFutureExt;
 
 use graph::blockchain::{Blockchain, BlockchainKind};
 use graph::components::link_resolver::{ArweaveClient, FileSizeLimit};
+use graph::components::server::persisted::QueryAllowlist;
 use graph::components::subgraph::Settings;
 use graph::data::graphql::load_manager::LoadManager;
 use graph::endpoint::EndpointMetrics;
@@ -15,7 +16,7 @@ use graph::log::logger;
 use graph::prelude::*;
 use graph::prometheus::Registry;
 use graph::url::Url;
-use graph_core::polling_monitor::{arweave_service, ipfs_service};
+use graph_core::polling_monitor::{arweave_service, http_service, ipfs_service};
 use graph_core::{
     SubgraphAssignmentProvider as IpfsSubgraphAssignmentProvider, SubgraphInstanceManager,
     SubgraphRegistrar as IpfsSubgraphRegistrar,
@@ -26,6 +27,7 @@ use graph_node::network_setup::Networks;
 use graph_node::opt;
 use graph_node::store_builder::StoreBuilder;
 use graph_server_http::GraphQLServer as GraphQLQueryServer;
+use graph_server_http::PersistedQueries;
 use graph_server_index_node::IndexNodeServer;
 use graph_server_json_rpc::JsonRpcServer;
 use graph_server_metrics::PrometheusMetricsServer;
@@ -112,7 +114,7 @@ async fn main() {
         Ok(config) => config,
     };
 
-    let subgraph_settings = match env_vars.subgraph_settings {
+    let subgraph_settings = Arc::new(match env_vars.subgraph_settings {
         Some(ref path) => {
             info!(logger, "Reading subgraph configuration file `{}`", path);
             match Settings::from_file(path) {
@@ -124,7 +126,7 @@ async fn main() {
             }
         }
         None => Settings::default(),
-    };
+    });
 
     if opt.check_config {
         match config.to_json() {
@@ -228,6 +230,13 @@ async fn main() {
         },
     );
 
+    let http_service = http_service(
+        env_vars.mappings.max_http_file_bytes,
+        env_vars.mappings.http_file_timeout,
+        env_vars.mappings.http_file_request_limit,
+        env_vars.mappings.http_file_allow_private_addresses,
+    );
+
     // Convert the clients into a link resolver. Since we want to get past
     // possible temporary DNS failures, make the resolver retry
     let link_resolver = Arc::new(IpfsResolver::new(ipfs_client, env_vars.cheap_clone()));
@@ -253,6 +262,7 @@ async fn main() {
         &config,
         fork_base,
         metrics_registry.cheap_clone(),
+        subgraph_settings.cheap_clone(),
     )
     .await;
 
@@ -265,6 +275,7 @@ async fn main() {
         primary_pool.clone(),
         network_store.cheap_clone(),
         metrics_registry.cheap_clone(),
+        &config,
         &env_vars,
         &logger,
         &logger_factory,
@@ -351,10 +362,31 @@ async fn main() {
             subscription_manager.clone(),
             load_manager,
             graphql_metrics_registry,
+            subgraph_settings.cheap_clone(),
         ));
-        let graphql_server = GraphQLQueryServer::new(&logger_factory, graphql_runner.clone());
-        let subscription_server =
-            GraphQLSubscriptionServer::new(&logger, graphql_runner.clone(), network_store.clone());
+        let query_limits = config
+            .query_limits
+            .as_ref()
+            .map(|query_limits| query_limits.query_limits())
+            .unwrap_or_default();
+        // The HTTP and the WebSocket server share persisted queries so that
+        // both enforce the same allowlist
+        let persisted_queries = PersistedQueries::from_env(&logger)
+            .unwrap_or_else(|err| panic!("Invalid persisted query configuration: {err:#}"))
+            .map(Arc::new);
+        let graphql_server = GraphQLQueryServer::new(
+            &logger_factory,
+            graphql_runner.clone(),
+            query_limits,
+            metrics_registry.clone(),
+            persisted_queries.clone(),
+        );
+        let subscription_server = GraphQLSubscriptionServer::new(
+            &logger,
+            graphql_runner.clone(),
+            network_store.clone(),
+            persisted_queries.map(|queries| queries as Arc<dyn QueryAllowlist>),
+        );
 
         let index_node_server = IndexNodeServer::new(
             &logger_factory,
@@ -400,6 +432,7 @@ async fn main() {
             link_resolver.clone(),
             ipfs_service,
             arweave_service,
+            http_service,
             static_filters,
         );
 
@@ -424,7 +457,7 @@ async fn main() {
             blockchain_map,
             node_id.clone(),
             version_switching_mode,
-            Arc::new(subgraph_settings),
+            subgraph_settings,
         ));
         graph::spawn(
             subgraph_registrar
@@ -571,18 +604,26 @@ fn make_graphman_server_config<'a>(
     pool: ConnectionPool,
     store: Arc<Store>,
     metrics_registry: Arc<MetricsRegistry>,
+    config: &Config,
     env_vars: &EnvVars,
     logger: &Logger,
     logger_factory: &'a LoggerFactory,
 ) -> Option<GraphmanServerConfig<'a>> {
-    let Some(auth_token) = &env_vars.graphman_server_auth_token else {
+    let auth_token = env_vars.graphman_server_auth_token.clone();
+    let auth_tokens = config
+        .graphman
+        .as_ref()
+        .map(|graphman| graphman.auth_tokens())
+        .unwrap_or_default();
+
+    if auth_token.is_none() && auth_tokens.is_empty() {
         warn!(
             logger,
             "Missing graphman server auth token; graphman server will not start",
         );
 
         return None;
-    };
+    }
 
     let notification_sender = Arc::new(NotificationSender::new(metrics_registry.clone()));
 
@@ -591,6 +632,7 @@ fn make_graphman_server_config<'a>(
         notification_sender,
         store,
         logger_factory,
-        auth_token: auth_token.to_owned(),
+        auth_token,
+        auth_tokens,
     })
 }
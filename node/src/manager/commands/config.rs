@@ -4,14 +4,14 @@ use graph::{
     anyhow::{bail, Context},
     components::{
         adapter::{ChainId, IdentValidator, IdentValidatorError, NoopIdentValidator, ProviderName},
-        subgraph::{Setting, Settings},
+        subgraph::{Settings, SettingsTarget, SubgraphSettings},
     },
     endpoint::EndpointMetrics,
     env::EnvVars,
     itertools::Itertools,
     prelude::{
         anyhow::{anyhow, Error},
-        MetricsRegistry, NodeId, SubgraphName,
+        DeploymentHash, MetricsRegistry, NodeId, SubgraphName,
     },
     slog::Logger,
 };
@@ -196,18 +196,60 @@ pub async fn provider(
     Ok(())
 }
 
-pub fn setting(name: &str) -> Result<(), Error> {
+pub fn setting(
+    name: &str,
+    deployment: Option<String>,
+    network: Option<String>,
+    shard: Option<String>,
+) -> Result<(), Error> {
     let name = SubgraphName::new(name).map_err(|()| anyhow!("illegal subgraph name `{}`", name))?;
+    let deployment = deployment
+        .map(|hash| {
+            DeploymentHash::new(hash).map_err(|hash| anyhow!("illegal deployment hash `{}`", hash))
+        })
+        .transpose()?;
     let env_vars = EnvVars::from_env().unwrap();
     if let Some(path) = &env_vars.subgraph_settings {
         let settings = Settings::from_file(path)
             .with_context(|| format!("syntax error in subgraph settings `{}`", path))?;
-        match settings.for_name(&name) {
-            Some(Setting { history_blocks, .. }) => {
-                println!("setting for `{name}` will use history_blocks = {history_blocks}");
-            }
-            None => {
-                println!("no specific setting for `{name}`, defaults will be used");
+        let target = SettingsTarget {
+            names: std::slice::from_ref(&name),
+            deployment: deployment.as_ref(),
+            network: network.as_deref(),
+            shard: shard.as_deref(),
+        };
+        let values = settings.for_target(&target);
+        if values == SubgraphSettings::default() {
+            println!("no specific setting for `{name}`, defaults will be used");
+        } else {
+            let SubgraphSettings {
+                history_blocks,
+                entity_cache_size,
+                handler_timeout,
+                gas_limit,
+                write_batch_size,
+                max_first,
+                max_skip,
+                max_complexity,
+            } = values;
+            println!("setting for `{name}` will use");
+            let values = [
+                ("history_blocks", history_blocks.map(|v| v.to_string())),
+                (
+                    "entity_cache_size",
+                    entity_cache_size.map(|v| v.to_string()),
+                ),
+                ("handler_timeout", handler_timeout.map(|v| v.to_string())),
+                ("gas_limit", gas_limit.map(|v| v.to_string())),
+                ("write_batch_size", write_batch_size.map(|v| v.to_string())),
+                ("max_first", max_first.map(|v| v.to_string())),
+                ("max_skip", max_skip.map(|v| v.to_string())),
+                ("max_complexity", max_complexity.map(|v| v.to_string())),
+            ];
+            for (key, value) in values {
+                if let Some(value) = value {
+                    println!("  {key} = {value}");
+                }
             }
         }
     } else {
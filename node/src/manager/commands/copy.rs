@@ -2,12 +2,11 @@ use diesel::{ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl, RunQuery
 use std::{collections::HashMap, sync::Arc, time::SystemTime};
 
 use graph::{
-    components::store::{BlockStore as _, DeploymentId},
-    data::query::QueryTarget,
+    components::store::DeploymentId,
     prelude::{
         anyhow::{anyhow, bail, Error},
         chrono::{DateTime, Duration, SecondsFormat, Utc},
-        BlockPtr, ChainStore, DeploymentHash, NodeId, QueryStoreManager,
+        DeploymentHash,
     },
 };
 use graph_store_postgres::{
@@ -18,6 +17,8 @@ use graph_store_postgres::{
     PRIMARY_SHARD,
 };
 use graph_store_postgres::{connection_pool::ConnectionPool, Shard, Store, SubgraphStore};
+use graphman::commands::deployment::copy::{copy_deployment, load_copy_source};
+use graphman::deployment::DeploymentSelector;
 
 use crate::manager::deployment::DeploymentSearch;
 use crate::manager::display::List;
@@ -87,7 +88,7 @@ impl CopyState {
 pub async fn create(
     store: Arc<Store>,
     primary: ConnectionPool,
-    src: DeploymentSearch,
+    src: DeploymentSelector,
     shard: String,
     shards: Vec<String>,
     node: String,
@@ -95,7 +96,6 @@ pub async fn create(
     activate: bool,
     replace: bool,
 ) -> Result<(), Error> {
-    let block_offset = block_offset as i32;
     let on_sync = match (activate, replace) {
         (true, true) => bail!("--activate and --replace can't both be specified"),
         (true, false) => OnSync::Activate,
@@ -103,52 +103,17 @@ pub async fn create(
         (false, false) => OnSync::None,
     };
 
-    let subgraph_store = store.subgraph_store();
-    let src = src.locate_unique(&primary)?;
-    let query_store = store
-        .query_store(
-            QueryTarget::Deployment(src.hash.clone(), Default::default()),
-            true,
-        )
-        .await?;
-    let network = query_store.network_name();
-
-    let src_ptr = query_store.block_ptr().await?.ok_or_else(|| anyhow!("subgraph {} has not indexed any blocks yet and can not be used as the source of a copy", src))?;
-    let src_number = if src_ptr.number <= block_offset {
-        bail!("subgraph {} has only indexed up to block {}, but we need at least block {} before we can copy from it", src, src_ptr.number, block_offset);
-    } else {
-        src_ptr.number - block_offset
-    };
-
-    let chain_store = store
-        .block_store()
-        .chain_store(network)
-        .ok_or_else(|| anyhow!("could not find chain store for network {}", network))?;
-    let mut hashes = chain_store.block_hashes_by_block_number(src_number)?;
-    let hash = match hashes.len() {
-        0 => bail!(
-            "could not find a block with number {} in our cache",
-            src_number
-        ),
-        1 => hashes.pop().unwrap(),
-        n => bail!(
-            "the cache contains {} hashes for block number {}",
-            n,
-            src_number
-        ),
-    };
-    let base_ptr = BlockPtr::new(hash, src_number);
-
     if !shards.contains(&shard) {
         bail!(
             "unknown shard {shard}, only shards {} are configured",
             shards.join(", ")
         )
     }
-    let shard = Shard::new(shard)?;
-    let node = NodeId::new(node.clone()).map_err(|()| anyhow!("invalid node id `{}`", node))?;
 
-    let dst = subgraph_store.copy_deployment(&src, shard, node, base_ptr, on_sync)?;
+    let source = load_copy_source(primary, store.clone(), &src, block_offset).await?;
+    let src = source.locator().clone();
+
+    let dst = copy_deployment(store, source, &shard, &node, on_sync)?;
 
     println!("created deployment {} as copy of {}", dst, src);
     Ok(())
@@ -1,4 +1,6 @@
 pub mod info;
 pub mod pause;
+pub mod reassign;
 pub mod restart;
 pub mod resume;
+pub mod unassign;
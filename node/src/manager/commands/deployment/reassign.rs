@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use graph_store_postgres::connection_pool::ConnectionPool;
+use graph_store_postgres::NotificationSender;
+use graphman::commands::deployment::reassign::load_deployment;
+use graphman::commands::deployment::reassign::reassign_deployment;
+use graphman::commands::deployment::reassign::ReassignDeploymentError;
+use graphman::commands::deployment::reassign::ReassignResult;
+use graphman::deployment::DeploymentSelector;
+
+pub fn run(
+    primary_pool: ConnectionPool,
+    notification_sender: Arc<NotificationSender>,
+    deployment: DeploymentSelector,
+    node: String,
+) -> Result<()> {
+    let deployment = load_deployment(primary_pool.clone(), &deployment)?;
+
+    match deployment.assigned_node() {
+        Some(current) => println!(
+            "Reassigning deployment {} to {node} (was {current}) ...",
+            deployment.locator()
+        ),
+        None => println!(
+            "Assigning deployment {} to {node} ...",
+            deployment.locator()
+        ),
+    }
+
+    match reassign_deployment(primary_pool, notification_sender, deployment, &node) {
+        Ok(ReassignResult::Ok) => {}
+        Ok(ReassignResult::CompletedWithWarnings(warnings)) => {
+            for warning in warnings {
+                println!("warning: {warning}");
+            }
+        }
+        Err(ReassignDeploymentError::AlreadyAssigned(locator, node)) => {
+            println!("Deployment {locator} is already assigned to {node}");
+        }
+        Err(err) => return Err(err.into()),
+    }
+
+    Ok(())
+}
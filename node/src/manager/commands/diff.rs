@@ -0,0 +1,81 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use graph::log::escape_control_chars;
+use graph::prelude::{anyhow, r, serde_json, BlockNumber, Entity, EntityDiff, SubgraphStore as _};
+use graph_store_postgres::{connection_pool::ConnectionPool, Store};
+
+use crate::manager::deployment::DeploymentSearch;
+
+/// The number of diffs we fetch from the database at once
+const PAGE_SIZE: usize = 1000;
+
+fn entity_value(entity: Entity) -> r::Value {
+    r::Value::Object(
+        entity
+            .sorted()
+            .into_iter()
+            .map(|(name, value)| (name, value.into()))
+            .collect(),
+    )
+}
+
+fn diff_json(diff: EntityDiff) -> serde_json::Value {
+    serde_json::json!({
+        "type": diff.key.entity_type.to_string(),
+        "id": diff.key.entity_id.to_string(),
+        "kind": diff.kind().as_str(),
+        "old": diff.old.map(entity_value),
+        "new": diff.new.map(entity_value),
+    })
+}
+
+/// Write how the entities of `deployment` at block `to` differ from those
+/// at block `from`, one JSON object per line
+pub fn run(
+    store: Arc<Store>,
+    primary: ConnectionPool,
+    deployment: DeploymentSearch,
+    from: BlockNumber,
+    to: BlockNumber,
+    entity_types: Vec<String>,
+    first: Option<usize>,
+    skip: usize,
+    output: Option<String>,
+) -> Result<(), anyhow::Error> {
+    if from >= to {
+        anyhow::bail!("the block `from` ({from}) must be before the block `to` ({to})");
+    }
+
+    let locator = deployment.locate_unique(&primary)?;
+    let subgraph_store = store.subgraph_store();
+
+    let mut out: Box<dyn Write> = match output {
+        Some(output) => Box::new(File::create(output)?),
+        None => Box::new(io::stdout()),
+    };
+
+    let mut remaining = first.unwrap_or(usize::MAX);
+    let mut skip = skip;
+    while remaining > 0 {
+        let page = remaining.min(PAGE_SIZE);
+        let diffs =
+            subgraph_store.entity_diff(&locator.hash, from, to, &entity_types, page, skip)?;
+        let count = diffs.len();
+        for diff in diffs {
+            // Escape control characters in entity data, as a precaution
+            // against injecting control characters in a terminal.
+            let json = escape_control_chars(serde_json::to_string(&diff_json(diff))?);
+            writeln!(out, "{}", json)?;
+        }
+        if count < page {
+            break;
+        }
+        remaining -= count;
+        skip += count;
+    }
+    out.flush()?;
+
+    Ok(())
+}
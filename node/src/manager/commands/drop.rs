@@ -4,7 +4,9 @@ use crate::manager::{
     prompt::prompt_for_confirmation,
 };
 use graph::anyhow::{self, bail};
+use graph::itertools::Itertools;
 use graph_store_postgres::{connection_pool::ConnectionPool, NotificationSender, SubgraphStore};
+use graphman::deployment::DeploymentSelector;
 use std::sync::Arc;
 
 /// Finds, unassigns, record and remove matching deployments.
@@ -33,11 +35,21 @@ pub async fn run(
         }
     }
     // call `graphman unassign` to stop any active deployments
-    crate::manager::commands::assign::unassign(primary_pool, &sender, &search_term).await?;
+    for deployment in &deployments {
+        crate::manager::commands::deployment::unassign::run(
+            primary_pool.clone(),
+            sender.clone(),
+            DeploymentSelector::Schema(deployment.namespace.clone()),
+        )?;
+    }
 
     // call `graphman remove` to unregister the subgraph's name
-    for deployment in &deployments {
-        crate::manager::commands::remove::run(subgraph_store.clone(), &deployment.name)?;
+    for name in deployments
+        .iter()
+        .map(|deployment| &deployment.name)
+        .unique()
+    {
+        crate::manager::commands::remove::run(primary_pool.clone(), subgraph_store.clone(), name)?;
     }
 
     // call `graphman unused record` to register those deployments unused
@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow::array::{
+    make_builder, ArrayBuilder, ArrayRef, BinaryBuilder, BooleanBuilder, Int32Builder,
+    Int64Builder, ListBuilder, StringBuilder, TimestampMicrosecondBuilder,
+};
+use arrow::datatypes::{DataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use graph::data::graphql::TypeExt as _;
+use graph::prelude::{
+    anyhow::{self, anyhow, bail},
+    r, serde_json, BlockNumber, Entity, SubgraphStore as _, Value, ValueType,
+};
+use graph::schema::{EntityType, Field};
+use graph_store_postgres::{connection_pool::ConnectionPool, Store};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use crate::manager::deployment::DeploymentSearch;
+
+/// The key in the metadata of Parquet columns that holds the GraphQL type
+/// of the column
+const GRAPHQL_TYPE_KEY: &str = "graphql_type";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Parquet,
+    Csv,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Parquet => "parquet",
+            Format::Csv => "csv",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parquet" => Ok(Format::Parquet),
+            "csv" => Ok(Format::Csv),
+            _ => Err(anyhow!(
+                "unknown format `{}`, it must be `parquet` or `csv`",
+                s
+            )),
+        }
+    }
+}
+
+/// Writes the entities of one entity type to a file
+trait EntityWriter {
+    fn write(&mut self, entities: Vec<Entity>) -> Result<(), anyhow::Error>;
+
+    fn finish(self: Box<Self>) -> Result<(), anyhow::Error>;
+}
+
+/// The fields of `entity_type` that are stored in its table, in the order
+/// in which they are declared
+fn stored_fields(entity_type: &EntityType) -> Result<Vec<Field>, anyhow::Error> {
+    Ok(entity_type
+        .object_type()?
+        .fields
+        .iter()
+        .filter(|field| !field.is_derived())
+        .cloned()
+        .collect())
+}
+
+fn unexpected(field: &Field, value: &Value) -> anyhow::Error {
+    anyhow!(
+        "unexpected value {:?} for field `{}` of type {}",
+        value,
+        field.name,
+        field.field_type
+    )
+}
+
+struct ParquetWriter {
+    fields: Vec<Field>,
+    schema: SchemaRef,
+    writer: ArrowWriter<File>,
+}
+
+impl ParquetWriter {
+    fn new(path: &Path, fields: Vec<Field>) -> Result<Self, anyhow::Error> {
+        let columns: Vec<_> = fields
+            .iter()
+            .map(|field| {
+                ArrowField::new(
+                    field.name.as_str(),
+                    Self::data_type(field),
+                    !field.field_type.is_non_null(),
+                )
+                .with_metadata(HashMap::from([(
+                    GRAPHQL_TYPE_KEY.to_string(),
+                    field.field_type.to_string(),
+                )]))
+            })
+            .collect();
+        let schema = Arc::new(ArrowSchema::new(columns));
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(File::create(path)?, schema.clone(), Some(props))?;
+        Ok(ParquetWriter {
+            fields,
+            schema,
+            writer,
+        })
+    }
+
+    fn scalar_type(value_type: ValueType) -> DataType {
+        match value_type {
+            ValueType::Boolean => DataType::Boolean,
+            ValueType::Int => DataType::Int32,
+            ValueType::Int8 => DataType::Int64,
+            ValueType::Bytes => DataType::Binary,
+            ValueType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            // Arrow's decimal types can not hold every `BigInt` or
+            // `BigDecimal`; we store them as strings and rely on the
+            // GraphQL type in the column metadata to identify them. JSON
+            // values are stored as their text representation
+            ValueType::String | ValueType::BigInt | ValueType::BigDecimal | ValueType::Json => {
+                DataType::Utf8
+            }
+        }
+    }
+
+    fn data_type(field: &Field) -> DataType {
+        let scalar = Self::scalar_type(field.value_type);
+        if field.is_list() {
+            DataType::List(Arc::new(ArrowField::new("item", scalar, true)))
+        } else {
+            scalar
+        }
+    }
+
+    fn append_scalar(
+        builder: &mut dyn ArrayBuilder,
+        field: &Field,
+        value: Option<&Value>,
+    ) -> Result<(), anyhow::Error> {
+        macro_rules! append {
+            ($builder:ty, $($pat:pat => $value:expr),+) => {{
+                let builder = builder
+                    .as_any_mut()
+                    .downcast_mut::<$builder>()
+                    .expect("the builder matches the data type");
+                match value {
+                    None | Some(Value::Null) => builder.append_null(),
+                    $(Some($pat) => builder.append_value($value),)+
+                    Some(value) => return Err(unexpected(field, value)),
+                }
+            }};
+        }
+
+        match field.value_type {
+            ValueType::Boolean => append!(BooleanBuilder, Value::Bool(b) => *b),
+            ValueType::Int => append!(Int32Builder, Value::Int(i) => *i),
+            ValueType::Int8 => append!(Int64Builder, Value::Int8(i) => *i),
+            ValueType::Bytes => append!(BinaryBuilder, Value::Bytes(b) => b.as_slice()),
+            ValueType::Timestamp => append!(
+                TimestampMicrosecondBuilder,
+                Value::Timestamp(ts) => ts.as_microseconds_since_epoch()
+            ),
+            ValueType::String | ValueType::BigInt | ValueType::BigDecimal | ValueType::Json => {
+                append!(
+                    StringBuilder,
+                    Value::String(s) => s,
+                    Value::BigInt(n) => n.to_string(),
+                    Value::BigDecimal(d) => d.to_string(),
+                    Value::Json(json) => json.to_string()
+                )
+            }
+        }
+        Ok(())
+    }
+
+    fn append(
+        builder: &mut dyn ArrayBuilder,
+        field: &Field,
+        value: Option<&Value>,
+    ) -> Result<(), anyhow::Error> {
+        if !field.is_list() {
+            return Self::append_scalar(builder, field, value);
+        }
+
+        let builder = builder
+            .as_any_mut()
+            .downcast_mut::<ListBuilder<Box<dyn ArrayBuilder>>>()
+            .expect("the builder for lists is a list builder");
+        match value {
+            None | Some(Value::Null) => builder.append_null(),
+            Some(Value::List(values)) => {
+                for value in values {
+                    Self::append_scalar(builder.values().as_mut(), field, Some(value))?;
+                }
+                builder.append(true);
+            }
+            Some(value) => return Err(unexpected(field, value)),
+        }
+        Ok(())
+    }
+}
+
+impl EntityWriter for ParquetWriter {
+    fn write(&mut self, entities: Vec<Entity>) -> Result<(), anyhow::Error> {
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(self.fields.len());
+        for (field, column) in self.fields.iter().zip(self.schema.fields()) {
+            let mut builder = make_builder(column.data_type(), entities.len());
+            for entity in &entities {
+                Self::append(builder.as_mut(), field, entity.get(field.name.as_str()))?;
+            }
+            columns.push(builder.finish());
+        }
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+        self.writer.write(&batch)?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), anyhow::Error> {
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+struct CsvWriter {
+    fields: Vec<Field>,
+    writer: csv::Writer<File>,
+}
+
+impl CsvWriter {
+    fn new(path: &Path, fields: Vec<Field>) -> Result<Self, anyhow::Error> {
+        let mut writer = csv::Writer::from_path(path)?;
+        writer.write_record(fields.iter().map(|field| field.name.as_str()))?;
+        Ok(CsvWriter { fields, writer })
+    }
+
+    /// Scalars are written the way GraphQL queries return them, and lists
+    /// as JSON arrays; null values are empty
+    fn cell(value: Option<&Value>) -> Result<String, anyhow::Error> {
+        match value {
+            None | Some(Value::Null) => Ok(String::new()),
+            Some(Value::List(_)) => {
+                let value = value.cloned().map(r::Value::from);
+                Ok(serde_json::to_string(&value)?)
+            }
+            Some(value) => Ok(value.to_string()),
+        }
+    }
+}
+
+impl EntityWriter for CsvWriter {
+    fn write(&mut self, entities: Vec<Entity>) -> Result<(), anyhow::Error> {
+        for entity in entities {
+            let record = self
+                .fields
+                .iter()
+                .map(|field| Self::cell(entity.get(field.name.as_str())))
+                .collect::<Result<Vec<_>, _>>()?;
+            self.writer.write_record(record)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), anyhow::Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+fn writer_for(
+    dir: &Path,
+    format: Format,
+    entity_type: &EntityType,
+) -> Result<Box<dyn EntityWriter>, anyhow::Error> {
+    let path = dir.join(format!("{}.{}", entity_type.as_str(), format.extension()));
+    let fields = stored_fields(entity_type)?;
+    match format {
+        Format::Parquet => Ok(Box::new(ParquetWriter::new(&path, fields)?)),
+        Format::Csv => Ok(Box::new(CsvWriter::new(&path, fields)?)),
+    }
+}
+
+/// Write the entities of `deployment` as they were at `block` into `dir`,
+/// one file per entity type, and the GraphQL schema of the deployment
+/// into `dir/schema.graphql`
+pub fn run(
+    store: Arc<Store>,
+    primary: ConnectionPool,
+    deployment: DeploymentSearch,
+    block: BlockNumber,
+    format: Format,
+    dir: String,
+    batch_size: usize,
+) -> Result<(), anyhow::Error> {
+    if batch_size == 0 {
+        bail!("the batch size must be greater than 0");
+    }
+
+    let locator = deployment.locate_unique(&primary)?;
+    let subgraph_store = store.subgraph_store();
+    let schema = subgraph_store.input_schema(&locator.hash)?;
+
+    let dir = PathBuf::from(dir);
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("schema.graphql"), schema.document_string())?;
+
+    // The store passes all entities of one type before it moves on to the
+    // next type, so we only ever need one writer
+    let mut current: Option<(EntityType, Box<dyn EntityWriter>, usize)> = None;
+    subgraph_store.dump(
+        &locator,
+        block,
+        batch_size,
+        |entity_type, entities| -> Result<(), anyhow::Error> {
+            if current.as_ref().map(|(et, _, _)| et) != Some(entity_type) {
+                if let Some((entity_type, writer, count)) = current.take() {
+                    writer.finish()?;
+                    println!("{:>10} {}", count, entity_type);
+                }
+                current = Some((
+                    entity_type.clone(),
+                    writer_for(&dir, format, entity_type)?,
+                    0,
+                ));
+            }
+            // Entity types without entities still get a file with their columns
+            if entities.is_empty() {
+                return Ok(());
+            }
+            let (_, writer, count) = current.as_mut().unwrap();
+            *count += entities.len();
+            writer.write(entities)
+        },
+    )?;
+    if let Some((entity_type, writer, count)) = current.take() {
+        writer.finish()?;
+        println!("{:>10} {}", count, entity_type);
+    }
+
+    Ok(())
+}
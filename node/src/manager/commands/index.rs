@@ -1,31 +1,14 @@
 use crate::manager::{color::Terminal, deployment::DeploymentSearch, CmdResult};
-use graph::{
-    components::store::DeploymentLocator,
-    itertools::Itertools,
-    prelude::{anyhow, StoreError},
-};
+use graph::{components::store::DeploymentLocator, itertools::Itertools, prelude::anyhow};
 use graph_store_postgres::{
-    command_support::index::{CreateIndex, Method},
-    connection_pool::ConnectionPool,
-    SubgraphStore,
+    command_support::index::CreateIndex, connection_pool::ConnectionPool, SubgraphStore,
+};
+use graphman::commands::deployment::index::{
+    create_index, drop_index, load_deployment, CreateIndexError, IndexDefinition,
 };
+use graphman::deployment::DeploymentSelector;
 use std::io::Write as _;
-use std::{collections::HashSet, sync::Arc};
-
-pub const BLOCK_RANGE_COLUMN: &str = "block_range";
-
-fn validate_fields<T: AsRef<str>>(fields: &[T]) -> Result<(), anyhow::Error> {
-    // Must be non-empty. Double checking, since [`StructOpt`] already checks this.
-    if fields.is_empty() {
-        anyhow::bail!("at least one field must be informed")
-    }
-    // All values must be unique
-    let unique: HashSet<_> = fields.iter().map(AsRef::as_ref).collect();
-    if fields.len() != unique.len() {
-        anyhow::bail!("entity fields must be unique")
-    }
-    Ok(())
-}
+use std::sync::Arc;
 
 /// `after` allows for the creation of a partial index
 /// starting from a specified block number. This can improve
@@ -33,45 +16,28 @@ fn validate_fields<T: AsRef<str>>(fields: &[T]) -> Result<(), anyhow::Error> {
 pub async fn create(
     store: Arc<SubgraphStore>,
     pool: ConnectionPool,
-    search: DeploymentSearch,
+    deployment: DeploymentSelector,
     entity_name: &str,
     field_names: Vec<String>,
     index_method: Option<String>,
     after: Option<i32>,
 ) -> Result<(), anyhow::Error> {
-    validate_fields(&field_names)?;
-    let deployment_locator = search.locate_unique(&pool)?;
+    let deployment_locator = load_deployment(pool, &deployment)?;
     println!("Index creation started. Please wait.");
 
-    // If the fields contain the block range column, we use GIN
-    // indexes. Otherwise we default to B-tree indexes.
-    let index_method_str = index_method.as_deref().unwrap_or_else(|| {
-        if field_names.contains(&BLOCK_RANGE_COLUMN.to_string()) {
-            "gist"
-        } else {
-            "btree"
-        }
-    });
-
-    let index_method = index_method_str
-        .parse::<Method>()
-        .map_err(|_| anyhow!("unknown index method `{}`", index_method_str))?;
+    let index = IndexDefinition {
+        entity_name: entity_name.to_string(),
+        field_names,
+        method: index_method,
+        after,
+    };
 
-    match store
-        .create_manual_index(
-            &deployment_locator,
-            entity_name,
-            field_names,
-            index_method,
-            after,
-        )
-        .await
-    {
+    match create_index(store, &deployment_locator, index).await {
         Ok(()) => {
             println!("Index creation completed.",);
             Ok(())
         }
-        Err(StoreError::Canceled) => {
+        Err(CreateIndexError::Canceled) => {
             eprintln!("Index creation attempt failed. Please retry.");
             ::std::process::exit(1);
         }
@@ -205,13 +171,11 @@ pub async fn list(
 pub async fn drop(
     store: Arc<SubgraphStore>,
     pool: ConnectionPool,
-    search: DeploymentSearch,
+    deployment: DeploymentSelector,
     index_name: &str,
 ) -> Result<(), anyhow::Error> {
-    let deployment_locator = search.locate_unique(&pool)?;
-    store
-        .drop_index_for_deployment(&deployment_locator, index_name)
-        .await?;
+    let deployment_locator = load_deployment(pool, &deployment)?;
+    drop_index(store, &deployment_locator, index_name).await?;
     println!("Dropped index {index_name}");
     Ok(())
 }
@@ -7,14 +7,18 @@ pub mod create;
 pub mod database;
 pub mod deploy;
 pub mod deployment;
+pub mod diff;
 pub mod drop;
+pub mod dump;
 pub mod index;
 pub mod listen;
 pub mod prune;
 pub mod query;
 pub mod remove;
+pub mod replay;
 pub mod rewind;
 pub mod run;
+pub mod snapshot;
 pub mod stats;
 pub mod txn_speed;
 pub mod unused_deployments;
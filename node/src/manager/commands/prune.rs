@@ -6,20 +6,20 @@ use std::{
 };
 
 use graph::{
-    components::store::{PrunePhase, PruneRequest},
-    env::ENV_VARS,
+    components::store::PruneReporter,
+    prelude::{anyhow, BlockNumber},
 };
 use graph::{
-    components::store::{PruneReporter, StatusStore},
-    data::subgraph::status,
-    prelude::{anyhow, BlockNumber},
+    components::store::{PrunePhase, PruneRequest},
+    env::ENV_VARS,
 };
 use graph_store_postgres::{connection_pool::ConnectionPool, Store};
-
-use crate::manager::{
-    commands::stats::{abbreviate_table_name, show_stats},
-    deployment::DeploymentSearch,
+use graphman::commands::deployment::prune::{
+    load_prunable_deployment, prune_deployment, PruneOptions,
 };
+use graphman::deployment::DeploymentSelector;
+
+use crate::manager::commands::stats::{abbreviate_table_name, show_stats};
 
 struct Progress {
     start: Instant,
@@ -159,67 +159,31 @@ impl PruneReporter for Progress {
 pub async fn run(
     store: Arc<Store>,
     primary_pool: ConnectionPool,
-    search: DeploymentSearch,
+    deployment: DeploymentSelector,
     history: usize,
     rebuild_threshold: Option<f64>,
     delete_threshold: Option<f64>,
     once: bool,
 ) -> Result<(), anyhow::Error> {
     let history = history as BlockNumber;
-    let deployment = search.locate_unique(&primary_pool)?;
-    let mut info = store
-        .status(status::Filter::DeploymentIds(vec![deployment.id]))?
-        .pop()
-        .ok_or_else(|| anyhow!("deployment {deployment} not found"))?;
-    if info.chains.len() > 1 {
-        return Err(anyhow!(
-            "deployment {deployment} indexes {} chains, not sure how to deal with more than one chain",
-            info.chains.len()
-        ));
-    }
-    let status = info
-        .chains
-        .pop()
-        .ok_or_else(|| anyhow!("deployment {} does not index any chain", deployment))?;
-    let latest = status.latest_block.map(|ptr| ptr.number()).unwrap_or(0);
-    if latest <= history {
-        return Err(anyhow!("deployment {deployment} has only indexed up to block {latest} and we can't preserve {history} blocks of history"));
-    }
+    let deployment = load_prunable_deployment(primary_pool, store.clone(), &deployment, history)?;
+    let latest = deployment.latest_block_number();
 
-    println!("prune {deployment}");
+    println!("prune {}", deployment.locator());
     println!("    latest: {latest}");
     println!("     final: {}", latest - ENV_VARS.reorg_threshold);
     println!("  earliest: {}\n", latest - history);
 
-    let mut req = PruneRequest::new(
-        &deployment,
-        history,
-        ENV_VARS.reorg_threshold,
-        status.earliest_block_number,
-        latest,
-    )?;
-    if let Some(rebuild_threshold) = rebuild_threshold {
-        req.rebuild_threshold = rebuild_threshold;
-    }
-    if let Some(delete_threshold) = delete_threshold {
-        req.delete_threshold = delete_threshold;
-    }
+    let options = PruneOptions {
+        history_blocks: history,
+        rebuild_threshold,
+        delete_threshold,
+        once,
+    };
 
     let reporter = Box::new(Progress::new());
 
-    store
-        .subgraph_store()
-        .prune(reporter, &deployment, req)
-        .await?;
-
-    // Only after everything worked out, make the history setting permanent
-    if !once {
-        store.subgraph_store().set_history_blocks(
-            &deployment,
-            history,
-            ENV_VARS.reorg_threshold,
-        )?;
-    }
+    prune_deployment(store, deployment, options, reporter).await?;
 
     Ok(())
 }
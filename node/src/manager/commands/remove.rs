@@ -1,13 +1,28 @@
 use std::sync::Arc;
 
-use graph::prelude::{anyhow, Error, SubgraphName, SubgraphStore as _};
+use graph::prelude::Error;
+use graph_store_postgres::connection_pool::ConnectionPool;
 use graph_store_postgres::SubgraphStore;
+use graphman::commands::subgraph::remove::load_existing_subgraph;
+use graphman::commands::subgraph::remove::remove_existing_subgraph;
+use graphman::commands::subgraph::remove::RemoveSubgraphError;
 
-pub fn run(store: Arc<SubgraphStore>, name: &str) -> Result<(), Error> {
-    let name = SubgraphName::new(name).map_err(|()| anyhow!("illegal subgraph name `{}`", name))?;
+pub fn run(
+    primary_pool: ConnectionPool,
+    store: Arc<SubgraphStore>,
+    name: &str,
+) -> Result<(), Error> {
+    let existing_subgraph = match load_existing_subgraph(primary_pool, name) {
+        Ok(existing_subgraph) => existing_subgraph,
+        Err(RemoveSubgraphError::NotFound(name)) => {
+            println!("Subgraph {} does not exist", name);
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
 
-    println!("Removing subgraph {}", name);
-    store.remove_subgraph(name)?;
+    println!("Removing subgraph {}", existing_subgraph.name());
+    remove_existing_subgraph(store, existing_subgraph)?;
 
     Ok(())
 }
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::io::Write;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::manager::deployment::DeploymentSearch;
+use crate::network_setup::Networks;
+use crate::store_builder::StoreBuilder;
+use graph::cheap_clone::CheapClone;
+use graph::components::adapter::IdentValidator;
+use graph::components::link_resolver::{ArweaveClient, FileSizeLimit};
+use graph::endpoint::EndpointMetrics;
+use graph::env::EnvVars;
+use graph::log::escape_control_chars;
+use graph::prelude::{
+    anyhow, serde_json, serde_yaml, BlockNumber, IpfsResolver, LinkResolver, LoggerFactory,
+    MetricsRegistry, NodeId, SubgraphCountMetric, ENV_VARS,
+};
+use graph::slog::{info, Logger};
+use graph_core::polling_monitor::{arweave_service, http_service, ipfs_service};
+use graph_core::SubgraphInstanceManager;
+
+/// Run the handlers of `deployment` for the blocks `from..=to` without
+/// writing anything and write a JSON trace of what they did to `output`,
+/// or to stdout if no output file is given
+pub async fn run(
+    logger: Logger,
+    store_builder: StoreBuilder,
+    ipfs_url: Vec<String>,
+    arweave_url: String,
+    config: Config,
+    metrics_registry: Arc<MetricsRegistry>,
+    node_id: NodeId,
+    deployment: DeploymentSearch,
+    from: BlockNumber,
+    to: Option<BlockNumber>,
+    output: Option<String>,
+) -> Result<(), anyhow::Error> {
+    let to = to.unwrap_or(from);
+
+    let env_vars = Arc::new(EnvVars::from_env().unwrap());
+    let logger_factory = LoggerFactory::new(logger.clone(), None, metrics_registry.clone());
+
+    let ipfs_client = graph::ipfs::new_ipfs_client(&ipfs_url, &logger).await?;
+    let ipfs_service = ipfs_service(
+        ipfs_client.cheap_clone(),
+        env_vars.mappings.max_ipfs_file_bytes,
+        env_vars.mappings.ipfs_timeout,
+        env_vars.mappings.ipfs_request_limit,
+    );
+
+    let arweave_resolver = Arc::new(ArweaveClient::new(
+        logger.cheap_clone(),
+        arweave_url.parse().expect("invalid arweave url"),
+    ));
+    let arweave_service = arweave_service(
+        arweave_resolver.cheap_clone(),
+        env_vars.mappings.ipfs_request_limit,
+        match env_vars.mappings.max_ipfs_file_bytes {
+            0 => FileSizeLimit::Unlimited,
+            n => FileSizeLimit::MaxBytes(n as u64),
+        },
+    );
+
+    let http_service = http_service(
+        graph::prelude::reqwest::Client::new(),
+        env_vars.mappings.max_http_file_bytes,
+        env_vars.mappings.http_file_timeout,
+        env_vars.mappings.http_file_request_limit,
+    );
+
+    let endpoint_metrics = Arc::new(EndpointMetrics::new(
+        logger.clone(),
+        &config.chains.providers(),
+        metrics_registry.cheap_clone(),
+    ));
+
+    let link_resolver = Arc::new(IpfsResolver::new(ipfs_client, env_vars.cheap_clone()));
+
+    let locator = deployment.locate_unique(&store_builder.primary_pool())?;
+
+    let chain_head_update_listener = store_builder.chain_head_update_listener();
+    let network_store = store_builder.network_store(config.chain_ids());
+    let block_store = network_store.block_store();
+    let ident_validator: Arc<dyn IdentValidator> = network_store.block_store();
+    let networks = Networks::from_config(
+        logger.cheap_clone(),
+        &config,
+        metrics_registry.cheap_clone(),
+        endpoint_metrics,
+        ident_validator,
+        env_vars.genesis_validation_enabled,
+    )
+    .await
+    .expect("unable to parse network configuration");
+
+    let blockchain_map = Arc::new(
+        networks
+            .blockchain_map(
+                &env_vars,
+                &node_id,
+                &logger,
+                block_store,
+                &logger_factory,
+                metrics_registry.cheap_clone(),
+                chain_head_update_listener,
+            )
+            .await,
+    );
+
+    let subgraph_instance_manager = SubgraphInstanceManager::new(
+        &logger_factory,
+        env_vars.cheap_clone(),
+        network_store.subgraph_store(),
+        blockchain_map,
+        Arc::new(SubgraphCountMetric::new(metrics_registry.clone())),
+        metrics_registry.clone(),
+        link_resolver.cheap_clone(),
+        ipfs_service,
+        arweave_service,
+        http_service,
+        ENV_VARS.experimental_static_filters,
+    );
+
+    let file_bytes = link_resolver
+        .cat(&logger, &locator.hash.to_ipfs_link())
+        .await?;
+    let manifest: serde_yaml::Mapping = serde_yaml::from_slice(&file_bytes)?;
+
+    info!(&logger, "Replaying blocks"; "deployment" => locator.to_string(), "from" => from, "to" => to);
+    let traces = subgraph_instance_manager
+        .replay_blocks(locator, manifest, from, to)
+        .await?;
+
+    // Escape control characters in entity data, as a precaution against
+    // injecting control characters in a terminal.
+    let json = escape_control_chars(serde_json::to_string_pretty(&traces)?);
+    match output {
+        Some(output) => {
+            let mut f = File::create(output)?;
+            writeln!(f, "{}", json)?;
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
@@ -21,7 +21,7 @@ use graph::prelude::{
     SubgraphStore, SubgraphVersionSwitchingMode, ENV_VARS,
 };
 use graph::slog::{debug, info, Logger};
-use graph_core::polling_monitor::{arweave_service, ipfs_service};
+use graph_core::polling_monitor::{arweave_service, http_service, ipfs_service};
 use graph_core::{
     SubgraphAssignmentProvider as IpfsSubgraphAssignmentProvider, SubgraphInstanceManager,
     SubgraphRegistrar as IpfsSubgraphRegistrar,
@@ -80,6 +80,13 @@ pub async fn run(
         },
     );
 
+    let http_service = http_service(
+        env_vars.mappings.max_http_file_bytes,
+        env_vars.mappings.http_file_timeout,
+        env_vars.mappings.http_file_request_limit,
+        env_vars.mappings.http_file_allow_private_addresses,
+    );
+
     let endpoint_metrics = Arc::new(EndpointMetrics::new(
         logger.clone(),
         &config.chains.providers(),
@@ -135,6 +142,7 @@ pub async fn run(
         link_resolver.cheap_clone(),
         ipfs_service,
         arweave_service,
+        http_service,
         static_filters,
     );
 
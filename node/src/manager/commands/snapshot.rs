@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use graph::prelude::{
+    anyhow::{anyhow, bail, Error},
+    NodeId,
+};
+use graph_store_postgres::{connection_pool::ConnectionPool, Shard, Store};
+
+use crate::manager::deployment::DeploymentSearch;
+
+/// Write a snapshot of `deployment` into `dir`
+pub fn export(
+    store: Arc<Store>,
+    primary: ConnectionPool,
+    deployment: DeploymentSearch,
+    dir: String,
+) -> Result<(), Error> {
+    let locator = deployment.locate_unique(&primary)?;
+    let metadata = store
+        .subgraph_store()
+        .export_snapshot(&locator, &PathBuf::from(&dir))?;
+
+    for table in &metadata.tables {
+        println!("{:>10} {}", table.rows, table.name);
+    }
+    println!("{:>10} data sources", metadata.data_sources);
+    println!(
+        "exported {} at block {} ({}) to {}",
+        locator, metadata.head.number, metadata.head.hash, dir
+    );
+    Ok(())
+}
+
+/// Create a new deployment in `shard` from the snapshot in `dir` and
+/// assign it to `node`
+pub fn restore(
+    store: Arc<Store>,
+    dir: String,
+    shard: String,
+    shards: Vec<String>,
+    node: String,
+) -> Result<(), Error> {
+    if !shards.contains(&shard) {
+        bail!(
+            "unknown shard {shard}, only shards {} are configured",
+            shards.join(", ")
+        )
+    }
+    let shard = Shard::new(shard)?;
+    let node = NodeId::new(&node).map_err(|()| anyhow!("invalid node id `{}`", node))?;
+
+    let subgraph_store = store.subgraph_store();
+    let locator = subgraph_store.restore_snapshot(&PathBuf::from(&dir), shard, node.clone())?;
+
+    println!("restored deployment {} from {} onto {}", locator, dir, node);
+    Ok(())
+}
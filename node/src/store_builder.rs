@@ -1,6 +1,7 @@
 use std::iter::FromIterator;
 use std::{collections::HashMap, sync::Arc};
 
+use graph::components::subgraph::Settings;
 use graph::futures03::future::join_all;
 use graph::prelude::{o, MetricsRegistry, NodeId};
 use graph::url::Url;
@@ -41,6 +42,7 @@ impl StoreBuilder {
         config: &Config,
         fork_base: Option<Url>,
         registry: Arc<MetricsRegistry>,
+        settings: Arc<Settings>,
     ) -> Self {
         let primary_shard = config.primary_store().clone();
 
@@ -56,6 +58,7 @@ impl StoreBuilder {
             config,
             fork_base,
             registry.cheap_clone(),
+            settings,
         );
 
         // Try to perform setup (migrations etc.) for all the pools. If this
@@ -97,6 +100,7 @@ impl StoreBuilder {
         config: &Config,
         fork_base: Option<Url>,
         registry: Arc<MetricsRegistry>,
+        settings: Arc<Settings>,
     ) -> (
         Arc<SubgraphStore>,
         HashMap<ShardName, ConnectionPool>,
@@ -155,6 +159,7 @@ impl StoreBuilder {
             notification_sender,
             fork_base,
             registry,
+            settings,
         ));
 
         (store, pools, coord)
@@ -102,7 +102,15 @@ async fn test_valid_module_and_store_with_timeout(
     };
 
     let module = WasmInstance::from_valid_module_with_ctx(
-        Arc::new(ValidModule::new(&logger, data_source.mapping.runtime.as_ref(), timeout).unwrap()),
+        Arc::new(
+            ValidModule::new(
+                &logger,
+                data_source.mapping.runtime.as_ref(),
+                timeout,
+                ENV_VARS.max_gas_per_handler,
+            )
+            .unwrap(),
+        ),
         mock_context(
             deployment.clone(),
             data_source,
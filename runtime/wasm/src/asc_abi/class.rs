@@ -294,6 +294,18 @@ impl<T: AscValue> Array<T> {
             Self::ApiVersion0_0_5(a) => a.to_vec(heap, gas),
         }
     }
+
+    /// The number of elements in the array, without reading them
+    pub fn len(&self) -> usize {
+        match self {
+            Self::ApiVersion0_0_4(a) => a.len(),
+            Self::ApiVersion0_0_5(a) => a.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 impl<T> AscType for Array<T> {
@@ -560,6 +572,8 @@ pub enum StoreValueKind {
     BigInt,
     Int8,
     Timestamp,
+    /// The payload is the JSON value serialized as a string
+    Json,
 }
 
 impl StoreValueKind {
@@ -577,6 +591,7 @@ impl StoreValueKind {
             Value::Null => StoreValueKind::Null,
             Value::Bytes(_) => StoreValueKind::Bytes,
             Value::BigInt(_) => StoreValueKind::BigInt,
+            Value::Json(_) => StoreValueKind::Json,
         }
     }
 }
@@ -327,4 +327,8 @@ impl<T: AscValue> Array<T> {
             .read_ptr(heap, gas)?
             .get(0, self.length, heap.api_version())
     }
+
+    pub(crate) fn len(&self) -> usize {
+        self.length as usize
+    }
 }
@@ -312,4 +312,8 @@ impl<T: AscValue> Array<T> {
             heap.api_version(),
         )
     }
+
+    pub(crate) fn len(&self) -> usize {
+        self.length as u32 as usize
+    }
 }
@@ -7,7 +7,7 @@ use graph::futures03::channel::oneshot::channel;
 
 use graph::blockchain::{BlockTime, Blockchain, HostFn, RuntimeAdapter};
 use graph::components::store::{EnsLookup, SubgraphFork};
-use graph::components::subgraph::{MappingError, SharedProofOfIndexing};
+use graph::components::subgraph::{MappingError, SharedProofOfIndexing, SubgraphSettings};
 use graph::data_source::{
     DataSource, DataSourceTemplate, MappingTrigger, TriggerData, TriggerWithHandler,
 };
@@ -28,6 +28,10 @@ pub struct RuntimeHostBuilder<C: Blockchain> {
     runtime_adapter: Arc<dyn RuntimeAdapter<C>>,
     link_resolver: Arc<dyn LinkResolver>,
     ens_lookup: Arc<dyn EnsLookup>,
+    /// The timeout for handlers in the mappings that this builder spawns
+    timeout: Option<Duration>,
+    /// The maximum amount of gas a handler may use
+    gas_limit: u64,
 }
 
 impl<C: Blockchain> Clone for RuntimeHostBuilder<C> {
@@ -36,6 +40,8 @@ impl<C: Blockchain> Clone for RuntimeHostBuilder<C> {
             runtime_adapter: self.runtime_adapter.cheap_clone(),
             link_resolver: self.link_resolver.cheap_clone(),
             ens_lookup: self.ens_lookup.cheap_clone(),
+            timeout: self.timeout,
+            gas_limit: self.gas_limit,
         }
     }
 }
@@ -45,11 +51,14 @@ impl<C: Blockchain> RuntimeHostBuilder<C> {
         runtime_adapter: Arc<dyn RuntimeAdapter<C>>,
         link_resolver: Arc<dyn LinkResolver>,
         ens_lookup: Arc<dyn EnsLookup>,
+        settings: &SubgraphSettings,
     ) -> Self {
         RuntimeHostBuilder {
             runtime_adapter,
             link_resolver,
             ens_lookup,
+            timeout: settings.handler_timeout(),
+            gas_limit: settings.gas_limit(),
         }
     }
 }
@@ -62,6 +71,7 @@ where
     type Req = WasmRequest<C>;
 
     fn spawn_mapping(
+        &self,
         raw_module: &[u8],
         logger: Logger,
         subgraph_id: DeploymentHash,
@@ -76,7 +86,8 @@ where
             subgraph_id,
             metrics,
             tokio::runtime::Handle::current(),
-            ENV_VARS.mappings.timeout,
+            self.timeout,
+            self.gas_limit,
             experimental_features,
         )
     }
@@ -307,7 +318,8 @@ impl<C: Blockchain> RuntimeHostTrait<C> for RuntimeHost<C> {
         block: &Arc<C::Block>,
         logger: &Logger,
     ) -> Result<Option<TriggerWithHandler<MappingTrigger<C>>>, Error> {
-        self.data_source.match_and_decode(trigger, block, logger)
+        self.data_source
+            .match_and_decode(trigger, block, logger, &self.metrics)
     }
 
     async fn process_block(
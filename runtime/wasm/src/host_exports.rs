@@ -16,7 +16,7 @@ use graph::blockchain::BlockTime;
 use graph::blockchain::Blockchain;
 use graph::components::store::{EnsLookup, GetScope, LoadRelatedRequest};
 use graph::components::subgraph::{
-    InstanceDSTemplate, PoICausalityRegion, ProofOfIndexingEvent, SharedProofOfIndexing,
+    InstanceDSTemplate, PoICausalityRegion, ProofOfIndexingEvent, SharedProofOfIndexing, TraceEvent,
 };
 use graph::data::store::{self};
 use graph::data_source::{CausalityRegion, DataSource, EntityTypeAccess};
@@ -349,6 +349,7 @@ impl HostExports {
         poi_section.end();
 
         state.metrics.track_entity_write(&entity_type, &entity);
+        state.trace_event(|| TraceEvent::store_set(&key, &entity));
 
         state.entity_cache.set(key, entity)?;
 
@@ -386,6 +387,7 @@ impl HostExports {
             "store_remove",
         )?;
 
+        state.trace_event(|| TraceEvent::store_remove(&key));
         state.entity_cache.remove(key);
 
         Ok(())
@@ -420,6 +422,7 @@ impl HostExports {
         if let Some(ref entity) = result {
             state.metrics.track_entity_read(&entity_type, &entity)
         }
+        state.trace_event(|| TraceEvent::store_get(&store_key, result.as_deref()));
 
         Ok(result)
     }
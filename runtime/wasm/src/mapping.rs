@@ -23,12 +23,13 @@ pub fn spawn_module<C: Blockchain>(
     host_metrics: Arc<HostMetrics>,
     runtime: tokio::runtime::Handle,
     timeout: Option<Duration>,
+    gas_limit: u64,
     experimental_features: ExperimentalFeatures,
 ) -> Result<mpsc::Sender<WasmRequest<C>>, anyhow::Error>
 where
     <C as Blockchain>::MappingTrigger: ToAscPtr,
 {
-    let valid_module = Arc::new(ValidModule::new(&logger, raw_module, timeout)?);
+    let valid_module = Arc::new(ValidModule::new(&logger, raw_module, timeout, gas_limit)?);
 
     // Create channel for event handling requests
     let (mapping_request_sender, mapping_request_receiver) = mpsc::channel(100);
@@ -209,12 +210,16 @@ pub struct MappingContext {
 
 impl MappingContext {
     pub fn derive_with_empty_block_state(&self) -> Self {
+        let mut state = BlockState::new(self.state.entity_cache.store.clone(), Default::default());
+        if self.state.is_tracing() {
+            state = state.with_trace();
+        }
         MappingContext {
             logger: self.logger.cheap_clone(),
             host_exports: self.host_exports.cheap_clone(),
             block_ptr: self.block_ptr.cheap_clone(),
             timestamp: self.timestamp,
-            state: BlockState::new(self.state.entity_cache.store.clone(), Default::default()),
+            state,
             proof_of_indexing: self.proof_of_indexing.cheap_clone(),
             host_fns: self.host_fns.cheap_clone(),
             debug_fork: self.debug_fork.cheap_clone(),
@@ -250,6 +255,9 @@ pub struct ValidModule {
     // The timeout for the module.
     pub timeout: Option<Duration>,
 
+    // The maximum amount of gas a handler in the module may use.
+    pub gas_limit: u64,
+
     // Used as a guard to terminate this task dependency.
     epoch_counter_abort_handle: Option<tokio::task::AbortHandle>,
 }
@@ -260,6 +268,7 @@ impl ValidModule {
         logger: &Logger,
         raw_module: &[u8],
         timeout: Option<Duration>,
+        gas_limit: u64,
     ) -> Result<Self, anyhow::Error> {
         // Add the gas calls here. Module name "gas" must match. See also
         // e3f03e62-40e4-4f8c-b4a1-d0375cca0b76. We do this by round-tripping the module through
@@ -349,6 +358,7 @@ impl ValidModule {
             import_name_to_modules,
             start_function,
             timeout,
+            gas_limit,
             epoch_counter_abort_handle,
         })
     }
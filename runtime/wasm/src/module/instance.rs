@@ -8,16 +8,17 @@ use semver::Version;
 use wasmtime::{AsContextMut, Linker, Store, Trap};
 
 use graph::blockchain::{Blockchain, HostFnCtx};
+use graph::components::subgraph::MappingError;
 use graph::data::store;
 use graph::data::subgraph::schema::SubgraphError;
 use graph::data_source::{MappingTrigger, TriggerWithHandler};
 use graph::prelude::*;
+use graph::runtime::AscPtr;
 use graph::runtime::{
     asc_new,
     gas::{Gas, GasCounter, SaturatingInto},
     HostExportError, ToAscObj,
 };
-use graph::{components::subgraph::MappingError, runtime::AscPtr};
 
 use super::IntoWasmRet;
 use super::{IntoTrap, WasmInstanceContext};
@@ -27,6 +28,7 @@ use crate::mapping::ValidModule;
 use crate::module::WasmInstanceData;
 use crate::ExperimentalFeatures;
 
+use super::trace::{trace_pointer, TraceValue};
 use super::{is_trap_deterministic, AscHeapCtx, ToAscPtr};
 
 /// Handle to a WASM instance, which is terminated if and only if this is dropped.
@@ -76,7 +78,7 @@ impl WasmInstance {
         user_data: &store::Value,
     ) -> Result<BlockState, anyhow::Error> {
         let gas_metrics = self.store.data().host_metrics.gas_metrics.clone();
-        let gas = GasCounter::new(gas_metrics);
+        let gas = GasCounter::with_limit(gas_metrics, self.store.data().valid_module.gas_limit);
         let mut ctx = self.instance_ctx();
         let (value, user_data) = {
             let value = asc_new(&mut ctx, value, &gas);
@@ -86,7 +88,9 @@ impl WasmInstance {
             (value, user_data)
         };
 
-        self.instance_ctx().as_mut().ctx.state.enter_handler();
+        let mut ctx = self.instance_ctx();
+        ctx.as_mut().ctx.state.trace_handler(handler_name);
+        ctx.as_mut().ctx.state.enter_handler();
 
         // Invoke the callback
         self.instance
@@ -174,7 +178,9 @@ impl WasmInstance {
             .context("wasm function has incorrect signature")?;
 
         // Caution: Make sure all exit paths from this function call `exit_handler`.
-        self.instance_ctx().as_mut().ctx.state.enter_handler();
+        let mut ctx = self.instance_ctx();
+        ctx.as_mut().ctx.state.trace_handler(handler);
+        ctx.as_mut().ctx.state.enter_handler();
 
         // This `match` will return early if there was a non-deterministic trap.
         let deterministic_error: Option<Error> =
@@ -205,8 +211,11 @@ impl WasmInstance {
                     ))));
                 }
                 Err(trap) => {
-                    let trap_is_deterministic = is_trap_deterministic(&trap)
-                        || self.instance_ctx().as_ref().deterministic_host_trap;
+                    // Running out of a per-subgraph gas limit is not
+                    // deterministic, see `GasCounter::exceeded_lowered_limit`
+                    let trap_is_deterministic = (is_trap_deterministic(&trap)
+                        || self.instance_ctx().as_ref().deterministic_host_trap)
+                        && !self.gas.exceeded_lowered_limit();
                     match trap_is_deterministic {
                         true => Some(trap),
                         false => {
@@ -291,7 +300,7 @@ impl WasmInstance {
 
         // Because `gas` and `deterministic_host_trap` need to be accessed from the gas
         // host fn, they need to be separate from the rest of the context.
-        let gas = GasCounter::new(host_metrics.gas_metrics.clone());
+        let gas = GasCounter::with_limit(host_metrics.gas_metrics.clone(), valid_module.gas_limit);
         let deterministic_host_trap = Arc::new(AtomicBool::new(false));
 
         macro_rules! link {
@@ -316,13 +325,37 @@ impl WasmInstance {
                               $($param: u32),*|  {
                             let host_metrics = caller.data().host_metrics.cheap_clone();
                             let _section = host_metrics.stopwatch.start_section($section);
+                            let trace = caller.data_mut().ctx.state.trace_host_call($wasm_name);
+                            // Decoding values for the trace must not use
+                            // the handler's gas
+                            let trace_gas = GasCounter::with_limit(
+                                host_metrics.gas_metrics.clone(),
+                                u64::MAX,
+                            );
+                            #[allow(unused_mut)]
+                            let mut trace_args = Vec::new();
 
                             #[allow(unused_mut)]
                             let mut ctx = WasmInstanceContext::new(&mut caller);
                             let result = ctx.$rust_name(
                                 &gas,
-                                $($param.into()),*
+                                $({
+                                    let arg = $param.into();
+                                    if trace.is_some() {
+                                        trace_args.push(TraceValue::trace_value(
+                                            &arg, &ctx, &trace_gas,
+                                        ));
+                                    }
+                                    arg
+                                }),*
                             );
+                            if let Some(event) = trace {
+                                let value = result
+                                    .as_ref()
+                                    .ok()
+                                    .map(|result| TraceValue::trace_value(result, &ctx, &trace_gas));
+                                ctx.as_mut().ctx.state.trace_host_return(event, trace_args, value);
+                            }
                             match result {
                                 Ok(result) => Ok(result.into_wasm_ret()),
                                 Err(e) => {
@@ -368,6 +401,9 @@ impl WasmInstance {
                         let stopwatch = host_metrics.stopwatch.cheap_clone();
                         let _section =
                             stopwatch.start_section(&format!("host_export_{}", name_for_metrics));
+                        // The types of the arguments and results of
+                        // chain-specific host functions are not known here
+                        let trace = caller.data_mut().ctx.state.trace_host_call(host_fn.name);
 
                         let ctx = HostFnCtx {
                             logger: caller.data().ctx.logger.cheap_clone(),
@@ -386,7 +422,15 @@ impl WasmInstance {
                                 e
                             }
                             HostExportError::Unknown(e) => e,
-                        })?;
+                        });
+                        if let Some(event) = trace {
+                            caller.data_mut().ctx.state.trace_host_return(
+                                event,
+                                vec![trace_pointer(call_ptr)],
+                                ret.as_ref().ok().map(|ret| trace_pointer(*ret)),
+                            );
+                        }
+                        let ret = ret?;
                         host_metrics.observe_host_fn_execution_time(
                             start.elapsed().as_secs_f64(),
                             &name_for_metrics,
@@ -30,6 +30,7 @@ pub use instance::*;
 mod context;
 mod instance;
 mod into_wasm_ret;
+mod trace;
 
 // Convenience for a 'top-level' asc_get, with depth 0.
 fn asc_get<T, C: AscType, H: AscHeap + ?Sized>(
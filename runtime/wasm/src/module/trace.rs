@@ -0,0 +1,157 @@
+//! Decoding of the arguments and results of host functions for the traces
+//! that are collected when blocks are replayed with `graphman replay`
+
+use std::collections::HashMap;
+
+use graph::data::store::{self, scalar};
+use graph::data::value::Word;
+use graph::prelude::{ethabi, r, BigDecimal, BigInt};
+use graph::runtime::{gas::GasCounter, AscIndexId, AscPtr, AscType, FromAscObj};
+use never::Never;
+
+use crate::asc_abi::class::*;
+
+use super::{asc_get, WasmInstanceContext};
+
+/// Turn an argument or the result of a host function into a value for the
+/// trace. The `gas` that decoding uses must not be the gas counter of the
+/// handler so that tracing does not change how much gas handlers use
+pub(crate) trait TraceValue {
+    fn trace_value(&self, ctx: &WasmInstanceContext, gas: &GasCounter) -> r::Value;
+}
+
+/// A value whose type we do not know or do not decode is shown as its
+/// address in wasm memory
+pub(crate) fn trace_pointer(ptr: u32) -> r::Value {
+    r::Value::String(format!("<wasm pointer {:#x}>", ptr))
+}
+
+fn decode<C, T>(
+    ptr: AscPtr<C>,
+    ctx: &WasmInstanceContext,
+    gas: &GasCounter,
+    render: impl FnOnce(T) -> r::Value,
+) -> r::Value
+where
+    C: AscType + AscIndexId,
+    T: FromAscObj<C>,
+{
+    if ptr.is_null() {
+        return r::Value::Null;
+    }
+    match asc_get(ctx, ptr, gas) {
+        Ok(value) => render(value),
+        Err(e) => r::Value::String(format!("<failed to decode: {}>", e)),
+    }
+}
+
+fn entity_value(data: HashMap<Word, store::Value>) -> r::Value {
+    let mut data: Vec<_> = data.into_iter().collect();
+    data.sort_by(|(a, _), (b, _)| a.cmp(b));
+    r::Value::Object(
+        data.into_iter()
+            .map(|(name, value)| (name, value.into()))
+            .collect(),
+    )
+}
+
+macro_rules! trace_decoded {
+    ($asc:ty, $rust:ty, $render:expr) => {
+        impl TraceValue for AscPtr<$asc> {
+            fn trace_value(&self, ctx: &WasmInstanceContext, gas: &GasCounter) -> r::Value {
+                decode::<$asc, $rust>(*self, ctx, gas, $render)
+            }
+        }
+    };
+}
+
+macro_rules! trace_opaque {
+    ($asc:ty) => {
+        impl TraceValue for AscPtr<$asc> {
+            fn trace_value(&self, _: &WasmInstanceContext, _: &GasCounter) -> r::Value {
+                trace_pointer(self.wasm_ptr())
+            }
+        }
+    };
+}
+
+trace_decoded!(AscString, String, r::Value::String);
+// `AscBigInt` is also a `Uint8Array`; big integers therefore show up as
+// their bytes in little-endian two's complement
+trace_decoded!(Uint8Array, Vec<u8>, |bytes: Vec<u8>| {
+    r::Value::String(scalar::Bytes::from(bytes).to_string())
+});
+trace_decoded!(AscBigDecimal, BigDecimal, |n: BigDecimal| {
+    r::Value::String(n.to_string())
+});
+trace_decoded!(AscEntity, HashMap<Word, store::Value>, entity_value);
+trace_decoded!(
+    AscEnum<StoreValueKind>,
+    store::Value,
+    |value: store::Value| { value.into() }
+);
+trace_decoded!(
+    AscEnum<EthereumValueKind>,
+    ethabi::Token,
+    |token: ethabi::Token| r::Value::String(token.to_string())
+);
+trace_decoded!(Array<AscPtr<AscString>>, Vec<String>, |strings: Vec<
+    String,
+>| {
+    r::Value::List(strings.into_iter().map(r::Value::String).collect())
+});
+trace_decoded!(
+    Array<AscPtr<AscEntity>>,
+    Vec<HashMap<Word, store::Value>>,
+    |entities: Vec<HashMap<Word, store::Value>>| {
+        r::Value::List(entities.into_iter().map(entity_value).collect())
+    }
+);
+trace_opaque!(AscEnum<JsonValueKind>);
+trace_opaque!(AscJson);
+trace_opaque!(AscResult<AscPtr<AscEnum<JsonValueKind>>, bool>);
+
+impl TraceValue for () {
+    fn trace_value(&self, _: &WasmInstanceContext, _: &GasCounter) -> r::Value {
+        r::Value::Null
+    }
+}
+
+impl TraceValue for Never {
+    fn trace_value(&self, _: &WasmInstanceContext, _: &GasCounter) -> r::Value {
+        match *self {}
+    }
+}
+
+impl TraceValue for bool {
+    fn trace_value(&self, _: &WasmInstanceContext, _: &GasCounter) -> r::Value {
+        r::Value::Boolean(*self)
+    }
+}
+
+impl TraceValue for u32 {
+    fn trace_value(&self, _: &WasmInstanceContext, _: &GasCounter) -> r::Value {
+        r::Value::Int(*self as i64)
+    }
+}
+
+impl TraceValue for i64 {
+    fn trace_value(&self, _: &WasmInstanceContext, _: &GasCounter) -> r::Value {
+        r::Value::Int(*self)
+    }
+}
+
+impl TraceValue for u64 {
+    fn trace_value(&self, _: &WasmInstanceContext, _: &GasCounter) -> r::Value {
+        match i64::try_from(*self) {
+            Ok(n) => r::Value::Int(n),
+            Err(_) => r::Value::String(self.to_string()),
+        }
+    }
+}
+
+impl TraceValue for f64 {
+    fn trace_value(&self, _: &WasmInstanceContext, _: &GasCounter) -> r::Value {
+        r::Value::Float(*self)
+    }
+}
@@ -295,6 +295,13 @@ impl FromAscObj<AscEnum<StoreValueKind>> for store::Value {
                 let array: Vec<u8> = asc_get(heap, ptr, gas, depth)?;
                 Value::BigInt(store::scalar::BigInt::from_signed_bytes_le(&array)?)
             }
+            StoreValueKind::Json => {
+                let ptr: AscPtr<AscString> = AscPtr::from(payload);
+                let json: String = asc_get(heap, ptr, gas, depth)?;
+                let json = serde_json::from_str(&json)
+                    .map_err(|e| DeterministicHostError::Other(e.into()))?;
+                Value::from_json(json)
+            }
         })
     }
 }
@@ -325,6 +332,7 @@ impl ToAscObj<AscEnum<StoreValueKind>> for store::Value {
                     asc_new(heap, &*big_int.to_signed_bytes_le(), gas)?;
                 bytes_obj.into()
             }
+            Value::Json(json) => asc_new(heap, json.to_string().as_str(), gas)?.into(),
         };
 
         Ok(AscEnum {
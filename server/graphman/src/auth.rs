@@ -1,11 +1,59 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
 use anyhow::anyhow;
 use axum::http::HeaderMap;
 use graph::http::header::AUTHORIZATION;
 
 use crate::GraphmanServerError;
 
-/// Contains a valid authentication token and checks HTTP headers for valid tokens.
+/// The name of the token that is configured with `GRAPHMAN_SERVER_AUTH_TOKEN`.
+pub const DEFAULT_TOKEN_NAME: &str = "default";
+
+/// Describes what the holder of a token is allowed to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphmanAccess {
+    /// Allows only queries.
+    Read,
+
+    /// Allows queries and mutations.
+    Write,
+}
+
+/// A named auth token and the permissions it grants.
+#[derive(Clone, Debug)]
+pub struct GraphmanAuthToken {
+    /// Identifies the holder of the token in the execution records.
+    pub name: String,
+    pub token: String,
+    pub access: GraphmanAccess,
+
+    /// When not empty, the token only grants access to deployments with these IPFS hashes,
+    /// or to deployments with the namespaces in `namespaces`.
+    pub deployments: Vec<String>,
+
+    /// When not empty, the token only grants access to deployments with these
+    /// database namespaces, or to deployments with the IPFS hashes in `deployments`.
+    pub namespaces: Vec<String>,
+}
+
+/// The identity and permissions of an authenticated request.
+#[derive(Clone, Debug)]
+pub struct GraphmanUser {
+    name: String,
+    access: GraphmanAccess,
+    deployments: HashSet<String>,
+    namespaces: HashSet<String>,
+}
+
+/// Contains all valid tokens and finds the user a request was made by.
 #[derive(Clone)]
+pub struct Authenticator {
+    tokens: Vec<(AuthToken, Arc<GraphmanUser>)>,
+}
+
+/// Contains a valid authentication token and checks HTTP headers for valid tokens.
+#[derive(Clone, Debug)]
 pub struct AuthToken {
     token: Vec<u8>,
 }
@@ -45,6 +93,106 @@ impl AuthToken {
     }
 }
 
+impl GraphmanUser {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn can_write(&self) -> bool {
+        self.access == GraphmanAccess::Write
+    }
+
+    /// Returns `true` if the user can only access some of the deployments.
+    pub fn is_scoped(&self) -> bool {
+        !self.deployments.is_empty() || !self.namespaces.is_empty()
+    }
+
+    /// The IPFS hashes of the deployments a scoped user can access.
+    pub fn deployments(&self) -> &HashSet<String> {
+        &self.deployments
+    }
+
+    /// The database namespaces of the deployments a scoped user can access.
+    pub fn namespaces(&self) -> &HashSet<String> {
+        &self.namespaces
+    }
+
+    pub fn can_access(&self, hash: &str, namespace: &str) -> bool {
+        !self.is_scoped() || self.deployments.contains(hash) || self.namespaces.contains(namespace)
+    }
+}
+
+impl Authenticator {
+    pub fn new(tokens: Vec<GraphmanAuthToken>) -> Result<Self, GraphmanServerError> {
+        if tokens.is_empty() {
+            return Err(GraphmanServerError::InvalidAuthToken(anyhow!(
+                "at least one auth token is required"
+            )));
+        }
+
+        let mut names = HashSet::new();
+        let mut values = HashSet::new();
+
+        let tokens = tokens
+            .into_iter()
+            .map(|token| {
+                let GraphmanAuthToken {
+                    name,
+                    token,
+                    access,
+                    deployments,
+                    namespaces,
+                } = token;
+
+                if name.trim().is_empty() {
+                    return Err(GraphmanServerError::InvalidAuthToken(anyhow!(
+                        "auth token name can not be empty"
+                    )));
+                }
+
+                if !names.insert(name.clone()) {
+                    return Err(GraphmanServerError::InvalidAuthToken(anyhow!(
+                        "auth token name '{name}' is used more than once"
+                    )));
+                }
+
+                if !values.insert(token.trim().to_owned()) {
+                    return Err(GraphmanServerError::InvalidAuthToken(anyhow!(
+                        "auth token '{name}' has the same value as another token"
+                    )));
+                }
+
+                let token = AuthToken::new(token)?;
+
+                let user = GraphmanUser {
+                    name,
+                    access,
+                    deployments: deployments.into_iter().collect(),
+                    namespaces: namespaces.into_iter().collect(),
+                };
+
+                Ok((token, Arc::new(user)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { tokens })
+    }
+
+    /// Returns the user that owns the token in the HTTP headers, if the token is valid.
+    pub fn authenticate(&self, headers: &HeaderMap) -> Option<Arc<GraphmanUser>> {
+        let mut user = None;
+
+        // All tokens are checked, even after a match, to prevent timing attacks.
+        for (token, token_user) in &self.tokens {
+            if token.headers_contain_correct_token(headers) {
+                user = Some(token_user.clone());
+            }
+        }
+
+        user
+    }
+}
+
 pub fn unauthorized_graphql_message() -> serde_json::Value {
     serde_json::json!({
         "errors": [
@@ -145,4 +293,79 @@ mod tests {
         assert!(!token_a.headers_contain_correct_token(&headers));
         assert!(token_b.headers_contain_correct_token(&headers));
     }
+
+    fn auth_token(name: &str, token: &str, access: GraphmanAccess) -> GraphmanAuthToken {
+        GraphmanAuthToken {
+            name: name.to_owned(),
+            token: token.to_owned(),
+            access,
+            deployments: vec![],
+            namespaces: vec![],
+        }
+    }
+
+    #[test]
+    fn require_valid_token_configs() {
+        use GraphmanAccess::*;
+
+        assert!(Authenticator::new(vec![]).is_err());
+        assert!(Authenticator::new(vec![auth_token("", "123", Read)]).is_err());
+        assert!(Authenticator::new(vec![auth_token("a", "", Read)]).is_err());
+
+        assert!(Authenticator::new(vec![
+            auth_token("a", "123", Read),
+            auth_token("a", "abc", Write),
+        ])
+        .is_err());
+
+        assert!(Authenticator::new(vec![
+            auth_token("a", "123", Read),
+            auth_token("b", " 123", Write),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn authenticate_named_tokens() {
+        use GraphmanAccess::*;
+
+        let mut scoped = auth_token("scoped", "xyz", Write);
+        scoped.namespaces = vec!["sgd1".to_owned()];
+
+        let authenticator = Authenticator::new(vec![
+            auth_token("reader", "123", Read),
+            auth_token("writer", "abc", Write),
+            scoped,
+        ])
+        .unwrap();
+
+        let mut headers = HeaderMap::new();
+
+        assert!(authenticator.authenticate(&headers).is_none());
+
+        headers.insert(AUTHORIZATION, bearer_value("12"));
+
+        assert!(authenticator.authenticate(&headers).is_none());
+
+        headers.insert(AUTHORIZATION, bearer_value("123"));
+
+        let user = authenticator.authenticate(&headers).unwrap();
+        assert_eq!(user.name(), "reader");
+        assert!(!user.can_write());
+        assert!(user.can_access("Qm1", "sgd2"));
+
+        headers.insert(AUTHORIZATION, bearer_value("abc"));
+
+        let user = authenticator.authenticate(&headers).unwrap();
+        assert_eq!(user.name(), "writer");
+        assert!(user.can_write());
+
+        headers.insert(AUTHORIZATION, bearer_value("xyz"));
+
+        let user = authenticator.authenticate(&headers).unwrap();
+        assert_eq!(user.name(), "scoped");
+        assert!(user.is_scoped());
+        assert!(user.can_access("Qm1", "sgd1"));
+        assert!(!user.can_access("Qm1", "sgd2"));
+    }
 }
@@ -1,8 +1,18 @@
 use async_graphql::Enum;
 
-/// Types of commands that run in the background.
+/// Types of commands that store data about their execution.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Enum)]
 #[graphql(remote = "graphman_store::CommandKind")]
 pub enum CommandKind {
+    PauseDeployment,
+    ResumeDeployment,
     RestartDeployment,
+    ReassignDeployment,
+    UnassignDeployment,
+    RewindDeployment,
+    PruneDeployment,
+    CopyDeployment,
+    CreateIndex,
+    DropIndex,
+    RemoveSubgraph,
 }
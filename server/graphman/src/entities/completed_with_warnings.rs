@@ -0,0 +1,19 @@
+use async_graphql::SimpleObject;
+
+/// This type is used when an operation has been successful,
+/// but there were some issues that the user should be aware of.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct CompletedWithWarnings {
+    pub success: bool,
+    pub warnings: Vec<String>,
+}
+
+impl CompletedWithWarnings {
+    /// Returns a successful response with the specified warnings.
+    pub fn new(warnings: Vec<String>) -> Self {
+        Self {
+            success: true,
+            warnings,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use async_graphql::SimpleObject;
+use graph::components::store::DeploymentLocator;
+
+/// Describes the new deployment that was created as a copy of another deployment.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct DeploymentCopy {
+    pub hash: String,
+    pub namespace: String,
+}
+
+impl From<DeploymentLocator> for DeploymentCopy {
+    fn from(locator: DeploymentLocator) -> Self {
+        Self {
+            hash: locator.hash.to_string(),
+            namespace: format!("sgd{}", locator.id),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use async_graphql::InputObject;
+
+use crate::entities::BlockNumber;
+use crate::entities::SubgraphHealth;
+
+/// Criteria for filtering deployments by where they are stored and indexed,
+/// and by how they are doing.
+///
+/// All criteria are optional and are combined, a deployment must match all of them.
+#[derive(Clone, Debug, Default, InputObject)]
+pub struct DeploymentFilter {
+    /// Selects deployments with any of the specified health values.
+    pub health: Option<Vec<SubgraphHealth>>,
+
+    /// Selects deployments assigned to this node.
+    pub node_id: Option<String>,
+
+    /// Selects deployments stored in this database shard.
+    pub shard: Option<String>,
+
+    /// Selects deployments indexing this chain.
+    pub chain: Option<String>,
+
+    /// Selects deployments that are paused or not paused.
+    /// Unassigned deployments are treated as not paused.
+    pub is_paused: Option<bool>,
+
+    /// Selects deployments that are at least this many blocks behind the chain head.
+    pub min_lag: Option<BlockNumber>,
+}
+
+impl From<DeploymentFilter> for graph::data::subgraph::status::DeploymentFilter {
+    fn from(filter: DeploymentFilter) -> Self {
+        let DeploymentFilter {
+            health,
+            node_id,
+            shard,
+            chain,
+            is_paused,
+            min_lag,
+        } = filter;
+
+        Self {
+            health: health
+                .unwrap_or_default()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            node: node_id,
+            shard,
+            network: chain,
+            paused: is_paused,
+            min_lag: min_lag.map(|x| x.0),
+            hashes: vec![],
+            namespaces: vec![],
+        }
+    }
+}
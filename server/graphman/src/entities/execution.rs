@@ -17,6 +17,9 @@ pub struct Execution {
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+
+    /// The name of the auth token that was used to request the execution.
+    pub created_by: Option<String>,
 }
 
 /// All possible states of a command execution.
@@ -41,6 +44,7 @@ impl TryFrom<graphman_store::Execution> for Execution {
             created_at,
             updated_at,
             completed_at,
+            created_by,
         } = execution;
 
         Ok(Self {
@@ -51,6 +55,7 @@ impl TryFrom<graphman_store::Execution> for Execution {
             created_at,
             updated_at,
             completed_at,
+            created_by,
         })
     }
 }
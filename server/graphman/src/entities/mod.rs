@@ -2,6 +2,9 @@ mod block_hash;
 mod block_number;
 mod block_ptr;
 mod command_kind;
+mod completed_with_warnings;
+mod deployment_copy;
+mod deployment_filter;
 mod deployment_info;
 mod deployment_selector;
 mod deployment_status;
@@ -15,6 +18,9 @@ pub use self::block_hash::BlockHash;
 pub use self::block_number::BlockNumber;
 pub use self::block_ptr::BlockPtr;
 pub use self::command_kind::CommandKind;
+pub use self::completed_with_warnings::CompletedWithWarnings;
+pub use self::deployment_copy::DeploymentCopy;
+pub use self::deployment_filter::DeploymentFilter;
 pub use self::deployment_info::DeploymentInfo;
 pub use self::deployment_selector::DeploymentSelector;
 pub use self::deployment_status::DeploymentStatus;
@@ -26,11 +26,12 @@ pub async fn graphql_request_handler(
     headers: HeaderMap,
     req: GraphQLRequest,
 ) -> Response {
-    if !state.auth_token.headers_contain_correct_token(&headers) {
+    let Some(user) = state.authenticator.authenticate(&headers) else {
         return Json(unauthorized_graphql_message()).into_response();
-    }
+    };
 
-    let resp: GraphQLResponse = schema.execute(req.into_inner()).await.into();
+    let req = req.into_inner().data(user);
+    let resp: GraphQLResponse = schema.execute(req).await.into();
 
     resp.into_response()
 }
@@ -1,6 +1,6 @@
-use crate::auth::AuthToken;
+use crate::auth::Authenticator;
 
 /// The state that is shared between all request handlers.
 pub struct AppState {
-    pub auth_token: AuthToken,
+    pub authenticator: Authenticator,
 }
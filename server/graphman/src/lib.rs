@@ -6,6 +6,8 @@ mod resolvers;
 mod schema;
 mod server;
 
+pub use self::auth::GraphmanAccess;
+pub use self::auth::GraphmanAuthToken;
 pub use self::error::GraphmanServerError;
 pub use self::server::GraphmanServer;
 pub use self::server::GraphmanServerConfig;
@@ -1,15 +1,27 @@
+use std::future::Future;
 use std::sync::Arc;
 
 use async_graphql::Context;
 use async_graphql::Result;
 use graph_store_postgres::connection_pool::ConnectionPool;
+use graph_store_postgres::graphman::GraphmanStore;
 use graph_store_postgres::NotificationSender;
 use graph_store_postgres::Store;
+use graphman::deployment::Deployment;
+use graphman::deployment::DeploymentSelector;
+use graphman::deployment::DeploymentVersionSelector;
+use graphman_store::CommandKind;
+use graphman_store::Execution;
+use graphman_store::GraphmanStore as _;
+
+use crate::auth::GraphmanUser;
 
 pub struct GraphmanContext {
     pub primary_pool: ConnectionPool,
     pub notification_sender: Arc<NotificationSender>,
     pub store: Arc<Store>,
+    pub graphman_store: Arc<GraphmanStore>,
+    pub user: Arc<GraphmanUser>,
 }
 
 impl GraphmanContext {
@@ -17,11 +29,143 @@ impl GraphmanContext {
         let primary_pool = ctx.data::<ConnectionPool>()?.to_owned();
         let notification_sender = ctx.data::<Arc<NotificationSender>>()?.to_owned();
         let store = ctx.data::<Arc<Store>>()?.to_owned();
+        let graphman_store = ctx.data::<Arc<GraphmanStore>>()?.to_owned();
+        let user = ctx.data::<Arc<GraphmanUser>>()?.to_owned();
 
         Ok(GraphmanContext {
             primary_pool,
             notification_sender,
             store,
+            graphman_store,
+            user,
         })
     }
+
+    pub fn can_access(&self, deployment: &Deployment) -> bool {
+        self.user
+            .can_access(&deployment.hash, &deployment.namespace)
+    }
+
+    fn load_deployments(&self, deployment: &DeploymentSelector) -> Result<Vec<Deployment>> {
+        let deployments = graphman::commands::deployment::info::load_deployments(
+            self.primary_pool.clone(),
+            deployment,
+            &DeploymentVersionSelector::All,
+        )?;
+
+        Ok(deployments)
+    }
+
+    /// Fails if the selector matches any deployment the user is not allowed to access.
+    pub fn authorize(&self, deployment: &DeploymentSelector) -> Result<()> {
+        if !self.user.is_scoped() {
+            return Ok(());
+        }
+
+        let deployments = self.load_deployments(deployment)?;
+
+        if let Some(deployment) = deployments.iter().find(|d| !self.can_access(d)) {
+            return Err(format!(
+                "token '{}' is not allowed to access deployment '{}'",
+                self.user.name(),
+                deployment.hash
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Fails if a user that can only access some deployments tries to copy a deployment
+    /// into a different shard or onto a different node than the ones it is using now.
+    pub fn authorize_copy(
+        &self,
+        deployment: &DeploymentSelector,
+        shard: &str,
+        node: &str,
+    ) -> Result<()> {
+        if !self.user.is_scoped() {
+            return Ok(());
+        }
+
+        for deployment in self.load_deployments(deployment)? {
+            if deployment.shard != shard {
+                return Err(format!(
+                    "token '{}' is not allowed to copy deployment '{}' into shard '{}'",
+                    self.user.name(),
+                    deployment.hash,
+                    shard
+                )
+                .into());
+            }
+
+            if deployment.node_id.as_deref() != Some(node) {
+                return Err(format!(
+                    "token '{}' is not allowed to copy deployment '{}' onto node '{}'",
+                    self.user.name(),
+                    deployment.hash,
+                    node
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fails if a user that can only access some deployments tries to access
+    /// an execution that was started with a different token.
+    ///
+    /// Executions do not record the deployments they were run for, so this is
+    /// the only way to keep scoped tokens from seeing other deployments' executions.
+    pub fn authorize_execution(&self, execution: &Execution) -> Result<()> {
+        if !self.user.is_scoped() {
+            return Ok(());
+        }
+
+        if execution.created_by.as_deref() != Some(self.user.name()) {
+            return Err(format!(
+                "token '{}' is not allowed to access execution '{}'",
+                self.user.name(),
+                execution.id.0
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Fails unless the user is allowed to access all deployments.
+    pub fn authorize_all(&self) -> Result<()> {
+        if self.user.is_scoped() {
+            return Err(format!(
+                "token '{}' is only allowed to access some deployments",
+                self.user.name()
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Runs a command that completes immediately and stores a record of its execution,
+    /// so that it is known who ran it.
+    pub async fn record<T>(
+        &self,
+        kind: CommandKind,
+        command: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let id = self.graphman_store.new_execution(kind, self.user.name())?;
+
+        let result = command.await;
+
+        match &result {
+            Ok(_) => self.graphman_store.mark_execution_as_succeeded(id)?,
+            Err(err) => self
+                .graphman_store
+                .mark_execution_as_failed(id, err.message.clone())?,
+        }
+
+        result
+    }
 }
@@ -3,16 +3,31 @@ use std::sync::Arc;
 use async_graphql::Context;
 use async_graphql::Object;
 use async_graphql::Result;
+use graph::env::ENV_VARS;
 use graph_store_postgres::graphman::GraphmanStore;
+use graphman::commands::deployment::index::IndexDefinition;
+use graphman::commands::deployment::prune::PruneOptions;
+use graphman_store::CommandKind;
 
+use crate::entities::BlockHash;
+use crate::entities::BlockNumber;
+use crate::entities::CompletedWithWarnings;
+use crate::entities::DeploymentCopy;
 use crate::entities::DeploymentSelector;
 use crate::entities::EmptyResponse;
 use crate::entities::ExecutionId;
 use crate::resolvers::context::GraphmanContext;
 
+mod copy;
+mod create_index;
+mod drop_index;
 mod pause;
+mod prune;
+mod reassign;
 mod restart;
 mod resume;
+mod rewind;
+mod unassign;
 
 pub struct DeploymentMutation;
 
@@ -27,8 +42,12 @@ impl DeploymentMutation {
     ) -> Result<EmptyResponse> {
         let ctx = GraphmanContext::new(ctx)?;
         let deployment = deployment.try_into()?;
+        ctx.authorize(&deployment)?;
 
-        pause::run(&ctx, &deployment)?;
+        ctx.record(CommandKind::PauseDeployment, async {
+            pause::run(&ctx, &deployment)
+        })
+        .await?;
 
         Ok(EmptyResponse::new())
     }
@@ -41,8 +60,12 @@ impl DeploymentMutation {
     ) -> Result<EmptyResponse> {
         let ctx = GraphmanContext::new(ctx)?;
         let deployment = deployment.try_into()?;
+        ctx.authorize(&deployment)?;
 
-        resume::run(&ctx, &deployment)?;
+        ctx.record(CommandKind::ResumeDeployment, async {
+            resume::run(&ctx, &deployment)
+        })
+        .await?;
 
         Ok(EmptyResponse::new())
     }
@@ -62,7 +85,222 @@ impl DeploymentMutation {
         let store = ctx.data::<Arc<GraphmanStore>>()?.to_owned();
         let ctx = GraphmanContext::new(ctx)?;
         let deployment = deployment.try_into()?;
+        ctx.authorize(&deployment)?;
 
         restart::run_in_background(ctx, store, deployment, delay_seconds).await
     }
+
+    /// Assigns a deployment to a node, or moves it to another node if it is already assigned.
+    pub async fn reassign(
+        &self,
+        ctx: &Context<'_>,
+        deployment: DeploymentSelector,
+        #[graphql(desc = "The ID of the node that should index the deployment.")] node: String,
+    ) -> Result<CompletedWithWarnings> {
+        let ctx = GraphmanContext::new(ctx)?;
+        let deployment = deployment.try_into()?;
+        ctx.authorize(&deployment)?;
+
+        ctx.record(CommandKind::ReassignDeployment, async {
+            reassign::run(&ctx, &deployment, &node)
+        })
+        .await
+    }
+
+    /// Unassigns a deployment from its node, which stops indexing it.
+    pub async fn unassign(
+        &self,
+        ctx: &Context<'_>,
+        deployment: DeploymentSelector,
+    ) -> Result<EmptyResponse> {
+        let ctx = GraphmanContext::new(ctx)?;
+        let deployment = deployment.try_into()?;
+        ctx.authorize(&deployment)?;
+
+        ctx.record(CommandKind::UnassignDeployment, async {
+            unassign::run(&ctx, &deployment)
+        })
+        .await?;
+
+        Ok(EmptyResponse::new())
+    }
+
+    /// Pauses a deployment, rewinds it to the specified block, and resumes it.
+    ///
+    /// The deployment is resumed even if rewinding it fails.
+    pub async fn rewind(
+        &self,
+        ctx: &Context<'_>,
+        deployment: DeploymentSelector,
+        #[graphql(desc = "The hash of the block to rewind to.
+                          Required when `startBlock` is not set.")]
+        block_hash: Option<BlockHash>,
+        #[graphql(desc = "The number of the block to rewind to.
+                          Required when `startBlock` is not set.")]
+        block_number: Option<BlockNumber>,
+        #[graphql(
+            default = false,
+            desc = "Rewinds the deployment to its start block, which removes all its data."
+        )]
+        start_block: bool,
+        #[graphql(
+            default = false,
+            desc = "Rewinds the deployment even if the block is not found in the chain store."
+        )]
+        force: bool,
+        #[graphql(
+            default = 20,
+            desc = "The number of seconds to wait after pausing the deployment before rewinding it.
+                    When not specified, it defaults to 20 seconds."
+        )]
+        delay_seconds: u64,
+    ) -> Result<ExecutionId> {
+        let store = ctx.data::<Arc<GraphmanStore>>()?.to_owned();
+        let ctx = GraphmanContext::new(ctx)?;
+        let deployment = deployment.try_into()?;
+        ctx.authorize(&deployment)?;
+
+        let args = rewind::Args {
+            block_hash,
+            block_number,
+            start_block,
+            force,
+            delay_seconds,
+        };
+
+        rewind::run_in_background(ctx, store, deployment, args).await
+    }
+
+    /// Removes entity versions that are older than the specified number of blocks.
+    ///
+    /// Unless `once` is set, the history setting is permanent and the deployment will be
+    /// pruned periodically as it makes progress.
+    pub async fn prune(
+        &self,
+        ctx: &Context<'_>,
+        deployment: DeploymentSelector,
+        #[graphql(desc = "The number of blocks of history to keep.
+                          When not specified, it defaults to `GRAPH_MIN_HISTORY_BLOCKS`.")]
+        history_blocks: Option<BlockNumber>,
+        #[graphql(
+            desc = "Prune by rebuilding tables when removing more than this fraction
+                          of history. Defaults to `GRAPH_STORE_HISTORY_REBUILD_THRESHOLD`."
+        )]
+        rebuild_threshold: Option<f64>,
+        #[graphql(
+            desc = "Prune by deleting when removing more than this fraction of history
+                          but less than `rebuildThreshold`.
+                          Defaults to `GRAPH_STORE_HISTORY_DELETE_THRESHOLD`."
+        )]
+        delete_threshold: Option<f64>,
+        #[graphql(default = false, desc = "Prunes the deployment only this once.")] once: bool,
+    ) -> Result<ExecutionId> {
+        let store = ctx.data::<Arc<GraphmanStore>>()?.to_owned();
+        let ctx = GraphmanContext::new(ctx)?;
+        let deployment = deployment.try_into()?;
+        ctx.authorize(&deployment)?;
+
+        let options = PruneOptions {
+            history_blocks: history_blocks
+                .map(|block_number| block_number.0)
+                .unwrap_or(ENV_VARS.min_history_blocks),
+            rebuild_threshold,
+            delete_threshold,
+            once,
+        };
+
+        prune::run_in_background(ctx, store, deployment, options).await
+    }
+
+    /// Creates a copy of a deployment in the specified shard and assigns it to a node.
+    ///
+    /// The copy starts from the block that is `blockOffset` blocks behind the latest block
+    /// of the source deployment and is treated as its own deployment.
+    pub async fn copy(
+        &self,
+        ctx: &Context<'_>,
+        deployment: DeploymentSelector,
+        #[graphql(desc = "The name of the database shard into which to copy.")] shard: String,
+        #[graphql(desc = "The ID of the node that should index the copy.")] node: String,
+        #[graphql(
+            default = 200,
+            desc = "How far behind the latest block of the source deployment to copy.
+                    When not specified, it defaults to 200 blocks."
+        )]
+        block_offset: u32,
+        #[graphql(default = false, desc = "Activates the copy once it has synced.")] activate: bool,
+        #[graphql(
+            default = false,
+            desc = "Replaces the source deployment with the copy once it has synced."
+        )]
+        replace: bool,
+    ) -> Result<DeploymentCopy> {
+        let ctx = GraphmanContext::new(ctx)?;
+        let deployment = deployment.try_into()?;
+        ctx.authorize(&deployment)?;
+        ctx.authorize_copy(&deployment, &shard, &node)?;
+
+        let args = copy::Args {
+            shard,
+            node,
+            block_offset,
+            activate,
+            replace,
+        };
+
+        ctx.record(
+            CommandKind::CopyDeployment,
+            copy::run(&ctx, &deployment, args),
+        )
+        .await
+    }
+
+    /// Creates a database index on an entity table of a deployment.
+    pub async fn create_index(
+        &self,
+        ctx: &Context<'_>,
+        deployment: DeploymentSelector,
+        #[graphql(desc = "The name of the entity type.")] entity: String,
+        #[graphql(desc = "The entity fields that should be indexed.")] fields: Vec<String>,
+        #[graphql(desc = "The index method. When not specified, it defaults to `gist`
+                          for indexes on `block_range`, and to `btree` otherwise.")]
+        method: Option<String>,
+        #[graphql(desc = "Creates a partial index for entity versions after this block.")]
+        after: Option<BlockNumber>,
+    ) -> Result<ExecutionId> {
+        let store = ctx.data::<Arc<GraphmanStore>>()?.to_owned();
+        let ctx = GraphmanContext::new(ctx)?;
+        let deployment = deployment.try_into()?;
+        ctx.authorize(&deployment)?;
+
+        let index = IndexDefinition {
+            entity_name: entity,
+            field_names: fields,
+            method,
+            after: after.map(|block_number| block_number.0),
+        };
+
+        create_index::run_in_background(ctx, store, deployment, index).await
+    }
+
+    /// Drops a database index of a deployment.
+    pub async fn drop_index(
+        &self,
+        ctx: &Context<'_>,
+        deployment: DeploymentSelector,
+        #[graphql(desc = "The name of the index, as shown by `graphman index list`.")]
+        index_name: String,
+    ) -> Result<EmptyResponse> {
+        let ctx = GraphmanContext::new(ctx)?;
+        let deployment = deployment.try_into()?;
+        ctx.authorize(&deployment)?;
+
+        ctx.record(
+            CommandKind::DropIndex,
+            drop_index::run(&ctx, &deployment, &index_name),
+        )
+        .await?;
+
+        Ok(EmptyResponse::new())
+    }
 }
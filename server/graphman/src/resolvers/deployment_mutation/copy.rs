@@ -0,0 +1,49 @@
+use async_graphql::Result;
+use graph_store_postgres::command_support::OnSync;
+use graphman::commands::deployment::copy::copy_deployment;
+use graphman::commands::deployment::copy::load_copy_source;
+use graphman::deployment::DeploymentSelector;
+
+use crate::entities::DeploymentCopy;
+use crate::resolvers::context::GraphmanContext;
+
+pub struct Args {
+    pub shard: String,
+    pub node: String,
+    pub block_offset: u32,
+    pub activate: bool,
+    pub replace: bool,
+}
+
+pub async fn run(
+    ctx: &GraphmanContext,
+    deployment: &DeploymentSelector,
+    args: Args,
+) -> Result<DeploymentCopy> {
+    let Args {
+        shard,
+        node,
+        block_offset,
+        activate,
+        replace,
+    } = args;
+
+    let on_sync = match (activate, replace) {
+        (true, true) => return Err("activate and replace can not be used at the same time".into()),
+        (true, false) => OnSync::Activate,
+        (false, true) => OnSync::Replace,
+        (false, false) => OnSync::None,
+    };
+
+    let source = load_copy_source(
+        ctx.primary_pool.clone(),
+        ctx.store.clone(),
+        deployment,
+        block_offset,
+    )
+    .await?;
+
+    let copy = copy_deployment(ctx.store.clone(), source, &shard, &node, on_sync)?;
+
+    Ok(copy.into())
+}
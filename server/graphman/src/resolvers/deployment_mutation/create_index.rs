@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use async_graphql::Result;
+use graph_store_postgres::graphman::GraphmanStore;
+use graphman::commands::deployment::index::create_index;
+use graphman::commands::deployment::index::load_deployment;
+use graphman::commands::deployment::index::IndexDefinition;
+use graphman::deployment::DeploymentSelector;
+use graphman::GraphmanExecutionTracker;
+use graphman_store::CommandKind;
+use graphman_store::GraphmanStore as _;
+
+use crate::entities::ExecutionId;
+use crate::resolvers::context::GraphmanContext;
+
+pub async fn run_in_background(
+    ctx: GraphmanContext,
+    store: Arc<GraphmanStore>,
+    deployment: DeploymentSelector,
+    index: IndexDefinition,
+) -> Result<ExecutionId> {
+    let locator = load_deployment(ctx.primary_pool.clone(), &deployment)?;
+    let id = store.new_execution(CommandKind::CreateIndex, ctx.user.name())?;
+
+    graph::spawn(async move {
+        let tracker = GraphmanExecutionTracker::new(store, id);
+        let result = create_index(ctx.store.subgraph_store(), &locator, index).await;
+
+        match result {
+            Ok(()) => {
+                tracker.track_success().unwrap();
+            }
+            Err(err) => {
+                tracker.track_failure(format!("{err:#?}")).unwrap();
+            }
+        };
+    });
+
+    Ok(id.into())
+}
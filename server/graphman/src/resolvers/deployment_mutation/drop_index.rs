@@ -0,0 +1,18 @@
+use async_graphql::Result;
+use graphman::commands::deployment::index::drop_index;
+use graphman::commands::deployment::index::load_deployment;
+use graphman::deployment::DeploymentSelector;
+
+use crate::resolvers::context::GraphmanContext;
+
+pub async fn run(
+    ctx: &GraphmanContext,
+    deployment: &DeploymentSelector,
+    index_name: &str,
+) -> Result<()> {
+    let locator = load_deployment(ctx.primary_pool.clone(), deployment)?;
+
+    drop_index(ctx.store.subgraph_store(), &locator, index_name).await?;
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use async_graphql::Result;
+use graph::components::store::PruneReporter;
+use graph_store_postgres::graphman::GraphmanStore;
+use graphman::commands::deployment::prune::load_prunable_deployment;
+use graphman::commands::deployment::prune::prune_deployment;
+use graphman::commands::deployment::prune::PruneOptions;
+use graphman::deployment::DeploymentSelector;
+use graphman::GraphmanExecutionTracker;
+use graphman_store::CommandKind;
+use graphman_store::GraphmanStore as _;
+
+use crate::entities::ExecutionId;
+use crate::resolvers::context::GraphmanContext;
+
+/// The progress of background executions is only tracked by their status.
+struct NoopReporter;
+
+impl PruneReporter for NoopReporter {}
+
+pub async fn run_in_background(
+    ctx: GraphmanContext,
+    store: Arc<GraphmanStore>,
+    deployment: DeploymentSelector,
+    options: PruneOptions,
+) -> Result<ExecutionId> {
+    let prunable_deployment = load_prunable_deployment(
+        ctx.primary_pool.clone(),
+        ctx.store.clone(),
+        &deployment,
+        options.history_blocks,
+    )?;
+
+    let id = store.new_execution(CommandKind::PruneDeployment, ctx.user.name())?;
+
+    graph::spawn(async move {
+        let tracker = GraphmanExecutionTracker::new(store, id);
+        let result = prune_deployment(
+            ctx.store.clone(),
+            prunable_deployment,
+            options,
+            Box::new(NoopReporter),
+        )
+        .await;
+
+        match result {
+            Ok(()) => {
+                tracker.track_success().unwrap();
+            }
+            Err(err) => {
+                tracker.track_failure(format!("{err:#?}")).unwrap();
+            }
+        };
+    });
+
+    Ok(id.into())
+}
@@ -0,0 +1,30 @@
+use async_graphql::Result;
+use graphman::commands::deployment::reassign::load_deployment;
+use graphman::commands::deployment::reassign::reassign_deployment;
+use graphman::commands::deployment::reassign::ReassignResult;
+use graphman::deployment::DeploymentSelector;
+
+use crate::entities::CompletedWithWarnings;
+use crate::resolvers::context::GraphmanContext;
+
+pub fn run(
+    ctx: &GraphmanContext,
+    deployment: &DeploymentSelector,
+    node: &str,
+) -> Result<CompletedWithWarnings> {
+    let deployment = load_deployment(ctx.primary_pool.clone(), deployment)?;
+
+    let result = reassign_deployment(
+        ctx.primary_pool.clone(),
+        ctx.notification_sender.clone(),
+        deployment,
+        node,
+    )?;
+
+    let warnings = match result {
+        ReassignResult::Ok => vec![],
+        ReassignResult::CompletedWithWarnings(warnings) => warnings,
+    };
+
+    Ok(CompletedWithWarnings::new(warnings))
+}
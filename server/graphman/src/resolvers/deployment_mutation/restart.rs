@@ -17,7 +17,7 @@ pub async fn run_in_background(
     deployment: DeploymentSelector,
     delay_seconds: u64,
 ) -> Result<ExecutionId> {
-    let id = store.new_execution(CommandKind::RestartDeployment)?;
+    let id = store.new_execution(CommandKind::RestartDeployment, ctx.user.name())?;
 
     graph::spawn(async move {
         let tracker = GraphmanExecutionTracker::new(store, id);
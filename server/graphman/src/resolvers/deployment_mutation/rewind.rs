@@ -0,0 +1,140 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_graphql::Result;
+use graph::blockchain::BlockPtr;
+use graph_store_postgres::graphman::GraphmanStore;
+use graphman::commands::deployment::rewind::check_rewind_is_safe;
+use graphman::commands::deployment::rewind::load_block_ptr;
+use graphman::commands::deployment::rewind::load_deployments;
+use graphman::commands::deployment::rewind::pause_deployments;
+use graphman::commands::deployment::rewind::resume_deployments;
+use graphman::commands::deployment::rewind::rewind_deployment;
+use graphman::deployment::Deployment;
+use graphman::deployment::DeploymentSelector;
+use graphman::GraphmanExecutionTracker;
+use graphman_store::CommandKind;
+use graphman_store::GraphmanStore as _;
+
+use crate::entities::BlockHash;
+use crate::entities::BlockNumber;
+use crate::entities::ExecutionId;
+use crate::resolvers::context::GraphmanContext;
+
+pub struct Args {
+    pub block_hash: Option<BlockHash>,
+    pub block_number: Option<BlockNumber>,
+    pub start_block: bool,
+    pub force: bool,
+    pub delay_seconds: u64,
+}
+
+pub async fn run_in_background(
+    ctx: GraphmanContext,
+    store: Arc<GraphmanStore>,
+    deployment: DeploymentSelector,
+    args: Args,
+) -> Result<ExecutionId> {
+    let Args {
+        block_hash,
+        block_number,
+        start_block,
+        force,
+        delay_seconds,
+    } = args;
+
+    // Everything that can be checked up front is checked before the execution starts,
+    // so that invalid requests fail immediately instead of in the background.
+    let deployments = load_deployments(ctx.primary_pool.clone(), &[deployment])?;
+
+    let block_ptr = match (start_block, block_hash, block_number) {
+        (true, None, None) => None,
+        (false, Some(block_hash), Some(block_number)) => Some(
+            load_block_ptr(
+                ctx.store.clone(),
+                &deployments,
+                &block_hash.0,
+                block_number.0,
+                force,
+            )
+            .await?,
+        ),
+        (true, _, _) => {
+            return Err("block hash and block number can not be used with start block".into());
+        }
+        (false, _, _) => {
+            return Err(
+                "block hash and block number must be specified when start block is not set".into(),
+            );
+        }
+    };
+
+    check_rewind_is_safe(
+        ctx.primary_pool.clone(),
+        ctx.store.clone(),
+        &deployments,
+        block_ptr.as_ref(),
+    )?;
+
+    let id = store.new_execution(CommandKind::RewindDeployment, ctx.user.name())?;
+
+    graph::spawn(async move {
+        let tracker = GraphmanExecutionTracker::new(store, id);
+        let result = run(&ctx, &deployments, block_ptr, delay_seconds).await;
+
+        match result {
+            Ok(()) => {
+                tracker.track_success().unwrap();
+            }
+            Err(err) => {
+                tracker.track_failure(format!("{err:#?}")).unwrap();
+            }
+        };
+    });
+
+    Ok(id.into())
+}
+
+async fn run(
+    ctx: &GraphmanContext,
+    deployments: &[Deployment],
+    block_ptr: Option<BlockPtr>,
+    delay_seconds: u64,
+) -> Result<()> {
+    pause_deployments(
+        ctx.primary_pool.clone(),
+        ctx.notification_sender.clone(),
+        deployments,
+    )?;
+
+    // There's no good way to tell that a deployment has in fact stopped indexing.
+    tokio::time::sleep(Duration::from_secs(delay_seconds)).await;
+
+    let rewound = deployments.iter().try_for_each(|deployment| {
+        rewind_deployment(ctx.store.clone(), deployment, block_ptr.clone()).map(|_| ())
+    });
+
+    // The deployments are resumed even when rewinding failed, so that they
+    // don't stay paused; a deployment that failed to rewind is left at the
+    // block it was at before.
+    let resumed = resume_deployments(
+        ctx.primary_pool.clone(),
+        ctx.notification_sender.clone(),
+        deployments,
+    );
+
+    match (rewound, resumed) {
+        (Ok(()), Ok(())) => Ok(()),
+        (Err(err), Ok(())) => Err(err.into()),
+        (Ok(()), Err(err)) => Err(format!(
+            "the deployments were rewound but are still paused \
+             because resuming them failed: {err:#}"
+        )
+        .into()),
+        (Err(err), Err(resume_err)) => Err(format!(
+            "{err:#}; the deployments are still paused \
+             because resuming them failed: {resume_err:#}"
+        )
+        .into()),
+    }
+}
@@ -2,11 +2,13 @@ use async_graphql::Context;
 use async_graphql::Object;
 use async_graphql::Result;
 
+use crate::entities::DeploymentFilter;
 use crate::entities::DeploymentInfo;
 use crate::entities::DeploymentSelector;
 use crate::entities::DeploymentVersionSelector;
 
 mod info;
+mod list;
 
 pub struct DeploymentQuery;
 
@@ -26,4 +28,25 @@ impl DeploymentQuery {
     ) -> Result<Vec<DeploymentInfo>> {
         info::run(ctx, deployment, version)
     }
+
+    /// Returns a page of the active deployments that match the filter, ordered by deployment id.
+    ///
+    /// Each deployment is listed once. When it has multiple names,
+    /// the name that uses it as the current version is preferred.
+    pub async fn list(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Criteria the deployments must match.
+                          When not provided, it matches all active deployments.")]
+        filter: Option<DeploymentFilter>,
+        #[graphql(
+            default = 100,
+            desc = "The maximum number of deployments to return, which can not be larger than 1000.
+                    When not specified, it defaults to 100."
+        )]
+        first: u32,
+        #[graphql(default = 0, desc = "The number of matching deployments to skip.")] skip: u32,
+    ) -> Result<Vec<DeploymentInfo>> {
+        list::run(ctx, filter, first, skip)
+    }
 }
@@ -23,12 +23,14 @@ pub fn run(
         .map(Into::into)
         .unwrap_or(graphman::deployment::DeploymentVersionSelector::All);
 
-    let deployments = graphman::commands::deployment::info::load_deployments(
+    let mut deployments = graphman::commands::deployment::info::load_deployments(
         ctx.primary_pool.clone(),
         &deployment,
         &version,
     )?;
 
+    deployments.retain(|deployment| ctx.can_access(deployment));
+
     let statuses = if load_status {
         graphman::commands::deployment::info::load_deployment_statuses(
             ctx.store.clone(),
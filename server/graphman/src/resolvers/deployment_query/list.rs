@@ -0,0 +1,63 @@
+use async_graphql::Context;
+use async_graphql::Result;
+
+use crate::entities::DeploymentFilter;
+use crate::entities::DeploymentInfo;
+use crate::resolvers::context::GraphmanContext;
+
+/// The largest number of deployments that can be listed at once.
+const MAX_FIRST: u32 = 1000;
+
+pub fn run(
+    ctx: &Context<'_>,
+    filter: Option<DeploymentFilter>,
+    first: u32,
+    skip: u32,
+) -> Result<Vec<DeploymentInfo>> {
+    if first > MAX_FIRST {
+        return Err(format!("first can not be larger than {MAX_FIRST}").into());
+    }
+
+    let load_status = ctx.look_ahead().field("status").exists();
+    let ctx = GraphmanContext::new(ctx)?;
+
+    let mut filter: graph::data::subgraph::status::DeploymentFilter =
+        filter.unwrap_or_default().into();
+
+    // Deployments the user can not access must not count towards the page
+    if ctx.user.is_scoped() {
+        filter.hashes = ctx.user.deployments().iter().cloned().collect();
+        filter.namespaces = ctx.user.namespaces().iter().cloned().collect();
+    }
+
+    let deployments = graphman::commands::deployment::list::load_matching_deployments(
+        ctx.primary_pool.clone(),
+        ctx.store.clone(),
+        filter,
+        first as usize,
+        skip as usize,
+    )?;
+
+    let statuses = if load_status {
+        graphman::commands::deployment::info::load_deployment_statuses(
+            ctx.store.clone(),
+            &deployments,
+        )?
+    } else {
+        Default::default()
+    };
+
+    let resp = deployments
+        .into_iter()
+        .map(|deployment| {
+            let status = statuses.get(&deployment.id).cloned().map(Into::into);
+
+            let mut info: DeploymentInfo = deployment.into();
+            info.status = status;
+
+            info
+        })
+        .collect();
+
+    Ok(resp)
+}
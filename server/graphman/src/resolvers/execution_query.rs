@@ -1,13 +1,11 @@
-use std::sync::Arc;
-
 use async_graphql::Context;
 use async_graphql::Object;
 use async_graphql::Result;
-use graph_store_postgres::graphman::GraphmanStore;
 use graphman_store::GraphmanStore as _;
 
 use crate::entities::Execution;
 use crate::entities::ExecutionId;
+use crate::resolvers::context::GraphmanContext;
 
 pub struct ExecutionQuery;
 
@@ -16,8 +14,9 @@ pub struct ExecutionQuery;
 impl ExecutionQuery {
     /// Returns all stored command execution data.
     pub async fn info(&self, ctx: &Context<'_>, id: ExecutionId) -> Result<Execution> {
-        let store = ctx.data::<Arc<GraphmanStore>>()?.to_owned();
-        let execution = store.load_execution(id.into())?;
+        let ctx = GraphmanContext::new(ctx)?;
+        let execution = ctx.graphman_store.load_execution(id.into())?;
+        ctx.authorize_execution(&execution)?;
 
         Ok(execution.try_into()?)
     }
@@ -4,9 +4,11 @@ mod deployment_query;
 mod execution_query;
 mod mutation_root;
 mod query_root;
+mod subgraph_mutation;
 
 pub use self::deployment_mutation::DeploymentMutation;
 pub use self::deployment_query::DeploymentQuery;
 pub use self::execution_query::ExecutionQuery;
 pub use self::mutation_root::MutationRoot;
 pub use self::query_root::QueryRoot;
+pub use self::subgraph_mutation::SubgraphMutation;
@@ -1,6 +1,12 @@
+use std::sync::Arc;
+
+use async_graphql::Context;
 use async_graphql::Object;
+use async_graphql::Result;
 
+use crate::auth::GraphmanUser;
 use crate::resolvers::DeploymentMutation;
+use crate::resolvers::SubgraphMutation;
 
 /// Note: Converted to GraphQL schema as `mutation`.
 pub struct MutationRoot;
@@ -8,7 +14,26 @@ pub struct MutationRoot;
 #[Object]
 impl MutationRoot {
     /// Mutations related to one or multiple deployments.
-    pub async fn deployment(&self) -> DeploymentMutation {
-        DeploymentMutation {}
+    pub async fn deployment(&self, ctx: &Context<'_>) -> Result<DeploymentMutation> {
+        require_write_access(ctx)?;
+
+        Ok(DeploymentMutation {})
+    }
+
+    /// Mutations related to subgraph names.
+    pub async fn subgraph(&self, ctx: &Context<'_>) -> Result<SubgraphMutation> {
+        require_write_access(ctx)?;
+
+        Ok(SubgraphMutation {})
     }
 }
+
+fn require_write_access(ctx: &Context<'_>) -> Result<()> {
+    let user = ctx.data::<Arc<GraphmanUser>>()?;
+
+    if !user.can_write() {
+        return Err(format!("token '{}' is not allowed to run mutations", user.name()).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use async_graphql::Context;
+use async_graphql::Object;
+use async_graphql::Result;
+use graphman_store::CommandKind;
+
+use crate::entities::EmptyResponse;
+use crate::resolvers::context::GraphmanContext;
+
+mod remove;
+
+pub struct SubgraphMutation;
+
+/// Mutations related to subgraph names.
+#[Object]
+impl SubgraphMutation {
+    /// Removes a subgraph name and all its versions.
+    ///
+    /// Deployments that are no longer used by any subgraph are unassigned,
+    /// but their data is kept until they are removed as unused deployments.
+    pub async fn remove(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The full name of the subgraph.")] name: String,
+    ) -> Result<EmptyResponse> {
+        let ctx = GraphmanContext::new(ctx)?;
+        ctx.authorize_all()?;
+
+        ctx.record(CommandKind::RemoveSubgraph, async {
+            remove::run(&ctx, &name)
+        })
+        .await?;
+
+        Ok(EmptyResponse::new())
+    }
+}
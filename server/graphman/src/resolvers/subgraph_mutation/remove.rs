@@ -0,0 +1,13 @@
+use async_graphql::Result;
+use graphman::commands::subgraph::remove::load_existing_subgraph;
+use graphman::commands::subgraph::remove::remove_existing_subgraph;
+
+use crate::resolvers::context::GraphmanContext;
+
+pub fn run(ctx: &GraphmanContext, name: &str) -> Result<()> {
+    let existing_subgraph = load_existing_subgraph(ctx.primary_pool.clone(), name)?;
+
+    remove_existing_subgraph(ctx.store.subgraph_store(), existing_subgraph)?;
+
+    Ok(())
+}
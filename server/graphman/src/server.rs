@@ -18,7 +18,10 @@ use slog::{info, Logger};
 use tokio::sync::Notify;
 use tower_http::cors::{Any, CorsLayer};
 
-use crate::auth::AuthToken;
+use crate::auth::Authenticator;
+use crate::auth::GraphmanAccess;
+use crate::auth::GraphmanAuthToken;
+use crate::auth::DEFAULT_TOKEN_NAME;
 use crate::handlers::graphql_playground_handler;
 use crate::handlers::graphql_request_handler;
 use crate::handlers::AppState;
@@ -33,7 +36,7 @@ pub struct GraphmanServer {
     store: Arc<Store>,
     graphman_store: Arc<GraphmanStore>,
     logger: Logger,
-    auth_token: AuthToken,
+    authenticator: Authenticator,
 }
 
 #[derive(Clone)]
@@ -42,7 +45,12 @@ pub struct GraphmanServerConfig<'a> {
     pub notification_sender: Arc<NotificationSender>,
     pub store: Arc<Store>,
     pub logger_factory: &'a LoggerFactory,
-    pub auth_token: String,
+
+    /// A token with full access to the API, named `default` in the execution records.
+    pub auth_token: Option<String>,
+
+    /// Additional named tokens with restricted permissions.
+    pub auth_tokens: Vec<GraphmanAuthToken>,
 }
 
 pub struct GraphmanServerManager {
@@ -57,10 +65,22 @@ impl GraphmanServer {
             store,
             logger_factory,
             auth_token,
+            mut auth_tokens,
         } = config;
 
         let graphman_store = Arc::new(GraphmanStore::new(pool.clone()));
-        let auth_token = AuthToken::new(auth_token)?;
+
+        if let Some(token) = auth_token {
+            auth_tokens.push(GraphmanAuthToken {
+                name: DEFAULT_TOKEN_NAME.to_owned(),
+                token,
+                access: GraphmanAccess::Write,
+                deployments: vec![],
+                namespaces: vec![],
+            });
+        }
+
+        let authenticator = Authenticator::new(auth_tokens)?;
 
         let logger = logger_factory.component_logger(
             "GraphmanServer",
@@ -77,7 +97,7 @@ impl GraphmanServer {
             store,
             graphman_store,
             logger,
-            auth_token,
+            authenticator,
         })
     }
 
@@ -88,7 +108,7 @@ impl GraphmanServer {
             store,
             graphman_store,
             logger,
-            auth_token,
+            authenticator,
         } = self;
 
         info!(
@@ -96,7 +116,7 @@ impl GraphmanServer {
             "Starting graphman server at: http://localhost:{}", port,
         );
 
-        let app_state = Arc::new(AppState { auth_token });
+        let app_state = Arc::new(AppState { authenticator });
 
         let cors_layer = CorsLayer::new()
             .allow_origin(Any)
@@ -1,6 +1,8 @@
 pub mod util;
 
+use graph::prelude::DeploymentHash;
 use serde_json::json;
+use test_store::create_test_subgraph;
 
 use self::util::client::send_graphql_request;
 use self::util::client::send_request;
@@ -8,8 +10,13 @@ use self::util::client::BASE_URL;
 use self::util::client::CLIENT;
 use self::util::run_test;
 use self::util::server::INVALID_TOKEN;
+use self::util::server::READ_ONLY_TOKEN;
+use self::util::server::SCOPED_DEPLOYMENT;
+use self::util::server::SCOPED_TOKEN;
 use self::util::server::VALID_TOKEN;
 
+const TEST_SUBGRAPH_SCHEMA: &str = "type User @entity { id: ID!, name: String }";
+
 #[test]
 fn graphql_playground_is_accessible() {
     run_test(|| async {
@@ -64,3 +71,336 @@ fn graphql_requests_are_allowed_with_a_valid_token() {
         assert_eq!(resp, expected_resp);
     });
 }
+
+#[test]
+fn graphql_mutations_are_not_allowed_with_a_read_only_token() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new("subgraph_1").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"{
+                    deployment {
+                        info {
+                            hash
+                        }
+                    }
+                }"#
+            }),
+            READ_ONLY_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "deployment": {
+                    "info": [
+                        {
+                            "hash": "subgraph_1"
+                        }
+                    ]
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        pause(deployment: { hash: "subgraph_1" }) {
+                            success
+                        }
+                    }
+                }"#
+            }),
+            READ_ONLY_TOKEN,
+        )
+        .await;
+
+        assert_eq!(resp["data"], json!(null));
+        assert_eq!(
+            resp["errors"][0]["message"],
+            "token 'reader' is not allowed to run mutations"
+        );
+    });
+}
+
+#[test]
+fn graphql_scoped_tokens_only_access_their_deployments() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new(SCOPED_DEPLOYMENT).unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let deployment_hash = DeploymentHash::new("subgraph_2").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"{
+                    deployment {
+                        info {
+                            hash
+                        }
+                    }
+                }"#
+            }),
+            SCOPED_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "deployment": {
+                    "info": [
+                        {
+                            "hash": SCOPED_DEPLOYMENT
+                        }
+                    ]
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"{
+                    deployment {
+                        list(first: 1) {
+                            hash
+                        }
+                    }
+                }"#
+            }),
+            SCOPED_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "deployment": {
+                    "list": [
+                        {
+                            "hash": SCOPED_DEPLOYMENT
+                        }
+                    ]
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        pause(deployment: { hash: "subgraph_2" }) {
+                            success
+                        }
+                    }
+                }"#
+            }),
+            SCOPED_TOKEN,
+        )
+        .await;
+
+        assert_eq!(resp["data"], json!(null));
+        assert_eq!(
+            resp["errors"][0]["message"],
+            "token 'scoped' is not allowed to access deployment 'subgraph_2'"
+        );
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        pause(deployment: { hash: "subgraph_1" }) {
+                            success
+                        }
+                    }
+                }"#
+            }),
+            SCOPED_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "deployment": {
+                    "pause": {
+                        "success": true
+                    }
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+    });
+}
+
+#[test]
+fn graphql_executions_record_the_token_name() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new(SCOPED_DEPLOYMENT).unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        restart(deployment: { hash: "subgraph_1" }, delaySeconds: 0)
+                    }
+                }"#
+            }),
+            SCOPED_TOKEN,
+        )
+        .await;
+
+        let execution_id = resp["data"]["deployment"]["restart"].clone();
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"query Execution($id: String!) {
+                    execution {
+                        info(id: $id) {
+                            createdBy
+                        }
+                    }
+                }"#,
+                "variables": {
+                    "id": execution_id
+                }
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "execution": {
+                    "info": {
+                        "createdBy": "scoped"
+                    }
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+    });
+}
+
+#[test]
+fn graphql_scoped_tokens_only_access_their_executions() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new(SCOPED_DEPLOYMENT).unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let restart = |token| {
+            send_graphql_request(
+                json!({
+                    "query": r#"mutation {
+                        deployment {
+                            restart(deployment: { hash: "subgraph_1" }, delaySeconds: 0)
+                        }
+                    }"#
+                }),
+                token,
+            )
+        };
+
+        let execution_info = |id: serde_json::Value| {
+            send_graphql_request(
+                json!({
+                    "query": r#"query Execution($id: String!) {
+                        execution {
+                            info(id: $id) {
+                                createdBy
+                            }
+                        }
+                    }"#,
+                    "variables": {
+                        "id": id
+                    }
+                }),
+                SCOPED_TOKEN,
+            )
+        };
+
+        let resp = restart(VALID_TOKEN).await;
+        let execution_id = resp["data"]["deployment"]["restart"].clone();
+
+        let resp = execution_info(execution_id.clone()).await;
+
+        assert_eq!(resp["data"], json!(null));
+        assert_eq!(
+            resp["errors"][0]["message"],
+            format!(
+                "token 'scoped' is not allowed to access execution '{}'",
+                execution_id.as_str().unwrap()
+            )
+        );
+
+        let resp = restart(SCOPED_TOKEN).await;
+        let execution_id = resp["data"]["deployment"]["restart"].clone();
+
+        let resp = execution_info(execution_id).await;
+
+        let expected_resp = json!({
+            "data": {
+                "execution": {
+                    "info": {
+                        "createdBy": "scoped"
+                    }
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+    });
+}
+
+#[test]
+fn graphql_scoped_tokens_only_copy_to_their_shard_and_node() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new(SCOPED_DEPLOYMENT).unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let copy = |shard: &str, node: &str| {
+            send_graphql_request(
+                json!({
+                    "query": r#"mutation Copy($shard: String!, $node: String!) {
+                        deployment {
+                            copy(deployment: { hash: "subgraph_1" }, shard: $shard, node: $node) {
+                                hash
+                            }
+                        }
+                    }"#,
+                    "variables": {
+                        "shard": shard,
+                        "node": node
+                    }
+                }),
+                SCOPED_TOKEN,
+            )
+        };
+
+        let resp = copy("shard_1", "test").await;
+
+        assert_eq!(resp["data"], json!(null));
+        assert_eq!(
+            resp["errors"][0]["message"],
+            "token 'scoped' is not allowed to copy deployment 'subgraph_1' into shard 'shard_1'"
+        );
+
+        let resp = copy("primary", "other_node").await;
+
+        assert_eq!(resp["data"], json!(null));
+        assert_eq!(
+            resp["errors"][0]["message"],
+            "token 'scoped' is not allowed to copy deployment 'subgraph_1' onto node 'other_node'"
+        );
+    });
+}
@@ -6,6 +6,10 @@ use graph::prelude::DeploymentHash;
 use serde::Deserialize;
 use serde_json::json;
 use test_store::create_test_subgraph;
+use test_store::transact_and_wait;
+use test_store::BLOCKS;
+use test_store::PRIMARY_POOL;
+use test_store::SUBGRAPH_STORE;
 use tokio::time::sleep;
 
 use self::util::client::send_graphql_request;
@@ -266,3 +270,594 @@ fn graphql_allows_tracking_restart_deployment_executions() {
         assert_eq!(resp, expected_resp);
     });
 }
+
+async fn assert_deployment_node(hash: &str, expected_node: serde_json::Value) {
+    let query = r#"query DeploymentNode($hash: String!) {
+        deployment {
+            info(deployment: { hash: $hash }) {
+                nodeId
+            }
+        }
+    }"#;
+
+    let resp = send_graphql_request(
+        json!({
+            "query": query,
+            "variables": {
+                "hash": hash
+            }
+        }),
+        VALID_TOKEN,
+    )
+    .await;
+
+    let expected_resp = json!({
+        "data": {
+            "deployment": {
+                "info": [
+                    {
+                        "nodeId": expected_node
+                    }
+                ]
+            }
+        }
+    });
+
+    assert_eq!(resp, expected_resp);
+}
+
+#[test]
+fn graphql_can_unassign_deployments() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new("subgraph_1").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        unassign(deployment: { hash: "subgraph_1" }) {
+                            success
+                        }
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "deployment": {
+                    "unassign": {
+                        "success": true,
+                    }
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+
+        assert_deployment_node("subgraph_1", json!(null)).await;
+    });
+}
+
+#[test]
+fn graphql_can_reassign_deployments() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new("subgraph_1").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        reassign(deployment: { hash: "subgraph_1" }, node: "new_node") {
+                            success
+                            warnings
+                        }
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "deployment": {
+                    "reassign": {
+                        "success": true,
+                        "warnings": [
+                            "this is the only deployment assigned to 'new_node'; \
+                             please make sure that the node ID is spelled correctly"
+                        ],
+                    }
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+
+        assert_deployment_node("subgraph_1", json!("new_node")).await;
+    });
+}
+
+#[test]
+fn graphql_can_remove_subgraphs() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new("subgraph_1").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    subgraph {
+                        remove(name: "subgraph_1") {
+                            success
+                        }
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "subgraph": {
+                    "remove": {
+                        "success": true,
+                    }
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"{
+                    deployment {
+                        info(deployment: { hash: "subgraph_1" }) {
+                            hash
+                        }
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "deployment": {
+                    "info": []
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+    });
+}
+
+/// Creates a test subgraph that has indexed up to block 3.
+async fn create_indexed_test_subgraph(hash: &str) {
+    let deployment_hash = DeploymentHash::new(hash).unwrap();
+    let locator = create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+    transact_and_wait(&SUBGRAPH_STORE, &locator, BLOCKS[3].clone(), vec![])
+        .await
+        .unwrap();
+}
+
+/// Returns the execution ID that a background mutation returned.
+fn execution_id(resp: &serde_json::Value, mutation: &str) -> String {
+    resp["data"]["deployment"][mutation]
+        .as_str()
+        .unwrap_or_else(|| panic!("expected {mutation} to return an execution ID: {resp}"))
+        .to_owned()
+}
+
+/// Waits until the execution with the specified ID has completed and returns its info.
+async fn wait_for_execution(id: &str) -> serde_json::Value {
+    let query = r#"query ExecutionInfo($id: String!) {
+        execution {
+            info(id: $id) {
+                kind
+                status
+                errorMessage
+            }
+        }
+    }"#;
+
+    for _ in 0..30 {
+        let resp = send_graphql_request(
+            json!({
+                "query": query,
+                "variables": {
+                    "id": id
+                }
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let info = &resp["data"]["execution"]["info"];
+
+        if info["status"] != "INITIALIZING" && info["status"] != "RUNNING" {
+            return info.clone();
+        }
+
+        sleep(Duration::from_secs(1)).await;
+    }
+
+    panic!("execution {id} did not complete");
+}
+
+async fn deployment_status(hash: &str) -> serde_json::Value {
+    let query = r#"query DeploymentStatus($hash: String!) {
+        deployment {
+            info(deployment: { hash: $hash }) {
+                status {
+                    isPaused
+                    earliestBlockNumber
+                    latestBlock {
+                        number
+                    }
+                }
+            }
+        }
+    }"#;
+
+    let resp = send_graphql_request(
+        json!({
+            "query": query,
+            "variables": {
+                "hash": hash
+            }
+        }),
+        VALID_TOKEN,
+    )
+    .await;
+
+    resp["data"]["deployment"]["info"][0]["status"].clone()
+}
+
+#[test]
+fn graphql_can_rewind_deployments() {
+    run_test(|| async {
+        create_indexed_test_subgraph("subgraph_1").await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation RewindDeployment($blockHash: BlockHash!) {
+                    deployment {
+                        rewind(
+                            deployment: { hash: "subgraph_1" },
+                            blockHash: $blockHash,
+                            blockNumber: "1",
+                            force: true,
+                            delaySeconds: 0
+                        )
+                    }
+                }"#,
+                "variables": {
+                    "blockHash": BLOCKS[1].hash_hex()
+                }
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let info = wait_for_execution(&execution_id(&resp, "rewind")).await;
+
+        let expected_info = json!({
+            "kind": "REWIND_DEPLOYMENT",
+            "status": "SUCCEEDED",
+            "errorMessage": null,
+        });
+
+        assert_eq!(info, expected_info);
+
+        let status = deployment_status("subgraph_1").await;
+
+        assert_eq!(status["isPaused"], json!(false));
+        assert_eq!(status["latestBlock"]["number"], json!("1"));
+    });
+}
+
+#[test]
+fn graphql_validates_rewind_arguments() {
+    run_test(|| async {
+        create_indexed_test_subgraph("subgraph_1").await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        rewind(deployment: { hash: "subgraph_1" }, blockNumber: "1")
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        assert_eq!(
+            resp["errors"][0]["message"],
+            "block hash and block number must be specified when start block is not set"
+        );
+
+        assert_deployment_paused("subgraph_1", false).await;
+    });
+}
+
+#[test]
+fn graphql_resumes_deployments_when_rewind_fails() {
+    run_test(|| async {
+        use diesel::prelude::*;
+
+        create_indexed_test_subgraph("subgraph_1").await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation RewindDeployment($blockHash: BlockHash!) {
+                    deployment {
+                        rewind(
+                            deployment: { hash: "subgraph_1" },
+                            blockHash: $blockHash,
+                            blockNumber: "1",
+                            force: true,
+                            delaySeconds: 2
+                        )
+                    }
+                }"#,
+                "variables": {
+                    "blockHash": BLOCKS[1].hash_hex()
+                }
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let id = execution_id(&resp, "rewind");
+
+        // Pruning history while the deployment is paused makes the rewind fail
+        let mut conn = PRIMARY_POOL.get().unwrap();
+
+        diesel::sql_query(
+            "update subgraphs.subgraph_deployment \
+                set earliest_block_number = 2 \
+              where deployment = 'subgraph_1'",
+        )
+        .execute(&mut conn)
+        .expect("update is successful");
+
+        let info = wait_for_execution(&id).await;
+
+        assert_eq!(info["kind"], "REWIND_DEPLOYMENT");
+        assert_eq!(info["status"], "FAILED");
+        assert!(info["errorMessage"].is_string());
+
+        let status = deployment_status("subgraph_1").await;
+
+        assert_eq!(status["isPaused"], json!(false));
+        assert_eq!(status["latestBlock"]["number"], json!("3"));
+    });
+}
+
+#[test]
+fn graphql_can_prune_deployments() {
+    run_test(|| async {
+        create_indexed_test_subgraph("subgraph_1").await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        prune(deployment: { hash: "subgraph_1" }, historyBlocks: "1", once: true)
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let info = wait_for_execution(&execution_id(&resp, "prune")).await;
+
+        let expected_info = json!({
+            "kind": "PRUNE_DEPLOYMENT",
+            "status": "SUCCEEDED",
+            "errorMessage": null,
+        });
+
+        assert_eq!(info, expected_info);
+
+        let status = deployment_status("subgraph_1").await;
+
+        assert_eq!(status["earliestBlockNumber"], json!("2"));
+    });
+}
+
+#[test]
+fn graphql_does_not_prune_more_blocks_than_indexed() {
+    run_test(|| async {
+        create_indexed_test_subgraph("subgraph_1").await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        prune(deployment: { hash: "subgraph_1" }, historyBlocks: "10")
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let message = resp["errors"][0]["message"].as_str().unwrap();
+
+        assert!(
+            message.ends_with(
+                "has only indexed up to block 3 and can not preserve 10 blocks of history"
+            ),
+            "unexpected error: {message}"
+        );
+    });
+}
+
+#[test]
+fn graphql_validates_copy_sources() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new("subgraph_1").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let query = r#"mutation CopyDeployment($hash: String!) {
+            deployment {
+                copy(deployment: { hash: $hash }, shard: "primary", node: "test", blockOffset: 200) {
+                    hash
+                }
+            }
+        }"#;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": query,
+                "variables": {
+                    "hash": "subgraph_1"
+                }
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let message = resp["errors"][0]["message"].as_str().unwrap();
+
+        assert!(
+            message.ends_with(
+                "has not indexed any blocks yet and can not be used as the source of a copy"
+            ),
+            "unexpected error: {message}"
+        );
+
+        create_indexed_test_subgraph("subgraph_2").await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": query,
+                "variables": {
+                    "hash": "subgraph_2"
+                }
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let message = resp["errors"][0]["message"].as_str().unwrap();
+
+        assert!(
+            message.contains("has only indexed up to block 3, but at least block 200 is needed"),
+            "unexpected error: {message}"
+        );
+    });
+}
+
+fn index_exists(index_name: &str) -> bool {
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Bool;
+
+    let mut conn = PRIMARY_POOL.get().unwrap();
+
+    diesel::select(sql::<Bool>(&format!(
+        "exists (select 1 from pg_indexes where indexname = '{index_name}')"
+    )))
+    .get_result(&mut conn)
+    .unwrap()
+}
+
+#[test]
+fn graphql_can_create_and_drop_indexes() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new("subgraph_1").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        createIndex(deployment: { hash: "subgraph_1" }, entity: "User", fields: ["name"])
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let info = wait_for_execution(&execution_id(&resp, "createIndex")).await;
+
+        let expected_info = json!({
+            "kind": "CREATE_INDEX",
+            "status": "SUCCEEDED",
+            "errorMessage": null,
+        });
+
+        assert_eq!(info, expected_info);
+        assert!(index_exists("manual_user_name"));
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        dropIndex(deployment: { hash: "subgraph_1" }, indexName: "manual_user_name") {
+                            success
+                        }
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "deployment": {
+                    "dropIndex": {
+                        "success": true,
+                    }
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+        assert!(!index_exists("manual_user_name"));
+    });
+}
+
+#[test]
+fn graphql_reports_failed_index_creation() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new("subgraph_1").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"mutation {
+                    deployment {
+                        createIndex(deployment: { hash: "subgraph_1" }, entity: "User", fields: ["name", "name"])
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let info = wait_for_execution(&execution_id(&resp, "createIndex")).await;
+
+        assert_eq!(info["kind"], "CREATE_INDEX");
+        assert_eq!(info["status"], "FAILED");
+        assert!(info["errorMessage"].is_string());
+    });
+}
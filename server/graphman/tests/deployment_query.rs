@@ -249,3 +249,153 @@ fn graphql_returns_empty_deployment_info_when_there_are_no_deployments() {
         assert_eq!(resp, expected_resp);
     });
 }
+
+#[test]
+fn graphql_lists_deployments_page_by_page() {
+    run_test(|| async {
+        for hash in ["subgraph_1", "subgraph_2", "subgraph_3"] {
+            let deployment_hash = DeploymentHash::new(hash).unwrap();
+            create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+        }
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"{
+                    deployment {
+                        list(first: 1, skip: 1) {
+                            hash
+                        }
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "deployment": {
+                    "list": [
+                        {
+                            "hash": "subgraph_2"
+                        }
+                    ]
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+
+        // Filtering by health needs the indexing status of the deployments
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"{
+                    deployment {
+                        list(filter: { health: [HEALTHY] }, first: 1, skip: 1) {
+                            hash
+                        }
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        assert_eq!(resp, expected_resp);
+    });
+}
+
+#[test]
+fn graphql_does_not_list_too_many_deployments() {
+    run_test(|| async {
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"{
+                    deployment {
+                        list(first: 1001) {
+                            hash
+                        }
+                    }
+                }"#
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        assert_eq!(resp["data"], json!(null));
+        assert_eq!(
+            resp["errors"][0]["message"],
+            "first can not be larger than 1000"
+        );
+    });
+}
+
+#[test]
+fn graphql_lists_deployments_matching_filter() {
+    run_test(|| async {
+        let deployment_hash = DeploymentHash::new("subgraph_1").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let deployment_hash = DeploymentHash::new("subgraph_2").unwrap();
+        create_test_subgraph(&deployment_hash, TEST_SUBGRAPH_SCHEMA).await;
+
+        let resp = send_graphql_request(
+            json!({
+                "query": r#"query ListDeployments($chain: String!) {
+                    healthy: deployment {
+                        list(filter: { health: [HEALTHY], chain: $chain, isPaused: false }) {
+                            hash
+                            status {
+                                health
+                            }
+                        }
+                    }
+                    failed: deployment {
+                        list(filter: { health: [FAILED] }) {
+                            hash
+                        }
+                    }
+                    otherChain: deployment {
+                        list(filter: { chain: "not_a_chain" }) {
+                            hash
+                        }
+                    }
+                }"#,
+                "variables": {
+                    "chain": NETWORK_NAME
+                }
+            }),
+            VALID_TOKEN,
+        )
+        .await;
+
+        let expected_resp = json!({
+            "data": {
+                "healthy": {
+                    "list": [
+                        {
+                            "hash": "subgraph_1",
+                            "status": {
+                                "health": "HEALTHY"
+                            }
+                        },
+                        {
+                            "hash": "subgraph_2",
+                            "status": {
+                                "health": "HEALTHY"
+                            }
+                        }
+                    ]
+                },
+                "failed": {
+                    "list": []
+                },
+                "otherChain": {
+                    "list": []
+                }
+            }
+        });
+
+        assert_eq!(resp, expected_resp);
+    });
+}
@@ -2,6 +2,8 @@ use std::sync::Arc;
 
 use graph::prelude::LoggerFactory;
 use graph_store_postgres::NotificationSender;
+use graphman_server::GraphmanAccess;
+use graphman_server::GraphmanAuthToken;
 use graphman_server::GraphmanServer;
 use graphman_server::GraphmanServerConfig;
 use lazy_static::lazy_static;
@@ -14,6 +16,13 @@ use tokio::sync::OnceCell;
 pub const VALID_TOKEN: &str = "123";
 pub const INVALID_TOKEN: &str = "abc";
 
+/// A token that only allows queries.
+pub const READ_ONLY_TOKEN: &str = "456";
+
+/// A token that only allows access to the deployment with this hash.
+pub const SCOPED_TOKEN: &str = "789";
+pub const SCOPED_DEPLOYMENT: &str = "subgraph_1";
+
 pub const PORT: u16 = 8050;
 
 lazy_static! {
@@ -31,7 +40,23 @@ pub async fn start() {
                 notification_sender,
                 store: STORE.clone(),
                 logger_factory: &logger_factory,
-                auth_token: VALID_TOKEN.to_string(),
+                auth_token: Some(VALID_TOKEN.to_string()),
+                auth_tokens: vec![
+                    GraphmanAuthToken {
+                        name: "reader".to_string(),
+                        token: READ_ONLY_TOKEN.to_string(),
+                        access: GraphmanAccess::Read,
+                        deployments: vec![],
+                        namespaces: vec![],
+                    },
+                    GraphmanAuthToken {
+                        name: "scoped".to_string(),
+                        token: SCOPED_TOKEN.to_string(),
+                        access: GraphmanAccess::Write,
+                        deployments: vec![SCOPED_DEPLOYMENT.to_string()],
+                        namespaces: vec![],
+                    },
+                ],
             };
 
             let server = GraphmanServer::new(config).expect("graphman config is valid");
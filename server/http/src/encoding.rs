@@ -0,0 +1,282 @@
+//! Compression of query results and cache validators for them. Results are
+//! compressed with the best encoding the client accepts, and get a strong
+//! `ETag` that only changes when the query, the deployment or the blocks at
+//! which the query was run change, so that clients can revalidate their
+//! copy with `If-None-Match`.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use graph::components::server::query::ServerResponse;
+use graph::data::query::{Query, QueryResults};
+use graph::http_body_util::{BodyExt, Full};
+use graph::hyper::header::{
+    HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, ETAG, IF_NONE_MATCH, VARY,
+};
+use graph::hyper::{HeaderMap, StatusCode};
+use graph::prelude::{hex, serde_json};
+use sha2::{Digest, Sha256};
+
+/// Responses smaller than this are not worth compressing
+const MIN_COMPRESSED_SIZE: usize = 1024;
+
+/// Brotli quality level; higher levels are too slow for dynamic responses
+const BROTLI_QUALITY: u32 = 5;
+const BROTLI_WINDOW_BITS: u32 = 22;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Identity,
+    Gzip,
+    Brotli,
+}
+
+impl Encoding {
+    /// Pick the encoding for the response from the client's
+    /// `Accept-Encoding` header. Of the encodings with the highest weight,
+    /// we prefer Brotli over gzip since it compresses JSON better.
+    pub fn negotiate(headers: &HeaderMap) -> Self {
+        let mut best = (Encoding::Identity, 0.0);
+
+        for item in headers
+            .get_all(ACCEPT_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+        {
+            let mut parts = item.split(';');
+            let encoding = match parts.next().map(str::trim) {
+                Some("br") | Some("*") => Encoding::Brotli,
+                Some("gzip") | Some("x-gzip") => Encoding::Gzip,
+                _ => continue,
+            };
+            let weight = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if weight > 0.0
+                && (weight > best.1 || (weight == best.1 && encoding == Encoding::Brotli))
+            {
+                best = (encoding, weight);
+            }
+        }
+
+        best.0
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        match self {
+            Encoding::Identity => None,
+            Encoding::Gzip => Some("gzip"),
+            Encoding::Brotli => Some("br"),
+        }
+    }
+
+    fn encode(&self, body: &[u8]) -> Vec<u8> {
+        // Writing to a `Vec` can not fail
+        match self {
+            Encoding::Identity => body.to_vec(),
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+                encoder.write_all(body).expect("gzip into memory succeeds");
+                encoder.finish().expect("gzip into memory succeeds")
+            }
+            Encoding::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(
+                    Vec::new(),
+                    4096,
+                    BROTLI_QUALITY,
+                    BROTLI_WINDOW_BITS,
+                );
+                encoder
+                    .write_all(body)
+                    .expect("brotli into memory succeeds");
+                encoder.into_inner()
+            }
+        }
+    }
+}
+
+/// A hash of the query text and its variables that does not depend on how
+/// the client formatted the request
+pub fn query_hash(query: &Query) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(query.document.to_string().as_bytes());
+    // Sort the variables since their order is random
+    let variables = query
+        .variables
+        .as_ref()
+        .map(|variables| variables.iter().collect::<BTreeMap<_, _>>());
+    hasher.update(serde_json::to_vec(&variables).unwrap_or_default());
+    hasher.finalize().into()
+}
+
+/// A strong validator for `results`. It is only possible to compute one for
+/// results without errors that were computed at known blocks; errors can be
+/// transient, e.g., timeouts, and clients need to be able to retry them.
+pub fn etag(query_hash: &[u8; 32], results: &QueryResults) -> Option<String> {
+    let deployment = results.deployment_hash()?;
+    if results.has_errors() || results.blocks().is_empty() {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(query_hash);
+    hasher.update(deployment.as_str().as_bytes());
+    for block in results.blocks() {
+        hasher.update(block.number.to_be_bytes());
+        hasher.update(block.hash.as_slice());
+    }
+    Some(hex::encode(hasher.finalize()))
+}
+
+/// Whether the `If-None-Match` header of a request matches `etag`. The
+/// comparison is weak as RFC 9110 requires for `If-None-Match`.
+fn matches_etag(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Finish the `response` for a query: answer with `304 Not Modified` if
+/// the client already has the result, otherwise add the `ETag` and
+/// compress the body if the client accepts that.
+pub async fn encode_response(
+    response: ServerResponse,
+    request_headers: &HeaderMap,
+    etag: Option<String>,
+) -> ServerResponse {
+    let encoding = Encoding::negotiate(request_headers);
+    let (mut parts, body) = response.into_parts();
+
+    parts
+        .headers
+        .insert(VARY, HeaderValue::from_static("Accept-Encoding"));
+
+    // Different encodings of the same result need different strong
+    // validators
+    let etag = etag.map(|etag| match encoding.name() {
+        Some(name) => format!("\"{}-{}\"", etag, name),
+        None => format!("\"{}\"", etag),
+    });
+    if let Some(etag) = etag {
+        let not_modified = matches_etag(request_headers, &etag);
+        parts
+            .headers
+            .insert(ETAG, HeaderValue::from_str(&etag).expect("ETag is valid"));
+        if not_modified && parts.status == StatusCode::OK {
+            parts.status = StatusCode::NOT_MODIFIED;
+            parts.headers.remove(CONTENT_LENGTH);
+            return ServerResponse::from_parts(parts, Full::default());
+        }
+    }
+
+    // Collecting a `Full` body can not fail
+    let body = body.collect().await.unwrap().to_bytes();
+    let body = match encoding.name() {
+        Some(name) if body.len() >= MIN_COMPRESSED_SIZE => {
+            parts
+                .headers
+                .insert(CONTENT_ENCODING, HeaderValue::from_static(name));
+            parts.headers.remove(CONTENT_LENGTH);
+            encoding.encode(&body).into()
+        }
+        _ => body,
+    };
+
+    ServerResponse::from_parts(parts, Full::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+    use graph::components::server::query::ServerResponse;
+    use graph::http_body_util::{BodyExt, Full};
+    use graph::hyper::header::{
+        HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, ETAG, IF_NONE_MATCH,
+    };
+    use graph::hyper::HeaderMap;
+    use graph::prelude::tokio;
+
+    use super::{encode_response, Encoding};
+
+    fn headers(pairs: &[(graph::hyper::header::HeaderName, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(name, HeaderValue::from_static(*value));
+        }
+        headers
+    }
+
+    #[test]
+    fn negotiates_encoding() {
+        let negotiate =
+            |value: &'static str| Encoding::negotiate(&headers(&[(ACCEPT_ENCODING, value)]));
+
+        assert_eq!(Encoding::Identity, Encoding::negotiate(&HeaderMap::new()));
+        assert_eq!(Encoding::Identity, negotiate("deflate"));
+        assert_eq!(Encoding::Gzip, negotiate("gzip"));
+        assert_eq!(Encoding::Brotli, negotiate("gzip, deflate, br"));
+        assert_eq!(Encoding::Gzip, negotiate("br;q=0.5, gzip"));
+        assert_eq!(Encoding::Gzip, negotiate("br;q=0, gzip;q=0.1"));
+        assert_eq!(Encoding::Identity, negotiate("gzip;q=0"));
+    }
+
+    #[tokio::test]
+    async fn compresses_large_responses() {
+        let json = format!("[{}]", vec!["\"thing\""; 1000].join(","));
+        let response = ServerResponse::new(Full::from(json.clone()));
+
+        let response =
+            encode_response(response, &headers(&[(ACCEPT_ENCODING, "gzip")]), None).await;
+        assert_eq!("gzip", response.headers()[CONTENT_ENCODING]);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let mut decoded = String::new();
+        GzDecoder::new(&body[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(json, decoded);
+    }
+
+    #[tokio::test]
+    async fn answers_matching_etags_with_not_modified() {
+        let response = || ServerResponse::new(Full::from("{\"data\":{}}"));
+
+        let first = encode_response(response(), &HeaderMap::new(), Some("abc".to_string())).await;
+        assert_eq!(200, first.status());
+        assert_eq!("\"abc\"", first.headers()[ETAG]);
+
+        let second = encode_response(
+            response(),
+            &headers(&[(IF_NONE_MATCH, "\"abc\"")]),
+            Some("abc".to_string()),
+        )
+        .await;
+        assert_eq!(304, second.status());
+        assert!(second
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes()
+            .is_empty());
+
+        let changed = encode_response(
+            response(),
+            &headers(&[(IF_NONE_MATCH, "\"abc\"")]),
+            Some("def".to_string()),
+        )
+        .await;
+        assert_eq!(200, changed.status());
+    }
+}
@@ -2,10 +2,16 @@ extern crate graph;
 extern crate graph_graphql;
 extern crate serde;
 
+mod encoding;
+mod limits;
+mod persisted;
 mod request;
 mod server;
 mod service;
+mod sse;
 
+pub use self::limits::{ClientLimit, ComplexityBudget, QueryClient, QueryLimits};
+pub use self::persisted::PersistedQueries;
 pub use self::server::GraphQLServer;
 pub use self::service::GraphQLService;
 
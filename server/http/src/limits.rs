@@ -0,0 +1,596 @@
+//! Per-client rate limits for queries. Clients that send a known API key as
+//! a bearer token in the `Authorization` header get the limits configured
+//! for that key; all other clients are limited by their IP address. Limits
+//! are token buckets that allow `burst` queries at once and refill at
+//! `requests_per_second`. A client can also have a complexity budget, a
+//! token bucket that the complexity of each of its queries is taken from
+//! and that refills over time.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use graph::components::metrics::MetricsRegistry;
+use graph::env::ENV_VARS;
+use graph::hyper::header::AUTHORIZATION;
+use graph::hyper::HeaderMap;
+use graph::prometheus::IntCounterVec;
+use lru_time_cache::LruCache;
+
+/// The label under which metrics for clients without an API key are
+/// reported; we do not want a label per IP address
+const ANONYMOUS: &str = "anonymous";
+
+/// The number of IP addresses we track. When there are more, we forget
+/// about the address that sent a query the longest time ago, which gives
+/// it a fresh budget if it comes back
+const MAX_TRACKED_ADDRS: usize = 10_000;
+
+/// How many queries a client can run
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientLimit {
+    pub requests_per_second: f64,
+    /// The number of queries that can be run at once after the client has
+    /// been idle for a while
+    pub burst: u32,
+    /// The maximum complexity of each query of the client. This overrides
+    /// `GRAPH_GRAPHQL_MAX_COMPLEXITY`
+    pub max_complexity: Option<u64>,
+    /// The total complexity of the queries that the client can run over
+    /// time
+    pub complexity_budget: Option<ComplexityBudget>,
+}
+
+/// A budget for the complexity of a client's queries. Each query uses up
+/// its complexity from the budget, and the budget refills at `per_second`
+/// up to `burst`. Queries are rejected while the budget is used up, and
+/// each query can use at most what is left of the budget, but never more
+/// than the query could use without a budget
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComplexityBudget {
+    pub per_second: f64,
+    pub burst: u64,
+}
+
+/// A client that identifies itself with an API key
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryClient {
+    /// The name under which metrics for the client are reported
+    pub name: String,
+    pub api_key: String,
+    pub limit: ClientLimit,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct QueryLimits {
+    pub clients: Vec<QueryClient>,
+    /// The limit for each IP address that does not send a known API key.
+    /// If this is not set, such clients are not limited
+    pub anonymous: Option<ClientLimit>,
+    /// The number of proxies in front of the server that append the address
+    /// they received a request from to the `X-Forwarded-For` header. The
+    /// address of the client is the entry that the outermost of these
+    /// proxies added; entries further to the left can be set by the client
+    /// and are ignored. With 0, the header is ignored
+    pub trusted_proxies: usize,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, now: Instant) -> Self {
+        TokenBucket {
+            tokens: capacity,
+            last: now,
+        }
+    }
+
+    fn refill(&mut self, rate: f64, capacity: f64, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(capacity);
+        self.last = now;
+    }
+
+    /// Return the number of tokens in the bucket if there is at least one,
+    /// or how long the client has to wait until a token is available
+    fn available(&mut self, rate: f64, capacity: f64, now: Instant) -> Result<f64, Duration> {
+        self.refill(rate, capacity, now);
+        if self.tokens >= 1.0 {
+            Ok(self.tokens)
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+
+    /// Take a token from the bucket, or return how long the client has to
+    /// wait until a token is available
+    fn take(&mut self, rate: f64, capacity: f64, now: Instant) -> Result<(), Duration> {
+        self.available(rate, capacity, now)?;
+        self.tokens -= 1.0;
+        Ok(())
+    }
+
+    /// Take `amount` tokens from the bucket. The bucket can go into debt
+    /// when several queries of a client run at the same time; the client
+    /// then has to wait until the debt is paid off
+    fn charge(&mut self, amount: f64, rate: f64, capacity: f64, now: Instant) {
+        self.refill(rate, capacity, now);
+        self.tokens -= amount;
+    }
+}
+
+/// The buckets for one client
+struct ClientState {
+    requests: TokenBucket,
+    complexity: TokenBucket,
+}
+
+impl ClientState {
+    fn new(limit: &ClientLimit, now: Instant) -> Self {
+        let complexity = limit
+            .complexity_budget
+            .as_ref()
+            .map_or(0.0, |budget| budget.burst as f64);
+        ClientState {
+            requests: TokenBucket::new(limit.burst as f64, now),
+            complexity: TokenBucket::new(complexity, now),
+        }
+    }
+
+    /// Check whether the client may run another query and, if so, take a
+    /// token for it and determine the complexity it may have.
+    /// `default_max_complexity` is the limit for queries of clients that do
+    /// not set `max_complexity`
+    fn admit(
+        &mut self,
+        limit: &ClientLimit,
+        default_max_complexity: Option<u64>,
+        now: Instant,
+    ) -> Admission {
+        let budget = match &limit.complexity_budget {
+            Some(budget) => {
+                match self
+                    .complexity
+                    .available(budget.per_second, budget.burst as f64, now)
+                {
+                    Ok(budget) => Some(budget as u64),
+                    Err(retry_after) => return Admission::Limited { retry_after },
+                }
+            }
+            None => None,
+        };
+        if let Err(retry_after) =
+            self.requests
+                .take(limit.requests_per_second, limit.burst as f64, now)
+        {
+            return Admission::Limited { retry_after };
+        }
+        let max_complexity = match budget {
+            Some(budget) => Some(
+                limit
+                    .max_complexity
+                    .or(default_max_complexity)
+                    .map_or(budget, |max| max.min(budget)),
+            ),
+            None => limit.max_complexity,
+        };
+        Admission::Allowed { max_complexity }
+    }
+
+    fn charge(&mut self, limit: &ClientLimit, complexity: u64, now: Instant) {
+        if let Some(budget) = &limit.complexity_budget {
+            self.complexity.charge(
+                complexity as f64,
+                budget.per_second,
+                budget.burst as f64,
+                now,
+            );
+        }
+    }
+}
+
+/// The outcome of checking a request against the limits
+#[derive(Debug, PartialEq)]
+pub enum Admission {
+    /// Run the query with at most this complexity
+    Allowed { max_complexity: Option<u64> },
+    /// Reject the query; the client can try again after this long
+    Limited { retry_after: Duration },
+}
+
+struct KeyedClient {
+    client: QueryClient,
+    state: Mutex<ClientState>,
+}
+
+pub struct RateLimiter {
+    keyed: HashMap<String, KeyedClient>,
+    anonymous: Option<ClientLimit>,
+    addrs: Mutex<LruCache<IpAddr, ClientState>>,
+    trusted_proxies: usize,
+    /// The value of `GRAPH_GRAPHQL_MAX_COMPLEXITY`
+    default_max_complexity: Option<u64>,
+    requests: Box<IntCounterVec>,
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Don't print the API keys
+        write!(f, "RateLimiter {{ clients: {} }}", self.keyed.len())
+    }
+}
+
+impl RateLimiter {
+    pub fn new(limits: QueryLimits, registry: Arc<MetricsRegistry>) -> Self {
+        let now = Instant::now();
+        let keyed = limits
+            .clients
+            .into_iter()
+            .map(|client| {
+                let state = Mutex::new(ClientState::new(&client.limit, now));
+                (client.api_key.clone(), KeyedClient { client, state })
+            })
+            .collect();
+        let requests = registry
+            .new_int_counter_vec(
+                "query_client_requests",
+                "Number of queries per client, by whether they were allowed or rate limited",
+                &["client", "status"],
+            )
+            .expect("failed to create `query_client_requests` counter");
+
+        RateLimiter {
+            keyed,
+            anonymous: limits.anonymous,
+            addrs: Mutex::new(LruCache::with_capacity(MAX_TRACKED_ADDRS)),
+            trusted_proxies: limits.trusted_proxies,
+            default_max_complexity: ENV_VARS.graphql.max_complexity,
+            requests,
+        }
+    }
+
+    fn api_key(headers: &HeaderMap) -> Option<&str> {
+        headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+    }
+
+    /// The address of the client. Each trusted proxy appends the address
+    /// it received the request from to `X-Forwarded-For`, so that the
+    /// client's address is the `trusted_proxies`-th entry from the right
+    fn client_addr(&self, headers: &HeaderMap, remote_addr: Option<SocketAddr>) -> Option<IpAddr> {
+        if self.trusted_proxies > 0 {
+            let forwarded: Vec<_> = headers
+                .get_all("X-Forwarded-For")
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .map(str::trim)
+                .collect();
+            let addr = forwarded
+                .len()
+                .checked_sub(self.trusted_proxies)
+                .and_then(|idx| forwarded[idx].parse().ok());
+            if addr.is_some() {
+                return addr;
+            }
+        }
+        remote_addr.map(|addr| addr.ip())
+    }
+
+    fn observe(&self, client: &str, admission: &Admission) {
+        let status = match admission {
+            Admission::Allowed { .. } => "allowed",
+            Admission::Limited { .. } => "limited",
+        };
+        self.requests.with_label_values(&[client, status]).inc();
+    }
+
+    /// Run `f` with the state and limit of the client that sent a request
+    /// with `headers` from `remote_addr`, and the name under which metrics
+    /// for it are reported. Returns `None` if the client is not limited
+    fn with_client<T>(
+        &self,
+        headers: &HeaderMap,
+        remote_addr: Option<SocketAddr>,
+        f: impl FnOnce(&mut ClientState, &ClientLimit, &str) -> T,
+    ) -> Option<T> {
+        if let Some(keyed) = Self::api_key(headers).and_then(|key| self.keyed.get(key)) {
+            let mut state = keyed.state.lock().unwrap();
+            return Some(f(&mut state, &keyed.client.limit, &keyed.client.name));
+        }
+
+        let (limit, addr) = match (&self.anonymous, self.client_addr(headers, remote_addr)) {
+            (Some(limit), Some(addr)) => (limit, addr),
+            _ => return None,
+        };
+        let mut addrs = self.addrs.lock().unwrap();
+        if !addrs.contains_key(&addr) {
+            addrs.insert(addr, ClientState::new(limit, Instant::now()));
+        }
+        let state = addrs
+            .get_mut(&addr)
+            .expect("the state for the address was just inserted");
+        Some(f(state, limit, ANONYMOUS))
+    }
+
+    /// Check whether the client that sent a request with `headers` from
+    /// `remote_addr` may run another query
+    pub fn admit(&self, headers: &HeaderMap, remote_addr: Option<SocketAddr>) -> Admission {
+        let now = Instant::now();
+        let admission = self.with_client(headers, remote_addr, |state, limit, name| {
+            let admission = state.admit(limit, self.default_max_complexity, now);
+            self.observe(name, &admission);
+            admission
+        });
+        admission.unwrap_or(Admission::Allowed {
+            max_complexity: None,
+        })
+    }
+
+    /// Take the `complexity` of a query that the client that sent a
+    /// request with `headers` from `remote_addr` ran from its budget
+    pub fn charge(&self, headers: &HeaderMap, remote_addr: Option<SocketAddr>, complexity: u64) {
+        let now = Instant::now();
+        self.with_client(headers, remote_addr, |state, limit, _| {
+            state.charge(limit, complexity, now)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use graph::hyper::header::HeaderValue;
+
+    use super::*;
+
+    fn limit(requests_per_second: f64, burst: u32) -> ClientLimit {
+        ClientLimit {
+            requests_per_second,
+            burst,
+            max_complexity: Some(100),
+            complexity_budget: None,
+        }
+    }
+
+    fn limiter(limits: QueryLimits) -> RateLimiter {
+        RateLimiter::new(limits, Arc::new(MetricsRegistry::mock()))
+    }
+
+    #[test]
+    fn token_bucket_refills() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, start);
+
+        assert_eq!(Ok(()), bucket.take(2.0, 2.0, start));
+        assert_eq!(Ok(()), bucket.take(2.0, 2.0, start));
+        assert_eq!(
+            Err(Duration::from_millis(500)),
+            bucket.take(2.0, 2.0, start)
+        );
+        assert_eq!(
+            Ok(()),
+            bucket.take(2.0, 2.0, start + Duration::from_millis(500))
+        );
+        assert_eq!(
+            Ok(2.0),
+            bucket.available(2.0, 2.0, start + Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn limits_clients_separately() {
+        let limits = QueryLimits {
+            clients: vec![QueryClient {
+                name: "dashboard".to_string(),
+                api_key: "secret".to_string(),
+                limit: limit(1.0, 1),
+            }],
+            anonymous: Some(limit(1.0, 2)),
+            trusted_proxies: 0,
+        };
+        let limiter = limiter(limits);
+
+        let mut with_key = HeaderMap::new();
+        with_key.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        let addr: SocketAddr = "10.0.0.1:4000".parse().unwrap();
+        let other: SocketAddr = "10.0.0.2:4000".parse().unwrap();
+
+        let allowed = Admission::Allowed {
+            max_complexity: Some(100),
+        };
+        assert_eq!(allowed, limiter.admit(&with_key, Some(addr)));
+        assert!(matches!(
+            limiter.admit(&with_key, Some(addr)),
+            Admission::Limited { .. }
+        ));
+
+        // Clients without a key have their own budget per address
+        assert_eq!(allowed, limiter.admit(&HeaderMap::new(), Some(addr)));
+        assert_eq!(allowed, limiter.admit(&HeaderMap::new(), Some(addr)));
+        assert!(matches!(
+            limiter.admit(&HeaderMap::new(), Some(addr)),
+            Admission::Limited { .. }
+        ));
+        assert_eq!(allowed, limiter.admit(&HeaderMap::new(), Some(other)));
+    }
+
+    #[test]
+    fn uses_address_added_by_trusted_proxy() {
+        let limits = |trusted_proxies| QueryLimits {
+            clients: vec![],
+            anonymous: Some(limit(1.0, 1)),
+            trusted_proxies,
+        };
+        let proxy: SocketAddr = "10.0.0.1:4000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Forwarded-For",
+            HeaderValue::from_static("1.1.1.1, 2.2.2.2, 3.3.3.3"),
+        );
+        let addr = |limiter: &RateLimiter, headers: &HeaderMap| {
+            limiter.client_addr(headers, Some(proxy)).unwrap()
+        };
+
+        let limiter = limiter(limits(0));
+        assert_eq!(proxy.ip(), addr(&limiter, &headers));
+
+        let limiter = self::limiter(limits(1));
+        assert_eq!(
+            "3.3.3.3".parse::<IpAddr>().unwrap(),
+            addr(&limiter, &headers)
+        );
+
+        let limiter = self::limiter(limits(2));
+        assert_eq!(
+            "2.2.2.2".parse::<IpAddr>().unwrap(),
+            addr(&limiter, &headers)
+        );
+
+        // The request did not pass through all trusted proxies
+        let limiter = self::limiter(limits(4));
+        assert_eq!(proxy.ip(), addr(&limiter, &headers));
+
+        // A client can not avoid the limit by sending its own header
+        let limiter = self::limiter(limits(1));
+        let mut spoofed = HeaderMap::new();
+        spoofed.insert(
+            "X-Forwarded-For",
+            HeaderValue::from_static("4.4.4.4, 5.5.5.5"),
+        );
+        assert!(matches!(
+            limiter.admit(&spoofed, Some(proxy)),
+            Admission::Allowed { .. }
+        ));
+        spoofed.insert(
+            "X-Forwarded-For",
+            HeaderValue::from_static("6.6.6.6, 5.5.5.5"),
+        );
+        assert!(matches!(
+            limiter.admit(&spoofed, Some(proxy)),
+            Admission::Limited { .. }
+        ));
+    }
+
+    #[test]
+    fn forgets_least_recently_seen_addresses() {
+        let limiter = limiter(QueryLimits {
+            clients: vec![],
+            anonymous: Some(limit(1.0, 1)),
+            trusted_proxies: 0,
+        });
+        let first: SocketAddr = "10.0.0.1:4000".parse().unwrap();
+        assert!(matches!(
+            limiter.admit(&HeaderMap::new(), Some(first)),
+            Admission::Allowed { .. }
+        ));
+        for i in 0..MAX_TRACKED_ADDRS as u32 {
+            let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::from((11u32 << 24) + i)), 4000);
+            limiter.admit(&HeaderMap::new(), Some(addr));
+        }
+        assert_eq!(MAX_TRACKED_ADDRS, limiter.addrs.lock().unwrap().len());
+        assert!(!limiter.addrs.lock().unwrap().contains_key(&first.ip()));
+    }
+
+    #[test]
+    fn complexity_budget_drains_and_refills() {
+        let limit = ClientLimit {
+            requests_per_second: 100.0,
+            burst: 100,
+            max_complexity: Some(100),
+            complexity_budget: Some(ComplexityBudget {
+                per_second: 10.0,
+                burst: 250,
+            }),
+        };
+        let start = Instant::now();
+        let mut state = ClientState::new(&limit, start);
+
+        let allowed = |max_complexity| Admission::Allowed {
+            max_complexity: Some(max_complexity),
+        };
+        assert_eq!(allowed(100), state.admit(&limit, None, start));
+        state.charge(&limit, 100, start);
+        assert_eq!(allowed(100), state.admit(&limit, None, start));
+        state.charge(&limit, 100, start);
+        // The per-query limit is capped by what is left of the budget
+        assert_eq!(allowed(50), state.admit(&limit, None, start));
+        state.charge(&limit, 60, start);
+        // The budget is in debt by 10 and needs 1.1s to allow a query
+        assert_eq!(
+            Admission::Limited {
+                retry_after: Duration::from_millis(1100)
+            },
+            state.admit(&limit, None, start)
+        );
+        assert_eq!(
+            allowed(10),
+            state.admit(&limit, None, start + Duration::from_secs(2))
+        );
+        assert_eq!(
+            allowed(100),
+            state.admit(&limit, None, start + Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn complexity_budget_does_not_raise_max_complexity() {
+        let mut limiter = limiter(QueryLimits {
+            clients: vec![QueryClient {
+                name: "dashboard".to_string(),
+                api_key: "secret".to_string(),
+                limit: ClientLimit {
+                    requests_per_second: 100.0,
+                    burst: 100,
+                    max_complexity: None,
+                    complexity_budget: Some(ComplexityBudget {
+                        per_second: 1.0,
+                        burst: 20_000_000,
+                    }),
+                },
+            }],
+            anonymous: None,
+            trusted_proxies: 0,
+        });
+        limiter.default_max_complexity = Some(1_000_000);
+
+        let mut with_key = HeaderMap::new();
+        with_key.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+
+        // A query that is more complex than `GRAPH_GRAPHQL_MAX_COMPLEXITY`
+        // allows is still rejected even though the budget would allow it
+        assert_eq!(
+            Admission::Allowed {
+                max_complexity: Some(1_000_000)
+            },
+            limiter.admit(&with_key, None)
+        );
+
+        // Once the budget is lower than the global limit, it is the limit
+        limiter.charge(&with_key, None, 19_500_000);
+        assert!(matches!(
+            limiter.admit(&with_key, None),
+            Admission::Allowed {
+                max_complexity: Some(max)
+            } if max < 1_000_000
+        ));
+
+        // Without a global limit, the budget is the limit
+        limiter.default_max_complexity = None;
+        assert!(matches!(
+            limiter.admit(&with_key, None),
+            Admission::Allowed {
+                max_complexity: Some(max)
+            } if max >= 500_000
+        ));
+    }
+}
@@ -0,0 +1,339 @@
+//! Automatic persisted queries. Clients send the SHA-256 hash of a query in
+//! `extensions.persistedQuery.sha256Hash` instead of the query text. If we
+//! don't know the hash, the client is told so with a `PersistedQueryNotFound`
+//! error and sends the hash again together with the full query, which we
+//! then remember for later requests.
+//!
+//! When an allowlist is configured, only the queries in it can be run and
+//! clients can not register any new queries.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use graph::components::server::persisted::{PersistedQueryError, QueryAllowlist};
+use graph::components::server::query::ServerResponse;
+use graph::env::ENV_VARS;
+use graph::http_body_util::Full;
+use graph::hyper::header::{ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE};
+use graph::hyper::Response;
+use graph::prelude::serde_json::{self, json, Value};
+use graph::prelude::{anyhow, hex, info, Error, Logger};
+use graph::util::lfu_cache::LfuCache;
+use sha2::{Digest, Sha256};
+
+type JsonObject = serde_json::Map<String, Value>;
+
+/// The hex-encoded SHA-256 hash of `query`
+fn sha256_hash(query: &str) -> String {
+    hex::encode(Sha256::digest(query.as_bytes()))
+}
+
+fn error_code(e: &PersistedQueryError) -> &'static str {
+    match e {
+        PersistedQueryError::NotFound => "PERSISTED_QUERY_NOT_FOUND",
+        PersistedQueryError::NotAllowed => "PERSISTED_QUERY_NOT_ALLOWED",
+        PersistedQueryError::HashMismatch | PersistedQueryError::Invalid(_) => {
+            "INVALID_PERSISTED_QUERY"
+        }
+    }
+}
+
+/// The error as a GraphQL response. Clients only retry with the full
+/// query if they see a `PersistedQueryNotFound` error in a successful
+/// response; everything else is a client error.
+pub(crate) fn error_response(e: &PersistedQueryError) -> ServerResponse {
+    let status = match e {
+        PersistedQueryError::NotFound => 200,
+        _ => 400,
+    };
+    let response_obj = json!({
+        "errors": [{
+            "message": e.message(),
+            "extensions": { "code": error_code(e) }
+        }]
+    });
+    let response_str = serde_json::to_string(&response_obj).unwrap();
+
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Full::from(response_str))
+        .unwrap()
+}
+
+#[derive(Debug)]
+pub struct PersistedQueries {
+    /// If this is set, only these queries, keyed by their hash, can be run
+    allowlist: Option<HashMap<String, String>>,
+    /// Queries that clients registered, keyed by their hash
+    cache: Mutex<LfuCache<String, String>>,
+    max_weight: usize,
+}
+
+impl PersistedQueries {
+    pub fn new(max_weight: usize, allowlist: Option<HashMap<String, String>>) -> Self {
+        PersistedQueries {
+            allowlist,
+            cache: Mutex::new(LfuCache::new()),
+            max_weight,
+        }
+    }
+
+    /// Set up persisted queries as configured by the environment. Returns
+    /// `None` if they are not enabled.
+    pub fn from_env(logger: &Logger) -> Result<Option<Self>, Error> {
+        let env = &ENV_VARS.graphql;
+
+        let allowlist = match &env.persisted_queries_allowlist {
+            Some(path) => {
+                let allowlist = Self::load_allowlist(path)?;
+                info!(logger, "Only running queries from the allowlist of persisted queries";
+                      "path" => path.display().to_string(),
+                      "queries" => allowlist.len());
+                Some(allowlist)
+            }
+            None if env.persisted_queries => None,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self::new(env.persisted_queries_max_mem, allowlist)))
+    }
+
+    /// Read an allowlist, a JSON object that maps the hashes of queries to
+    /// their text, from `path`
+    fn load_allowlist(path: &Path) -> Result<HashMap<String, String>, Error> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            anyhow!(
+                "failed to read persisted query allowlist `{}`: {}",
+                path.display(),
+                e
+            )
+        })?;
+        let queries: HashMap<String, String> = serde_json::from_str(&contents).map_err(|e| {
+            anyhow!(
+                "persisted query allowlist `{}` is not a JSON object of hashes to queries: {}",
+                path.display(),
+                e
+            )
+        })?;
+
+        queries
+            .into_iter()
+            .map(|(hash, query)| {
+                let hash = hash.to_ascii_lowercase();
+                if hash != sha256_hash(&query) {
+                    return Err(anyhow!(
+                        "the hash `{}` in the persisted query allowlist `{}` does not match its query",
+                        hash,
+                        path.display()
+                    ));
+                }
+                Ok((hash, query))
+            })
+            .collect()
+    }
+
+    fn lookup(&self, hash: &str) -> Result<String, PersistedQueryError> {
+        match &self.allowlist {
+            Some(allowlist) => allowlist
+                .get(hash)
+                .cloned()
+                .ok_or(PersistedQueryError::NotAllowed),
+            None => self
+                .cache
+                .lock()
+                .unwrap()
+                .get(&hash.to_string())
+                .cloned()
+                .ok_or(PersistedQueryError::NotFound),
+        }
+    }
+
+    fn register(&self, hash: String, query: &str) -> Result<(), PersistedQueryError> {
+        match &self.allowlist {
+            Some(allowlist) if allowlist.contains_key(&hash) => Ok(()),
+            Some(_) => Err(PersistedQueryError::NotAllowed),
+            None => {
+                let mut cache = self.cache.lock().unwrap();
+                if !cache.contains_key(&hash) {
+                    cache.insert(hash, query.to_string());
+                    cache.evict(self.max_weight);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Make sure the GraphQL request `obj` has a `query` we may run. If the
+    /// request only has the hash of a query, the query is looked up and
+    /// added to the request. If it has both, the query is registered under
+    /// its hash.
+    pub fn resolve(&self, obj: &mut JsonObject) -> Result<(), PersistedQueryError> {
+        let hash = match obj
+            .get("extensions")
+            .and_then(|extensions| extensions.get("persistedQuery"))
+        {
+            None => None,
+            Some(persisted_query) => {
+                if persisted_query.get("version").and_then(Value::as_u64) != Some(1) {
+                    return Err(PersistedQueryError::Invalid(
+                        "Unsupported persisted query version".to_string(),
+                    ));
+                }
+                let hash = persisted_query
+                    .get("sha256Hash")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| {
+                        PersistedQueryError::Invalid(
+                            "The \"sha256Hash\" of the persisted query is missing".to_string(),
+                        )
+                    })?;
+                Some(hash.to_ascii_lowercase())
+            }
+        };
+
+        let resolved = match (hash, obj.get("query").and_then(Value::as_str)) {
+            (Some(hash), None) => Some(self.lookup(&hash)?),
+            (Some(hash), Some(query)) => {
+                if sha256_hash(query) != hash {
+                    return Err(PersistedQueryError::HashMismatch);
+                }
+                self.register(hash, query)?;
+                None
+            }
+            (None, Some(query)) => {
+                self.check(query)?;
+                None
+            }
+            // Let parsing the request complain about the missing query
+            (None, None) => None,
+        };
+
+        if let Some(query) = resolved {
+            obj.insert("query".to_string(), Value::String(query));
+        }
+        Ok(())
+    }
+}
+
+impl QueryAllowlist for PersistedQueries {
+    fn check(&self, query: &str) -> Result<(), PersistedQueryError> {
+        match &self.allowlist {
+            Some(allowlist) if !allowlist.contains_key(&sha256_hash(query)) => {
+                Err(PersistedQueryError::NotAllowed)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use graph::components::server::persisted::PersistedQueryError;
+    use graph::prelude::serde_json::{self, json, Value};
+
+    use super::{sha256_hash, JsonObject, PersistedQueries};
+
+    const QUERY: &str = "{ things { id } }";
+
+    fn request(value: Value) -> JsonObject {
+        match value {
+            Value::Object(obj) => obj,
+            _ => unreachable!(),
+        }
+    }
+
+    fn by_hash(hash: &str) -> JsonObject {
+        request(json!({
+            "extensions": { "persistedQuery": { "version": 1, "sha256Hash": hash } }
+        }))
+    }
+
+    fn with_query(hash: &str, query: &str) -> JsonObject {
+        let mut obj = by_hash(hash);
+        obj.insert(
+            "query".to_string(),
+            serde_json::Value::String(query.to_string()),
+        );
+        obj
+    }
+
+    #[test]
+    fn registers_and_looks_up_queries() {
+        let queries = PersistedQueries::new(1_000_000, None);
+        let hash = sha256_hash(QUERY);
+
+        assert_eq!(
+            Err(PersistedQueryError::NotFound),
+            queries.resolve(&mut by_hash(&hash))
+        );
+
+        assert_eq!(Ok(()), queries.resolve(&mut with_query(&hash, QUERY)));
+
+        let mut obj = by_hash(&hash.to_ascii_uppercase());
+        assert_eq!(Ok(()), queries.resolve(&mut obj));
+        assert_eq!(Some(QUERY), obj.get("query").and_then(Value::as_str));
+    }
+
+    #[test]
+    fn rejects_mismatched_hashes() {
+        let queries = PersistedQueries::new(1_000_000, None);
+        let hash = sha256_hash("{ other { id } }");
+
+        assert_eq!(
+            Err(PersistedQueryError::HashMismatch),
+            queries.resolve(&mut with_query(&hash, QUERY))
+        );
+        assert_eq!(
+            Err(PersistedQueryError::NotFound),
+            queries.resolve(&mut by_hash(&hash))
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_versions() {
+        let queries = PersistedQueries::new(1_000_000, None);
+        let mut obj = request(json!({
+            "query": QUERY,
+            "extensions": { "persistedQuery": { "version": 2, "sha256Hash": sha256_hash(QUERY) } }
+        }));
+
+        assert!(matches!(
+            queries.resolve(&mut obj),
+            Err(PersistedQueryError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn allowlist_only_runs_known_queries() {
+        let allowed = "{ allowed { id } }";
+        let allowlist = HashMap::from([(sha256_hash(allowed), allowed.to_string())]);
+        let queries = PersistedQueries::new(1_000_000, Some(allowlist));
+
+        let mut obj = by_hash(&sha256_hash(allowed));
+        assert_eq!(Ok(()), queries.resolve(&mut obj));
+        assert_eq!(Some(allowed), obj.get("query").and_then(Value::as_str));
+
+        let mut obj = request(json!({ "query": allowed }));
+        assert_eq!(Ok(()), queries.resolve(&mut obj));
+
+        let hash = sha256_hash(QUERY);
+        assert_eq!(
+            Err(PersistedQueryError::NotAllowed),
+            queries.resolve(&mut with_query(&hash, QUERY))
+        );
+        assert_eq!(
+            Err(PersistedQueryError::NotAllowed),
+            queries.resolve(&mut by_hash(&hash))
+        );
+        assert_eq!(
+            Err(PersistedQueryError::NotAllowed),
+            queries.resolve(&mut request(json!({ "query": QUERY })))
+        );
+    }
+}
@@ -3,17 +3,58 @@ use graph::prelude::serde_json;
 use graph::components::server::query::ServerError;
 use graph::hyper::body::Bytes;
 use graph::prelude::*;
+use graph::url::form_urlencoded;
 
 pub fn parse_graphql_request(body: &Bytes, trace: bool) -> Result<Query, ServerError> {
+    parse_graphql_object(&parse_graphql_json(body)?, trace)
+}
+
+/// Parse the request body as a JSON object without looking at its contents
+pub fn parse_graphql_json(
+    body: &Bytes,
+) -> Result<serde_json::Map<String, serde_json::Value>, ServerError> {
     // Parse request body as JSON
     let json: serde_json::Value =
         serde_json::from_slice(body).map_err(|e| ServerError::ClientError(format!("{}", e)))?;
 
     // Ensure the JSON data is an object
-    let obj = json
-        .as_object()
-        .ok_or_else(|| ServerError::ClientError(String::from("Request data is not an object")))?;
+    match json {
+        serde_json::Value::Object(obj) => Ok(obj),
+        _ => Err(ServerError::ClientError(String::from(
+            "Request data is not an object",
+        ))),
+    }
+}
 
+/// Parse a GraphQL request that is passed in the URL query string as the
+/// `query`, `variables` and `extensions` parameters, which is the only way
+/// for clients like browsers' `EventSource` to send a request. The request
+/// is turned into the same JSON object that clients would send in the body
+pub fn parse_graphql_query_params(
+    params: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>, ServerError> {
+    let mut obj = serde_json::Map::new();
+    for (key, value) in form_urlencoded::parse(params.as_bytes()) {
+        match key.as_ref() {
+            "query" => {
+                obj.insert("query".to_string(), serde_json::Value::String(value.into()));
+            }
+            "variables" | "extensions" => {
+                let json = serde_json::from_str(&value)
+                    .map_err(|e| ServerError::ClientError(format!("{}", e)))?;
+                obj.insert(key.into_owned(), json);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(obj)
+}
+
+pub fn parse_graphql_object(
+    obj: &serde_json::Map<String, serde_json::Value>,
+    trace: bool,
+) -> Result<Query, ServerError> {
     // Ensure the JSON data has a "query" field
     let query_value = obj.get("query").ok_or_else(|| {
         ServerError::ClientError(String::from(
@@ -59,7 +100,7 @@ mod tests {
         prelude::*,
     };
 
-    use super::parse_graphql_request;
+    use super::{parse_graphql_object, parse_graphql_query_params, parse_graphql_request};
 
     lazy_static! {
         static ref TARGET: QueryTarget = QueryTarget::Name(
@@ -171,4 +212,28 @@ mod tests {
         assert_eq!(query.document, expected_query);
         assert_eq!(query.variables, Some(expected_variables));
     }
+
+    #[test]
+    fn parses_query_params() {
+        let obj = parse_graphql_query_params(
+            "query=subscription%20%7B%20user%20%7B%20name%20%7D%20%7D&variables=%7B%22int%22%3A5%7D",
+        )
+        .expect("Should accept valid query parameters");
+        let query = parse_graphql_object(&obj, false).expect("Should accept a valid query");
+
+        let expected_query = q::parse_query("subscription { user { name } }")
+            .unwrap()
+            .into_static();
+        let expected_variables = QueryVariables::new(HashMap::from_iter(
+            vec![(String::from("int"), r::Value::Int(5))].into_iter(),
+        ));
+
+        assert_eq!(query.document, expected_query);
+        assert_eq!(query.variables, Some(expected_variables));
+
+        let obj = parse_graphql_query_params("variables=%7B%7D")
+            .expect("Should accept valid query parameters");
+        parse_graphql_object(&obj, false)
+            .expect_err("Should reject query parameters without a query");
+    }
 }
@@ -6,18 +6,28 @@ use graph::components::server::server::{start, ServerHandle};
 use graph::log::factory::{ComponentLoggerConfig, ElasticComponentLoggerConfig};
 use graph::slog::info;
 
+use crate::limits::{QueryLimits, RateLimiter};
+use crate::persisted::PersistedQueries;
 use crate::service::GraphQLService;
-use graph::prelude::{GraphQlRunner, Logger, LoggerFactory};
+use graph::prelude::{GraphQlRunner, Logger, LoggerFactory, MetricsRegistry};
 
 /// A GraphQL server based on Hyper.
 pub struct GraphQLServer<Q> {
     logger: Logger,
     graphql_runner: Arc<Q>,
+    rate_limiter: Arc<RateLimiter>,
+    persisted_queries: Option<Arc<PersistedQueries>>,
 }
 
 impl<Q: GraphQlRunner> GraphQLServer<Q> {
     /// Creates a new GraphQL server.
-    pub fn new(logger_factory: &LoggerFactory, graphql_runner: Arc<Q>) -> Self {
+    pub fn new(
+        logger_factory: &LoggerFactory,
+        graphql_runner: Arc<Q>,
+        query_limits: QueryLimits,
+        metrics_registry: Arc<MetricsRegistry>,
+        persisted_queries: Option<Arc<PersistedQueries>>,
+    ) -> Self {
         let logger = logger_factory.component_logger(
             "GraphQLServer",
             Some(ComponentLoggerConfig {
@@ -26,9 +36,12 @@ impl<Q: GraphQlRunner> GraphQLServer<Q> {
                 }),
             }),
         );
+        let rate_limiter = Arc::new(RateLimiter::new(query_limits, metrics_registry));
         GraphQLServer {
             logger,
             graphql_runner,
+            rate_limiter,
+            persisted_queries,
         }
     }
 
@@ -42,11 +55,17 @@ impl<Q: GraphQlRunner> GraphQLServer<Q> {
 
         let graphql_runner = self.graphql_runner.clone();
 
-        let service = Arc::new(GraphQLService::new(logger.clone(), graphql_runner, ws_port));
+        let service = Arc::new(GraphQLService::new(
+            logger.clone(),
+            graphql_runner,
+            ws_port,
+            self.rate_limiter.cheap_clone(),
+            self.persisted_queries.clone(),
+        ));
 
         start(logger, port, move |req| {
             let service = service.cheap_clone();
-            async move { Ok::<_, _>(service.cheap_clone().call(req).await) }
+            async move { Ok::<_, _>(service.cheap_clone().call_with_events(req).await) }
         })
         .await
     }
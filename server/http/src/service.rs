@@ -1,21 +1,23 @@
 use std::convert::TryFrom;
 use std::env;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use graph::cheap_clone::CheapClone;
 use graph::components::graphql::GraphQlRunner;
 use graph::components::server::query::ServerResponse;
 use graph::components::server::query::ServerResult;
+use graph::components::server::server::RemoteAddr;
 use graph::components::versions::ApiVersion;
-use graph::data::query::QueryResult;
+use graph::data::query::{QueryResult, QueryResults};
 use graph::data::subgraph::DeploymentHash;
 use graph::data::subgraph::SubgraphName;
+use graph::data::subscription::{Subscription, SubscriptionError};
 use graph::env::ENV_VARS;
 use graph::http_body_util::{BodyExt, Full};
 use graph::hyper::header::{
     ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
-    CONTENT_LENGTH, CONTENT_TYPE, LOCATION,
+    CONTENT_LENGTH, CONTENT_TYPE, LOCATION, RETRY_AFTER,
 };
 use graph::hyper::{body::Body, header::HeaderValue};
 use graph::hyper::{Method, Request, Response, StatusCode};
@@ -24,10 +26,17 @@ use graph::prelude::serde_json::json;
 use graph::semver::VersionReq;
 use graph::slog::error;
 use graph::slog::Logger;
+use graph::tokio::sync::Semaphore;
 use graph::url::form_urlencoded;
 use graph::{components::server::query::ServerError, data::query::QueryTarget};
 
-use crate::request::parse_graphql_request;
+use crate::encoding::{encode_response, etag, query_hash};
+use crate::limits::{Admission, RateLimiter};
+use crate::persisted::{self, PersistedQueries};
+use crate::request::{
+    parse_graphql_json, parse_graphql_object, parse_graphql_query_params, parse_graphql_request,
+};
+use crate::sse::{accepts_event_stream, event_stream_response, ResponseBody};
 
 fn client_error(msg: impl Into<String>) -> ServerResponse {
     let response_obj = json!({
@@ -43,12 +52,47 @@ fn client_error(msg: impl Into<String>) -> ServerResponse {
         .unwrap()
 }
 
+fn too_many_requests(retry_after: Duration) -> ServerResponse {
+    let response_obj = json!({
+        "error": "Too many requests, please slow down"
+    });
+    let response_str = serde_json::to_string(&response_obj).unwrap();
+    // `Retry-After` is in whole seconds; round up so clients don't retry
+    // too early
+    let retry_after = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+    Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header(CONTENT_TYPE, "application/json")
+        .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(RETRY_AFTER, retry_after.max(1))
+        .body(Full::from(response_str))
+        .unwrap()
+}
+
+// Filter out empty strings from path segments
+fn filter_and_join_segments(segments: &[&str]) -> String {
+    segments
+        .iter()
+        .filter(|&&segment| !segment.is_empty())
+        .map(|&segment| segment)
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
 /// A Hyper Service that serves GraphQL over a POST / endpoint.
 #[derive(Debug)]
 pub struct GraphQLService<Q> {
     logger: Logger,
     graphql_runner: Arc<Q>,
     ws_port: u16,
+    /// Limits the number of subscriptions that are streamed as server-sent
+    /// events at the same time
+    sse_permits: Arc<Semaphore>,
+    /// Set if clients can send queries by their hash, or if only queries
+    /// from an allowlist can be run
+    persisted_queries: Option<Arc<PersistedQueries>>,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl<Q> GraphQLService<Q>
@@ -56,11 +100,20 @@ where
     Q: GraphQlRunner,
 {
     /// Creates a new GraphQL service.
-    pub fn new(logger: Logger, graphql_runner: Arc<Q>, ws_port: u16) -> Self {
+    pub fn new(
+        logger: Logger,
+        graphql_runner: Arc<Q>,
+        ws_port: u16,
+        rate_limiter: Arc<RateLimiter>,
+        persisted_queries: Option<Arc<PersistedQueries>>,
+    ) -> Self {
         GraphQLService {
             logger,
             graphql_runner,
             ws_port,
+            sse_permits: Arc::new(Semaphore::new(ENV_VARS.graphql.max_sse_subscriptions)),
+            persisted_queries,
+            rate_limiter,
         }
     }
 
@@ -123,18 +176,39 @@ where
         Ok(version)
     }
 
-    async fn handle_graphql_query_by_name<T: Body>(
+    fn target_by_name<T>(
         &self,
         subgraph_name: String,
-        request: Request<T>,
-    ) -> ServerResult {
-        let version = self.resolve_api_version(&request)?;
+        request: &Request<T>,
+    ) -> Result<QueryTarget, ServerError> {
+        let version = self.resolve_api_version(request)?;
         let subgraph_name = SubgraphName::new(subgraph_name.as_str()).map_err(|()| {
             ServerError::ClientError(format!("Invalid subgraph name {:?}", subgraph_name))
         })?;
 
-        self.handle_graphql_query(QueryTarget::Name(subgraph_name, version), request)
-            .await
+        Ok(QueryTarget::Name(subgraph_name, version))
+    }
+
+    fn target_by_id<T>(
+        &self,
+        id: String,
+        request: &Request<T>,
+    ) -> Result<QueryTarget, ServerError> {
+        let id = DeploymentHash::new(id)
+            .map_err(|id| ServerError::ClientError(format!("Invalid subgraph id `{}`", id)))?;
+        let version = self.resolve_api_version(request)?;
+
+        Ok(QueryTarget::Deployment(id, version))
+    }
+
+    async fn handle_graphql_query_by_name<T: Body>(
+        &self,
+        subgraph_name: String,
+        request: Request<T>,
+    ) -> ServerResult {
+        let target = self.target_by_name(subgraph_name, &request)?;
+
+        self.handle_graphql_query(target, request).await
     }
 
     async fn handle_graphql_query_by_id<T: Body>(
@@ -142,12 +216,9 @@ where
         id: String,
         request: Request<T>,
     ) -> ServerResult {
-        let id = DeploymentHash::new(id)
-            .map_err(|id| ServerError::ClientError(format!("Invalid subgraph id `{}`", id)))?;
-        let version = self.resolve_api_version(&request)?;
+        let target = self.target_by_id(id, &request)?;
 
-        self.handle_graphql_query(QueryTarget::Deployment(id, version), request)
-            .await
+        self.handle_graphql_query(target, request).await
     }
 
     async fn handle_graphql_query<T: Body>(
@@ -168,25 +239,59 @@ where
                     })
                     .unwrap_or(false)
         };
+        let remote_addr = request.extensions().get::<RemoteAddr>().map(|addr| addr.0);
+        let max_complexity = match self.rate_limiter.admit(request.headers(), remote_addr) {
+            Admission::Allowed { max_complexity } => max_complexity,
+            Admission::Limited { retry_after } => return Ok(too_many_requests(retry_after)),
+        };
+        let headers = request.headers().clone();
         let body = request
             .collect()
             .await
             .map_err(|_| ServerError::InternalError("Failed to read request body".into()))?
             .to_bytes();
-        let query = parse_graphql_request(&body, trace);
+        let query = match &self.persisted_queries {
+            Some(persisted_queries) => {
+                let mut obj = parse_graphql_json(&body)?;
+                if let Err(e) = persisted_queries.resolve(&mut obj) {
+                    return Ok(persisted::error_response(&e));
+                }
+                parse_graphql_object(&obj, trace)
+            }
+            None => parse_graphql_request(&body, trace),
+        };
         let query_parsing_time = start.elapsed();
 
+        // Traces differ from run to run, results with them can't be cached
+        let mut hash = None;
         let mut result = match query {
             Ok(query) => {
-                self.graphql_runner
-                    .cheap_clone()
-                    .run_query(query, target)
-                    .await
+                if !trace {
+                    hash = Some(query_hash(&query));
+                }
+                let runner = self.graphql_runner.cheap_clone();
+                match max_complexity {
+                    Some(max_complexity) => {
+                        runner
+                            .run_query_with_complexity(
+                                query,
+                                target,
+                                Some(max_complexity),
+                                Some(ENV_VARS.graphql.max_depth),
+                                Some(ENV_VARS.graphql.max_first),
+                                Some(ENV_VARS.graphql.max_skip),
+                            )
+                            .await
+                    }
+                    None => runner.run_query(query, target).await,
+                }
             }
             Err(ServerError::QueryError(e)) => QueryResult::from(e).into(),
             Err(e) => return Err(e),
         };
 
+        self.rate_limiter
+            .charge(&headers, remote_addr, result.complexity());
         result.trace.query_parsing(query_parsing_time);
         self.graphql_runner
             .metrics()
@@ -195,7 +300,88 @@ where
             .metrics()
             .observe_query_execution(start.elapsed(), &result);
 
-        Ok(result.as_http_response())
+        let etag = hash.and_then(|hash| etag(&hash, &result));
+        Ok(encode_response(result.as_http_response(), &headers, etag).await)
+    }
+
+    /// Runs the subscription from the request and streams its results to
+    /// the client as server-sent events. `GET` requests pass the query in
+    /// the URL, all other requests in the body. Starting a subscription
+    /// counts against the client's rate limit like a query, but the
+    /// complexity of the subscription is not taken from its budget.
+    async fn handle_graphql_subscription<T: Body>(
+        &self,
+        target: QueryTarget,
+        request: Request<T>,
+    ) -> Result<Response<ResponseBody>, ServerError> {
+        let remote_addr = request.extensions().get::<RemoteAddr>().map(|addr| addr.0);
+        if let Admission::Limited { retry_after } =
+            self.rate_limiter.admit(request.headers(), remote_addr)
+        {
+            return Ok(too_many_requests(retry_after).map(|body| body.boxed_unsync()));
+        }
+        let mut obj = if request.method() == Method::GET {
+            parse_graphql_query_params(request.uri().query().unwrap_or_default())?
+        } else {
+            let body = request
+                .collect()
+                .await
+                .map_err(|_| ServerError::InternalError("Failed to read request body".into()))?
+                .to_bytes();
+            parse_graphql_json(&body)?
+        };
+        // Subscriptions have to pass the same checks as queries
+        if let Some(persisted_queries) = &self.persisted_queries {
+            if let Err(e) = persisted_queries.resolve(&mut obj) {
+                return Ok(persisted::error_response(&e).map(|body| body.boxed_unsync()));
+            }
+        }
+
+        let query = match parse_graphql_object(&obj, false) {
+            Ok(query) => query,
+            Err(ServerError::QueryError(e)) => {
+                let result: QueryResults = QueryResult::from(e).into();
+                return Ok(result.as_http_response().map(|body| body.boxed_unsync()));
+            }
+            Err(e) => return Err(e),
+        };
+
+        let permit = match self.sse_permits.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                let response_obj = json!({
+                    "error": format!(
+                        "Reached the limit of {} concurrent subscriptions",
+                        ENV_VARS.graphql.max_sse_subscriptions
+                    )
+                });
+                let response_str = serde_json::to_string(&response_obj).unwrap();
+
+                return Ok(Response::builder()
+                    .status(StatusCode::SERVICE_UNAVAILABLE)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                    .body(Full::from(response_str).boxed_unsync())
+                    .unwrap());
+            }
+        };
+
+        match self
+            .graphql_runner
+            .cheap_clone()
+            .run_subscription(Subscription { query }, target)
+            .await
+        {
+            Ok(results) => Ok(event_stream_response(
+                results,
+                ENV_VARS.graphql.sse_heartbeat_interval,
+                permit,
+            )),
+            Err(SubscriptionError::GraphQLError(errors)) => {
+                let result: QueryResults = QueryResult::from(errors).into();
+                Ok(result.as_http_response().map(|body| body.boxed_unsync()))
+            }
+        }
     }
 
     // Handles OPTIONS requests
@@ -291,16 +477,6 @@ where
             }
         }
 
-        // Filter out empty strings from path segments
-        fn filter_and_join_segments(segments: &[&str]) -> String {
-            segments
-                .iter()
-                .filter(|&&segment| !segment.is_empty())
-                .map(|&segment| segment)
-                .collect::<Vec<&str>>()
-                .join("/")
-        }
-
         let is_mutation = req
             .uri()
             .query()
@@ -354,7 +530,51 @@ where
 
         match result {
             Ok(response) => response,
-            Err(err @ ServerError::ClientError(_)) => {
+            Err(err) => self.error_response(err),
+        }
+    }
+
+    /// Like `call`, but requests to the query routes of a subgraph that
+    /// accept `text/event-stream` run a subscription whose results are sent
+    /// as server-sent events.
+    pub async fn call_with_events<T: Body + std::fmt::Debug>(
+        &self,
+        req: Request<T>,
+    ) -> Response<ResponseBody> {
+        if !accepts_event_stream(req.headers()) {
+            return self.call(req).await.map(|body| body.boxed_unsync());
+        }
+
+        match self.handle_event_stream_call(req).await {
+            Ok(response) => response,
+            Err(err) => self.error_response(err).map(|body| body.boxed_unsync()),
+        }
+    }
+
+    async fn handle_event_stream_call<T: Body>(
+        &self,
+        req: Request<T>,
+    ) -> Result<Response<ResponseBody>, ServerError> {
+        let path = req.uri().path().to_owned();
+        let path_segments = path.split('/').skip(1).collect::<Vec<_>>();
+
+        let target = match (req.method(), path_segments.as_slice()) {
+            (&Method::GET | &Method::POST, &["subgraphs", "id", subgraph_id]) => {
+                self.target_by_id(subgraph_id.to_owned(), &req)?
+            }
+            (&Method::GET | &Method::POST, ["subgraphs", "name", ..]) => {
+                let subgraph_name = filter_and_join_segments(&path_segments[2..]);
+                self.target_by_name(subgraph_name, &req)?
+            }
+            _ => return Ok(self.handle_not_found()?.map(|body| body.boxed_unsync())),
+        };
+
+        self.handle_graphql_subscription(target, req).await
+    }
+
+    fn error_response(&self, err: ServerError) -> ServerResponse {
+        match err {
+            err @ ServerError::ClientError(_) => {
                 let response_obj = json!({
                     "error": err.to_string()
                 });
@@ -367,7 +587,7 @@ where
                     .body(Full::from(response_str))
                     .unwrap()
             }
-            Err(err @ ServerError::QueryError(_)) => {
+            err @ ServerError::QueryError(_) => {
                 error!(self.logger, "GraphQLService call failed: {}", err);
 
                 let response_obj = json!({
@@ -382,7 +602,7 @@ where
                     .body(Full::from(response_str))
                     .unwrap()
             }
-            Err(err @ ServerError::InternalError(_)) => {
+            err @ ServerError::InternalError(_) => {
                 error!(self.logger, "GraphQLService call failed: {}", err);
 
                 Response::builder()
@@ -398,19 +618,23 @@ where
 
 #[cfg(test)]
 mod tests {
+    use graph::components::server::server::RemoteAddr;
     use graph::data::value::{Object, Word};
     use graph::http_body_util::{BodyExt, Full};
     use graph::hyper::body::Bytes;
-    use graph::hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+    use graph::hyper::header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE};
     use graph::hyper::{Method, Request, StatusCode};
     use graph::prelude::serde_json::json;
 
     use graph::data::query::{QueryResults, QueryTarget};
     use graph::prelude::*;
+    use std::collections::HashMap;
 
     use crate::test_utils;
 
     use super::GraphQLService;
+    use crate::limits::{ClientLimit, QueryLimits, RateLimiter};
+    use crate::persisted::PersistedQueries;
 
     /// A simple stupid query runner for testing.
     pub struct TestGraphQlRunner;
@@ -462,12 +686,19 @@ mod tests {
         }
     }
 
+    fn rate_limiter() -> Arc<RateLimiter> {
+        Arc::new(RateLimiter::new(
+            QueryLimits::default(),
+            Arc::new(MetricsRegistry::mock()),
+        ))
+    }
+
     #[tokio::test]
     async fn querying_not_found_routes_responds_correctly() {
         let logger = Logger::root(slog::Discard, o!());
         let graphql_runner = Arc::new(TestGraphQlRunner);
 
-        let service = GraphQLService::new(logger, graphql_runner, 8001);
+        let service = GraphQLService::new(logger, graphql_runner, 8001, rate_limiter(), None);
 
         let request: Request<Full<Bytes>> = Request::builder()
             .method(Method::GET)
@@ -499,7 +730,7 @@ mod tests {
         let subgraph_id = USERS.clone();
         let graphql_runner = Arc::new(TestGraphQlRunner);
 
-        let service = GraphQLService::new(logger, graphql_runner, 8001);
+        let service = GraphQLService::new(logger, graphql_runner, 8001, rate_limiter(), None);
 
         let request: Request<Full<Bytes>> = Request::builder()
             .method(Method::POST)
@@ -531,7 +762,7 @@ mod tests {
         let subgraph_id = USERS.clone();
         let graphql_runner = Arc::new(TestGraphQlRunner);
 
-        let service = GraphQLService::new(logger, graphql_runner, 8001);
+        let service = GraphQLService::new(logger, graphql_runner, 8001, rate_limiter(), None);
 
         let request: Request<Full<Bytes>> = Request::builder()
             .method(Method::POST)
@@ -557,4 +788,77 @@ mod tests {
             .expect("Query result field \"name\" is not a string");
         assert_eq!(name, "Jordi".to_string());
     }
+
+    #[tokio::test]
+    async fn event_streams_only_run_allowed_queries() {
+        let logger = Logger::root(slog::Discard, o!());
+        let graphql_runner = Arc::new(TestGraphQlRunner);
+        let allowed = "subscription { allowed }";
+        let allowlist = HashMap::from([("0".repeat(64), allowed.to_string())]);
+        let persisted_queries = Arc::new(PersistedQueries::new(1_000_000, Some(allowlist)));
+
+        let service = GraphQLService::new(
+            logger,
+            graphql_runner,
+            8001,
+            rate_limiter(),
+            Some(persisted_queries),
+        );
+
+        // `TestGraphQlRunner` panics if it is asked to run a subscription
+        let request: Request<Full<Bytes>> = Request::builder()
+            .method(Method::GET)
+            .header(ACCEPT, "text/event-stream")
+            .uri(format!(
+                "http://localhost:8000/subgraphs/id/{}?query=subscription%20%7B%20name%20%7D",
+                *USERS
+            ))
+            .body(Full::from(""))
+            .unwrap();
+
+        let response = service.call_with_events(request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json["errors"][0]["extensions"]["code"],
+            json!("PERSISTED_QUERY_NOT_ALLOWED")
+        );
+    }
+
+    #[tokio::test]
+    async fn event_streams_are_rate_limited() {
+        let logger = Logger::root(slog::Discard, o!());
+        let graphql_runner = Arc::new(TestGraphQlRunner);
+        let limits = QueryLimits {
+            anonymous: Some(ClientLimit {
+                requests_per_second: 1.0,
+                burst: 0,
+                max_complexity: None,
+                complexity_budget: None,
+            }),
+            ..QueryLimits::default()
+        };
+        let rate_limiter = Arc::new(RateLimiter::new(limits, Arc::new(MetricsRegistry::mock())));
+
+        let service = GraphQLService::new(logger, graphql_runner, 8001, rate_limiter, None);
+
+        // `TestGraphQlRunner` panics if it is asked to run a subscription
+        let mut request: Request<Full<Bytes>> = Request::builder()
+            .method(Method::GET)
+            .header(ACCEPT, "text/event-stream")
+            .uri(format!(
+                "http://localhost:8000/subgraphs/id/{}?query=subscription%20%7B%20name%20%7D",
+                *USERS
+            ))
+            .body(Full::from(""))
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(RemoteAddr("10.0.0.1:4000".parse().unwrap()));
+
+        let response = service.call_with_events(request).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
 }
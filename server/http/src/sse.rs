@@ -0,0 +1,107 @@
+//! Streaming of GraphQL subscription results as server-sent events. This
+//! follows the "distinct connections mode" of the GraphQL over SSE protocol:
+//! every request runs exactly one subscription, each result is sent as a
+//! `next` event, and a `complete` event is sent when the subscription ends.
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use graph::data::query::QueryResult;
+use graph::data::subscription::QueryResultStream;
+use graph::futures03::future;
+use graph::futures03::stream::{self, StreamExt};
+use graph::http_body_util::combinators::UnsyncBoxBody;
+use graph::http_body_util::{BodyExt, StreamBody};
+use graph::hyper::body::{Bytes, Frame};
+use graph::hyper::header::{ACCEPT, ACCESS_CONTROL_ALLOW_ORIGIN, CACHE_CONTROL, CONTENT_TYPE};
+use graph::hyper::{HeaderMap, Response};
+use graph::prelude::serde_json;
+use graph::tokio::sync::OwnedSemaphorePermit;
+use graph::tokio::time;
+
+pub const EVENT_STREAM: &str = "text/event-stream";
+
+/// The body of responses of the HTTP server, which can either be a
+/// complete response or a stream of events.
+pub type ResponseBody = UnsyncBoxBody<Bytes, Infallible>;
+
+/// Returns `true` if the client asked for a stream of events in its
+/// `Accept` header
+pub fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_range| media_range.split(';').next())
+        .any(|media_type| media_type.trim() == EVENT_STREAM)
+}
+
+fn next_event(result: &QueryResult) -> Bytes {
+    let data = serde_json::to_string(result).expect("query results can be serialized");
+    Bytes::from(format!("event: next\ndata: {}\n\n", data))
+}
+
+/// Builds a response that streams the `results` of a subscription as
+/// events. A heartbeat comment is sent every `heartbeat_interval` so that
+/// proxies do not close the connection while the subscription is idle. The
+/// `permit` is released when the client goes away.
+pub fn event_stream_response(
+    results: QueryResultStream,
+    heartbeat_interval: Duration,
+    permit: OwnedSemaphorePermit,
+) -> Response<ResponseBody> {
+    // `None` marks the end of the subscription
+    let events = results
+        .map(|result| Some(next_event(&result)))
+        .chain(stream::iter([
+            Some(Bytes::from_static(b"event: complete\ndata:\n\n")),
+            None,
+        ]));
+
+    let heartbeats = stream::unfold(time::interval(heartbeat_interval), |mut interval| async {
+        interval.tick().await;
+        Some((Some(Bytes::from_static(b": heartbeat\n\n")), interval))
+    });
+
+    let frames = stream::select(events, heartbeats)
+        .take_while(|event| future::ready(event.is_some()))
+        .map(move |event| {
+            // Keep the permit for as long as the stream is alive
+            let _ = &permit;
+            Ok::<_, Infallible>(Frame::data(event.unwrap_or_default()))
+        });
+
+    Response::builder()
+        .status(200)
+        .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(CONTENT_TYPE, EVENT_STREAM)
+        .header(CACHE_CONTROL, "no-cache")
+        // Keep nginx from buffering the events
+        .header("X-Accel-Buffering", "no")
+        .body(StreamBody::new(frames).boxed_unsync())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::hyper::header::{HeaderValue, ACCEPT};
+    use graph::hyper::HeaderMap;
+
+    use super::accepts_event_stream;
+
+    #[test]
+    fn detects_event_stream_requests() {
+        let accepts = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT, HeaderValue::from_static(value));
+            accepts_event_stream(&headers)
+        };
+
+        assert!(accepts("text/event-stream"));
+        assert!(accepts("application/json, text/event-stream;q=0.9"));
+        assert!(!accepts("application/json"));
+        assert!(!accepts("*/*"));
+        assert!(!accepts_event_stream(&HeaderMap::new()));
+    }
+}
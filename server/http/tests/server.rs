@@ -7,6 +7,7 @@ use graph::data::{
 };
 use graph::prelude::*;
 use graph_server_http::GraphQLServer as HyperGraphQLServer;
+use graph_server_http::QueryLimits;
 
 use tokio::time::sleep;
 
@@ -171,7 +172,13 @@ mod test {
         let logger_factory = LoggerFactory::new(logger, None, Arc::new(MetricsRegistry::mock()));
         let id = USERS.clone();
         let query_runner = Arc::new(TestGraphQlRunner);
-        let server = HyperGraphQLServer::new(&logger_factory, query_runner);
+        let server = HyperGraphQLServer::new(
+            &logger_factory,
+            query_runner,
+            QueryLimits::default(),
+            Arc::new(MetricsRegistry::mock()),
+            None,
+        );
         let server_handle = server
             .start(8007, 8008)
             .await
@@ -203,7 +210,13 @@ mod test {
         let logger_factory = LoggerFactory::new(logger, None, Arc::new(MetricsRegistry::mock()));
         let id = USERS.clone();
         let query_runner = Arc::new(TestGraphQlRunner);
-        let server = HyperGraphQLServer::new(&logger_factory, query_runner);
+        let server = HyperGraphQLServer::new(
+            &logger_factory,
+            query_runner,
+            QueryLimits::default(),
+            Arc::new(MetricsRegistry::mock()),
+            None,
+        );
         let server_handle = server
             .start(8002, 8003)
             .await
@@ -275,7 +288,13 @@ mod test {
         let logger_factory = LoggerFactory::new(logger, None, Arc::new(MetricsRegistry::mock()));
         let id = USERS.clone();
         let query_runner = Arc::new(TestGraphQlRunner);
-        let server = HyperGraphQLServer::new(&logger_factory, query_runner);
+        let server = HyperGraphQLServer::new(
+            &logger_factory,
+            query_runner,
+            QueryLimits::default(),
+            Arc::new(MetricsRegistry::mock()),
+            None,
+        );
         let server_handle = server
             .start(8003, 8004)
             .await
@@ -312,7 +331,13 @@ mod test {
         let logger_factory = LoggerFactory::new(logger, None, Arc::new(MetricsRegistry::mock()));
         let id = USERS.clone();
         let query_runner = Arc::new(TestGraphQlRunner);
-        let server = HyperGraphQLServer::new(&logger_factory, query_runner);
+        let server = HyperGraphQLServer::new(
+            &logger_factory,
+            query_runner,
+            QueryLimits::default(),
+            Arc::new(MetricsRegistry::mock()),
+            None,
+        );
         let server_handle = server
             .start(8005, 8006)
             .await
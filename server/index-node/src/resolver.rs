@@ -22,6 +22,10 @@ use crate::auth::PoiProtection;
 /// Timeout for calls to fetch the block from JSON-RPC or Firehose.
 const BLOCK_HASH_FROM_NUMBER_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Page size limits for `entityDiff`
+const ENTITY_DIFF_DEFAULT_FIRST: u32 = 100;
+const ENTITY_DIFF_MAX_FIRST: u32 = 1000;
+
 git_testament!(TESTAMENT);
 
 lazy_static! {
@@ -187,6 +191,62 @@ impl<S: Store> IndexNodeResolver<S> {
         Ok(entity_changes_to_graphql(entity_changes))
     }
 
+    fn resolve_entity_diff(&self, field: &a::Field) -> Result<r::Value, QueryExecutionError> {
+        let subgraph_id = field
+            .get_required::<DeploymentHash>("subgraphId")
+            .expect("Valid subgraphId required");
+
+        let from = field
+            .get_required::<BlockNumber>("fromBlock")
+            .expect("Valid fromBlock required");
+
+        let to = field
+            .get_required::<BlockNumber>("toBlock")
+            .expect("Valid toBlock required");
+
+        let entity_types = field
+            .get_optional::<Vec<String>>("entityTypes")
+            .expect("Invalid entityTypes")
+            .unwrap_or_default();
+
+        let first = field
+            .get_optional::<i32>("first")
+            .expect("Invalid first")
+            .unwrap_or(ENTITY_DIFF_DEFAULT_FIRST as i32);
+        if first < 0 || first as u32 > ENTITY_DIFF_MAX_FIRST {
+            return Err(QueryExecutionError::RangeArgumentsError(
+                "first",
+                ENTITY_DIFF_MAX_FIRST,
+                first as i64,
+            ));
+        }
+
+        let skip = field
+            .get_optional::<i32>("skip")
+            .expect("Invalid skip")
+            .unwrap_or(0);
+        if skip < 0 {
+            return Err(QueryExecutionError::RangeArgumentsError(
+                "skip",
+                i32::MAX as u32,
+                skip as i64,
+            ));
+        }
+
+        let diffs = self.store.subgraph_store().entity_diff(
+            &subgraph_id,
+            from,
+            to,
+            &entity_types,
+            first as usize,
+            skip as usize,
+        )?;
+
+        Ok(r::Value::List(
+            diffs.into_iter().map(entity_diff_to_graphql).collect(),
+        ))
+    }
+
     async fn resolve_block_data(&self, field: &a::Field) -> Result<r::Value, QueryExecutionError> {
         let network = field
             .get_required::<String>("network")
@@ -751,6 +811,26 @@ impl<S: Store> BlockPtrForNumber for IndexNodeResolver<S> {
     }
 }
 
+fn entity_to_graphql(entity: Entity) -> r::Value {
+    r::Value::object(
+        entity
+            .sorted()
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect(),
+    )
+}
+
+fn entity_diff_to_graphql(diff: EntityDiff) -> r::Value {
+    object! {
+        type: diff.key.entity_type.to_string(),
+        id: diff.key.entity_id.to_string(),
+        kind: r::Value::Enum(diff.kind().as_str().to_string()),
+        old: diff.old.map(entity_to_graphql),
+        new: diff.new.map(entity_to_graphql),
+    }
+}
+
 fn entity_changes_to_graphql(entity_changes: Vec<EntityOperation>) -> r::Value {
     // Results are sorted first alphabetically by entity type, then by entity
     // ID, and then aphabetically by field name.
@@ -784,14 +864,7 @@ fn entity_changes_to_graphql(entity_changes: Vec<EntityOperation>) -> r::Value {
             entities:
                 entities
                     .into_iter()
-                    .map(|e| {
-                        r::Value::object(
-                            e.sorted()
-                                .into_iter()
-                                .map(|(name, value)| (name.into(), value.into()))
-                                .collect(),
-                        )
-                    })
+                    .map(entity_to_graphql)
                     .collect::<Vec<r::Value>>(),
         });
     }
@@ -900,6 +973,7 @@ impl<S: Store> Resolver for IndexNodeResolver<S> {
             }
             (None, "subgraphFeatures") => self.resolve_subgraph_features(field).await,
             (None, "entityChangesInBlock") => self.resolve_entity_changes_in_block(field),
+            (None, "entityDiff") => self.resolve_entity_diff(field),
             // The top-level `subgraphVersions` field
             (None, "apiVersions") => self.resolve_api_versions(field),
             (None, "version") => self.version(),
@@ -1,6 +1,6 @@
 use graph::futures01::sync::mpsc;
 use graph::futures01::{Future, IntoFuture, Sink as _, Stream as _};
-use graph::futures03::future::TryFutureExt;
+use graph::futures03::future::{BoxFuture, TryFutureExt};
 use graph::futures03::sink::SinkExt;
 use graph::futures03::stream::{SplitStream, StreamExt, TryStreamExt};
 use std::collections::HashMap;
@@ -11,12 +11,18 @@ use tokio_tungstenite::tungstenite::{
 use tokio_tungstenite::WebSocketStream;
 use uuid::Uuid;
 
+use graph::components::server::persisted::QueryAllowlist;
 use graph::futures03::compat::Future01CompatExt;
 use graph::{data::query::QueryTarget, prelude::*};
 
+use crate::protocol::{
+    close_code, close_message, Protocol, TransportError, TransportIncomingMessage,
+    TransportOutgoingMessage,
+};
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct StartPayload {
+pub(crate) struct StartPayload {
     query: String,
     variables: Option<serde_json::Value>,
     operation_name: Option<String>,
@@ -80,6 +86,28 @@ impl OutgoingMessage {
     pub fn from_error_string(id: String, s: String) -> Self {
         OutgoingMessage::Error { id, payload: s }
     }
+
+    /// Encode the message the way `protocol` expects it
+    fn into_ws_message(self, protocol: Protocol) -> WsMessage {
+        match protocol {
+            Protocol::GraphQlWs => self.into(),
+            Protocol::GraphQlTransportWs => TransportOutgoingMessage::from(self).into(),
+        }
+    }
+}
+
+impl From<OutgoingMessage> for TransportOutgoingMessage {
+    fn from(msg: OutgoingMessage) -> Self {
+        match msg {
+            OutgoingMessage::ConnectionAck => TransportOutgoingMessage::ConnectionAck,
+            OutgoingMessage::Error { id, payload } => TransportOutgoingMessage::Error {
+                id,
+                payload: vec![TransportError { message: payload }],
+            },
+            OutgoingMessage::Data { id, payload } => TransportOutgoingMessage::Next { id, payload },
+            OutgoingMessage::Complete { id } => TransportOutgoingMessage::Complete { id },
+        }
+    }
 }
 
 impl From<OutgoingMessage> for WsMessage {
@@ -88,30 +116,52 @@ impl From<OutgoingMessage> for WsMessage {
     }
 }
 
+/// Helper function to send raw WebSocket messages.
+fn send_ws_message(sink: &mpsc::UnboundedSender<WsMessage>, msg: WsMessage) -> Result<(), WsError> {
+    sink.unbounded_send(msg).map_err(|_| {
+        let mut response = WsResponse::new(None);
+        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+        WsError::Http(response)
+    })
+}
+
 /// Helper function to send outgoing messages.
 fn send_message(
     sink: &mpsc::UnboundedSender<WsMessage>,
+    protocol: Protocol,
     msg: OutgoingMessage,
 ) -> Result<(), WsError> {
-    sink.unbounded_send(msg.into()).map_err(|_| {
-        let mut response = WsResponse::new(None);
-        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
-        WsError::Http(response)
-    })
+    send_ws_message(sink, msg.into_ws_message(protocol))
 }
 
 /// Helper function to send error messages.
 fn send_error_string(
     sink: &mpsc::UnboundedSender<WsMessage>,
+    protocol: Protocol,
     operation_id: String,
     error: String,
 ) -> Result<(), WsError> {
-    sink.unbounded_send(OutgoingMessage::from_error_string(operation_id, error).into())
-        .map_err(|_| {
-            let mut response = WsResponse::new(None);
-            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
-            WsError::Http(response)
-        })
+    send_message(
+        sink,
+        protocol,
+        OutgoingMessage::from_error_string(operation_id, error),
+    )
+}
+
+/// Helper function to close the connection with a `graphql-transport-ws`
+/// close code.
+fn close_connection(
+    sink: &mpsc::UnboundedSender<WsMessage>,
+    logger: &Logger,
+    connection_id: &str,
+    code: u16,
+    reason: String,
+) -> Result<(), WsError> {
+    debug!(logger, "Closing connection";
+           "connection" => connection_id,
+           "code" => code,
+           "reason" => &reason);
+    send_ws_message(sink, close_message(code, reason))
 }
 
 /// Responsible for recording operation ids and stopping them.
@@ -119,13 +169,15 @@ fn send_error_string(
 struct Operations {
     operations: HashMap<String, CancelGuard>,
     msg_sink: mpsc::UnboundedSender<WsMessage>,
+    protocol: Protocol,
 }
 
 impl Operations {
-    fn new(msg_sink: mpsc::UnboundedSender<WsMessage>) -> Self {
+    fn new(msg_sink: mpsc::UnboundedSender<WsMessage>, protocol: Protocol) -> Self {
         Self {
             operations: HashMap::new(),
             msg_sink,
+            protocol,
         }
     }
 
@@ -147,6 +199,7 @@ impl Operations {
                 // Send a GQL_COMPLETE to indicate the operation is been completed.
                 send_message(
                     &self.msg_sink,
+                    self.protocol,
                     OutgoingMessage::Complete {
                         id: operation_id.clone(),
                     },
@@ -154,11 +207,21 @@ impl Operations {
             }
             None => send_error_string(
                 &self.msg_sink,
+                self.protocol,
                 operation_id.clone(),
                 format!("Unknown operation ID: {}", operation_id),
             ),
         }
     }
+
+    /// Stop an operation because the client completed it. With
+    /// `graphql-transport-ws`, the client does not expect a `complete` in
+    /// response, and completing an unknown operation is not an error.
+    fn complete(&mut self, operation_id: &str) {
+        if let Some(stopper) = self.operations.remove(operation_id) {
+            stopper.cancel();
+        }
+    }
 }
 
 impl Drop for Operations {
@@ -178,6 +241,8 @@ pub struct GraphQlConnection<Q, S> {
     graphql_runner: Arc<Q>,
     stream: WebSocketStream<S>,
     deployment: DeploymentHash,
+    protocol: Protocol,
+    persisted_queries: Option<Arc<dyn QueryAllowlist>>,
 }
 
 impl<Q, S> GraphQlConnection<Q, S>
@@ -191,13 +256,18 @@ where
         deployment: DeploymentHash,
         stream: WebSocketStream<S>,
         graphql_runner: Arc<Q>,
+        protocol: Protocol,
+        persisted_queries: Option<Arc<dyn QueryAllowlist>>,
     ) -> Self {
         GraphQlConnection {
             id: Uuid::new_v4().to_string(),
-            logger: logger.new(o!("component" => "GraphQlConnection")),
+            logger: logger.new(o!("component" => "GraphQlConnection",
+                                  "protocol" => protocol.name())),
             graphql_runner,
             stream,
             deployment,
+            protocol,
+            persisted_queries,
         }
     }
 
@@ -208,8 +278,10 @@ where
         connection_id: String,
         deployment: DeploymentHash,
         graphql_runner: Arc<Q>,
+        persisted_queries: Option<Arc<dyn QueryAllowlist>>,
     ) -> Result<(), WsError> {
-        let mut operations = Operations::new(msg_sink.clone());
+        let protocol = Protocol::GraphQlWs;
+        let mut operations = Operations::new(msg_sink.clone(), protocol);
 
         // Process incoming messages as long as the WebSocket is open
         while let Some(ws_msg) = ws_stream.try_next().await? {
@@ -228,7 +300,7 @@ where
 
             match msg {
                 // Always accept connection init requests
-                ConnectionInit { payload: _ } => send_message(&msg_sink, ConnectionAck),
+                ConnectionInit { payload: _ } => send_message(&msg_sink, protocol, ConnectionAck),
 
                 // When receiving a connection termination request
                 ConnectionTerminate => {
@@ -248,142 +320,323 @@ where
                     if operations.contains(&id) {
                         return send_error_string(
                             &msg_sink,
+                            protocol,
                             id.clone(),
                             format!("Operation with ID already started: {}", id),
                         );
                     }
 
-                    let max_ops = ENV_VARS.graphql.max_operations_per_connection;
-                    if operations.operations.len() >= max_ops {
-                        return send_error_string(
+                    Self::start_operation(
+                        &mut operations,
+                        &msg_sink,
+                        &logger,
+                        &connection_id,
+                        &deployment,
+                        &graphql_runner,
+                        &persisted_queries,
+                        id,
+                        payload,
+                    )
+                }
+            }?
+        }
+        Ok(())
+    }
+
+    /// Process messages of the `graphql-transport-ws` protocol. Protocol
+    /// violations close the connection with one of the close codes from
+    /// `close_code`; after that, we only wait for the client to acknowledge
+    /// the close.
+    async fn handle_incoming_transport_messages(
+        mut ws_stream: SplitStream<WebSocketStream<S>>,
+        msg_sink: mpsc::UnboundedSender<WsMessage>,
+        logger: Logger,
+        connection_id: String,
+        deployment: DeploymentHash,
+        graphql_runner: Arc<Q>,
+        persisted_queries: Option<Arc<dyn QueryAllowlist>>,
+    ) -> Result<(), WsError> {
+        let protocol = Protocol::GraphQlTransportWs;
+        let mut operations = Operations::new(msg_sink.clone(), protocol);
+
+        let init_deadline =
+            tokio::time::Instant::now() + ENV_VARS.graphql.ws_connection_init_timeout;
+        let mut acknowledged = false;
+        let mut closing = false;
+
+        loop {
+            // The client has to initialise the connection within the timeout
+            let ws_msg = if acknowledged || closing {
+                ws_stream.try_next().await?
+            } else {
+                match tokio::time::timeout_at(init_deadline, ws_stream.try_next()).await {
+                    Ok(ws_msg) => ws_msg?,
+                    Err(_) => {
+                        close_connection(
                             &msg_sink,
-                            id,
-                            format!("Reached the limit of {} operations per connection", max_ops),
-                        );
+                            &logger,
+                            &connection_id,
+                            close_code::CONNECTION_INIT_TIMEOUT,
+                            "Connection initialisation timeout".to_string(),
+                        )?;
+                        closing = true;
+                        continue;
                     }
+                }
+            };
 
-                    // Parse the GraphQL query document; respond with a GQL_ERROR if
-                    // the query is invalid
-                    let query = match q::parse_query(&payload.query) {
-                        Ok(query) => query.into_static(),
-                        Err(e) => {
-                            return send_error_string(
-                                &msg_sink,
-                                id,
-                                format!("Invalid query: {}: {}", payload.query, e),
-                            );
-                        }
-                    };
-
-                    // Parse the query variables, if present
-                    let variables = match payload.variables {
-                        None | Some(serde_json::Value::Null) => None,
-                        Some(variables @ serde_json::Value::Object(_)) => {
-                            match serde_json::from_value(variables.clone()) {
-                                Ok(variables) => Some(variables),
-                                Err(e) => {
-                                    return send_error_string(
-                                        &msg_sink,
-                                        id,
-                                        format!("Invalid variables provided: {}", e),
-                                    );
-                                }
-                            }
-                        }
-                        _ => {
-                            return send_error_string(
-                                &msg_sink,
-                                id,
-                                "Invalid variables provided (must be an object)".to_string(),
-                            );
-                        }
-                    };
-
-                    // Construct a subscription
-                    let target = QueryTarget::Deployment(deployment.clone(), Default::default());
-                    let subscription = Subscription {
-                        // Subscriptions currently do not benefit from the generational cache
-                        // anyways, so don't bother passing a network.
-                        query: Query::new(query, variables, false),
-                    };
-
-                    debug!(logger, "Start operation";
-                           "connection" => &connection_id,
-                           "id" => &id);
-
-                    // Execute the GraphQL subscription
-                    let error_sink = msg_sink.clone();
-                    let result_sink = msg_sink.clone();
-                    let result_id = id.clone();
-                    let err_id = id.clone();
-                    let err_connection_id = connection_id.clone();
-                    let err_logger = logger.clone();
-                    let run_subscription = graphql_runner
-                        .cheap_clone()
-                        .run_subscription(subscription, target)
-                        .compat()
-                        .map_err(move |e| {
-                            debug!(err_logger, "Subscription error";
-                                               "connection" => &err_connection_id,
-                                               "id" => &err_id,
-                                               "error" => format!("{:?}", e));
-
-                            // Send errors back to the client as GQL_DATA
-                            match e {
-                                SubscriptionError::GraphQLError(e) => {
-                                    // Don't bug clients with transient `TooExpensive` errors,
-                                    // simply skip updating them
-                                    if !e
-                                        .iter()
-                                        .any(|err| matches!(err, QueryExecutionError::TooExpensive))
-                                    {
-                                        let result = Arc::new(QueryResult::from(e));
-                                        let msg = OutgoingMessage::from_query_result(
-                                            err_id.clone(),
-                                            result,
-                                        );
-
-                                        // An error means the client closed the websocket, ignore
-                                        // and let it be handled in the websocket loop above.
-                                        let _ = error_sink.unbounded_send(msg.into());
-                                    }
-                                }
-                            };
-                        })
-                        .and_then(move |result_stream| {
-                            // Send results back to the client as GQL_DATA
-                            result_stream
-                                .map(move |result| {
-                                    OutgoingMessage::from_query_result(result_id.clone(), result)
-                                })
-                                .map(WsMessage::from)
-                                .map(Ok)
-                                .compat()
-                                .forward(result_sink.sink_map_err(|_| ()))
-                                .map(|_| ())
-                        });
-
-                    // Setup cancelation.
-                    let guard = CancelGuard::new();
-                    let logger = logger.clone();
-                    let cancel_id = id.clone();
-                    let connection_id = connection_id.clone();
-                    let run_subscription =
-                        run_subscription.compat().cancelable(&guard, move || {
-                            debug!(logger, "Stopped operation";
-                                       "connection" => &connection_id,
-                                       "id" => &cancel_id);
-                            Ok(())
-                        });
-                    operations.insert(id, guard);
-
-                    graph::spawn_allow_panic(run_subscription);
-                    Ok(())
+            let ws_msg = match ws_msg {
+                Some(ws_msg) => ws_msg,
+                None => break,
+            };
+
+            // Control frames are handled by the WebSocket library
+            if closing || !(ws_msg.is_text() || ws_msg.is_binary()) {
+                continue;
+            }
+
+            debug!(logger, "Received message";
+                   "connection" => &connection_id,
+                   "msg" => format!("{}", ws_msg).as_str());
+
+            let msg = match ws_msg
+                .into_text()
+                .map_err(|e| e.to_string())
+                .and_then(|text| {
+                    serde_json::from_str::<TransportIncomingMessage>(&text)
+                        .map_err(|e| format!("Invalid message received: {}", e))
+                }) {
+                Ok(msg) => msg,
+                Err(reason) => {
+                    close_connection(
+                        &msg_sink,
+                        &logger,
+                        &connection_id,
+                        close_code::BAD_REQUEST,
+                        reason,
+                    )?;
+                    closing = true;
+                    continue;
                 }
-            }?
+            };
+
+            debug!(logger, "GraphQL/WebSocket message";
+                   "connection" => &connection_id,
+                   "msg" => format!("{:?}", msg).as_str());
+
+            match msg {
+                TransportIncomingMessage::ConnectionInit { payload: _ } => {
+                    if acknowledged {
+                        close_connection(
+                            &msg_sink,
+                            &logger,
+                            &connection_id,
+                            close_code::TOO_MANY_INIT_REQUESTS,
+                            "Too many initialisation requests".to_string(),
+                        )?;
+                        closing = true;
+                    } else {
+                        acknowledged = true;
+                        send_message(&msg_sink, protocol, OutgoingMessage::ConnectionAck)?;
+                    }
+                }
+
+                TransportIncomingMessage::Ping { payload } => {
+                    send_ws_message(&msg_sink, TransportOutgoingMessage::Pong { payload }.into())?
+                }
+
+                TransportIncomingMessage::Pong { payload: _ } => {}
+
+                TransportIncomingMessage::Subscribe { id, payload } => {
+                    if !acknowledged {
+                        close_connection(
+                            &msg_sink,
+                            &logger,
+                            &connection_id,
+                            close_code::UNAUTHORIZED,
+                            "Unauthorized".to_string(),
+                        )?;
+                        closing = true;
+                    } else if operations.contains(&id) {
+                        close_connection(
+                            &msg_sink,
+                            &logger,
+                            &connection_id,
+                            close_code::SUBSCRIBER_ALREADY_EXISTS,
+                            format!("Subscriber for {} already exists", id),
+                        )?;
+                        closing = true;
+                    } else {
+                        Self::start_operation(
+                            &mut operations,
+                            &msg_sink,
+                            &logger,
+                            &connection_id,
+                            &deployment,
+                            &graphql_runner,
+                            &persisted_queries,
+                            id,
+                            payload,
+                        )?;
+                    }
+                }
+
+                TransportIncomingMessage::Complete { id } => operations.complete(&id),
+            }
         }
         Ok(())
     }
+
+    /// Start the subscription `id` and send its results to the client
+    #[allow(clippy::too_many_arguments)]
+    fn start_operation(
+        operations: &mut Operations,
+        msg_sink: &mpsc::UnboundedSender<WsMessage>,
+        logger: &Logger,
+        connection_id: &str,
+        deployment: &DeploymentHash,
+        graphql_runner: &Arc<Q>,
+        persisted_queries: &Option<Arc<dyn QueryAllowlist>>,
+        id: String,
+        payload: StartPayload,
+    ) -> Result<(), WsError> {
+        let protocol = operations.protocol;
+
+        let max_ops = ENV_VARS.graphql.max_operations_per_connection;
+        if operations.operations.len() >= max_ops {
+            return send_error_string(
+                msg_sink,
+                protocol,
+                id,
+                format!("Reached the limit of {} operations per connection", max_ops),
+            );
+        }
+
+        // Only run queries from the allowlist if there is one. Since we
+        // only accept the full query, there is nothing else to check
+        if let Some(persisted_queries) = persisted_queries {
+            if let Err(e) = persisted_queries.check(&payload.query) {
+                return send_error_string(msg_sink, protocol, id, e.message());
+            }
+        }
+
+        // Parse the GraphQL query document; respond with a GQL_ERROR if
+        // the query is invalid
+        let query = match q::parse_query(&payload.query) {
+            Ok(query) => query.into_static(),
+            Err(e) => {
+                return send_error_string(
+                    msg_sink,
+                    protocol,
+                    id,
+                    format!("Invalid query: {}: {}", payload.query, e),
+                );
+            }
+        };
+
+        // Parse the query variables, if present
+        let variables = match payload.variables {
+            None | Some(serde_json::Value::Null) => None,
+            Some(variables @ serde_json::Value::Object(_)) => {
+                match serde_json::from_value(variables.clone()) {
+                    Ok(variables) => Some(variables),
+                    Err(e) => {
+                        return send_error_string(
+                            msg_sink,
+                            protocol,
+                            id,
+                            format!("Invalid variables provided: {}", e),
+                        );
+                    }
+                }
+            }
+            _ => {
+                return send_error_string(
+                    msg_sink,
+                    protocol,
+                    id,
+                    "Invalid variables provided (must be an object)".to_string(),
+                );
+            }
+        };
+
+        // Construct a subscription
+        let target = QueryTarget::Deployment(deployment.clone(), Default::default());
+        let subscription = Subscription {
+            // Subscriptions currently do not benefit from the generational cache
+            // anyways, so don't bother passing a network.
+            query: Query::new(query, variables, false),
+        };
+
+        debug!(logger, "Start operation";
+               "connection" => connection_id,
+               "id" => &id);
+
+        // Execute the GraphQL subscription
+        let error_sink = msg_sink.clone();
+        let result_sink = msg_sink.clone();
+        let result_id = id.clone();
+        let err_id = id.clone();
+        let err_connection_id = connection_id.to_string();
+        let err_logger = logger.clone();
+        let run_subscription = graphql_runner
+            .cheap_clone()
+            .run_subscription(subscription, target)
+            .compat()
+            .map_err(move |e| {
+                debug!(err_logger, "Subscription error";
+                                   "connection" => &err_connection_id,
+                                   "id" => &err_id,
+                                   "error" => format!("{:?}", e));
+
+                // Send errors back to the client as GQL_DATA
+                match e {
+                    SubscriptionError::GraphQLError(e) => {
+                        // Don't bug clients with transient `TooExpensive` errors,
+                        // simply skip updating them
+                        if !e
+                            .iter()
+                            .any(|err| matches!(err, QueryExecutionError::TooExpensive))
+                        {
+                            let result = Arc::new(QueryResult::from(e));
+                            let msg = OutgoingMessage::from_query_result(err_id.clone(), result);
+
+                            // An error means the client closed the websocket, ignore
+                            // and let it be handled in the websocket loop above.
+                            let _ = error_sink.unbounded_send(msg.into_ws_message(protocol));
+                        }
+                    }
+                };
+            })
+            .and_then(move |result_stream| {
+                // Send results back to the client as GQL_DATA
+                result_stream
+                    .map(move |result| {
+                        OutgoingMessage::from_query_result(result_id.clone(), result)
+                    })
+                    .map(move |msg| msg.into_ws_message(protocol))
+                    .map(Ok)
+                    .compat()
+                    .forward(result_sink.sink_map_err(|_| ()))
+                    .map(|_| ())
+            });
+
+        // Setup cancelation.
+        let guard = CancelGuard::new();
+        let logger = logger.clone();
+        let cancel_id = id.clone();
+        let connection_id = connection_id.to_string();
+        let run_subscription = run_subscription.compat().cancelable(&guard, move || {
+            debug!(logger, "Stopped operation";
+                       "connection" => &connection_id,
+                       "id" => &cancel_id);
+            Ok(())
+        });
+        operations.insert(id, guard);
+
+        graph::spawn_allow_panic(run_subscription);
+        Ok(())
+    }
 }
 
 impl<Q, S> IntoFuture for GraphQlConnection<Q, S>
@@ -405,14 +658,26 @@ where
         let (msg_sink, msg_stream) = mpsc::unbounded();
 
         // Handle incoming messages asynchronously
-        let ws_reader = Self::handle_incoming_messages(
-            ws_stream,
-            msg_sink,
-            self.logger.clone(),
-            self.id.clone(),
-            self.deployment.clone(),
-            self.graphql_runner.clone(),
-        );
+        let ws_reader: BoxFuture<'static, Result<(), WsError>> = match self.protocol {
+            Protocol::GraphQlWs => Box::pin(Self::handle_incoming_messages(
+                ws_stream,
+                msg_sink,
+                self.logger.clone(),
+                self.id.clone(),
+                self.deployment.clone(),
+                self.graphql_runner.clone(),
+                self.persisted_queries.clone(),
+            )),
+            Protocol::GraphQlTransportWs => Box::pin(Self::handle_incoming_transport_messages(
+                ws_stream,
+                msg_sink,
+                self.logger.clone(),
+                self.id.clone(),
+                self.deployment.clone(),
+                self.graphql_runner.clone(),
+                self.persisted_queries.clone(),
+            )),
+        };
 
         // Send outgoing messages asynchronously
         let ws_writer = msg_stream.forward(ws_sink.compat().sink_map_err(|_| ()));
@@ -422,7 +687,7 @@ where
         // as a result of this but most will try to reconnect (GraphiQL for sure,
         // Apollo maybe).
         let ws_writer = ws_writer.map(|_| ());
-        let ws_reader = Box::pin(ws_reader.map_err(|_| ()));
+        let ws_reader = ws_reader.map_err(|_| ());
 
         // Return a future that is fulfilled when either we or the client close
         // our/their end of the WebSocket stream
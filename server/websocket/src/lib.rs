@@ -1,4 +1,5 @@
 mod connection;
+mod protocol;
 mod server;
 
 pub use self::server::SubscriptionServer;
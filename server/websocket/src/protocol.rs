@@ -0,0 +1,187 @@
+use std::borrow::Cow;
+
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+use graph::prelude::*;
+
+use crate::connection::StartPayload;
+
+/// The GraphQL over WebSocket subprotocols the server understands. The
+/// protocol is negotiated with the `Sec-WebSocket-Protocol` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Protocol {
+    /// The legacy protocol of `subscriptions-transport-ws`
+    GraphQlWs,
+    /// The protocol of the `graphql-ws` library
+    GraphQlTransportWs,
+}
+
+impl Protocol {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Protocol::GraphQlWs => "graphql-ws",
+            Protocol::GraphQlTransportWs => "graphql-transport-ws",
+        }
+    }
+
+    /// Pick the first protocol from the comma-separated list of protocols in
+    /// the client's `Sec-WebSocket-Protocol` header that we support. Clients
+    /// that do not ask for a protocol we know about get the legacy protocol
+    /// since that is all we used to support.
+    pub fn negotiate(header: Option<&str>) -> Self {
+        header
+            .into_iter()
+            .flat_map(|header| header.split(','))
+            .find_map(|name| match name.trim() {
+                "graphql-ws" => Some(Protocol::GraphQlWs),
+                "graphql-transport-ws" => Some(Protocol::GraphQlTransportWs),
+                _ => None,
+            })
+            .unwrap_or(Protocol::GraphQlWs)
+    }
+}
+
+/// Close codes that `graphql-transport-ws` uses to signal why the server
+/// closed the connection.
+pub(crate) mod close_code {
+    pub const BAD_REQUEST: u16 = 4400;
+    pub const UNAUTHORIZED: u16 = 4401;
+    pub const CONNECTION_INIT_TIMEOUT: u16 = 4408;
+    pub const SUBSCRIBER_ALREADY_EXISTS: u16 = 4409;
+    pub const TOO_MANY_INIT_REQUESTS: u16 = 4429;
+}
+
+/// Build the message that closes the connection with `code` and `reason`
+pub(crate) fn close_message(code: u16, reason: impl Into<String>) -> WsMessage {
+    WsMessage::Close(Some(CloseFrame {
+        code: CloseCode::from(code),
+        reason: Cow::Owned(reason.into()),
+    }))
+}
+
+/// `graphql-transport-ws` message received from a client.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum TransportIncomingMessage {
+    ConnectionInit {
+        #[allow(dead_code)]
+        payload: Option<serde_json::Value>,
+    },
+    Ping {
+        payload: Option<serde_json::Value>,
+    },
+    Pong {
+        #[allow(dead_code)]
+        payload: Option<serde_json::Value>,
+    },
+    Subscribe {
+        id: String,
+        payload: StartPayload,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+/// An error as it is reported in the payload of an `error` message
+#[derive(Debug, Serialize)]
+pub(crate) struct TransportError {
+    pub message: String,
+}
+
+/// `graphql-transport-ws` message to be sent to the client.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum TransportOutgoingMessage {
+    ConnectionAck,
+    Pong {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<serde_json::Value>,
+    },
+    Next {
+        id: String,
+        payload: Arc<QueryResult>,
+    },
+    Error {
+        id: String,
+        payload: Vec<TransportError>,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+impl From<TransportOutgoingMessage> for WsMessage {
+    fn from(msg: TransportOutgoingMessage) -> Self {
+        WsMessage::text(serde_json::to_string(&msg).expect("invalid GraphQL/WebSocket message"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiate_protocol() {
+        use Protocol::*;
+
+        assert_eq!(GraphQlWs, Protocol::negotiate(None));
+        assert_eq!(GraphQlWs, Protocol::negotiate(Some("graphql-ws")));
+        assert_eq!(GraphQlWs, Protocol::negotiate(Some("unknown")));
+        assert_eq!(
+            GraphQlTransportWs,
+            Protocol::negotiate(Some("graphql-transport-ws"))
+        );
+        assert_eq!(
+            GraphQlTransportWs,
+            Protocol::negotiate(Some("unknown, graphql-transport-ws, graphql-ws"))
+        );
+        assert_eq!(
+            GraphQlWs,
+            Protocol::negotiate(Some("graphql-ws,graphql-transport-ws"))
+        );
+    }
+
+    #[test]
+    fn parse_incoming_messages() {
+        let msg: TransportIncomingMessage = serde_json::from_str(
+            r#"{"type":"subscribe","id":"1","payload":{"query":"subscription { things { id } }"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            msg,
+            TransportIncomingMessage::Subscribe { id, .. } if id == "1"
+        ));
+
+        let msg: TransportIncomingMessage = serde_json::from_str(r#"{"type":"ping"}"#).unwrap();
+        assert!(matches!(
+            msg,
+            TransportIncomingMessage::Ping { payload: None }
+        ));
+
+        // `start` belongs to the legacy protocol
+        assert!(serde_json::from_str::<TransportIncomingMessage>(
+            r#"{"type":"start","id":"1","payload":{"query":"{ things { id } }"}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn serialize_outgoing_messages() {
+        let msg = TransportOutgoingMessage::Error {
+            id: "1".to_string(),
+            payload: vec![TransportError {
+                message: "boom".to_string(),
+            }],
+        };
+        assert_eq!(
+            r#"{"type":"error","id":"1","payload":[{"message":"boom"}]}"#,
+            serde_json::to_string(&msg).unwrap()
+        );
+
+        let msg = TransportOutgoingMessage::Pong { payload: None };
+        assert_eq!(r#"{"type":"pong"}"#, serde_json::to_string(&msg).unwrap());
+    }
+}
@@ -1,4 +1,6 @@
 use crate::connection::GraphQlConnection;
+use crate::protocol::Protocol;
+use graph::components::server::persisted::QueryAllowlist;
 use graph::futures01::IntoFuture as _;
 use graph::futures03::compat::Future01CompatExt;
 use graph::futures03::future::FutureExt;
@@ -12,7 +14,8 @@ use tokio::net::TcpListener;
 use tokio_tungstenite::accept_hdr_async;
 use tokio_tungstenite::tungstenite::handshake::server::Request;
 use tokio_tungstenite::tungstenite::http::{
-    header::ACCESS_CONTROL_ALLOW_ORIGIN, header::CONTENT_TYPE, HeaderValue, Response, StatusCode,
+    header::ACCESS_CONTROL_ALLOW_ORIGIN, header::CONTENT_TYPE, header::SEC_WEBSOCKET_PROTOCOL,
+    HeaderValue, Response, StatusCode,
 };
 
 /// A GraphQL subscription server based on Hyper / Websockets.
@@ -20,6 +23,7 @@ pub struct SubscriptionServer<Q, S> {
     logger: Logger,
     graphql_runner: Arc<Q>,
     store: Arc<S>,
+    persisted_queries: Option<Arc<dyn QueryAllowlist>>,
 }
 
 impl<Q, S> SubscriptionServer<Q, S>
@@ -27,11 +31,17 @@ where
     Q: GraphQlRunner,
     S: QueryStoreManager,
 {
-    pub fn new(logger: &Logger, graphql_runner: Arc<Q>, store: Arc<S>) -> Self {
+    pub fn new(
+        logger: &Logger,
+        graphql_runner: Arc<Q>,
+        store: Arc<S>,
+        persisted_queries: Option<Arc<dyn QueryAllowlist>>,
+    ) -> Self {
         SubscriptionServer {
             logger: logger.new(o!("component" => "SubscriptionServer")),
             graphql_runner,
             store,
+            persisted_queries,
         }
     }
 
@@ -125,11 +135,16 @@ where
             let logger2 = self.logger.clone();
             let graphql_runner = self.graphql_runner.clone();
             let store = self.store.clone();
+            let persisted_queries = self.persisted_queries.clone();
 
             // Subgraph that the request is resolved to (if any)
             let subgraph_id = Arc::new(Mutex::new(None));
             let accept_subgraph_id = subgraph_id.clone();
 
+            // Protocol negotiated with the client
+            let protocol = Arc::new(Mutex::new(Protocol::GraphQlWs));
+            let accept_protocol = protocol.clone();
+
             accept_hdr_async(stream, move |request: &Request, mut response: Response<()>| {
                 // Try to obtain the subgraph ID or name from the URL path.
                 // Return a 404 if the URL path contains no name/ID segment.
@@ -182,10 +197,18 @@ where
                             .unwrap());
                     }
 
+                let negotiated = Protocol::negotiate(
+                    request
+                        .headers()
+                        .get(SEC_WEBSOCKET_PROTOCOL)
+                        .and_then(|value| value.to_str().ok()),
+                );
+
                 *accept_subgraph_id.lock().unwrap() = Some(state.id);
+                *accept_protocol.lock().unwrap() = negotiated;
                 response.headers_mut().insert(
-                    "Sec-WebSocket-Protocol",
-                    HeaderValue::from_static("graphql-ws"),
+                    SEC_WEBSOCKET_PROTOCOL,
+                    HeaderValue::from_static(negotiated.name()),
                 );
                 Ok(response)
             })
@@ -194,6 +217,7 @@ where
                     Ok(ws_stream) => {
                         // Obtain the subgraph ID or name that we resolved the request to
                         let subgraph_id = subgraph_id.lock().unwrap().clone().unwrap();
+                        let protocol = *protocol.lock().unwrap();
 
                         // Spawn a GraphQL over WebSocket connection
                         let service = GraphQlConnection::new(
@@ -201,6 +225,8 @@ where
                             subgraph_id,
                             ws_stream,
                             graphql_runner.clone(),
+                            protocol,
+                            persisted_queries.clone(),
                         );
 
                         graph::spawn_allow_panic(service.into_future().compat());
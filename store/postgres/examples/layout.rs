@@ -55,6 +55,7 @@ fn print_diesel_tables(layout: &Layout) {
             ColumnType::Int => "Integer",
             ColumnType::Int8 => "Int8",
             ColumnType::Timestamp => "Timestamp",
+            ColumnType::Json => "Jsonb",
             ColumnType::String | ColumnType::Enum(_) | ColumnType::TSVector(_) => "Text",
         }
         .to_owned();
@@ -77,6 +78,7 @@ fn print_diesel_tables(layout: &Layout) {
             ColumnType::Int8 => "i64",
             ColumnType::String | ColumnType::Enum(_) | ColumnType::TSVector(_) => "String",
             ColumnType::Timestamp => "Timestamp",
+            ColumnType::Json => "serde_json::Value",
         }
         .to_owned();
 
@@ -0,0 +1,13 @@
+delete from public.graphman_command_executions
+ where kind in ('pause_deployment', 'resume_deployment', 'reassign_deployment',
+                'unassign_deployment', 'copy_deployment', 'drop_index', 'remove_subgraph');
+
+alter table public.graphman_command_executions
+    drop constraint graphman_command_executions_kind_check;
+
+alter table public.graphman_command_executions
+    add constraint graphman_command_executions_kind_check
+        check (kind in ('restart_deployment', 'rewind_deployment', 'prune_deployment', 'create_index'));
+
+alter table public.graphman_command_executions
+    drop column created_by;
@@ -0,0 +1,12 @@
+alter table public.graphman_command_executions
+    add column created_by varchar default null;
+
+alter table public.graphman_command_executions
+    drop constraint graphman_command_executions_kind_check;
+
+alter table public.graphman_command_executions
+    add constraint graphman_command_executions_kind_check
+        check (kind in ('pause_deployment', 'resume_deployment', 'restart_deployment',
+                        'reassign_deployment', 'unassign_deployment', 'rewind_deployment',
+                        'prune_deployment', 'copy_deployment', 'create_index', 'drop_index',
+                        'remove_subgraph'));
@@ -0,0 +1,18 @@
+-- remove content_hash column from data_sources$ table for each subgraph deployment
+do $$
+declare
+  deployments cursor for
+     select t.table_schema as sgd
+       from information_schema.tables t
+      where t.table_schema like 'sgd%'
+        and t.table_name = 'data_sources$'
+        and exists (select 1 from information_schema.columns c
+                     where c.table_name = t.table_name
+                       and c.table_schema = t.table_schema
+                       and c.column_name = 'content_hash');
+begin
+  for d in deployments loop
+    execute 'alter table ' || d.sgd || '.data_sources$ drop column content_hash';
+  end loop;
+end;
+$$;
@@ -342,6 +342,9 @@ mod data {
         }
     }
 
+    /// Extract the timestamp from the JSON data of a block
+    const TIMESTAMP_QUERY: &str = "coalesce(data->'block'->>'timestamp', data->>'timestamp')";
+
     impl Storage {
         const PREFIX: &'static str = "chain";
         const PUBLIC: &'static str = "public";
@@ -705,9 +708,6 @@ mod data {
             conn: &mut PgConnection,
             hash: &BlockHash,
         ) -> Result<Option<(BlockNumber, Option<u64>, Option<BlockHash>)>, StoreError> {
-            const TIMESTAMP_QUERY: &str =
-                "coalesce(data->'block'->>'timestamp', data->>'timestamp')";
-
             let number = match self {
                 Storage::Shared => {
                     use public::ethereum_blocks as b;
@@ -796,6 +796,236 @@ mod data {
             Ok(HashMap::from_iter(pairs))
         }
 
+        /// Return the block with the lowest number in `lower..=upper`
+        /// together with its timestamp, ignoring the blocks in `excluded`.
+        /// Blocks below `window` are only returned if there is no other
+        /// block with the same number in the cache
+        fn first_block_in_range(
+            &self,
+            conn: &mut PgConnection,
+            chain: &str,
+            lower: BlockNumber,
+            upper: BlockNumber,
+            window: BlockNumber,
+            excluded: &[BlockHash],
+        ) -> Result<Option<(BlockPtr, Option<u64>)>, StoreError> {
+            // Uses the index on the block number for each block it checks
+            const NO_SIBLING_QUERY: &str = "not exists (
+                select 1 from {blocks} s
+                 where {network} s.number = {outer}.number
+                   and s.hash <> {outer}.hash)";
+
+            let block = match self {
+                Storage::Shared => {
+                    use public::ethereum_blocks as b;
+
+                    let no_sibling = NO_SIBLING_QUERY
+                        .replace("{blocks}", ETHEREUM_BLOCKS_TABLE_NAME)
+                        .replace("{outer}", "ethereum_blocks")
+                        .replace(
+                            "{network}",
+                            "s.network_name = ethereum_blocks.network_name and",
+                        );
+                    let excluded: Vec<_> = excluded.iter().map(|hash| hash.hash_hex()).collect();
+                    b::table
+                        .select((b::hash, b::number, sql::<Nullable<Text>>(TIMESTAMP_QUERY)))
+                        .filter(b::network_name.eq(chain))
+                        .filter(b::number.ge(lower as i64))
+                        .filter(b::number.le(upper as i64))
+                        .filter(b::hash.ne_all(excluded))
+                        .filter(b::number.ge(window as i64).or(sql::<Bool>(&no_sibling)))
+                        .order_by((b::number, b::hash))
+                        .first::<(String, i64, Option<String>)>(conn)
+                        .optional()?
+                        .map(|(hash, number, ts)| {
+                            BlockPtr::try_from((hash.as_str(), number)).map(|ptr| (ptr, ts))
+                        })
+                        .transpose()?
+                }
+                Storage::Private(Schema { blocks, .. }) => {
+                    let no_sibling = NO_SIBLING_QUERY
+                        .replace("{blocks}", blocks.qname.as_str())
+                        .replace("{outer}", "blocks")
+                        .replace("{network}", "");
+                    let excluded: Vec<_> = excluded.iter().map(|hash| hash.as_slice()).collect();
+                    blocks
+                        .table()
+                        .select((
+                            blocks.hash(),
+                            blocks.number(),
+                            sql::<Nullable<Text>>(TIMESTAMP_QUERY),
+                        ))
+                        .filter(blocks.number().ge(lower as i64))
+                        .filter(blocks.number().le(upper as i64))
+                        .filter(blocks.hash().ne_all(excluded))
+                        .filter(
+                            blocks
+                                .number()
+                                .ge(window as i64)
+                                .or(sql::<Bool>(&no_sibling)),
+                        )
+                        .order_by((blocks.number(), blocks.hash()))
+                        .first::<(Vec<u8>, i64, Option<String>)>(conn)
+                        .optional()?
+                        .map(|(hash, number, ts)| {
+                            BlockPtr::try_from((hash.as_slice(), number)).map(|ptr| (ptr, ts))
+                        })
+                        .transpose()?
+                }
+            };
+
+            block
+                .map(|(ptr, ts)| Ok((ptr, crate::chain_store::try_parse_timestamp(ts)?)))
+                .transpose()
+        }
+
+        /// Return the hashes of the blocks in `window..=head.number` that
+        /// are not ancestors of `head`. The block cache can contain several
+        /// blocks with the same number when there were reorgs; for those
+        /// numbers, we follow parent hashes from `head` to find out which
+        /// block is on its chain. The caller makes sure that `window` is
+        /// within the reorg threshold of `head` so that the cost of this
+        /// does not grow with the length of the chain
+        fn non_canonical_blocks(
+            &self,
+            conn: &mut PgConnection,
+            chain: &str,
+            window: BlockNumber,
+            head: &BlockPtr,
+        ) -> Result<Vec<BlockHash>, StoreError> {
+            const AMBIGUOUS_QUERY: &str = "
+                select hash, number from {blocks}
+                 where {network} number in (
+                       select number from {blocks}
+                        where {network} number between $1 and $2
+                        group by number
+                       having count(*) > 1)";
+            const ANCESTORS_QUERY: &str = "
+                with recursive ancestors(block_hash, block_offset) as (
+                    values ($1, 0)
+                    union all
+                    select b.parent_hash, a.block_offset + 1
+                      from ancestors a, {blocks} b
+                     where a.block_hash = b.hash
+                       and a.block_offset < $2
+                )
+                select block_hash as hash from ancestors";
+
+            #[derive(QueryableByName)]
+            struct TextHashAndNumber {
+                #[diesel(sql_type = Text)]
+                hash: String,
+                #[diesel(sql_type = BigInt)]
+                number: i64,
+            }
+
+            #[derive(QueryableByName)]
+            struct ByteaHashAndNumber {
+                #[diesel(sql_type = Bytea)]
+                hash: Vec<u8>,
+                #[diesel(sql_type = BigInt)]
+                number: i64,
+            }
+
+            let ambiguous: Vec<(BlockHash, BlockNumber)> = match self {
+                Storage::Shared => {
+                    let query = AMBIGUOUS_QUERY
+                        .replace("{blocks}", ETHEREUM_BLOCKS_TABLE_NAME)
+                        .replace("{network}", "network_name = $3 and");
+                    sql_query(query)
+                        .bind::<BigInt, _>(window as i64)
+                        .bind::<BigInt, _>(head.number as i64)
+                        .bind::<Text, _>(chain)
+                        .load::<TextHashAndNumber>(conn)?
+                        .into_iter()
+                        .map(|block| Ok((block.hash.parse()?, block.number as BlockNumber)))
+                        .collect::<Result<_, Error>>()?
+                }
+                Storage::Private(Schema { blocks, .. }) => {
+                    let query = AMBIGUOUS_QUERY
+                        .replace("{blocks}", blocks.qname.as_str())
+                        .replace("{network}", "");
+                    sql_query(query)
+                        .bind::<BigInt, _>(window as i64)
+                        .bind::<BigInt, _>(head.number as i64)
+                        .load::<ByteaHashAndNumber>(conn)?
+                        .into_iter()
+                        .map(|block| (BlockHash::from(block.hash), block.number as BlockNumber))
+                        .collect()
+                }
+            };
+
+            let lowest = ambiguous.iter().map(|(_, number)| *number).min();
+            let ancestors: Vec<BlockHash> = match (lowest, self) {
+                (None, _) => vec![],
+                (Some(lowest), Storage::Shared) => {
+                    let query = ANCESTORS_QUERY.replace("{blocks}", ETHEREUM_BLOCKS_TABLE_NAME);
+                    sql_query(query)
+                        .bind::<Text, _>(head.hash_hex())
+                        .bind::<BigInt, _>((head.number - lowest) as i64)
+                        .load::<BlockHashText>(conn)?
+                        .into_iter()
+                        .map(|block| block.hash.parse())
+                        .collect::<Result<_, _>>()?
+                }
+                (Some(lowest), Storage::Private(Schema { blocks, .. })) => {
+                    let query = ANCESTORS_QUERY.replace("{blocks}", blocks.qname.as_str());
+                    sql_query(query)
+                        .bind::<Bytea, _>(head.hash_slice())
+                        .bind::<BigInt, _>((head.number - lowest) as i64)
+                        .load::<BlockHashBytea>(conn)?
+                        .into_iter()
+                        .map(|block| BlockHash::from(block.hash))
+                        .collect()
+                }
+            };
+
+            Ok(ambiguous
+                .into_iter()
+                .map(|(hash, _)| hash)
+                .filter(|hash| !ancestors.contains(hash))
+                .collect())
+        }
+
+        /// Find the block with the highest number in `earliest..=head.number`
+        /// that is an ancestor of `head` and whose timestamp is at or before
+        /// `timestamp`. Since timestamps increase with block numbers, we do
+        /// a binary search over the blocks in the block cache; blocks that
+        /// are not in the cache, that do not have a timestamp, or that are
+        /// not known to be on the chain that ends in `head` are never
+        /// returned
+        pub(super) fn block_for_timestamp(
+            &self,
+            conn: &mut PgConnection,
+            chain: &str,
+            timestamp: u64,
+            earliest: BlockNumber,
+            head: &BlockPtr,
+        ) -> Result<Option<BlockPtr>, StoreError> {
+            // Only blocks within the reorg threshold of `head` can be on a
+            // different chain than `head`; further back, we skip blocks that
+            // are not the only one with their number
+            let window = earliest.max(head.number - graph::env::ENV_VARS.reorg_threshold);
+            let excluded = self.non_canonical_blocks(conn, chain, window, head)?;
+
+            // The block we are looking for is always in `lower..=upper`
+            let mut lower = earliest;
+            let mut upper = head.number;
+            while lower < upper {
+                let mid = lower + (upper - lower + 1) / 2;
+                match self.first_block_in_range(conn, chain, mid, upper, window, &excluded)? {
+                    Some((ptr, Some(ts))) if ts <= timestamp => lower = ptr.number,
+                    // All blocks from `mid` to `upper` are either missing or
+                    // too late
+                    Some(_) | None => upper = mid - 1,
+                }
+            }
+            match self.first_block_in_range(conn, chain, lower, lower, window, &excluded)? {
+                Some((ptr, Some(ts))) if ts <= timestamp => Ok(Some(ptr)),
+                Some(_) | None => Ok(None),
+            }
+        }
+
         /// Find the first block that is missing from the database needed to
         /// complete the chain from block `hash` to the block with number
         /// `first_block`.
@@ -2284,6 +2514,25 @@ impl ChainStoreTrait for ChainStore {
             .await
     }
 
+    /// Find the latest block in `earliest..=latest` whose timestamp, in
+    /// seconds since the epoch, is at or before `timestamp`
+    async fn block_for_timestamp(
+        &self,
+        timestamp: u64,
+        earliest: BlockNumber,
+        head: BlockPtr,
+    ) -> Result<Option<BlockPtr>, StoreError> {
+        let storage = self.storage.clone();
+        let chain = self.chain.clone();
+        self.pool
+            .with_conn(move |conn, _| {
+                storage
+                    .block_for_timestamp(conn, &chain, timestamp, earliest, &head)
+                    .map_err(|e| e.into())
+            })
+            .await
+    }
+
     async fn clear_call_cache(&self, from: BlockNumber, to: BlockNumber) -> Result<(), Error> {
         let mut conn = self.get_conn()?;
         if let Some(head) = self.chain_head_block(&self.chain)? {
@@ -19,8 +19,8 @@ use graph::data_source::CausalityRegion;
 use graph::derive::CheapClone;
 use graph::futures03::FutureExt;
 use graph::prelude::{
-    ApiVersion, CancelHandle, CancelToken, CancelableError, EntityOperation, PoolWaitStats,
-    SubgraphDeploymentEntity,
+    ApiVersion, CancelHandle, CancelToken, CancelableError, EntityDiff, EntityOperation,
+    PoolWaitStats, SubgraphDeploymentEntity,
 };
 use graph::semver::Version;
 use graph::tokio::task::JoinHandle;
@@ -41,8 +41,8 @@ use graph::constraint_violation;
 use graph::data::subgraph::schema::{DeploymentCreate, SubgraphError};
 use graph::prelude::{
     anyhow, debug, info, o, warn, web3, AttributeNames, BlockNumber, BlockPtr, CheapClone,
-    DeploymentHash, DeploymentState, Entity, EntityQuery, Error, Logger, QueryExecutionError,
-    StopwatchMetrics, StoreError, StoreEvent, UnfailOutcome, Value, ENV_VARS,
+    DeploymentHash, DeploymentState, Entity, EntityAggregate, EntityQuery, Error, Logger,
+    QueryExecutionError, StopwatchMetrics, StoreError, StoreEvent, UnfailOutcome, Value, ENV_VARS,
 };
 use graph::schema::{ApiSchema, EntityKey, EntityType, InputSchema};
 use web3::types::Address;
@@ -55,6 +55,7 @@ use crate::primary::DeploymentId;
 use crate::relational::index::{CreateIndex, IndexList, Method};
 use crate::relational::{Layout, LayoutCache, SqlName, Table};
 use crate::relational_queries::FromEntityData;
+use crate::snapshot::{self, Snapshot, SnapshotMetadata};
 use crate::{advisory_lock, catalog, retry};
 use crate::{connection_pool::ConnectionPool, detail};
 use crate::{dynds, primary::Site};
@@ -286,6 +287,22 @@ impl DeploymentStore {
         layout.query(&logger, conn, query)
     }
 
+    pub(crate) fn execute_aggregate(
+        &self,
+        conn: &mut PgConnection,
+        site: Arc<Site>,
+        query: EntityQuery,
+        aggregates: &[EntityAggregate],
+    ) -> Result<(Vec<Value>, Trace), QueryExecutionError> {
+        let layout = self.layout(conn, site)?;
+
+        let logger = query
+            .logger
+            .cheap_clone()
+            .unwrap_or_else(|| self.logger.cheap_clone());
+        layout.aggregate(&logger, conn, query, aggregates)
+    }
+
     fn check_intf_uniqueness(
         &self,
         conn: &mut PgConnection,
@@ -1088,6 +1105,124 @@ impl DeploymentStore {
         Ok(changes)
     }
 
+    pub(crate) fn dump<E, F>(
+        &self,
+        site: Arc<Site>,
+        block: BlockNumber,
+        batch_size: usize,
+        mut f: F,
+    ) -> Result<(), E>
+    where
+        E: From<StoreError>,
+        F: FnMut(&EntityType, Vec<Entity>) -> Result<(), E>,
+    {
+        let mut conn = self.get_conn()?;
+        let layout = self.layout(&mut conn, site.cheap_clone())?;
+
+        let mut entity_types: Vec<_> = layout
+            .tables
+            .keys()
+            .filter(|entity_type| !entity_type.is_poi())
+            .cloned()
+            .collect();
+        entity_types.sort();
+
+        // Read all entity types in one transaction so that the dump is
+        // consistent even if the deployment is being indexed while we take it
+        conn.build_transaction()
+            .read_only()
+            .repeatable_read()
+            .run(|conn| -> Result<Result<(), E>, StoreError> {
+                let state = deployment::state(conn, site.deployment.clone())?;
+                if block > state.latest_block.number || block < state.earliest_block_number {
+                    return Err(StoreError::Unknown(anyhow!(
+                        "deployment {} only has entities for blocks {} to {}, can not dump block {}",
+                        site.deployment,
+                        state.earliest_block_number,
+                        state.latest_block.number,
+                        block
+                    )));
+                }
+
+                Ok(entity_types.iter().try_for_each(|entity_type| {
+                    let mut empty = true;
+                    layout.dump(conn, entity_type, block, batch_size, |entities| {
+                        empty = false;
+                        f(entity_type, entities)
+                    })?;
+                    if empty {
+                        f(entity_type, Vec::new())?;
+                    }
+                    Ok(())
+                }))
+            })?
+    }
+
+    /// Write a snapshot of the deployment into `dir`. All data is read in
+    /// one transaction so that the snapshot is consistent even if the
+    /// deployment is being indexed while we take it
+    pub(crate) fn export_snapshot(
+        &self,
+        site: Arc<Site>,
+        dir: &std::path::Path,
+    ) -> Result<SnapshotMetadata, StoreError> {
+        let mut conn = self.get_conn()?;
+        let layout = self.layout(&mut conn, site.cheap_clone())?;
+
+        conn.build_transaction()
+            .read_only()
+            .repeatable_read()
+            .run(|conn| {
+                let deployment = detail::deployment_entity(conn, &site, &layout.input_schema)?;
+                snapshot::export(conn, &layout, deployment, dir)
+            })
+    }
+
+    /// Load the data from `snapshot` into the newly created deployment
+    /// `site` and move its block pointer to the head of the snapshot
+    pub(crate) fn restore_snapshot(
+        &self,
+        site: Arc<Site>,
+        snapshot: &Snapshot,
+    ) -> Result<(), StoreError> {
+        let head = snapshot.head()?;
+
+        let mut conn = self.get_conn()?;
+        let layout = self.layout(&mut conn, site.cheap_clone())?;
+        conn.transaction(|conn| -> Result<(), StoreError> {
+            snapshot.restore(conn, &layout)?;
+
+            deployment::set_entity_count(conn, &site, &layout.count_query)?;
+            deployment::set_earliest_block(conn, &site, snapshot.metadata.earliest_block)?;
+            crate::deployment::forward_block_ptr(conn, &site.deployment, &head)?;
+
+            for entity_name in layout.tables.keys() {
+                self.analyze_with_conn(site.cheap_clone(), entity_name.as_str(), conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub(crate) fn get_diff(
+        &self,
+        site: Arc<Site>,
+        from: BlockNumber,
+        to: BlockNumber,
+        entity_types: &[String],
+        first: usize,
+        skip: usize,
+    ) -> Result<Vec<EntityDiff>, StoreError> {
+        let mut conn = self.get_conn()?;
+        let state = crate::deployment::state(&mut conn, site.deployment.clone())?;
+        for block in [from, to] {
+            state
+                .block_queryable(block)
+                .map_err(StoreError::QueryExecutionError)?;
+        }
+        let layout = self.layout(&mut conn, site)?;
+        layout.find_diff(&mut conn, from, to, entity_types, first, skip)
+    }
+
     // Only used by tests
     #[cfg(debug_assertions)]
     pub(crate) fn find(
@@ -1898,10 +2033,30 @@ pub fn generate_index_creation_sql(
 ) -> Result<(String, String), StoreError> {
     let schema_name = layout.site.namespace.clone();
     let table = resolve_table_name(&layout, &entity_name)?;
+    let (field_names, descending): (Vec<_>, Vec<_>) = field_names
+        .iter()
+        .map(|field| split_index_direction(field))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
     let (column_names, index_exprs) = resolve_column_names_and_index_exprs(table, &field_names)?;
 
-    let column_names_sep_by_underscores = column_names.join("_");
-    let index_exprs_joined = index_exprs.join(", ");
+    let column_names_sep_by_underscores = column_names
+        .iter()
+        .zip(&descending)
+        .map(|(name, desc)| match desc {
+            true => format!("{}_desc", name),
+            false => name.to_string(),
+        })
+        .join("_");
+    let index_exprs_joined = index_exprs
+        .iter()
+        .zip(&descending)
+        .map(|(expr, desc)| match desc {
+            true => format!("{} desc", expr),
+            false => expr.clone(),
+        })
+        .join(", ");
     let table_name = &table.name;
     let index_name = format!(
         "manual_{table_name}_{column_names_sep_by_underscores}{}",
@@ -1932,6 +2087,24 @@ pub fn generate_index_creation_sql(
     Ok((index_name, sql))
 }
 
+/// Splits a field for a manual index into the name of the field and
+/// whether the index should sort it in descending order. That is requested
+/// with a `:desc` suffix, e.g. `volume:desc`; a `:asc` suffix is also
+/// accepted. Indexes that match the directions of a multi-column `orderBy`
+/// can be used for sorting, even when the directions are mixed
+fn split_index_direction(field: &str) -> Result<(&str, bool), StoreError> {
+    match field.rsplit_once(':') {
+        None => Ok((field, false)),
+        Some((name, "asc")) => Ok((name, false)),
+        Some((name, "desc")) => Ok((name, true)),
+        Some((_, direction)) => Err(StoreError::Unknown(anyhow!(
+            "invalid sort direction `{}` for `{}`, it must be either `asc` or `desc`",
+            direction,
+            field
+        ))),
+    }
+}
+
 /// Resolves column names against the `table`. The `field_names` can be
 /// either GraphQL attributes or the SQL names of columns. We also accept
 /// the names `block_range` and `block$` and map that to the correct name
@@ -33,6 +33,7 @@ pub(crate) struct DataSourcesTable {
     param: DynColumn<Nullable<Binary>>,
     context: DynColumn<Nullable<Jsonb>>,
     done_at: DynColumn<Nullable<Integer>>,
+    content_hash: DynColumn<Nullable<Binary>>,
 }
 
 impl DataSourcesTable {
@@ -52,10 +53,15 @@ impl DataSourcesTable {
             param: table.column("param"),
             context: table.column("context"),
             done_at: table.column("done_at"),
+            content_hash: table.column("content_hash"),
             table,
         }
     }
 
+    pub(crate) fn qualified_name(&self) -> &str {
+        &self.qname
+    }
+
     pub(crate) fn as_ddl(&self) -> String {
         format!(
             "
@@ -68,7 +74,8 @@ impl DataSourcesTable {
                 id bytea,
                 param bytea,
                 context jsonb,
-                done_at int
+                done_at int,
+                content_hash bytea
             );
 
             create index gist_block_range_data_sources$ on {nsp}.data_sources$ using gist (block_range);
@@ -94,6 +101,7 @@ impl DataSourcesTable {
             Option<serde_json::Value>,
             CausalityRegion,
             Option<i32>,
+            Option<Vec<u8>>,
         );
         let tuples = self
             .table
@@ -106,6 +114,7 @@ impl DataSourcesTable {
                 &self.context,
                 &self.causality_region,
                 &self.done_at,
+                &self.content_hash,
             ))
             .order_by(&self.vid)
             .load::<Tuple>(conn)?;
@@ -113,7 +122,15 @@ impl DataSourcesTable {
         let mut dses: Vec<_> = tuples
             .into_iter()
             .map(
-                |(block_range, manifest_idx, param, context, causality_region, done_at)| {
+                |(
+                    block_range,
+                    manifest_idx,
+                    param,
+                    context,
+                    causality_region,
+                    done_at,
+                    content_hash,
+                )| {
                     let creation_block = match block_range.0 {
                         Bound::Included(block) => Some(block),
 
@@ -130,6 +147,7 @@ impl DataSourcesTable {
                         creation_block,
                         done_at,
                         causality_region,
+                        content_hash: content_hash.map(|hash| hash.into()),
                     }
                 },
             )
@@ -158,6 +176,7 @@ impl DataSourcesTable {
                     creation_block,
                     done_at,
                     causality_region,
+                    content_hash,
                 } = ds;
 
                 // Nested offchain data sources might not pass this check, as their `creation_block`
@@ -173,8 +192,8 @@ impl DataSourcesTable {
                 // Offchain data sources have a unique causality region assigned from a sequence in the
                 // database, while onchain data sources always have causality region 0.
                 let query = format!(
-                "insert into {}(block_range, manifest_idx, param, context, causality_region, done_at) \
-                            values (int4range($1, null), $2, $3, $4, $5, $6)",
+                "insert into {}(block_range, manifest_idx, param, context, causality_region, done_at, content_hash) \
+                            values (int4range($1, null), $2, $3, $4, $5, $6, $7)",
                 self.qname
             );
 
@@ -184,7 +203,8 @@ impl DataSourcesTable {
                     .bind::<Nullable<Binary>, _>(param.as_ref().map(|p| &**p))
                     .bind::<Nullable<Jsonb>, _>(context)
                     .bind::<Integer, _>(causality_region)
-                    .bind::<Nullable<Integer>, _>(done_at);
+                    .bind::<Nullable<Integer>, _>(done_at)
+                    .bind::<Nullable<Binary>, _>(content_hash.as_ref().map(|h| &**h));
 
                 inserted_total += query.execute(conn)?;
             }
@@ -233,6 +253,7 @@ impl DataSourcesTable {
             Option<serde_json::Value>,
             i32,
             Option<i32>,
+            Option<Vec<u8>>,
         );
 
         let src_tuples = self
@@ -248,12 +269,21 @@ impl DataSourcesTable {
                 &self.context,
                 &self.causality_region,
                 &self.done_at,
+                &self.content_hash,
             ))
             .order_by(&self.vid)
             .load::<Tuple>(conn)?;
 
         let mut count = 0;
-        for (block_range, src_manifest_idx, param, context, causality_region, done_at) in src_tuples
+        for (
+            block_range,
+            src_manifest_idx,
+            param,
+            context,
+            causality_region,
+            done_at,
+            content_hash,
+        ) in src_tuples
         {
             let name = &src_manifest_idx_and_name
                 .iter()
@@ -280,12 +310,12 @@ impl DataSourcesTable {
 
             let query = format!(
                 "\
-             insert into {dst}(block_range, manifest_idx, param, context, causality_region, done_at)
+             insert into {dst}(block_range, manifest_idx, param, context, causality_region, done_at, content_hash)
              values(case
                  when upper($2) <= $1 then $2
                  else int4range(lower($2), null)
              end,
-             $3, $4, $5, $6, $7)
+             $3, $4, $5, $6, $7, $8)
              ",
                 dst = dst.qname
             );
@@ -298,6 +328,7 @@ impl DataSourcesTable {
                 .bind::<Nullable<Jsonb>, _>(context)
                 .bind::<Integer, _>(causality_region)
                 .bind::<Nullable<Integer>, _>(done_at)
+                .bind::<Nullable<Binary>, _>(content_hash)
                 .execute(conn)?;
         }
 
@@ -323,12 +354,13 @@ impl DataSourcesTable {
         for (_, dss) in &data_sources.entries {
             for ds in dss {
                 let query = format!(
-                    "update {} set done_at = $1 where causality_region = $2",
+                    "update {} set done_at = $1, content_hash = $2 where causality_region = $3",
                     self.qname
                 );
 
                 let count = sql_query(query)
                     .bind::<Nullable<Integer>, _>(ds.done_at)
+                    .bind::<Nullable<Binary>, _>(ds.content_hash.as_ref().map(|h| &**h))
                     .bind::<Integer, _>(ds.causality_region)
                     .execute(conn)?;
 
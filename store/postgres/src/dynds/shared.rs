@@ -85,6 +85,7 @@ pub(super) fn load(
             // subgraphs that use file data sources.
             done_at: None,
             causality_region: CausalityRegion::ONCHAIN,
+            content_hash: None,
         };
 
         if data_sources.last().and_then(|d| d.creation_block) > data_source.creation_block {
@@ -123,6 +124,7 @@ pub(super) fn insert(
                     creation_block: _,
                     done_at: _,
                     causality_region,
+                    content_hash: _,
                 } = ds;
 
                 if causality_region != &CausalityRegion::ONCHAIN {
@@ -24,7 +24,7 @@ impl GraphmanStore {
 }
 
 impl graphman_store::GraphmanStore for GraphmanStore {
-    fn new_execution(&self, kind: CommandKind) -> Result<ExecutionId> {
+    fn new_execution(&self, kind: CommandKind, created_by: &str) -> Result<ExecutionId> {
         let mut conn = self.primary_pool.get()?;
 
         let id: i64 = diesel::insert_into(gce::table)
@@ -32,6 +32,7 @@ impl graphman_store::GraphmanStore for GraphmanStore {
                 gce::kind.eq(kind),
                 gce::status.eq(ExecutionStatus::Initializing),
                 gce::created_at.eq(Utc::now()),
+                gce::created_by.eq(created_by),
             ))
             .returning(gce::id)
             .get_result(&mut conn)?;
@@ -7,5 +7,6 @@ diesel::table! {
         created_at -> Timestamptz,
         updated_at -> Nullable<Timestamptz>,
         completed_at -> Nullable<Timestamptz>,
+        created_by -> Nullable<Varchar>,
     }
 }
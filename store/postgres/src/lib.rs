@@ -32,6 +32,7 @@ pub mod query_store;
 mod relational;
 mod relational_queries;
 mod retry;
+mod snapshot;
 mod store;
 mod store_events;
 mod subgraph_store;
@@ -63,6 +64,7 @@ pub use self::detail::DeploymentDetail;
 pub use self::jobs::register as register_jobs;
 pub use self::notification_listener::NotificationSender;
 pub use self::primary::{db_version, UnusedDeployment};
+pub use self::snapshot::{SnapshotBlock, SnapshotMetadata, SnapshotTable};
 pub use self::store::Store;
 pub use self::store_events::SubscriptionManager;
 pub use self::subgraph_store::{unused, DeploymentPlacer, Shard, SubgraphStore, PRIMARY_SHARD};
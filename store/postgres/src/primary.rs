@@ -591,6 +591,53 @@ mod queries {
             .collect()
     }
 
+    /// Find a page of the active sites that match the criteria of `filter`
+    /// that do not need the indexing status, ordered by their id
+    pub(super) fn find_sites_matching(
+        conn: &mut PgConnection,
+        filter: &status::DeploymentFilter,
+        first: usize,
+        skip: usize,
+    ) -> Result<Vec<Site>, StoreError> {
+        let mut query = ds::table
+            .left_outer_join(a::table.on(a::id.eq(ds::id)))
+            .filter(ds::active)
+            .select(ds::all_columns)
+            .into_boxed();
+
+        if let Some(shard) = &filter.shard {
+            query = query.filter(ds::shard.eq(shard));
+        }
+        if let Some(network) = &filter.network {
+            query = query.filter(ds::network.eq(network));
+        }
+        if let Some(node) = &filter.node {
+            query = query.filter(a::node_id.nullable().eq(node));
+        }
+        // Unassigned deployments count as not paused
+        match filter.paused {
+            Some(true) => query = query.filter(a::paused_at.nullable().is_not_null()),
+            Some(false) => query = query.filter(a::paused_at.nullable().is_null()),
+            None => {}
+        }
+        if !filter.hashes.is_empty() || !filter.namespaces.is_empty() {
+            query = query.filter(
+                ds::subgraph
+                    .eq_any(&filter.hashes)
+                    .or(ds::name.eq_any(&filter.namespaces)),
+            );
+        }
+
+        query
+            .order_by(ds::id)
+            .limit(first as i64)
+            .offset(skip as i64)
+            .load::<Schema>(conn)?
+            .into_iter()
+            .map(Site::try_from)
+            .collect()
+    }
+
     pub(super) fn find_site_in_shard(
         conn: &mut PgConnection,
         subgraph: &DeploymentHash,
@@ -2064,6 +2111,17 @@ impl Mirror {
         self.read(|conn| queries::find_sites_by_id(conn, ids))
     }
 
+    /// Find a page of the active sites that match the criteria of `filter`
+    /// that do not need the indexing status, ordered by their id
+    pub fn find_sites_matching(
+        &self,
+        filter: &status::DeploymentFilter,
+        first: usize,
+        skip: usize,
+    ) -> Result<Vec<Site>, StoreError> {
+        self.read(|conn| queries::find_sites_matching(conn, filter, first, skip))
+    }
+
     pub fn fill_assignments(&self, infos: &mut [status::Info]) -> Result<(), StoreError> {
         self.read(|conn| queries::fill_assignments(conn, infos))
     }
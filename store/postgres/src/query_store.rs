@@ -57,6 +57,26 @@ impl QueryStoreTrait for QueryStore {
             })
     }
 
+    fn aggregate_query_values(
+        &self,
+        query: EntityQuery,
+        aggregates: Vec<EntityAggregate>,
+    ) -> Result<(Vec<Value>, Trace), QueryExecutionError> {
+        assert_eq!(&self.site.deployment, &query.subgraph_id);
+        let start = Instant::now();
+        let mut conn = self
+            .store
+            .get_replica_conn(self.replica_id)
+            .map_err(|e| QueryExecutionError::StoreError(e.into()))?;
+        let wait = start.elapsed();
+        self.store
+            .execute_aggregate(&mut conn, self.site.clone(), query, &aggregates)
+            .map(|(values, mut trace)| {
+                trace.conn_wait(wait);
+                (values, trace)
+            })
+    }
+
     /// Return true if the deployment with the given id is fully synced,
     /// and return false otherwise. Errors from the store are passed back up
     async fn is_deployment_synced(&self) -> Result<bool, Error> {
@@ -112,6 +132,17 @@ impl QueryStoreTrait for QueryStore {
         self.chain_store.block_numbers(block_hashes).await
     }
 
+    async fn block_for_timestamp(
+        &self,
+        timestamp: u64,
+        earliest: BlockNumber,
+        head: BlockPtr,
+    ) -> Result<Option<BlockPtr>, StoreError> {
+        self.chain_store
+            .block_for_timestamp(timestamp, earliest, head)
+            .await
+    }
+
     fn wait_stats(&self) -> Result<PoolWaitStats, StoreError> {
         self.store.wait_stats(self.replica_id)
     }
@@ -37,7 +37,7 @@ use graph::data::graphql::TypeExt as _;
 use graph::data::query::Trace;
 use graph::data::value::Word;
 use graph::data_source::CausalityRegion;
-use graph::prelude::{q, EntityQuery, StopwatchMetrics, ENV_VARS};
+use graph::prelude::{q, EntityAggregate, EntityQuery, StopwatchMetrics, Value, ENV_VARS};
 use graph::schema::{
     EntityKey, EntityType, Field, FulltextConfig, FulltextDefinition, InputSchema,
 };
@@ -56,7 +56,8 @@ use std::time::{Duration, Instant};
 
 use crate::relational::value::{FromOidRow, OidRow};
 use crate::relational_queries::{
-    ConflictingEntitiesData, ConflictingEntitiesQuery, FindChangesQuery, FindDerivedQuery,
+    AggregateData, AggregateQuery, ConflictingEntitiesData, ConflictingEntitiesQuery,
+    DumpEntityData, DumpQuery, EntityDiffData, FindChangesQuery, FindDerivedQuery, FindDiffQuery,
     FindPossibleDeletionsQuery, ReturnedEntityData,
 };
 use crate::{
@@ -70,8 +71,8 @@ use graph::components::store::{AttributeNames, DerivedEntityQuery};
 use graph::data::store::{Id, IdList, IdType, BYTES_SCALAR};
 use graph::data::subgraph::schema::POI_TABLE;
 use graph::prelude::{
-    anyhow, info, BlockNumber, DeploymentHash, Entity, EntityChange, EntityOperation, Logger,
-    QueryExecutionError, StoreError, StoreEvent, ValueType, BLOCK_NUMBER_MAX,
+    anyhow, info, BlockNumber, DeploymentHash, Entity, EntityChange, EntityDiff, EntityOperation,
+    Logger, QueryExecutionError, StoreError, StoreEvent, ValueType, BLOCK_NUMBER_MAX,
 };
 
 use crate::block_range::{BLOCK_COLUMN, BLOCK_RANGE_COLUMN};
@@ -614,6 +615,100 @@ impl Layout {
         Ok(changes)
     }
 
+    /// Read all entities of `entity_type` that are visible at `block` in
+    /// batches of at most `batch_size` entities and pass each batch to `f`.
+    /// Entities are read in the order in which they were written
+    pub fn dump<E, F>(
+        &self,
+        conn: &mut PgConnection,
+        entity_type: &EntityType,
+        block: BlockNumber,
+        batch_size: usize,
+        mut f: F,
+    ) -> Result<(), E>
+    where
+        E: From<StoreError>,
+        F: FnMut(Vec<Entity>) -> Result<(), E>,
+    {
+        let table = self.table_for_entity(entity_type)?;
+        let mut after_vid = -1;
+        loop {
+            let rows = DumpQuery::new(table, block, after_vid, batch_size)
+                .load::<DumpEntityData>(conn)
+                .map_err(StoreError::from)?;
+            let count = rows.len();
+            let mut entities = Vec::with_capacity(count);
+            for row in rows {
+                let (vid, data) = row.into_entity_data();
+                after_vid = vid;
+                entities.push(data.deserialize_with_layout(self, None)?);
+            }
+            if !entities.is_empty() {
+                f(entities)?;
+            }
+            if count < batch_size {
+                return Ok(());
+            }
+        }
+    }
+
+    pub fn find_diff(
+        &self,
+        conn: &mut PgConnection,
+        from: BlockNumber,
+        to: BlockNumber,
+        entity_types: &[String],
+        first: usize,
+        skip: usize,
+    ) -> Result<Vec<EntityDiff>, StoreError> {
+        let mut tables = Vec::new();
+        if entity_types.is_empty() {
+            for table in self.tables.values() {
+                if table.name.as_str() != POI_TABLE {
+                    tables.push(&**table);
+                }
+            }
+        } else {
+            for name in entity_types {
+                let entity_type = self
+                    .input_schema
+                    .entity_type(name.as_str())
+                    .map_err(|_| StoreError::UnknownTable(name.clone()))?;
+                tables.push(&**self.table_for_entity(&entity_type)?);
+            }
+        }
+        if tables.is_empty() || from >= to {
+            return Ok(vec![]);
+        }
+
+        let rows =
+            FindDiffQuery::new(&tables[..], from, to, first, skip).load::<EntityDiffData>(conn)?;
+
+        let mut diffs = Vec::with_capacity(rows.len());
+        for row in rows {
+            let (old, new) = row.into_entity_data();
+            // The query guarantees that at least one of them is present
+            let entity_type = match old.as_ref().or(new.as_ref()) {
+                Some(data) => data.entity_type(&self.input_schema),
+                None => continue,
+            };
+            let old: Option<Entity> = old
+                .map(|data| data.deserialize_with_layout(self, None))
+                .transpose()?;
+            let new: Option<Entity> = new
+                .map(|data| data.deserialize_with_layout(self, None))
+                .transpose()?;
+            let key = match new.as_ref().or(old.as_ref()) {
+                Some(entity) => {
+                    entity_type.key_in(entity.id(), CausalityRegion::from_entity(entity))
+                }
+                None => continue,
+            };
+            diffs.push(EntityDiff { key, old, new });
+        }
+        Ok(diffs)
+    }
+
     pub fn insert<'a>(
         &'a self,
         conn: &mut PgConnection,
@@ -730,6 +825,7 @@ impl Layout {
             query.filter.as_ref(),
             query.order,
             query.range,
+            query.after.as_ref(),
             query.block,
             query.query_id,
             &self.site,
@@ -785,6 +881,60 @@ impl Layout {
             .map(|values| (values, trace))
     }
 
+    /// Compute `aggregates` over all entities that match `query`. The
+    /// order and range of `query` are ignored
+    pub fn aggregate(
+        &self,
+        logger: &Logger,
+        conn: &mut PgConnection,
+        query: EntityQuery,
+        aggregates: &[EntityAggregate],
+    ) -> Result<(Vec<Value>, Trace), QueryExecutionError> {
+        let filter_collection =
+            FilterCollection::new(self, query.collection, query.filter.as_ref(), query.block)?;
+        let agg_query = AggregateQuery::new(
+            &filter_collection,
+            aggregates,
+            query.block,
+            query.query_id,
+            &self.site,
+        )?;
+
+        let start = Instant::now();
+        let data = conn
+            .transaction(|conn| {
+                if let Some(ref timeout_sql) = *STATEMENT_TIMEOUT {
+                    conn.batch_execute(timeout_sql)?;
+                }
+                agg_query.get_result::<AggregateData>(conn)
+            })
+            .map_err(|e| {
+                QueryExecutionError::ResolveEntitiesError(format!(
+                    "{e}, query = {}",
+                    debug_query(&agg_query)
+                ))
+            })?;
+        let elapsed = start.elapsed();
+
+        let trace = if query.trace {
+            Trace::query(&debug_query(&agg_query).to_string(), elapsed, 1)
+        } else {
+            Trace::None
+        };
+        if ENV_VARS.log_sql_timing() {
+            info!(
+                logger,
+                "Query timing (SQL)";
+                "query" => debug_query(&agg_query).to_string().replace('\n', "\t"),
+                "time_ms" => elapsed.as_millis(),
+                "entity_count" => 1
+            );
+        }
+
+        let values = agg_query.values(data)?;
+        Ok((values, trace))
+    }
+
     pub fn update<'a>(
         &'a self,
         conn: &mut PgConnection,
@@ -1070,11 +1220,21 @@ impl Layout {
             let agg_table = tables
                 .get(&agg_type)
                 .ok_or_else(|| constraint_violation!("Table for {agg_type} is missing"))?;
+            let finer_table = match mapping.finer_agg_type(schema) {
+                Some(finer_type) => Some(
+                    tables
+                        .get(&finer_type)
+                        .ok_or_else(|| constraint_violation!("Table for {finer_type} is missing"))?
+                        .as_ref(),
+                ),
+                None => None,
+            };
             let aggregation = mapping.aggregation(schema);
             let rollup = Rollup::new(
                 mapping.interval,
                 aggregation,
                 source_table,
+                finer_table,
                 agg_table.cheap_clone(),
             )?;
             rollups.push(rollup);
@@ -1153,11 +1313,11 @@ impl Layout {
                 // `t2`, `t3`, and `t4`.
                 match buckets.first() {
                     None => {
-                        // The rollups are in increasing order of interval size, so
-                        // if a smaller interval doesn't have a bucket between
-                        // last_rollup and block_time, a larger one can't either and
-                        // we are done with this rollup.
-                        break;
+                        // There is nothing to roll up for this interval. We
+                        // still need to look at the remaining rollups since
+                        // not all intervals are nested; a month can end
+                        // without a week ending at the same time
+                        continue;
                     }
                     Some(bucket) => {
                         rollup.insert(conn, &bucket, *block)?;
@@ -1205,6 +1365,7 @@ pub enum ColumnType {
     Int8,
     Timestamp,
     String,
+    Json,
     TSVector(FulltextConfig),
     Enum(EnumType),
 }
@@ -1230,6 +1391,7 @@ impl std::fmt::Display for ColumnType {
             ColumnType::Int8 => write!(f, "Int8"),
             ColumnType::Timestamp => write!(f, "Timestamp"),
             ColumnType::String => write!(f, "String"),
+            ColumnType::Json => write!(f, "JSON"),
             ColumnType::TSVector(_) => write!(f, "TSVector"),
             ColumnType::Enum(enum_type) => write!(f, "Enum({})", enum_type.name),
         }
@@ -1286,6 +1448,7 @@ impl ColumnType {
             ValueType::Int8 => Ok(ColumnType::Int8),
             ValueType::Timestamp => Ok(ColumnType::Timestamp),
             ValueType::String => Ok(ColumnType::String),
+            ValueType::Json => Ok(ColumnType::Json),
         }
     }
 
@@ -1299,6 +1462,10 @@ impl ColumnType {
             ColumnType::Int8 => "int8",
             ColumnType::Timestamp => "timestamptz",
             ColumnType::String => "text",
+            ColumnType::Json => "jsonb",
+            // Fuzzy search uses trigrams over the plain text of the
+            // included fields
+            ColumnType::TSVector(config) if config.is_fuzzy() => "text",
             ColumnType::TSVector(_) => "tsvector",
             ColumnType::Enum(enum_type) => enum_type.name.as_str(),
         }
@@ -1441,10 +1608,18 @@ impl Column {
         matches!(self.column_type, ColumnType::Enum(_))
     }
 
+    pub fn is_json(&self) -> bool {
+        self.column_type == ColumnType::Json
+    }
+
     pub fn is_fulltext(&self) -> bool {
         self.field_type.get_base_type() == "fulltext"
     }
 
+    pub fn is_fuzzy_fulltext(&self) -> bool {
+        matches!(&self.column_type, ColumnType::TSVector(config) if config.is_fuzzy())
+    }
+
     pub fn is_reference(&self) -> bool {
         self.is_reference
     }
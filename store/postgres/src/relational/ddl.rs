@@ -248,11 +248,13 @@ impl Table {
                 // Handle other types if necessary, or maintain the unreachable statement
                 _ => unreachable!("only String and Bytes can have arbitrary size"),
             }
+        } else if column.is_fuzzy_fulltext() {
+            format!("{} gin_trgm_ops", column.name.quoted())
         } else {
             column.name.quoted()
         };
 
-        let method = if column.is_list() || column.is_fulltext() {
+        let method = if column.is_list() || column.is_fulltext() || column.is_json() {
             "gin".to_string()
         } else {
             "btree".to_string()
@@ -297,8 +299,9 @@ impl Table {
             // indexes on array attributes. Experience has shown that these
             // indexes are very expensive to update and can have a very bad
             // impact on the write performance of the database, but are
-            // hardly ever used or needed by queries.
-            if !column.is_list() || ENV_VARS.store.create_gin_indexes {
+            // hardly ever used or needed by queries. The same goes for
+            // JSON attributes, which can also only be indexed with GIN
+            if !(column.is_list() || column.is_json()) || ENV_VARS.store.create_gin_indexes {
                 write!(
                     out,
                     "create index attr_{table_index}_{column_index}_{table_name}_{column_name}\n    on {qname} using {method}({index_expr});\n",
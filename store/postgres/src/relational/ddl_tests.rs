@@ -134,6 +134,15 @@ fn test_manual_index_creation_ddl() {
         None
     );
 
+    assert_generated_sql(
+        layout.clone(),
+        "Book",
+        vec!["page_count:desc".to_string(), "title:asc".to_string()],
+        BTREE,
+        "create index concurrently if not exists manual_book_page_count_desc_title on {namespace}.book using btree (\"page_count\" desc, left(\"title\", 256))",
+        None
+    );
+
     assert_generated_sql(
         layout.clone(),
         "Book",
@@ -235,6 +244,10 @@ fn generate_ddl() {
     let sql = layout.as_ddl(None).expect("Failed to generate DDL");
     check_eqv(FULLTEXT_DDL, &sql);
 
+    let layout = test_layout(FUZZY_GQL);
+    let sql = layout.as_ddl(None).expect("Failed to generate DDL");
+    check_eqv(FUZZY_DDL, &sql);
+
     let layout = test_layout(FORWARD_ENUM_GQL);
     let sql = layout.as_ddl(None).expect("Failed to generate DDL");
     check_eqv(FORWARD_ENUM_SQL, &sql);
@@ -829,6 +842,54 @@ create index attr_2_1_habitat_most_common
 
 "#;
 
+const FUZZY_GQL: &str = r#"
+type _Schema_ @fulltext(
+    name: "search"
+    language: simple
+    algorithm: fuzzy
+    include: [
+        {
+            entity: "Token",
+            fields: [
+                {name: "name"},
+                {name: "symbol"}
+            ]
+        }
+    ]
+)
+type Token @entity  {
+    id: ID!,
+    name: String!
+    symbol: String!
+}"#;
+
+const FUZZY_DDL: &str = r#"create table "sgd0815"."token" (
+        vid                  bigserial primary key,
+        block_range          int4range not null,
+        "id"                 text not null,
+        "name"               text not null,
+        "symbol"             text not null,
+        "search"             text
+);
+alter table "sgd0815"."token"
+  add constraint token_id_block_range_excl exclude using gist (id with =, block_range with &&);
+create index brin_token
+    on "sgd0815"."token"
+ using brin(lower(block_range) int4_minmax_ops, coalesce(upper(block_range), 2147483647) int4_minmax_ops, vid int8_minmax_ops);
+create index token_block_range_closed
+    on "sgd0815"."token"(coalesce(upper(block_range), 2147483647))
+ where coalesce(upper(block_range), 2147483647) < 2147483647;
+create index attr_0_0_token_id
+    on "sgd0815"."token" using btree("id");
+create index attr_0_1_token_name
+    on "sgd0815"."token" using btree(left("name", 256));
+create index attr_0_2_token_symbol
+    on "sgd0815"."token" using btree(left("symbol", 256));
+create index attr_0_3_token_search
+    on "sgd0815"."token" using gin("search" gin_trgm_ops);
+
+"#;
+
 const FORWARD_ENUM_GQL: &str = r#"
 type Thing @entity  {
     id: ID!,
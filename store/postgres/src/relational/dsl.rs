@@ -16,7 +16,7 @@ use diesel::query_dsl::methods::SelectDsl;
 use diesel::query_source::QuerySource;
 
 use diesel::sql_types::{
-    Array, BigInt, Binary, Bool, Integer, Nullable, Numeric, SingleValue, Text, Timestamptz,
+    Array, BigInt, Binary, Bool, Integer, Jsonb, Nullable, Numeric, SingleValue, Text, Timestamptz,
     Untyped,
 };
 use diesel::{AppearsOnTable, Expression, QueryDsl, QueryResult, SelectableExpression};
@@ -350,6 +350,7 @@ impl<'a> Table<'a> {
                 ColumnType::Int8 => add_field::<BigInt>(&mut selection, self, column),
                 ColumnType::Timestamp => add_field::<Timestamptz>(&mut selection, self, column),
                 ColumnType::String => add_field::<Text>(&mut selection, self, column),
+                ColumnType::Json => add_field::<Jsonb>(&mut selection, self, column),
                 ColumnType::TSVector(_) => {
                     // Skip tsvector columns in SELECT as they are for full-text search only and not
                     // meant to be directly queried or returned
@@ -713,6 +714,10 @@ impl<'a> Column<'a> {
         self.column.is_fulltext()
     }
 
+    pub(crate) fn is_fuzzy_fulltext(&self) -> bool {
+        self.column.is_fuzzy_fulltext()
+    }
+
     pub(crate) fn column_type(&self) -> &'a ColumnType {
         &self.column.column_type
     }
@@ -53,6 +53,25 @@
 //!                    group by id, timestamp, <dimensions>)
 //!   select id, timestamp, <dimensions>, <aggregates> from combined
 //! ```
+//!
+//! When the aggregation also has a finer interval whose buckets nest
+//! inside the buckets of the interval we are rolling up, for example,
+//! hourly buckets for a daily aggregation, and all aggregate functions can
+//! be computed from partial aggregates, we aggregate the already
+//! materialized aggregations for the finer interval instead of the raw
+//! timeseries. That query has the same shape as the first one, but reads
+//! from the aggregation table for the finer interval and combines the
+//! aggregates with the functions used for combining cumulative values.
+//! Cumulative aggregates in the finer table already cover the entire
+//! timeseries up to the end of their bucket, and we therefore simply take
+//! the value from the last finer bucket:
+//!
+//! ```text
+//!   select max(id) as id, timestamp, <dimensions>, <combined aggregates> from (
+//!     select id, date_trunc(interval, timestamp), <dimensions>, <aggregates>
+//!       from <finer aggregation> where timestamp >= $start and timestamp < $end) data
+//!    group by timestamp, <dimensions>
+//! ```
 use std::collections::HashSet;
 use std::fmt;
 use std::ops::Range;
@@ -180,6 +199,20 @@ impl<'a> Agg<'a> {
                 write!(w, "arg_max_{}(({}, {time}))", sql_type, src)?
             }
             Count => write!(w, "count(*)")?,
+            Avg => write!(w, "avg({})", src)?,
+            // We use the population variants so that buckets with a single
+            // value produce `0` rather than `null`
+            Stddev => write!(w, "stddev_pop({})", src)?,
+            Variance => write!(w, "var_pop({})", src)?,
+            CountDistinct => write!(w, "count(distinct {})", src)?,
+            Percentile => {
+                let fraction = self.aggregate.fraction.unwrap_or(0.5);
+                write!(
+                    w,
+                    "percentile_cont({fraction}) within group (order by ({})::float8)",
+                    src
+                )?
+            }
         }
         write!(w, " as \"{}\"", self.agg_column.name)
     }
@@ -207,10 +240,33 @@ impl<'a> Agg<'a> {
                 return self.aggregate_over(&name, time, w);
             }
             Count => write!(w, "sum(\"{}\")", self.agg_column.name)?,
+            Avg | Stddev | Variance | CountDistinct | Percentile => {
+                // These functions can not be cumulative, and the previous
+                // value is therefore always `null`; the only non-null value
+                // is the one from the current bucket
+                write!(w, "max(\"{}\")", self.agg_column.name)?
+            }
         }
         write!(w, " as \"{}\"", self.agg_column.name)
     }
 
+    /// Generate a SQL fragment `func(agg_column) as agg_column` that
+    /// computes the aggregate from the aggregates for a finer interval.
+    /// For cumulative aggregates, we use the value from the latest finer
+    /// bucket since that already covers all earlier buckets
+    fn aggregate_finer(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        if self.aggregate.cumulative {
+            let sql_type = self.agg_column.column_type.sql_type();
+            write!(
+                w,
+                "arg_max_{}((\"{}\", id)) as \"{}\"",
+                sql_type, self.agg_column.name, self.agg_column.name
+            )
+        } else {
+            self.combine("id", w)
+        }
+    }
+
     /// Generate a SQL fragment that computes that selects the previous
     /// value from an aggregation when the aggregation is cumulative and
     /// `null` when it is not
@@ -237,10 +293,16 @@ pub(crate) struct Rollup {
 }
 
 impl Rollup {
+    /// Create the rollup for `interval`. If `finer_table` is given, it
+    /// must be the aggregation table for an interval whose buckets nest
+    /// inside the buckets for `interval`; we compute the rollup from that
+    /// table if all the aggregates can be computed from partial aggregates
+    /// and from the raw timeseries in `src_table` otherwise
     pub(crate) fn new(
         interval: AggregationInterval,
         aggregation: &Aggregation,
         src_table: &Table,
+        finer_table: Option<&Table>,
         agg_table: Arc<Table>,
     ) -> Result<Self, StoreError> {
         let dimensions: Box<[_]> = aggregation
@@ -252,9 +314,17 @@ impl Rollup {
             .iter()
             .map(|aggregate| Agg::new(aggregate, src_table, &agg_table))
             .collect::<Result<_, _>>()?;
+        let finer_table = finer_table
+            .filter(|_| {
+                aggregates
+                    .iter()
+                    .all(|agg| agg.aggregate.func.is_decomposable())
+            })
+            .map(|finer_table| &finer_table.qualified_name);
         let sql = RollupSql::new(
             interval,
             &src_table.qualified_name,
+            finer_table,
             &agg_table,
             &dimensions,
             &aggregates,
@@ -313,6 +383,9 @@ impl Rollup {
 struct RollupSql<'a> {
     interval: AggregationInterval,
     src_table: &'a SqlName,
+    /// The aggregation table for a finer interval that we should roll up
+    /// from instead of `src_table`
+    finer_table: Option<&'a SqlName>,
     agg_table: &'a Table,
     dimensions: &'a [&'a Column],
     aggregates: &'a [Agg<'a>],
@@ -322,6 +395,7 @@ impl<'a> RollupSql<'a> {
     fn new(
         interval: AggregationInterval,
         src_table: &'a SqlName,
+        finer_table: Option<&'a SqlName>,
         agg_table: &'a Table,
         dimensions: &'a [&Column],
         aggregates: &'a [Agg],
@@ -329,6 +403,7 @@ impl<'a> RollupSql<'a> {
         Self {
             interval,
             src_table,
+            finer_table,
             agg_table,
             dimensions,
             aggregates,
@@ -371,10 +446,10 @@ impl<'a> RollupSql<'a> {
         }
         write_dims(self.dimensions, w)?;
         comma_sep(self.aggregates, w, |w, agg| agg.aggregate("id", w))?;
-        let secs = self.interval.as_duration().as_secs();
         write!(
             w,
-            " from (select id, date_bin('{secs}s', timestamp, 'epoch'::timestamptz) as timestamp"
+            " from (select id, {} as timestamp",
+            bucket_start(self.interval, "timestamp")
         )?;
         write_dims(self.dimensions, w)?;
         let agg_srcs: Vec<&str> = {
@@ -505,8 +580,48 @@ impl<'a> RollupSql<'a> {
         write!(w, " from combined")
     }
 
+    /// Generate a query that rolls up the aggregations for a finer
+    /// interval into an aggregation over one time window
+    ///
+    /// insert into <aggregation>(id, timestamp, block$, <dimensions>,
+    /// <aggregates>)
+    /// select max(id) as id, timestamp, $3, <dimensions>,
+    ///        <combined aggregates>
+    ///   from (select id, rounded_timestamp, <dimensions>, <aggregates>
+    ///           from <finer aggregation>
+    ///          where timestamp >= $start
+    ///            and timestamp < $end
+    ///          order by timestamp) data
+    ///  group by timestamp, <dimensions>
+    fn insert_finer(&self, finer_table: &SqlName, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.insert_into(w)?;
+        write!(w, "select max(id) as id, timestamp, $3")?;
+        write_dims(self.dimensions, w)?;
+        comma_sep(self.aggregates, w, |w, agg| agg.aggregate_finer(w))?;
+        write!(
+            w,
+            " from (select id, {} as timestamp",
+            bucket_start(self.interval, "timestamp")
+        )?;
+        write_dims(self.dimensions, w)?;
+        comma_sep(self.aggregates, w, |w, agg| {
+            write!(w, "\"{}\"", agg.agg_column.name)
+        })?;
+        write!(
+            w,
+            " from {finer_table} where {finer_table}.timestamp >= $1 and {finer_table}.timestamp < $2"
+        )?;
+        write!(
+            w,
+            " order by {finer_table}.timestamp) data group by timestamp"
+        )?;
+        write_dims(self.dimensions, w)
+    }
+
     fn insert(&self, w: &mut dyn fmt::Write) -> fmt::Result {
-        if self.has_cumulative_aggregates() {
+        if let Some(finer_table) = self.finer_table {
+            self.insert_finer(finer_table, w)
+        } else if self.has_cumulative_aggregates() {
             self.insert_cumulative(w)
         } else {
             self.insert_bucket(w)
@@ -519,14 +634,39 @@ impl<'a> RollupSql<'a> {
         // last bucket. The last rollup was therefore at least
         // `self.interval` after that. We add 1 second to make sure we are
         // well within the next bucket
-        let secs = self.interval.as_duration().as_secs() + 1;
+        let length = match self.interval.as_duration() {
+            Some(length) => format!("{} s", length.as_secs() + 1),
+            None => "1 month 1 s".to_string(),
+        };
         format!(
-            "select max(timestamp) + '{} s'::interval as last_rollup from {}",
-            secs, self.agg_table.qualified_name
+            "select max(timestamp) + '{}'::interval as last_rollup from {}",
+            length, self.agg_table.qualified_name
         )
     }
 }
 
+/// Return a SQL expression that rounds the `timestamptz` column `column`
+/// down to the start of the bucket for `interval` that contains it. This
+/// must produce the same bucket boundaries as
+/// `AggregationInterval::bucket_start`
+fn bucket_start(interval: AggregationInterval, column: &str) -> String {
+    use AggregationInterval::*;
+
+    match interval {
+        Minute | FiveMinutes | FifteenMinutes | Hour | Day => {
+            let secs = interval.as_duration().unwrap().as_secs();
+            format!("date_bin('{secs}s', {column}, 'epoch'::timestamptz)")
+        }
+        Week => {
+            // Weeks start on Monday, and 1970-01-05 was the first Monday
+            // after the epoch
+            let secs = interval.as_duration().unwrap().as_secs();
+            format!("date_bin('{secs}s', {column}, '1970-01-05T00:00:00Z'::timestamptz)")
+        }
+        Month => format!("date_trunc('month', {column}, 'UTC')"),
+    }
+}
+
 /// Write the elements in `list` separated by commas into `w`. The list
 /// elements are written by calling `out` with each of them.
 fn comma_sep<T, F>(list: impl IntoIterator<Item = T>, w: &mut dyn fmt::Write, out: F) -> fmt::Result
@@ -624,6 +764,38 @@ mod tests {
         timestamp: Timestamp!
         count: Int8! @aggregate(fn: "count")
       }
+
+      type Volatility @aggregation(intervals: ["day"], source: "Data") {
+        id: Int8!
+        timestamp: Timestamp!
+        avg: BigDecimal! @aggregate(fn: "avg", arg: "price")
+        stddev: BigDecimal! @aggregate(fn: "stddev", arg: "price")
+        variance: BigDecimal! @aggregate(fn: "variance", arg: "price")
+        tokens: Int8! @aggregate(fn: "countDistinct", arg: "token")
+        median: BigDecimal! @aggregate(fn: "percentile", arg: "price", fraction: 0.5)
+      }
+
+      type Periodic @aggregation(intervals: ["week", "month"], source: "Data") {
+        id: Int8!
+        timestamp: Timestamp!
+        sum: BigDecimal! @aggregate(fn: "sum", arg: "price")
+      }
+
+      type Candles @aggregation(intervals: ["hour", "day"], source: "Data") {
+        id: Int8!
+        timestamp: Timestamp!
+        token: Bytes!
+        open: BigDecimal! @aggregate(fn: "first", arg: "price")
+        close: BigDecimal! @aggregate(fn: "last", arg: "price")
+        count: Int8! @aggregate(fn: "count")
+        total: Int8! @aggregate(fn: "count", cumulative: true)
+      }
+
+      type AvgStats @aggregation(intervals: ["hour", "day"], source: "Data") {
+        id: Int8!
+        timestamp: Timestamp!
+        avg: BigDecimal! @aggregate(fn: "avg", arg: "price")
+      }
       "#;
 
         const STATS_HOUR_SQL: &str = r#"\
@@ -637,11 +809,11 @@ mod tests {
 
         const STATS_DAY_SQL: &str = r#"\
         insert into "sgd007"."stats_day"(id, timestamp, block$, "token", "sum", "max") \
-        select max(id) as id, timestamp, $3, "token", sum("price") as "sum", max("amount") as "max" from (\
-            select id, date_bin('86400s', timestamp, 'epoch'::timestamptz) as timestamp, "token", "amount", "price" \
-              from "sgd007"."data" \
-             where "sgd007"."data".timestamp >= $1 and "sgd007"."data".timestamp < $2 \
-             order by "sgd007"."data".timestamp) data \
+        select max(id) as id, timestamp, $3, "token", sum("sum") as "sum", max("max") as "max" from (\
+            select id, date_bin('86400s', timestamp, 'epoch'::timestamptz) as timestamp, "token", "sum", "max" \
+              from "sgd007"."stats_hour" \
+             where "sgd007"."stats_hour".timestamp >= $1 and "sgd007"."stats_hour".timestamp < $2 \
+             order by "sgd007"."stats_hour".timestamp) data \
         group by timestamp, "token""#;
 
         const TOTAL_SQL: &str = r#"\
@@ -704,6 +876,59 @@ mod tests {
              order by "sgd007"."data".timestamp) data \
         group by timestamp"#;
 
+        const VOLATILITY_SQL: &str = r#"\
+        insert into "sgd007"."volatility_day"(id, timestamp, block$, "avg", "stddev", "variance", "tokens", "median") \
+        select max(id) as id, timestamp, $3, avg("price") as "avg", \
+               stddev_pop("price") as "stddev", var_pop("price") as "variance", \
+               count(distinct "token") as "tokens", \
+               percentile_cont(0.5) within group (order by ("price")::float8) as "median" \
+          from (select id, date_bin('86400s', timestamp, 'epoch'::timestamptz) as timestamp, "price", "token" \
+                  from "sgd007"."data" \
+                 where "sgd007"."data".timestamp >= $1 and "sgd007"."data".timestamp < $2 \
+                 order by "sgd007"."data".timestamp) data \
+        group by timestamp"#;
+
+        const PERIODIC_WEEK_SQL: &str = r#"\
+        insert into "sgd007"."periodic_week"(id, timestamp, block$, "sum") \
+        select max(id) as id, timestamp, $3, sum("price") as "sum" \
+          from (select id, date_bin('604800s', timestamp, '1970-01-05T00:00:00Z'::timestamptz) as timestamp, "price" \
+                  from "sgd007"."data" \
+                 where "sgd007"."data".timestamp >= $1 and "sgd007"."data".timestamp < $2 \
+                 order by "sgd007"."data".timestamp) data \
+        group by timestamp"#;
+
+        const PERIODIC_MONTH_SQL: &str = r#"\
+        insert into "sgd007"."periodic_month"(id, timestamp, block$, "sum") \
+        select max(id) as id, timestamp, $3, sum("price") as "sum" \
+          from (select id, date_trunc('month', timestamp, 'UTC') as timestamp, "price" \
+                  from "sgd007"."data" \
+                 where "sgd007"."data".timestamp >= $1 and "sgd007"."data".timestamp < $2 \
+                 order by "sgd007"."data".timestamp) data \
+        group by timestamp"#;
+
+        const CANDLES_DAY_SQL: &str = r#"\
+        insert into "sgd007"."candles_day"(id, timestamp, block$, "token", "open", "close", "count", "total") \
+        select max(id) as id, timestamp, $3, "token", \
+               arg_min_numeric(("open", id)) as "open", \
+               arg_max_numeric(("close", id)) as "close", \
+               sum("count") as "count", \
+               arg_max_int8(("total", id)) as "total" \
+          from (select id, date_bin('86400s', timestamp, 'epoch'::timestamptz) as timestamp, \
+                       "token", "open", "close", "count", "total" \
+                  from "sgd007"."candles_hour" \
+                 where "sgd007"."candles_hour".timestamp >= $1 and "sgd007"."candles_hour".timestamp < $2 \
+                 order by "sgd007"."candles_hour".timestamp) data \
+        group by timestamp, "token""#;
+
+        const AVG_STATS_DAY_SQL: &str = r#"\
+        insert into "sgd007"."avg_stats_day"(id, timestamp, block$, "avg") \
+        select max(id) as id, timestamp, $3, avg("price") as "avg" \
+          from (select id, date_bin('86400s', timestamp, 'epoch'::timestamptz) as timestamp, "price" \
+                  from "sgd007"."data" \
+                 where "sgd007"."data".timestamp >= $1 and "sgd007"."data".timestamp < $2 \
+                 order by "sgd007"."data".timestamp) data \
+        group by timestamp"#;
+
         #[track_caller]
         fn rollup_for<'a>(layout: &'a Layout, table_name: &str) -> &'a Rollup {
             layout
@@ -719,7 +944,7 @@ mod tests {
         let site = Arc::new(make_dummy_site(hash, nsp, "rollup".to_string()));
         let catalog = Catalog::for_tests(site.clone(), BTreeSet::new()).unwrap();
         let layout = Layout::new(site, &schema, catalog).unwrap();
-        assert_eq!(6, layout.rollups.len());
+        assert_eq!(13, layout.rollups.len());
 
         // Intervals are non-decreasing
         assert!(layout.rollups[0].interval <= layout.rollups[1].interval);
@@ -741,5 +966,25 @@ mod tests {
 
         let count_only = rollup_for(&layout, "count_only_day");
         check_eqv(COUNT_ONLY_SQL, &count_only.insert_sql);
+
+        let volatility = rollup_for(&layout, "volatility_day");
+        check_eqv(VOLATILITY_SQL, &volatility.insert_sql);
+
+        let periodic_week = rollup_for(&layout, "periodic_week");
+        check_eqv(PERIODIC_WEEK_SQL, &periodic_week.insert_sql);
+        let periodic_month = rollup_for(&layout, "periodic_month");
+        check_eqv(PERIODIC_MONTH_SQL, &periodic_month.insert_sql);
+        check_eqv(
+            r#"select max(timestamp) + '1 month 1 s'::interval as last_rollup
+                 from "sgd007"."periodic_month""#,
+            &periodic_month.last_rollup_sql,
+        );
+
+        // Daily candles are rolled up from the hourly candles, but the
+        // daily average has to be computed from the raw data
+        let candles_day = rollup_for(&layout, "candles_day");
+        check_eqv(CANDLES_DAY_SQL, &candles_day.insert_sql);
+        let avg_stats_day = rollup_for(&layout, "avg_stats_day");
+        check_eqv(AVG_STATS_DAY_SQL, &avg_stats_day.insert_sql);
     }
 }
@@ -2,7 +2,7 @@
 
 use std::num::NonZeroU32;
 
-use diesel::sql_types::{Array, BigInt, Binary, Bool, Integer, Numeric, Text, Timestamptz};
+use diesel::sql_types::{Array, BigInt, Binary, Bool, Integer, Jsonb, Numeric, Text, Timestamptz};
 use diesel::{deserialize::FromSql, pg::Pg};
 use diesel_dynamic_schema::dynamic_value::{Any, DynamicRow};
 
@@ -45,6 +45,7 @@ pub enum OidValue {
     BigDecimalArray(Vec<BigDecimal>),
     Timestamp(Timestamp),
     TimestampArray(Vec<Timestamp>),
+    Json(serde_json::Value),
     Null,
 }
 
@@ -66,6 +67,7 @@ impl FromSql<Any, Pg> for OidValue {
         const NUMERIC_ARY_OID: NonZeroU32 = unsafe { NonZeroU32::new_unchecked(1231) };
         const TIMESTAMPTZ_OID: NonZeroU32 = unsafe { NonZeroU32::new_unchecked(1184) };
         const TIMESTAMPTZ_ARY_OID: NonZeroU32 = unsafe { NonZeroU32::new_unchecked(1185) };
+        const JSONB_OID: NonZeroU32 = unsafe { NonZeroU32::new_unchecked(3802) };
 
         match value.get_oid() {
             VARCHAR_OID | TEXT_OID => {
@@ -102,6 +104,9 @@ impl FromSql<Any, Pg> for OidValue {
                 <Vec<Timestamp> as FromSql<Array<Timestamptz>, Pg>>::from_sql(value)
                     .map(OidValue::TimestampArray)
             }
+            JSONB_OID => {
+                <serde_json::Value as FromSql<Jsonb, Pg>>::from_sql(value).map(OidValue::Json)
+            }
             e => Err(format!("Unknown type: {e}").into()),
         }
     }
@@ -143,6 +148,7 @@ impl FromOidValue for r::Value {
             O::BigDecimalArray(b) => as_list(b, |b| Self::String(b.to_string())),
             O::Timestamp(t) => Self::Timestamp(t),
             O::TimestampArray(t) => as_list(t, Self::Timestamp),
+            O::Json(json) => Self::from(json),
             O::Null => Self::Null,
         };
         Ok(value)
@@ -195,6 +201,7 @@ impl FromOidValue for graph::prelude::Value {
             },
             O::Timestamp(t) => Self::Timestamp(t),
             O::TimestampArray(t) => as_list(t, Self::Timestamp),
+            O::Json(json) => Self::from_json(json),
             O::Null => Self::Null,
         };
         Ok(value)
@@ -10,20 +10,25 @@ use diesel::query_builder::{AstPass, Query, QueryFragment, QueryId};
 use diesel::query_dsl::RunQueryDsl;
 use diesel::result::{Error as DieselError, QueryResult};
 use diesel::sql_types::Untyped;
-use diesel::sql_types::{Array, BigInt, Binary, Bool, Int8, Integer, Jsonb, Text, Timestamptz};
+use diesel::sql_types::{
+    Array, BigInt, Binary, Bool, Double, Int8, Integer, Jsonb, Nullable, Text, Timestamptz,
+};
 use diesel::QuerySource as _;
 use graph::components::store::write::{EntityWrite, RowGroup, WriteChunk};
-use graph::components::store::{Child as StoreChild, DerivedEntityQuery};
-use graph::data::store::{Id, IdType, NULL};
+use graph::components::store::{Child as StoreChild, DerivedEntityQuery, OrderDirection};
+use graph::data::store::{Id, IdType, ValueType, NULL};
 use graph::data::store::{IdList, IdRef, QueryObject};
 use graph::data::value::{Object, Word};
 use graph::data_source::CausalityRegion;
 use graph::prelude::{
-    anyhow, r, serde_json, BlockNumber, ChildMultiplicity, Entity, EntityCollection, EntityFilter,
-    EntityLink, EntityOrder, EntityOrderByChild, EntityOrderByChildInfo, EntityRange, EntityWindow,
-    ParentLink, QueryExecutionError, StoreError, Value, ENV_VARS,
+    anyhow, r, serde_json, BlockNumber, ChildMultiplicity, Entity, EntityAggregate,
+    EntityCollection, EntityCursor, EntityFilter, EntityLink, EntityOrder, EntityOrderByChild,
+    EntityOrderByChildInfo, EntityRange, EntityWindow, ParentLink, QueryExecutionError, StoreError,
+    Value, ENV_VARS,
+};
+use graph::schema::{
+    EntityType, FulltextAlgorithm, FulltextConfig, InputSchema, FUZZY_DEFAULT_THRESHOLD,
 };
-use graph::schema::{EntityType, FulltextAlgorithm, FulltextConfig, InputSchema};
 use graph::{components::store::AttributeNames, data::store::scalar};
 use inflector::Inflector;
 use itertools::Itertools;
@@ -37,7 +42,7 @@ use std::string::ToString;
 use crate::relational::dsl::AtBlock;
 use crate::relational::{
     dsl, Column, ColumnType, Layout, SqlName, Table, BYTE_ARRAY_PREFIX_SIZE, PRIMARY_KEY_COLUMN,
-    STRING_PREFIX_SIZE,
+    STRING_PREFIX_SIZE, VID_COLUMN,
 };
 use crate::{
     block_range::{
@@ -214,6 +219,8 @@ pub trait FromColumnValue: Sized + std::fmt::Debug {
 
     fn from_vec(v: Vec<Self>) -> Self;
 
+    fn from_json(json: serde_json::Value) -> Self;
+
     fn from_column_value(
         column_type: &ColumnType,
         json: serde_json::Value,
@@ -224,6 +231,7 @@ pub trait FromColumnValue: Sized + std::fmt::Debug {
         // a column that is actually nullable
         match (json, column_type) {
             (j::Null, _) => Ok(Self::null()),
+            (json, ColumnType::Json) => Ok(Self::from_json(json)),
             (j::Bool(b), _) => Ok(Self::from_bool(b)),
             (j::Number(number), ColumnType::Int) => match number.as_i64() {
                 Some(i) => i32::try_from(i).map(Self::from_i32).map_err(|e| {
@@ -337,6 +345,10 @@ impl FromColumnValue for r::Value {
     fn from_vec(v: Vec<Self>) -> Self {
         r::Value::List(v)
     }
+
+    fn from_json(json: serde_json::Value) -> Self {
+        r::Value::from(json)
+    }
 }
 
 impl FromColumnValue for graph::prelude::Value {
@@ -391,6 +403,10 @@ impl FromColumnValue for graph::prelude::Value {
     fn from_vec(v: Vec<Self>) -> Self {
         graph::prelude::Value::List(v)
     }
+
+    fn from_json(json: serde_json::Value) -> Self {
+        graph::prelude::Value::from_json(json)
+    }
 }
 
 /// A [`diesel`] utility `struct` for fetching only [`EntityType`] and entity's
@@ -419,6 +435,53 @@ impl EntityDeletion {
     }
 }
 
+/// An entity as returned by [`DumpQuery`] together with its `vid`
+#[derive(QueryableByName)]
+pub struct DumpEntityData {
+    #[diesel(sql_type = BigInt)]
+    vid: i64,
+    #[diesel(sql_type = Text)]
+    entity: String,
+    #[diesel(sql_type = Jsonb)]
+    data: serde_json::Value,
+}
+
+impl DumpEntityData {
+    pub fn into_entity_data(self) -> (i64, EntityData) {
+        let data = EntityData {
+            entity: self.entity,
+            data: self.data,
+        };
+        (self.vid, data)
+    }
+}
+
+/// The versions of an entity at two blocks as returned by
+/// [`FindDiffQuery`]; at most one of them is missing
+#[derive(QueryableByName)]
+pub struct EntityDiffData {
+    #[diesel(sql_type = Text)]
+    entity: String,
+    #[diesel(sql_type = Nullable<Jsonb>)]
+    old_data: Option<serde_json::Value>,
+    #[diesel(sql_type = Nullable<Jsonb>)]
+    new_data: Option<serde_json::Value>,
+}
+
+impl EntityDiffData {
+    pub fn into_entity_data(self) -> (Option<EntityData>, Option<EntityData>) {
+        let old = self.old_data.map(|data| EntityData {
+            entity: self.entity.clone(),
+            data,
+        });
+        let new = self.new_data.map(|data| EntityData {
+            entity: self.entity,
+            data,
+        });
+        (old, new)
+    }
+}
+
 pub fn parse_id(id_type: IdType, json: serde_json::Value) -> Result<Id, StoreError> {
     const HEX_PREFIX: &str = "\\x";
     if let serde_json::Value::String(s) = json {
@@ -545,6 +608,7 @@ enum SqlValue<'a> {
     Null,
     Bytes(&'a scalar::Bytes),
     Binary(scalar::Bytes),
+    Json(&'a serde_json::Value),
 }
 
 impl<'a> SqlValue<'a> {
@@ -585,6 +649,7 @@ impl<'a> SqlValue<'a> {
                     ColumnType::Int8|
                     ColumnType::String|
                     ColumnType::Timestamp|
+                    ColumnType::Json|
                     ColumnType::Enum(_)|
                     ColumnType::TSVector(_) => {
                         S::List(values)
@@ -598,6 +663,7 @@ impl<'a> SqlValue<'a> {
             BigInt(i) => {
                 S::Numeric(i.to_string())
             }
+            Json(json) => S::Json(json),
         };
         Ok(value)
     }
@@ -619,13 +685,14 @@ impl std::fmt::Display for SqlValue<'_> {
             S::Null => write!(f, "null"),
             S::Bytes(b) => write!(f, "{}", b),
             S::Binary(b) => write!(f, "{}", b),
+            S::Json(json) => write!(f, "{}", json),
         }
     }
 }
 
 /// A `QueryValue` makes it possible to bind a `Value` into a SQL query
 /// using the metadata from Column
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct QueryValue<'a> {
     value: SqlValue<'a>,
     column_type: &'a ColumnType,
@@ -673,6 +740,9 @@ impl<'a> QueryFragment<Pg> for QueryValue<'a> {
                     out.push_sql(enum_type.name.as_str());
                     Ok(())
                 }
+                ColumnType::TSVector(config) if config.is_fuzzy() => {
+                    out.push_bind_param::<Text, _>(s)
+                }
                 ColumnType::TSVector(_) => {
                     out.push_sql("to_tsquery(");
                     out.push_bind_param::<Text, _>(s)?;
@@ -718,7 +788,7 @@ impl<'a> QueryFragment<Pg> for QueryValue<'a> {
                     }
                     // TSVector will only be in a Value::List() for inserts so "to_tsvector" can always be used here
                     ColumnType::TSVector(config) => {
-                        process_vec_ast(values, &mut out, config.language.as_sql())?;
+                        process_vec_ast(values, &mut out, config)?;
                         Ok(())
                     }
                     ColumnType::BigDecimal | ColumnType::BigInt => {
@@ -726,6 +796,9 @@ impl<'a> QueryFragment<Pg> for QueryValue<'a> {
                             "BigDecimal and BigInt use SqlValue::Numerics instead of List"
                         );
                     }
+                    ColumnType::Json => {
+                        unreachable!("JSON attributes can not be lists")
+                    }
                 }
             }
             S::Numerics(values) => {
@@ -739,6 +812,7 @@ impl<'a> QueryFragment<Pg> for QueryValue<'a> {
             }
             S::Bytes(b) => out.push_bind_param::<Binary, _>(b.as_slice()),
             S::Binary(b) => out.push_bind_param::<Binary, _>(b.as_slice()),
+            S::Json(json) => out.push_bind_param::<Jsonb, _>(*json),
         }
     }
 }
@@ -746,8 +820,20 @@ impl<'a> QueryFragment<Pg> for QueryValue<'a> {
 fn process_vec_ast<'a, T: diesel::serialize::ToSql<Text, Pg>>(
     values: &'a Vec<T>,
     out: &mut AstPass<'_, 'a, Pg>,
-    sql_language: &str,
+    config: &FulltextConfig,
 ) -> Result<(), DieselError> {
+    if config.is_fuzzy() {
+        // Fuzzy search works on the text of all fields, separated by spaces
+        out.push_sql("concat_ws(' '");
+        for value in values {
+            out.push_sql(", ");
+            out.push_bind_param::<Text, _>(value)?;
+        }
+        out.push_sql(")");
+        return Ok(());
+    }
+
+    let sql_language = config.language.as_sql();
     if values.is_empty() {
         out.push_sql("''::tsvector");
     } else {
@@ -816,7 +902,7 @@ impl Comparison {
                 | Comparison::LessOrEqual
                 | Comparison::GreaterOrEqual
                 | Comparison::Greater,
-                Value::Bool(_) | Value::List(_) | Value::Null,
+                Value::Bool(_) | Value::List(_) | Value::Json(_) | Value::Null,
             )
             | (Comparison::Match, _) => {
                 return Err(StoreError::UnsupportedFilter(
@@ -1281,6 +1367,22 @@ pub enum Filter<'a> {
     Child(Box<QueryChild<'a>>),
     /// The value is never null for fulltext queries
     Fulltext(dsl::Column<'a>, QueryValue<'a>),
+    /// Fuzzy fulltext search; the word similarity between the value and
+    /// the column must be at least `threshold`
+    FuzzyFulltext {
+        column: dsl::Column<'a>,
+        value: QueryValue<'a>,
+        threshold: f64,
+    },
+    /// Compare the value at `path` in a JSON column with `value`, either
+    /// for equality or containment. A missing `value` checks that there
+    /// is no value at `path`
+    JsonPath {
+        column: dsl::Column<'a>,
+        path: &'a Vec<String>,
+        contains: bool,
+        value: Option<&'a serde_json::Value>,
+    },
 }
 
 impl<'a> Filter<'a> {
@@ -1341,6 +1443,7 @@ impl<'a> Filter<'a> {
                 | Value::Int(_)
                 | Value::Int8(_)
                 | Value::List(_)
+                | Value::Json(_)
                 | Value::Null => {
                     return Err(StoreError::UnsupportedFilter(
                         op.to_owned(),
@@ -1410,7 +1513,8 @@ impl<'a> Filter<'a> {
                 | SqlValue::List(_)
                 | SqlValue::Null
                 | SqlValue::Bytes(_)
-                | SqlValue::Binary(_) => pattern,
+                | SqlValue::Binary(_)
+                | SqlValue::Json(_) => pattern,
             };
             Ok(Filter::Contains {
                 column,
@@ -1419,6 +1523,43 @@ impl<'a> Filter<'a> {
             })
         }
 
+        fn json_path<'s>(
+            table: dsl::Table<'s>,
+            attr: &String,
+            path: &'s Vec<String>,
+            value: &'s Value,
+            contains: bool,
+        ) -> Result<Filter<'s>, StoreError> {
+            let column = table.column_for_field(attr)?;
+            let filter = if contains {
+                "path.contains"
+            } else {
+                "path.equals"
+            };
+            if column.column_type() != &ColumnType::Json {
+                return Err(StoreError::UnsupportedFilter(
+                    filter.to_owned(),
+                    value.to_string(),
+                ));
+            }
+            let value = match value {
+                Value::Json(json) => Some(json),
+                Value::Null if !contains => None,
+                _ => {
+                    return Err(StoreError::UnsupportedFilter(
+                        filter.to_owned(),
+                        value.to_string(),
+                    ))
+                }
+            };
+            Ok(Filter::JsonPath {
+                column,
+                path,
+                contains,
+                value,
+            })
+        }
+
         use Comparison as C;
         use ContainsOp as K;
         use EntityFilter::*;
@@ -1497,8 +1638,32 @@ impl<'a> Filter<'a> {
                         value.to_string(),
                     ));
                 }
-                Ok(F::Fulltext(column, value))
+                if column.is_fuzzy_fulltext() {
+                    Ok(F::FuzzyFulltext {
+                        column,
+                        value,
+                        threshold: FUZZY_DEFAULT_THRESHOLD,
+                    })
+                } else {
+                    Ok(F::Fulltext(column, value))
+                }
             }
+            FuzzyFulltext(attr, value, threshold) => {
+                let (column, value) = column_and_value(table, attr, value)?;
+                if value.is_null() || !column.is_fuzzy_fulltext() {
+                    return Err(StoreError::UnsupportedFilter(
+                        "fuzzy fulltext".to_owned(),
+                        value.to_string(),
+                    ));
+                }
+                Ok(F::FuzzyFulltext {
+                    column,
+                    value,
+                    threshold: *threshold,
+                })
+            }
+            JsonPathEqual(attr, path, value) => json_path(table, attr, path, value, false),
+            JsonPathContains(attr, path, value) => json_path(table, attr, path, value, true),
         }
     }
 
@@ -1557,6 +1722,38 @@ impl<'a> Filter<'a> {
         qv.walk_ast(out)
     }
 
+    /// Generate
+    ///   [$value <% column and] word_similarity($value, column) >= $threshold
+    ///
+    /// The `<%` operator can use the trigram index on the column, but it
+    /// compares against the `pg_trgm.word_similarity_threshold` setting,
+    /// which we assume is at most its default of `FUZZY_DEFAULT_THRESHOLD`;
+    /// with a higher setting, `<%` would filter out matches. We can
+    /// therefore only use it when `threshold` is at least that default
+    fn fuzzy_fulltext<'b>(
+        column: &'b dsl::Column<'b>,
+        qv: &'b QueryValue,
+        threshold: &'b f64,
+        mut out: AstPass<'_, 'b, Pg>,
+    ) -> QueryResult<()> {
+        assert!(!qv.is_null());
+        out.push_sql("(");
+        if *threshold >= FUZZY_DEFAULT_THRESHOLD {
+            qv.walk_ast(out.reborrow())?;
+            out.push_sql(" <% ");
+            column.walk_ast(out.reborrow())?;
+            out.push_sql(" and ");
+        }
+        out.push_sql("word_similarity(");
+        qv.walk_ast(out.reborrow())?;
+        out.push_sql(", ");
+        column.walk_ast(out.reborrow())?;
+        out.push_sql(") >= ");
+        out.push_bind_param::<Double, _>(threshold)?;
+        out.push_sql(")");
+        Ok(())
+    }
+
     fn contains<'b>(
         column: &'b dsl::Column<'b>,
         op: &'b ContainsOp,
@@ -1591,6 +1788,16 @@ impl<'a> Filter<'a> {
                     out.push_sql(") > 0");
                 }
             }
+            SqlValue::Json(_) => {
+                if op.negated() {
+                    out.push_sql("not ");
+                }
+                out.push_sql("(");
+                column.walk_ast(out.reborrow())?;
+                out.push_sql(" @> ");
+                qv.walk_ast(out.reborrow())?;
+                out.push_sql(")");
+            }
             SqlValue::List(_) | SqlValue::Numerics(_) => {
                 if op.negated() {
                     out.push_sql(" not ");
@@ -1722,6 +1929,11 @@ impl<'a> fmt::Display for Filter<'a> {
                 write!(f, "{}", fs.iter().map(|f| f.to_string()).join(" or "))
             }
             Fulltext(a, v) => write!(f, "{a} = {v}"),
+            FuzzyFulltext {
+                column,
+                value,
+                threshold,
+            } => write!(f, "{column} % {value} >= {threshold}"),
             PrefixCmp(PrefixComparison {
                 op,
                 kind: _,
@@ -1763,6 +1975,18 @@ impl<'a> fmt::Display for Filter<'a> {
                 child.child_from,
                 child.child_filter
             ),
+            JsonPath {
+                column,
+                path,
+                contains,
+                value,
+            } => {
+                let op = if *contains { "@>" } else { "=" };
+                match value {
+                    Some(v) => write!(f, "{column}#>{{{}}} {op} {v}", path.join(",")),
+                    None => write!(f, "{column}#>{{{}}} is null", path.join(",")),
+                }
+            }
         }
     }
 }
@@ -1784,6 +2008,11 @@ impl<'a> QueryFragment<Pg> for Filter<'a> {
             PrefixCmp(pc) => pc.walk_ast(out)?,
             Cmp(column, op, value) => Self::cmp(column, value, *op, out)?,
             Fulltext(column, value) => Self::fulltext(column, value, out)?,
+            FuzzyFulltext {
+                column,
+                value,
+                threshold,
+            } => Self::fuzzy_fulltext(column, value, threshold, out)?,
             In(attr, values) => Self::in_array(attr, values, false, out)?,
             NotIn(attr, values) => Self::in_array(attr, values, true, out)?,
             StartsOrEndsWith {
@@ -1797,6 +2026,25 @@ impl<'a> QueryFragment<Pg> for Filter<'a> {
             }
             ChangeBlockGte(changed_since) => changed_since.walk_ast(out.reborrow())?,
             Child(child) => child.walk_ast(out)?,
+            JsonPath {
+                column,
+                path,
+                contains,
+                value,
+            } => {
+                out.push_sql("(");
+                column.walk_ast(out.reborrow())?;
+                out.push_sql(" #> ");
+                out.push_bind_param::<Array<Text>, _>(*path)?;
+                out.push_sql(")");
+                match value {
+                    None => out.push_sql(" is null"),
+                    Some(json) => {
+                        out.push_sql(if *contains { " @> " } else { " = " });
+                        out.push_bind_param::<Jsonb, _>(*json)?;
+                    }
+                }
+            }
         }
         Ok(())
     }
@@ -1912,6 +2160,168 @@ impl<'a> Query for FindPossibleDeletionsQuery<'a> {
 
 impl<'a, Conn> RunQueryDsl<Conn> for FindPossibleDeletionsQuery<'a> {}
 
+/// Builds a query over a given set of [`Table`]s that finds the entities
+/// that differ between the blocks `from` and `to`, together with their
+/// versions at `from` and at `to`.
+///
+/// For mutable tables, the old version is the version that is visible at
+/// `from` and ends at or before `to`, and the new version is the one that is
+/// visible at `to` and starts after `from`. Immutable entities can only be
+/// created. An entity that was changed and then changed back to what it was
+/// at `from` is not part of the result.
+#[derive(Debug)]
+pub struct FindDiffQuery<'a> {
+    pub(crate) tables: &'a [&'a Table],
+    from: BlockNumber,
+    to: BlockNumber,
+    first: i64,
+    skip: i64,
+}
+
+impl<'a> FindDiffQuery<'a> {
+    pub fn new(
+        tables: &'a [&'a Table],
+        from: BlockNumber,
+        to: BlockNumber,
+        first: usize,
+        skip: usize,
+    ) -> Self {
+        Self {
+            tables,
+            from,
+            to,
+            first: first as i64,
+            skip: skip as i64,
+        }
+    }
+
+    fn mutable<'b>(&'b self, table: &'b Table, out: &mut AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        // Generate
+        //   select $object as entity, coalesce(o.id, n.id)::text as sort_id,
+        //          case when o.id is null then null else to_jsonb(o.*) end as old_data,
+        //          case when n.id is null then null else to_jsonb(n.*) end as new_data
+        //     from (select * from schema.<table> e
+        //            where e.block_range @> $from
+        //              and coalesce(upper(e.block_range), 2147483647) <= $to) o
+        //     full outer join
+        //          (select * from schema.<table> e
+        //            where e.block_range @> $to
+        //              and lower(e.block_range) > $from) n
+        //       on o.id = n.id and o.causality_region = n.causality_region
+        //    where o.id is null or n.id is null
+        //       or to_jsonb(o.*) - 'vid' - 'block_range'
+        //          <> to_jsonb(n.*) - 'vid' - 'block_range'
+        out.push_sql("select ");
+        out.push_bind_param::<Text, _>(table.object.as_str())?;
+        out.push_sql(" as entity, coalesce(o.id, n.id)::text as sort_id,\n");
+        out.push_sql(
+            "       case when o.id is null then null else to_jsonb(o.*) end as old_data,\n",
+        );
+        out.push_sql(
+            "       case when n.id is null then null else to_jsonb(n.*) end as new_data\n",
+        );
+        out.push_sql("  from (select * from ");
+        out.push_sql(table.qualified_name.as_str());
+        out.push_sql(" e where e.");
+        out.push_identifier(BLOCK_RANGE_COLUMN)?;
+        out.push_sql(" @> ");
+        out.push_bind_param::<Integer, _>(&self.from)?;
+        out.push_sql(" and coalesce(upper(e.");
+        out.push_identifier(BLOCK_RANGE_COLUMN)?;
+        out.push_sql("), 2147483647) <= ");
+        out.push_bind_param::<Integer, _>(&self.to)?;
+        out.push_sql(") o\n  full outer join\n       (select * from ");
+        out.push_sql(table.qualified_name.as_str());
+        out.push_sql(" e where e.");
+        out.push_identifier(BLOCK_RANGE_COLUMN)?;
+        out.push_sql(" @> ");
+        out.push_bind_param::<Integer, _>(&self.to)?;
+        out.push_sql(" and lower(e.");
+        out.push_identifier(BLOCK_RANGE_COLUMN)?;
+        out.push_sql(") > ");
+        out.push_bind_param::<Integer, _>(&self.from)?;
+        out.push_sql(") n\n    on o.id = n.id");
+        if table.has_causality_region {
+            out.push_sql(" and o.");
+            out.push_identifier(CAUSALITY_REGION_COLUMN)?;
+            out.push_sql(" = n.");
+            out.push_identifier(CAUSALITY_REGION_COLUMN)?;
+        }
+        // Versions that only differ in their vid and block range are the
+        // same entity
+        out.push_sql("\n where o.id is null or n.id is null\n    or to_jsonb(o.*) - '");
+        out.push_sql(VID_COLUMN);
+        out.push_sql("' - '");
+        out.push_sql(BLOCK_RANGE_COLUMN);
+        out.push_sql("'\n       <> to_jsonb(n.*) - '");
+        out.push_sql(VID_COLUMN);
+        out.push_sql("' - '");
+        out.push_sql(BLOCK_RANGE_COLUMN);
+        out.push_sql("'");
+        Ok(())
+    }
+
+    fn immutable<'b>(&'b self, table: &'b Table, out: &mut AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        // Generate
+        //   select $object as entity, e.id::text as sort_id,
+        //          null::jsonb as old_data, to_jsonb(e.*) as new_data
+        //     from schema.<table> e
+        //    where e.block$ > $from and e.block$ <= $to
+        out.push_sql("select ");
+        out.push_bind_param::<Text, _>(table.object.as_str())?;
+        out.push_sql(" as entity, e.id::text as sort_id,\n");
+        out.push_sql("       null::jsonb as old_data, to_jsonb(e.*) as new_data\n");
+        out.push_sql("  from ");
+        out.push_sql(table.qualified_name.as_str());
+        out.push_sql(" e\n where e.");
+        out.push_identifier(BLOCK_COLUMN)?;
+        out.push_sql(" > ");
+        out.push_bind_param::<Integer, _>(&self.from)?;
+        out.push_sql(" and e.");
+        out.push_identifier(BLOCK_COLUMN)?;
+        out.push_sql(" <= ");
+        out.push_bind_param::<Integer, _>(&self.to)?;
+        Ok(())
+    }
+}
+
+impl<'a> QueryFragment<Pg> for FindDiffQuery<'a> {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        let out = &mut out;
+        out.unsafe_to_cache_prepared();
+
+        out.push_sql("select entity, old_data, new_data from (\n");
+        for (i, table) in self.tables.iter().enumerate() {
+            if i > 0 {
+                out.push_sql("\nunion all\n");
+            }
+            if table.immutable {
+                self.immutable(table, out)?;
+            } else {
+                self.mutable(table, out)?;
+            }
+        }
+        out.push_sql(") d\n order by entity, sort_id\n limit ");
+        out.push_bind_param::<BigInt, _>(&self.first)?;
+        out.push_sql(" offset ");
+        out.push_bind_param::<BigInt, _>(&self.skip)?;
+
+        Ok(())
+    }
+}
+
+impl<'a> QueryId for FindDiffQuery<'a> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<'a> Query for FindDiffQuery<'a> {
+    type SqlType = Untyped;
+}
+
+impl<'a, Conn> RunQueryDsl<Conn> for FindDiffQuery<'a> {}
+
 #[derive(Debug)]
 pub struct FindManyQuery<'a> {
     pub(crate) tables: Vec<(&'a Table, CausalityRegion, BlockRangeColumn<'a>)>,
@@ -1987,6 +2397,66 @@ impl<'a> Query for FindManyQuery<'a> {
 
 impl<'a, Conn> RunQueryDsl<Conn> for FindManyQuery<'a> {}
 
+/// A query that reads the entities of a table as of a block in batches,
+/// ordered by `vid`. Each batch starts after the `vid` of the last entity
+/// of the previous batch.
+#[derive(Debug)]
+pub struct DumpQuery<'a> {
+    table: &'a Table,
+    br_column: BlockRangeColumn<'a>,
+    after_vid: i64,
+    batch_size: i64,
+}
+
+impl<'a> DumpQuery<'a> {
+    pub fn new(table: &'a Table, block: BlockNumber, after_vid: i64, batch_size: usize) -> Self {
+        let br_column = BlockRangeColumn::new(table, "e.", block);
+        Self {
+            table,
+            br_column,
+            after_vid,
+            batch_size: batch_size as i64,
+        }
+    }
+}
+
+impl<'a> QueryFragment<Pg> for DumpQuery<'a> {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.unsafe_to_cache_prepared();
+
+        // Generate
+        //    select e.vid, $object as entity, to_jsonb(e.*) as data
+        //      from schema.<table> e
+        //     where {br_column.contains} and e.vid > $after_vid
+        //     order by e.vid
+        //     limit $batch_size
+        out.push_sql("select e.vid, ");
+        out.push_bind_param::<Text, _>(self.table.object.as_str())?;
+        out.push_sql(" as entity, to_jsonb(e.*) as data\n");
+        out.push_sql("  from ");
+        out.push_sql(self.table.qualified_name.as_str());
+        out.push_sql(" e\n where ");
+        self.br_column.contains(&mut out, false)?;
+        out.push_sql(" and e.vid > ");
+        out.push_bind_param::<BigInt, _>(&self.after_vid)?;
+        out.push_sql("\n order by e.vid\n limit ");
+        out.push_bind_param::<BigInt, _>(&self.batch_size)?;
+        Ok(())
+    }
+}
+
+impl<'a> QueryId for DumpQuery<'a> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<'a> Query for DumpQuery<'a> {
+    type SqlType = Untyped;
+}
+
+impl<'a, Conn> RunQueryDsl<Conn> for DumpQuery<'a> {}
+
 /// A query that finds an entity by key. Used during indexing.
 /// See also `FindManyQuery`.
 #[derive(Debug)]
@@ -2081,7 +2551,7 @@ impl<'a> QueryFragment<Pg> for InsertValue<'a> {
         match self {
             InsertValue::Value(qv) => qv.walk_ast(out),
             InsertValue::Fulltext(qvs, config) => {
-                process_vec_ast(qvs, &mut out, config.language.as_sql())?;
+                process_vec_ast(qvs, &mut out, config)?;
                 Ok(())
             }
         }
@@ -3130,6 +3600,12 @@ pub enum SortKey<'a> {
     },
     /// Order by some other column; `column` will never be `id`
     ChildKey(ChildKey<'a>),
+    /// Order by several columns in turn, and then by `id`; none of the
+    /// `columns` will be `id`
+    Multi {
+        columns: Vec<(dsl::Column<'a>, SortDirection)>,
+        id_direction: SortDirection,
+    },
 }
 
 /// String representation that is useful for debugging when `walk_ast` fails
@@ -3200,11 +3676,20 @@ impl<'a> fmt::Display for SortKey<'a> {
                     )
                 }
             },
+            SortKey::Multi {
+                columns,
+                id_direction,
+            } => {
+                for (column, direction) in columns {
+                    write!(f, "{}{}, ", column, direction)?;
+                }
+                write!(f, "{}{}", PRIMARY_KEY_COLUMN, id_direction)
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SortDirection {
     Asc,
     Desc,
@@ -3220,6 +3705,24 @@ impl SortDirection {
             SortDirection::Desc => " desc",
         }
     }
+
+    /// Generate the operator that selects values that sort after a given
+    /// value in this direction
+    fn after_op(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => " > ",
+            SortDirection::Desc => " < ",
+        }
+    }
+}
+
+impl From<OrderDirection> for SortDirection {
+    fn from(direction: OrderDirection) -> Self {
+        match direction {
+            OrderDirection::Ascending => SortDirection::Asc,
+            OrderDirection::Descending => SortDirection::Desc,
+        }
+    }
 }
 
 impl std::fmt::Display for SortDirection {
@@ -3260,11 +3763,13 @@ impl<'a> SortKey<'a> {
             let column = table.column_for_field(&attribute)?;
             if column.is_fulltext() {
                 match filter {
-                    Some(EntityFilter::Fulltext(_, value)) => {
+                    Some(EntityFilter::Fulltext(_, value))
+                    | Some(EntityFilter::FuzzyFulltext(_, value, _)) => {
                         sort_key_from_value(column, value, direction)
                     }
                     Some(EntityFilter::And(vec)) => match vec.first() {
-                        Some(EntityFilter::Fulltext(_, value)) => {
+                        Some(EntityFilter::Fulltext(_, value))
+                        | Some(EntityFilter::FuzzyFulltext(_, value, _)) => {
                             sort_key_from_value(column, value, direction)
                         }
                         _ => unreachable!(),
@@ -3283,6 +3788,38 @@ impl<'a> SortKey<'a> {
             }
         }
 
+        fn with_keys<'a>(
+            table: dsl::Table<'a>,
+            attributes: Vec<(String, ValueType, OrderDirection)>,
+            use_block_column: UseBlockColumn,
+        ) -> Result<SortKey<'a>, QueryExecutionError> {
+            let mut columns = Vec::new();
+            let mut id_direction = SortDirection::Asc;
+            for (attribute, _, direction) in attributes {
+                let column = table.column_for_field(&attribute)?;
+                id_direction = direction.into();
+                if column.is_fulltext() {
+                    return Err(QueryExecutionError::NotSupported(
+                        "Sorting by fulltext fields together with other attributes".to_string(),
+                    ));
+                } else if column.is_primary_key() {
+                    // Since `id` is unique, attributes after it do not
+                    // change the order
+                    break;
+                }
+                columns.push((column, id_direction));
+            }
+            if columns.is_empty() {
+                let block_column = use_block_column.block_column(table);
+                Ok(SortKey::Id(id_direction, block_column))
+            } else {
+                Ok(SortKey::Multi {
+                    columns,
+                    id_direction,
+                })
+            }
+        }
+
         fn with_child_object_key<'a>(
             block: BlockNumber,
             parent_table: dsl::Table<'a>,
@@ -3525,6 +4062,7 @@ impl<'a> SortKey<'a> {
             EntityOrder::Descending(attr, _) => {
                 with_key(table, attr, filter, Desc, use_block_column)
             }
+            EntityOrder::Multiple(attributes) => with_keys(table, attributes, use_block_column),
             EntityOrder::Default => Ok(SortKey::Id(Asc, use_block_column.block_column(table))),
             EntityOrder::Unordered => Ok(SortKey::None),
             EntityOrder::ChildAscending(kind) => match kind {
@@ -3662,6 +4200,17 @@ impl<'a> SortKey<'a> {
                     out.push_sql(SORT_KEY_COLUMN);
                 }
             }
+            SortKey::Multi { columns, .. } => {
+                for (i, (column, _)) in columns.iter().enumerate() {
+                    out.push_sql(", ");
+                    if let SelectStatementLevel::InnerStatement = select_statement_level {
+                        column.walk_ast(out.reborrow())?;
+                        out.push_sql(" as ");
+                    }
+                    out.push_sql(SORT_KEY_COLUMN);
+                    out.push_sql(&i.to_string());
+                }
+            }
         }
         Ok(())
     }
@@ -3733,6 +4282,13 @@ impl<'a> SortKey<'a> {
                     }
                 }
             }
+            SortKey::Multi {
+                columns,
+                id_direction,
+            } => {
+                out.push_sql("order by ");
+                SortKey::multi_key_expr(columns, id_direction, use_sort_key_alias, out)
+            }
         }
     }
 
@@ -3771,6 +4327,13 @@ impl<'a> SortKey<'a> {
             SortKey::ChildKey(_) => Err(diesel::result::Error::QueryBuilderError(
                 "SortKey::ChildKey cannot be used for parent ordering (yet)".into(),
             )),
+            SortKey::Multi {
+                columns,
+                id_direction,
+            } => {
+                order_by_parent_id(out);
+                SortKey::multi_key_expr(columns, id_direction, use_sort_key_alias, out)
+            }
         }
     }
 
@@ -3803,18 +4366,27 @@ impl<'a> SortKey<'a> {
                 let algorithm = match config.algorithm {
                     FulltextAlgorithm::Rank => "ts_rank(",
                     FulltextAlgorithm::ProximityRank => "ts_rank_cd(",
+                    FulltextAlgorithm::Fuzzy => "word_similarity(",
                 };
                 out.push_sql(algorithm);
+                if config.is_fuzzy() {
+                    out.push_bind_param::<Text, _>(value.unwrap())?;
+                    out.push_sql(", ");
+                }
                 if use_sort_key_alias {
                     out.push_sql(SORT_KEY_COLUMN);
                 } else {
                     column.walk_ast(out.reborrow())?;
                 }
 
-                out.push_sql(", to_tsquery(");
+                if config.is_fuzzy() {
+                    out.push_sql(")");
+                } else {
+                    out.push_sql(", to_tsquery(");
 
-                out.push_bind_param::<Text, _>(value.unwrap())?;
-                out.push_sql("))");
+                    out.push_bind_param::<Text, _>(value.unwrap())?;
+                    out.push_sql("))");
+                }
             }
             _ => {
                 if use_sort_key_alias {
@@ -3834,6 +4406,29 @@ impl<'a> SortKey<'a> {
         Ok(())
     }
 
+    /// Generate
+    ///   name1 direction1, name2 direction2, .., id id_direction
+    fn multi_key_expr<'b>(
+        columns: &'b [(dsl::Column<'b>, SortDirection)],
+        id_direction: &SortDirection,
+        use_sort_key_alias: bool,
+        out: &mut AstPass<'_, 'b, Pg>,
+    ) -> QueryResult<()> {
+        for (i, (column, direction)) in columns.iter().enumerate() {
+            if use_sort_key_alias {
+                out.push_sql(SORT_KEY_COLUMN);
+                out.push_sql(&i.to_string());
+            } else {
+                column.walk_ast(out.reborrow())?;
+            }
+            out.push_sql(direction.as_sql());
+            out.push_sql(", ");
+        }
+        out.push_identifier(PRIMARY_KEY_COLUMN)?;
+        out.push_sql(id_direction.as_sql());
+        Ok(())
+    }
+
     /// Generate
     ///   [COALESCE(name1, name2) direction,] id1, id2
     fn multi_sort_expr<'b>(
@@ -4046,6 +4641,241 @@ impl QueryFragment<Pg> for FilterRange {
     }
 }
 
+/// Restrict a query to the entities that sort after an `EntityCursor`.
+/// When all sort columns are sorted in the same direction and can not be
+/// null, the condition compares the sort key as a whole, i.e., the sort
+/// columns and `id`, so that the database can use the same indexes that
+/// it uses for the `order by` of the query to find the start of the page
+#[derive(Debug, Clone)]
+pub struct CursorFilter<'a> {
+    /// The columns we sort by, in order, together with the cursor's value
+    /// for them. This is empty when we sort by `id`
+    keys: Vec<CursorKey<'a>>,
+    id_column: dsl::Column<'a>,
+    id: QueryValue<'a>,
+    id_direction: SortDirection,
+}
+
+/// One of the columns of a `CursorFilter`
+#[derive(Debug, Clone)]
+struct CursorKey<'a> {
+    column: dsl::Column<'a>,
+    value: QueryValue<'a>,
+    direction: SortDirection,
+    /// Whether any of the tables we query can have nulls in the column
+    nullable: bool,
+}
+
+impl<'a> CursorFilter<'a> {
+    fn new(
+        cursor: &'a EntityCursor,
+        collection: &'a FilterCollection,
+        sort_key: &SortKey<'a>,
+    ) -> Result<Self, QueryExecutionError> {
+        let entities = match collection {
+            FilterCollection::All(entities) => entities,
+            FilterCollection::SingleWindow(_) | FilterCollection::MultiWindow(_, _) => {
+                return Err(QueryExecutionError::NotSupported(
+                    "Cursors can only be used for top-level collections".to_string(),
+                ))
+            }
+        };
+        let id_column = collection
+            .first_table()
+            .expect("an entity query always contains at least one entity type/table")
+            .primary_key();
+        let id = QueryValue::new(&cursor.id, id_column.column_type())?;
+
+        let (columns, id_direction) = match sort_key {
+            SortKey::Id(direction, _) => (vec![], *direction),
+            SortKey::Key {
+                column,
+                value: None,
+                direction,
+            } if !column.is_fulltext() => (vec![(*column, *direction)], *direction),
+            SortKey::Multi {
+                columns,
+                id_direction,
+            } => (columns.clone(), *id_direction),
+            SortKey::None | SortKey::Key { .. } | SortKey::ChildKey(_) => {
+                return Err(QueryExecutionError::NotSupported(
+                    "Cursors can not be used when sorting by fulltext or child attributes"
+                        .to_string(),
+                ))
+            }
+        };
+        // The sort key leaves out attributes after `id`, and the cursor
+        // leaves out `id` and the attributes after it; they therefore
+        // have to agree on the number of columns
+        if columns.len() != cursor.values.len() {
+            return Err(QueryExecutionError::ConstraintViolation(format!(
+                "a cursor with {} values can not be used when sorting by {} attributes",
+                cursor.values.len(),
+                columns.len()
+            )));
+        }
+
+        let keys = columns
+            .into_iter()
+            .zip(cursor.values.iter())
+            .map(|((column, direction), value)| {
+                let value = QueryValue::new(value, column.column_type())?;
+                let nullable = entities.iter().any(|wh| {
+                    wh.table
+                        .meta
+                        .columns
+                        .iter()
+                        .find(|col| col.name.as_str() == column.name())
+                        .map_or(true, |col| col.is_nullable())
+                });
+                Ok(CursorKey {
+                    column,
+                    value,
+                    direction,
+                    nullable,
+                })
+            })
+            .collect::<Result<Vec<_>, QueryExecutionError>>()?;
+
+        Ok(CursorFilter {
+            keys,
+            id_column,
+            id,
+            id_direction,
+        })
+    }
+
+    /// Whether we can compare the sort key as a whole with a row
+    /// comparison. That is only possible if all columns are sorted in the
+    /// same direction, and none of them contain nulls. For a single
+    /// nullable column with a non-null value, we can add a condition for
+    /// the nulls that sort last in ascending order
+    fn use_row_comparison(&self) -> bool {
+        self.keys.iter().all(|key| {
+            key.direction == self.id_direction
+                && !key.value.is_null()
+                && (!key.nullable || self.keys.len() == 1)
+        })
+    }
+
+    /// Generate
+    ///   (col1, .., id) > ($value1, .., $id) [or col1 is null]
+    /// with `>` for ascending and `<` for descending order
+    fn row_comparison<'b>(&'b self, out: &mut AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        let nulls_last = self
+            .keys
+            .iter()
+            .any(|key| key.nullable && key.direction == SortDirection::Asc);
+        if nulls_last {
+            out.push_sql("(");
+        }
+        out.push_sql("(");
+        for key in &self.keys {
+            key.column.walk_ast(out.reborrow())?;
+            out.push_sql(", ");
+        }
+        self.id_column.walk_ast(out.reborrow())?;
+        out.push_sql(")");
+        out.push_sql(self.id_direction.after_op());
+        out.push_sql("(");
+        for key in &self.keys {
+            key.value.walk_ast(out.reborrow())?;
+            out.push_sql(", ");
+        }
+        self.id.walk_ast(out.reborrow())?;
+        out.push_sql(")");
+        if nulls_last {
+            for key in &self.keys {
+                out.push_sql(" or ");
+                key.column.walk_ast(out.reborrow())?;
+                out.push_sql(" is null");
+            }
+            out.push_sql(")");
+        }
+        Ok(())
+    }
+
+    /// Generate a condition that selects rows that sort after the cursor
+    /// by the keys from `keys` on, comparing one column at a time:
+    ///   (after(col1) or (equal(col1) and <condition for the rest>))
+    /// where the last condition is `id > $id` or `id < $id`
+    fn lexicographic<'b>(
+        &'b self,
+        keys: &'b [CursorKey<'a>],
+        out: &mut AstPass<'_, 'b, Pg>,
+    ) -> QueryResult<()> {
+        let Some((key, rest)) = keys.split_first() else {
+            self.id_column.walk_ast(out.reborrow())?;
+            out.push_sql(self.id_direction.after_op());
+            return self.id.walk_ast(out.reborrow());
+        };
+
+        out.push_sql("(");
+        if key.after(out)? {
+            out.push_sql(" or ");
+        }
+        out.push_sql("(");
+        key.column.walk_ast(out.reborrow())?;
+        if key.value.is_null() {
+            out.push_sql(" is null");
+        } else {
+            out.push_sql(" = ");
+            key.value.walk_ast(out.reborrow())?;
+        }
+        out.push_sql(" and ");
+        self.lexicographic(rest, out)?;
+        out.push_sql("))");
+        Ok(())
+    }
+}
+
+impl<'a> CursorKey<'a> {
+    /// Generate the condition for rows whose value for this column sorts
+    /// strictly after the cursor's value. Postgres sorts nulls last in
+    /// ascending and first in descending order, which leads to
+    ///   col > $value [or col is null]    for a value, asc
+    ///   col < $value                     for a value, desc
+    ///   col is not null                  for a null value, desc
+    /// For a null value and ascending order, no rows sort after the
+    /// cursor's value; in that case, generate nothing and return `false`
+    fn after<'b>(&'b self, out: &mut AstPass<'_, 'b, Pg>) -> QueryResult<bool> {
+        match (self.value.is_null(), self.direction) {
+            (true, SortDirection::Asc) => return Ok(false),
+            (true, SortDirection::Desc) => {
+                self.column.walk_ast(out.reborrow())?;
+                out.push_sql(" is not null");
+            }
+            (false, direction) => {
+                self.column.walk_ast(out.reborrow())?;
+                out.push_sql(direction.after_op());
+                self.value.walk_ast(out.reborrow())?;
+                if self.nullable && direction == SortDirection::Asc {
+                    out.push_sql(" or ");
+                    self.column.walk_ast(out.reborrow())?;
+                    out.push_sql(" is null");
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<'a> QueryFragment<Pg> for CursorFilter<'a> {
+    /// Generate a condition that selects the rows that sort after the
+    /// cursor. That is `id > $id` or `id < $id` when sorting by `id`, a
+    /// comparison of the entire sort key if possible, and otherwise a
+    /// comparison of one column at a time
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.unsafe_to_cache_prepared();
+
+        if self.use_row_comparison() {
+            self.row_comparison(&mut out)
+        } else {
+            self.lexicographic(&self.keys, &mut out)
+        }
+    }
+}
+
 /// The parallel to `EntityQuery`.
 ///
 /// Details of how query generation for `FilterQuery` works can be found
@@ -4054,6 +4884,7 @@ impl QueryFragment<Pg> for FilterRange {
 pub struct FilterQuery<'a> {
     collection: &'a FilterCollection<'a>,
     limit: ParentLimit<'a>,
+    cursor: Option<CursorFilter<'a>>,
     block: BlockNumber,
     query_id: Option<String>,
     site: &'a Site,
@@ -4081,17 +4912,22 @@ impl<'a> FilterQuery<'a> {
         filter: Option<&'a EntityFilter>,
         order: EntityOrder,
         range: EntityRange,
+        after: Option<&'a EntityCursor>,
         block: BlockNumber,
         query_id: Option<String>,
         site: &'a Site,
     ) -> Result<Self, QueryExecutionError> {
         let sort_key = SortKey::new(order, collection, filter, layout, block)?;
+        let cursor = after
+            .map(|cursor| CursorFilter::new(cursor, collection, &sort_key))
+            .transpose()?;
         let range = FilterRange(range);
         let limit = ParentLimit { sort_key, range };
 
         Ok(FilterQuery {
             collection,
             limit,
+            cursor,
             block,
             query_id,
             site,
@@ -4102,6 +4938,7 @@ impl<'a> FilterQuery<'a> {
     ///     from schema.table c
     ///    where block_range @> $block
     ///      and query_filter
+    ///      and cursor_filter
     /// Only used when the query is against a `FilterCollection::All`, i.e.
     /// when we do not need to window
     fn filtered_rows<'b>(
@@ -4121,6 +4958,10 @@ impl<'a> FilterQuery<'a> {
             out.push_sql(" and ");
             filter.walk_ast(out.reborrow())?;
         }
+        if let Some(cursor) = &self.cursor {
+            out.push_sql(" and ");
+            cursor.walk_ast(out.reborrow())?;
+        }
         out.push_sql("\n");
         Ok(())
     }
@@ -4405,6 +5246,224 @@ impl<'a> Query for FilterQuery<'a> {
 
 impl<'a, Conn> RunQueryDsl<Conn> for FilterQuery<'a> {}
 
+/// Helper struct for retrieving the result of an [`AggregateQuery`]. Since
+/// the number of aggregates depends on the query, they are all returned
+/// as text in one array
+#[derive(QueryableByName)]
+pub struct AggregateData {
+    #[diesel(sql_type = Array<Nullable<Text>>)]
+    aggregates: Vec<Option<String>>,
+}
+
+/// A query that computes aggregates over all entities of one type that
+/// match a filter. It is the parallel to `FilterQuery` for queries that
+/// only need aggregates and not the entities themselves
+#[derive(Debug)]
+pub struct AggregateQuery<'a> {
+    wh: &'a WholeTable<'a>,
+    /// The aggregates and the column they aggregate; the column is `None`
+    /// for `count`
+    aggregates: Vec<(&'a EntityAggregate, Option<dsl::Column<'a>>)>,
+    block: BlockNumber,
+    query_id: Option<String>,
+    site: &'a Site,
+}
+
+impl<'a> fmt::Display for AggregateQuery<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{} from {} at {}",
+            self.aggregates.iter().map(|(agg, _)| agg).join(", "),
+            self.wh
+                .table
+                .meta
+                .qualified_name
+                .as_str()
+                .replace("\\\"", ""),
+            self.block
+        )?;
+        if let Some(filter) = &self.wh.filter {
+            write!(f, " where {}", filter)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> AggregateQuery<'a> {
+    pub fn new(
+        collection: &'a FilterCollection<'a>,
+        aggregates: &'a [EntityAggregate],
+        block: BlockNumber,
+        query_id: Option<String>,
+        site: &'a Site,
+    ) -> Result<Self, QueryExecutionError> {
+        let wh = match collection {
+            FilterCollection::All(entities) if entities.len() == 1 => &entities[0],
+            _ => {
+                return Err(QueryExecutionError::NotSupported(
+                    "aggregates over more than one entity type or over nested fields".to_string(),
+                ))
+            }
+        };
+
+        let aggregates = aggregates
+            .iter()
+            .map(|agg| {
+                use EntityAggregate::*;
+
+                let attr = match agg {
+                    Count => return Ok((agg, None)),
+                    Sum(attr) | Min(attr) | Max(attr) | Avg(attr) => attr,
+                };
+                let column = wh.table.column_for_field(attr)?;
+                let numeric = matches!(
+                    column.column_type(),
+                    ColumnType::Int
+                        | ColumnType::Int8
+                        | ColumnType::BigInt
+                        | ColumnType::BigDecimal
+                );
+                if column.is_list() || !numeric {
+                    return Err(QueryExecutionError::NotSupported(format!(
+                        "aggregating the attribute `{}` since it is not numeric",
+                        attr
+                    )));
+                }
+                Ok((agg, Some(column)))
+            })
+            .collect::<Result<_, QueryExecutionError>>()?;
+
+        Ok(AggregateQuery {
+            wh,
+            aggregates,
+            block,
+            query_id,
+            site,
+        })
+    }
+
+    /// Turn the text that the database returned for each aggregate into a
+    /// value of the type that the aggregate produces: `count` is an
+    /// `Int8`, `avg` a `BigDecimal`, and `min` and `max` have the type of
+    /// the attribute. The `sum` of `Int` attributes is an `Int8`, and of
+    /// `Int8` attributes a `BigInt` since Postgres widens sums to avoid
+    /// overflow
+    pub fn values(&self, data: AggregateData) -> Result<Vec<Value>, StoreError> {
+        fn parse<T: FromStr>(text: &str) -> Result<T, StoreError>
+        where
+            T::Err: std::fmt::Display,
+        {
+            text.parse::<T>().map_err(|e| {
+                constraint_violation!("failed to parse aggregate value `{}`: {}", text, e)
+            })
+        }
+
+        if data.aggregates.len() != self.aggregates.len() {
+            return Err(constraint_violation!(
+                "expected {} aggregate values but got {}",
+                self.aggregates.len(),
+                data.aggregates.len()
+            ));
+        }
+
+        self.aggregates
+            .iter()
+            .zip(data.aggregates)
+            .map(|((agg, column), text)| {
+                use EntityAggregate::*;
+
+                let Some(text) = text else {
+                    return Ok(Value::Null);
+                };
+                let column_type = column.as_ref().map(|column| column.column_type());
+                let value = match (agg, column_type) {
+                    (Count, _) => Value::Int8(parse(&text)?),
+                    (Avg(_), _) => Value::BigDecimal(parse(&text)?),
+                    (Sum(_), Some(ColumnType::Int)) => Value::Int8(parse(&text)?),
+                    (Min(_) | Max(_), Some(ColumnType::Int)) => Value::Int(parse(&text)?),
+                    (Min(_) | Max(_), Some(ColumnType::Int8)) => Value::Int8(parse(&text)?),
+                    (_, Some(ColumnType::BigDecimal)) => Value::BigDecimal(parse(&text)?),
+                    (_, Some(ColumnType::Int8 | ColumnType::BigInt)) => {
+                        Value::BigInt(parse(&text)?)
+                    }
+                    (_, column_type) => {
+                        return Err(constraint_violation!(
+                            "unexpected column type {:?} for aggregate {}",
+                            column_type,
+                            agg
+                        ))
+                    }
+                };
+                Ok(value)
+            })
+            .collect()
+    }
+}
+
+impl<'a> QueryFragment<Pg> for AggregateQuery<'a> {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.unsafe_to_cache_prepared();
+
+        if let Some(qid) = &self.query_id {
+            out.push_sql("/* controller='aggregate',application='");
+            out.push_sql(self.site.namespace.as_str());
+            out.push_sql("',route='");
+            out.push_sql(qid);
+            out.push_sql("',action='");
+            out.push_sql(&self.block.to_string());
+            out.push_sql("' */\n");
+        }
+
+        // Generate
+        //    select array[count(*)::text, sum(c.attr)::text, ..] as aggregates
+        //      from schema.table c
+        //     where block_range @> $block
+        //       and query_filter
+        out.push_sql("select array[");
+        for (i, (agg, column)) in self.aggregates.iter().enumerate() {
+            if i > 0 {
+                out.push_sql(", ");
+            }
+            let func = match agg {
+                EntityAggregate::Count => "count(",
+                EntityAggregate::Sum(_) => "sum(",
+                EntityAggregate::Min(_) => "min(",
+                EntityAggregate::Max(_) => "max(",
+                EntityAggregate::Avg(_) => "avg(",
+            };
+            out.push_sql(func);
+            match column {
+                Some(column) => column.walk_ast(out.reborrow())?,
+                None => out.push_sql("*"),
+            }
+            out.push_sql(")::text");
+        }
+        out.push_sql("]::text[] as aggregates");
+        out.push_sql("\n  from ");
+        self.wh.from_table.walk_ast(out.reborrow())?;
+        out.push_sql("\n where ");
+        self.wh.at_block.walk_ast(out.reborrow())?;
+        if let Some(filter) = &self.wh.filter {
+            out.push_sql(" and ");
+            filter.walk_ast(out.reborrow())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> QueryId for AggregateQuery<'a> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<'a> Query for AggregateQuery<'a> {
+    type SqlType = Untyped;
+}
+
+impl<'a, Conn> RunQueryDsl<Conn> for AggregateQuery<'a> {}
+
 /// Reduce the upper bound of the current entry's block range to `block` as
 /// long as that does not result in an empty block range
 #[derive(Debug)]
@@ -0,0 +1,449 @@
+//! Snapshots of a deployment that can be restored in an unrelated
+//! installation of graph-node.
+//!
+//! A snapshot is a directory that contains
+//!
+//!   - `metadata.json`: the deployment metadata and the block pointer of
+//!     the head of the deployment when the snapshot was taken
+//!   - `schema.graphql` and `manifest.yaml`: the GraphQL schema and the
+//!     manifest of the deployment
+//!   - `tables/<table>.jsonl`: all versions of all entities in `<table>`,
+//!     including the Proof of Indexing, one JSON object per row
+//!   - `data_sources.jsonl`: the dynamic data sources of the deployment
+//!
+//! Rows are written with Postgres' `to_jsonb` and read back with
+//! `jsonb_populate_recordset` so that they retain their block ranges and
+//! their `vid`. Since `metadata.json` is written last, a snapshot without
+//! it is incomplete and can not be restored.
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use diesel::connection::SimpleConnection;
+use diesel::sql_types::{BigInt, Text};
+use diesel::{sql_query, PgConnection, RunQueryDsl};
+use graph::anyhow::Context;
+use graph::data::subgraph::schema::{DeploymentCreate, SubgraphManifestEntity};
+use graph::prelude::serde::{Deserialize, Serialize};
+use graph::prelude::{
+    anyhow, serde_json, BlockNumber, BlockPtr, DeploymentHash, StoreError, SubgraphDeploymentEntity,
+};
+use graph::schema::InputSchema;
+use graph::semver::Version;
+
+use crate::catalog;
+use crate::dynds::DataSourcesTable;
+use crate::primary::Site;
+use crate::relational::Layout;
+
+/// The version of the snapshot format. It needs to be bumped whenever the
+/// format changes in a way that older versions of graph-node can not read
+const SNAPSHOT_VERSION: u32 = 1;
+
+const METADATA_FILE: &str = "metadata.json";
+const SCHEMA_FILE: &str = "schema.graphql";
+const MANIFEST_FILE: &str = "manifest.yaml";
+const TABLES_DIR: &str = "tables";
+const DATA_SOURCES_FILE: &str = "data_sources.jsonl";
+
+/// The number of rows we read or write with one query
+const BATCH_SIZE: usize = 10_000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotBlock {
+    pub number: BlockNumber,
+    pub hash: String,
+}
+
+impl From<&BlockPtr> for SnapshotBlock {
+    fn from(ptr: &BlockPtr) -> Self {
+        SnapshotBlock {
+            number: ptr.number,
+            hash: ptr.hash_hex(),
+        }
+    }
+}
+
+impl TryFrom<&SnapshotBlock> for BlockPtr {
+    type Error = StoreError;
+
+    fn try_from(block: &SnapshotBlock) -> Result<Self, Self::Error> {
+        BlockPtr::try_from((block.hash.as_str(), block.number as i64))
+            .with_context(|| format!("invalid block hash `{}` in snapshot", block.hash))
+            .map_err(StoreError::from)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotTable {
+    /// The name of the table in the database
+    pub name: String,
+    pub rows: usize,
+    pub account_like: bool,
+}
+
+/// The contents of `metadata.json`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotMetadata {
+    pub version: u32,
+    pub deployment: String,
+    pub network: String,
+    /// The block the deployment had processed when the snapshot was taken
+    pub head: SnapshotBlock,
+    pub start_block: Option<SnapshotBlock>,
+    pub earliest_block: BlockNumber,
+    pub spec_version: String,
+    pub description: Option<String>,
+    pub repository: Option<String>,
+    pub features: Vec<String>,
+    pub entities_with_causality_region: Vec<String>,
+    pub history_blocks: BlockNumber,
+    pub tables: Vec<SnapshotTable>,
+    pub data_sources: usize,
+}
+
+/// A snapshot in a directory that we are about to restore
+pub(crate) struct Snapshot {
+    dir: PathBuf,
+    pub metadata: SnapshotMetadata,
+    pub schema: String,
+    raw_yaml: Option<String>,
+}
+
+#[derive(QueryableByName)]
+struct Row {
+    #[diesel(sql_type = BigInt)]
+    vid: i64,
+    #[diesel(sql_type = Text)]
+    data: String,
+}
+
+fn read_file(path: &Path) -> Result<String, StoreError> {
+    fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))
+        .map_err(StoreError::from)
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), StoreError> {
+    fs::write(path, contents)
+        .with_context(|| format!("failed to write {}", path.display()))
+        .map_err(StoreError::from)
+}
+
+/// Write all rows of the table `qname` to `path`, ordered by `vid`, and
+/// return how many rows were written
+fn export_rows(conn: &mut PgConnection, qname: &str, path: &Path) -> Result<usize, StoreError> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+
+    let query = format!(
+        "select t.vid::int8 as vid, to_jsonb(t.*)::text as data \
+           from {qname} t \
+          where t.vid > $1 \
+          order by t.vid \
+          limit $2"
+    );
+    let mut after = -1;
+    let mut count = 0;
+    loop {
+        let rows = sql_query(&query)
+            .bind::<BigInt, _>(after)
+            .bind::<BigInt, _>(BATCH_SIZE as i64)
+            .load::<Row>(conn)?;
+        for row in &rows {
+            writeln!(out, "{}", row.data)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        count += rows.len();
+        match rows.last() {
+            Some(row) if rows.len() == BATCH_SIZE => after = row.vid,
+            _ => break,
+        }
+    }
+    out.flush()
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(count)
+}
+
+/// Insert the rows from `path` into the table `qname` and make sure that
+/// the sequence for `vid` continues after the largest `vid` we inserted.
+/// Rows are inserted in the order in which they appear in the file.
+/// Return how many rows were inserted
+fn import_rows(conn: &mut PgConnection, qname: &str, path: &Path) -> Result<usize, StoreError> {
+    fn insert(
+        conn: &mut PgConnection,
+        qname: &str,
+        rows: &mut Vec<String>,
+    ) -> Result<usize, StoreError> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let query = format!(
+            "insert into {qname} \
+             select * from jsonb_populate_recordset(null::{qname}, $1::jsonb)"
+        );
+        let json = format!("[{}]", rows.join(","));
+        rows.clear();
+        Ok(sql_query(query).bind::<Text, _>(json).execute(conn)?)
+    }
+
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut rows = Vec::with_capacity(BATCH_SIZE);
+    let mut count = 0;
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("failed to read {}", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        rows.push(line);
+        if rows.len() == BATCH_SIZE {
+            count += insert(conn, qname, &mut rows)?;
+        }
+    }
+    count += insert(conn, qname, &mut rows)?;
+
+    let query =
+        format!("select setval(pg_get_serial_sequence('{qname}', 'vid'), max(vid)) from {qname}");
+    conn.batch_execute(&query)?;
+    Ok(count)
+}
+
+/// Write a snapshot of the deployment with `layout` into `dir`. The
+/// caller must make sure that `conn` is in a transaction that sees a
+/// consistent state of the deployment, i.e., in a transaction with
+/// isolation level `repeatable read`
+pub(crate) fn export(
+    conn: &mut PgConnection,
+    layout: &Layout,
+    deployment: SubgraphDeploymentEntity,
+    dir: &Path,
+) -> Result<SnapshotMetadata, StoreError> {
+    let site = &layout.site;
+
+    if deployment.failed {
+        return Err(StoreError::Unknown(anyhow!(
+            "can not take a snapshot of deployment {} because it has failed",
+            site.deployment
+        )));
+    }
+    let head = deployment.latest_block.as_ref().ok_or_else(|| {
+        StoreError::Unknown(anyhow!(
+            "can not take a snapshot of deployment {} because it has not processed any blocks",
+            site.deployment
+        ))
+    })?;
+    if !site.schema_version.private_data_sources() {
+        return Err(StoreError::Unknown(anyhow!(
+            "can not take a snapshot of deployment {} because it uses an old storage scheme; \
+             copy it with `graphman copy` first",
+            site.deployment
+        )));
+    }
+
+    if dir.exists()
+        && fs::read_dir(dir)
+            .with_context(|| format!("failed to read {}", dir.display()))?
+            .next()
+            .is_some()
+    {
+        return Err(StoreError::Unknown(anyhow!(
+            "the snapshot directory {} must be empty",
+            dir.display()
+        )));
+    }
+    let tables_dir = dir.join(TABLES_DIR);
+    fs::create_dir_all(&tables_dir)
+        .with_context(|| format!("failed to create {}", tables_dir.display()))?;
+
+    let manifest = deployment.manifest;
+    write_file(&dir.join(SCHEMA_FILE), &manifest.schema)?;
+    if let Some(raw_yaml) = &manifest.raw_yaml {
+        write_file(&dir.join(MANIFEST_FILE), raw_yaml)?;
+    }
+
+    let mut tables: Vec<_> = layout.tables.values().collect();
+    tables.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+    let tables = tables
+        .into_iter()
+        .map(|table| {
+            let path = tables_dir.join(format!("{}.jsonl", table.name.as_str()));
+            let rows = export_rows(conn, table.qualified_name.as_str(), &path)?;
+            Ok(SnapshotTable {
+                name: table.name.to_string(),
+                rows,
+                account_like: table.is_account_like,
+            })
+        })
+        .collect::<Result<Vec<_>, StoreError>>()?;
+
+    let data_sources = DataSourcesTable::new(site.namespace.clone());
+    let data_sources = export_rows(
+        conn,
+        data_sources.qualified_name(),
+        &dir.join(DATA_SOURCES_FILE),
+    )?;
+
+    let metadata = SnapshotMetadata {
+        version: SNAPSHOT_VERSION,
+        deployment: site.deployment.to_string(),
+        network: site.network.clone(),
+        head: SnapshotBlock::from(head),
+        start_block: deployment.start_block.as_ref().map(SnapshotBlock::from),
+        earliest_block: deployment.earliest_block_number,
+        spec_version: manifest.spec_version,
+        description: manifest.description,
+        repository: manifest.repository,
+        features: manifest.features,
+        entities_with_causality_region: manifest
+            .entities_with_causality_region
+            .iter()
+            .map(|entity_type| entity_type.to_string())
+            .collect(),
+        history_blocks: manifest.history_blocks,
+        tables,
+        data_sources,
+    };
+    write_file(
+        &dir.join(METADATA_FILE),
+        &serde_json::to_string_pretty(&metadata)?,
+    )?;
+    Ok(metadata)
+}
+
+impl Snapshot {
+    pub fn open(dir: &Path) -> Result<Self, StoreError> {
+        let metadata = dir.join(METADATA_FILE);
+        if !metadata.exists() {
+            return Err(StoreError::Unknown(anyhow!(
+                "{} does not contain a snapshot or the snapshot is incomplete",
+                dir.display()
+            )));
+        }
+        let metadata: SnapshotMetadata = serde_json::from_str(&read_file(&metadata)?)?;
+        if metadata.version != SNAPSHOT_VERSION {
+            return Err(StoreError::Unknown(anyhow!(
+                "the snapshot in {} has version {} but we can only restore version {}",
+                dir.display(),
+                metadata.version,
+                SNAPSHOT_VERSION
+            )));
+        }
+        let schema = read_file(&dir.join(SCHEMA_FILE))?;
+        let manifest = dir.join(MANIFEST_FILE);
+        let raw_yaml = if manifest.exists() {
+            Some(read_file(&manifest)?)
+        } else {
+            None
+        };
+
+        Ok(Snapshot {
+            dir: dir.to_path_buf(),
+            metadata,
+            schema,
+            raw_yaml,
+        })
+    }
+
+    pub fn deployment_hash(&self) -> Result<DeploymentHash, StoreError> {
+        DeploymentHash::new(self.metadata.deployment.as_str()).map_err(|id| {
+            StoreError::Unknown(anyhow!("invalid deployment hash `{}` in snapshot", id))
+        })
+    }
+
+    pub fn input_schema(&self) -> Result<InputSchema, StoreError> {
+        let spec_version = Version::parse(&self.metadata.spec_version).with_context(|| {
+            format!(
+                "invalid spec version `{}` in snapshot",
+                self.metadata.spec_version
+            )
+        })?;
+        InputSchema::parse(&spec_version, &self.schema, self.deployment_hash()?)
+            .context("invalid GraphQL schema in snapshot")
+            .map_err(StoreError::from)
+    }
+
+    /// The deployment metadata with which the restored deployment must be
+    /// created
+    pub fn deployment_create(&self, schema: &InputSchema) -> Result<DeploymentCreate, StoreError> {
+        let metadata = &self.metadata;
+        let entities_with_causality_region = metadata
+            .entities_with_causality_region
+            .iter()
+            .map(|name| schema.entity_type(name.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let start_block = metadata
+            .start_block
+            .as_ref()
+            .map(BlockPtr::try_from)
+            .transpose()?;
+
+        Ok(DeploymentCreate {
+            manifest: SubgraphManifestEntity {
+                spec_version: metadata.spec_version.clone(),
+                description: metadata.description.clone(),
+                repository: metadata.repository.clone(),
+                features: metadata.features.clone(),
+                schema: self.schema.clone(),
+                raw_yaml: self.raw_yaml.clone(),
+                entities_with_causality_region,
+                history_blocks: metadata.history_blocks,
+            },
+            start_block,
+            graft_base: None,
+            graft_block: None,
+            debug_fork: None,
+            history_blocks_override: None,
+        })
+    }
+
+    pub fn head(&self) -> Result<BlockPtr, StoreError> {
+        BlockPtr::try_from(&self.metadata.head)
+    }
+
+    /// Load the data of the snapshot into the freshly created deployment
+    /// with `layout`. Only the tables and the account-like flags are
+    /// restored, the caller needs to take care of the other deployment
+    /// metadata
+    pub fn restore(&self, conn: &mut PgConnection, layout: &Layout) -> Result<(), StoreError> {
+        let site: &Site = &layout.site;
+        let tables_dir = self.dir.join(TABLES_DIR);
+
+        for snapshot_table in &self.metadata.tables {
+            let table = layout
+                .tables
+                .values()
+                .find(|table| table.name.as_str() == snapshot_table.name)
+                .ok_or_else(|| {
+                    StoreError::Unknown(anyhow!(
+                        "the snapshot contains table `{}` which is not in the schema",
+                        snapshot_table.name
+                    ))
+                })?;
+            let path = tables_dir.join(format!("{}.jsonl", snapshot_table.name));
+            let rows = import_rows(conn, table.qualified_name.as_str(), &path)?;
+            check_count(&path, snapshot_table.rows, rows)?;
+            if snapshot_table.account_like {
+                catalog::set_account_like(conn, site, &table.name, true)?;
+            }
+        }
+
+        let data_sources = DataSourcesTable::new(site.namespace.clone());
+        let path = self.dir.join(DATA_SOURCES_FILE);
+        let rows = import_rows(conn, data_sources.qualified_name(), &path)?;
+        check_count(&path, self.metadata.data_sources, rows)
+    }
+}
+
+fn check_count(path: &Path, expected: usize, actual: usize) -> Result<(), StoreError> {
+    if expected != actual {
+        return Err(StoreError::Unknown(anyhow!(
+            "{} should have {} rows but has {}",
+            path.display(),
+            expected,
+            actual
+        )));
+    }
+    Ok(())
+}
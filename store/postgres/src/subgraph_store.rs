@@ -5,6 +5,7 @@ use diesel::{
     sql_types::{self, Text},
 };
 use std::fmt;
+use std::path::Path;
 use std::{
     collections::{BTreeMap, HashMap},
     sync::{atomic::AtomicU8, Arc, Mutex},
@@ -20,18 +21,19 @@ use graph::{
             self, BlockPtrForNumber, BlockStore, DeploymentLocator, EnsLookup as EnsLookupTrait,
             PruneReporter, PruneRequest, SubgraphFork,
         },
+        subgraph::{Settings, SettingsTarget, SubgraphSettings},
     },
     constraint_violation,
     data::query::QueryTarget,
     data::subgraph::{schema::DeploymentCreate, status, DeploymentFeatures},
     prelude::{
         anyhow, lazy_static, o, web3::types::Address, ApiVersion, BlockNumber, BlockPtr,
-        ChainStore, DeploymentHash, EntityOperation, Logger, MetricsRegistry, NodeId,
-        PartialBlockPtr, StoreError, SubgraphDeploymentEntity, SubgraphName,
+        ChainStore, DeploymentHash, Entity, EntityDiff, EntityOperation, Logger, MetricsRegistry,
+        NodeId, PartialBlockPtr, StoreError, SubgraphDeploymentEntity, SubgraphName,
         SubgraphStore as SubgraphStoreTrait, SubgraphVersionSwitchingMode,
     },
     prelude::{CancelableError, StoreEvent},
-    schema::{ApiSchema, InputSchema},
+    schema::{ApiSchema, EntityType, InputSchema},
     url::Url,
     util::timed_cache::TimedCache,
 };
@@ -44,6 +46,7 @@ use crate::{
         index::{IndexList, Method},
         Layout,
     },
+    snapshot::{Snapshot, SnapshotMetadata},
     writable::WritableStore,
     NotificationSender,
 };
@@ -207,6 +210,8 @@ pub struct SubgraphStore {
     /// subgraph forks will fetch entities.
     /// Example: https://api.thegraph.com/subgraphs/
     fork_base: Option<Url>,
+    /// Subgraph-specific settings from `GRAPH_EXPERIMENTAL_SUBGRAPH_SETTINGS`
+    settings: Arc<Settings>,
 }
 
 impl SubgraphStore {
@@ -224,6 +229,9 @@ impl SubgraphStore {
     /// pool. One of the shards must be named `primary`
     ///
     /// The `placer` determines where `create_subgraph_deployment` puts a new deployment
+    ///
+    /// The `settings` are used to determine deployment-specific settings
+    /// for writing to a deployment
     pub fn new(
         logger: &Logger,
         stores: Vec<(Shard, ConnectionPool, Vec<ConnectionPool>, Vec<usize>)>,
@@ -231,15 +239,36 @@ impl SubgraphStore {
         sender: Arc<NotificationSender>,
         fork_base: Option<Url>,
         registry: Arc<MetricsRegistry>,
+        settings: Arc<Settings>,
     ) -> Self {
         Self {
             inner: Arc::new(SubgraphStoreInner::new(
                 logger, stores, placer, sender, registry,
             )),
             fork_base,
+            settings,
         }
     }
 
+    /// Combine the subgraph-specific settings that apply to the deployment
+    /// in `site`. Settings that match on a name apply if any of the names
+    /// that point to the deployment match
+    fn settings_for_site(&self, site: &Site) -> Result<SubgraphSettings, StoreError> {
+        let names: Vec<_> = self
+            .mirror
+            .subgraphs_by_deployment_hash(site.deployment.as_str())?
+            .into_iter()
+            .filter_map(|(name, _)| SubgraphName::new(name).ok())
+            .collect();
+        let target = SettingsTarget {
+            names: &names,
+            deployment: Some(&site.deployment),
+            network: Some(site.network.as_str()),
+            shard: Some(site.shard.as_str()),
+        };
+        Ok(self.settings.for_target(&target))
+    }
+
     pub async fn get_proof_of_indexing(
         &self,
         id: &DeploymentHash,
@@ -708,6 +737,82 @@ impl SubgraphStoreInner {
         Ok(dst.as_ref().into())
     }
 
+    /// Write a snapshot of `deployment` into the directory `dir` from
+    /// which it can be restored with `restore_snapshot`, possibly in a
+    /// different installation of graph-node
+    pub fn export_snapshot(
+        &self,
+        deployment: &DeploymentLocator,
+        dir: &Path,
+    ) -> Result<SnapshotMetadata, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(site.as_ref())?;
+        store.export_snapshot(site, dir)
+    }
+
+    /// Create a new deployment in `shard` from the snapshot in `dir` and
+    /// assign it to `node`. The deployment continues indexing from the
+    /// block at which the snapshot was taken. Since the snapshot contains
+    /// the deployment hash, there can not already be a deployment with
+    /// that hash.
+    ///
+    /// Like `copy_deployment`, this is not transactional across the
+    /// primary and the shard. A deployment that was only partially
+    /// restored is not assigned to any node and needs to be removed
+    /// before trying again
+    pub fn restore_snapshot(
+        &self,
+        dir: &Path,
+        shard: Shard,
+        node: NodeId,
+    ) -> Result<DeploymentLocator, StoreError> {
+        let snapshot = Snapshot::open(dir)?;
+        let hash = snapshot.deployment_hash()?;
+        let schema = snapshot.input_schema()?;
+        let deployment = snapshot.deployment_create(&schema)?;
+
+        let (site, created) = self.primary_conn()?.allocate_site(
+            shard.clone(),
+            &hash,
+            snapshot.metadata.network.clone(),
+            None,
+        )?;
+        if !created {
+            return Err(StoreError::Unknown(anyhow!(
+                "can not restore deployment {} since it already exists as {}",
+                hash,
+                site.namespace
+            )));
+        }
+        let site = Arc::new(site);
+
+        let deployment_store = self
+            .stores
+            .get(&shard)
+            .ok_or_else(|| StoreError::UnknownShard(shard.to_string()))?;
+
+        deployment_store.create_deployment(
+            &schema,
+            deployment,
+            site.clone(),
+            None,
+            false,
+            OnSync::None,
+            None,
+        )?;
+        deployment_store.restore_snapshot(site.clone(), &snapshot)?;
+
+        let mut pconn = self.primary_conn()?;
+        pconn.transaction(|conn| -> Result<_, StoreError> {
+            let mut pconn = primary::Connection::new(conn);
+            let changes = pconn.assign_subgraph(site.as_ref(), &node)?;
+            let event = StoreEvent::new(changes);
+            pconn.send_store_event(&self.sender, &event)?;
+            Ok(())
+        })?;
+        Ok(site.as_ref().into())
+    }
+
     /// Mark `deployment` as the only active deployment amongst all sites
     /// with the same deployment hash. Activating this specific deployment
     /// will make queries use that instead of whatever was active before
@@ -921,6 +1026,11 @@ impl SubgraphStoreInner {
                 let ids: Vec<_> = ids.into_iter().map(|id| id.into()).collect();
                 self.mirror.find_sites_by_id(&ids)?
             }
+            status::Filter::Matching {
+                filter,
+                first,
+                skip,
+            } => self.mirror.find_sites_matching(&filter, first, skip)?,
         };
 
         let by_shard: HashMap<Shard, Vec<Arc<Site>>> = self.deployments_by_shard(sites)?;
@@ -1121,6 +1231,26 @@ impl SubgraphStoreInner {
         store.analyze(site, entity_name)
     }
 
+    /// Read all entities of `deployment` as they were at `block`, one
+    /// entity type after the other, and pass them to `f` in batches of at
+    /// most `batch_size` entities. Entity types without any entities are
+    /// passed to `f` once with an empty batch. All entities are read in one
+    /// transaction
+    pub fn dump<E, F>(
+        &self,
+        deployment: &DeploymentLocator,
+        block: BlockNumber,
+        batch_size: usize,
+        f: F,
+    ) -> Result<(), E>
+    where
+        E: From<StoreError>,
+        F: FnMut(&EntityType, Vec<Entity>) -> Result<(), E>,
+    {
+        let (store, site) = self.store(&deployment.hash)?;
+        store.dump(site, block, batch_size, f)
+    }
+
     /// Return the statistics targets for all tables of `deployment`. The
     /// first return value is the default target, and the second value maps
     /// the name of each table to a map of column name to its statistics
@@ -1444,6 +1574,19 @@ impl SubgraphStoreTrait for SubgraphStore {
         Ok(changes)
     }
 
+    fn entity_diff(
+        &self,
+        subgraph_id: &DeploymentHash,
+        from: BlockNumber,
+        to: BlockNumber,
+        entity_types: &[String],
+        first: usize,
+        skip: usize,
+    ) -> Result<Vec<EntityDiff>, StoreError> {
+        let (store, site) = self.store(subgraph_id)?;
+        store.get_diff(site, from, to, entity_types, first, skip)
+    }
+
     fn input_schema(&self, id: &DeploymentHash) -> Result<InputSchema, StoreError> {
         let (store, site) = self.store(id)?;
         let layout = store.find_layout(site)?;
@@ -1503,11 +1646,14 @@ impl SubgraphStoreTrait for SubgraphStore {
 
         // Ideally the lower level functions would be asyncified.
         let this = self.clone();
-        let site = graph::spawn_blocking_allow_panic(move || -> Result<_, StoreError> {
-            this.find_site(deployment)
-        })
-        .await
-        .unwrap()?; // Propagate panics, there shouldn't be any.
+        let (site, settings) =
+            graph::spawn_blocking_allow_panic(move || -> Result<_, StoreError> {
+                let site = this.find_site(deployment)?;
+                let settings = this.settings_for_site(&site)?;
+                Ok((site, settings))
+            })
+            .await
+            .unwrap()?; // Propagate panics, there shouldn't be any.
 
         let writable = Arc::new(
             WritableStore::new(
@@ -1516,6 +1662,7 @@ impl SubgraphStoreTrait for SubgraphStore {
                 site,
                 manifest_idx_and_name,
                 self.registry.clone(),
+                settings,
             )
             .await?,
         );
@@ -8,12 +8,13 @@ use std::{collections::BTreeMap, sync::Arc};
 use graph::blockchain::block_stream::FirehoseCursor;
 use graph::blockchain::BlockTime;
 use graph::components::store::{Batch, DeploymentCursorTracker, DerivedEntityQuery, ReadStore};
+use graph::components::subgraph::SubgraphSettings;
 use graph::constraint_violation;
 use graph::data::store::IdList;
 use graph::data::subgraph::schema;
 use graph::data_source::CausalityRegion;
 use graph::prelude::{
-    BlockNumber, CacheWeight, Entity, MetricsRegistry, SubgraphDeploymentEntity,
+    anyhow, BlockNumber, CacheWeight, Entity, MetricsRegistry, SubgraphDeploymentEntity,
     SubgraphStore as _, BLOCK_NUMBER_MAX,
 };
 use graph::schema::{EntityKey, EntityType, InputSchema};
@@ -159,6 +160,7 @@ struct SyncStore {
     input_schema: InputSchema,
     manifest_idx_and_name: Arc<Vec<(u32, String)>>,
     last_rollup: LastRollupTracker,
+    settings: SubgraphSettings,
 }
 
 impl SyncStore {
@@ -168,6 +170,7 @@ impl SyncStore {
         site: Arc<Site>,
         manifest_idx_and_name: Arc<Vec<(u32, String)>>,
         block: Option<BlockNumber>,
+        settings: SubgraphSettings,
     ) -> Result<Self, StoreError> {
         let store = WritableSubgraphStore(subgraph_store.clone());
         let writable = subgraph_store.for_site(site.as_ref())?.clone();
@@ -187,6 +190,7 @@ impl SyncStore {
             input_schema,
             manifest_idx_and_name,
             last_rollup,
+            settings,
         })
     }
 
@@ -373,6 +377,16 @@ impl SyncStore {
         .await
     }
 
+    async fn earliest_block_number(&self) -> Result<BlockNumber, StoreError> {
+        retry::forever_async(&self.logger, "earliest_block_number", || async {
+            self.writable
+                .deployment_state_from_id(self.site.deployment.cheap_clone())
+                .await
+                .map(|state| state.earliest_block_number)
+        })
+        .await
+    }
+
     fn unassign_subgraph(&self, site: &Site) -> Result<(), StoreError> {
         retry::forever(&self.logger, "unassign_subgraph", || {
             let mut pconn = self.store.primary_conn()?;
@@ -742,8 +756,13 @@ impl Request {
     /// request
     fn should_process(&self) -> bool {
         match self {
-            Request::Write { queued, batch, .. } => {
-                batch.read().unwrap().weight() >= ENV_VARS.store.write_batch_size
+            Request::Write {
+                queued,
+                batch,
+                store,
+                ..
+            } => {
+                batch.read().unwrap().weight() >= store.settings.write_batch_size()
                     || queued.elapsed() >= ENV_VARS.store.write_batch_duration
             }
             Request::RevertTo { .. } | Request::Stop => true,
@@ -1011,7 +1030,8 @@ impl Queue {
     ///   4. The newest write request is not older than
     ///      `GRAPH_STORE_WRITE_BATCH_DURATION`
     ///   5. The newest write request is not bigger than
-    ///      `GRAPH_STORE_WRITE_BATCH_SIZE`
+    ///      `GRAPH_STORE_WRITE_BATCH_SIZE` or the `write_batch_size` from
+    ///      the subgraph settings
     ///
     /// In all other cases, we queue a new write request. Note that (3)
     /// means that the oldest request (front of the queue) does not
@@ -1023,7 +1043,8 @@ impl Queue {
     /// a 'full' write batch, i.e., one that is either big enough or old
     /// enough
     async fn push_write(&self, batch: Batch) -> Result<(), StoreError> {
-        let batch = if ENV_VARS.store.write_batch_size == 0
+        let write_batch_size = self.store.settings.write_batch_size();
+        let batch = if write_batch_size == 0
             || ENV_VARS.store.write_batch_duration.is_zero()
             || !self.batch_writes()
         {
@@ -1062,9 +1083,9 @@ impl Queue {
                             // slow down queueing requests unnecessarily
                             match existing.try_write() {
                                 Ok(mut existing) => {
-                                    if existing.weight() < ENV_VARS.store.write_batch_size {
+                                    if existing.weight() < write_batch_size {
                                         let res = existing.append(batch).map(|()| None);
-                                        if existing.weight() >= ENV_VARS.store.write_batch_size {
+                                        if existing.weight() >= write_batch_size {
                                             self.batch_ready_notify.notify_one();
                                         }
                                         res
@@ -1502,6 +1523,7 @@ impl WritableStore {
         site: Arc<Site>,
         manifest_idx_and_name: Arc<Vec<(u32, String)>>,
         registry: Arc<MetricsRegistry>,
+        settings: SubgraphSettings,
     ) -> Result<Self, StoreError> {
         let block_ptr = subgraph_store
             .for_site(&site)?
@@ -1514,6 +1536,7 @@ impl WritableStore {
                 site,
                 manifest_idx_and_name,
                 block_ptr.as_ref().map(|ptr| ptr.number),
+                settings,
             )
             .await?,
         );
@@ -1570,6 +1593,37 @@ impl ReadStore for WritableStore {
     }
 }
 
+/// The entities of a deployment as they were at a fixed block. Reads go
+/// straight to the database and bypass the write queue
+struct HistoricalStore {
+    store: Arc<SyncStore>,
+    block: BlockNumber,
+}
+
+impl ReadStore for HistoricalStore {
+    fn get(&self, key: &EntityKey) -> Result<Option<Entity>, StoreError> {
+        self.store.get(key, self.block)
+    }
+
+    fn get_many(
+        &self,
+        keys: BTreeSet<EntityKey>,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        self.store.get_many(keys, self.block)
+    }
+
+    fn get_derived(
+        &self,
+        key: &DerivedEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        self.store.get_derived(key, self.block, vec![])
+    }
+
+    fn input_schema(&self) -> InputSchema {
+        self.store.input_schema()
+    }
+}
+
 impl DeploymentCursorTracker for WritableStore {
     fn block_ptr(&self) -> Option<BlockPtr> {
         self.block_ptr.lock().unwrap().clone()
@@ -1733,6 +1787,10 @@ impl WritableStoreTrait for WritableStore {
         self.store.shard()
     }
 
+    fn settings(&self) -> &SubgraphSettings {
+        &self.store.settings
+    }
+
     async fn health(&self) -> Result<schema::SubgraphHealth, StoreError> {
         self.store.health().await
     }
@@ -1741,6 +1799,23 @@ impl WritableStoreTrait for WritableStore {
         self.writer.flush().await
     }
 
+    async fn entities_at(&self, block: BlockNumber) -> Result<Arc<dyn ReadStore>, StoreError> {
+        let earliest_block = self.store.earliest_block_number().await?;
+        if block < earliest_block {
+            return Err(StoreError::Unknown(anyhow!(
+                "deployment {} only has data starting at block number {} \
+                 and data for block number {} is therefore not available",
+                self.store.site.deployment,
+                earliest_block,
+                block
+            )));
+        }
+        Ok(Arc::new(HistoricalStore {
+            store: self.store.cheap_clone(),
+            block,
+        }))
+    }
+
     async fn restart(self: Arc<Self>) -> Result<Option<Arc<dyn WritableStoreTrait>>, StoreError> {
         if self.poisoned() {
             // When the writer is poisoned, the background thread has
@@ -3,6 +3,7 @@ use graph::blockchain::mock::MockDataSource;
 use graph::blockchain::BlockTime;
 use graph::blockchain::ChainIdentifier;
 use graph::components::store::BlockStore;
+use graph::components::subgraph::Settings;
 use graph::data::graphql::load_manager::LoadManager;
 use graph::data::query::QueryResults;
 use graph::data::query::QueryTarget;
@@ -619,7 +620,15 @@ fn build_store() -> (Arc<Store>, ConnectionPool, Config, Arc<SubscriptionManager
     let registry = Arc::new(MetricsRegistry::mock());
     std::thread::spawn(move || {
         STORE_RUNTIME.handle().block_on(async {
-            let builder = StoreBuilder::new(&LOGGER, &NODE_ID, &config, None, registry).await;
+            let builder = StoreBuilder::new(
+                &LOGGER,
+                &NODE_ID,
+                &config,
+                None,
+                registry,
+                Arc::new(Settings::default()),
+            )
+            .await;
             let subscription_manager = builder.subscription_manager();
             let primary_pool = builder.primary_pool();
 
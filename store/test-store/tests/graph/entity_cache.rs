@@ -4,6 +4,7 @@ use graph::components::store::{
     DeploymentCursorTracker, DerivedEntityQuery, GetScope, LoadRelatedRequest, ReadStore,
     StoredDynamicDataSource, WritableStore,
 };
+use graph::components::subgraph::SubgraphSettings;
 use graph::data::store::Id;
 use graph::data::subgraph::schema::{DeploymentCreate, SubgraphError, SubgraphHealth};
 use graph::data_source::CausalityRegion;
@@ -164,6 +165,10 @@ impl WritableStore for MockStore {
         unimplemented!()
     }
 
+    fn settings(&self) -> &SubgraphSettings {
+        unimplemented!()
+    }
+
     async fn health(&self) -> Result<SubgraphHealth, StoreError> {
         unimplemented!()
     }
@@ -172,6 +177,10 @@ impl WritableStore for MockStore {
         unimplemented!()
     }
 
+    async fn entities_at(&self, _: BlockNumber) -> Result<Arc<dyn ReadStore>, StoreError> {
+        unimplemented!()
+    }
+
     async fn causality_region_curr_val(&self) -> Result<Option<CausalityRegion>, StoreError> {
         unimplemented!()
     }
@@ -21,6 +21,7 @@ use test_store::block_store::{
 use graph::futures03::stream::StreamExt;
 use graph::{
     components::store::DeploymentLocator,
+    components::subgraph::Settings,
     data::graphql::{object, object_value},
     data::subgraph::schema::SubgraphError,
     data::{
@@ -612,6 +613,7 @@ async fn execute_query_document_with_variables(
         SUBSCRIPTION_MANAGER.clone(),
         LOAD_MANAGER.clone(),
         METRICS_REGISTRY.clone(),
+        Arc::new(Settings::default()),
     ));
     let target = QueryTarget::Deployment(id.clone(), Default::default());
     let query = Query::new(query, variables, false);
@@ -1007,6 +1009,90 @@ fn can_query_with_fulltext_search_filter() {
     })
 }
 
+#[test]
+fn can_query_with_sorting_by_multiple_attributes() {
+    const QUERY: &str = "
+    query {
+        songs(orderBy: [writtenBy, title], orderDirection: [desc, asc]) {
+            title
+        }
+        musician(id: \"m1\") {
+            writtenSongs(orderBy: [writtenBy, title], orderDirection: desc) {
+                title
+            }
+        }
+    }";
+
+    run_query(QUERY, |result, _| {
+        let exp = object! {
+            songs: vec![
+                object! { title: "Folk Tune" },
+                object! { title: "Rock Tune" },
+                object! { title: "Cheesy Tune" },
+                object! { title: "Pop Tune" },
+            ],
+            musician: object! {
+                writtenSongs: vec![
+                    object! { title: "Pop Tune" },
+                    object! { title: "Cheesy Tune" },
+                ]
+            }
+        };
+
+        let data = extract_data!(result).unwrap();
+        assert_eq!(data, exp);
+    })
+}
+
+#[test]
+fn can_query_with_single_valued_order_by() {
+    // `orderBy` and `orderDirection` are lists, but a single value is
+    // coerced into a list with just that value, both as a literal and
+    // as a variable. Queries written before they were lists declare
+    // their variables with the non-list types
+    const QUERY: &str = "
+    query songs($orderBy: [Song_orderBy!], $orderDirection: [OrderDirection!],
+                $singleOrderBy: Song_orderBy, $singleDirection: OrderDirection!) {
+        literal: songs(orderBy: title, orderDirection: desc) {
+            title
+        }
+        variables: songs(orderBy: $orderBy, orderDirection: $orderDirection) {
+            title
+        }
+        single: songs(orderBy: $singleOrderBy, orderDirection: $singleDirection) {
+            title
+        }
+    }";
+
+    run_query(
+        (
+            QUERY,
+            object! {
+                orderBy: "title",
+                orderDirection: "desc",
+                singleOrderBy: "title",
+                singleDirection: "desc"
+            },
+        ),
+        |result, _| {
+            let songs = vec![
+                object! { title: "Rock Tune" },
+                object! { title: "Pop Tune" },
+                object! { title: "Folk Tune" },
+                object! { title: "Cheesy Tune" },
+            ];
+            let exp = object! {
+                literal: songs.clone(),
+                variables: songs.clone(),
+                single: songs,
+            };
+
+            let data = extract_data!(result).unwrap();
+            assert_eq!(data, exp);
+        },
+    )
+}
+
 #[test]
 fn can_query_with_sorting_by_child_entity() {
     const QUERY: &str = "
@@ -2454,6 +2540,15 @@ fn query_at_block() {
     musicians_at(&hash(&BLOCKS[2]), Ok(vec!["m1", "m2", "m3", "m4"]), "h2");
     musicians_at(&hash(&BLOCKS[3]), Err(BLOCK_NOT_INDEXED2), "h3");
     musicians_at(&hash(&BLOCKS[4]), Err(BLOCK_HASH_NOT_FOUND), "h4");
+
+    // All blocks in the test chain have timestamp 0, and the subgraph is
+    // at block 2; a timestamp that does not fit into an `Int` also works
+    musicians_at("timestamp_lte: 0", Ok(vec!["m1", "m2", "m3", "m4"]), "ts0");
+    musicians_at(
+        "timestamp_lte: 4000000000",
+        Ok(vec!["m1", "m2", "m3", "m4"]),
+        "ts4e9",
+    );
 }
 
 #[test]
@@ -3168,3 +3263,182 @@ fn simple_aggregation() {
         assert_eq!(data, exp);
     })
 }
+
+#[test]
+fn can_page_through_connection() {
+    const QUERY: &str = "
+    query page($first: Int, $after: String, $before: String) {
+        musiciansConnection(first: $first, after: $after, before: $before) {
+            nodes { id }
+            pageInfo { startCursor endCursor hasNextPage hasPreviousPage }
+        }
+    }";
+
+    /// The cursor for the musician with `id` when musicians are sorted
+    /// by `id`
+    fn cursor(id: &str) -> r::Value {
+        let json = serde_json::json!({ "orderBy": [], "values": [], "id": id });
+        r::Value::String(format!(
+            "0x{}",
+            graph::prelude::hex::encode(json.to_string())
+        ))
+    }
+
+    /// The id of the entity at `cursor`
+    fn cursor_id(cursor: &r::Value) -> Option<String> {
+        let r::Value::String(cursor) = cursor else {
+            return None;
+        };
+        let bytes = graph::prelude::hex::decode(cursor.trim_start_matches("0x")).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        json["id"].as_str().map(str::to_owned)
+    }
+
+    fn field<'a>(value: &'a r::Value, name: &str) -> &'a r::Value {
+        match value {
+            r::Value::Object(obj) => obj.get(name).unwrap(),
+            _ => panic!("expected an object but got {:?}", value),
+        }
+    }
+
+    fn check_page(
+        vars: r::Value,
+        ids: &'static [&'static str],
+        has_next_page: bool,
+        has_previous_page: bool,
+    ) {
+        run_query((QUERY, vars.clone()), move |result, _| {
+            let data = extract_data!(result).unwrap();
+            let conn = field(&data, "musiciansConnection");
+
+            let exp: Vec<_> = ids.iter().map(|id| object! { id: *id }).collect();
+            assert_eq!(&r::Value::List(exp), field(conn, "nodes"), "{:?}", vars);
+
+            let page_info = field(conn, "pageInfo");
+            let first = ids.first().map(|id| id.to_string());
+            let last = ids.last().map(|id| id.to_string());
+            assert_eq!(first, cursor_id(field(page_info, "startCursor")));
+            assert_eq!(last, cursor_id(field(page_info, "endCursor")));
+            assert_eq!(
+                &r::Value::Boolean(has_next_page),
+                field(page_info, "hasNextPage"),
+                "hasNextPage for {:?}",
+                vars
+            );
+            assert_eq!(
+                &r::Value::Boolean(has_previous_page),
+                field(page_info, "hasPreviousPage"),
+                "hasPreviousPage for {:?}",
+                vars
+            );
+        })
+    }
+
+    // Forward
+    check_page(object! { first: 2 }, &["m1", "m2"], true, false);
+    check_page(
+        object! { first: 2, after: cursor("m2") },
+        &["m3", "m4"],
+        false,
+        true,
+    );
+    check_page(object! { first: 2, after: cursor("m4") }, &[], false, true);
+
+    // Backward
+    check_page(
+        object! { first: 2, before: cursor("m3") },
+        &["m1", "m2"],
+        true,
+        false,
+    );
+    check_page(
+        object! { first: 2, before: cursor("m4") },
+        &["m2", "m3"],
+        true,
+        true,
+    );
+    check_page(object! { first: 2, before: cursor("m1") }, &[], true, false);
+    // Nothing comes after a cursor that is past the last musician
+    check_page(
+        object! { first: 2, before: cursor("m9") },
+        &["m3", "m4"],
+        false,
+        true,
+    );
+}
+
+#[test]
+fn can_query_entity_aggregates() {
+    const QUERY: &str = "
+    query {
+        musicianAggregate {
+            __typename
+            count
+            sum { __typename favoriteCount }
+            min { favoriteCount }
+            max { favoriteCount }
+            avg { favoriteCount }
+        }
+    }";
+
+    const FILTER_QUERY: &str = r#"
+    query {
+        musicianAggregate(where: { name_in: ["John", "Lisa"] }) {
+            count
+            sum { favoriteCount }
+            min { favoriteCount }
+            max { favoriteCount }
+            avg { favoriteCount }
+        }
+    }"#;
+
+    const EMPTY_QUERY: &str = r#"
+    query {
+        musicianAggregate(where: { name: "Nobody" }) {
+            count
+            sum { favoriteCount }
+            avg { favoriteCount }
+        }
+    }"#;
+
+    run_query(QUERY, |result, _| {
+        let exp = object! {
+            musicianAggregate: object! {
+                __typename: "Musician_aggregate",
+                count: "4",
+                sum: object! { __typename: "Musician_aggregate_sum", favoriteCount: "135" },
+                min: object! { favoriteCount: "5" },
+                max: object! { favoriteCount: "100" },
+                avg: object! { favoriteCount: "33.75" },
+            }
+        };
+        let data = extract_data!(result).unwrap();
+        assert_eq!(data, exp);
+    });
+
+    run_query(FILTER_QUERY, |result, _| {
+        let exp = object! {
+            musicianAggregate: object! {
+                count: "2",
+                sum: object! { favoriteCount: "110" },
+                min: object! { favoriteCount: "10" },
+                max: object! { favoriteCount: "100" },
+                avg: object! { favoriteCount: "55" },
+            }
+        };
+        let data = extract_data!(result).unwrap();
+        assert_eq!(data, exp);
+    });
+
+    run_query(EMPTY_QUERY, |result, _| {
+        let exp = object! {
+            musicianAggregate: object! {
+                count: "0",
+                sum: object! { favoriteCount: r::Value::Null },
+                avg: object! { favoriteCount: r::Value::Null },
+            }
+        };
+        let data = extract_data!(result).unwrap();
+        assert_eq!(data, exp);
+    });
+}
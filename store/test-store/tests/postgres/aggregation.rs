@@ -18,7 +18,7 @@ use graph::{
         subgraph::DeploymentHash,
     },
     entity,
-    prelude::lazy_static,
+    prelude::{lazy_static, web3::types::H256},
     schema::InputSchema,
 };
 use graph_store_postgres::{Store as DieselStore, SubgraphStore};
@@ -46,6 +46,21 @@ type Data @entity(timeseries: true) {
     totalValue: BigDecimal! @aggregate(fn: "sum", arg: "price * amount", cumulative: true)
   }
 
+  # The same as `Stats`, but since it only has a daily interval, it is
+  # always rolled up from the raw timeseries
+  type RawStats @aggregation(intervals: ["day"], source: "Data") {
+    id: Int8!
+    timestamp: Timestamp!
+    token: Bytes!
+    sum: BigDecimal! @aggregate(fn: "sum", arg: "price")
+    sum_sq: BigDecimal! @aggregate(fn: "sum", arg: "price * price")
+    max: BigDecimal! @aggregate(fn: "max", arg: "amount")
+    first: BigDecimal @aggregate(fn: "first", arg: "amount")
+    last: BigDecimal! @aggregate(fn: "last", arg: "amount")
+    value: BigDecimal! @aggregate(fn: "sum", arg: "price * amount")
+    totalValue: BigDecimal! @aggregate(fn: "sum", arg: "price * amount", cumulative: true)
+  }
+
   type TotalStats @aggregation(intervals: ["hour"], source: "Data") {
     id: Int8!
     timestamp: Timestamp!
@@ -301,3 +316,30 @@ fn simple() {
         }
     })
 }
+
+#[test]
+fn rollup_from_finer_interval() {
+    run_test(|env| async move {
+        let schema = env.writable.input_schema();
+
+        // Cross into the next day to trigger the daily rollup
+        let ts = minutes(24 * 60 + 10);
+        let block = BlockPtr::from((H256::from([4; 32]), 4 as BlockNumber));
+        let entities = vec![
+            entity! { schema => id: 41i64, timestamp: ts, token: TOKEN1.clone(), price: bd(5), amount: bd(5) },
+        ];
+        insert(&env.writable, &env.deployment, block, ts, entities)
+            .await
+            .unwrap();
+        env.writable.flush().await.unwrap();
+
+        // `Stats_day` is rolled up from `Stats_hour`, `RawStats_day` from
+        // the timeseries; apart from the type, they must be identical
+        let finer = env.all_entities("Stats_day", BlockNumber::MAX);
+        let raw = env.all_entities("RawStats_day", BlockNumber::MAX);
+        assert_eq!(2, finer.len());
+        let finer: Vec<_> = finer.into_iter().map(|e| e.sorted()).collect();
+        let raw: Vec<_> = raw.into_iter().map(|e| e.sorted()).collect();
+        assert_eq!(raw, finer);
+    })
+}
@@ -460,6 +460,61 @@ fn copy() {
     })
 }
 
+#[test]
+fn snapshot() {
+    run_test(|store, src| async move {
+        let dir = std::env::temp_dir().join(format!("graft-snapshot-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let metadata = store.export_snapshot(&src, &dir)?;
+        assert_eq!(BLOCKS[2].number, metadata.head.number);
+        assert_eq!(BLOCKS[2].hash_hex(), metadata.head.hash);
+
+        // Exporting into a directory that is not empty is not allowed
+        assert!(store.export_snapshot(&src, &dir).is_err());
+
+        // Restoring the snapshot as is fails since the deployment exists;
+        // pretend it is a snapshot of another deployment
+        let shard = store.shard(&src)?;
+        assert!(store
+            .restore_snapshot(&dir, shard.clone(), NODE_ID.clone())
+            .is_err());
+
+        let path = dir.join("metadata.json");
+        let mut json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        json["deployment"] = serde_json::Value::from("snapshotrestore");
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        let dst = store.restore_snapshot(&dir, shard, NODE_ID.clone())?;
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let writable = store
+            .cheap_clone()
+            .writable(LOGGER.clone(), dst.id, Arc::new(Vec::new()))
+            .await?;
+        assert_eq!(Some(BLOCKS[2].clone()), writable.block_ptr());
+
+        // All versions of the entities were restored
+        let user_type = store.input_schema(&dst.hash)?.entity_type(USER).unwrap();
+        let email = |block: BlockNumber| {
+            let query = EntityQuery::new(
+                dst.hash.clone(),
+                block,
+                EntityCollection::All(vec![(user_type.clone(), AttributeNames::All)]),
+            )
+            .filter(EntityFilter::Equal("id".to_owned(), "3".into()));
+            let entities = store.find(query).unwrap();
+            assert_eq!(1, entities.len());
+            entities[0].get("email").cloned()
+        };
+        assert_eq!(Some(Value::from("queensha@email.com")), email(1));
+        assert_eq!(Some(Value::from("teeko@email.com")), email(2));
+
+        Ok(())
+    })
+}
+
 // Test that the on_sync behavior is correct when `deployment_synced` gets
 // run. This test will only do something if the test configuration uses at
 // least two shards
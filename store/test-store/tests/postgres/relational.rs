@@ -2,11 +2,14 @@
 use diesel::connection::SimpleConnection as _;
 use diesel::pg::PgConnection;
 use graph::components::store::write::{EntityModification, RowGroup};
+use graph::components::store::OrderDirection;
 use graph::data::store::scalar;
 use graph::entity;
+use graph::prelude::serde_json::json;
 use graph::prelude::{
-    o, slog, tokio, web3::types::H256, DeploymentHash, Entity, EntityCollection, EntityFilter,
-    EntityOrder, EntityQuery, Logger, StopwatchMetrics, Value, ValueType, BLOCK_NUMBER_MAX,
+    o, slog, tokio, web3::types::H256, DeploymentHash, Entity, EntityCollection, EntityCursor,
+    EntityFilter, EntityOrder, EntityQuery, Logger, StopwatchMetrics, StoreError, Value, ValueType,
+    BLOCK_NUMBER_MAX,
 };
 use graph::prelude::{BlockNumber, MetricsRegistry};
 use graph::schema::{EntityKey, EntityType, InputSchema};
@@ -62,6 +65,19 @@ const THINGS_GQL: &str = r#"
                 ]
             }
         ]
+    ) @fulltext(
+        name: "userFuzzySearch"
+        language: simple
+        algorithm: fuzzy
+        include: [
+            {
+                entity: "User",
+                fields: [
+                    { name: "name"},
+                    { name: "email"},
+                ]
+            }
+        ]
     ) @fulltext(
         name: "nullableStringsSearch"
         language: en
@@ -99,7 +115,8 @@ const THINGS_GQL: &str = r#"
         bigIntArray: [BigInt!]!
         color: Color,
         int8: Int8,
-        timestamp: Timestamp
+        timestamp: Timestamp,
+        json: JSON
     }
 
     interface Pet {
@@ -205,6 +222,11 @@ lazy_static! {
             bigInt: big_int.clone(),
             bigIntArray: vec![big_int.clone(), (big_int + 1.into())],
             color: "yellow",
+            json: Value::Json(json!({
+                "name": "one",
+                "level": 1,
+                "traits": [{ "type": "color", "value": "red" }]
+            })),
         }
     };
     static ref EMPTY_NULLABLESTRINGS_ENTITY: Entity = {
@@ -816,6 +838,122 @@ fn insert_many_and_delete_many() {
     });
 }
 
+#[test]
+fn find_diff() {
+    run_test(|conn, layout| {
+        let one = SCALAR_ENTITY.clone();
+        let mut two = SCALAR_ENTITY.clone();
+        two.set("id", "two").unwrap();
+        let mut three = SCALAR_ENTITY.clone();
+        three.set("id", "three").unwrap();
+        insert_entity(conn, layout, &*SCALAR_TYPE, vec![one, two, three]);
+
+        let mut updated = SCALAR_ENTITY.clone();
+        updated.set("string", "updated").unwrap();
+        update_entity_at(conn, layout, &*SCALAR_TYPE, vec![updated.clone()], 2);
+
+        let group = row_group_delete(
+            &*SCALAR_TYPE,
+            3,
+            vec![SCALAR_TYPE.parse_key("two").unwrap()],
+        );
+        layout
+            .delete(conn, &group, &MOCK_STOPWATCH)
+            .expect("Failed to delete");
+
+        let mut four = SCALAR_ENTITY.clone();
+        four.set("id", "four").unwrap();
+        insert_entity_at(conn, layout, &*SCALAR_TYPE, vec![four], 4);
+
+        // Change `three` and then change it back
+        let mut changed = SCALAR_ENTITY.clone();
+        changed.set("id", "three").unwrap();
+        changed.set("string", "changed").unwrap();
+        update_entity_at(conn, layout, &*SCALAR_TYPE, vec![changed], 6);
+        let mut three = SCALAR_ENTITY.clone();
+        three.set("id", "three").unwrap();
+        update_entity_at(conn, layout, &*SCALAR_TYPE, vec![three], 7);
+
+        let scalar = vec!["Scalar".to_string()];
+        let mut diff = |from, to, first, skip| {
+            layout
+                .find_diff(conn, from, to, &scalar, first, skip)
+                .expect("Failed to find diff")
+                .into_iter()
+                .map(|diff| (diff.key.entity_id.to_string(), diff.kind()))
+                .collect::<Vec<_>>()
+        };
+
+        use graph::prelude::EntityDiffKind::*;
+        assert_eq!(
+            vec![
+                ("four".to_string(), Created),
+                ("one".to_string(), Updated),
+                ("two".to_string(), Deleted)
+            ],
+            diff(0, 5, 100, 0)
+        );
+        // The update happened at block 2 and is not part of the diff
+        assert_eq!(
+            vec![("four".to_string(), Created), ("two".to_string(), Deleted)],
+            diff(2, 5, 100, 0)
+        );
+        assert_eq!(vec![("one".to_string(), Updated)], diff(0, 5, 1, 1));
+        assert!(diff(4, 5, 100, 0).is_empty());
+        assert_eq!(vec![("three".to_string(), Updated)], diff(5, 6, 100, 0));
+        // Versions that only differ in their vid and block range are not
+        // a change
+        assert!(diff(5, 8, 100, 0).is_empty());
+
+        let diffs = layout
+            .find_diff(conn, 1, 2, &scalar, 100, 0)
+            .expect("Failed to find diff");
+        assert_eq!(1, diffs.len());
+        assert_entity_eq!(scrub(&SCALAR_ENTITY), diffs[0].old.clone().unwrap());
+        assert_entity_eq!(scrub(&updated), diffs[0].new.clone().unwrap());
+    });
+}
+
+#[test]
+fn dump() {
+    run_test(|conn, layout| {
+        let one = SCALAR_ENTITY.clone();
+        let mut two = SCALAR_ENTITY.clone();
+        two.set("id", "two").unwrap();
+        let mut three = SCALAR_ENTITY.clone();
+        three.set("id", "three").unwrap();
+        insert_entity(conn, layout, &*SCALAR_TYPE, vec![one, two, three]);
+
+        let group = row_group_delete(
+            &*SCALAR_TYPE,
+            3,
+            vec![SCALAR_TYPE.parse_key("two").unwrap()],
+        );
+        layout
+            .delete(conn, &group, &MOCK_STOPWATCH)
+            .expect("Failed to delete");
+
+        let mut dump = |block| {
+            let mut batches = Vec::new();
+            layout
+                .dump(conn, &*SCALAR_TYPE, block, 2, |entities| {
+                    batches.push(
+                        entities
+                            .iter()
+                            .map(|entity| entity.id().to_string())
+                            .collect::<Vec<_>>(),
+                    );
+                    Ok::<_, StoreError>(())
+                })
+                .expect("Failed to dump");
+            batches
+        };
+
+        assert_eq!(vec![vec!["one", "two"], vec!["three"]], dump(1));
+        assert_eq!(vec![vec!["one", "three"]], dump(3));
+    });
+}
+
 #[tokio::test]
 async fn layout_cache() {
     // We need to use `block_on` to call the `create_test_subgraph` function which must be called
@@ -1182,6 +1320,342 @@ fn check_block_finds() {
     });
 }
 
+#[test]
+fn check_aggregates() {
+    use graph::prelude::EntityAggregate::*;
+
+    run_test(move |conn, layout| {
+        insert_users(conn, layout);
+
+        let aggregates = vec![
+            Count,
+            Sum("age".to_string()),
+            Sum("visits".to_string()),
+            Min("visits".to_string()),
+            Max("age".to_string()),
+            Max("seconds_age".to_string()),
+            Avg("age".to_string()),
+        ];
+        let (values, _) = layout
+            .aggregate(&LOGGER, conn, user_query(), &aggregates)
+            .expect("aggregate query succeeds");
+        assert_eq!(
+            values,
+            vec![
+                Value::Int8(3),
+                Value::Int8(138),
+                Value::BigInt(BigInt::from(132)),
+                Value::Int8(22),
+                Value::Int(67),
+                Value::BigInt(BigInt::from(67) * BigInt::from(31557600_u64)),
+                Value::BigDecimal(BigDecimal::from(46)),
+            ]
+        );
+
+        // Only the users that don't drink coffee
+        let query = user_query().filter(EntityFilter::Equal("coffee".to_owned(), false.into()));
+        let (values, _) = layout
+            .aggregate(&LOGGER, conn, query, &aggregates[0..2])
+            .expect("aggregate query succeeds");
+        assert_eq!(values, vec![Value::Int8(2), Value::Int8(95)]);
+
+        // Aggregates over no entities are null, except for the count
+        let query = user_query().filter(EntityFilter::Equal("name".to_owned(), "Nobody".into()));
+        let (values, _) = layout
+            .aggregate(&LOGGER, conn, query, &aggregates[0..2])
+            .expect("aggregate query succeeds");
+        assert_eq!(values, vec![Value::Int8(0), Value::Null]);
+
+        // Only numeric attributes can be aggregated
+        let res = layout.aggregate(&LOGGER, conn, user_query(), &[Sum("name".to_string())]);
+        assert!(res.is_err());
+    });
+}
+
+fn multi_order(attrs: &[(&str, OrderDirection)]) -> EntityOrder {
+    // The ValueType doesn't matter since relational layouts ignore it
+    EntityOrder::Multiple(
+        attrs
+            .iter()
+            .map(|(attr, direction)| (attr.to_string(), ValueType::String, *direction))
+            .collect(),
+    )
+}
+
+#[test]
+fn check_multi_column_order() {
+    use OrderDirection::*;
+    run_test(move |mut conn, layout| {
+        let types = vec![&*CAT_TYPE, &*DOG_TYPE];
+        // Users 1 and 3 do not drink coffee and are 67 and 28 years old,
+        // user 2 drinks coffee
+        QueryChecker::new(&mut conn, layout)
+            .check(
+                vec!["1", "3", "2"],
+                user_query().order(multi_order(&[("coffee", Ascending), ("age", Descending)])),
+            )
+            .check(
+                vec!["3", "1", "2"],
+                user_query().order(multi_order(&[("coffee", Ascending), ("name", Descending)])),
+            )
+            .check(
+                vec!["2", "1", "3"],
+                user_query().order(multi_order(&[("coffee", Descending), ("name", Ascending)])),
+            )
+            .check(
+                vec!["3"],
+                user_query()
+                    .order(multi_order(&[("coffee", Ascending), ("age", Descending)]))
+                    .first(1)
+                    .skip(1),
+            )
+            // Attributes after `id` do not change the order
+            .check(
+                vec!["3", "2", "1"],
+                user_query().order(multi_order(&[("id", Descending), ("name", Ascending)])),
+            )
+            // Interfaces
+            .check(
+                vec!["pluto", "garfield"],
+                query(&types).order(multi_order(&[("name", Descending), ("id", Ascending)])),
+            );
+    });
+}
+
+#[test]
+fn check_cursors() {
+    fn cursor(values: Vec<Value>, id: &str) -> EntityCursor {
+        EntityCursor {
+            values,
+            id: Value::from(id),
+        }
+    }
+
+    use OrderDirection::*;
+    run_test(move |mut conn, layout| {
+        let types = vec![&*CAT_TYPE, &*DOG_TYPE];
+        QueryChecker::new(&mut conn, layout)
+            // Sorting by id
+            .check(vec!["2", "3"], user_query().after(cursor(vec![], "1")))
+            .check(
+                vec!["1"],
+                user_query().desc("id").after(cursor(vec![], "2")),
+            )
+            // Sorting by name; the names are 'Cindini', 'Jono', and
+            // 'Shaqueeena' for users 2, 1, and 3
+            .check(
+                vec!["1", "3"],
+                user_query()
+                    .asc("name")
+                    .after(cursor(vec!["Cindini".into()], "2")),
+            )
+            .check(
+                vec!["2"],
+                user_query()
+                    .desc("name")
+                    .after(cursor(vec!["Jono".into()], "1")),
+            )
+            .check(
+                vec!["3"],
+                user_query()
+                    .asc("name")
+                    .first(1)
+                    .after(cursor(vec!["Cindini".into()], "2"))
+                    .skip(1),
+            )
+            // Sorting by a nullable column; the colors are 'yellow', 'red'
+            // and null for users 1, 2, and 3, and nulls come last when
+            // sorting ascending, and first when sorting descending
+            .check(
+                vec!["2", "3"],
+                user_query()
+                    .asc("favorite_color")
+                    .after(cursor(vec!["yellow".into()], "1")),
+            )
+            .check(
+                vec!["3"],
+                user_query()
+                    .asc("favorite_color")
+                    .after(cursor(vec![Value::Null], "0")),
+            )
+            .check(
+                vec![],
+                user_query()
+                    .asc("favorite_color")
+                    .after(cursor(vec![Value::Null], "3")),
+            )
+            .check(
+                vec!["2", "1"],
+                user_query()
+                    .desc("favorite_color")
+                    .after(cursor(vec![Value::Null], "3")),
+            )
+            .check(
+                vec!["1"],
+                user_query()
+                    .desc("favorite_color")
+                    .after(cursor(vec!["red".into()], "2")),
+            )
+            // Interfaces
+            .check(
+                vec!["pluto"],
+                query(&types)
+                    .asc("name")
+                    .after(cursor(vec!["Garfield".into()], "garfield")),
+            )
+            .check(
+                vec!["garfield"],
+                query(&types).desc("id").after(cursor(vec![], "pluto")),
+            )
+            // Sorting by multiple columns; ordered by `coffee` and `age`,
+            // the users are 1, 3, 2
+            .check(
+                vec!["3", "2"],
+                user_query()
+                    .order(multi_order(&[("coffee", Ascending), ("age", Descending)]))
+                    .after(cursor(vec![false.into(), 67.into()], "1")),
+            )
+            .check(
+                vec!["2"],
+                user_query()
+                    .order(multi_order(&[("coffee", Ascending), ("age", Descending)]))
+                    .after(cursor(vec![false.into(), 28.into()], "3")),
+            )
+            .check(
+                vec!["1", "3"],
+                user_query()
+                    .order(multi_order(&[("coffee", Descending), ("name", Ascending)]))
+                    .after(cursor(vec![true.into(), "Cindini".into()], "2")),
+            )
+            // Attributes from `id` on are not part of the cursor
+            .check(
+                vec!["1", "2"],
+                user_query()
+                    .order(multi_order(&[
+                        ("coffee", Ascending),
+                        ("id", Descending),
+                        ("name", Ascending),
+                    ]))
+                    .after(cursor(vec![false.into()], "3")),
+            )
+            // Multiple columns where one of them is nullable
+            .check(
+                vec!["1", "3"],
+                user_query()
+                    .order(multi_order(&[
+                        ("favorite_color", Ascending),
+                        ("name", Ascending),
+                    ]))
+                    .after(cursor(vec!["red".into(), "Cindini".into()], "2")),
+            )
+            .check(
+                vec![],
+                user_query()
+                    .order(multi_order(&[
+                        ("favorite_color", Ascending),
+                        ("name", Ascending),
+                    ]))
+                    .after(cursor(vec![Value::Null, "Shaqueeena".into()], "3")),
+            )
+            .check(
+                vec!["1", "2"],
+                user_query()
+                    .order(multi_order(&[
+                        ("favorite_color", Descending),
+                        ("name", Ascending),
+                    ]))
+                    .after(cursor(vec![Value::Null, "Shaqueeena".into()], "3")),
+            );
+    });
+}
+
+#[test]
+fn check_json_filters() {
+    run_test(move |mut conn, layout| {
+        let one = SCALAR_ENTITY.clone();
+        let mut two = SCALAR_ENTITY.clone();
+        two.set("id", "two").unwrap();
+        two.set(
+            "json",
+            Value::Json(json!({
+                "name": "two",
+                "level": 2,
+                "traits": [
+                    { "type": "color", "value": "blue" },
+                    { "type": "size", "value": "large" }
+                ]
+            })),
+        )
+        .unwrap();
+        let mut three = SCALAR_ENTITY.clone();
+        three.set("id", "three").unwrap();
+        three.set("json", Value::Null).unwrap();
+        insert_entity(conn, layout, &*SCALAR_TYPE, vec![one, two, three]);
+
+        let scalars = || query(&[&*SCALAR_TYPE]);
+        let path = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+
+        QueryChecker::new(&mut conn, layout)
+            .check(
+                vec!["one"],
+                scalars().filter(EntityFilter::Equal(
+                    "json".into(),
+                    SCALAR_ENTITY.get("json").unwrap().clone(),
+                )),
+            )
+            .check(
+                vec!["three"],
+                scalars().filter(EntityFilter::Equal("json".into(), Value::Null)),
+            )
+            .check(
+                vec!["two"],
+                scalars().filter(EntityFilter::Contains(
+                    "json".into(),
+                    Value::Json(json!({ "traits": [{ "value": "blue" }] })),
+                )),
+            )
+            .check(
+                vec!["one"],
+                scalars().filter(EntityFilter::NotContains(
+                    "json".into(),
+                    Value::Json(json!({ "traits": [{ "value": "blue" }] })),
+                )),
+            )
+            .check(
+                vec!["two"],
+                scalars().filter(EntityFilter::JsonPathEqual(
+                    "json".into(),
+                    path(&["level"]),
+                    Value::Json(json!(2)),
+                )),
+            )
+            .check(
+                vec!["two"],
+                scalars().filter(EntityFilter::JsonPathEqual(
+                    "json".into(),
+                    path(&["traits", "1", "value"]),
+                    Value::Json(json!("large")),
+                )),
+            )
+            .check(
+                vec!["one", "two"],
+                scalars().filter(EntityFilter::JsonPathContains(
+                    "json".into(),
+                    path(&["traits"]),
+                    Value::Json(json!([{ "type": "color" }])),
+                )),
+            )
+            .check(
+                vec!["three"],
+                scalars().filter(EntityFilter::JsonPathEqual(
+                    "json".into(),
+                    path(&["name"]),
+                    Value::Null,
+                )),
+            );
+    });
+}
+
 #[test]
 fn check_find() {
     run_test(move |mut conn, layout| {
@@ -1231,6 +1705,32 @@ fn check_find() {
                 )),
             );
 
+        // fuzzy fulltext tolerates typos and partial words
+        let checker = checker
+            .check(
+                vec!["2"],
+                user_query().filter(EntityFilter::Fulltext(
+                    "userFuzzySearch".into(),
+                    "Cindni".into(),
+                )),
+            )
+            .check(
+                vec!["3"],
+                user_query().filter(EntityFilter::FuzzyFulltext(
+                    "userFuzzySearch".into(),
+                    "Shakeena".into(),
+                    0.3,
+                )),
+            )
+            .check(
+                vec![],
+                user_query().filter(EntityFilter::FuzzyFulltext(
+                    "userFuzzySearch".into(),
+                    "Cindni".into(),
+                    0.95,
+                )),
+            );
+
         // list contains
         fn drinks_query(v: Vec<&str>) -> EntityQuery {
             let drinks: Option<Value> = Some(v.into());
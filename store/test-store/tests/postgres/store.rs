@@ -1936,6 +1936,21 @@ fn find_at_block() {
     shaqueeena_at_block(7000, "teeko@email.com");
 }
 
+#[test]
+fn entity_diff_needs_available_blocks() {
+    run_test(|store, _, deployment| async move {
+        let store = store.subgraph_store();
+        let diff = |from, to| store.entity_diff(&deployment.hash, from, to, &[], 100, 0);
+
+        let diffs = diff(1, 2).expect("blocks 1 and 2 have been indexed");
+        assert_eq!(1, diffs.len());
+        // The subgraph has only indexed up to block 2
+        assert!(diff(1, 3).is_err());
+        // and has no data before block 0
+        assert!(diff(-1, 2).is_err());
+    })
+}
+
 #[test]
 fn cleanup_cached_blocks() {
     if store_is_sharded() {
@@ -2036,6 +2051,108 @@ fn parse_timestamp_firehose() {
     })
 }
 
+#[test]
+fn block_for_timestamp() {
+    const TS: u64 = 1657712166;
+
+    run_test(|store, _, _| async move {
+        use block_store::*;
+        // Blocks 0 to 2 have a timestamp of 0
+        block_store::set_chain(
+            vec![
+                &*GENESIS_BLOCK,
+                &*BLOCK_ONE,
+                &*BLOCK_TWO,
+                &*BLOCK_THREE_TIMESTAMP,
+            ],
+            NETWORK_NAME,
+        )
+        .await;
+        let chain_store = store
+            .block_store()
+            .chain_store(NETWORK_NAME)
+            .expect("fake chain store");
+
+        let head = BLOCK_THREE_TIMESTAMP.block_ptr();
+        let block = chain_store
+            .block_for_timestamp(TS, 0, head.clone())
+            .await
+            .unwrap();
+        assert_eq!(Some(BLOCK_THREE_TIMESTAMP.block_ptr()), block);
+
+        let block = chain_store
+            .block_for_timestamp(TS + 100, 0, head.clone())
+            .await
+            .unwrap();
+        assert_eq!(Some(BLOCK_THREE_TIMESTAMP.block_ptr()), block);
+
+        let block = chain_store
+            .block_for_timestamp(TS - 1, 0, head.clone())
+            .await
+            .unwrap();
+        assert_eq!(Some(BLOCK_TWO.block_ptr()), block);
+
+        let block = chain_store
+            .block_for_timestamp(TS - 1, 0, BLOCK_ONE.block_ptr())
+            .await
+            .unwrap();
+        assert_eq!(Some(BLOCK_ONE.block_ptr()), block);
+
+        let block = chain_store
+            .block_for_timestamp(TS, 3, head.clone())
+            .await
+            .unwrap();
+        assert_eq!(Some(BLOCK_THREE_TIMESTAMP.block_ptr()), block);
+
+        let block = chain_store
+            .block_for_timestamp(TS - 1, 3, head)
+            .await
+            .unwrap();
+        assert_eq!(None, block);
+    })
+}
+
+#[test]
+fn block_for_timestamp_ignores_other_forks() {
+    run_test(|store, _, _| async move {
+        use block_store::*;
+        // Blocks 0 to 2 have a timestamp of 0
+        block_store::set_chain(
+            vec![
+                &*GENESIS_BLOCK,
+                &*BLOCK_ONE,
+                &*BLOCK_ONE_SIBLING,
+                &*BLOCK_TWO,
+            ],
+            NETWORK_NAME,
+        )
+        .await;
+        let chain_store = store
+            .block_store()
+            .chain_store(NETWORK_NAME)
+            .expect("fake chain store");
+
+        let block = chain_store
+            .block_for_timestamp(0, 0, BLOCK_ONE.block_ptr())
+            .await
+            .unwrap();
+        assert_eq!(Some(BLOCK_ONE.block_ptr()), block);
+
+        let block = chain_store
+            .block_for_timestamp(0, 0, BLOCK_ONE_SIBLING.block_ptr())
+            .await
+            .unwrap();
+        assert_eq!(Some(BLOCK_ONE_SIBLING.block_ptr()), block);
+
+        // Limiting the search to block 1 must not return the sibling
+        let block = chain_store
+            .block_for_timestamp(0, 1, BLOCK_ONE.block_ptr())
+            .await
+            .unwrap();
+        assert_eq!(Some(BLOCK_ONE.block_ptr()), block);
+    })
+}
+
 #[test]
 /// checks if retrieving the timestamp from the data blob works.
 /// on ethereum, the block has timestamp as U256 so it will always have a value
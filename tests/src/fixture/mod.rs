@@ -45,7 +45,7 @@ use graph::schema::InputSchema;
 use graph_chain_ethereum::chain::RuntimeAdapterBuilder;
 use graph_chain_ethereum::network::EthereumNetworkAdapters;
 use graph_chain_ethereum::Chain;
-use graph_core::polling_monitor::{arweave_service, ipfs_service};
+use graph_core::polling_monitor::{arweave_service, http_service, ipfs_service};
 use graph_core::{
     SubgraphAssignmentProvider as IpfsSubgraphAssignmentProvider, SubgraphInstanceManager,
     SubgraphRegistrar as IpfsSubgraphRegistrar, SubgraphTriggerProcessor,
@@ -397,8 +397,15 @@ pub async fn stores(test_name: &str, store_config_path: &str) -> Stores {
     let logger = test_logger(test_name);
     let mock_registry: Arc<MetricsRegistry> = Arc::new(MetricsRegistry::mock());
     let node_id = NodeId::new(NODE_ID).unwrap();
-    let store_builder =
-        StoreBuilder::new(&logger, &node_id, &config, None, mock_registry.clone()).await;
+    let store_builder = StoreBuilder::new(
+        &logger,
+        &node_id,
+        &config,
+        None,
+        mock_registry.clone(),
+        Arc::new(Settings::default()),
+    )
+    .await;
 
     let network_name: ChainId = config
         .chains
@@ -491,6 +498,13 @@ pub async fn setup<C: Blockchain>(
             n => FileSizeLimit::MaxBytes(n as u64),
         },
     );
+
+    let http_service = http_service(
+        env_vars.mappings.max_http_file_bytes,
+        env_vars.mappings.http_file_timeout,
+        env_vars.mappings.http_file_request_limit,
+        env_vars.mappings.http_file_allow_private_addresses,
+    );
     let sg_count = Arc::new(SubgraphCountMetric::new(mock_registry.cheap_clone()));
 
     let blockchain_map = Arc::new(blockchain_map);
@@ -504,6 +518,7 @@ pub async fn setup<C: Blockchain>(
         link_resolver.cheap_clone(),
         ipfs_service,
         arweave_service,
+        http_service,
         static_filters,
     );
 
@@ -516,6 +531,7 @@ pub async fn setup<C: Blockchain>(
         subscription_manager.clone(),
         Arc::new(load_manager),
         mock_registry.clone(),
+        Arc::new(Settings::default()),
     ));
 
     let indexing_status_service = Arc::new(IndexNodeService::new(
@@ -942,6 +958,84 @@ impl<C: Blockchain> TriggersAdapterSelector<C> for MockAdapterSelector<C> {
     }
 }
 
+/// A selector for triggers adapters that take the triggers for a range of
+/// blocks from `blocks`, regardless of the filter; this is what replaying
+/// blocks needs. Triggers for single blocks are never found
+pub struct StaticAdapterSelector<C: Blockchain> {
+    pub blocks: Vec<BlockWithTriggers<C>>,
+}
+
+impl<C: Blockchain> TriggersAdapterSelector<C> for StaticAdapterSelector<C>
+where
+    C::TriggerData: Clone,
+{
+    fn triggers_adapter(
+        &self,
+        _loc: &DeploymentLocator,
+        _capabilities: &<C as Blockchain>::NodeCapabilities,
+        _unified_api_version: graph::data::subgraph::UnifiedMappingApiVersion,
+    ) -> Result<Arc<dyn graph::blockchain::TriggersAdapter<C>>, Error> {
+        Ok(Arc::new(StaticTriggersAdapter {
+            blocks: self.blocks.clone(),
+        }))
+    }
+}
+
+struct StaticTriggersAdapter<C: Blockchain> {
+    blocks: Vec<BlockWithTriggers<C>>,
+}
+
+#[async_trait]
+impl<C: Blockchain> TriggersAdapter<C> for StaticTriggersAdapter<C>
+where
+    C::TriggerData: Clone,
+{
+    async fn ancestor_block(
+        &self,
+        _ptr: BlockPtr,
+        _offset: BlockNumber,
+        _root: Option<BlockHash>,
+    ) -> Result<Option<<C as Blockchain>::Block>, Error> {
+        todo!()
+    }
+
+    async fn scan_triggers(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+        _filter: &<C as Blockchain>::TriggerFilter,
+    ) -> Result<(Vec<BlockWithTriggers<C>>, BlockNumber), Error> {
+        let blocks = self
+            .blocks
+            .iter()
+            .filter(|block| block.ptr().number >= from && block.ptr().number <= to)
+            .cloned()
+            .collect();
+        Ok((blocks, to))
+    }
+
+    async fn triggers_in_block(
+        &self,
+        logger: &Logger,
+        block: <C as Blockchain>::Block,
+        _filter: &<C as Blockchain>::TriggerFilter,
+    ) -> Result<BlockWithTriggers<C>, Error> {
+        Ok(BlockWithTriggers::new(block, Vec::new(), logger))
+    }
+
+    async fn is_on_main_chain(&self, _ptr: BlockPtr) -> Result<bool, Error> {
+        todo!()
+    }
+
+    async fn parent_ptr(&self, block: &BlockPtr) -> Result<Option<BlockPtr>, Error> {
+        Ok(self
+            .blocks
+            .iter()
+            .find(|b| &b.ptr() == block)
+            .and_then(|b| b.parent_ptr()))
+    }
+}
+
 struct MockTriggersAdapter<C: Blockchain> {
     x: PhantomData<C>,
     triggers_in_block_sleep: Duration,
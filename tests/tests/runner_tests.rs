@@ -8,6 +8,8 @@ use std::time::Duration;
 use assert_json_diff::assert_json_eq;
 use graph::blockchain::block_stream::BlockWithTriggers;
 use graph::blockchain::{Block, BlockPtr, Blockchain};
+use graph::components::store::EntityOperation;
+use graph::components::subgraph::{EntityChange, TraceEvent};
 use graph::data::store::scalar::Bytes;
 use graph::data::subgraph::schema::{SubgraphError, SubgraphHealth};
 use graph::data::value::Word;
@@ -28,8 +30,8 @@ use graph_tests::fixture::ethereum::{
 
 use graph_tests::fixture::substreams::chain as substreams_chain;
 use graph_tests::fixture::{
-    self, stores, test_ptr, test_ptr_reorged, MockAdapterSelector, NoopAdapterSelector, Stores,
-    TestChainTrait, TestContext, TestInfo,
+    self, stores, test_ptr, test_ptr_reorged, MockAdapterSelector, NoopAdapterSelector,
+    StaticAdapterSelector, Stores, TestChainTrait, TestContext, TestInfo,
 };
 use graph_tests::helpers::run_cmd;
 use slog::{o, Discard, Logger};
@@ -628,6 +630,104 @@ async fn end_block() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn replay_blocks() -> anyhow::Result<()> {
+    let RunnerTestRecipe { stores, test_info } =
+        RunnerTestRecipe::new("replay_blocks", "end-block").await;
+
+    let blocks = {
+        let block_0 = genesis();
+        let block_1 = empty_block(block_0.ptr(), test_ptr(1));
+        let block_2 = empty_block(block_1.ptr(), test_ptr(2));
+        let block_3 = empty_block(block_2.ptr(), test_ptr(3));
+        let block_4 = empty_block(block_3.ptr(), test_ptr(4));
+        let block_5 = empty_block(block_4.ptr(), test_ptr(5));
+        vec![block_0, block_1, block_2, block_3, block_4, block_5]
+    };
+
+    let stop_block = blocks.last().unwrap().block.ptr();
+
+    let selector = StaticAdapterSelector {
+        blocks: blocks.clone(),
+    };
+    let chain = chain(
+        &test_info.test_name,
+        blocks,
+        &stores,
+        Some(Arc::new(selector)),
+    )
+    .await;
+    let ctx = fixture::setup(&test_info, &stores, &chain, None, None).await;
+    ctx.start_and_sync_to(stop_block.clone()).await;
+
+    let (_, deployment, raw) = ctx.get_runner_context().await;
+    let traces = ctx
+        .instance_manager
+        .replay_blocks(deployment.clone(), raw.clone(), 2, 4)
+        .await?;
+
+    let numbers: Vec<_> = traces.iter().map(|trace| trace.block_number).collect();
+    assert_eq!(vec![2, 3, 4], numbers);
+
+    // Replaying must produce the same changes that indexing wrote
+    for trace in &traces {
+        let mut expected: Vec<_> = ctx
+            .store
+            .entity_changes_in_block(&deployment.hash, trace.block_number)?
+            .into_iter()
+            .map(|op| match op {
+                EntityOperation::Set { key, data } => EntityChange::new(&key, None, Some(&data)),
+                EntityOperation::Remove { key } => EntityChange::new(&key, None, None),
+            })
+            .map(|change| (change.entity_type, change.id, change.after))
+            .collect();
+        expected.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+
+        let mut actual: Vec<_> = trace
+            .changes
+            .iter()
+            .map(|change| {
+                (
+                    change.entity_type.clone(),
+                    change.id.clone(),
+                    change.after.clone(),
+                )
+            })
+            .collect();
+        actual.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+
+        assert!(!expected.is_empty());
+        assert_eq!(expected, actual, "block {}", trace.block_number);
+
+        // The host calls of the handler are traced with their arguments
+        let host_calls: Vec<_> = trace
+            .handlers
+            .iter()
+            .flat_map(|handler| handler.events.iter())
+            .filter_map(|event| match event {
+                TraceEvent::HostCall { name, args, .. } => Some((name.as_str(), args.len())),
+                _ => None,
+            })
+            .collect();
+        assert!(host_calls
+            .iter()
+            .any(|(name, args)| *name == "store.set" && *args > 0));
+    }
+
+    // Replaying never writes to the store
+    let block = ctx.store.least_block_ptr(&deployment.hash).await?;
+    assert_eq!(Some(stop_block), block);
+
+    // There is no data before the start of the subgraph to replay from
+    let res = ctx
+        .instance_manager
+        .replay_blocks(deployment.clone(), raw, 0, 1)
+        .await;
+    assert!(res.is_err());
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn file_data_sources() {
     let RunnerTestRecipe { stores, test_info } =
@@ -1244,6 +1344,8 @@ async fn arweave_file_data_sources() {
     let ds = datasources.first().unwrap();
     assert_ne!(ds.causality_region, CausalityRegion::ONCHAIN);
     assert_eq!(ds.done_at.is_some(), true);
+    // Only `file/http` data sources record the hash of their content
+    assert_eq!(ds.content_hash, None);
     assert_eq!(
         ds.param.as_ref().unwrap(),
         &Bytes::from(Word::from(id).as_bytes())